@@ -1,7 +1,106 @@
 use convert_case::{Case, Casing};
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
-use syn::{self, Data, Fields, Ident};
+use syn::{self, Data, Fields, FieldsNamed, Ident, LitInt, LitStr, Type};
+
+struct CodecField {
+    ident: Ident,
+    ty: Type,
+    // name of the field type inside the `I<Struct>` trait
+    type_ident: Ident,
+    skip: bool,
+    default: bool,
+    index: Option<u32>,
+}
+
+/// Parses field attributes and returns fields in the encoding order:
+/// - `#[codec(skip)]` - field isn't encoded and it's decoded as default value
+/// - `#[codec(default)]` - field is decoded as default value if it's missing in the buffer
+///   (encoded by the older layout), such buffers can't be decoded in the strict mode or with
+///   header validation
+/// - `#[codec(index = N)]` - position of the field in the layout
+/// - `#[codec(rename = "name")]` - name of the field type inside the `I<Struct>` trait
+fn parse_fields(named_fields: &FieldsNamed) -> Vec<CodecField> {
+    let mut fields = named_fields
+        .named
+        .iter()
+        .map(|field| {
+            let ident = field.ident.clone().unwrap();
+            let mut result = CodecField {
+                type_ident: Ident::new(
+                    ident.to_string().to_case(Case::Pascal).as_str(),
+                    ident.span(),
+                ),
+                ident,
+                ty: field.ty.clone(),
+                skip: false,
+                default: false,
+                index: None,
+            };
+            for attr in field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("codec"))
+            {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("skip") {
+                        result.skip = true;
+                    } else if meta.path.is_ident("default") {
+                        result.default = true;
+                    } else if meta.path.is_ident("index") {
+                        let index: LitInt = meta.value()?.parse()?;
+                        result.index = Some(index.base10_parse()?);
+                    } else if meta.path.is_ident("rename") {
+                        let name: LitStr = meta.value()?.parse()?;
+                        result.type_ident =
+                            Ident::new(name.value().to_case(Case::Pascal).as_str(), name.span());
+                    } else {
+                        return Err(meta.error("unsupported codec attribute"));
+                    }
+                    Ok(())
+                })
+                .unwrap_or_else(|err| panic!("{}", err));
+            }
+            result
+        })
+        .collect::<Vec<_>>();
+    let indexed_fields = fields
+        .iter()
+        .filter(|field| !field.skip && field.index.is_some())
+        .count();
+    let encoded_fields = fields.iter().filter(|field| !field.skip).count();
+    if indexed_fields > 0 {
+        if indexed_fields != encoded_fields {
+            panic!("either all or none of the encoded fields must have codec index");
+        }
+        // stable sort keeps declaration order of skipped fields
+        fields.sort_by_key(|field| field.index.unwrap_or(u32::MAX));
+        let mut indices = fields
+            .iter()
+            .filter_map(|field| field.index)
+            .collect::<Vec<_>>();
+        indices.dedup();
+        if indices.len() != indexed_fields {
+            panic!("codec indices must be unique");
+        }
+    }
+    // optional fields can only be appended to the end of the layout
+    if let Some(i) = fields
+        .iter()
+        .filter(|field| !field.skip)
+        .position(|field| field.default)
+    {
+        if fields
+            .iter()
+            .filter(|field| !field.skip)
+            .skip(i)
+            .any(|field| !field.default)
+        {
+            panic!("fields with codec default must be placed at the end of the layout");
+        }
+    }
+    fields
+}
 
 fn impl_derive_codec(ast: &syn::DeriveInput) -> TokenStream {
     let crate_name = std::env::var("CARGO_PKG_NAME").unwrap();
@@ -20,45 +119,60 @@ fn impl_derive_codec(ast: &syn::DeriveInput) -> TokenStream {
         Fields::Named(named_fields) => named_fields,
         _ => panic!("only named fields are supported"),
     };
-    let header_sizes = named_fields.named.iter().map(|field| {
+    let fields = parse_fields(named_fields);
+    let encoded_fields = fields
+        .iter()
+        .filter(|field| !field.skip)
+        .collect::<Vec<_>>();
+    let header_sizes = encoded_fields.iter().map(|field| {
         let ty = &field.ty;
         quote! {
             <#ty as #crate_name::Encoder<#ty>>::HEADER_SIZE
         }
     });
-    let encode_types = named_fields.named.iter().map(|field| {
-        let ident = field.ident.as_ref().unwrap();
+    let encode_types = encoded_fields.iter().map(|field| {
+        let ident = &field.ident;
         let ty = &field.ty;
         quote! {
             self.#ident.encode(encoder, field_offset);
             field_offset += <#ty as #crate_name::Encoder<#ty>>::HEADER_SIZE;
         }
     });
-    let decode_types = named_fields.named.iter().map(|field| {
-        let ident = field.ident.as_ref().unwrap();
+    let decode_types = encoded_fields.iter().map(|field| {
+        let ident = &field.ident;
         let ty = &field.ty;
+        if field.default {
+            // field can be missing in the buffers encoded before it was added
+            quote! {
+                if field_offset + <#ty as #crate_name::Encoder<#ty>>::HEADER_SIZE <= decoder.header_end() {
+                    <#ty as #crate_name::Encoder<#ty>>::decode_body(decoder, field_offset, &mut result.#ident);
+                } else {
+                    result.#ident = Default::default();
+                }
+                field_offset += <#ty as #crate_name::Encoder<#ty>>::HEADER_SIZE;
+            }
+        } else {
+            quote! {
+                <#ty as #crate_name::Encoder<#ty>>::decode_body(decoder, field_offset, &mut result.#ident);
+                field_offset += <#ty as #crate_name::Encoder<#ty>>::HEADER_SIZE;
+            }
+        }
+    });
+    let skip_types = fields.iter().filter(|field| field.skip).map(|field| {
+        let ident = &field.ident;
         quote! {
-            <#ty as #crate_name::Encoder<#ty>>::decode_body(decoder, field_offset, &mut result.#ident);
-            field_offset += <#ty as #crate_name::Encoder<#ty>>::HEADER_SIZE;
+            result.#ident = Default::default();
         }
     });
-    let impl_types = named_fields.named.iter().map(|field| {
-        let ident = field.ident.as_ref().unwrap();
-        let span = ident.span();
-        let ident = ident.to_string().to_case(Case::Pascal);
-        let ident = Ident::new(ident.as_str(), span);
+    let impl_types = encoded_fields.iter().map(|field| {
+        let ident = &field.type_ident;
         quote! {
             type #ident;
         }
     });
-    let field_names = named_fields.named.clone();
-    let impl_defs = named_fields.named.iter().enumerate().map(|(i, field)| {
-        let ident = field.ident.as_ref().unwrap();
-        let span = ident.span();
-        let ident = ident.to_string().to_case(Case::Pascal);
-        let ident = ident.to_string().to_case(Case::Pascal);
-        let ident = Ident::new(ident.as_str(), span);
-        let sum_of_field_offsets = field_names.iter().take(i).map(|field| {
+    let impl_defs = encoded_fields.iter().enumerate().map(|(i, field)| {
+        let ident = &field.type_ident;
+        let sum_of_field_offsets = encoded_fields.iter().take(i).map(|field| {
             let ty = &field.ty;
             quote! {
                 <#ty as #crate_name::Encoder<#ty>>::HEADER_SIZE
@@ -80,6 +194,7 @@ fn impl_derive_codec(ast: &syn::DeriveInput) -> TokenStream {
             }
             fn decode_header(decoder: &mut #crate_name::BufferDecoder, mut field_offset: usize, result: &mut #struct_name #type_generics) -> (usize, usize) {
                 #( #decode_types; )*
+                #( #skip_types; )*
                 (0, 0)
             }
         }
@@ -93,7 +208,7 @@ fn impl_derive_codec(ast: &syn::DeriveInput) -> TokenStream {
     TokenStream::from(output)
 }
 
-#[proc_macro_derive(Codec)]
+#[proc_macro_derive(Codec, attributes(codec))]
 pub fn codec_macro_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse(input).unwrap();
     impl_derive_codec(&ast)
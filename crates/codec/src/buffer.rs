@@ -1,5 +1,9 @@
 use alloc::vec::Vec;
 use byteorder::{ByteOrder, LittleEndian};
+use core::{
+    cell::{Cell, RefCell},
+    fmt,
+};
 use paste::paste;
 
 pub trait WritableBuffer {
@@ -11,7 +15,59 @@ pub trait WritableBuffer {
     fn write_u32(&mut self, field_offset: usize, value: u32) -> usize;
     fn write_i64(&mut self, field_offset: usize, value: i64) -> usize;
     fn write_u64(&mut self, field_offset: usize, value: u64) -> usize;
-    fn write_bytes(&mut self, field_offset: usize, bytes: &[u8]) -> usize;
+    fn write_bytes(&mut self, field_offset: usize, bytes: &[u8]) -> usize {
+        self.write_bytes_with::<LongHeaders>(field_offset, bytes)
+    }
+    fn write_bytes_with<P: HeaderProfile>(&mut self, field_offset: usize, bytes: &[u8]) -> usize;
+}
+
+/// Layout of the dynamic data headers (offset and length of the data inside the buffer)
+pub trait HeaderProfile {
+    /// Size of the one header field (offset, length or number of elements)
+    const FIELD_SIZE: usize;
+    /// Max size of the encoded buffer that can be addressed by the header
+    const MAX_PAYLOAD_SIZE: usize;
+
+    fn write_field<W: WritableBuffer>(encoder: &mut W, field_offset: usize, value: usize) -> usize;
+
+    fn read_field(decoder: &BufferDecoder, field_offset: usize) -> usize;
+}
+
+/// Default profile with `u32` header fields
+pub struct LongHeaders;
+
+impl HeaderProfile for LongHeaders {
+    const FIELD_SIZE: usize = core::mem::size_of::<u32>();
+    const MAX_PAYLOAD_SIZE: usize = u32::MAX as usize;
+
+    fn write_field<W: WritableBuffer>(encoder: &mut W, field_offset: usize, value: usize) -> usize {
+        encoder.write_u32(field_offset, value as u32)
+    }
+
+    fn read_field(decoder: &BufferDecoder, field_offset: usize) -> usize {
+        decoder.read_u32(field_offset) as usize
+    }
+}
+
+/// Profile with `u16` header fields, it halves header overhead of small messages, but encoded
+/// buffer can't exceed 64 KiB
+pub struct ShortHeaders;
+
+impl HeaderProfile for ShortHeaders {
+    const FIELD_SIZE: usize = core::mem::size_of::<u16>();
+    const MAX_PAYLOAD_SIZE: usize = u16::MAX as usize;
+
+    fn write_field<W: WritableBuffer>(encoder: &mut W, field_offset: usize, value: usize) -> usize {
+        assert!(
+            value <= Self::MAX_PAYLOAD_SIZE,
+            "value doesn't fit into short header"
+        );
+        encoder.write_u16(field_offset, value as u16)
+    }
+
+    fn read_field(decoder: &BufferDecoder, field_offset: usize) -> usize {
+        decoder.read_u16(field_offset) as usize
+    }
 }
 
 macro_rules! encode_le_int {
@@ -73,16 +129,93 @@ impl<const N: usize> WritableBuffer for FixedEncoder<N> {
     encode_le_int!(u64);
     encode_le_int!(i64);
 
-    fn write_bytes(&mut self, field_offset: usize, bytes: &[u8]) -> usize {
+    fn write_bytes_with<P: HeaderProfile>(&mut self, field_offset: usize, bytes: &[u8]) -> usize {
         let data_offset = self.len();
         let data_length = bytes.len();
+        assert!(
+            data_offset + data_length <= P::MAX_PAYLOAD_SIZE,
+            "encoded buffer exceeds header profile limit"
+        );
         // write header with data offset and length
-        self.write_u32(field_offset + 0, data_offset as u32);
-        self.write_u32(field_offset + 4, data_length as u32);
+        P::write_field(self, field_offset, data_offset);
+        P::write_field(self, field_offset + P::FIELD_SIZE, data_length);
         // write bytes to the end of the buffer
         self.buffer[data_offset..(data_offset + data_length)].copy_from_slice(bytes);
         self.body_length += bytes.len();
-        8
+        P::FIELD_SIZE * 2
+    }
+}
+
+/// Encoder that writes into the borrowed buffer, for example into a region of the guest memory
+pub struct SliceEncoder<'a> {
+    header_length: usize,
+    body_length: usize,
+    buffer: &'a mut [u8],
+}
+
+impl<'a> SliceEncoder<'a> {
+    pub fn new(buffer: &'a mut [u8], header_length: usize) -> Self {
+        assert!(
+            header_length <= buffer.len(),
+            "buffer is too small for the header"
+        );
+        buffer[..header_length].fill(0);
+        Self {
+            header_length,
+            body_length: 0,
+            buffer,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.header_length + self.body_length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns number of the written bytes
+    pub fn finalize(self) -> usize {
+        self.len()
+    }
+}
+
+impl<'a> WritableBuffer for SliceEncoder<'a> {
+    fn write_i8(&mut self, field_offset: usize, value: i8) -> usize {
+        self.buffer[field_offset] = value as u8;
+        1
+    }
+    fn write_u8(&mut self, field_offset: usize, value: u8) -> usize {
+        self.buffer[field_offset] = value;
+        1
+    }
+
+    encode_le_int!(u16);
+    encode_le_int!(i16);
+    encode_le_int!(u32);
+    encode_le_int!(i32);
+    encode_le_int!(u64);
+    encode_le_int!(i64);
+
+    fn write_bytes_with<P: HeaderProfile>(&mut self, field_offset: usize, bytes: &[u8]) -> usize {
+        let data_offset = self.len();
+        let data_length = bytes.len();
+        assert!(
+            data_offset + data_length <= P::MAX_PAYLOAD_SIZE,
+            "encoded buffer exceeds header profile limit"
+        );
+        assert!(
+            data_offset + data_length <= self.buffer.len(),
+            "buffer is too small for the encoded value"
+        );
+        // write header with data offset and length
+        P::write_field(self, field_offset, data_offset);
+        P::write_field(self, field_offset + P::FIELD_SIZE, data_length);
+        // write bytes to the end of the buffer
+        self.buffer[data_offset..(data_offset + data_length)].copy_from_slice(bytes);
+        self.body_length += bytes.len();
+        P::FIELD_SIZE * 2
     }
 }
 
@@ -120,28 +253,87 @@ impl WritableBuffer for BufferEncoder {
     encode_le_int!(u64);
     encode_le_int!(i64);
 
-    fn write_bytes(&mut self, field_offset: usize, bytes: &[u8]) -> usize {
+    fn write_bytes_with<P: HeaderProfile>(&mut self, field_offset: usize, bytes: &[u8]) -> usize {
         let data_offset = self.buffer.len();
         let data_length = bytes.len();
+        assert!(
+            data_offset + data_length <= P::MAX_PAYLOAD_SIZE,
+            "encoded buffer exceeds header profile limit"
+        );
         // write header with data offset and length
-        self.write_u32(field_offset + 0, data_offset as u32);
-        self.write_u32(field_offset + 4, data_length as u32);
+        P::write_field(self, field_offset, data_offset);
+        P::write_field(self, field_offset + P::FIELD_SIZE, data_length);
         // write bytes to the end of the buffer
         self.buffer.extend(bytes);
-        8
+        P::FIELD_SIZE * 2
     }
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CodecError {
+    /// Field or data region is out of the buffer
+    OutOfBounds { offset: usize, length: usize },
+    /// Data region overlaps the header
+    OverlapsHeader { offset: usize, length: usize },
+    /// Data region overlaps the data region of another field
+    AliasedField { offset: usize, length: usize },
+    /// Encoding is valid, but it's not the one produced by the encoder (strict mode only)
+    NonCanonical,
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::OutOfBounds { offset, length } => {
+                write!(
+                    f,
+                    "field is out of bounds (offset={offset}, length={length})"
+                )
+            }
+            CodecError::OverlapsHeader { offset, length } => {
+                write!(f, "data overlaps header (offset={offset}, length={length})")
+            }
+            CodecError::AliasedField { offset, length } => {
+                write!(
+                    f,
+                    "data overlaps another field (offset={offset}, length={length})"
+                )
+            }
+            CodecError::NonCanonical => write!(f, "non-canonical encoding"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CodecError {}
+
+/// Decoder of the encoded buffer.
+///
+/// Offsets and lengths are validated against the buffer, so malformed input never panics:
+/// invalid fields are decoded as zeros (or empty data) and the first error is kept till
+/// [`BufferDecoder::finish`] is called. The strict mode additionally requires canonical
+/// encoding: data regions follow each other in the field order without gaps and the trailing
+/// bytes, flags are 0 or 1.
 #[derive(Default)]
 pub struct BufferDecoder<'a> {
     buffer: &'a [u8],
+    header_length: usize,
+    strict: bool,
+    // data regions read so far
+    regions: RefCell<Vec<(usize, usize)>>,
+    // end of the last data region
+    data_end: Cell<usize>,
+    error: Cell<Option<CodecError>>,
 }
 
 macro_rules! decode_le_int {
     ($typ:ty) => {
         paste! {
             pub fn [<read_ $typ>](&self, field_offset: usize) -> $typ {
-                LittleEndian::[<read_ $typ>](&self.buffer[field_offset..])
+                match self.field(field_offset, core::mem::size_of::<$typ>()) {
+                    Some(bytes) => LittleEndian::[<read_ $typ>](bytes),
+                    None => 0,
+                }
             }
         }
     };
@@ -149,14 +341,143 @@ macro_rules! decode_le_int {
 
 impl<'a> BufferDecoder<'a> {
     pub fn new(input: &'a [u8]) -> Self {
-        Self { buffer: input }
+        Self {
+            buffer: input,
+            ..Default::default()
+        }
+    }
+
+    /// Creates decoder that rejects data regions overlapping the header
+    pub fn with_header_length(input: &'a [u8], header_length: usize) -> Self {
+        let result = Self {
+            buffer: input,
+            header_length,
+            data_end: Cell::new(header_length),
+            ..Default::default()
+        };
+        if header_length > input.len() {
+            result.report_error(CodecError::OutOfBounds {
+                offset: 0,
+                length: header_length,
+            });
+        }
+        result
+    }
+
+    /// Creates decoder in the strict mode that accepts canonical encoding only
+    pub fn new_strict(input: &'a [u8], header_length: usize) -> Self {
+        let mut result = Self::with_header_length(input, header_length);
+        result.strict = true;
+        result
+    }
+
+    /// Creates decoder of the nested buffer (for example elements of the vector) with the same
+    /// mode, errors of the nested decoder must be merged back with [`BufferDecoder::merge`]
+    pub fn nested<'b>(&self, input: &'b [u8], header_length: usize) -> BufferDecoder<'b> {
+        let mut result = BufferDecoder::with_header_length(input, header_length);
+        result.strict = self.strict;
+        result
+    }
+
+    pub fn merge(&self, nested: BufferDecoder) {
+        if let Err(err) = nested.finish() {
+            self.report_error(err);
+        }
+    }
+
+    /// Returns end of the header region: start of the first data region read so far or the
+    /// buffer length, fields behind it are missing in the buffer
+    pub fn header_end(&self) -> usize {
+        self.regions
+            .borrow()
+            .iter()
+            .map(|(offset, _)| *offset)
+            .min()
+            .unwrap_or(self.buffer.len())
+            .min(self.buffer.len())
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Records the error, only the first error is kept
+    pub fn report_error(&self, err: CodecError) {
+        if self.error.get().is_none() {
+            self.error.set(Some(err));
+        }
+    }
+
+    /// Returns the first decoding error, in the strict mode it also verifies that there are no
+    /// trailing bytes after the last data region
+    pub fn finish(self) -> Result<(), CodecError> {
+        if let Some(err) = self.error.get() {
+            return Err(err);
+        }
+        if self.strict && self.data_end.get() != self.buffer.len() {
+            return Err(CodecError::NonCanonical);
+        }
+        Ok(())
     }
 
-    pub fn read_i8(&mut self, field_offset: usize) -> i8 {
-        self.buffer[field_offset] as i8
+    fn field(&self, offset: usize, length: usize) -> Option<&'a [u8]> {
+        let result = offset
+            .checked_add(length)
+            .and_then(|end| self.buffer.get(offset..end));
+        if result.is_none() {
+            self.report_error(CodecError::OutOfBounds { offset, length });
+        }
+        result
     }
-    pub fn read_u8(&mut self, field_offset: usize) -> u8 {
-        self.buffer[field_offset]
+
+    fn data_region(&self, offset: usize, length: usize) -> &'a [u8] {
+        let Some(data) = self.field(offset, length) else {
+            return &[];
+        };
+        let mut regions = self.regions.borrow_mut();
+        // the same field can be read several times
+        if regions.contains(&(offset, length)) {
+            return data;
+        }
+        if self.strict && offset != self.data_end.get() {
+            self.report_error(CodecError::NonCanonical);
+            return &[];
+        }
+        if length == 0 {
+            return data;
+        }
+        if offset < self.header_length {
+            self.report_error(CodecError::OverlapsHeader { offset, length });
+            return &[];
+        }
+        let end = offset + length;
+        if regions.iter().any(|(region_offset, region_length)| {
+            offset < region_offset + region_length && *region_offset < end
+        }) {
+            self.report_error(CodecError::AliasedField { offset, length });
+            return &[];
+        }
+        regions.push((offset, length));
+        self.data_end.set(self.data_end.get().max(end));
+        data
+    }
+
+    pub fn read_i8(&self, field_offset: usize) -> i8 {
+        self.read_u8(field_offset) as i8
+    }
+    pub fn read_u8(&self, field_offset: usize) -> u8 {
+        self.field(field_offset, 1)
+            .map(|v| v[0])
+            .unwrap_or_default()
+    }
+
+    /// Reads boolean flag, in the strict mode flag must be 0 or 1
+    pub fn read_flag(&self, field_offset: usize) -> bool {
+        let value = self.read_u8(field_offset);
+        if self.strict && value > 1 {
+            self.report_error(CodecError::NonCanonical);
+        }
+        value != 0
     }
 
     decode_le_int!(i16);
@@ -166,18 +487,41 @@ impl<'a> BufferDecoder<'a> {
     decode_le_int!(i64);
     decode_le_int!(u64);
 
+    /// Validates number of the encoded elements against the size of their data, it protects
+    /// from huge allocations requested by the malformed input
+    pub fn check_count(&self, count: usize, element_size: usize, data_length: usize) -> usize {
+        match count.checked_mul(element_size) {
+            Some(size) if size <= data_length => count,
+            _ => {
+                self.report_error(CodecError::OutOfBounds {
+                    offset: 0,
+                    length: data_length,
+                });
+                0
+            }
+        }
+    }
+
     pub fn read_bytes_header(&self, field_offset: usize) -> (usize, usize) {
-        let bytes_offset = self.read_u32(field_offset + 0) as usize;
-        let bytes_length = self.read_u32(field_offset + 4) as usize;
+        self.read_bytes_header_with::<LongHeaders>(field_offset)
+    }
+
+    pub fn read_bytes_header_with<P: HeaderProfile>(&self, field_offset: usize) -> (usize, usize) {
+        let bytes_offset = P::read_field(self, field_offset);
+        let bytes_length = P::read_field(self, field_offset + P::FIELD_SIZE);
         (bytes_offset, bytes_length)
     }
 
-    pub fn read_bytes(&self, field_offset: usize) -> &[u8] {
-        let (bytes_offset, bytes_length) = self.read_bytes_header(field_offset);
-        &self.buffer[bytes_offset..(bytes_offset + bytes_length)]
+    pub fn read_bytes(&self, field_offset: usize) -> &'a [u8] {
+        self.read_bytes_with::<LongHeaders>(field_offset)
+    }
+
+    pub fn read_bytes_with<P: HeaderProfile>(&self, field_offset: usize) -> &'a [u8] {
+        let (bytes_offset, bytes_length) = self.read_bytes_header_with::<P>(field_offset);
+        self.data_region(bytes_offset, bytes_length)
     }
 
-    pub fn read_bytes2(&self, field1_offset: usize, field2_offset: usize) -> (&[u8], &[u8]) {
+    pub fn read_bytes2(&self, field1_offset: usize, field2_offset: usize) -> (&'a [u8], &'a [u8]) {
         (
             self.read_bytes(field1_offset),
             self.read_bytes(field2_offset),
@@ -187,7 +531,7 @@ impl<'a> BufferDecoder<'a> {
 
 #[cfg(test)]
 mod test {
-    use crate::buffer::{BufferDecoder, BufferEncoder, FixedEncoder, WritableBuffer};
+    use crate::buffer::{BufferDecoder, BufferEncoder, FixedEncoder, SliceEncoder, WritableBuffer};
 
     #[test]
     fn test_simple_encoding() {
@@ -263,6 +607,21 @@ mod test {
         assert_eq!(decoder.read_u32(24), 0x7f);
     }
 
+    #[test]
+    fn test_slice_encoding() {
+        let mut memory = [0xffu8; 64];
+        let length = {
+            let mut buffer = SliceEncoder::new(&mut memory[8..], 4 + 8);
+            buffer.write_u32(0, 0xbadcab1e);
+            buffer.write_bytes(4, &[0, 1, 2, 3, 4]);
+            buffer.finalize()
+        };
+        assert_eq!(length, 17);
+        let decoder = BufferDecoder::new(&memory[8..(8 + length)]);
+        assert_eq!(decoder.read_u32(0), 0xbadcab1e);
+        assert_eq!(decoder.read_bytes(4).to_vec(), vec![0, 1, 2, 3, 4]);
+    }
+
     #[test]
     fn test_bytes_array() {
         let buffer = {
@@ -1,4 +1,11 @@
-use crate::buffer::{BufferDecoder, BufferEncoder, FixedEncoder, WritableBuffer};
+use crate::buffer::{
+    BufferDecoder,
+    BufferEncoder,
+    CodecError,
+    FixedEncoder,
+    SliceEncoder,
+    WritableBuffer,
+};
 use alloc::vec::Vec;
 use core::marker::PhantomData;
 
@@ -19,6 +26,13 @@ pub trait Encoder<T: Sized> {
         self.encode(&mut buffer_encoder, field_offset);
         buffer_encoder.finalize()
     }
+    /// Encodes value into the borrowed buffer and returns number of the written bytes, panics if
+    /// the buffer is too small
+    fn encode_to_slice(&self, buffer: &mut [u8], field_offset: usize) -> usize {
+        let mut buffer_encoder = SliceEncoder::new(buffer, Self::HEADER_SIZE);
+        self.encode(&mut buffer_encoder, field_offset);
+        buffer_encoder.finalize()
+    }
 
     fn encode<W: WritableBuffer>(&self, encoder: &mut W, field_offset: usize);
 
@@ -31,6 +45,21 @@ pub trait Encoder<T: Sized> {
     fn decode_body(decoder: &mut BufferDecoder, field_offset: usize, result: &mut T) {
         Self::decode_header(decoder, field_offset, result);
     }
+
+    /// Decodes value from the untrusted buffer, malformed offsets and lengths are reported as
+    /// errors instead of panics
+    fn try_decode(buffer: &[u8], result: &mut T) -> Result<(), CodecError> {
+        let mut buffer_decoder = BufferDecoder::with_header_length(buffer, Self::HEADER_SIZE);
+        Self::decode_body(&mut buffer_decoder, 0, result);
+        buffer_decoder.finish()
+    }
+
+    /// The same as [`Encoder::try_decode`], but accepts the canonical encoding only
+    fn try_decode_strict(buffer: &[u8], result: &mut T) -> Result<(), CodecError> {
+        let mut buffer_decoder = BufferDecoder::new_strict(buffer, Self::HEADER_SIZE);
+        Self::decode_body(&mut buffer_decoder, 0, result);
+        buffer_decoder.finish()
+    }
 }
 
 pub struct FieldEncoder<T: Sized + Encoder<T>, const FIELD_OFFSET: usize>(PhantomData<T>);
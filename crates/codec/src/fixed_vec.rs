@@ -0,0 +1,154 @@
+use crate::{
+    buffer::{HeaderProfile, LongHeaders, WritableBuffer},
+    vec::encode_vec,
+    BufferDecoder,
+    Encoder,
+};
+use core::ops::Deref;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FixedVecError {
+    /// Number of elements exceeds the capacity of the vector
+    Overflow { capacity: usize, length: usize },
+}
+
+/// Vector with the const capacity that doesn't use heap, so allocation-free guests can decode
+/// dynamic-length inputs into bounded buffers.
+///
+/// It's encoded in the same way as `Vec<T>`, so it can decode `Vec<T>` values and vice versa.
+#[derive(Debug, Clone)]
+pub struct FixedVec<T, const N: usize> {
+    items: [T; N],
+    len: usize,
+}
+
+impl<T: Default, const N: usize> Default for FixedVec<T, N> {
+    fn default() -> Self {
+        Self {
+            items: core::array::from_fn(|_| T::default()),
+            len: 0,
+        }
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for FixedVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for FixedVec<T, N> {}
+
+impl<T, const N: usize> Deref for FixedVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T: Default + Clone, const N: usize> TryFrom<&[T]> for FixedVec<T, N> {
+    type Error = FixedVecError;
+
+    fn try_from(value: &[T]) -> Result<Self, Self::Error> {
+        let mut result = Self::default();
+        for item in value {
+            result.push(item.clone())?;
+        }
+        Ok(result)
+    }
+}
+
+impl<T, const N: usize> FixedVec<T, N> {
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.items[..self.len]
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.items[..self.len]
+    }
+
+    pub fn push(&mut self, value: T) -> Result<(), FixedVecError> {
+        if self.len == N {
+            return Err(FixedVecError::Overflow {
+                capacity: N,
+                length: N + 1,
+            });
+        }
+        self.items[self.len] = value;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes all elements, dropped elements stay in the storage until they're overwritten
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<T: Default + Encoder<T>, const N: usize> FixedVec<T, N> {
+    /// Decodes the vector body and returns an overflow error if the encoded vector has more
+    /// elements than the capacity
+    pub fn try_decode_body(
+        decoder: &mut BufferDecoder,
+        field_offset: usize,
+        result: &mut FixedVec<T, N>,
+    ) -> Result<(), FixedVecError> {
+        let length = LongHeaders::read_field(decoder, field_offset);
+        if length > N {
+            return Err(FixedVecError::Overflow {
+                capacity: N,
+                length,
+            });
+        }
+        result.clear();
+        if length == 0 {
+            return Ok(());
+        }
+        let input_bytes = decoder.read_bytes(field_offset + LongHeaders::FIELD_SIZE);
+        let length = decoder.check_count(length, T::HEADER_SIZE, input_bytes.len());
+        let mut value_decoder = decoder.nested(input_bytes, T::HEADER_SIZE * length);
+        for i in 0..length {
+            T::decode_body(&mut value_decoder, T::HEADER_SIZE * i, &mut result.items[i]);
+        }
+        decoder.merge(value_decoder);
+        result.len = length;
+        Ok(())
+    }
+}
+
+impl<T: Default + Sized + Encoder<T>, const N: usize> Encoder<FixedVec<T, N>> for FixedVec<T, N> {
+    const HEADER_SIZE: usize = LongHeaders::FIELD_SIZE * 3;
+
+    fn encode<W: WritableBuffer>(&self, encoder: &mut W, field_offset: usize) {
+        encode_vec::<T, LongHeaders, W>(self.as_slice(), encoder, field_offset);
+    }
+
+    fn decode_header(
+        decoder: &mut BufferDecoder,
+        field_offset: usize,
+        _result: &mut FixedVec<T, N>,
+    ) -> (usize, usize) {
+        decoder.read_bytes_header(field_offset + LongHeaders::FIELD_SIZE)
+    }
+
+    /// Panics if the encoded vector doesn't fit into the capacity, use
+    /// [`FixedVec::try_decode_body`] to handle the overflow
+    fn decode_body(decoder: &mut BufferDecoder, field_offset: usize, result: &mut FixedVec<T, N>) {
+        if let Err(err) = Self::try_decode_body(decoder, field_offset, result) {
+            panic!("can't decode fixed vector: {:?}", err);
+        }
+    }
+}
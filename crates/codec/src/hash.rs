@@ -36,10 +36,10 @@ impl<K: Default + Sized + Encoder<K> + Eq + Hash + Ord, V: Default + Sized + Enc
     ) -> (usize, usize) {
         // read length and reserve required capacity in hashmap
         let length = decoder.read_u32(field_offset) as usize;
-        result.reserve(length);
         // read bytes header to calculate hint
         let (keys_offset, keys_length) = decoder.read_bytes_header(field_offset + 4);
         let (_, values_length) = decoder.read_bytes_header(field_offset + 12);
+        result.reserve(decoder.check_count(length, K::HEADER_SIZE, keys_length));
         // sum of keys and values are total body length
         (keys_offset, keys_length + values_length)
     }
@@ -48,22 +48,26 @@ impl<K: Default + Sized + Encoder<K> + Eq + Hash + Ord, V: Default + Sized + Enc
         // decode length, keys and values
         let length = decoder.read_u32(field_offset) as usize;
         let (key_bytes, value_bytes) = decoder.read_bytes2(field_offset + 4, field_offset + 12);
+        let length = decoder.check_count(length, K::HEADER_SIZE, key_bytes.len());
+        let length = decoder.check_count(length, V::HEADER_SIZE, value_bytes.len());
         // decode keys
-        let mut key_decoder = BufferDecoder::new(key_bytes);
+        let mut key_decoder = decoder.nested(key_bytes, K::HEADER_SIZE * length);
         let keys = (0..length).map(|i| {
             let mut result = Default::default();
             K::decode_body(&mut key_decoder, K::HEADER_SIZE * i, &mut result);
             result
         });
         // decode values
-        let mut value_decoder = BufferDecoder::new(value_bytes);
+        let mut value_decoder = decoder.nested(value_bytes, V::HEADER_SIZE * length);
         let values = (0..length).map(|i| {
             let mut result = Default::default();
             V::decode_body(&mut value_decoder, V::HEADER_SIZE * i, &mut result);
             result
         });
         // zip into map
-        *result = keys.zip(values).collect()
+        *result = keys.zip(values).collect();
+        decoder.merge(key_decoder);
+        decoder.merge(value_decoder);
     }
 }
 
@@ -92,9 +96,9 @@ impl<T: Default + Sized + Encoder<T> + Eq + Hash + Ord> Encoder<HashSet<T>> for
     ) -> (usize, usize) {
         // read set size and reserve required memory
         let length = decoder.read_u32(field_offset) as usize;
-        result.reserve(length);
         // read bytes header
         let (value_offset, value_length) = decoder.read_bytes_header(field_offset + 4);
+        result.reserve(decoder.check_count(length, T::HEADER_SIZE, value_length));
         (value_offset, value_length)
     }
 
@@ -102,14 +106,16 @@ impl<T: Default + Sized + Encoder<T> + Eq + Hash + Ord> Encoder<HashSet<T>> for
         // decode length, keys and values
         let length = decoder.read_u32(field_offset) as usize;
         let value_bytes = decoder.read_bytes(field_offset + 4);
+        let length = decoder.check_count(length, T::HEADER_SIZE, value_bytes.len());
         // decode values
-        let mut value_decoder = BufferDecoder::new(value_bytes);
+        let mut value_decoder = decoder.nested(value_bytes, T::HEADER_SIZE * length);
         let values = (0..length).map(|i| {
             let mut result = Default::default();
             T::decode_body(&mut value_decoder, T::HEADER_SIZE * i, &mut result);
             result
         });
         // zip into map
-        *result = values.collect()
+        *result = values.collect();
+        decoder.merge(value_decoder);
     }
 }
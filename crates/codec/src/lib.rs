@@ -4,19 +4,35 @@ extern crate alloc;
 extern crate core;
 
 pub use crate::{
-    buffer::{BufferDecoder, BufferEncoder, WritableBuffer},
+    buffer::{
+        BufferDecoder,
+        BufferEncoder,
+        CodecError,
+        HeaderProfile,
+        LongHeaders,
+        ShortHeaders,
+        SliceEncoder,
+        WritableBuffer,
+    },
     empty::EmptyVec,
     encoder::{Encoder, FieldEncoder},
+    fixed_vec::{FixedVec, FixedVecError},
+    short::{ShortBytes, ShortVec},
 };
 
 mod buffer;
 mod empty;
 mod encoder;
 mod evm;
+mod fixed_vec;
 mod hash;
 mod macros;
 mod primitive;
+pub mod rlp;
 mod serde;
+mod short;
+#[cfg(feature = "ssz")]
+pub mod ssz;
 #[cfg(test)]
 mod tests;
 mod tuple;
@@ -24,7 +24,7 @@ impl Encoder<bool> for bool {
         field_offset: usize,
         result: &mut bool,
     ) -> (usize, usize) {
-        *result = decoder.read_u8(field_offset) != 0;
+        *result = decoder.read_flag(field_offset);
         (0, 0)
     }
 }
@@ -107,9 +107,7 @@ impl<T: Sized + Encoder<T> + Default> Encoder<Option<T>> for Option<T> {
     }
 
     fn decode_body(decoder: &mut BufferDecoder, field_offset: usize, result: &mut Option<T>) {
-        let mut option_flag: u8 = 0;
-        u8::decode_header(decoder, field_offset, &mut option_flag);
-        *result = if option_flag != 0 {
+        *result = if decoder.read_flag(field_offset) {
             let mut result_inner: T = Default::default();
             T::decode_body(decoder, field_offset + 1, &mut result_inner);
             Some(result_inner)
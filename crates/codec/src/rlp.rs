@@ -0,0 +1,196 @@
+//! RLP codec on top of `alloy-rlp`, the crate is `no_std` so contracts and the host share the same
+//! encoding rules (headers, receipts and transactions of Ethereum)
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use alloy_primitives::{keccak256, B256};
+pub use alloy_rlp::{self, Decodable, Encodable, Error as RlpError, Header};
+
+/// Appends RLP header for the payload of the specified length
+pub fn rlp_encode_header(out: &mut Vec<u8>, is_list: bool, payload_length: usize) {
+    Header {
+        list: is_list,
+        payload_length,
+    }
+    .encode(out)
+}
+
+pub fn rlp_encode_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    bytes.encode(out)
+}
+
+/// Encodes list of items
+pub fn rlp_encode_list<T: Encodable>(out: &mut Vec<u8>, items: &[T]) {
+    alloy_rlp::encode_list::<T, T>(items, out)
+}
+
+/// Shortcuts over [`Encodable`] that write into a vector
+pub trait RlpEncode: Encodable {
+    fn rlp_encode(&self, out: &mut Vec<u8>) {
+        self.encode(out)
+    }
+
+    fn rlp_encode_to_vec(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.length());
+        self.encode(&mut out);
+        out
+    }
+}
+
+impl<T: Encodable + ?Sized> RlpEncode for T {}
+
+/// Shortcuts over [`Decodable`] that read from the [`RlpDecoder`]
+pub trait RlpDecode: Decodable {
+    fn rlp_decode(decoder: &mut RlpDecoder) -> Result<Self, RlpError> {
+        decoder.decode()
+    }
+
+    /// Decodes the item and fails with `UnexpectedLength` if any bytes are left
+    fn rlp_decode_exact(input: &[u8]) -> Result<Self, RlpError> {
+        let mut decoder = RlpDecoder::new(input);
+        let result = Self::rlp_decode(&mut decoder)?;
+        if !decoder.is_empty() {
+            return Err(RlpError::UnexpectedLength);
+        }
+        Ok(result)
+    }
+}
+
+impl<T: Decodable> RlpDecode for T {}
+
+/// Streaming RLP decoder that reads items from the input buffer one by one, the buffer is
+/// advanced only if the item is decoded successfully
+pub struct RlpDecoder<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> RlpDecoder<'a> {
+    pub fn new(buffer: &'a [u8]) -> Self {
+        Self { buffer }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    pub fn remaining(&self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Reads item header and returns `(is_list, payload)`, the payload is consumed
+    pub fn read_item(&mut self) -> Result<(bool, &'a [u8]), RlpError> {
+        let mut buffer = self.buffer;
+        let header = Header::decode(&mut buffer)?;
+        if buffer.len() < header.payload_length {
+            return Err(RlpError::InputTooShort);
+        }
+        let (payload, rest) = buffer.split_at(header.payload_length);
+        self.buffer = rest;
+        Ok((header.list, payload))
+    }
+
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], RlpError> {
+        match self.read_item()? {
+            (false, payload) => Ok(payload),
+            (true, _) => Err(RlpError::UnexpectedList),
+        }
+    }
+
+    /// Reads list header and returns decoder over the list payload
+    pub fn read_list(&mut self) -> Result<RlpDecoder<'a>, RlpError> {
+        match self.read_item()? {
+            (true, payload) => Ok(RlpDecoder::new(payload)),
+            (false, _) => Err(RlpError::UnexpectedString),
+        }
+    }
+
+    pub fn decode<T: Decodable>(&mut self) -> Result<T, RlpError> {
+        let mut buffer = self.buffer;
+        let result = T::decode(&mut buffer)?;
+        self.buffer = buffer;
+        Ok(result)
+    }
+}
+
+/// Keccak256 hash of the RLP encoded item (block and transaction hashes, empty trie roots), guest
+/// contracts should hash the encoded item with the keccak256 syscall instead
+#[cfg(feature = "std")]
+pub fn rlp_hash<T: Encodable + ?Sized>(item: &T) -> B256 {
+    keccak256(item.rlp_encode_to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rlp::{RlpDecode, RlpDecoder, RlpEncode, RlpError};
+    use alloc::vec::Vec;
+    use alloy_primitives::{Bytes, U256};
+    use hex_literal::hex;
+
+    #[test]
+    fn test_rlp_strings() {
+        assert_eq!(Bytes::new().rlp_encode_to_vec(), hex!("80"));
+        assert_eq!(
+            Bytes::from_static(b"dog").rlp_encode_to_vec(),
+            hex!("83646f67")
+        );
+        assert_eq!(0u64.rlp_encode_to_vec(), hex!("80"));
+        assert_eq!(15u64.rlp_encode_to_vec(), hex!("0f"));
+        assert_eq!(1024u64.rlp_encode_to_vec(), hex!("820400"));
+        let long = Bytes::from(vec![0xaau8; 56]);
+        let encoded = long.rlp_encode_to_vec();
+        assert_eq!(&encoded[..2], &hex!("b838"));
+        assert_eq!(Bytes::rlp_decode_exact(&encoded).unwrap(), long);
+    }
+
+    #[test]
+    fn test_rlp_lists() {
+        let list = vec![Bytes::from_static(b"cat"), Bytes::from_static(b"dog")];
+        let encoded = list.rlp_encode_to_vec();
+        assert_eq!(encoded, hex!("c88363617483646f67"));
+        assert_eq!(Vec::<Bytes>::rlp_decode_exact(&encoded).unwrap(), list);
+        assert_eq!(Vec::<u64>::new().rlp_encode_to_vec(), hex!("c0"));
+        // items are read one by one from the list payload
+        let mut decoder = RlpDecoder::new(&encoded);
+        let mut items = decoder.read_list().unwrap();
+        assert!(decoder.is_empty());
+        assert_eq!(items.read_bytes().unwrap(), b"cat");
+        assert_eq!(items.decode::<Bytes>().unwrap(), Bytes::from_static(b"dog"));
+        assert_eq!(items.read_bytes(), Err(RlpError::InputTooShort));
+    }
+
+    #[test]
+    fn test_rlp_non_canonical() {
+        assert_eq!(
+            u64::rlp_decode_exact(&hex!("8100")),
+            Err(RlpError::NonCanonicalSingleByte)
+        );
+        assert_eq!(
+            u64::rlp_decode_exact(&hex!("820004")),
+            Err(RlpError::LeadingZero)
+        );
+        assert_eq!(
+            u64::rlp_decode_exact(&hex!("0f00")),
+            Err(RlpError::UnexpectedLength)
+        );
+        assert_eq!(
+            U256::rlp_decode_exact(&hex!("820400")).unwrap(),
+            U256::from(1024)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_rlp_hash() {
+        use crate::rlp::rlp_hash;
+        use alloy_primitives::b256;
+
+        // hash of the empty ommers list and the root of the empty trie
+        assert_eq!(
+            rlp_hash(&Vec::<Bytes>::new()),
+            b256!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347")
+        );
+        assert_eq!(
+            rlp_hash(&Bytes::new()),
+            b256!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421")
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    buffer::{HeaderProfile, ShortHeaders, WritableBuffer},
+    vec::{decode_vec_body, decode_vec_header, encode_vec},
+    BufferDecoder,
+    Encoder,
+};
+use alloc::vec::Vec;
+use alloy_primitives::Bytes;
+use core::ops::{Deref, DerefMut};
+
+/// Vector encoded with the [`ShortHeaders`] profile: `u16` length, offset and size (6 bytes
+/// header instead of 12), the encoded buffer of the message can't exceed 64 KiB
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ShortVec<T>(pub Vec<T>);
+
+impl<T> From<Vec<T>> for ShortVec<T> {
+    fn from(value: Vec<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Deref for ShortVec<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for ShortVec<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Default + Sized + Encoder<T>> Encoder<ShortVec<T>> for ShortVec<T> {
+    const HEADER_SIZE: usize = ShortHeaders::FIELD_SIZE * 3;
+
+    fn encode<W: WritableBuffer>(&self, encoder: &mut W, field_offset: usize) {
+        encode_vec::<T, ShortHeaders, W>(&self.0, encoder, field_offset);
+    }
+
+    fn decode_header(
+        decoder: &mut BufferDecoder,
+        field_offset: usize,
+        result: &mut ShortVec<T>,
+    ) -> (usize, usize) {
+        decode_vec_header::<T, ShortHeaders>(decoder, field_offset, &mut result.0)
+    }
+
+    fn decode_body(decoder: &mut BufferDecoder, field_offset: usize, result: &mut ShortVec<T>) {
+        decode_vec_body::<T, ShortHeaders>(decoder, field_offset, &mut result.0);
+    }
+}
+
+/// Bytes encoded with the [`ShortHeaders`] profile: `u16` offset and length (4 bytes header
+/// instead of 8), the encoded buffer of the message can't exceed 64 KiB
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ShortBytes(pub Bytes);
+
+impl From<Bytes> for ShortBytes {
+    fn from(value: Bytes) -> Self {
+        Self(value)
+    }
+}
+
+impl Deref for ShortBytes {
+    type Target = Bytes;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Encoder<ShortBytes> for ShortBytes {
+    const HEADER_SIZE: usize = ShortHeaders::FIELD_SIZE * 2;
+
+    fn encode<W: WritableBuffer>(&self, encoder: &mut W, field_offset: usize) {
+        encoder.write_bytes_with::<ShortHeaders>(field_offset, &self.0);
+    }
+
+    fn decode_header(
+        decoder: &mut BufferDecoder,
+        field_offset: usize,
+        _result: &mut ShortBytes,
+    ) -> (usize, usize) {
+        decoder.read_bytes_header_with::<ShortHeaders>(field_offset)
+    }
+
+    fn decode_body(decoder: &mut BufferDecoder, field_offset: usize, result: &mut ShortBytes) {
+        let bytes = decoder.read_bytes_with::<ShortHeaders>(field_offset);
+        *result = ShortBytes(Bytes::copy_from_slice(bytes));
+    }
+}
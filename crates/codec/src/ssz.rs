@@ -0,0 +1,359 @@
+use alloc::vec::Vec;
+use alloy_primitives::{FixedBytes, U256};
+use sha2::{Digest, Sha256};
+
+/// Size of the offset for variable-size fields
+pub const SSZ_OFFSET_SIZE: usize = 4;
+/// Size of the merkle chunk
+pub const SSZ_CHUNK_SIZE: usize = 32;
+
+pub type SszRoot = [u8; 32];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SszError {
+    InvalidLength,
+    InvalidOffset,
+    InvalidValue,
+}
+
+pub trait SszEncode {
+    /// Returns `Some(size)` for fixed-size types and `None` for variable-size ones
+    fn ssz_fixed_size() -> Option<usize>;
+
+    fn ssz_append(&self, out: &mut Vec<u8>);
+
+    fn ssz_encode_to_vec(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.ssz_append(&mut out);
+        out
+    }
+}
+
+pub trait SszDecode: Sized {
+    fn ssz_decode(bytes: &[u8]) -> Result<Self, SszError>;
+}
+
+pub trait SszHashTreeRoot {
+    fn hash_tree_root(&self) -> SszRoot;
+}
+
+fn sha256_pair(left: &[u8], right: &[u8]) -> SszRoot {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Returns hash of zero subtree of the specified depth
+fn zero_hash(depth: usize) -> SszRoot {
+    let mut result = [0u8; 32];
+    for _ in 0..depth {
+        result = sha256_pair(&result, &result);
+    }
+    result
+}
+
+/// Merkleizes chunks padding them with zero chunks up to the `limit` (or the next power of
+/// two if limit is not specified)
+pub fn ssz_merkleize(chunks: &[SszRoot], limit: Option<usize>) -> Result<SszRoot, SszError> {
+    let limit = limit.unwrap_or(chunks.len());
+    if chunks.len() > limit {
+        return Err(SszError::InvalidLength);
+    }
+    let width = limit.max(1).next_power_of_two();
+    let depth = width.trailing_zeros() as usize;
+    let mut layer = chunks.to_vec();
+    for level in 0..depth {
+        if layer.len() % 2 == 1 {
+            layer.push(zero_hash(level));
+        }
+        layer = layer
+            .chunks(2)
+            .map(|pair| sha256_pair(&pair[0], &pair[1]))
+            .collect();
+    }
+    Ok(layer.first().copied().unwrap_or_else(|| zero_hash(depth)))
+}
+
+pub fn ssz_mix_in_length(root: &SszRoot, length: usize) -> SszRoot {
+    let mut length_chunk = [0u8; 32];
+    length_chunk[..8].copy_from_slice(&(length as u64).to_le_bytes());
+    sha256_pair(root, &length_chunk)
+}
+
+/// Packs serialized basic values into 32-byte chunks
+pub fn ssz_pack(bytes: &[u8]) -> Vec<SszRoot> {
+    bytes
+        .chunks(SSZ_CHUNK_SIZE)
+        .map(|chunk| {
+            let mut result = [0u8; 32];
+            result[..chunk.len()].copy_from_slice(chunk);
+            result
+        })
+        .collect()
+}
+
+/// Computes hash tree root of the container from the roots of its fields
+pub fn ssz_container_root(field_roots: &[SszRoot]) -> SszRoot {
+    ssz_merkleize(field_roots, None).unwrap()
+}
+
+macro_rules! impl_ssz_uint {
+    ($typ:ty) => {
+        impl SszEncode for $typ {
+            fn ssz_fixed_size() -> Option<usize> {
+                Some(core::mem::size_of::<$typ>())
+            }
+            fn ssz_append(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes());
+            }
+        }
+        impl SszDecode for $typ {
+            fn ssz_decode(bytes: &[u8]) -> Result<Self, SszError> {
+                let bytes: [u8; core::mem::size_of::<$typ>()] =
+                    bytes.try_into().map_err(|_| SszError::InvalidLength)?;
+                Ok(<$typ>::from_le_bytes(bytes))
+            }
+        }
+        impl SszHashTreeRoot for $typ {
+            fn hash_tree_root(&self) -> SszRoot {
+                ssz_pack(&self.to_le_bytes())[0]
+            }
+        }
+    };
+}
+
+impl_ssz_uint!(u8);
+impl_ssz_uint!(u16);
+impl_ssz_uint!(u32);
+impl_ssz_uint!(u64);
+impl_ssz_uint!(u128);
+
+impl SszEncode for bool {
+    fn ssz_fixed_size() -> Option<usize> {
+        Some(1)
+    }
+    fn ssz_append(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+}
+
+impl SszDecode for bool {
+    fn ssz_decode(bytes: &[u8]) -> Result<Self, SszError> {
+        match bytes {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            [_] => Err(SszError::InvalidValue),
+            _ => Err(SszError::InvalidLength),
+        }
+    }
+}
+
+impl SszHashTreeRoot for bool {
+    fn hash_tree_root(&self) -> SszRoot {
+        (*self as u8).hash_tree_root()
+    }
+}
+
+impl SszEncode for U256 {
+    fn ssz_fixed_size() -> Option<usize> {
+        Some(U256::BYTES)
+    }
+    fn ssz_append(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.as_le_slice());
+    }
+}
+
+impl SszDecode for U256 {
+    fn ssz_decode(bytes: &[u8]) -> Result<Self, SszError> {
+        if bytes.len() != U256::BYTES {
+            return Err(SszError::InvalidLength);
+        }
+        Ok(U256::from_le_slice(bytes))
+    }
+}
+
+impl SszHashTreeRoot for U256 {
+    fn hash_tree_root(&self) -> SszRoot {
+        self.to_le_bytes::<{ U256::BYTES }>()
+    }
+}
+
+/// Fixed bytes are encoded as `Vector[byte, N]`
+impl<const N: usize> SszEncode for FixedBytes<N> {
+    fn ssz_fixed_size() -> Option<usize> {
+        Some(N)
+    }
+    fn ssz_append(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.as_slice());
+    }
+}
+
+impl<const N: usize> SszDecode for FixedBytes<N> {
+    fn ssz_decode(bytes: &[u8]) -> Result<Self, SszError> {
+        if bytes.len() != N {
+            return Err(SszError::InvalidLength);
+        }
+        Ok(FixedBytes::from_slice(bytes))
+    }
+}
+
+impl<const N: usize> SszHashTreeRoot for FixedBytes<N> {
+    fn hash_tree_root(&self) -> SszRoot {
+        ssz_merkleize(&ssz_pack(self.as_slice()), None).unwrap()
+    }
+}
+
+/// Vectors are encoded as SSZ lists, fixed-size elements are concatenated and variable-size
+/// elements are prefixed with the table of offsets.
+impl<T: SszEncode> SszEncode for Vec<T> {
+    fn ssz_fixed_size() -> Option<usize> {
+        None
+    }
+
+    fn ssz_append(&self, out: &mut Vec<u8>) {
+        if T::ssz_fixed_size().is_some() {
+            self.iter().for_each(|item| item.ssz_append(out));
+            return;
+        }
+        let mut offset = self.len() * SSZ_OFFSET_SIZE;
+        let mut body = Vec::new();
+        for item in self.iter() {
+            out.extend_from_slice(&(offset as u32).to_le_bytes());
+            let item_start = body.len();
+            item.ssz_append(&mut body);
+            offset += body.len() - item_start;
+        }
+        out.extend_from_slice(&body);
+    }
+}
+
+impl<T: SszEncode + SszDecode> SszDecode for Vec<T> {
+    fn ssz_decode(bytes: &[u8]) -> Result<Self, SszError> {
+        if bytes.is_empty() {
+            return Ok(Vec::new());
+        }
+        if let Some(item_size) = T::ssz_fixed_size() {
+            if item_size == 0 || bytes.len() % item_size != 0 {
+                return Err(SszError::InvalidLength);
+            }
+            return bytes.chunks(item_size).map(T::ssz_decode).collect();
+        }
+        let read_offset = |index: usize| -> Result<usize, SszError> {
+            let offset_bytes = bytes
+                .get(index * SSZ_OFFSET_SIZE..(index + 1) * SSZ_OFFSET_SIZE)
+                .ok_or(SszError::InvalidOffset)?;
+            Ok(u32::from_le_bytes(offset_bytes.try_into().unwrap()) as usize)
+        };
+        let first_offset = read_offset(0)?;
+        // non-empty list has at least one offset, so the first one can't point before the end
+        // of the offsets
+        if first_offset < SSZ_OFFSET_SIZE
+            || first_offset % SSZ_OFFSET_SIZE != 0
+            || first_offset > bytes.len()
+        {
+            return Err(SszError::InvalidOffset);
+        }
+        let count = first_offset / SSZ_OFFSET_SIZE;
+        let mut result = Vec::with_capacity(count);
+        for i in 0..count {
+            let start = read_offset(i)?;
+            let end = if i + 1 < count {
+                read_offset(i + 1)?
+            } else {
+                bytes.len()
+            };
+            if start > end || end > bytes.len() {
+                return Err(SszError::InvalidOffset);
+            }
+            result.push(T::ssz_decode(&bytes[start..end])?);
+        }
+        Ok(result)
+    }
+}
+
+/// Computes hash tree root of `List[T, limit]` where `T` is a basic type
+pub fn ssz_list_basic_root<T: SszEncode>(items: &[T], limit: usize) -> Result<SszRoot, SszError> {
+    let item_size = T::ssz_fixed_size().ok_or(SszError::InvalidValue)?;
+    let mut bytes = Vec::with_capacity(items.len() * item_size);
+    items.iter().for_each(|item| item.ssz_append(&mut bytes));
+    let chunks_limit = (limit * item_size + SSZ_CHUNK_SIZE - 1) / SSZ_CHUNK_SIZE;
+    let root = ssz_merkleize(&ssz_pack(&bytes), Some(chunks_limit))?;
+    Ok(ssz_mix_in_length(&root, items.len()))
+}
+
+/// Computes hash tree root of `List[T, limit]` where `T` is a composite type
+pub fn ssz_list_composite_root<T: SszHashTreeRoot>(
+    items: &[T],
+    limit: usize,
+) -> Result<SszRoot, SszError> {
+    let roots = items
+        .iter()
+        .map(|item| item.hash_tree_root())
+        .collect::<Vec<_>>();
+    let root = ssz_merkleize(&roots, Some(limit))?;
+    Ok(ssz_mix_in_length(&root, items.len()))
+}
+
+/// Computes hash tree root of `Vector[T, N]` where `T` is a composite type
+pub fn ssz_vector_composite_root<T: SszHashTreeRoot>(items: &[T]) -> SszRoot {
+    let roots = items
+        .iter()
+        .map(|item| item.hash_tree_root())
+        .collect::<Vec<_>>();
+    ssz_merkleize(&roots, None).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssz::{
+        ssz_list_basic_root,
+        ssz_merkleize,
+        zero_hash,
+        SszDecode,
+        SszEncode,
+        SszError,
+        SszHashTreeRoot,
+    };
+    use alloy_primitives::B256;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_ssz_encode_decode() {
+        let values: Vec<u64> = vec![1, 2, 3];
+        let encoded = values.ssz_encode_to_vec();
+        assert_eq!(encoded.len(), 24);
+        assert_eq!(Vec::<u64>::ssz_decode(&encoded).unwrap(), values);
+        let nested: Vec<Vec<u16>> = vec![vec![1, 2], vec![], vec![3]];
+        let encoded = nested.ssz_encode_to_vec();
+        assert_eq!(
+            encoded,
+            hex!("0c000000100000001000000001000200 0300").to_vec()
+        );
+        assert_eq!(Vec::<Vec<u16>>::ssz_decode(&encoded).unwrap(), nested);
+    }
+
+    #[test]
+    fn test_ssz_decode_zero_first_offset() {
+        // zero first offset would decode non-empty input as an empty list
+        assert_eq!(
+            Vec::<Vec<u16>>::ssz_decode(&hex!("00000000")),
+            Err(SszError::InvalidOffset)
+        );
+        assert_eq!(
+            Vec::<Vec<u16>>::ssz_decode(&hex!("000000000100")),
+            Err(SszError::InvalidOffset)
+        );
+    }
+
+    #[test]
+    fn test_ssz_merkleize() {
+        assert_eq!(ssz_merkleize(&[], Some(4)).unwrap(), zero_hash(2));
+        assert_eq!(1u64.hash_tree_root()[0], 1);
+        assert_eq!(B256::ZERO.hash_tree_root(), [0u8; 32]);
+        assert_eq!(
+            ssz_list_basic_root::<u64>(&[], 4).unwrap(),
+            hex!("f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a92759fb4b")
+        );
+    }
+}
@@ -275,3 +275,193 @@ fn test_complex_tuple() {
     Tuple::decode_body(&mut decoder, 0, &mut result);
     assert_eq!(result, original_data)
 }
+
+#[test]
+fn test_short_headers() {
+    use crate::{ShortBytes, ShortVec};
+    use fluentbase_codec_derive::Codec;
+
+    #[derive(Default, Debug, Codec, PartialEq)]
+    struct LongMessage {
+        data: Bytes,
+        values: Vec<u32>,
+    }
+    #[derive(Default, Debug, Codec, PartialEq)]
+    struct ShortMessage {
+        data: ShortBytes,
+        values: ShortVec<u32>,
+    }
+
+    assert_eq!(LongMessage::HEADER_SIZE, 20);
+    assert_eq!(ShortMessage::HEADER_SIZE, 10);
+    let long_message = LongMessage {
+        data: Bytes::from_static(b"Hello, World"),
+        values: vec![1, 2, 3],
+    };
+    let short_message = ShortMessage {
+        data: long_message.data.clone().into(),
+        values: long_message.values.clone().into(),
+    };
+    let long_buffer = long_message.encode_to_vec(0);
+    let short_buffer = short_message.encode_to_vec(0);
+    assert_eq!(long_buffer.len() - short_buffer.len(), 10);
+    let mut buffer_decoder = BufferDecoder::new(&short_buffer);
+    let mut decoded_message = ShortMessage::default();
+    ShortMessage::decode_body(&mut buffer_decoder, 0, &mut decoded_message);
+    assert_eq!(decoded_message, short_message);
+}
+
+#[test]
+#[should_panic(expected = "encoded buffer exceeds header profile limit")]
+fn test_short_headers_overflow() {
+    use crate::ShortBytes;
+    let value = ShortBytes(Bytes::from(vec![0u8; 0x10000]));
+    value.encode_to_vec(0);
+}
+
+#[test]
+fn test_fixed_vec() {
+    use crate::{FixedVec, FixedVecError};
+    let values = vec![1u32, 2, 3];
+    let encoded_buffer = values.encode_to_vec(0);
+    // decode dynamic vector into the bounded one
+    let mut buffer_decoder = BufferDecoder::new(&encoded_buffer);
+    let mut fixed_values = FixedVec::<u32, 4>::default();
+    FixedVec::try_decode_body(&mut buffer_decoder, 0, &mut fixed_values).unwrap();
+    assert_eq!(fixed_values.as_slice(), values.as_slice());
+    assert_eq!(fixed_values.encode_to_vec(0), encoded_buffer);
+    // not enough capacity
+    let mut fixed_values = FixedVec::<u32, 2>::default();
+    assert_eq!(
+        FixedVec::try_decode_body(&mut buffer_decoder, 0, &mut fixed_values),
+        Err(FixedVecError::Overflow {
+            capacity: 2,
+            length: 3
+        })
+    );
+    assert!(fixed_values.is_empty());
+    assert_eq!(
+        FixedVec::<u32, 2>::try_from(values.as_slice()),
+        Err(FixedVecError::Overflow {
+            capacity: 2,
+            length: 3
+        })
+    );
+}
+
+#[test]
+fn test_malicious_offsets() {
+    use crate::CodecError;
+    use byteorder::{ByteOrder, LittleEndian};
+    let values = vec![vec![1u32, 2], vec![3u32]];
+    let buffer = values.encode_to_vec(0);
+    let mut result = Vec::<Vec<u32>>::default();
+    Vec::<Vec<u32>>::try_decode_strict(&buffer, &mut result).unwrap();
+    assert_eq!(result, values);
+    // data out of the buffer
+    let mut malformed = buffer.clone();
+    LittleEndian::write_u32(&mut malformed[8..], 0x1000);
+    assert_eq!(
+        Vec::<Vec<u32>>::try_decode(&malformed, &mut result),
+        Err(CodecError::OutOfBounds {
+            offset: 12,
+            length: 0x1000
+        })
+    );
+    // data overlaps the header
+    let mut malformed = buffer.clone();
+    LittleEndian::write_u32(&mut malformed[4..], 0);
+    assert_eq!(
+        Vec::<Vec<u32>>::try_decode(&malformed, &mut result),
+        Err(CodecError::OverlapsHeader {
+            offset: 0,
+            length: 36
+        })
+    );
+    // huge number of elements
+    let mut malformed = buffer.clone();
+    LittleEndian::write_u32(&mut malformed[0..], u32::MAX);
+    assert!(Vec::<Vec<u32>>::try_decode(&malformed, &mut result).is_err());
+    // two fields point to the same data
+    let tuple = (Bytes::from_static(b"abcd"), Bytes::from_static(b"efgh"));
+    let mut malformed = tuple.encode_to_vec(0);
+    LittleEndian::write_u32(&mut malformed[8..], 18);
+    LittleEndian::write_u32(&mut malformed[12..], 2);
+    let mut result = <(Bytes, Bytes)>::default();
+    assert_eq!(
+        <(Bytes, Bytes)>::try_decode(&malformed, &mut result),
+        Err(CodecError::AliasedField {
+            offset: 18,
+            length: 2
+        })
+    );
+    // trailing bytes are accepted in the lenient mode only
+    let mut buffer = tuple.encode_to_vec(0);
+    buffer.push(0);
+    assert_eq!(<(Bytes, Bytes)>::try_decode(&buffer, &mut result), Ok(()));
+    assert_eq!(
+        <(Bytes, Bytes)>::try_decode_strict(&buffer, &mut result),
+        Err(CodecError::NonCanonical)
+    );
+    // non-canonical boolean flag
+    assert_eq!(
+        bool::try_decode_strict(&[2], &mut false),
+        Err(CodecError::NonCanonical)
+    );
+}
+
+#[test]
+fn test_codec_field_attributes() {
+    use fluentbase_codec_derive::Codec;
+
+    #[derive(Default, Debug, Codec, PartialEq)]
+    struct OldLayout {
+        a: u32,
+        b: Bytes,
+    }
+    #[derive(Default, Debug, Codec, PartialEq)]
+    struct NewLayout {
+        #[codec(index = 1)]
+        b: Bytes,
+        #[codec(skip)]
+        cache: u64,
+        #[codec(index = 0, rename = "first")]
+        a: u32,
+        #[codec(index = 2, default)]
+        c: u64,
+    }
+
+    assert_eq!(NewLayout::HEADER_SIZE, 4 + 8 + 8);
+    assert_eq!(<NewLayout as INewLayout>::First::FIELD_OFFSET, 0);
+    assert_eq!(<NewLayout as INewLayout>::B::FIELD_OFFSET, 4);
+    assert_eq!(<NewLayout as INewLayout>::C::FIELD_OFFSET, 12);
+    let old_value = OldLayout {
+        a: 7,
+        b: Bytes::from_static(b"Hello, World"),
+    };
+    // new layout decodes buffers of the old one
+    let mut buffer_decoder = BufferDecoder::new(&old_value.encode_to_vec(0));
+    let mut new_value = NewLayout {
+        cache: 100,
+        c: 100,
+        ..Default::default()
+    };
+    NewLayout::decode_body(&mut buffer_decoder, 0, &mut new_value);
+    assert_eq!(
+        new_value,
+        NewLayout {
+            b: old_value.b.clone(),
+            cache: 0,
+            a: 7,
+            c: 0,
+        }
+    );
+    // skipped fields aren't encoded
+    new_value.cache = 100;
+    new_value.c = 5;
+    let buffer = new_value.encode_to_vec(0);
+    let mut decoded_value = NewLayout::default();
+    NewLayout::try_decode_strict(&buffer, &mut decoded_value).unwrap();
+    assert_eq!(decoded_value.cache, 0);
+    assert_eq!(decoded_value.c, 5);
+}
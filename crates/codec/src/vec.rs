@@ -1,4 +1,9 @@
-use crate::{buffer::WritableBuffer, BufferDecoder, BufferEncoder, Encoder};
+use crate::{
+    buffer::{HeaderProfile, LongHeaders, WritableBuffer},
+    BufferDecoder,
+    BufferEncoder,
+    Encoder,
+};
 use alloc::vec::Vec;
 
 ///
@@ -14,15 +19,10 @@ use alloc::vec::Vec;
 /// it helps to reduce empty vector size from 12 to 4 bytes.
 impl<T: Default + Sized + Encoder<T>> Encoder<Vec<T>> for Vec<T> {
     // u32: length + values (bytes)
-    const HEADER_SIZE: usize = core::mem::size_of::<u32>() * 3;
+    const HEADER_SIZE: usize = LongHeaders::FIELD_SIZE * 3;
 
     fn encode<W: WritableBuffer>(&self, encoder: &mut W, field_offset: usize) {
-        encoder.write_u32(field_offset, self.len() as u32);
-        let mut value_encoder = BufferEncoder::new(T::HEADER_SIZE * self.len(), None);
-        for (i, obj) in self.iter().enumerate() {
-            obj.encode(&mut value_encoder, T::HEADER_SIZE * i);
-        }
-        encoder.write_bytes(field_offset + 4, value_encoder.finalize().as_slice());
+        encode_vec::<T, LongHeaders, W>(self, encoder, field_offset);
     }
 
     fn decode_header(
@@ -30,28 +30,62 @@ impl<T: Default + Sized + Encoder<T>> Encoder<Vec<T>> for Vec<T> {
         field_offset: usize,
         result: &mut Vec<T>,
     ) -> (usize, usize) {
-        let count = decoder.read_u32(field_offset) as usize;
-        if count > result.capacity() {
-            result.reserve(count - result.capacity());
-        }
-        let (offset, length) = decoder.read_bytes_header(field_offset + 4);
-        (offset, length)
+        decode_vec_header::<T, LongHeaders>(decoder, field_offset, result)
     }
 
     fn decode_body(decoder: &mut BufferDecoder, field_offset: usize, result: &mut Vec<T>) {
-        let input_len = decoder.read_u32(field_offset) as usize;
-        if input_len == 0 {
-            result.clear();
-            return;
-        }
-        let input_bytes = decoder.read_bytes(field_offset + 4);
-        let mut value_decoder = BufferDecoder::new(input_bytes);
-        *result = (0..input_len)
-            .map(|i| {
-                let mut result = T::default();
-                T::decode_body(&mut value_decoder, T::HEADER_SIZE * i, &mut result);
-                result
-            })
-            .collect()
+        decode_vec_body::<T, LongHeaders>(decoder, field_offset, result);
     }
 }
+
+pub(crate) fn encode_vec<T: Encoder<T>, P: HeaderProfile, W: WritableBuffer>(
+    values: &[T],
+    encoder: &mut W,
+    field_offset: usize,
+) {
+    P::write_field(encoder, field_offset, values.len());
+    let mut value_encoder = BufferEncoder::new(T::HEADER_SIZE * values.len(), None);
+    for (i, obj) in values.iter().enumerate() {
+        obj.encode(&mut value_encoder, T::HEADER_SIZE * i);
+    }
+    encoder.write_bytes_with::<P>(
+        field_offset + P::FIELD_SIZE,
+        value_encoder.finalize().as_slice(),
+    );
+}
+
+pub(crate) fn decode_vec_header<T: Encoder<T>, P: HeaderProfile>(
+    decoder: &mut BufferDecoder,
+    field_offset: usize,
+    result: &mut Vec<T>,
+) -> (usize, usize) {
+    let (offset, length) = decoder.read_bytes_header_with::<P>(field_offset + P::FIELD_SIZE);
+    let count = decoder.check_count(P::read_field(decoder, field_offset), T::HEADER_SIZE, length);
+    if count > result.capacity() {
+        result.reserve(count - result.capacity());
+    }
+    (offset, length)
+}
+
+pub(crate) fn decode_vec_body<T: Default + Encoder<T>, P: HeaderProfile>(
+    decoder: &mut BufferDecoder,
+    field_offset: usize,
+    result: &mut Vec<T>,
+) {
+    let input_len = P::read_field(decoder, field_offset);
+    if input_len == 0 {
+        result.clear();
+        return;
+    }
+    let input_bytes = decoder.read_bytes_with::<P>(field_offset + P::FIELD_SIZE);
+    let input_len = decoder.check_count(input_len, T::HEADER_SIZE, input_bytes.len());
+    let mut value_decoder = decoder.nested(input_bytes, T::HEADER_SIZE * input_len);
+    *result = (0..input_len)
+        .map(|i| {
+            let mut result = T::default();
+            T::decode_body(&mut value_decoder, T::HEADER_SIZE * i, &mut result);
+            result
+        })
+        .collect();
+    decoder.merge(value_decoder);
+}
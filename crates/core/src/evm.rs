@@ -11,6 +11,8 @@ pub mod log1;
 pub mod log2;
 pub mod log3;
 pub mod log4;
+#[cfg(feature = "evm-precompiles")]
+pub mod precompile;
 pub mod r#return;
 pub mod selfbalance;
 pub mod sload;
@@ -68,8 +68,14 @@ pub fn _evm_call<CR: ContextReader, AM: AccountManager>(
         am.write_account(&caller_account);
     }
 
-    // check is it precompile
-    if let Some(result) = am.precompile(&input.callee, &input.input, input.gas_limit) {
+    // check is it precompile, standard EVM precompiles are served by the shim if the account
+    // manager doesn't provide them
+    let result = am.precompile(&input.callee, &input.input, input.gas_limit);
+    #[cfg(feature = "evm-precompiles")]
+    let result = result.or_else(|| {
+        crate::evm::precompile::_evm_precompile(&input.callee, &input.input, input.gas_limit)
+    });
+    if let Some(result) = result {
         if ExitCode::from(result.exit_code).is_ok() {
             am.commit();
         } else {
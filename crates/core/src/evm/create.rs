@@ -52,7 +52,7 @@ pub fn _evm_create<CR: ContextReader, AM: AccountManager>(
 
     // check init max code size for EIP-3860 and charge 2 gas per word
     if input.bytecode.len() > MAX_INITCODE_SIZE {
-        return EvmCreateMethodOutput::from_exit_code(ExitCode::ContractSizeLimit)
+        return EvmCreateMethodOutput::from_exit_code(ExitCode::InitCodeSizeLimit)
             .with_gas(input.gas_limit, 0);
     }
 
@@ -0,0 +1,74 @@
+use fluentbase_sdk::types::EvmCallMethodOutput;
+use fluentbase_types::{
+    contracts::{PRECOMPILE_KZG_POINT_EVALUATION, PRECOMPILE_SECP256K1_ECRECOVER},
+    Address,
+    Bytes,
+    ExitCode,
+};
+use revm_precompile::{PrecompileError, PrecompileErrors, PrecompileResult};
+
+/// Returns whether the address is one of the standard EVM precompiles (0x01-0x0a)
+pub fn is_evm_precompile(address: &Address) -> bool {
+    address >= &PRECOMPILE_SECP256K1_ECRECOVER && address <= &PRECOMPILE_KZG_POINT_EVALUATION
+}
+
+/// Executes the standard EVM precompile with the same implementations as the precompile
+/// contracts, so EVM contracts executed by the interpreter can call precompiles by their
+/// Ethereum addresses. Returns `None` if the address isn't a precompile.
+///
+/// Failed precompile consumes all the gas (the same as in EVM), KZG point evaluation isn't
+/// supported and fails with `NotSupportedCall`.
+pub fn _evm_precompile(
+    address: &Address,
+    input: &Bytes,
+    gas_limit: u64,
+) -> Option<EvmCallMethodOutput> {
+    if !is_evm_precompile(address) {
+        return None;
+    }
+    // precompiles are numbered by the last byte of the address
+    let result: PrecompileResult = match address[19] {
+        0x01 => revm_precompile::secp256k1::ec_recover_run(input, gas_limit),
+        0x02 => revm_precompile::hash::sha256_run(input, gas_limit),
+        0x03 => revm_precompile::hash::ripemd160_run(input, gas_limit),
+        0x04 => revm_precompile::identity::identity_run(input, gas_limit),
+        0x05 => revm_precompile::modexp::berlin_run(input, gas_limit),
+        0x06 => revm_precompile::bn128::run_add(
+            input,
+            revm_precompile::bn128::add::ISTANBUL_ADD_GAS_COST,
+            gas_limit,
+        ),
+        0x07 => revm_precompile::bn128::run_mul(
+            input,
+            revm_precompile::bn128::mul::ISTANBUL_MUL_GAS_COST,
+            gas_limit,
+        ),
+        0x08 => revm_precompile::bn128::run_pair(
+            input,
+            revm_precompile::bn128::pair::ISTANBUL_PAIR_PER_POINT,
+            revm_precompile::bn128::pair::ISTANBUL_PAIR_BASE,
+            gas_limit,
+        ),
+        0x09 => revm_precompile::blake2::run(input, gas_limit),
+        // KZG point evaluation (0x0a)
+        _ => {
+            return Some(EvmCallMethodOutput::from_exit_code(
+                ExitCode::NotSupportedCall,
+            ))
+        }
+    };
+    Some(match result {
+        Ok(output) => EvmCallMethodOutput::from_exit_code(ExitCode::Ok)
+            .with_output(output.bytes)
+            .with_gas(gas_limit - output.gas_used, 0),
+        Err(err) => EvmCallMethodOutput::from_exit_code(exit_code_from_precompile_error(err)),
+    })
+}
+
+fn exit_code_from_precompile_error(err: PrecompileErrors) -> ExitCode {
+    match err {
+        PrecompileErrors::Error(PrecompileError::OutOfGas) => ExitCode::OutOfGas,
+        PrecompileErrors::Error(_) => ExitCode::PrecompileError,
+        PrecompileErrors::Fatal { .. } => ExitCode::FatalExternalError,
+    }
+}
@@ -122,6 +122,7 @@ fn contract_context_from_call_inputs<CR: ContextReader>(
         block_prevrandao: cr.block_prevrandao(),
         block_gas_limit: cr.block_gas_limit(),
         block_base_fee: cr.block_base_fee(),
+        block_blob_base_fee: cr.block_blob_base_fee(),
         tx_gas_limit: cr.tx_gas_limit(),
         tx_nonce: cr.tx_nonce(),
         tx_gas_price: cr.tx_gas_price(),
@@ -151,6 +152,7 @@ fn contract_context_from_create_inputs<CR: ContextReader>(
         block_prevrandao: cr.block_prevrandao(),
         block_gas_limit: cr.block_gas_limit(),
         block_base_fee: cr.block_base_fee(),
+        block_blob_base_fee: cr.block_blob_base_fee(),
         tx_gas_limit: cr.tx_gas_limit(),
         tx_nonce: cr.tx_nonce(),
         tx_gas_price: cr.tx_gas_price(),
@@ -342,7 +344,7 @@ pub(crate) fn exec_evm_bytecode<CR: ContextReader, AM: AccountManager>(
 pub fn evm_error_from_exit_code(exit_code: ExitCode) -> InstructionResult {
     match exit_code {
         ExitCode::Ok => InstructionResult::Stop,
-        ExitCode::Panic => InstructionResult::Revert,
+        ExitCode::Revert | ExitCode::Panic => InstructionResult::Revert,
         ExitCode::CallDepthOverflow => InstructionResult::CallTooDeep,
         ExitCode::InsufficientBalance => InstructionResult::OutOfFunds,
         ExitCode::OutOfGas => InstructionResult::OutOfGas,
@@ -359,6 +361,7 @@ pub fn evm_error_from_exit_code(exit_code: ExitCode) -> InstructionResult {
         ExitCode::PrecompileError => InstructionResult::PrecompileError,
         ExitCode::NonceOverflow => InstructionResult::NonceOverflow,
         ExitCode::ContractSizeLimit => InstructionResult::CreateContractSizeLimit,
+        ExitCode::InitCodeSizeLimit => InstructionResult::CreateInitCodeSizeLimit,
         ExitCode::CreateContractStartingWithEF => InstructionResult::CreateContractStartingWithEF,
         ExitCode::FatalExternalError => InstructionResult::FatalExternalError,
         // ExitCode::ReturnContract => InstructionResult::ReturnContract,
@@ -377,7 +380,7 @@ pub fn exit_code_from_evm_error(evm_error: InstructionResult) -> ExitCode {
         | InstructionResult::Return
         | InstructionResult::SelfDestruct
         | InstructionResult::CallOrCreate => ExitCode::Ok,
-        InstructionResult::Revert => ExitCode::Panic,
+        InstructionResult::Revert => ExitCode::Revert,
         InstructionResult::CallTooDeep => ExitCode::CallDepthOverflow,
         InstructionResult::OutOfFunds => ExitCode::InsufficientBalance,
         InstructionResult::OutOfGas
@@ -398,9 +401,8 @@ pub fn exit_code_from_evm_error(evm_error: InstructionResult) -> ExitCode {
         InstructionResult::OverflowPayment => ExitCode::OverflowPayment,
         InstructionResult::PrecompileError => ExitCode::PrecompileError,
         InstructionResult::NonceOverflow => ExitCode::NonceOverflow,
-        InstructionResult::CreateContractSizeLimit | InstructionResult::CreateInitCodeSizeLimit => {
-            ExitCode::ContractSizeLimit
-        }
+        InstructionResult::CreateContractSizeLimit => ExitCode::ContractSizeLimit,
+        InstructionResult::CreateInitCodeSizeLimit => ExitCode::InitCodeSizeLimit,
         InstructionResult::CreateContractStartingWithEF => ExitCode::CreateContractStartingWithEF,
         InstructionResult::FatalExternalError => ExitCode::FatalExternalError,
         // InstructionResult::ReturnContract => ExitCode::ReturnContract,
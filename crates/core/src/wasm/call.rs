@@ -6,7 +6,7 @@ use fluentbase_sdk::{
     ContextReader,
     ContractInput,
 };
-use fluentbase_types::{ExitCode, STATE_MAIN, U256};
+use fluentbase_types::{ExitCode, Gas, STATE_MAIN, U256};
 
 pub fn _wasm_call<CR: ContextReader, AM: AccountManager>(
     cr: &CR,
@@ -36,7 +36,7 @@ pub fn _wasm_call<CR: ContextReader, AM: AccountManager>(
     // parse callee address
     let (callee_account, _) = am.account(input.callee);
 
-    let mut gas_limit = input.gas_limit as u32;
+    let mut gas_limit = Gas::new(input.gas_limit).saturating_u32();
 
     let mut context = ContractInput::clone_from_cr(cr);
     context.contract_gas_limit = gas_limit as u64;
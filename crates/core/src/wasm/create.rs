@@ -1,6 +1,7 @@
 use crate::{debug_log, helpers::wasm2rwasm};
 use fluentbase_codec::Encoder;
 use fluentbase_sdk::{
+    alias::register_alias,
     types::{WasmCreateMethodInput, WasmCreateMethodOutput},
     Account,
     AccountManager,
@@ -9,7 +10,7 @@ use fluentbase_sdk::{
     LowLevelSDK,
     SharedAPI,
 };
-use fluentbase_types::{Bytes, ExitCode, B256, STATE_DEPLOY};
+use fluentbase_types::{Bytes, ExitCode, Gas, B256, STATE_DEPLOY};
 use revm_primitives::WASM_MAX_CODE_SIZE;
 
 pub fn _wasm_create<CR: ContextReader, AM: AccountManager>(
@@ -35,9 +36,9 @@ pub fn _wasm_create<CR: ContextReader, AM: AccountManager>(
     if input.bytecode.len() > WASM_MAX_CODE_SIZE {
         debug_log!(
             "_wasm_create return: Err: exit_code: {}",
-            ExitCode::ContractSizeLimit
+            ExitCode::InitCodeSizeLimit
         );
-        return WasmCreateMethodOutput::from_exit_code(ExitCode::ContractSizeLimit);
+        return WasmCreateMethodOutput::from_exit_code(ExitCode::InitCodeSizeLimit);
     }
 
     let mut source_code_hash: B256 = B256::ZERO;
@@ -111,13 +112,16 @@ pub fn _wasm_create<CR: ContextReader, AM: AccountManager>(
     // write contract to the trie
     contract_account.update_bytecode(am, &input.bytecode, None, &rwasm_bytecode.into(), None);
 
+    // link the contract with its EVM-visible alias
+    register_alias(am, &contract_account.address);
+
     let mut context = ContractInput::clone_from_cr(cr);
     context.contract_value = input.value;
     context.contract_gas_limit = input.gas_limit;
     context.contract_address = contract_account.address;
     let contract_context = context.encode_to_vec(0);
 
-    let mut gas_limit = input.gas_limit as u32;
+    let mut gas_limit = Gas::new(input.gas_limit).saturating_u32();
     let (_, exit_code) = am.exec_hash(
         contract_account.rwasm_code_hash.as_ptr(),
         &contract_context,
@@ -1,7 +1,5 @@
-use crate::{ChainConfig, Genesis, GenesisAccount};
-use fluentbase_poseidon::poseidon_hash;
+use crate::{system::SystemContractRegistry, ChainConfig, Genesis, GenesisAccount};
 use fluentbase_types::{address, b256, contracts::PRECOMPILE_EVM, Address, Bytes, B256, U256};
-use revm_primitives::keccak256;
 use std::collections::BTreeMap;
 
 pub fn devnet_chain_config() -> ChainConfig {
@@ -91,26 +89,17 @@ pub fn devnet_genesis() -> Genesis {
         initial_balance!("8626f6940E2eb28930eFb4CeF49B2d1F2C9C1199"),
     ]);
 
+    let mut system_contracts = SystemContractRegistry::new();
     macro_rules! enable_rwasm_contract {
         ($addr:ident, $file_path:literal) => {{
             use std::io::Write;
             let bytecode = Bytes::from(include_bytes!($file_path));
             print!("creating genesis account (0x{})... ", hex::encode($addr));
             std::io::stdout().flush().unwrap();
-            let poseidon_hash = poseidon_hash(&bytecode);
-            let keccak_hash = keccak256(&bytecode);
-            println!("{}", hex::encode(poseidon_hash));
-            alloc.insert(
-                $addr,
-                GenesisAccount {
-                    code: Some(bytecode),
-                    storage: Some(BTreeMap::from([
-                        (POSEIDON_HASH_KEY, poseidon_hash.into()),
-                        (KECCAK_HASH_KEY, keccak_hash.into()),
-                    ])),
-                    ..Default::default()
-                },
-            );
+            let contract = system_contracts
+                .install($addr, bytecode, None)
+                .expect("failed to install system contract");
+            println!("{}", hex::encode(contract.rwasm_code_hash));
         }};
     }
     enable_rwasm_contract!(
@@ -149,6 +138,7 @@ pub fn devnet_genesis() -> Genesis {
     //     EXAMPLE_GREETING_ADDRESS,
     //     "../../../examples/greeting/lib.rwasm"
     // );
+    system_contracts.apply_to_genesis(&mut alloc);
     Genesis {
         config: devnet_chain_config(),
         nonce: 0,
@@ -0,0 +1,368 @@
+use crate::{
+    devnet::{KECCAK_HASH_KEY, POSEIDON_HASH_KEY},
+    Genesis,
+    GenesisAccount,
+};
+use fluentbase_poseidon::poseidon_hash;
+use fluentbase_types::{Address, Bytes, SystemContractView, B256, F254};
+use revm_primitives::keccak256;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemContract {
+    pub address: Address,
+    pub bytecode: Bytes,
+    pub rwasm_code_hash: F254,
+    pub source_code_hash: B256,
+    /// Block number since this version of the contract is active (zero for genesis)
+    pub activation_block: u64,
+}
+
+impl SystemContract {
+    pub fn new(address: Address, bytecode: Bytes, activation_block: u64) -> Self {
+        let rwasm_code_hash = F254::from(poseidon_hash(&bytecode));
+        let source_code_hash = keccak256(&bytecode);
+        Self {
+            address,
+            bytecode,
+            rwasm_code_hash,
+            source_code_hash,
+            activation_block,
+        }
+    }
+
+    pub fn to_genesis_account(&self) -> GenesisAccount {
+        GenesisAccount {
+            code: Some(self.bytecode.clone()),
+            storage: Some(BTreeMap::from([
+                (POSEIDON_HASH_KEY, self.rwasm_code_hash),
+                (KECCAK_HASH_KEY, self.source_code_hash),
+            ])),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SystemContractError {
+    CodeHashMismatch { expected: F254, actual: F254 },
+    AlreadyInstalled(Address),
+    NotInstalled(Address),
+    InvalidActivationBlock(Address),
+    MissingCode(Address),
+}
+
+/// Registry of system contracts installed at fixed addresses, it keeps every version of the
+/// contract to let node resolve active code for any block.
+#[derive(Debug, Clone, Default)]
+pub struct SystemContractRegistry {
+    contracts: BTreeMap<Address, Vec<SystemContract>>,
+}
+
+impl SystemContractRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn verify_code_hash(
+        contract: &SystemContract,
+        expected_hash: Option<F254>,
+    ) -> Result<(), SystemContractError> {
+        match expected_hash {
+            Some(expected) if expected != contract.rwasm_code_hash => {
+                Err(SystemContractError::CodeHashMismatch {
+                    expected,
+                    actual: contract.rwasm_code_hash,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Installs system contract at genesis, if expected hash is specified then bytecode's
+    /// poseidon hash must match it
+    pub fn install(
+        &mut self,
+        address: Address,
+        bytecode: Bytes,
+        expected_hash: Option<F254>,
+    ) -> Result<&SystemContract, SystemContractError> {
+        if self.contracts.contains_key(&address) {
+            return Err(SystemContractError::AlreadyInstalled(address));
+        }
+        let contract = SystemContract::new(address, bytecode, 0);
+        Self::verify_code_hash(&contract, expected_hash)?;
+        let versions = self.contracts.entry(address).or_default();
+        versions.push(contract);
+        Ok(versions.last().unwrap())
+    }
+
+    /// Schedules upgrade of already installed system contract at the specified block
+    pub fn upgrade(
+        &mut self,
+        address: Address,
+        bytecode: Bytes,
+        expected_hash: Option<F254>,
+        activation_block: u64,
+    ) -> Result<&SystemContract, SystemContractError> {
+        let versions = self
+            .contracts
+            .get_mut(&address)
+            .ok_or(SystemContractError::NotInstalled(address))?;
+        if versions
+            .last()
+            .map(|v| v.activation_block >= activation_block)
+            .unwrap_or_default()
+        {
+            return Err(SystemContractError::InvalidActivationBlock(address));
+        }
+        let contract = SystemContract::new(address, bytecode, activation_block);
+        Self::verify_code_hash(&contract, expected_hash)?;
+        versions.push(contract);
+        Ok(versions.last().unwrap())
+    }
+
+    /// Returns version of the system contract that is active at the specified block
+    pub fn resolve(&self, address: &Address, block_number: u64) -> Option<&SystemContract> {
+        self.contracts.get(address).and_then(|versions| {
+            versions
+                .iter()
+                .rev()
+                .find(|v| v.activation_block <= block_number)
+        })
+    }
+
+    /// Returns the latest version of the system contract
+    pub fn latest(&self, address: &Address) -> Option<&SystemContract> {
+        self.contracts
+            .get(address)
+            .and_then(|versions| versions.last())
+    }
+
+    pub fn is_system_contract(&self, address: &Address) -> bool {
+        self.contracts.contains_key(address)
+    }
+
+    /// Finds system contract by its rWASM code hash (any version)
+    pub fn find_by_code_hash(&self, rwasm_code_hash: &F254) -> Option<&SystemContract> {
+        self.contracts
+            .values()
+            .flatten()
+            .find(|v| v.rwasm_code_hash == *rwasm_code_hash)
+    }
+
+    /// Returns all contracts that must be (re)written into the state at the specified block
+    pub fn upgrades_at(&self, block_number: u64) -> Vec<&SystemContract> {
+        self.contracts
+            .values()
+            .flatten()
+            .filter(|v| v.activation_block == block_number)
+            .collect()
+    }
+
+    pub fn addresses(&self) -> impl Iterator<Item = &Address> {
+        self.contracts.keys()
+    }
+
+    /// Returns rWASM bytecodes of all versions of all system contracts, a node can pass them into
+    /// the runtime warm-up to translate system modules at startup
+    pub fn bytecodes(&self) -> impl Iterator<Item = &Bytes> {
+        self.contracts.values().flatten().map(|v| &v.bytecode)
+    }
+
+    /// Writes genesis versions of all system contracts into genesis allocation
+    pub fn apply_to_genesis(&self, alloc: &mut BTreeMap<Address, GenesisAccount>) {
+        for contract in self.upgrades_at(0) {
+            alloc.insert(contract.address, contract.to_genesis_account());
+        }
+    }
+
+    /// Restores registry from the genesis file, only accounts at the specified addresses (usually
+    /// [`fluentbase_types::contracts::SYSTEM_CONTRACT_ADDRESSES`]) are treated as system
+    /// contracts, addresses that are absent in the genesis are skipped and listed accounts without
+    /// code or poseidon hash key are rejected
+    pub fn from_genesis(
+        genesis: &Genesis,
+        addresses: &[Address],
+    ) -> Result<Self, SystemContractError> {
+        let mut registry = Self::new();
+        for address in addresses {
+            let Some(account) = genesis.alloc.get(address) else {
+                continue;
+            };
+            let expected_hash = account
+                .storage
+                .as_ref()
+                .and_then(|storage| storage.get(&POSEIDON_HASH_KEY))
+                .cloned();
+            match (&account.code, expected_hash) {
+                (Some(bytecode), Some(expected_hash)) => {
+                    registry.install(*address, bytecode.clone(), Some(expected_hash))?;
+                }
+                _ => return Err(SystemContractError::MissingCode(*address)),
+            }
+        }
+        Ok(registry)
+    }
+}
+
+impl SystemContractView for SystemContractRegistry {
+    fn active_code_hash(&self, address: &Address, block_number: u64) -> Option<F254> {
+        self.resolve(address, block_number)
+            .map(|contract| contract.rwasm_code_hash)
+    }
+
+    fn is_system_contract(&self, address: &Address) -> bool {
+        self.contracts.contains_key(address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        system::{SystemContract, SystemContractError, SystemContractRegistry},
+        Genesis,
+    };
+    use fluentbase_types::{Address, Bytes, SystemContractView};
+    use std::collections::BTreeMap;
+
+    const SYSTEM_ADDRESS: Address = Address::repeat_byte(0x52);
+
+    fn bytecode(version: u8) -> Bytes {
+        Bytes::from(vec![0xef, 0x00, 0x52, version])
+    }
+
+    #[test]
+    fn test_code_hash_mismatch() {
+        let mut registry = SystemContractRegistry::new();
+        let expected = SystemContract::new(SYSTEM_ADDRESS, bytecode(1), 0).rwasm_code_hash;
+        let actual = SystemContract::new(SYSTEM_ADDRESS, bytecode(2), 0).rwasm_code_hash;
+        assert_eq!(
+            registry
+                .install(SYSTEM_ADDRESS, bytecode(2), Some(expected))
+                .err(),
+            Some(SystemContractError::CodeHashMismatch { expected, actual })
+        );
+        // failed installation doesn't register the contract
+        assert!(!registry.is_system_contract(&SYSTEM_ADDRESS));
+        registry
+            .install(SYSTEM_ADDRESS, bytecode(1), Some(expected))
+            .unwrap();
+        // upgrades are verified the same way
+        assert_eq!(
+            registry
+                .upgrade(SYSTEM_ADDRESS, bytecode(2), Some(expected), 10)
+                .err(),
+            Some(SystemContractError::CodeHashMismatch { expected, actual })
+        );
+        assert!(registry.upgrades_at(10).is_empty());
+    }
+
+    #[test]
+    fn test_upgrade_activation() {
+        let mut registry = SystemContractRegistry::new();
+        assert_eq!(
+            registry
+                .upgrade(SYSTEM_ADDRESS, bytecode(2), None, 10)
+                .err(),
+            Some(SystemContractError::NotInstalled(SYSTEM_ADDRESS))
+        );
+        registry.install(SYSTEM_ADDRESS, bytecode(1), None).unwrap();
+        assert_eq!(
+            registry.install(SYSTEM_ADDRESS, bytecode(1), None).err(),
+            Some(SystemContractError::AlreadyInstalled(SYSTEM_ADDRESS))
+        );
+        registry
+            .upgrade(SYSTEM_ADDRESS, bytecode(2), None, 10)
+            .unwrap();
+        // upgrades must be activated in order
+        for activation_block in [0, 5, 10] {
+            assert_eq!(
+                registry
+                    .upgrade(SYSTEM_ADDRESS, bytecode(3), None, activation_block)
+                    .err(),
+                Some(SystemContractError::InvalidActivationBlock(SYSTEM_ADDRESS))
+            );
+        }
+        registry
+            .upgrade(SYSTEM_ADDRESS, bytecode(3), None, 20)
+            .unwrap();
+        let resolve = |block_number: u64| {
+            registry
+                .resolve(&SYSTEM_ADDRESS, block_number)
+                .map(|v| v.bytecode.clone())
+        };
+        assert_eq!(resolve(0), Some(bytecode(1)));
+        assert_eq!(resolve(9), Some(bytecode(1)));
+        assert_eq!(resolve(10), Some(bytecode(2)));
+        assert_eq!(resolve(19), Some(bytecode(2)));
+        assert_eq!(resolve(20), Some(bytecode(3)));
+        assert_eq!(resolve(u64::MAX), Some(bytecode(3)));
+        assert_eq!(registry.resolve(&Address::ZERO, 0), None);
+        assert_eq!(
+            registry.latest(&SYSTEM_ADDRESS).map(|v| v.activation_block),
+            Some(20)
+        );
+        assert_eq!(registry.upgrades_at(10).len(), 1);
+        assert_eq!(registry.upgrades_at(15).len(), 0);
+        // the runtime sees the same versions through the read-only view
+        let view: &dyn SystemContractView = &registry;
+        assert_eq!(
+            view.active_code_hash(&SYSTEM_ADDRESS, 15),
+            registry
+                .resolve(&SYSTEM_ADDRESS, 15)
+                .map(|v| v.rwasm_code_hash)
+        );
+        assert!(view.is_system_contract(&SYSTEM_ADDRESS));
+        assert_eq!(view.active_code_hash(&Address::ZERO, 0), None);
+    }
+
+    #[test]
+    fn test_genesis_round_trip() {
+        let mut registry = SystemContractRegistry::new();
+        registry.install(SYSTEM_ADDRESS, bytecode(1), None).unwrap();
+        registry
+            .install(Address::repeat_byte(0x53), bytecode(2), None)
+            .unwrap();
+        // upgrades aren't a part of the genesis
+        registry
+            .upgrade(SYSTEM_ADDRESS, bytecode(3), None, 10)
+            .unwrap();
+        let mut alloc = BTreeMap::new();
+        registry.apply_to_genesis(&mut alloc);
+        // an account with the same layout outside the address list isn't a system contract
+        let user_contract = SystemContract::new(Address::repeat_byte(0x54), bytecode(5), 0);
+        alloc.insert(user_contract.address, user_contract.to_genesis_account());
+        let genesis = Genesis {
+            alloc,
+            ..Default::default()
+        };
+        let addresses = [SYSTEM_ADDRESS, Address::repeat_byte(0x53), Address::ZERO];
+        let restored = SystemContractRegistry::from_genesis(&genesis, &addresses).unwrap();
+        assert!(!restored.is_system_contract(&user_contract.address));
+        assert_eq!(
+            restored.addresses().collect::<Vec<_>>(),
+            registry.addresses().collect::<Vec<_>>()
+        );
+        for address in registry.addresses() {
+            assert_eq!(restored.latest(address), registry.resolve(address, 0));
+        }
+        assert_eq!(
+            restored.resolve(&SYSTEM_ADDRESS, 10).unwrap().bytecode,
+            bytecode(1)
+        );
+        // tampered code doesn't match the poseidon hash stored in the genesis
+        let mut genesis = genesis;
+        genesis.alloc.get_mut(&SYSTEM_ADDRESS).unwrap().code = Some(bytecode(4));
+        assert!(matches!(
+            SystemContractRegistry::from_genesis(&genesis, &addresses),
+            Err(SystemContractError::CodeHashMismatch { .. })
+        ));
+        // listed account must carry the code
+        genesis.alloc.get_mut(&SYSTEM_ADDRESS).unwrap().code = None;
+        assert_eq!(
+            SystemContractRegistry::from_genesis(&genesis, &addresses).err(),
+            Some(SystemContractError::MissingCode(SYSTEM_ADDRESS))
+        );
+    }
+}
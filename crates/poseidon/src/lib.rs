@@ -1,8 +1,20 @@
 use halo2curves::bn256::Fr;
 pub use poseidon::Poseidon;
 
+lazy_static::lazy_static! {
+    /// Computing round constants and MDS matrices takes much longer than hashing itself, so the
+    /// spec is computed once and every hasher is cloned from the template
+    static ref POSEIDON_TEMPLATE: Poseidon<Fr, 3, 2> = Poseidon::new(8, 56);
+}
+
+/// Returns fresh hasher with the spec used across the trie and host functions. Field arithmetic
+/// is backed by the x86_64 assembly with the `asm` feature (needs a CPU with BMI2 and ADX)
+pub fn poseidon_hasher() -> Poseidon<Fr, 3, 2> {
+    POSEIDON_TEMPLATE.clone()
+}
+
 pub fn poseidon_hash(data: &[u8]) -> [u8; 32] {
-    let mut hasher = Poseidon::<Fr, 3, 2>::new(8, 56);
+    let mut hasher = poseidon_hasher();
     const CHUNK_LEN: usize = 31;
     for chunk in data.chunks(CHUNK_LEN).into_iter() {
         let mut buffer32: [u8; 32] = [0u8; 32];
@@ -15,7 +27,7 @@ pub fn poseidon_hash(data: &[u8]) -> [u8; 32] {
 }
 
 pub fn hash_with_domain(arr: &[Fr], _domain: &Fr) -> Fr {
-    let mut hasher = Poseidon::<Fr, 3, 2>::new(8, 56);
+    let mut hasher = poseidon_hasher();
     hasher.update(arr);
     hasher.squeeze()
 }
@@ -50,6 +62,14 @@ mod poseidon_tests {
         assert_eq!(hash.as_slice(), expected.as_slice());
     }
 
+    #[test]
+    fn reused_template() {
+        // hashers are cloned from the same template, so the state mustn't leak between them
+        let data: Vec<u8> = From::from("hello world");
+        assert_eq!(poseidon_hash(&data), poseidon_hash(&data));
+        assert_ne!(poseidon_hash(&data), poseidon_hash(&[0u8; 0]));
+    }
+
     #[test]
     fn full_32b() {
         let data = vec![0xff; 32];
@@ -0,0 +1,156 @@
+use crate::{types::RuntimeError, ExecutionResult, Runtime, RuntimeContext};
+use fluentbase_types::{Address, IJournaledTrie, B256, U256};
+use hashbrown::HashMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, RwLock},
+};
+
+/// EIP-2930 access list item
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<B256>,
+}
+
+pub type AccessList = Vec<AccessListItem>;
+
+#[derive(Default)]
+struct AccessListRecorderInner {
+    /// Poseidon hash inputs, we need them to recover address and slot from the storage key
+    hashes: HashMap<[u8; 32], ([u8; 32], [u8; 32])>,
+    touched_keys: Vec<[u8; 32]>,
+}
+
+/// Records all trie keys touched during the execution, it's shared between nested calls, so
+/// the result contains accesses made by the whole call tree.
+#[derive(Clone, Default)]
+pub struct AccessListRecorder {
+    inner: Arc<RwLock<AccessListRecorderInner>>,
+}
+
+impl AccessListRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn record_hash(&self, fa: &[u8], fb: &[u8], output: &[u8; 32]) {
+        let (Ok(fa), Ok(fb)) = (fa.try_into(), fb.try_into()) else {
+            return;
+        };
+        self.inner.write().unwrap().hashes.insert(*output, (fa, fb));
+    }
+
+    pub(crate) fn record_key(&self, key: &[u8]) {
+        let Ok(key) = key.try_into() else {
+            return;
+        };
+        self.inner.write().unwrap().touched_keys.push(key);
+    }
+
+    /// Storage key is computed as `p(address, p(slot_0, slot_1))` where address is padded to
+    /// 32 bytes (with 11 bytes offset) and slot is split into two 16-byte halves
+    fn resolve_storage_key(
+        hashes: &HashMap<[u8; 32], ([u8; 32], [u8; 32])>,
+        key: &[u8; 32],
+    ) -> Option<(Address, B256)> {
+        let (address32, slot_hash) = hashes.get(key)?;
+        let (slot0, slot1) = hashes.get(slot_hash)?;
+        if address32[..11].iter().any(|v| *v != 0) || address32[31] != 0 {
+            return None;
+        }
+        let mut slot32 = [0u8; 32];
+        slot32[..16].copy_from_slice(&slot0[..16]);
+        slot32[16..].copy_from_slice(&slot1[..16]);
+        Some((
+            Address::from_slice(&address32[11..31]),
+            B256::from(U256::from_le_bytes(slot32)),
+        ))
+    }
+
+    /// Resolves the storage key into the address and the slot, the key must be computed during
+    /// the recorded execution
+    pub(crate) fn resolve_key(&self, key: &[u8; 32]) -> Option<(Address, B256)> {
+        Self::resolve_storage_key(&self.inner.read().unwrap().hashes, key)
+    }
+
+    /// Returns access list sorted by addresses and storage keys, keys that can't be resolved
+    /// (like preimages or custom keys) are ignored
+    pub fn access_list(&self) -> AccessList {
+        let inner = self.inner.read().unwrap();
+        let mut result = BTreeMap::<Address, BTreeSet<B256>>::new();
+        for key in inner.touched_keys.iter() {
+            if let Some((address, slot)) = Self::resolve_storage_key(&inner.hashes, key) {
+                result.entry(address).or_default().insert(slot);
+            } else if key[..12].iter().all(|v| *v == 0) {
+                // account keys are addresses padded to 32 bytes
+                result.entry(Address::from_slice(&key[12..])).or_default();
+            }
+        }
+        result
+            .into_iter()
+            .map(|(address, storage_keys)| AccessListItem {
+                address,
+                storage_keys: storage_keys.into_iter().collect(),
+            })
+            .collect()
+    }
+}
+
+impl<DB: IJournaledTrie> Runtime<DB> {
+    /// Simulates execution of the context and returns every address and storage slot touched
+    /// during the execution, all state changes are rolled back
+    pub fn create_access_list(
+        runtime_context: RuntimeContext<DB>,
+    ) -> Result<(ExecutionResult, AccessList), RuntimeError> {
+        let recorder = AccessListRecorder::new();
+        let checkpoint = runtime_context.jzkt.as_ref().map(|jzkt| jzkt.checkpoint());
+        let mut runtime = Self::new(runtime_context.with_access_list_recorder(recorder.clone()));
+        let execution_result = runtime.call();
+        if let (Some(jzkt), Some(checkpoint)) = (runtime.data().jzkt.as_ref(), checkpoint) {
+            jzkt.rollback(checkpoint);
+        }
+        Ok((execution_result?, recorder.access_list()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{access_list::AccessListRecorder, instruction::poseidon_hash::SyscallPoseidonHash};
+    use fluentbase_types::{address, b256, Address, B256, U256};
+
+    const DOMAIN: [u8; 32] =
+        b256!("0000000000000000000000000000000000000000000000010000000000000000").0;
+
+    fn hash(recorder: &AccessListRecorder, fa: &[u8; 32], fb: &[u8; 32]) -> [u8; 32] {
+        let output = SyscallPoseidonHash::fn_impl(fa, fb, &DOMAIN).unwrap();
+        recorder.record_hash(fa, fb, &output);
+        output
+    }
+
+    #[test]
+    fn test_access_list_resolves_storage_keys() {
+        let recorder = AccessListRecorder::new();
+        let contract = address!("0000000000000000000000000000000000005210");
+        let caller = address!("1231238908230948230948209348203984029834");
+        let slot = U256::from(7);
+        let slot32 = slot.to_le_bytes::<32>();
+        let (mut slot0, mut slot1) = ([0u8; 32], [0u8; 32]);
+        slot0[..16].copy_from_slice(&slot32[..16]);
+        slot1[..16].copy_from_slice(&slot32[16..]);
+        let mut address32 = [0u8; 32];
+        address32[11..31].copy_from_slice(contract.as_slice());
+        let slot_hash = hash(&recorder, &slot0, &slot1);
+        let storage_key = hash(&recorder, &address32, &slot_hash);
+        recorder.record_key(caller.into_word().as_slice());
+        recorder.record_key(&storage_key);
+        recorder.record_key(&storage_key);
+        let access_list = recorder.access_list();
+        assert_eq!(access_list.len(), 2);
+        let contract_item = access_list.iter().find(|v| v.address == contract).unwrap();
+        assert_eq!(contract_item.storage_keys, vec![B256::from(slot)]);
+        let caller_item = access_list.iter().find(|v| v.address == caller).unwrap();
+        assert!(caller_item.storage_keys.is_empty());
+        assert_ne!(caller_item.address, Address::ZERO);
+    }
+}
@@ -0,0 +1,156 @@
+use std::sync::{Arc, RwLock};
+
+/// Max number of free buffers kept by the arena, the rest is dropped on release
+pub const MAX_POOLED_BUFFERS: usize = 64;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArenaStats {
+    /// Buffers allocated from the heap
+    pub allocations: u64,
+    /// Buffers served from the pool without heap allocation
+    pub reuses: u64,
+    /// Total capacity of the heap-allocated buffers
+    pub allocated_bytes: u64,
+    /// Max number of buffers taken from the arena at the same time
+    pub peak_buffers_in_use: usize,
+}
+
+#[derive(Default)]
+struct BufferArenaInner {
+    free: Vec<Vec<u8>>,
+    buffers_in_use: usize,
+    stats: ArenaStats,
+}
+
+/// Pool of byte buffers for call inputs, outputs, return data and syscall scratch data, it's
+/// shared between nested calls and reset before every top-level call, so buffers released by
+/// one syscall are reused by the next ones instead of allocating short-lived vectors.
+#[derive(Clone, Default)]
+pub struct BufferArena {
+    inner: Arc<RwLock<BufferArenaInner>>,
+}
+
+impl BufferArena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns an empty buffer with at least `capacity` bytes of capacity
+    pub fn alloc(&self, capacity: usize) -> Vec<u8> {
+        let mut inner = self.inner.write().unwrap();
+        inner.buffers_in_use += 1;
+        inner.stats.peak_buffers_in_use = inner.stats.peak_buffers_in_use.max(inner.buffers_in_use);
+        // the smallest free buffer that fits
+        let best_fit = inner
+            .free
+            .iter()
+            .enumerate()
+            .filter(|(_, buffer)| buffer.capacity() >= capacity)
+            .min_by_key(|(_, buffer)| buffer.capacity())
+            .map(|(i, _)| i);
+        match best_fit {
+            Some(i) => {
+                inner.stats.reuses += 1;
+                inner.free.swap_remove(i)
+            }
+            None => {
+                inner.stats.allocations += 1;
+                inner.stats.allocated_bytes += capacity as u64;
+                Vec::with_capacity(capacity)
+            }
+        }
+    }
+
+    /// Returns a buffer filled with the data
+    pub fn alloc_from(&self, data: &[u8]) -> Vec<u8> {
+        let mut buffer = self.alloc(data.len());
+        buffer.extend_from_slice(data);
+        buffer
+    }
+
+    /// Gives the buffer back to the arena for reuse
+    pub fn release(&self, mut buffer: Vec<u8>) {
+        let mut inner = self.inner.write().unwrap();
+        inner.buffers_in_use = inner.buffers_in_use.saturating_sub(1);
+        if buffer.capacity() == 0 || inner.free.len() >= MAX_POOLED_BUFFERS {
+            return;
+        }
+        buffer.clear();
+        inner.free.push(buffer);
+    }
+
+    /// Drops all pooled buffers and resets stats
+    pub fn reset(&self) {
+        let mut inner = self.inner.write().unwrap();
+        inner.free.clear();
+        inner.buffers_in_use = 0;
+        inner.stats = ArenaStats::default();
+    }
+
+    pub fn stats(&self) -> ArenaStats {
+        self.inner.read().unwrap().stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        arena::BufferArena,
+        tests::wat2rwasm,
+        DefaultEmptyRuntimeDatabase,
+        Runtime,
+        RuntimeContext,
+    };
+
+    #[test]
+    fn test_buffer_reuse() {
+        let arena = BufferArena::new();
+        let buffer = arena.alloc_from(&[1, 2, 3]);
+        let big_buffer = arena.alloc(1024);
+        assert_eq!(arena.stats().peak_buffers_in_use, 2);
+        arena.release(buffer);
+        arena.release(big_buffer);
+        // the smallest buffer that fits is reused and it's empty
+        let buffer = arena.alloc(2);
+        assert!(buffer.is_empty() && buffer.capacity() < 1024);
+        let big_buffer = arena.alloc(512);
+        assert!(big_buffer.capacity() >= 1024);
+        let stats = arena.stats();
+        assert_eq!((stats.allocations, stats.reuses), (2, 2));
+        assert_eq!(stats.allocated_bytes, 3 + 1024);
+        arena.reset();
+        assert_eq!(arena.stats().allocations, 0);
+    }
+
+    #[test]
+    fn test_runtime_reuses_buffers() {
+        let rwasm_binary = wat2rwasm(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 5
+    call $_write
+    i32.const 7
+    i32.const 5
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "Hello, World")
+  (export "main" (func $main)))
+    "#,
+        );
+        let arena = BufferArena::new();
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+            .with_fuel_limit(1_000_000)
+            .with_arena(arena.clone());
+        let execution_result = Runtime::run_with_context(ctx).unwrap();
+        assert_eq!(execution_result.output, b"HelloWorld".to_vec());
+        let stats = arena.stats();
+        assert_eq!((stats.allocations, stats.reuses), (1, 1));
+        assert_eq!(stats.peak_buffers_in_use, 1);
+    }
+}
@@ -0,0 +1,301 @@
+use crate::RuntimeStackLimits;
+use byteorder::{ByteOrder, LittleEndian};
+use fluentbase_types::{create_sovereign_import_linker, F254, HOST_ABI_VERSION};
+use keccak_hash::keccak;
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Magic prefix of the artifact files
+pub const MODULE_ARTIFACT_MAGIC: [u8; 4] = *b"FBMA";
+pub const MODULE_ARTIFACT_FORMAT_VERSION: u32 = 1;
+
+const ARTIFACT_EXTENSION: &str = "artifact";
+const HEADER_SIZE: usize = 4 + 4 + 32 + 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArtifactError {
+    NotFound,
+    /// Checksum doesn't match (a torn write or a damaged file)
+    Corrupted,
+    UnsupportedVersion(u32),
+    /// Artifact was produced by the engine with another configuration
+    EngineMismatch,
+    Io(String),
+}
+
+impl Display for ArtifactError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArtifactError::NotFound => write!(f, "module artifact not found"),
+            ArtifactError::Corrupted => write!(f, "module artifact is corrupted"),
+            ArtifactError::UnsupportedVersion(version) => {
+                write!(f, "unsupported module artifact version {}", version)
+            }
+            ArtifactError::EngineMismatch => {
+                write!(f, "module artifact was produced by another engine config")
+            }
+            ArtifactError::Io(err) => write!(f, "module artifact io error: {}", err),
+        }
+    }
+}
+
+impl From<std::io::Error> for ArtifactError {
+    fn from(value: std::io::Error) -> Self {
+        match value.kind() {
+            std::io::ErrorKind::NotFound => ArtifactError::NotFound,
+            _ => ArtifactError::Io(value.to_string()),
+        }
+    }
+}
+
+/// Hash of everything the translation of the modules depends on: version of the runtime, host
+/// ABI, syscalls of the linker and stack limits of the engine. Artifacts of another fingerprint
+/// are stale and never loaded.
+pub fn engine_fingerprint(stack_limits: RuntimeStackLimits) -> [u8; 32] {
+    let mut buffer = env!("CARGO_PKG_VERSION").as_bytes().to_vec();
+    buffer.extend_from_slice(&HOST_ABI_VERSION.to_le_bytes());
+    let import_linker: Vec<(&str, &str, u32, u32)> = create_sovereign_import_linker();
+    for (module, name, sys_func_idx, fuel_cost) in import_linker {
+        buffer.extend_from_slice(module.as_bytes());
+        buffer.extend_from_slice(name.as_bytes());
+        buffer.extend_from_slice(&sys_func_idx.to_le_bytes());
+        buffer.extend_from_slice(&fuel_cost.to_le_bytes());
+    }
+    for limit in [
+        stack_limits.initial_value_stack_height(),
+        stack_limits.max_value_stack_height(),
+        stack_limits.max_recursion_depth(),
+    ] {
+        buffer.extend_from_slice(&(limit as u64).to_le_bytes());
+    }
+    keccak(&buffer).0
+}
+
+/// Persisted module: rWASM bytecode of the contract and an optional engine artifact (opaque
+/// bytes of the embedders with serializable backends, like precompiled native code)
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ModuleArtifact {
+    pub rwasm_bytecode: Vec<u8>,
+    pub engine_artifact: Option<Vec<u8>>,
+}
+
+/// On-disk store of the modules keyed by the rWASM code hash, so restarted nodes load the
+/// deployed contracts without reading preimages from the trie (see
+/// [`crate::RuntimeContext::with_artifact_store`] and [`warm_up_modules_from_store`]).
+///
+/// Every artifact is a file `<code hash>.artifact` with the format `magic || version ||
+/// fingerprint(32) || code_hash(32) || len(u32) || rwasm_bytecode || flag(u8) || [len(u32) ||
+/// engine_artifact] || keccak256(all above)`. Files are written to a temporary file and renamed,
+/// so readers never see partial artifacts.
+#[derive(Debug, Clone)]
+pub struct ModuleArtifactStore {
+    dir: PathBuf,
+    fingerprint: [u8; 32],
+}
+
+impl ModuleArtifactStore {
+    /// Opens (and creates if needed) the store directory for the engine with the stack limits
+    pub fn open<P: AsRef<Path>>(dir: P, stack_limits: RuntimeStackLimits) -> std::io::Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+            fingerprint: engine_fingerprint(stack_limits),
+        })
+    }
+
+    pub fn fingerprint(&self) -> &[u8; 32] {
+        &self.fingerprint
+    }
+
+    fn artifact_path(&self, code_hash: &F254) -> PathBuf {
+        self.dir
+            .join(hex::encode(code_hash.as_slice()))
+            .with_extension(ARTIFACT_EXTENSION)
+    }
+
+    pub fn store(&self, code_hash: &F254, artifact: &ModuleArtifact) -> Result<(), ArtifactError> {
+        let bytes = self.encode(code_hash, artifact);
+        let path = self.artifact_path(code_hash);
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    pub fn load(&self, code_hash: &F254) -> Result<ModuleArtifact, ArtifactError> {
+        let bytes = fs::read(self.artifact_path(code_hash))?;
+        self.decode(code_hash, &bytes)
+    }
+
+    /// Removes the artifact, returns `false` if it wasn't stored
+    pub fn remove(&self, code_hash: &F254) -> Result<bool, ArtifactError> {
+        match fs::remove_file(self.artifact_path(code_hash)) {
+            Ok(_) => Ok(true),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Code hashes of all stored artifacts (including stale ones)
+    pub fn code_hashes(&self) -> Result<Vec<F254>, ArtifactError> {
+        let mut code_hashes = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|v| v.to_str()) != Some(ARTIFACT_EXTENSION) {
+                continue;
+            }
+            let code_hash = path
+                .file_stem()
+                .and_then(|v| v.to_str())
+                .and_then(|v| hex::decode(v).ok())
+                .filter(|v| v.len() == 32);
+            if let Some(code_hash) = code_hash {
+                code_hashes.push(F254::from_slice(&code_hash));
+            }
+        }
+        Ok(code_hashes)
+    }
+
+    /// Removes artifacts of other engine configs and damaged artifacts, returns the number of
+    /// removed artifacts
+    pub fn prune(&self) -> Result<usize, ArtifactError> {
+        let mut removed = 0;
+        for code_hash in self.code_hashes()? {
+            match self.load(&code_hash) {
+                Ok(_) | Err(ArtifactError::NotFound) => {}
+                Err(ArtifactError::Io(err)) => return Err(ArtifactError::Io(err)),
+                Err(_) => {
+                    self.remove(&code_hash)?;
+                    removed += 1;
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    fn encode(&self, code_hash: &F254, artifact: &ModuleArtifact) -> Vec<u8> {
+        let mut buffer = MODULE_ARTIFACT_MAGIC.to_vec();
+        buffer.extend_from_slice(&MODULE_ARTIFACT_FORMAT_VERSION.to_le_bytes());
+        buffer.extend_from_slice(&self.fingerprint);
+        buffer.extend_from_slice(code_hash.as_slice());
+        write_bytes(&mut buffer, &artifact.rwasm_bytecode);
+        match &artifact.engine_artifact {
+            Some(engine_artifact) => {
+                buffer.push(1);
+                write_bytes(&mut buffer, engine_artifact);
+            }
+            None => buffer.push(0),
+        }
+        let checksum = keccak(&buffer);
+        buffer.extend_from_slice(checksum.as_bytes());
+        buffer
+    }
+
+    fn decode(&self, code_hash: &F254, bytes: &[u8]) -> Result<ModuleArtifact, ArtifactError> {
+        if bytes.len() < HEADER_SIZE + 32 || bytes[..4] != MODULE_ARTIFACT_MAGIC {
+            return Err(ArtifactError::Corrupted);
+        }
+        let (body, checksum) = bytes.split_at(bytes.len() - 32);
+        if keccak(body).as_bytes() != checksum {
+            return Err(ArtifactError::Corrupted);
+        }
+        let version = LittleEndian::read_u32(&body[4..8]);
+        if version != MODULE_ARTIFACT_FORMAT_VERSION {
+            return Err(ArtifactError::UnsupportedVersion(version));
+        }
+        if body[8..40] != self.fingerprint {
+            return Err(ArtifactError::EngineMismatch);
+        }
+        // the file was renamed or copied under another hash
+        if body[40..72] != *code_hash.as_slice() {
+            return Err(ArtifactError::Corrupted);
+        }
+        let mut offset = HEADER_SIZE;
+        let rwasm_bytecode = read_bytes(body, &mut offset)?;
+        let engine_artifact = match body.get(offset) {
+            Some(0) => None,
+            Some(1) => {
+                offset += 1;
+                Some(read_bytes(body, &mut offset)?)
+            }
+            _ => return Err(ArtifactError::Corrupted),
+        };
+        Ok(ModuleArtifact {
+            rwasm_bytecode,
+            engine_artifact,
+        })
+    }
+}
+
+fn write_bytes(buffer: &mut Vec<u8>, value: &[u8]) {
+    buffer.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(value);
+}
+
+fn read_bytes(body: &[u8], offset: &mut usize) -> Result<Vec<u8>, ArtifactError> {
+    let length = body
+        .get(*offset..*offset + 4)
+        .map(LittleEndian::read_u32)
+        .ok_or(ArtifactError::Corrupted)? as usize;
+    *offset += 4;
+    let value = body
+        .get(*offset..*offset + length)
+        .ok_or(ArtifactError::Corrupted)?;
+    *offset += length;
+    Ok(value.to_vec())
+}
+
+/// Translates all stored modules of the engine config, so the first executions after the restart
+/// don't load the code from the trie and translate it (see [`crate::warm_up_modules`]). Stale
+/// and damaged artifacts are skipped.
+pub fn warm_up_modules_from_store(
+    stack_limits: RuntimeStackLimits,
+    artifact_store: &ModuleArtifactStore,
+) -> Result<crate::WarmUpReport, ArtifactError> {
+    let rwasm_bytecodes = artifact_store
+        .code_hashes()?
+        .iter()
+        .filter_map(|code_hash| artifact_store.load(code_hash).ok())
+        .map(|artifact| artifact.rwasm_bytecode)
+        .collect::<Vec<_>>();
+    Ok(crate::warm_up_modules(stack_limits, rwasm_bytecodes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_artifact_store() {
+        let dir = std::env::temp_dir().join(format!("fluentbase-artifacts-{}", std::process::id()));
+        let store = ModuleArtifactStore::open(&dir, RuntimeStackLimits::default()).unwrap();
+        let code_hash = F254::repeat_byte(1);
+        let artifact = ModuleArtifact {
+            rwasm_bytecode: vec![0xef, 1, 2, 3],
+            engine_artifact: Some(vec![4, 5]),
+        };
+        assert_eq!(store.load(&code_hash), Err(ArtifactError::NotFound));
+        store.store(&code_hash, &artifact).unwrap();
+        assert_eq!(store.load(&code_hash), Ok(artifact.clone()));
+        assert_eq!(store.code_hashes().unwrap(), vec![code_hash]);
+        // artifacts of another engine config are stale
+        let other_store =
+            ModuleArtifactStore::open(&dir, RuntimeStackLimits::new(1024, 4096, 16)).unwrap();
+        assert_eq!(
+            other_store.load(&code_hash),
+            Err(ArtifactError::EngineMismatch)
+        );
+        // damaged artifact fails the checksum
+        let path = store.artifact_path(&code_hash);
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[HEADER_SIZE + 4] ^= 0xff;
+        fs::write(&path, bytes).unwrap();
+        assert_eq!(store.load(&code_hash), Err(ArtifactError::Corrupted));
+        assert_eq!(store.prune(), Ok(1));
+        assert!(store.code_hashes().unwrap().is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
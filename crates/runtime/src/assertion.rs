@@ -0,0 +1,69 @@
+use crate::RuntimeContext;
+use fluentbase_types::{ExitCode, IJournaledTrie};
+use std::fmt::{Display, Formatter};
+
+/// Assertion syscalls are debugging tools, so they are available only if the context enables
+/// them (see [`RuntimeContext::with_debug_assertions`])
+pub(crate) fn check_debug_assertions<DB: IJournaledTrie>(
+    ctx: &RuntimeContext<DB>,
+) -> Result<(), ExitCode> {
+    if ctx.debug_assertions {
+        Ok(())
+    } else {
+        Err(ExitCode::CapabilityDenied)
+    }
+}
+
+/// Assertion syscall that failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AssertionKind {
+    /// `_assert_eq_mem`, two memory regions aren't equal
+    EqMem = 1,
+    /// `_expect_storage`, the storage slot has an unexpected value
+    Storage = 2,
+    /// `_expect_fuel_le`, the consumed fuel exceeds the limit
+    FuelLe = 3,
+}
+
+impl AssertionKind {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::EqMem),
+            2 => Some(Self::Storage),
+            3 => Some(Self::FuelLe),
+            _ => None,
+        }
+    }
+}
+
+impl Display for AssertionKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            AssertionKind::EqMem => "assert_eq_mem",
+            AssertionKind::Storage => "expect_storage",
+            AssertionKind::FuelLe => "expect_fuel_le",
+        })
+    }
+}
+
+/// Diagnostics of the failed in-guest assertion, the execution fails with `AssertionFailed`
+/// and the failure is returned in the execution result, so tests don't have to copy the
+/// checked data to the host.
+///
+/// Expected and actual values are raw bytes: compared memory regions, little-endian storage
+/// values and little-endian fuel amounts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AssertionFailure {
+    pub kind: AssertionKind,
+    pub expected: Vec<u8>,
+    pub actual: Vec<u8>,
+    pub message: String,
+}
+
+impl Display for AssertionFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "assertion {} failed: {}", self.kind, self.message)
+    }
+}
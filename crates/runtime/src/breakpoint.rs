@@ -0,0 +1,206 @@
+use crate::{
+    runtime::{CallMode, CallStep, PauseReason},
+    types::RuntimeError,
+    ExecutionResult,
+    Runtime,
+    RuntimeContext,
+};
+use fluentbase_types::{IJournaledTrie, SysFuncIdx};
+use rwasm::{
+    core::{HostError, Trap},
+    AsContextMut,
+    Value,
+};
+use std::fmt::{Display, Formatter};
+
+/// Results of the host function as engine values, it's implemented for all result types of the
+/// syscalls
+pub trait IntoHostResults {
+    fn into_host_results(self) -> Vec<Value>;
+}
+
+impl IntoHostResults for () {
+    fn into_host_results(self) -> Vec<Value> {
+        vec![]
+    }
+}
+
+impl IntoHostResults for i32 {
+    fn into_host_results(self) -> Vec<Value> {
+        vec![Value::I32(self)]
+    }
+}
+
+impl IntoHostResults for u32 {
+    fn into_host_results(self) -> Vec<Value> {
+        vec![Value::I32(self as i32)]
+    }
+}
+
+impl IntoHostResults for u64 {
+    fn into_host_results(self) -> Vec<Value> {
+        vec![Value::I64(self as i64)]
+    }
+}
+
+/// Host call that hit the breakpoint, the host function is already executed and its results are
+/// returned to the guest once the execution is resumed
+#[derive(Debug, Clone)]
+pub struct SysBreakpointResumable {
+    pub sys_func_idx: SysFuncIdx,
+    pub results: Vec<Value>,
+}
+
+impl SysBreakpointResumable {
+    /// Pauses the execution after the successful host call, failed calls aren't paused
+    pub fn pause<R: IntoHostResults>(
+        sys_func_idx: SysFuncIdx,
+        result: Result<R, Trap>,
+    ) -> Result<R, Trap> {
+        let results = result?.into_host_results();
+        Err(Self {
+            sys_func_idx,
+            results,
+        }
+        .into())
+    }
+}
+
+impl Display for SysBreakpointResumable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "execution is paused at {}", self.sys_func_idx)
+    }
+}
+
+impl HostError for SysBreakpointResumable {}
+
+/// Result of the resumable call
+pub enum ResumableExecution<'r, DB: IJournaledTrie> {
+    Finished(ExecutionResult),
+    Paused(HostCallBreakpoint<'r, DB>),
+}
+
+impl<'r, DB: IJournaledTrie> ResumableExecution<'r, DB> {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, ResumableExecution::Finished(_))
+    }
+
+    pub fn into_result(self) -> Option<ExecutionResult> {
+        match self {
+            ResumableExecution::Finished(execution_result) => Some(execution_result),
+            ResumableExecution::Paused(_) => None,
+        }
+    }
+}
+
+/// Execution paused right after the host call with a breakpoint, the context (including the
+/// changes made by the host call) can be inspected and changed before the execution is resumed
+pub struct HostCallBreakpoint<'r, DB: IJournaledTrie> {
+    runtime: &'r mut Runtime<DB>,
+    breakpoint: SysBreakpointResumable,
+}
+
+impl<'r, DB: IJournaledTrie> HostCallBreakpoint<'r, DB> {
+    pub fn sys_func_idx(&self) -> SysFuncIdx {
+        self.breakpoint.sys_func_idx
+    }
+
+    /// Results returned by the host function
+    pub fn results(&self) -> &[Value] {
+        &self.breakpoint.results
+    }
+
+    pub fn context(&self) -> &RuntimeContext<DB> {
+        self.runtime.data()
+    }
+
+    pub fn context_mut(&mut self) -> &mut RuntimeContext<DB> {
+        self.runtime.data_mut()
+    }
+
+    /// Resumes the execution, the guest gets the results of the host function
+    pub fn resume(self) -> Result<ResumableExecution<'r, DB>, RuntimeError> {
+        self.runtime.resume_paused(None)
+    }
+
+    /// Resumes the execution, the guest gets the provided results instead of the results of the
+    /// host function. The results must match the signature of the host function, otherwise the
+    /// execution fails.
+    pub fn resume_with(
+        self,
+        results: Vec<Value>,
+    ) -> Result<ResumableExecution<'r, DB>, RuntimeError> {
+        self.runtime.resume_paused(Some(results))
+    }
+}
+
+impl<DB: IJournaledTrie> Runtime<DB> {
+    /// Executes the call that is paused after every host call with a breakpoint (see
+    /// [`RuntimeContext::with_breakpoint`]), it lets debuggers and async host backends inspect the
+    /// context and provide results of the host calls.
+    ///
+    /// Only the top-level call is paused, nested calls don't inherit breakpoints. Calls executed
+    /// with [`Runtime::call`] ignore breakpoints, `_suspend` fails with `NotSupportedCall`.
+    pub fn call_resumable(&mut self) -> Result<ResumableExecution<'_, DB>, RuntimeError> {
+        self.call_mode = CallMode::Resumable;
+        self.paused = None;
+        if self.store.data().depth == 0 {
+            self.store.data().arena.reset();
+        }
+        let step = self.call_inner();
+        self.complete_resumable_step(step)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        matches!(
+            self.paused.as_ref().map(|paused| &paused.reason),
+            Some(PauseReason::Breakpoint(_))
+        )
+    }
+
+    fn resume_paused(
+        &mut self,
+        results: Option<Vec<Value>>,
+    ) -> Result<ResumableExecution<'_, DB>, RuntimeError> {
+        if !self.is_paused() {
+            return Err(RuntimeError::NotPaused);
+        }
+        let paused = self.paused.take().ok_or(RuntimeError::NotPaused)?;
+        let PauseReason::Breakpoint(breakpoint) = paused.reason else {
+            return Err(RuntimeError::NotPaused);
+        };
+        let results = results.unwrap_or(breakpoint.results);
+        let next_result = paused
+            .invocation
+            .resume(
+                self.store.as_context_mut(),
+                &results,
+                &mut self.call_results,
+            )
+            .map_err(Into::<RuntimeError>::into);
+        let step = self.drive(paused.checkpoint, next_result);
+        self.complete_resumable_step(step)
+    }
+
+    fn complete_resumable_step(
+        &mut self,
+        step: Result<CallStep, RuntimeError>,
+    ) -> Result<ResumableExecution<'_, DB>, RuntimeError> {
+        let result = match step {
+            Ok(CallStep::Paused) => match self.paused.as_ref().map(|paused| &paused.reason) {
+                Some(PauseReason::Breakpoint(breakpoint)) => {
+                    let breakpoint = breakpoint.clone();
+                    return Ok(ResumableExecution::Paused(HostCallBreakpoint {
+                        runtime: self,
+                        breakpoint,
+                    }));
+                }
+                // only breakpoints pause resumable calls
+                _ => Err(RuntimeError::UnexpectedPause),
+            },
+            Ok(CallStep::Finished(execution_result)) => Ok(execution_result),
+            Err(err) => Err(err),
+        };
+        self.finish_call(result).map(ResumableExecution::Finished)
+    }
+}
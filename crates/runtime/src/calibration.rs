@@ -0,0 +1,539 @@
+use crate::{types::RuntimeError, DefaultEmptyRuntimeDatabase, Runtime, RuntimeContext};
+use fluentbase_types::{
+    create_sovereign_import_linker,
+    ExitCode,
+    Fuel,
+    SysFuncIdx,
+    SysFuncIdx::STATE,
+    STATE_DEPLOY,
+    STATE_MAIN,
+};
+use k256::ecdsa::SigningKey;
+use rwasm::{
+    engine::{bytecode::Instruction, RwasmConfig, StateRouterConfig},
+    rwasm::{BinaryFormat, RwasmModule},
+};
+use serde_json::json;
+use std::{collections::BTreeMap, fmt::Write, time::Instant};
+
+/// Fuel limit of benchmark executions, it's big enough to never run out of fuel
+const CALIBRATION_FUEL_LIMIT: u64 = 1 << 50;
+
+/// Classes of WASM instructions with similar execution cost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OpcodeClass {
+    /// Constants, locals and cheap integer arithmetic (`i32.add`, `i32.and`, ...), all other
+    /// prices are relative to this class
+    Arithmetic,
+    /// Integer multiplication, division and remainder
+    Division,
+    /// Memory loads and stores
+    Memory,
+    /// Function calls and returns
+    Call,
+}
+
+impl OpcodeClass {
+    pub const ALL: [OpcodeClass; 4] = [
+        OpcodeClass::Arithmetic,
+        OpcodeClass::Division,
+        OpcodeClass::Memory,
+        OpcodeClass::Call,
+    ];
+
+    fn body(&self) -> &'static str {
+        match self {
+            OpcodeClass::Arithmetic => "local.get $x i32.const 3 i32.add local.set $x",
+            OpcodeClass::Division => "local.get $x i32.const 3 i32.div_u local.set $x",
+            OpcodeClass::Memory => "i32.const 64 i32.const 64 i32.load i32.store",
+            OpcodeClass::Call => "call $noop",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CalibrationTarget {
+    Syscall(SysFuncIdx),
+    Opcode(OpcodeClass),
+}
+
+/// Benchmark of one syscall or opcode class with the specific input. The body is a WAT snippet
+/// that must leave the stack unchanged, it's executed in the loop and can use `$x` local, `$noop`
+/// function and memory initialized with the data (memory after 2048 bytes is free for outputs).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationCase {
+    pub name: String,
+    pub target: CalibrationTarget,
+    /// Import declarations required by the body
+    pub imports: Vec<String>,
+    pub body: String,
+    pub data: Vec<u8>,
+    pub input: Vec<u8>,
+}
+
+impl CalibrationCase {
+    pub fn new<N: Into<String>, B: Into<String>>(
+        name: N,
+        target: CalibrationTarget,
+        body: B,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            target,
+            imports: vec![],
+            body: body.into(),
+            data: vec![],
+            input: vec![],
+        }
+    }
+
+    pub fn opcode(opcode_class: OpcodeClass) -> Self {
+        Self::new(
+            format!("{:?}", opcode_class),
+            CalibrationTarget::Opcode(opcode_class),
+            opcode_class.body(),
+        )
+    }
+
+    pub fn syscall<B: Into<String>>(
+        sys_func_idx: SysFuncIdx,
+        input_name: &str,
+        import: &str,
+        body: B,
+    ) -> Self {
+        let mut result = Self::new(
+            format!("{}({})", sys_func_idx, input_name),
+            CalibrationTarget::Syscall(sys_func_idx),
+            body,
+        );
+        result.imports.push(import.to_string());
+        result
+    }
+
+    pub fn with_data(mut self, data: Vec<u8>) -> Self {
+        self.data = data;
+        self
+    }
+
+    pub fn with_input(mut self, input: Vec<u8>) -> Self {
+        self.input = input;
+        self
+    }
+
+    fn to_wat(&self, iterations: u32, unroll: u32, with_body: bool) -> String {
+        let mut wat = String::from("(module\n");
+        for import in self.imports.iter() {
+            writeln!(wat, "  {}", import).unwrap();
+        }
+        wat.push_str("  (func $noop)\n");
+        wat.push_str("  (func $main (local $i i32) (local $x i32)\n    (loop $loop\n");
+        if with_body {
+            for _ in 0..unroll {
+                writeln!(wat, "      {}", self.body).unwrap();
+            }
+        }
+        writeln!(
+            wat,
+            "      local.get $i i32.const 1 i32.add local.tee $i i32.const {} i32.lt_u br_if $loop))",
+            iterations
+        )
+        .unwrap();
+        wat.push_str("  (memory 1)\n");
+        wat.push_str("  (data (i32.const 0) \"");
+        self.data
+            .iter()
+            .for_each(|byte| write!(wat, "\\{:02x}", byte).unwrap());
+        wat.push_str("\")\n");
+        wat.push_str("  (export \"main\" (func $main)))\n");
+        wat
+    }
+}
+
+/// Representative cases for all opcode classes and stateless syscalls, state syscalls depend on
+/// the trie size, so they should be calibrated with custom cases over the real state
+pub fn default_calibration_cases() -> Vec<CalibrationCase> {
+    let mut result = OpcodeClass::ALL
+        .into_iter()
+        .map(CalibrationCase::opcode)
+        .collect::<Vec<_>>();
+    const KECCAK256: &str =
+        r#"(import "fluentbase_v1preview" "_keccak256" (func $_keccak256 (param i32 i32 i32)))"#;
+    for len in [32, 1024] {
+        result.push(CalibrationCase::syscall(
+            SysFuncIdx::KECCAK256,
+            &format!("{} bytes", len),
+            KECCAK256,
+            format!(
+                "i32.const 0 i32.const {} i32.const 2048 call $_keccak256",
+                len
+            ),
+        ));
+    }
+    result.push(CalibrationCase::syscall(
+        SysFuncIdx::POSEIDON_HASH,
+        "zero elements",
+        r#"(import "fluentbase_v1preview" "_poseidon_hash" (func $_poseidon_hash (param i32 i32 i32 i32)))"#,
+        "i32.const 0 i32.const 32 i32.const 64 i32.const 2048 call $_poseidon_hash",
+    ));
+    // recovery of the valid signature, the data is the digest followed by the signature
+    let digest = [7u8; 32];
+    let signing_key = SigningKey::from_slice(&[1u8; 32]).unwrap();
+    let (signature, recovery_id) = signing_key.sign_prehash_recoverable(&digest).unwrap();
+    let mut data = digest.to_vec();
+    data.extend_from_slice(&signature.to_bytes());
+    result.push(
+        CalibrationCase::syscall(
+            SysFuncIdx::ECRECOVER,
+            "valid signature",
+            r#"(import "fluentbase_v1preview" "_ecrecover" (func $_ecrecover (param i32 i32 i32 i32)))"#,
+            format!(
+                "i32.const 0 i32.const 32 i32.const 2048 i32.const {} call $_ecrecover",
+                recovery_id.to_byte()
+            ),
+        )
+        .with_data(data),
+    );
+    for len in [32, 1024] {
+        result.push(
+            CalibrationCase::syscall(
+                SysFuncIdx::READ,
+                &format!("{} bytes", len),
+                r#"(import "fluentbase_v1preview" "_read" (func $_read (param i32 i32 i32)))"#,
+                format!("i32.const 2048 i32.const 0 i32.const {} call $_read", len),
+            )
+            .with_input(vec![0u8; len]),
+        );
+    }
+    result.push(CalibrationCase::syscall(
+        SysFuncIdx::WRITE,
+        "32 bytes",
+        r#"(import "fluentbase_v1preview" "_write" (func $_write (param i32 i32)))"#,
+        "i32.const 0 i32.const 32 call $_write",
+    ));
+    let getters = [
+        (SysFuncIdx::INPUT_SIZE, "_input_size", "i32"),
+        (SysFuncIdx::STATE, "_state", "i32"),
+        (SysFuncIdx::RETURN_DATA_SIZE, "_return_data_size", "i32"),
+        (SysFuncIdx::FUEL_REMAINING, "_fuel_remaining", "i64"),
+        (SysFuncIdx::FUEL_CONSUMED, "_fuel_consumed", "i64"),
+    ];
+    for (sys_func_idx, name, result_type) in getters {
+        result.push(CalibrationCase::syscall(
+            sys_func_idx,
+            "no input",
+            &format!(
+                r#"(import "fluentbase_v1preview" "{}" (func ${} (result {})))"#,
+                name, name, result_type
+            ),
+            format!("call ${} drop", name),
+        ));
+    }
+    result
+}
+
+#[derive(Debug)]
+pub enum CalibrationError {
+    Translation(String),
+    Runtime(RuntimeError),
+    /// Benchmark didn't finish successfully, so its timing is meaningless
+    Failed {
+        name: String,
+        exit_code: i32,
+    },
+    /// The reference `Arithmetic` class isn't measured and the fuel price isn't specified
+    MissingReference,
+}
+
+impl From<RuntimeError> for CalibrationError {
+    fn from(value: RuntimeError) -> Self {
+        Self::Runtime(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationMeasurement {
+    pub name: String,
+    pub target: CalibrationTarget,
+    /// Median time of one execution of the body without the loop overhead
+    pub nanos_per_op: f64,
+    pub proposed_fuel: u64,
+}
+
+/// Proposed fuel prices, syscalls measured with several inputs get the price of the most
+/// expensive input
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FuelPricingTable {
+    pub syscalls: BTreeMap<SysFuncIdx, u64>,
+    pub opcodes: BTreeMap<OpcodeClass, u64>,
+}
+
+impl FuelPricingTable {
+    pub fn syscall_cost(&self, sys_func_idx: SysFuncIdx) -> Option<u64> {
+        self.syscalls.get(&sys_func_idx).copied()
+    }
+
+    pub fn opcode_cost(&self, opcode_class: OpcodeClass) -> Option<u64> {
+        self.opcodes.get(&opcode_class).copied()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "syscalls": self
+                .syscalls
+                .iter()
+                .map(|(sys_func_idx, fuel)| (sys_func_idx.to_string(), json!(fuel)))
+                .collect::<serde_json::Map<_, _>>(),
+            "opcodes": self
+                .opcodes
+                .iter()
+                .map(|(opcode_class, fuel)| (format!("{:?}", opcode_class), json!(fuel)))
+                .collect::<serde_json::Map<_, _>>(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationReport {
+    pub measurements: Vec<CalibrationMeasurement>,
+    pub nanos_per_fuel: f64,
+    pub pricing_table: FuelPricingTable,
+}
+
+/// Measures real execution time of syscalls and opcode classes and proposes fuel prices. Every
+/// case is executed as a loop with the unrolled body and compared with the same loop without
+/// the body, the difference is converted into fuel using the time of the `Arithmetic` class as
+/// one unit of fuel (unless the price of fuel is specified explicitly).
+///
+/// Timings depend on the machine, so the calibration should be done on the reference hardware
+/// with an optimized build.
+pub struct FuelCalibration {
+    cases: Vec<CalibrationCase>,
+    iterations: u32,
+    unroll: u32,
+    samples: usize,
+    nanos_per_fuel: Option<f64>,
+}
+
+impl Default for FuelCalibration {
+    fn default() -> Self {
+        Self {
+            cases: default_calibration_cases(),
+            iterations: 1_000,
+            unroll: 16,
+            samples: 5,
+            nanos_per_fuel: None,
+        }
+    }
+}
+
+impl FuelCalibration {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_cases(mut self, cases: Vec<CalibrationCase>) -> Self {
+        self.cases = cases;
+        self
+    }
+
+    pub fn with_case(mut self, case: CalibrationCase) -> Self {
+        self.cases.push(case);
+        self
+    }
+
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn with_unroll(mut self, unroll: u32) -> Self {
+        self.unroll = unroll;
+        self
+    }
+
+    pub fn with_samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    pub fn with_nanos_per_fuel(mut self, nanos_per_fuel: f64) -> Self {
+        self.nanos_per_fuel = Some(nanos_per_fuel);
+        self
+    }
+
+    pub fn run(&self) -> Result<CalibrationReport, CalibrationError> {
+        assert!(
+            self.iterations > 0 && self.unroll > 0 && self.samples > 0,
+            "calibration parameters must be positive"
+        );
+        let timings = self
+            .cases
+            .iter()
+            .map(|case| Ok((case, self.measure(case)?)))
+            .collect::<Result<Vec<_>, CalibrationError>>()?;
+        let nanos_per_fuel = match self.nanos_per_fuel {
+            Some(nanos_per_fuel) => nanos_per_fuel,
+            None => timings
+                .iter()
+                .find(|(case, _)| case.target == CalibrationTarget::Opcode(OpcodeClass::Arithmetic))
+                .map(|(_, nanos_per_op)| *nanos_per_op)
+                .ok_or(CalibrationError::MissingReference)?,
+        }
+        // reference can be lost in the noise, but the price of fuel must stay positive
+        .max(f64::EPSILON);
+        let mut pricing_table = FuelPricingTable::default();
+        let mut measurements = Vec::with_capacity(timings.len());
+        for (case, nanos_per_op) in timings {
+            let proposed_fuel = ((nanos_per_op / nanos_per_fuel).ceil() as u64).max(1);
+            let price = match case.target {
+                CalibrationTarget::Syscall(sys_func_idx) => {
+                    pricing_table.syscalls.entry(sys_func_idx).or_default()
+                }
+                CalibrationTarget::Opcode(opcode_class) => {
+                    pricing_table.opcodes.entry(opcode_class).or_default()
+                }
+            };
+            *price = (*price).max(proposed_fuel);
+            measurements.push(CalibrationMeasurement {
+                name: case.name.clone(),
+                target: case.target,
+                nanos_per_op,
+                proposed_fuel,
+            });
+        }
+        Ok(CalibrationReport {
+            measurements,
+            nanos_per_fuel,
+            pricing_table,
+        })
+    }
+
+    /// Returns median time of one body execution in nanoseconds
+    fn measure(&self, case: &CalibrationCase) -> Result<f64, CalibrationError> {
+        let with_body = self.compile(case, true)?;
+        let baseline = self.compile(case, false)?;
+        let with_body = self.median_nanos(case, &with_body)?;
+        let baseline = self.median_nanos(case, &baseline)?;
+        let ops = self.iterations as f64 * self.unroll as f64;
+        Ok((with_body - baseline).max(0.0) / ops)
+    }
+
+    fn compile(
+        &self,
+        case: &CalibrationCase,
+        with_body: bool,
+    ) -> Result<Vec<u8>, CalibrationError> {
+        let wat = case.to_wat(self.iterations, self.unroll, with_body);
+        let wasm_binary =
+            wat::parse_str(&wat).map_err(|err| CalibrationError::Translation(err.to_string()))?;
+        wasm2rwasm(&wasm_binary)
+    }
+
+    fn median_nanos(
+        &self,
+        case: &CalibrationCase,
+        rwasm_binary: &[u8],
+    ) -> Result<f64, CalibrationError> {
+        // the first execution compiles and caches the module, so it isn't measured
+        let mut timings = Vec::with_capacity(self.samples);
+        for i in 0..=self.samples {
+            let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.to_vec())
+                .with_input(case.input.clone())
+                .with_fuel_limit(Fuel(CALIBRATION_FUEL_LIMIT));
+            let time = Instant::now();
+            let execution_result = Runtime::run_with_context(ctx)?;
+            let elapsed = time.elapsed().as_nanos() as f64;
+            if execution_result.exit_code != ExitCode::Ok.into_i32() {
+                return Err(CalibrationError::Failed {
+                    name: case.name.clone(),
+                    exit_code: execution_result.exit_code,
+                });
+            }
+            if i > 0 {
+                timings.push(elapsed);
+            }
+        }
+        timings.sort_by(|a, b| a.total_cmp(b));
+        Ok(timings[timings.len() / 2])
+    }
+}
+
+fn wasm2rwasm(wasm_binary: &[u8]) -> Result<Vec<u8>, CalibrationError> {
+    let import_linker = Runtime::new_sovereign_linker();
+    let mut rwasm_config = RwasmModule::default_config(Some(import_linker));
+    rwasm_config.rwasm_config(RwasmConfig {
+        state_router: Some(StateRouterConfig {
+            states: Box::new([
+                ("deploy".to_string(), STATE_DEPLOY),
+                ("main".to_string(), STATE_MAIN),
+            ]),
+            opcode: Instruction::Call(STATE.into()),
+        }),
+        entrypoint_name: None,
+        import_linker: Some(create_sovereign_import_linker()),
+        wrap_import_functions: true,
+    });
+    let rwasm_module = RwasmModule::compile_with_config(wasm_binary, &rwasm_config)
+        .map_err(|err| CalibrationError::Translation(format!("{:?}", err)))?;
+    let mut result = Vec::new();
+    rwasm_module
+        .write_binary_to_vec(&mut result)
+        .map_err(|err| CalibrationError::Translation(format!("{:?}", err)))?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calibration::{
+        default_calibration_cases,
+        CalibrationCase,
+        CalibrationError,
+        CalibrationTarget,
+        FuelCalibration,
+        OpcodeClass,
+    };
+    use fluentbase_types::{ExitCode, SysFuncIdx};
+
+    #[test]
+    fn test_fuel_calibration() {
+        let report = FuelCalibration::new()
+            .with_iterations(20)
+            .with_unroll(4)
+            .with_samples(1)
+            .run()
+            .unwrap();
+        let cases = default_calibration_cases();
+        assert_eq!(report.measurements.len(), cases.len());
+        let table = &report.pricing_table;
+        for case in cases.iter() {
+            let fuel = match case.target {
+                CalibrationTarget::Syscall(sys_func_idx) => table.syscall_cost(sys_func_idx),
+                CalibrationTarget::Opcode(opcode_class) => table.opcode_cost(opcode_class),
+            };
+            assert!(fuel.unwrap() >= 1, "{} isn't priced", case.name);
+        }
+        let json = table.to_json();
+        assert!(json["syscalls"]["KECCAK256"].as_u64().unwrap() >= 1);
+        assert!(json["opcodes"]["Arithmetic"].as_u64().unwrap() >= 1);
+
+        // failed benchmarks aren't priced
+        let failing = CalibrationCase::syscall(
+            SysFuncIdx::EXIT,
+            "revert",
+            r#"(import "fluentbase_v1preview" "_exit" (func $_exit (param i32)))"#,
+            "i32.const -72 call $_exit",
+        );
+        let result = FuelCalibration::new()
+            .with_cases(vec![
+                CalibrationCase::opcode(OpcodeClass::Arithmetic),
+                failing,
+            ])
+            .with_iterations(1)
+            .with_samples(1)
+            .run();
+        assert!(matches!(
+            result,
+            Err(CalibrationError::Failed { exit_code, .. }) if exit_code == ExitCode::Revert.into_i32()
+        ));
+    }
+}
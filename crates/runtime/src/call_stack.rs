@@ -0,0 +1,91 @@
+use fluentbase_types::{ExitCode, Fuel, F254};
+use std::sync::{Arc, Mutex};
+
+/// Max depth of the nested calls if the limit isn't configured (the top-level call has depth 0)
+pub const DEFAULT_CALL_STACK_LIMIT: u32 = 1024;
+
+/// Nested call in progress, frames are pushed by `_exec`, `_static_exec`, `_exec_address` and
+/// context calls before the callee is started and popped once it returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallFrame {
+    pub code_hash: F254,
+    pub depth: u32,
+    pub fuel_limit: Fuel,
+    pub is_static: bool,
+}
+
+/// Call stack of the execution, nested calls share the frames with the caller, so the embedder
+/// (or an inspector) can see the whole chain of the calls from any context of the call tree
+#[derive(Debug, Clone)]
+pub struct CallStack {
+    limit: u32,
+    frames: Arc<Mutex<Vec<CallFrame>>>,
+}
+
+impl Default for CallStack {
+    fn default() -> Self {
+        Self::new(DEFAULT_CALL_STACK_LIMIT)
+    }
+}
+
+impl CallStack {
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            frames: Default::default(),
+        }
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// Frames of the nested calls in progress from the outermost to the innermost one
+    pub fn frames(&self) -> Vec<CallFrame> {
+        self.frames.lock().unwrap().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Enters the nested call, fails with `CallDepthOverflow` if the callee exceeds the limit
+    pub(crate) fn push(&self, frame: CallFrame) -> Result<(), ExitCode> {
+        if frame.depth > self.limit {
+            return Err(ExitCode::CallDepthOverflow);
+        }
+        self.frames.lock().unwrap().push(frame);
+        Ok(())
+    }
+
+    pub(crate) fn pop(&self) -> Option<CallFrame> {
+        self.frames.lock().unwrap().pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_stack_limit() {
+        let call_stack = CallStack::new(2);
+        let frame = |depth| CallFrame {
+            code_hash: F254::repeat_byte(depth as u8),
+            depth,
+            fuel_limit: Fuel(1000),
+            is_static: false,
+        };
+        call_stack.push(frame(1)).unwrap();
+        // nested contexts share the frames
+        call_stack.clone().push(frame(2)).unwrap();
+        assert_eq!(call_stack.push(frame(3)), Err(ExitCode::CallDepthOverflow));
+        assert_eq!(call_stack.frames(), vec![frame(1), frame(2)]);
+        assert_eq!(call_stack.pop(), Some(frame(2)));
+        assert_eq!(call_stack.len(), 1);
+    }
+}
@@ -0,0 +1,132 @@
+use fluentbase_types::{SysFuncIdx, F254};
+use hashbrown::HashMap;
+use std::sync::Arc;
+
+/// Syscalls available only to sovereign executions, shared executions can use them only if
+/// they are granted by the escalation policy
+pub const SOVEREIGN_SYSCALLS: [SysFuncIdx; 9] = [
+    SysFuncIdx::CONTEXT_CALL,
+    SysFuncIdx::CHECKPOINT,
+    SysFuncIdx::UPDATE_LEAF,
+    SysFuncIdx::REMOVE_LEAF,
+    SysFuncIdx::COMPUTE_ROOT,
+    SysFuncIdx::COMMIT,
+    SysFuncIdx::ROLLBACK,
+    SysFuncIdx::PREIMAGE_SIZE,
+    SysFuncIdx::UPDATE_PREIMAGE,
+];
+
+/// Set of sovereign syscalls the execution is allowed to use, syscalls that aren't sovereign
+/// are always allowed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Capabilities(u32);
+
+impl Capabilities {
+    /// Capabilities of the shared execution
+    pub const NONE: Self = Self(0);
+    /// Capabilities of the sovereign execution
+    pub const ALL: Self = Self((1 << SOVEREIGN_SYSCALLS.len()) - 1);
+
+    fn bit(sys_func_idx: SysFuncIdx) -> Option<u32> {
+        SOVEREIGN_SYSCALLS
+            .iter()
+            .position(|v| *v == sys_func_idx)
+            .map(|index| 1 << index)
+    }
+
+    /// Creates capabilities from the list of syscalls, non-sovereign syscalls are ignored
+    pub fn from_syscalls<I: IntoIterator<Item = SysFuncIdx>>(syscalls: I) -> Self {
+        syscalls
+            .into_iter()
+            .fold(Self::NONE, |capabilities, sys_func_idx| {
+                capabilities.with(sys_func_idx)
+            })
+    }
+
+    pub fn with(self, sys_func_idx: SysFuncIdx) -> Self {
+        Self(self.0 | Self::bit(sys_func_idx).unwrap_or_default())
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn is_sovereign(&self) -> bool {
+        *self == Self::ALL
+    }
+
+    pub fn allows(&self, sys_func_idx: SysFuncIdx) -> bool {
+        match Self::bit(sys_func_idx) {
+            Some(bit) => self.0 & bit != 0,
+            None => true,
+        }
+    }
+
+    /// Granted sovereign syscalls
+    pub fn syscalls(&self) -> Vec<SysFuncIdx> {
+        SOVEREIGN_SYSCALLS
+            .iter()
+            .copied()
+            .filter(|sys_func_idx| self.allows(*sys_func_idx))
+            .collect()
+    }
+}
+
+/// Grants sovereign capabilities to trusted bytecode (like system contracts) executed in the
+/// shared mode, the bytecode is identified by its rWASM hash.
+///
+/// Granted capabilities aren't inherited, nested calls of the trusted contract get the base
+/// capabilities of the execution plus capabilities granted to the callee.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EscalationPolicy {
+    grants: Arc<HashMap<F254, Capabilities>>,
+}
+
+impl EscalationPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_grant(mut self, code_hash: F254, capabilities: Capabilities) -> Self {
+        self.grant(code_hash, capabilities);
+        self
+    }
+
+    pub fn grant(&mut self, code_hash: F254, capabilities: Capabilities) {
+        let grants = Arc::make_mut(&mut self.grants);
+        let granted = grants.entry(code_hash).or_default();
+        *granted = granted.union(capabilities);
+    }
+
+    pub fn capabilities_for(&self, code_hash: &F254) -> Capabilities {
+        self.grants.get(code_hash).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::capability::{Capabilities, EscalationPolicy};
+    use fluentbase_types::{SysFuncIdx, F254};
+
+    #[test]
+    fn test_capabilities() {
+        let capabilities = Capabilities::from_syscalls([SysFuncIdx::CHECKPOINT, SysFuncIdx::EXIT]);
+        assert!(capabilities.allows(SysFuncIdx::CHECKPOINT));
+        assert!(!capabilities.allows(SysFuncIdx::COMMIT));
+        // shared syscalls are always allowed
+        assert!(Capabilities::NONE.allows(SysFuncIdx::EXIT));
+        assert_eq!(capabilities.syscalls(), vec![SysFuncIdx::CHECKPOINT]);
+        assert!(Capabilities::ALL.is_sovereign());
+        assert!(!capabilities.union(Capabilities::NONE).is_sovereign());
+
+        let code_hash = F254::repeat_byte(1);
+        let policy = EscalationPolicy::new()
+            .with_grant(code_hash, capabilities)
+            .with_grant(code_hash, Capabilities::NONE.with(SysFuncIdx::ROLLBACK));
+        assert_eq!(
+            policy.capabilities_for(&code_hash).syscalls(),
+            vec![SysFuncIdx::CHECKPOINT, SysFuncIdx::ROLLBACK]
+        );
+        assert_eq!(policy.capabilities_for(&F254::ZERO), Capabilities::NONE);
+    }
+}
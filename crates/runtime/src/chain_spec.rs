@@ -0,0 +1,301 @@
+use crate::{
+    deploy::CodeSizeLimits,
+    fuel_policy::FuelPolicy,
+    policy::BytecodePolicy,
+    RuntimeContext,
+};
+use fluentbase_types::{
+    create_sovereign_import_linker,
+    Fuel,
+    FuelSchedule,
+    IJournaledTrie,
+    IntrinsicCostSchedule,
+    SysFuncIdx,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+/// Storage trie of the chain, the embedder opens the state database of the backend
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TrieBackend {
+    /// Sparse binary trie with poseidon hashing (see [`crate::zktrie::ZkTrieStateDb`])
+    #[default]
+    ZkTrie,
+    /// Merkle Patricia trie with keccak256 hashing (see [`crate::mptrie::MPTrieStateDb`])
+    MerklePatricia,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChainSpecError {
+    Json(String),
+    Toml(String),
+    /// Syscall name isn't known to the sovereign linker
+    UnknownSyscall(String),
+    ZeroFuelPerGas,
+}
+
+impl Display for ChainSpecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChainSpecError::Json(err) => write!(f, "malformed json chain spec: {}", err),
+            ChainSpecError::Toml(err) => write!(f, "malformed toml chain spec: {}", err),
+            ChainSpecError::UnknownSyscall(name) => write!(f, "unknown syscall {}", name),
+            ChainSpecError::ZeroFuelPerGas => write!(f, "fuel per gas must be positive"),
+        }
+    }
+}
+
+/// Execution rules of the chain, so the same binary can run devnet, testnet and mainnet rules.
+/// The spec is loaded from JSON or TOML and configures the context of every top-level call of
+/// the block with [`ChainSpec::configure`].
+///
+/// Syscalls are referenced by their import names (like `_keccak256`), hardforks are named
+/// switches activated at block numbers and interpreted by the embedder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ChainSpec {
+    pub name: String,
+    pub chain_id: u64,
+    pub fuel_schedule: FuelSchedule,
+    pub intrinsic_cost: IntrinsicCostSchedule,
+    /// Fuel charged for the calls of the syscalls on top of the executed instructions
+    pub syscall_costs: BTreeMap<String, u64>,
+    /// Syscalls contracts can't import, bytecode importing them fails the validation
+    pub disabled_syscalls: Vec<String>,
+    pub code_size_limits: CodeSizeLimits,
+    pub trie_backend: TrieBackend,
+    /// Activation block of every hardfork, absent hardforks are never active
+    pub hardforks: BTreeMap<String, u64>,
+}
+
+impl Default for ChainSpec {
+    fn default() -> Self {
+        Self {
+            name: "devnet".to_string(),
+            chain_id: 0,
+            fuel_schedule: FuelSchedule::DEFAULT,
+            intrinsic_cost: IntrinsicCostSchedule::new(),
+            syscall_costs: BTreeMap::new(),
+            disabled_syscalls: vec![],
+            code_size_limits: CodeSizeLimits::DEFAULT,
+            trie_backend: TrieBackend::ZkTrie,
+            hardforks: BTreeMap::new(),
+        }
+    }
+}
+
+impl ChainSpec {
+    pub fn new(name: &str, chain_id: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            chain_id,
+            ..Default::default()
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, ChainSpecError> {
+        let chain_spec: Self =
+            serde_json::from_str(json).map_err(|err| ChainSpecError::Json(err.to_string()))?;
+        chain_spec.validate()?;
+        Ok(chain_spec)
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> Result<Self, ChainSpecError> {
+        let chain_spec: Self =
+            toml::from_str(toml).map_err(|err| ChainSpecError::Toml(err.to_string()))?;
+        chain_spec.validate()?;
+        Ok(chain_spec)
+    }
+
+    /// Checks the rules that can't be expressed by the types of the fields (loaded specs are
+    /// validated on load)
+    pub fn validate(&self) -> Result<(), ChainSpecError> {
+        if self.fuel_schedule.fuel_per_gas() == 0 {
+            return Err(ChainSpecError::ZeroFuelPerGas);
+        }
+        self.fuel_policy()?;
+        self.bytecode_policy()?;
+        Ok(())
+    }
+
+    pub fn with_hardfork(mut self, hardfork: &str, block_number: u64) -> Self {
+        self.hardforks.insert(hardfork.to_string(), block_number);
+        self
+    }
+
+    pub fn is_active(&self, hardfork: &str, block_number: u64) -> bool {
+        self.hardforks
+            .get(hardfork)
+            .is_some_and(|activation| *activation <= block_number)
+    }
+
+    pub fn fuel_policy(&self) -> Result<FuelPolicy, ChainSpecError> {
+        self.syscall_costs
+            .iter()
+            .try_fold(FuelPolicy::new(), |fuel_policy, (name, fuel)| {
+                Ok(fuel_policy.with_syscall_cost(resolve_syscall(name)?, Fuel(*fuel)))
+            })
+    }
+
+    /// Policy allowing every syscall of the sovereign linker except the disabled ones, `None`
+    /// if no syscalls are disabled
+    pub fn bytecode_policy(&self) -> Result<Option<BytecodePolicy>, ChainSpecError> {
+        if self.disabled_syscalls.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(self.restrict_bytecode_policy(BytecodePolicy::new())?))
+    }
+
+    /// Disallows imports of the disabled syscalls on top of the restrictions of the policy
+    pub fn restrict_bytecode_policy(
+        &self,
+        bytecode_policy: BytecodePolicy,
+    ) -> Result<BytecodePolicy, ChainSpecError> {
+        let import_linker: Vec<(&str, &str, u32, u32)> = create_sovereign_import_linker();
+        let mut disabled_imports = Vec::with_capacity(self.disabled_syscalls.len());
+        for name in self.disabled_syscalls.iter() {
+            resolve_syscall(name)?;
+            disabled_imports.extend(
+                import_linker
+                    .iter()
+                    .filter(|(_, v, _, _)| v == name)
+                    .map(|(module, name, _, _)| (module.to_string(), name.to_string())),
+            );
+        }
+        Ok(bytecode_policy.with_disallowed_imports(disabled_imports))
+    }
+
+    /// Applies the rules active at the block to the context of the top-level call, disabled
+    /// syscalls are merged into the bytecode policy of the context (if any)
+    pub fn configure<DB: IJournaledTrie>(
+        &self,
+        ctx: RuntimeContext<DB>,
+        block_number: u64,
+    ) -> Result<RuntimeContext<DB>, ChainSpecError> {
+        let mut ctx = ctx
+            .with_fuel_schedule(self.fuel_schedule)
+            .with_intrinsic_cost(self.intrinsic_cost.at(block_number))
+            .with_fuel_policy(self.fuel_policy()?)
+            .with_code_size_limits(self.code_size_limits);
+        if !self.disabled_syscalls.is_empty() {
+            let bytecode_policy = ctx.bytecode_policy.take().unwrap_or_default();
+            ctx = ctx.with_bytecode_policy(self.restrict_bytecode_policy(bytecode_policy)?);
+        }
+        Ok(ctx)
+    }
+}
+
+fn resolve_syscall(name: &str) -> Result<SysFuncIdx, ChainSpecError> {
+    let import_linker: Vec<(&str, &str, u32, u32)> = create_sovereign_import_linker();
+    import_linker
+        .iter()
+        .find(|(_, v, _, _)| *v == name)
+        .and_then(|(_, _, sys_func_idx, _)| SysFuncIdx::from_repr(*sys_func_idx))
+        .ok_or_else(|| ChainSpecError::UnknownSyscall(name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DefaultEmptyRuntimeDatabase;
+    use fluentbase_types::IntrinsicCost;
+
+    #[test]
+    fn test_chain_spec_rules() {
+        let chain_spec = ChainSpec {
+            intrinsic_cost: IntrinsicCostSchedule::new()
+                .with_activation(100, IntrinsicCost::ETHEREUM),
+            syscall_costs: BTreeMap::from([("_keccak256".to_string(), 100)]),
+            disabled_syscalls: vec!["_debug_log".to_string()],
+            ..ChainSpec::new("testnet", 1337)
+        }
+        .with_hardfork("cancun", 10);
+        chain_spec.validate().unwrap();
+        assert!(!chain_spec.is_active("cancun", 9));
+        assert!(chain_spec.is_active("cancun", 10));
+        assert!(!chain_spec.is_active("prague", u64::MAX));
+        assert_eq!(
+            chain_spec
+                .fuel_policy()
+                .unwrap()
+                .syscall_cost(SysFuncIdx::KECCAK256),
+            Fuel(100)
+        );
+        let bytecode_policy = chain_spec.bytecode_policy().unwrap().unwrap();
+        let allowed_imports = bytecode_policy.allowed_imports.unwrap();
+        assert!(allowed_imports
+            .contains(&("fluentbase_v1preview".to_string(), "_keccak256".to_string())));
+        assert!(!allowed_imports
+            .contains(&("fluentbase_v1preview".to_string(), "_debug_log".to_string())));
+
+        // disabled syscalls narrow down the policy of the context instead of replacing it
+        let import = |name: &str| ("fluentbase_v1preview".to_string(), name.to_string());
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::default().with_bytecode_policy(
+            BytecodePolicy::new()
+                .with_max_function_count(7)
+                .with_allowed_imports([import("_write"), import("_debug_log")]),
+        );
+        let bytecode_policy = chain_spec
+            .configure(ctx, 0)
+            .unwrap()
+            .bytecode_policy
+            .unwrap();
+        assert_eq!(bytecode_policy.max_function_count, 7);
+        assert_eq!(
+            bytecode_policy.allowed_imports,
+            Some([import("_write")].into_iter().collect())
+        );
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::default();
+        assert_eq!(
+            chain_spec.configure(ctx, 0).unwrap().bytecode_policy,
+            chain_spec.bytecode_policy().unwrap()
+        );
+
+        let chain_spec = ChainSpec {
+            disabled_syscalls: vec!["_unknown".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            chain_spec.validate(),
+            Err(ChainSpecError::UnknownSyscall("_unknown".to_string()))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_chain_spec_from_json() {
+        let chain_spec = ChainSpec::from_json(
+            r#"{
+                "name": "mainnet",
+                "chain_id": 20993,
+                "fuel_schedule": { "fuel_per_gas": 10 },
+                "code_size_limits": {
+                    "max_code_size": 24576,
+                    "max_init_code_size": 49152,
+                    "init_code_word": 2
+                },
+                "trie_backend": "merkle_patricia",
+                "hardforks": { "cancun": 0 }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(chain_spec.chain_id, 20993);
+        assert_eq!(chain_spec.fuel_schedule, FuelSchedule::new(10));
+        assert_eq!(chain_spec.code_size_limits, CodeSizeLimits::ETHEREUM);
+        assert_eq!(chain_spec.trie_backend, TrieBackend::MerklePatricia);
+        assert!(chain_spec.is_active("cancun", 0));
+        // absent fields have the default values
+        assert!(chain_spec.disabled_syscalls.is_empty());
+        assert_eq!(
+            ChainSpec::from_json(r#"{ "fuel_schedule": { "fuel_per_gas": 0 } }"#),
+            Err(ChainSpecError::ZeroFuelPerGas)
+        );
+    }
+}
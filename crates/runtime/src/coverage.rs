@@ -0,0 +1,222 @@
+use hashbrown::HashMap;
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Arc, RwLock},
+};
+
+/// Basic block (instruction sequence) of the instrumented function
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageBlock {
+    pub func_idx: u32,
+    /// Offset of the first block's instruction inside the original code section
+    pub code_offset: u32,
+}
+
+/// Information about all blocks of the instrumented module, block id is an index in the list
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoverageMap {
+    pub blocks: Vec<CoverageBlock>,
+    pub func_names: HashMap<u32, String>,
+}
+
+/// Counts hits of the instrumented blocks, collector can be shared between multiple executions
+/// to accumulate coverage of the whole test suite
+#[derive(Clone, Default)]
+pub struct CoverageCollector {
+    hits: Arc<RwLock<HashMap<u32, u64>>>,
+}
+
+impl CoverageCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn hit(&self, block_id: u32) {
+        *self.hits.write().unwrap().entry(block_id).or_default() += 1;
+    }
+
+    pub fn hits(&self, block_id: u32) -> u64 {
+        self.hits
+            .read()
+            .unwrap()
+            .get(&block_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns number of covered and total blocks
+    pub fn summary(&self, coverage_map: &CoverageMap) -> (usize, usize) {
+        let covered = (0..coverage_map.blocks.len() as u32)
+            .filter(|block_id| self.hits(*block_id) > 0)
+            .count();
+        (covered, coverage_map.blocks.len())
+    }
+
+    /// Exports lcov-like report, functions are keyed by their indices (or names if name section
+    /// is present). Line resolver maps code offsets to source lines (using DWARF info), if
+    /// it's not specified or line can't be resolved then code offset is used instead of line
+    pub fn to_lcov(
+        &self,
+        source_file: &str,
+        coverage_map: &CoverageMap,
+        line_resolver: Option<&dyn Fn(u32) -> Option<u32>>,
+    ) -> String {
+        let resolve_line = |code_offset: u32| {
+            line_resolver
+                .and_then(|resolver| resolver(code_offset))
+                .unwrap_or(code_offset)
+        };
+        let func_name = |func_idx: u32| {
+            coverage_map
+                .func_names
+                .get(&func_idx)
+                .cloned()
+                .unwrap_or_else(|| format!("func[{}]", func_idx))
+        };
+        // the first block of every function is the function entry
+        let mut funcs = BTreeMap::<u32, (u32, u64)>::new();
+        let mut lines = BTreeMap::<u32, u64>::new();
+        for (block_id, block) in coverage_map.blocks.iter().enumerate() {
+            let hits = self.hits(block_id as u32);
+            funcs
+                .entry(block.func_idx)
+                .or_insert((resolve_line(block.code_offset), hits));
+            *lines.entry(resolve_line(block.code_offset)).or_default() += hits;
+        }
+        let mut result = String::new();
+        writeln!(result, "TN:").unwrap();
+        writeln!(result, "SF:{}", source_file).unwrap();
+        for (func_idx, (line, _)) in funcs.iter() {
+            writeln!(result, "FN:{},{}", line, func_name(*func_idx)).unwrap();
+        }
+        for (func_idx, (_, hits)) in funcs.iter() {
+            writeln!(result, "FNDA:{},{}", hits, func_name(*func_idx)).unwrap();
+        }
+        writeln!(result, "FNF:{}", funcs.len()).unwrap();
+        writeln!(
+            result,
+            "FNH:{}",
+            funcs.values().filter(|(_, hits)| *hits > 0).count()
+        )
+        .unwrap();
+        for (line, hits) in lines.iter() {
+            writeln!(result, "DA:{},{}", line, hits).unwrap();
+        }
+        writeln!(result, "LF:{}", lines.len()).unwrap();
+        writeln!(
+            result,
+            "LH:{}",
+            lines.values().filter(|hits| **hits > 0).count()
+        )
+        .unwrap();
+        writeln!(result, "end_of_record").unwrap();
+        result
+    }
+}
+
+/// Injects `_coverage_hit(block_id)` at the beginning of every instruction sequence (function
+/// body, block, loop and if/else arms) of the local functions
+#[cfg(feature = "coverage")]
+pub fn instrument_coverage(wasm_binary: &[u8]) -> Result<(Vec<u8>, CoverageMap), String> {
+    use walrus::{
+        ir::{dfs_in_order, Call, Const, InstrSeq, InstrSeqId, Value, Visitor},
+        FunctionKind,
+        Module,
+        ValType,
+    };
+
+    #[derive(Default)]
+    struct InstrSeqCollector(Vec<InstrSeqId>);
+
+    impl<'instr> Visitor<'instr> for InstrSeqCollector {
+        fn start_instr_seq(&mut self, instr_seq: &'instr InstrSeq) {
+            self.0.push(instr_seq.id());
+        }
+    }
+
+    let mut module = Module::from_buffer(wasm_binary).map_err(|err| err.to_string())?;
+    let hit_type = module.types.add(&[ValType::I32], &[]);
+    let local_funcs = module
+        .funcs
+        .iter_local()
+        .map(|(id, _)| id)
+        .collect::<Vec<_>>();
+    let (hit_func, _) = module.add_import_func("fluentbase_v1preview", "_coverage_hit", hit_type);
+
+    let mut coverage_map = CoverageMap::default();
+    for id in local_funcs {
+        let func_idx = id.index() as u32;
+        let func = module.funcs.get_mut(id);
+        if let Some(name) = func.name.as_ref() {
+            coverage_map.func_names.insert(func_idx, name.clone());
+        }
+        let FunctionKind::Local(local_func) = &mut func.kind else {
+            continue;
+        };
+        let entry = local_func.entry_block();
+        let mut collector = InstrSeqCollector::default();
+        dfs_in_order(&mut collector, local_func, entry);
+        for instr_seq_id in collector.0 {
+            let instrs = &mut local_func.block_mut(instr_seq_id).instrs;
+            let block_id = coverage_map.blocks.len() as u32;
+            coverage_map.blocks.push(CoverageBlock {
+                func_idx,
+                code_offset: instrs
+                    .first()
+                    .map(|(_, loc)| loc.data())
+                    .unwrap_or_default(),
+            });
+            instrs.insert(
+                0,
+                (
+                    Const {
+                        value: Value::I32(block_id as i32),
+                    }
+                    .into(),
+                    Default::default(),
+                ),
+            );
+            instrs.insert(1, (Call { func: hit_func }.into(), Default::default()));
+        }
+    }
+    Ok((module.emit_wasm(), coverage_map))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coverage::{CoverageBlock, CoverageCollector, CoverageMap};
+    use hashbrown::HashMap;
+
+    #[test]
+    fn test_lcov_report() {
+        let coverage_map = CoverageMap {
+            blocks: vec![
+                CoverageBlock {
+                    func_idx: 0,
+                    code_offset: 10,
+                },
+                CoverageBlock {
+                    func_idx: 0,
+                    code_offset: 20,
+                },
+                CoverageBlock {
+                    func_idx: 1,
+                    code_offset: 30,
+                },
+            ],
+            func_names: HashMap::from([(0, "main".to_string())]),
+        };
+        let collector = CoverageCollector::new();
+        collector.hit(0);
+        collector.hit(0);
+        collector.hit(1);
+        assert_eq!(collector.summary(&coverage_map), (2, 3));
+        let report = collector.to_lcov("contract.wasm", &coverage_map, None);
+        assert!(report.contains("FN:10,main\n"));
+        assert!(report.contains("FNDA:2,main\n"));
+        assert!(report.contains("FNDA:0,func[1]\n"));
+        assert!(report.contains("DA:30,0\n"));
+        assert!(report.contains("LH:2\n"));
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{
+    instruction::{keccak256::SyscallKeccak256, poseidon::SyscallPoseidon},
+    types::RuntimeError,
+    BytecodeOrHash,
+    ExecutionResult,
+    Runtime,
+    RuntimeContext,
+};
+use byteorder::{ByteOrder, LittleEndian};
+use fluentbase_types::{
+    Address,
+    ExitCode,
+    Fuel,
+    Gas,
+    IJournaledTrie,
+    F254,
+    JZKT_ACCOUNT_COMPRESSION_FLAGS,
+    JZKT_ACCOUNT_FIELDS_COUNT,
+    JZKT_ACCOUNT_NONCE_FIELD,
+    JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+    JZKT_ACCOUNT_RWASM_CODE_SIZE_FIELD,
+    JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD,
+    JZKT_ACCOUNT_SOURCE_CODE_SIZE_FIELD,
+    POSEIDON_EMPTY,
+    STATE_DEPLOY,
+};
+
+/// Max size of the deployed bytecode (EIP-170)
+pub const MAX_CODE_SIZE: usize = 0x6000;
+
+/// Max size of the init bytecode (EIP-3860)
+pub const MAX_INIT_CODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+
+/// Size limits of the deployment, chains pick one of the presets or their own values. Too large
+/// init code fails with `InitCodeSizeLimit` before the execution (all the fuel is consumed) and
+/// too large deployed code fails with `ContractSizeLimit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CodeSizeLimits {
+    pub max_code_size: usize,
+    pub max_init_code_size: usize,
+    /// Fuel charged for every 32-byte word of the init code before the execution
+    pub init_code_word: Fuel,
+}
+
+impl Default for CodeSizeLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl CodeSizeLimits {
+    /// Deployed code is limited (EIP-170), init code isn't limited nor charged
+    pub const DEFAULT: Self = Self::new(MAX_CODE_SIZE, usize::MAX, 0);
+
+    /// Limits of Ethereum (EIP-170 and EIP-3860), assuming one fuel per gas
+    pub const ETHEREUM: Self = Self::new(MAX_CODE_SIZE, MAX_INIT_CODE_SIZE, 2);
+
+    pub const fn new(max_code_size: usize, max_init_code_size: usize, init_code_word: u64) -> Self {
+        Self {
+            max_code_size,
+            max_init_code_size,
+            init_code_word: Fuel(init_code_word),
+        }
+    }
+
+    /// Fuel charged for the init code, partial word is charged in full, saturates at `Fuel::MAX`
+    pub const fn init_code_fuel(&self, init_code_size: usize) -> Fuel {
+        Fuel((init_code_size.div_ceil(32) as u64).saturating_mul(self.init_code_word.0))
+    }
+}
+
+#[derive(Clone)]
+pub struct DeployResult {
+    pub address: Address,
+    pub rwasm_code_hash: F254,
+    pub gas_used: Gas,
+    pub execution_result: ExecutionResult,
+}
+
+impl DeployResult {
+    pub fn is_ok(&self) -> bool {
+        self.execution_result.exit_code == ExitCode::Ok.into_i32()
+    }
+}
+
+impl<DB: IJournaledTrie> Runtime<DB> {
+    /// Runs init bytecode in the deploy state and stores returned bytecode under the address
+    /// derived from the deployer and its nonce.
+    ///
+    /// The deployment is atomic, if init code fails or returned bytecode violates code size limit
+    /// then all journal changes made since the deployment started are rolled back. Returned
+    /// bytecode is validated against the bytecode policy (if any), a violation fails the
+    /// deployment with the exit code of the violation and the fuel spent by the init code is
+    /// still charged. Intrinsic cost of the deployed code is charged after the init code, the
+    /// deployment fails with `OutOfGas` if there is not enough fuel left.
+    ///
+    /// Init code (if it's passed as bytecode) is checked against the code size limits of the
+    /// context and charged per word before the execution.
+    pub fn deploy(
+        runtime_context: RuntimeContext<DB>,
+        deployer: &Address,
+        nonce: u64,
+    ) -> Result<DeployResult, RuntimeError> {
+        let address = deployer.create(nonce);
+        let checkpoint = runtime_context
+            .jzkt
+            .as_ref()
+            .map(|jzkt| jzkt.checkpoint())
+            .ok_or(RuntimeError::StorageError(
+                "jzkt is not initialized".to_string(),
+            ))?;
+        let mut runtime = Self::new(runtime_context.with_state(STATE_DEPLOY));
+        let code_size_limits = runtime.data().code_size_limits;
+        if let BytecodeOrHash::Bytecode(init_code, _) = &runtime.data().bytecode {
+            let init_code_size = init_code.len();
+            let exit_code = if init_code_size > code_size_limits.max_init_code_size {
+                Some(ExitCode::InitCodeSizeLimit)
+            } else if !runtime
+                .consume_intrinsic_fuel(code_size_limits.init_code_fuel(init_code_size))
+            {
+                Some(ExitCode::OutOfGas)
+            } else {
+                None
+            };
+            if let Some(exit_code) = exit_code {
+                let jzkt = runtime.data().jzkt.as_ref().unwrap();
+                jzkt.release_checkpoint(checkpoint);
+                let fuel_limit = runtime.data().fuel_limit;
+                let mut execution_result = ExecutionResult::new_error(exit_code.into_i32());
+                execution_result.fuel_consumed = fuel_limit;
+                return Ok(DeployResult {
+                    address,
+                    rwasm_code_hash: POSEIDON_EMPTY,
+                    gas_used: runtime.data().fuel_schedule.fuel_to_gas(fuel_limit),
+                    execution_result,
+                });
+            }
+        }
+        let mut execution_result = match runtime.call() {
+            Ok(execution_result) => execution_result,
+            Err(err) => {
+                let jzkt = runtime.data().jzkt.as_ref().unwrap();
+                jzkt.rollback(checkpoint);
+                jzkt.release_checkpoint(checkpoint);
+                return Err(err);
+            }
+        };
+
+        if execution_result.exit_code == ExitCode::Ok.into_i32() {
+            if let Err(err) = runtime.data().verify_bytecode(&execution_result.output) {
+                // fuel spent by the init code is still charged
+                execution_result.exit_code = err.exit_code().into_i32();
+                execution_result.output.clear();
+            }
+        }
+        if execution_result.exit_code == ExitCode::Ok.into_i32() {
+            // deployed code is charged on top of the init code execution
+            let code_fuel = runtime
+                .data()
+                .intrinsic_cost
+                .code_fuel(execution_result.output.len());
+            if runtime.consume_intrinsic_fuel(code_fuel) {
+                execution_result.fuel_consumed =
+                    Fuel(runtime.store.fuel_consumed().unwrap_or_default());
+            } else {
+                execution_result.exit_code = ExitCode::OutOfGas.into_i32();
+                execution_result.fuel_consumed = runtime.data().fuel_limit;
+                execution_result.output.clear();
+            }
+        }
+        let jzkt = runtime.data().jzkt.as_ref().unwrap();
+        let gas_used = runtime
+            .data()
+            .fuel_schedule
+            .fuel_to_gas(execution_result.fuel_consumed);
+        let result = if execution_result.exit_code != ExitCode::Ok.into_i32() {
+            Err(ExitCode::from(execution_result.exit_code))
+        } else {
+            Self::write_deployed_bytecode(
+                jzkt,
+                &address,
+                &execution_result.output,
+                code_size_limits.max_code_size,
+            )
+        };
+        let rwasm_code_hash = match result {
+            Ok(rwasm_code_hash) => rwasm_code_hash,
+            Err(exit_code) => {
+                jzkt.rollback(checkpoint);
+                execution_result.exit_code = exit_code.into_i32();
+                execution_result.logs.clear();
+                POSEIDON_EMPTY
+            }
+        };
+        jzkt.release_checkpoint(checkpoint);
+        Ok(DeployResult {
+            address,
+            rwasm_code_hash,
+            gas_used,
+            execution_result,
+        })
+    }
+
+    fn write_deployed_bytecode(
+        jzkt: &DB,
+        address: &Address,
+        bytecode: &[u8],
+        max_code_size: usize,
+    ) -> Result<F254, ExitCode> {
+        if bytecode.len() > max_code_size {
+            return Err(ExitCode::ContractSizeLimit);
+        }
+        let address32 = address.into_word();
+        // account can't be created over the existing contract or account with non-zero nonce
+        let mut fields = match jzkt.get(&address32, false) {
+            Some((fields, _, _)) => {
+                let nonce = LittleEndian::read_u64(&fields[JZKT_ACCOUNT_NONCE_FIELD as usize]);
+                let rwasm_code_hash = fields[JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD as usize];
+                if nonce != 0
+                    || (rwasm_code_hash != [0u8; 32] && rwasm_code_hash != POSEIDON_EMPTY.0)
+                {
+                    return Err(ExitCode::CreateCollision);
+                }
+                fields
+            }
+            None => vec![[0u8; 32]; JZKT_ACCOUNT_FIELDS_COUNT as usize],
+        };
+        let rwasm_code_hash = SyscallPoseidon::fn_impl(bytecode);
+        let source_code_hash = SyscallKeccak256::fn_impl(bytecode);
+        // new contracts start with nonce 1 (EIP-161)
+        LittleEndian::write_u64(&mut fields[JZKT_ACCOUNT_NONCE_FIELD as usize], 1);
+        LittleEndian::write_u64(
+            &mut fields[JZKT_ACCOUNT_SOURCE_CODE_SIZE_FIELD as usize],
+            bytecode.len() as u64,
+        );
+        fields[JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD as usize].copy_from_slice(&source_code_hash);
+        LittleEndian::write_u64(
+            &mut fields[JZKT_ACCOUNT_RWASM_CODE_SIZE_FIELD as usize],
+            bytecode.len() as u64,
+        );
+        fields[JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD as usize].copy_from_slice(&rwasm_code_hash);
+        jzkt.update(&address32, &fields, JZKT_ACCOUNT_COMPRESSION_FLAGS);
+        jzkt.update_preimage(&address32, JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD, bytecode);
+        jzkt.update_preimage(&address32, JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD, bytecode);
+        Ok(F254::from(rwasm_code_hash))
+    }
+}
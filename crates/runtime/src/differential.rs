@@ -0,0 +1,359 @@
+use crate::{types::RuntimeError, DefaultEmptyRuntimeDatabase, Runtime, RuntimeContext};
+use fluentbase_types::{
+    create_sovereign_import_linker,
+    ExitCode,
+    Fuel,
+    SysFuncIdx::STATE,
+    STATE_DEPLOY,
+    STATE_MAIN,
+};
+use rwasm::{
+    engine::{bytecode::Instruction, RwasmConfig, StateRouterConfig},
+    rwasm::{BinaryFormat, RwasmModule},
+};
+use wasmtime::{Caller, Engine, Linker, Module, Store, Trap};
+
+/// Result of the execution that is compared between engines
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DifferentialOutcome {
+    pub exit_code: i32,
+    pub output: Vec<u8>,
+    pub memory: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DifferentialMismatch {
+    ExitCode {
+        rwasm: i32,
+        wasmtime: i32,
+    },
+    Output {
+        rwasm: Vec<u8>,
+        wasmtime: Vec<u8>,
+    },
+    /// Offset of the first byte that differs
+    Memory {
+        offset: usize,
+    },
+}
+
+#[derive(Debug)]
+pub enum DifferentialError {
+    Rwasm(RuntimeError),
+    Wasmtime(wasmtime::Error),
+    Translation(String),
+}
+
+/// Runs the same WASM module using rWASM runtime and wasmtime and compares exit codes, outputs
+/// and first `memory_len` bytes of the memory. Wasmtime uses host stubs only for basic system
+/// calls (`_exit`, `_write`, `_input_size`, `_read`), so module must not use state-related calls.
+pub struct DifferentialExecutor {
+    wasm_binary: Vec<u8>,
+    input: Vec<u8>,
+    fuel_limit: Fuel,
+    memory_len: u32,
+}
+
+impl DifferentialExecutor {
+    pub fn new(wasm_binary: Vec<u8>) -> Self {
+        Self {
+            wasm_binary,
+            input: vec![],
+            fuel_limit: Fuel(10_000_000),
+            memory_len: 0,
+        }
+    }
+
+    pub fn with_input(mut self, input: Vec<u8>) -> Self {
+        self.input = input;
+        self
+    }
+
+    pub fn with_fuel_limit(mut self, fuel_limit: impl Into<Fuel>) -> Self {
+        self.fuel_limit = fuel_limit.into();
+        self
+    }
+
+    /// Number of memory bytes (starting from zero offset) to compare, module must export memory
+    /// as `memory` if it's not zero
+    pub fn with_memory_len(mut self, memory_len: u32) -> Self {
+        self.memory_len = memory_len;
+        self
+    }
+
+    pub fn run_rwasm(&self) -> Result<DifferentialOutcome, DifferentialError> {
+        let rwasm_binary = wasm2rwasm(&self.wasm_binary)?;
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+            .with_input(self.input.clone())
+            .with_fuel_limit(self.fuel_limit);
+        let mut runtime = Runtime::new(ctx);
+        let execution_result = runtime.call().map_err(DifferentialError::Rwasm)?;
+        let memory = if self.memory_len > 0 {
+            runtime
+                .read_memory(0, self.memory_len)
+                .map_err(DifferentialError::Rwasm)?
+        } else {
+            vec![]
+        };
+        Ok(DifferentialOutcome {
+            exit_code: execution_result.exit_code,
+            output: execution_result.output,
+            memory,
+        })
+    }
+
+    pub fn run_wasmtime(&self) -> Result<DifferentialOutcome, DifferentialError> {
+        let engine = Engine::default();
+        let module =
+            Module::new(&engine, &self.wasm_binary).map_err(DifferentialError::Wasmtime)?;
+        let mut linker = Linker::<WasmtimeHostState>::new(&engine);
+        wasmtime_register_stubs(&mut linker).map_err(DifferentialError::Wasmtime)?;
+        let mut store = Store::new(
+            &engine,
+            WasmtimeHostState {
+                input: self.input.clone(),
+                ..Default::default()
+            },
+        );
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .map_err(DifferentialError::Wasmtime)?;
+        let main = instance
+            .get_typed_func::<(), ()>(&mut store, "main")
+            .map_err(DifferentialError::Wasmtime)?;
+        let exit_code = match main.call(&mut store, ()) {
+            Ok(_) => store.data().exit_code,
+            Err(_) if store.data().exited => store.data().exit_code,
+            Err(err) => match err.downcast_ref::<Trap>() {
+                Some(trap) => wasmtime_trap_to_exit_code(trap).into_i32(),
+                None => return Err(DifferentialError::Wasmtime(err)),
+            },
+        };
+        let memory =
+            if self.memory_len > 0 {
+                let memory = instance.get_memory(&mut store, "memory").ok_or(
+                    DifferentialError::Translation("missing memory export".to_string()),
+                )?;
+                let mut buffer = vec![0u8; self.memory_len as usize];
+                memory
+                    .read(&store, 0, &mut buffer)
+                    .map_err(|err| DifferentialError::Wasmtime(err.into()))?;
+                buffer
+            } else {
+                vec![]
+            };
+        Ok(DifferentialOutcome {
+            exit_code,
+            output: store.data().output.clone(),
+            memory,
+        })
+    }
+
+    /// Executes module using both engines and returns all found divergences
+    pub fn run(&self) -> Result<Vec<DifferentialMismatch>, DifferentialError> {
+        let rwasm = self.run_rwasm()?;
+        let wasmtime = self.run_wasmtime()?;
+        Ok(diff_outcomes(&rwasm, &wasmtime))
+    }
+}
+
+pub fn diff_outcomes(
+    rwasm: &DifferentialOutcome,
+    wasmtime: &DifferentialOutcome,
+) -> Vec<DifferentialMismatch> {
+    let mut result = Vec::new();
+    if rwasm.exit_code != wasmtime.exit_code {
+        result.push(DifferentialMismatch::ExitCode {
+            rwasm: rwasm.exit_code,
+            wasmtime: wasmtime.exit_code,
+        });
+    }
+    if rwasm.output != wasmtime.output {
+        result.push(DifferentialMismatch::Output {
+            rwasm: rwasm.output.clone(),
+            wasmtime: wasmtime.output.clone(),
+        });
+    }
+    // memory after trap is not consensus critical
+    if rwasm.exit_code == ExitCode::Ok.into_i32() {
+        if let Some(offset) = rwasm
+            .memory
+            .iter()
+            .zip(wasmtime.memory.iter())
+            .position(|(a, b)| a != b)
+        {
+            result.push(DifferentialMismatch::Memory { offset });
+        } else if rwasm.memory.len() != wasmtime.memory.len() {
+            result.push(DifferentialMismatch::Memory {
+                offset: rwasm.memory.len().min(wasmtime.memory.len()),
+            });
+        }
+    }
+    result
+}
+
+fn wasm2rwasm(wasm_binary: &[u8]) -> Result<Vec<u8>, DifferentialError> {
+    let import_linker = Runtime::new_sovereign_linker();
+    let mut rwasm_config = RwasmModule::default_config(Some(import_linker));
+    rwasm_config.rwasm_config(RwasmConfig {
+        state_router: Some(StateRouterConfig {
+            states: Box::new([
+                ("deploy".to_string(), STATE_DEPLOY),
+                ("main".to_string(), STATE_MAIN),
+            ]),
+            opcode: Instruction::Call(STATE.into()),
+        }),
+        entrypoint_name: None,
+        import_linker: Some(create_sovereign_import_linker()),
+        wrap_import_functions: true,
+    });
+    let rwasm_module = RwasmModule::compile_with_config(wasm_binary, &rwasm_config)
+        .map_err(|err| DifferentialError::Translation(format!("{:?}", err)))?;
+    let mut result = Vec::new();
+    rwasm_module
+        .write_binary_to_vec(&mut result)
+        .map_err(|err| DifferentialError::Translation(format!("{:?}", err)))?;
+    Ok(result)
+}
+
+#[derive(Default)]
+struct WasmtimeHostState {
+    input: Vec<u8>,
+    output: Vec<u8>,
+    exit_code: i32,
+    exited: bool,
+}
+
+fn wasmtime_memory(
+    caller: &mut Caller<'_, WasmtimeHostState>,
+) -> wasmtime::Result<wasmtime::Memory> {
+    caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| wasmtime::Error::msg("missing memory export"))
+}
+
+fn wasmtime_register_stubs(linker: &mut Linker<WasmtimeHostState>) -> wasmtime::Result<()> {
+    linker.func_wrap(
+        "fluentbase_v1preview",
+        "_exit",
+        |mut caller: Caller<'_, WasmtimeHostState>, exit_code: i32| -> wasmtime::Result<()> {
+            caller.data_mut().exit_code = exit_code;
+            caller.data_mut().exited = true;
+            Err(wasmtime::Error::msg("exit"))
+        },
+    )?;
+    linker.func_wrap(
+        "fluentbase_v1preview",
+        "_write",
+        |mut caller: Caller<'_, WasmtimeHostState>,
+         offset: u32,
+         length: u32|
+         -> wasmtime::Result<()> {
+            let memory = wasmtime_memory(&mut caller)?;
+            let mut buffer = vec![0u8; length as usize];
+            memory.read(&caller, offset as usize, &mut buffer)?;
+            caller.data_mut().output.extend_from_slice(&buffer);
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        "fluentbase_v1preview",
+        "_input_size",
+        |caller: Caller<'_, WasmtimeHostState>| -> u32 { caller.data().input.len() as u32 },
+    )?;
+    linker.func_wrap(
+        "fluentbase_v1preview",
+        "_read",
+        |mut caller: Caller<'_, WasmtimeHostState>,
+         target: u32,
+         offset: u32,
+         length: u32|
+         -> wasmtime::Result<()> {
+            let input = caller
+                .data()
+                .input
+                .get(offset as usize..(offset + length) as usize)
+                .ok_or(Trap::MemoryOutOfBounds)?
+                .to_vec();
+            let memory = wasmtime_memory(&mut caller)?;
+            memory.write(&mut caller, target as usize, &input)?;
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+fn wasmtime_trap_to_exit_code(trap: &Trap) -> ExitCode {
+    match trap {
+        Trap::UnreachableCodeReached => ExitCode::UnreachableCodeReached,
+        Trap::MemoryOutOfBounds => ExitCode::MemoryOutOfBounds,
+        Trap::TableOutOfBounds => ExitCode::TableOutOfBounds,
+        Trap::IndirectCallToNull => ExitCode::IndirectCallToNull,
+        Trap::IntegerDivisionByZero => ExitCode::IntegerDivisionByZero,
+        Trap::IntegerOverflow => ExitCode::IntegerOverflow,
+        Trap::BadConversionToInteger => ExitCode::BadConversionToInteger,
+        Trap::StackOverflow => ExitCode::StackOverflow,
+        Trap::BadSignature => ExitCode::BadSignature,
+        Trap::OutOfFuel => ExitCode::OutOfGas,
+        _ => ExitCode::UnknownError,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::differential::DifferentialExecutor;
+
+    #[test]
+    fn test_differential_echo() {
+        let wasm_binary = wat::parse_str(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (param i32 i32 i32)))
+  (type (;2;) (func (result i32)))
+  (type (;3;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_read" (func $_read (type 1)))
+  (import "fluentbase_v1preview" "_input_size" (func $_input_size (type 2)))
+  (func $main (type 3)
+    i32.const 100
+    i32.const 0
+    call $_input_size
+    call $_read
+    i32.const 100
+    call $_input_size
+    call $_write
+    )
+  (memory (;0;) 1)
+  (export "memory" (memory 0))
+  (export "main" (func $main)))
+    "#,
+        )
+        .unwrap();
+        let executor = DifferentialExecutor::new(wasm_binary)
+            .with_input("Hello, World".as_bytes().to_vec())
+            .with_memory_len(256);
+        let mismatches = executor.run().unwrap();
+        assert!(mismatches.is_empty(), "divergence found: {:?}", mismatches);
+    }
+
+    #[test]
+    fn test_differential_trap() {
+        let wasm_binary = wat::parse_str(
+            r#"
+(module
+  (func $main
+    i32.const 1
+    i32.const 0
+    i32.div_u
+    drop
+    )
+  (export "main" (func $main)))
+    "#,
+        )
+        .unwrap();
+        let mismatches = DifferentialExecutor::new(wasm_binary).run().unwrap();
+        assert!(mismatches.is_empty(), "divergence found: {:?}", mismatches);
+    }
+}
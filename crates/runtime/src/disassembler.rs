@@ -0,0 +1,156 @@
+use crate::types::RuntimeError;
+use fluentbase_types::{create_sovereign_import_linker, SysFuncIdx};
+use rwasm::{engine::bytecode::Instruction, rwasm::RwasmModule};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisassembledInstr {
+    /// Position of the instruction inside the code section
+    pub pc: usize,
+    pub instr: Instruction,
+    /// Resolved name of the imported (system) function for call instructions
+    pub import_name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisassembledFunction {
+    pub func_idx: u32,
+    pub instrs: Vec<DisassembledInstr>,
+}
+
+/// Human-readable representation of the rWASM module: instructions split by function
+/// boundaries, resolved import names and segments info
+#[derive(Debug, Clone, PartialEq)]
+pub struct RwasmDisassembly {
+    pub functions: Vec<DisassembledFunction>,
+    pub memory_section: Vec<u8>,
+    pub element_section: Vec<u32>,
+}
+
+/// Returns name of the system function (like `fluentbase_v1preview::_write`)
+pub fn resolve_import_name(func_idx: u32) -> Option<String> {
+    let import_linker: Vec<(&'static str, &'static str, u32, u32)> =
+        create_sovereign_import_linker();
+    import_linker
+        .iter()
+        .find(|(_, _, idx, _)| *idx == func_idx)
+        .map(|(module, name, _, _)| format!("{}::{}", module, name))
+        .or_else(|| SysFuncIdx::from_repr(func_idx).map(|idx| format!("{:?}", idx)))
+}
+
+impl RwasmDisassembly {
+    pub fn from_bytecode(rwasm_bytecode: &[u8]) -> Result<Self, RuntimeError> {
+        let rwasm_module = RwasmModule::new(rwasm_bytecode).map_err(Into::<RuntimeError>::into)?;
+        Ok(Self::from_module(&rwasm_module))
+    }
+
+    pub fn from_module(rwasm_module: &RwasmModule) -> Self {
+        let instrs = rwasm_module.code_section.instr.clone();
+        // function section stores length of each function (in instructions), the rest of the
+        // code (if any) belongs to the entrypoint
+        let mut functions = Vec::new();
+        let mut pc = 0usize;
+        let mut lengths = rwasm_module
+            .func_section
+            .iter()
+            .map(|len| *len as usize)
+            .collect::<Vec<_>>();
+        let total_len = lengths.iter().sum::<usize>();
+        if total_len < instrs.len() {
+            lengths.push(instrs.len() - total_len);
+        }
+        for (func_idx, len) in lengths.into_iter().enumerate() {
+            let end = (pc + len).min(instrs.len());
+            let instrs = instrs[pc..end]
+                .iter()
+                .enumerate()
+                .map(|(i, instr)| DisassembledInstr {
+                    pc: pc + i,
+                    instr: *instr,
+                    import_name: match instr {
+                        Instruction::Call(func_idx) => resolve_import_name(func_idx.to_u32()),
+                        _ => None,
+                    },
+                })
+                .collect();
+            functions.push(DisassembledFunction {
+                func_idx: func_idx as u32,
+                instrs,
+            });
+            pc = end;
+        }
+        Self {
+            functions,
+            memory_section: rwasm_module.memory_section.clone(),
+            element_section: rwasm_module.element_section.clone(),
+        }
+    }
+
+    pub fn instr_count(&self) -> usize {
+        self.functions.iter().map(|func| func.instrs.len()).sum()
+    }
+}
+
+impl Display for RwasmDisassembly {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            ";; functions: {}, instructions: {}, memory section: {} bytes, element section: {} items",
+            self.functions.len(),
+            self.instr_count(),
+            self.memory_section.len(),
+            self.element_section.len(),
+        )?;
+        for func in self.functions.iter() {
+            writeln!(f)?;
+            writeln!(f, "func[{}]:", func.func_idx)?;
+            for instr in func.instrs.iter() {
+                match &instr.import_name {
+                    Some(import_name) => {
+                        writeln!(f, "  {:04}: {:?} ;; {}", instr.pc, instr.instr, import_name)?
+                    }
+                    None => writeln!(f, "  {:04}: {:?}", instr.pc, instr.instr)?,
+                }
+            }
+        }
+        if !self.memory_section.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "memory: 0x{}", hex::encode(&self.memory_section))?;
+        }
+        if !self.element_section.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "elements: {:?}", self.element_section)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{disassembler::RwasmDisassembly, tests::wat2rwasm};
+
+    #[test]
+    fn test_disassemble_with_import_names() {
+        let rwasm_binary = wat2rwasm(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 12
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "Hello, World")
+  (export "main" (func $main)))
+    "#,
+        );
+        let disassembly = RwasmDisassembly::from_bytecode(&rwasm_binary).unwrap();
+        assert!(disassembly.instr_count() > 0);
+        let listing = disassembly.to_string();
+        assert!(listing.contains("fluentbase_v1preview::_write"));
+        assert!(listing.contains("func[0]:"));
+    }
+}
@@ -0,0 +1,237 @@
+use byteorder::{ByteOrder, LittleEndian};
+use fluentbase_types::{ExitCode, F254};
+use std::sync::Arc;
+
+/// Magic prefix of the encoded input envelope
+pub const INPUT_ENVELOPE_MAGIC: [u8; 4] = *b"FBIE";
+pub const INPUT_ENVELOPE_FORMAT_VERSION: u32 = 1;
+
+const REVEAL_ACCESS_ANYONE: u8 = 0x00;
+const REVEAL_ACCESS_CODE_HASHES: u8 = 0x01;
+
+/// Host-side decryption of the sealed input sections, it's the integration point of threshold
+/// decryption services and TEEs. The runtime never sees the keys, it only forwards the
+/// ciphertext of the section requested by an authorized contract.
+pub trait InputDecryptor: Send + Sync {
+    /// Returns the plaintext of the section or `None` if it can't be decrypted
+    fn decrypt(&self, section: u32, ciphertext: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// Contracts that can request the plaintext of the sealed section, the access must be granted
+/// explicitly, so an empty list of code hashes authorizes nobody
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevealAccess {
+    /// Any contract of the call can reveal the section
+    Anyone,
+    /// Only contracts with the listed rWASM code hashes can reveal the section
+    CodeHashes(Vec<F254>),
+}
+
+/// Encrypted part of the input with the contracts allowed to reveal it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SealedSection {
+    pub ciphertext: Vec<u8>,
+    pub access: RevealAccess,
+}
+
+impl SealedSection {
+    pub fn is_authorized(&self, code_hash: &F254) -> bool {
+        match &self.access {
+            RevealAccess::Anyone => true,
+            RevealAccess::CodeHashes(code_hashes) => code_hashes.contains(code_hash),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnvelopeError {
+    BadMagic,
+    UnsupportedVersion(u32),
+    UnknownAccess(u8),
+    UnexpectedEof,
+    TrailingBytes,
+}
+
+/// Input of the call with sealed sections, the plain input is available through the regular
+/// input syscalls and sections are revealed by the `_input_reveal` syscall
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InputEnvelope {
+    pub input: Vec<u8>,
+    pub sections: Vec<SealedSection>,
+}
+
+impl InputEnvelope {
+    pub fn new(input: Vec<u8>) -> Self {
+        Self {
+            input,
+            sections: vec![],
+        }
+    }
+
+    /// Appends the section, sections are addressed by the index in the order of appending
+    pub fn with_section(mut self, ciphertext: Vec<u8>, access: RevealAccess) -> Self {
+        self.sections.push(SealedSection { ciphertext, access });
+        self
+    }
+
+    /// Encodes the envelope as `magic || version || input || sections_count || (ciphertext ||
+    /// access)*` with little-endian u32 counts and length-prefixed buffers, the access is either
+    /// `0x00` (anyone) or `0x01 || code_hashes_count || code_hash*`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = INPUT_ENVELOPE_MAGIC.to_vec();
+        write_u32(&mut buffer, INPUT_ENVELOPE_FORMAT_VERSION);
+        write_bytes(&mut buffer, &self.input);
+        write_u32(&mut buffer, self.sections.len() as u32);
+        for section in self.sections.iter() {
+            write_bytes(&mut buffer, &section.ciphertext);
+            match &section.access {
+                RevealAccess::Anyone => buffer.push(REVEAL_ACCESS_ANYONE),
+                RevealAccess::CodeHashes(code_hashes) => {
+                    buffer.push(REVEAL_ACCESS_CODE_HASHES);
+                    write_u32(&mut buffer, code_hashes.len() as u32);
+                    code_hashes
+                        .iter()
+                        .for_each(|code_hash| buffer.extend_from_slice(code_hash.as_slice()));
+                }
+            }
+        }
+        buffer
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EnvelopeError> {
+        let mut reader = EnvelopeReader { bytes, offset: 0 };
+        if reader.read_slice(4)? != INPUT_ENVELOPE_MAGIC {
+            return Err(EnvelopeError::BadMagic);
+        }
+        let version = reader.read_u32()?;
+        if version != INPUT_ENVELOPE_FORMAT_VERSION {
+            return Err(EnvelopeError::UnsupportedVersion(version));
+        }
+        let mut envelope = Self::new(reader.read_bytes()?);
+        for _ in 0..reader.read_u32()? {
+            let ciphertext = reader.read_bytes()?;
+            let access = match reader.read_slice(1)?[0] {
+                REVEAL_ACCESS_ANYONE => RevealAccess::Anyone,
+                REVEAL_ACCESS_CODE_HASHES => RevealAccess::CodeHashes(
+                    (0..reader.read_u32()?)
+                        .map(|_| Ok(F254::from_slice(reader.read_slice(32)?)))
+                        .collect::<Result<Vec<_>, _>>()?,
+                ),
+                tag => return Err(EnvelopeError::UnknownAccess(tag)),
+            };
+            envelope = envelope.with_section(ciphertext, access);
+        }
+        if reader.offset != bytes.len() {
+            return Err(EnvelopeError::TrailingBytes);
+        }
+        Ok(envelope)
+    }
+}
+
+fn write_u32(buffer: &mut Vec<u8>, value: u32) {
+    let mut bytes = [0u8; 4];
+    LittleEndian::write_u32(&mut bytes, value);
+    buffer.extend_from_slice(&bytes);
+}
+
+fn write_bytes(buffer: &mut Vec<u8>, value: &[u8]) {
+    write_u32(buffer, value.len() as u32);
+    buffer.extend_from_slice(value);
+}
+
+struct EnvelopeReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> EnvelopeReader<'a> {
+    fn read_slice(&mut self, length: usize) -> Result<&'a [u8], EnvelopeError> {
+        let result = self
+            .bytes
+            .get(self.offset..self.offset + length)
+            .ok_or(EnvelopeError::UnexpectedEof)?;
+        self.offset += length;
+        Ok(result)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, EnvelopeError> {
+        Ok(LittleEndian::read_u32(self.read_slice(4)?))
+    }
+
+    fn read_bytes(&mut self) -> Result<Vec<u8>, EnvelopeError> {
+        let length = self.read_u32()? as usize;
+        Ok(self.read_slice(length)?.to_vec())
+    }
+}
+
+/// Sealed sections of the call with the decryptor, nested calls share them, so the contract
+/// that is authorized to reveal a section can be called through routers
+#[derive(Clone)]
+pub struct SealedInputs {
+    sections: Arc<Vec<SealedSection>>,
+    decryptor: Arc<dyn InputDecryptor>,
+}
+
+impl SealedInputs {
+    pub fn new(sections: Vec<SealedSection>, decryptor: Arc<dyn InputDecryptor>) -> Self {
+        Self {
+            sections: Arc::new(sections),
+            decryptor,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// Decrypts the section for the contract with the code hash. Unknown sections fail with
+    /// `InputOutOfBounds`, unauthorized contracts with `CapabilityDenied` and sections the
+    /// decryptor can't decrypt with `DecryptionFailed`
+    pub fn reveal(&self, section: u32, code_hash: &F254) -> Result<Vec<u8>, ExitCode> {
+        let sealed_section = self
+            .sections
+            .get(section as usize)
+            .ok_or(ExitCode::InputOutOfBounds)?;
+        if !sealed_section.is_authorized(code_hash) {
+            return Err(ExitCode::CapabilityDenied);
+        }
+        self.decryptor
+            .decrypt(section, &sealed_section.ciphertext)
+            .ok_or(ExitCode::DecryptionFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_envelope_codec() {
+        let envelope = InputEnvelope::new(b"plain".to_vec())
+            .with_section(b"sealed1".to_vec(), RevealAccess::Anyone)
+            .with_section(
+                b"sealed2".to_vec(),
+                RevealAccess::CodeHashes(vec![F254::repeat_byte(1)]),
+            );
+        let bytes = envelope.to_bytes();
+        assert_eq!(InputEnvelope::from_bytes(&bytes), Ok(envelope));
+        assert_eq!(
+            InputEnvelope::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(EnvelopeError::UnexpectedEof)
+        );
+        assert_eq!(
+            InputEnvelope::from_bytes(b"FBER"),
+            Err(EnvelopeError::BadMagic)
+        );
+        // empty list of code hashes doesn't authorize anyone
+        let section = SealedSection {
+            ciphertext: vec![],
+            access: RevealAccess::CodeHashes(vec![]),
+        };
+        assert!(!section.is_authorized(&F254::repeat_byte(1)));
+    }
+}
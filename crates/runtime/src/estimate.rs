@@ -0,0 +1,93 @@
+use crate::{types::RuntimeError, ExecutionResult, Runtime, RuntimeContext};
+use fluentbase_types::{ExitCode, Fuel, IJournaledTrie};
+
+/// Fuel cap used for the estimation if context doesn't specify fuel limit
+pub const DEFAULT_FUEL_ESTIMATION_CAP: Fuel = Fuel(30_000_000);
+
+/// Returns max fuel that can be forwarded into the nested call, the caller always keeps one 64th
+/// of the remaining fuel (EIP-150)
+pub fn max_forwarded_fuel(fuel_remaining: Fuel) -> Fuel {
+    Fuel(fuel_remaining.get() - fuel_remaining.get() / 64)
+}
+
+/// Returns fuel limit of the nested call, an explicit limit is capped by the max forwarded fuel
+/// and zero limit requests all forwardable fuel
+pub fn nested_call_fuel_limit(requested_fuel: Fuel, fuel_remaining: Fuel) -> Fuel {
+    let max_fuel = max_forwarded_fuel(fuel_remaining);
+    if requested_fuel.is_zero() {
+        max_fuel
+    } else {
+        requested_fuel.min(max_fuel)
+    }
+}
+
+impl<DB: IJournaledTrie + Clone> Runtime<DB> {
+    /// Finds the minimal fuel limit required for the successful execution of the context (the
+    /// same as `eth_estimateGas`), fuel limit of the context is used as an upper bound.
+    ///
+    /// Every attempt is rolled back, so estimation doesn't affect the state.
+    pub fn estimate_fuel(mut runtime_context: RuntimeContext<DB>) -> Result<Fuel, RuntimeError> {
+        // resolve hash once to let all attempts reuse the same cached module
+        runtime_context.resolve_bytecode();
+        let fuel_cap = if !runtime_context.fuel_limit.is_zero() {
+            runtime_context.fuel_limit
+        } else {
+            DEFAULT_FUEL_ESTIMATION_CAP
+        };
+
+        // if execution fails with the max fuel then there is nothing to estimate
+        let execution_result = Self::execute_with_fuel_limit(&runtime_context, fuel_cap)?;
+        if !ExitCode::from(execution_result.exit_code).is_ok() {
+            return Err(RuntimeError::ExecutionFailed(execution_result.exit_code));
+        }
+
+        // consumed fuel is a lower bound, but nested calls can't receive all remaining fuel
+        // because of the 63/64 rule, so the real limit might be higher; `lo` always fails and
+        // `hi` always succeeds
+        let fuel_consumed = execution_result.fuel_consumed.get();
+        let mut lo = fuel_consumed.saturating_sub(1);
+        let mut hi = fuel_cap.get();
+
+        // most of the calls succeed with the consumed fuel plus the withheld 64th, so let's try
+        // it first to reduce number of iterations
+        let optimistic_limit = fuel_consumed.saturating_mul(64) / 63;
+        if optimistic_limit > lo && optimistic_limit < hi {
+            if Self::is_enough_fuel(&runtime_context, Fuel(optimistic_limit))? {
+                hi = optimistic_limit;
+            } else {
+                lo = optimistic_limit;
+            }
+        }
+
+        while lo + 1 < hi {
+            let mid = lo + (hi - lo) / 2;
+            if Self::is_enough_fuel(&runtime_context, Fuel(mid))? {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        Ok(Fuel(hi))
+    }
+
+    fn is_enough_fuel(
+        runtime_context: &RuntimeContext<DB>,
+        fuel_limit: Fuel,
+    ) -> Result<bool, RuntimeError> {
+        let execution_result = Self::execute_with_fuel_limit(runtime_context, fuel_limit)?;
+        Ok(ExitCode::from(execution_result.exit_code).is_ok())
+    }
+
+    fn execute_with_fuel_limit(
+        runtime_context: &RuntimeContext<DB>,
+        fuel_limit: Fuel,
+    ) -> Result<ExecutionResult, RuntimeError> {
+        let checkpoint = runtime_context.jzkt.as_ref().map(|jzkt| jzkt.checkpoint());
+        let execution_result =
+            Self::new(runtime_context.clone().with_fuel_limit(fuel_limit)).call();
+        if let (Some(jzkt), Some(checkpoint)) = (runtime_context.jzkt.as_ref(), checkpoint) {
+            jzkt.rollback(checkpoint);
+        }
+        execution_result
+    }
+}
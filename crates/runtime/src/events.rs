@@ -0,0 +1,669 @@
+use crate::instruction::keccak256::SyscallKeccak256;
+use fluentbase_types::{Address, Bytes, JournalLog, B256, U256};
+use hashbrown::HashMap;
+use serde_json::{json, Map, Value};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventError {
+    Json(String),
+    Abi(String),
+    /// There is no event with the selector (the first topic) in the ABI
+    UnknownEvent(Option<B256>),
+    /// Topics or data don't match the event ABI
+    Malformed(String),
+    /// The decoded event has no parameter with the name or its type is different
+    Param(String),
+}
+
+impl From<serde_json::Error> for EventError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value.to_string())
+    }
+}
+
+/// Solidity types of event parameters, arrays and tuples aren't supported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventParamType {
+    Address,
+    Bool,
+    Uint(usize),
+    Int(usize),
+    FixedBytes(usize),
+    Bytes,
+    String,
+}
+
+impl EventParamType {
+    pub fn parse(name: &str) -> Result<Self, EventError> {
+        let bits = |suffix: &str| -> Result<usize, EventError> {
+            if suffix.is_empty() {
+                return Ok(256);
+            }
+            match suffix.parse::<usize>() {
+                Ok(bits) if bits > 0 && bits <= 256 && bits % 8 == 0 => Ok(bits),
+                _ => Err(EventError::Abi(format!("invalid type ({})", name))),
+            }
+        };
+        Ok(match name {
+            "address" => Self::Address,
+            "bool" => Self::Bool,
+            "bytes" => Self::Bytes,
+            "string" => Self::String,
+            _ if name.starts_with("uint") => Self::Uint(bits(&name[4..])?),
+            _ if name.starts_with("int") => Self::Int(bits(&name[3..])?),
+            _ if name.starts_with("bytes") => match name[5..].parse::<usize>() {
+                Ok(size) if size > 0 && size <= 32 => Self::FixedBytes(size),
+                _ => return Err(EventError::Abi(format!("invalid type ({})", name))),
+            },
+            _ => return Err(EventError::Abi(format!("unsupported type ({})", name))),
+        })
+    }
+
+    /// Dynamic values are stored by offset in the data and by hash in the topics
+    pub fn is_dynamic(&self) -> bool {
+        matches!(self, Self::Bytes | Self::String)
+    }
+
+    fn decode_word(&self, word: &[u8; 32]) -> EventValue {
+        match self {
+            Self::Address => EventValue::Address(Address::from_word(B256::from(*word))),
+            Self::Bool => EventValue::Bool(word.iter().any(|byte| *byte != 0)),
+            Self::Uint(_) => EventValue::Uint(U256::from_be_bytes(*word)),
+            // values are sign-extended to 256 bits by the encoder
+            Self::Int(_) => EventValue::Int(U256::from_be_bytes(*word)),
+            Self::FixedBytes(size) => {
+                EventValue::FixedBytes(Bytes::copy_from_slice(&word[..*size]))
+            }
+            Self::Bytes | Self::String => EventValue::Hash(B256::from(*word)),
+        }
+    }
+}
+
+impl Display for EventParamType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Address => write!(f, "address"),
+            Self::Bool => write!(f, "bool"),
+            Self::Uint(bits) => write!(f, "uint{}", bits),
+            Self::Int(bits) => write!(f, "int{}", bits),
+            Self::FixedBytes(size) => write!(f, "bytes{}", size),
+            Self::Bytes => write!(f, "bytes"),
+            Self::String => write!(f, "string"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventParam {
+    pub name: String,
+    pub ty: EventParamType,
+    pub indexed: bool,
+}
+
+/// Event entry of the contract ABI JSON, like `{"type": "event", "name": ..., "inputs": [...]}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventAbi {
+    pub name: String,
+    pub inputs: Vec<EventParam>,
+    pub anonymous: bool,
+}
+
+impl EventAbi {
+    pub fn from_json(value: &Value) -> Result<Self, EventError> {
+        let name = value
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| EventError::Abi("missing event name".to_string()))?;
+        let inputs = value
+            .get("inputs")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .map(|input| {
+                let ty = input
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| EventError::Abi(format!("missing input type of {}", name)))?;
+                Ok(EventParam {
+                    name: input
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    ty: EventParamType::parse(ty)?,
+                    indexed: input
+                        .get("indexed")
+                        .and_then(Value::as_bool)
+                        .unwrap_or_default(),
+                })
+            })
+            .collect::<Result<Vec<_>, EventError>>()?;
+        Ok(Self {
+            name: name.to_string(),
+            inputs,
+            anonymous: value
+                .get("anonymous")
+                .and_then(Value::as_bool)
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Canonical signature, like `Transfer(address,address,uint256)`
+    pub fn signature(&self) -> String {
+        let types = self
+            .inputs
+            .iter()
+            .map(|input| input.ty.to_string())
+            .collect::<Vec<_>>();
+        format!("{}({})", self.name, types.join(","))
+    }
+
+    /// The first topic of non-anonymous events
+    pub fn selector(&self) -> B256 {
+        B256::from(SyscallKeccak256::fn_impl(self.signature().as_bytes()))
+    }
+
+    /// Decodes indexed parameters from the topics and the rest from the data, values of
+    /// indexed dynamic parameters are only available as hashes
+    pub fn decode(&self, log: &JournalLog) -> Result<DecodedEvent, EventError> {
+        let mut topics = log.topics.iter();
+        if !self.anonymous {
+            match topics.next() {
+                Some(selector) if *selector == self.selector() => {}
+                selector => return Err(EventError::UnknownEvent(selector.copied())),
+            }
+        }
+        let indexed_count = self.inputs.iter().filter(|input| input.indexed).count();
+        if topics.len() != indexed_count {
+            return Err(EventError::Malformed(format!(
+                "{} expects {} indexed topics, but {} are given",
+                self.name,
+                indexed_count,
+                topics.len()
+            )));
+        }
+        let data = log.data.as_ref();
+        let mut head_offset = 0;
+        let params = self
+            .inputs
+            .iter()
+            .map(|input| {
+                let value = if input.indexed {
+                    input.ty.decode_word(&topics.next().unwrap().0)
+                } else {
+                    let word = read_word(data, head_offset)?;
+                    head_offset += 32;
+                    if input.ty.is_dynamic() {
+                        decode_dynamic(data, &word, input.ty)?
+                    } else {
+                        input.ty.decode_word(&word)
+                    }
+                };
+                Ok((input.name.clone(), value))
+            })
+            .collect::<Result<Vec<_>, EventError>>()?;
+        Ok(DecodedEvent {
+            name: self.name.clone(),
+            address: log.address,
+            params,
+        })
+    }
+}
+
+fn read_word(data: &[u8], offset: usize) -> Result<[u8; 32], EventError> {
+    data.get(offset..offset + 32)
+        .map(|word| word.try_into().unwrap())
+        .ok_or_else(|| EventError::Malformed(format!("data is too short ({})", data.len())))
+}
+
+fn word_to_usize(word: &[u8; 32]) -> Result<usize, EventError> {
+    let value = U256::from_be_bytes(*word);
+    if value > U256::from(u32::MAX) {
+        return Err(EventError::Malformed(format!("invalid offset ({})", value)));
+    }
+    Ok(value.to::<usize>())
+}
+
+/// Dynamic value is stored as the length word followed by the padded bytes
+fn decode_dynamic(
+    data: &[u8],
+    offset_word: &[u8; 32],
+    ty: EventParamType,
+) -> Result<EventValue, EventError> {
+    let offset = word_to_usize(offset_word)?;
+    let len = word_to_usize(&read_word(data, offset)?)?;
+    let bytes = data
+        .get(offset + 32..offset + 32 + len)
+        .ok_or_else(|| EventError::Malformed(format!("data is too short ({})", data.len())))?;
+    Ok(match ty {
+        EventParamType::String => EventValue::String(
+            String::from_utf8(bytes.to_vec())
+                .map_err(|_| EventError::Malformed("string isn't valid UTF-8".to_string()))?,
+        ),
+        _ => EventValue::Bytes(Bytes::copy_from_slice(bytes)),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventValue {
+    Address(Address),
+    Bool(bool),
+    Uint(U256),
+    /// Two's complement value sign-extended to 256 bits
+    Int(U256),
+    FixedBytes(Bytes),
+    Bytes(Bytes),
+    String(String),
+    /// Indexed dynamic values are stored as their keccak256 hash
+    Hash(B256),
+}
+
+impl EventValue {
+    /// Numbers are encoded as decimal strings (they don't fit into JSON numbers), bytes and
+    /// hashes as 0x-prefixed hex strings
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::Address(address) => json!(address.to_string()),
+            Self::Bool(value) => json!(value),
+            Self::Uint(value) => json!(value.to_string()),
+            Self::Int(value) if value.bit(255) => {
+                json!(format!("-{}", (!*value).wrapping_add(U256::from(1))))
+            }
+            Self::Int(value) => json!(value.to_string()),
+            Self::FixedBytes(bytes) | Self::Bytes(bytes) => {
+                json!(format!("0x{}", hex::encode(bytes)))
+            }
+            Self::String(value) => json!(value),
+            Self::Hash(hash) => json!(format!("0x{}", hex::encode(hash))),
+        }
+    }
+}
+
+/// Conversion of decoded parameters into Rust types, see [`DecodedEvent::param`]
+pub trait FromEventValue: Sized {
+    fn from_event_value(value: &EventValue) -> Option<Self>;
+}
+
+impl FromEventValue for Address {
+    fn from_event_value(value: &EventValue) -> Option<Self> {
+        match value {
+            EventValue::Address(address) => Some(*address),
+            _ => None,
+        }
+    }
+}
+
+impl FromEventValue for bool {
+    fn from_event_value(value: &EventValue) -> Option<Self> {
+        match value {
+            EventValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl FromEventValue for U256 {
+    fn from_event_value(value: &EventValue) -> Option<Self> {
+        match value {
+            EventValue::Uint(value) | EventValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl FromEventValue for u64 {
+    fn from_event_value(value: &EventValue) -> Option<Self> {
+        match value {
+            EventValue::Uint(value) => (*value).try_into().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromEventValue for i64 {
+    fn from_event_value(value: &EventValue) -> Option<Self> {
+        match value {
+            EventValue::Int(value) if value.bit(255) => {
+                let abs: u64 = (!*value).wrapping_add(U256::from(1)).try_into().ok()?;
+                0i64.checked_sub_unsigned(abs)
+            }
+            EventValue::Int(value) => (*value).try_into().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromEventValue for B256 {
+    fn from_event_value(value: &EventValue) -> Option<Self> {
+        match value {
+            EventValue::Hash(hash) => Some(*hash),
+            EventValue::FixedBytes(bytes) if bytes.len() == 32 => Some(B256::from_slice(bytes)),
+            _ => None,
+        }
+    }
+}
+
+impl FromEventValue for Bytes {
+    fn from_event_value(value: &EventValue) -> Option<Self> {
+        match value {
+            EventValue::FixedBytes(bytes) | EventValue::Bytes(bytes) => Some(bytes.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FromEventValue for String {
+    fn from_event_value(value: &EventValue) -> Option<Self> {
+        match value {
+            EventValue::String(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Typed event structs, implementations read parameters with [`DecodedEvent::param`]
+pub trait FromEvent: Sized {
+    const NAME: &'static str;
+
+    fn from_event(event: &DecodedEvent) -> Result<Self, EventError>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedEvent {
+    pub name: String,
+    pub address: Address,
+    pub params: Vec<(String, EventValue)>,
+}
+
+impl DecodedEvent {
+    pub fn get(&self, name: &str) -> Option<&EventValue> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn param<T: FromEventValue>(&self, name: &str) -> Result<T, EventError> {
+        self.get(name)
+            .and_then(T::from_event_value)
+            .ok_or_else(|| EventError::Param(format!("{}.{}", self.name, name)))
+    }
+
+    /// Converts into the typed event, events with other names are rejected
+    pub fn to_typed<T: FromEvent>(&self) -> Result<T, EventError> {
+        if self.name != T::NAME {
+            return Err(EventError::Param(format!(
+                "expected {} event, but {} is given",
+                T::NAME,
+                self.name
+            )));
+        }
+        T::from_event(self)
+    }
+
+    /// Parameters as an object, see [`EventValue::to_json`] for the value encoding
+    pub fn params_json(&self) -> Value {
+        Value::Object(
+            self.params
+                .iter()
+                .map(|(name, value)| (name.clone(), value.to_json()))
+                .collect::<Map<_, _>>(),
+        )
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "event": self.name,
+            "address": self.address.to_string(),
+            "params": self.params_json(),
+        })
+    }
+
+    /// Deserializes parameters (encoded by [`DecodedEvent::params_json`]) into the struct
+    #[cfg(feature = "serde")]
+    pub fn deserialize<T: serde::de::DeserializeOwned>(&self) -> Result<T, EventError> {
+        Ok(serde_json::from_value(self.params_json())?)
+    }
+}
+
+/// Decodes journal logs by the events of contract ABIs, events are matched by the selector
+/// (anonymous events by the number of topics)
+#[derive(Debug, Clone, Default)]
+pub struct EventDecoder {
+    events: HashMap<B256, EventAbi>,
+    anonymous: Vec<EventAbi>,
+}
+
+impl EventDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accepts the ABI array or an object with the `abi` field (like compiler artifacts),
+    /// entries other than events are ignored
+    pub fn from_abi_json(json: &str) -> Result<Self, EventError> {
+        let mut decoder = Self::new();
+        decoder.add_abi_json(json)?;
+        Ok(decoder)
+    }
+
+    pub fn add_abi_json(&mut self, json: &str) -> Result<(), EventError> {
+        let value: Value = serde_json::from_str(json)?;
+        let entries = value
+            .get("abi")
+            .unwrap_or(&value)
+            .as_array()
+            .ok_or_else(|| EventError::Abi("ABI must be an array".to_string()))?;
+        for entry in entries {
+            if entry.get("type").and_then(Value::as_str) == Some("event") {
+                self.add_event(EventAbi::from_json(entry)?);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn with_event(mut self, event: EventAbi) -> Self {
+        self.add_event(event);
+        self
+    }
+
+    pub fn add_event(&mut self, event: EventAbi) {
+        if event.anonymous {
+            self.anonymous.push(event);
+        } else {
+            self.events.insert(event.selector(), event);
+        }
+    }
+
+    pub fn decode(&self, log: &JournalLog) -> Result<DecodedEvent, EventError> {
+        if let Some(event) = log
+            .topics
+            .first()
+            .and_then(|selector| self.events.get(selector))
+        {
+            return event.decode(log);
+        }
+        self.anonymous
+            .iter()
+            .find_map(|event| event.decode(log).ok())
+            .ok_or_else(|| EventError::UnknownEvent(log.topics.first().copied()))
+    }
+
+    /// Decodes known events, logs of unknown events are skipped
+    pub fn decode_known(&self, logs: &[JournalLog]) -> Vec<DecodedEvent> {
+        logs.iter()
+            .filter_map(|log| self.decode(log).ok())
+            .collect()
+    }
+
+    pub fn decode_typed<T: FromEvent>(&self, log: &JournalLog) -> Result<T, EventError> {
+        self.decode(log)?.to_typed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        events::{DecodedEvent, EventDecoder, EventError, EventParamType, EventValue, FromEvent},
+        instruction::keccak256::SyscallKeccak256,
+    };
+    use fluentbase_types::{address, Address, Bytes, JournalLog, B256, U256};
+    use serde_json::json;
+
+    const ABI: &str = r#"{
+        "abi": [
+            {"type": "function", "name": "transfer", "inputs": [], "outputs": []},
+            {
+                "type": "event",
+                "name": "Transfer",
+                "anonymous": false,
+                "inputs": [
+                    {"name": "from", "type": "address", "indexed": true},
+                    {"name": "to", "type": "address", "indexed": true},
+                    {"name": "value", "type": "uint256", "indexed": false}
+                ]
+            },
+            {
+                "type": "event",
+                "name": "Note",
+                "anonymous": false,
+                "inputs": [
+                    {"name": "tag", "type": "string", "indexed": true},
+                    {"name": "delta", "type": "int64", "indexed": false},
+                    {"name": "text", "type": "string", "indexed": false},
+                    {"name": "flag", "type": "bool", "indexed": false}
+                ]
+            }
+        ]
+    }"#;
+
+    fn word(value: U256) -> [u8; 32] {
+        value.to_be_bytes::<32>()
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Transfer {
+        from: Address,
+        to: Address,
+        value: U256,
+    }
+
+    impl FromEvent for Transfer {
+        const NAME: &'static str = "Transfer";
+
+        fn from_event(event: &DecodedEvent) -> Result<Self, EventError> {
+            Ok(Self {
+                from: event.param("from")?,
+                to: event.param("to")?,
+                value: event.param("value")?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_decode_events() {
+        let decoder = EventDecoder::from_abi_json(ABI).unwrap();
+        let contract = address!("1111111111111111111111111111111111111111");
+        let from = address!("2222222222222222222222222222222222222222");
+        let to = address!("3333333333333333333333333333333333333333");
+        let transfer_selector = B256::from(SyscallKeccak256::fn_impl(
+            b"Transfer(address,address,uint256)",
+        ));
+        let transfer_log = JournalLog {
+            address: contract,
+            topics: vec![transfer_selector, from.into_word(), to.into_word()],
+            data: Bytes::copy_from_slice(&word(U256::from(1000))),
+        };
+        let transfer = decoder.decode_typed::<Transfer>(&transfer_log).unwrap();
+        assert_eq!(
+            transfer,
+            Transfer {
+                from,
+                to,
+                value: U256::from(1000)
+            }
+        );
+
+        // dynamic values are decoded from the data, indexed ones are hashes
+        let note_selector =
+            B256::from(SyscallKeccak256::fn_impl(b"Note(string,int64,string,bool)"));
+        let tag_hash = B256::from(SyscallKeccak256::fn_impl(b"tag"));
+        let mut data = Vec::new();
+        data.extend_from_slice(&word(U256::MAX - U256::from(4)));
+        data.extend_from_slice(&word(U256::from(96)));
+        data.extend_from_slice(&word(U256::from(1)));
+        data.extend_from_slice(&word(U256::from(5)));
+        data.extend_from_slice(b"hello");
+        data.resize(data.len() + 27, 0);
+        let note_log = JournalLog {
+            address: contract,
+            topics: vec![note_selector, tag_hash],
+            data: data.into(),
+        };
+        let note = decoder.decode(&note_log).unwrap();
+        assert_eq!(note.get("tag"), Some(&EventValue::Hash(tag_hash)));
+        assert_eq!(note.param::<i64>("delta").unwrap(), -5);
+        assert_eq!(note.param::<String>("text").unwrap(), "hello");
+        assert!(note.param::<bool>("flag").unwrap());
+        assert_eq!(
+            note.to_json(),
+            json!({
+                "event": "Note",
+                "address": contract.to_string(),
+                "params": {
+                    "tag": format!("0x{}", hex::encode(tag_hash)),
+                    "delta": "-5",
+                    "text": "hello",
+                    "flag": true,
+                },
+            })
+        );
+        assert!(note.to_typed::<Transfer>().is_err());
+
+        // unknown and malformed logs
+        let unknown_log = JournalLog {
+            address: contract,
+            topics: vec![B256::ZERO],
+            data: Bytes::new(),
+        };
+        assert_eq!(
+            decoder.decode(&unknown_log),
+            Err(EventError::UnknownEvent(Some(B256::ZERO)))
+        );
+        let truncated_log = JournalLog {
+            data: Bytes::new(),
+            ..transfer_log.clone()
+        };
+        assert!(matches!(
+            decoder.decode(&truncated_log),
+            Err(EventError::Malformed(_))
+        ));
+        assert_eq!(
+            decoder
+                .decode_known(&[unknown_log, transfer_log, note_log])
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_event_param_types() {
+        assert_eq!(
+            EventParamType::parse("uint").unwrap(),
+            EventParamType::Uint(256)
+        );
+        assert_eq!(
+            EventParamType::parse("int8").unwrap(),
+            EventParamType::Int(8)
+        );
+        assert_eq!(
+            EventParamType::parse("bytes4").unwrap(),
+            EventParamType::FixedBytes(4)
+        );
+        assert!(EventParamType::parse("uint7").is_err());
+        assert!(EventParamType::parse("bytes33").is_err());
+        assert!(EventParamType::parse("uint256[]").is_err());
+    }
+}
@@ -0,0 +1,79 @@
+use fluentbase_types::{Fuel, SysFuncIdx};
+use std::{collections::BTreeMap, sync::Arc};
+
+/// Fuel charged by the runtime for the host calls on top of the fuel of the executed
+/// instructions, it lets operators tune the gas schedule without changes of the runtime.
+/// The default policy charges nothing extra.
+///
+/// Instructions are charged by the engine with its own costs (in the eager mode), the policy
+/// doesn't change them since the fuel of the basic blocks is computed during the translation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FuelPolicy {
+    // the table is shared with the nested calls, so cloning the policy must be cheap
+    syscall_costs: Arc<BTreeMap<SysFuncIdx, Fuel>>,
+    default_syscall_cost: Fuel,
+}
+
+impl FuelPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Charges the fuel for every call of the syscall, replaces the default cost
+    pub fn with_syscall_cost(mut self, sys_func_idx: SysFuncIdx, fuel: Fuel) -> Self {
+        Arc::make_mut(&mut self.syscall_costs).insert(sys_func_idx, fuel);
+        self
+    }
+
+    /// Charges the fuel for every call of the syscall without its own cost
+    pub fn with_default_syscall_cost(mut self, fuel: Fuel) -> Self {
+        self.default_syscall_cost = fuel;
+        self
+    }
+
+    pub fn syscall_cost(&self, sys_func_idx: SysFuncIdx) -> Fuel {
+        self.syscall_costs
+            .get(&sys_func_idx)
+            .copied()
+            .unwrap_or(self.default_syscall_cost)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.default_syscall_cost.is_zero()
+            && self.syscall_costs.values().all(|fuel| fuel.is_zero())
+    }
+
+    /// Policy charging the proposed syscall prices of the calibration, the opcode classes are
+    /// ignored (see the type docs)
+    #[cfg(feature = "calibration")]
+    pub fn from_pricing_table(pricing_table: &crate::calibration::FuelPricingTable) -> Self {
+        pricing_table
+            .syscalls
+            .iter()
+            .fold(Self::new(), |fuel_policy, (sys_func_idx, fuel)| {
+                fuel_policy.with_syscall_cost(*sys_func_idx, Fuel(*fuel))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syscall_cost_fallback() {
+        let fuel_policy = FuelPolicy::new()
+            .with_default_syscall_cost(Fuel(5))
+            .with_syscall_cost(SysFuncIdx::KECCAK256, Fuel(100));
+        assert_eq!(fuel_policy.syscall_cost(SysFuncIdx::KECCAK256), Fuel(100));
+        assert_eq!(fuel_policy.syscall_cost(SysFuncIdx::READ), Fuel(5));
+        assert!(!fuel_policy.is_zero());
+        assert!(FuelPolicy::new().is_zero());
+        // clones share the table until one of them is changed
+        let cloned = fuel_policy
+            .clone()
+            .with_syscall_cost(SysFuncIdx::READ, Fuel(1));
+        assert_eq!(fuel_policy.syscall_cost(SysFuncIdx::READ), Fuel(5));
+        assert_eq!(cloned.syscall_cost(SysFuncIdx::READ), Fuel(1));
+    }
+}
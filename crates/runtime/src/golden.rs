@@ -0,0 +1,492 @@
+use crate::{
+    types::{InMemoryTrieDb, RuntimeError},
+    zktrie::ZkTrieStateDb,
+    JournaledTrie,
+    Runtime,
+    RuntimeContext,
+};
+use fluentbase_types::{
+    create_sovereign_import_linker,
+    Fuel,
+    IJournaledTrie,
+    SysFuncIdx::STATE,
+    STATE_DEPLOY,
+    STATE_MAIN,
+};
+use rwasm::{
+    engine::{bytecode::Instruction, RwasmConfig, StateRouterConfig},
+    rwasm::{BinaryFormat, RwasmModule},
+};
+use serde_json::json;
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable that switches golden suites into the update mode
+pub const UPDATE_GOLDEN_ENV: &str = "FLUENTBASE_UPDATE_GOLDEN";
+
+/// Default fuel limit of fixtures
+const GOLDEN_FUEL_LIMIT: u64 = 10_000_000;
+
+/// Contract of the golden corpus, the WAT module must export `main`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldenFixture {
+    pub name: String,
+    pub wat: String,
+    pub input: Vec<u8>,
+    pub fuel_limit: u64,
+}
+
+impl GoldenFixture {
+    pub fn new<N: Into<String>, W: Into<String>>(name: N, wat: W) -> Self {
+        Self {
+            name: name.into(),
+            wat: wat.into(),
+            input: vec![],
+            fuel_limit: GOLDEN_FUEL_LIMIT,
+        }
+    }
+
+    pub fn with_input(mut self, input: Vec<u8>) -> Self {
+        self.input = input;
+        self
+    }
+
+    pub fn with_fuel_limit(mut self, fuel_limit: u64) -> Self {
+        self.fuel_limit = fuel_limit;
+        self
+    }
+
+    /// Executes the fixture over the empty state and commits its changes
+    pub fn execute(&self) -> Result<GoldenRecord, GoldenError> {
+        let wasm_binary = wat::parse_str(&self.wat).map_err(|err| GoldenError::Translation {
+            name: self.name.clone(),
+            reason: err.to_string(),
+        })?;
+        let rwasm_binary = wasm2rwasm(&wasm_binary).map_err(|reason| GoldenError::Translation {
+            name: self.name.clone(),
+            reason,
+        })?;
+        let jzkt = JournaledTrie::new(ZkTrieStateDb::new_empty(InMemoryTrieDb::default()));
+        let ctx = RuntimeContext::new(rwasm_binary)
+            .with_input(self.input.clone())
+            .with_fuel_limit(Fuel(self.fuel_limit))
+            .with_jzkt(jzkt.clone());
+        let execution_result =
+            Runtime::run_with_context(ctx).map_err(|err: RuntimeError| GoldenError::Runtime {
+                name: self.name.clone(),
+                reason: format!("{:?}", err),
+            })?;
+        let (state_root, _) = jzkt.commit().map_err(|exit_code| GoldenError::Runtime {
+            name: self.name.clone(),
+            reason: format!("commit failed: {}", exit_code),
+        })?;
+        Ok(GoldenRecord {
+            exit_code: execution_result.exit_code,
+            fuel_consumed: execution_result.fuel_consumed.get(),
+            output: execution_result.output,
+            state_root,
+        })
+    }
+}
+
+/// Results of the fixture that are pinned by the golden file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GoldenRecord {
+    pub exit_code: i32,
+    pub fuel_consumed: u64,
+    pub output: Vec<u8>,
+    pub state_root: [u8; 32],
+}
+
+impl GoldenRecord {
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "exit_code": self.exit_code,
+            "fuel_consumed": self.fuel_consumed,
+            "output": hex::encode(&self.output),
+            "state_root": hex::encode(self.state_root),
+        })
+    }
+
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            exit_code: value["exit_code"].as_i64()?.try_into().ok()?,
+            fuel_consumed: value["fuel_consumed"].as_u64()?,
+            output: hex::decode(value["output"].as_str()?).ok()?,
+            state_root: hex::decode(value["state_root"].as_str()?)
+                .ok()?
+                .try_into()
+                .ok()?,
+        })
+    }
+
+    /// Human-readable differences with another record, one line per field
+    pub fn diff(&self, other: &GoldenRecord) -> Vec<String> {
+        let mut result = Vec::new();
+        if self.exit_code != other.exit_code {
+            result.push(format!(
+                "exit_code: {} -> {}",
+                self.exit_code, other.exit_code
+            ));
+        }
+        if self.fuel_consumed != other.fuel_consumed {
+            result.push(format!(
+                "fuel_consumed: {} -> {} ({:+})",
+                self.fuel_consumed,
+                other.fuel_consumed,
+                other.fuel_consumed as i128 - self.fuel_consumed as i128
+            ));
+        }
+        if self.output != other.output {
+            result.push(format!(
+                "output: 0x{} -> 0x{}",
+                hex::encode(&self.output),
+                hex::encode(&other.output)
+            ));
+        }
+        if self.state_root != other.state_root {
+            result.push(format!(
+                "state_root: 0x{} -> 0x{}",
+                hex::encode(self.state_root),
+                hex::encode(other.state_root)
+            ));
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GoldenError {
+    Translation { name: String, reason: String },
+    Runtime { name: String, reason: String },
+    Io { path: PathBuf, reason: String },
+    MalformedGolden { path: PathBuf },
+}
+
+impl Display for GoldenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GoldenError::Translation { name, reason } => {
+                write!(f, "fixture {} can't be translated: {}", name, reason)
+            }
+            GoldenError::Runtime { name, reason } => {
+                write!(f, "fixture {} failed: {}", name, reason)
+            }
+            GoldenError::Io { path, reason } => write!(f, "{}: {}", path.display(), reason),
+            GoldenError::MalformedGolden { path } => {
+                write!(f, "{}: malformed golden file", path.display())
+            }
+        }
+    }
+}
+
+/// Difference between the golden file and the actual result, the golden file is missing if
+/// `expected` is `None`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldenMismatch {
+    pub name: String,
+    pub expected: Option<GoldenRecord>,
+    pub actual: GoldenRecord,
+}
+
+impl Display for GoldenMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.expected {
+            Some(expected) => {
+                writeln!(f, "{}:", self.name)?;
+                for line in expected.diff(&self.actual) {
+                    writeln!(f, "  {}", line)?;
+                }
+                Ok(())
+            }
+            None => writeln!(f, "{}: golden file is missing", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GoldenReport {
+    pub mismatches: Vec<GoldenMismatch>,
+    /// Fixtures whose golden files were written in the update mode
+    pub updated: Vec<String>,
+}
+
+impl GoldenReport {
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Panics with the diff of all mismatches
+    pub fn assert_ok(&self) {
+        if self.is_ok() {
+            return;
+        }
+        let diff = self
+            .mismatches
+            .iter()
+            .map(|mismatch| mismatch.to_string())
+            .collect::<String>();
+        panic!(
+            "golden files don't match (run with {}=1 to update them):\n{}",
+            UPDATE_GOLDEN_ENV, diff
+        );
+    }
+}
+
+/// Executes the corpus of fixtures and compares exit codes, consumed fuel, outputs and state
+/// roots with the golden files (`<dir>/<name>.json`). In the update mode the changed and
+/// missing golden files are rewritten instead of being reported, so changes of pricing,
+/// translation or trie hashing show up as a diff of the golden files.
+pub struct GoldenSuite {
+    dir: PathBuf,
+    fixtures: Vec<GoldenFixture>,
+    update: bool,
+}
+
+impl GoldenSuite {
+    /// Creates suite with the default corpus, the update mode is enabled by the
+    /// [`UPDATE_GOLDEN_ENV`] environment variable
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            fixtures: default_golden_fixtures(),
+            update: std::env::var(UPDATE_GOLDEN_ENV).is_ok_and(|value| value != "0"),
+        }
+    }
+
+    pub fn with_fixtures(mut self, fixtures: Vec<GoldenFixture>) -> Self {
+        self.fixtures = fixtures;
+        self
+    }
+
+    pub fn with_fixture(mut self, fixture: GoldenFixture) -> Self {
+        self.fixtures.push(fixture);
+        self
+    }
+
+    pub fn with_update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    pub fn golden_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    pub fn run(&self) -> Result<GoldenReport, GoldenError> {
+        let mut report = GoldenReport::default();
+        for fixture in self.fixtures.iter() {
+            let actual = fixture.execute()?;
+            let path = self.golden_path(&fixture.name);
+            let expected = self.read_golden(&path)?;
+            if expected.as_ref() == Some(&actual) {
+                continue;
+            }
+            if self.update {
+                self.write_golden(&path, &actual)?;
+                report.updated.push(fixture.name.clone());
+            } else {
+                report.mismatches.push(GoldenMismatch {
+                    name: fixture.name.clone(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+        Ok(report)
+    }
+
+    fn read_golden(&self, path: &Path) -> Result<Option<GoldenRecord>, GoldenError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read_to_string(path).map_err(|err| GoldenError::Io {
+            path: path.to_path_buf(),
+            reason: err.to_string(),
+        })?;
+        serde_json::from_str(&data)
+            .ok()
+            .and_then(|value| GoldenRecord::from_json(&value))
+            .map(Some)
+            .ok_or(GoldenError::MalformedGolden {
+                path: path.to_path_buf(),
+            })
+    }
+
+    fn write_golden(&self, path: &Path, record: &GoldenRecord) -> Result<(), GoldenError> {
+        let io_error = |err: std::io::Error| GoldenError::Io {
+            path: path.to_path_buf(),
+            reason: err.to_string(),
+        };
+        fs::create_dir_all(&self.dir).map_err(io_error)?;
+        let mut data = serde_json::to_string_pretty(&record.to_json()).unwrap();
+        data.push('\n');
+        fs::write(path, data).map_err(io_error)
+    }
+}
+
+/// Corpus that covers translation, fuel charging of opcodes and syscalls, traps and trie
+/// hashing
+pub fn default_golden_fixtures() -> Vec<GoldenFixture> {
+    vec![
+        GoldenFixture::new(
+            "echo",
+            r#"(module
+  (import "fluentbase_v1preview" "_read" (func $_read (param i32 i32 i32)))
+  (import "fluentbase_v1preview" "_input_size" (func $_input_size (result i32)))
+  (import "fluentbase_v1preview" "_write" (func $_write (param i32 i32)))
+  (memory 1)
+  (func (export "main")
+    i32.const 0 i32.const 0 call $_input_size call $_read
+    i32.const 0 call $_input_size call $_write))"#,
+        )
+        .with_input(b"Hello, World".to_vec()),
+        GoldenFixture::new(
+            "arithmetic_loop",
+            r#"(module
+  (import "fluentbase_v1preview" "_write" (func $_write (param i32 i32)))
+  (memory 1)
+  (func (export "main")
+    (local $i i32) (local $x i32)
+    loop $loop
+      local.get $x local.get $i i32.mul i32.const 7 i32.add local.set $x
+      local.get $i i32.const 1 i32.add local.tee $i
+      i32.const 1000 i32.lt_u br_if $loop
+    end
+    i32.const 0 local.get $x i32.store
+    i32.const 0 i32.const 4 call $_write))"#,
+        ),
+        GoldenFixture::new(
+            "keccak256",
+            r#"(module
+  (import "fluentbase_v1preview" "_read" (func $_read (param i32 i32 i32)))
+  (import "fluentbase_v1preview" "_input_size" (func $_input_size (result i32)))
+  (import "fluentbase_v1preview" "_keccak256" (func $_keccak256 (param i32 i32 i32)))
+  (import "fluentbase_v1preview" "_write" (func $_write (param i32 i32)))
+  (memory 1)
+  (func (export "main")
+    i32.const 32 i32.const 0 call $_input_size call $_read
+    i32.const 32 call $_input_size i32.const 0 call $_keccak256
+    i32.const 0 i32.const 32 call $_write))"#,
+        )
+        .with_input(b"Hello, World".to_vec()),
+        GoldenFixture::new(
+            "storage_write",
+            r#"(module
+  (import "fluentbase_v1preview" "_read" (func $_read (param i32 i32 i32)))
+  (import "fluentbase_v1preview" "_update_leaf" (func $_update_leaf (param i32 i32 i32 i32)))
+  (memory 1)
+  (data (i32.const 0) "golden")
+  (func (export "main")
+    i32.const 32 i32.const 0 i32.const 64 call $_read
+    i32.const 0 i32.const 0 i32.const 32 i32.const 2 call $_update_leaf))"#,
+        )
+        .with_input([7u8; 64].to_vec()),
+        GoldenFixture::new(
+            "unreachable",
+            r#"(module
+  (func (export "main") unreachable))"#,
+        ),
+        GoldenFixture::new(
+            "out_of_fuel",
+            r#"(module
+  (func (export "main") loop $loop br $loop end))"#,
+        )
+        .with_fuel_limit(10_000),
+    ]
+}
+
+fn wasm2rwasm(wasm_binary: &[u8]) -> Result<Vec<u8>, String> {
+    let import_linker = Runtime::new_sovereign_linker();
+    let mut rwasm_config = RwasmModule::default_config(Some(import_linker));
+    rwasm_config.rwasm_config(RwasmConfig {
+        state_router: Some(StateRouterConfig {
+            states: Box::new([
+                ("deploy".to_string(), STATE_DEPLOY),
+                ("main".to_string(), STATE_MAIN),
+            ]),
+            opcode: Instruction::Call(STATE.into()),
+        }),
+        entrypoint_name: None,
+        import_linker: Some(create_sovereign_import_linker()),
+        wrap_import_functions: true,
+    });
+    let rwasm_module = RwasmModule::compile_with_config(wasm_binary, &rwasm_config)
+        .map_err(|err| format!("{:?}", err))?;
+    let mut result = Vec::new();
+    rwasm_module
+        .write_binary_to_vec(&mut result)
+        .map_err(|err| format!("{:?}", err))?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::golden::{GoldenFixture, GoldenRecord, GoldenSuite};
+    use fluentbase_types::ExitCode;
+    use std::fs;
+
+    #[test]
+    fn test_golden_suite() {
+        let dir = std::env::temp_dir().join(format!("fluentbase-golden-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let suite = GoldenSuite::new(&dir).with_update(false);
+
+        // missing golden files are reported unless the suite is in the update mode
+        let report = suite.run().unwrap();
+        assert_eq!(report.mismatches.len(), 6);
+        assert!(report.mismatches.iter().all(|v| v.expected.is_none()));
+        let report = GoldenSuite::new(&dir).with_update(true).run().unwrap();
+        assert_eq!(report.updated.len(), 6);
+        let report = suite.run().unwrap();
+        assert!(report.is_ok());
+        report.assert_ok();
+
+        let read = |name: &str| {
+            let data = fs::read_to_string(suite.golden_path(name)).unwrap();
+            GoldenRecord::from_json(&serde_json::from_str(&data).unwrap()).unwrap()
+        };
+        assert_eq!(read("echo").output, b"Hello, World".to_vec());
+        assert_ne!(read("storage_write").state_root, [0u8; 32]);
+        assert_eq!(read("echo").state_root, [0u8; 32]);
+        assert_eq!(read("out_of_fuel").exit_code, ExitCode::OutOfGas.into_i32());
+        assert_eq!(
+            read("unreachable").exit_code,
+            ExitCode::UnreachableCodeReached.into_i32()
+        );
+
+        // any change of the result is reported as a diff
+        let mut record = read("arithmetic_loop");
+        record.fuel_consumed += 1;
+        fs::write(
+            suite.golden_path("arithmetic_loop"),
+            record.to_json().to_string(),
+        )
+        .unwrap();
+        let report = suite.run().unwrap();
+        assert_eq!(report.mismatches.len(), 1);
+        let diff = report.mismatches[0].to_string();
+        assert!(
+            diff.starts_with("arithmetic_loop:\n  fuel_consumed: "),
+            "{}",
+            diff
+        );
+        assert!(diff.ends_with("(-1)\n"), "{}", diff);
+
+        // custom fixtures are executed too
+        let report = GoldenSuite::new(&dir)
+            .with_fixtures(vec![])
+            .with_fixture(GoldenFixture::new(
+                "empty",
+                r#"(module (func (export "main")))"#,
+            ))
+            .with_update(true)
+            .run()
+            .unwrap();
+        assert_eq!(report.updated, vec!["empty".to_string()]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
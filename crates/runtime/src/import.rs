@@ -0,0 +1,331 @@
+use crate::{
+    instruction::{keccak256::SyscallKeccak256, poseidon_hash::SyscallPoseidonHash},
+    JournaledTrie,
+    TrieStorage,
+};
+use byteorder::{ByteOrder, LittleEndian};
+use fluentbase_types::{
+    Address,
+    ExitCode,
+    IJournaledTrie,
+    JZKT_ACCOUNT_BALANCE_FIELD,
+    JZKT_ACCOUNT_COMPRESSION_FLAGS,
+    JZKT_ACCOUNT_FIELDS_COUNT,
+    JZKT_ACCOUNT_NONCE_FIELD,
+    JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+    JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD,
+    JZKT_ACCOUNT_SOURCE_CODE_SIZE_FIELD,
+    JZKT_STORAGE_COMPRESSION_FLAGS,
+    KECCAK_EMPTY,
+    POSEIDON_EMPTY,
+    U256,
+};
+use std::io::BufRead;
+
+/// Default number of imported entries committed at once
+pub const DEFAULT_IMPORT_BATCH_SIZE: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportError {
+    Io(String),
+    Parse { line: usize, message: String },
+    Storage(ExitCode),
+}
+
+impl From<std::io::Error> for ImportError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value.to_string())
+    }
+}
+
+impl From<ExitCode> for ImportError {
+    fn from(value: ExitCode) -> Self {
+        Self::Storage(value)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportProgress {
+    pub lines: usize,
+    pub accounts: usize,
+    pub slots: usize,
+    pub batches: usize,
+    /// State root after the last committed batch
+    pub root: [u8; 32],
+}
+
+/// Streams accounts and storage slots into the trie, changes are committed in batches, so
+/// every touched trie path is re-hashed once per batch instead of once per key.
+///
+/// The importer reads a line-based CSV snapshot:
+///
+/// ```text
+/// # empty lines and lines started with '#' are ignored
+/// account,<address>,<balance>,<nonce>[,<code>]
+/// storage,<address>,<slot>,<value>
+/// ```
+///
+/// Numbers are decimal or `0x`-prefixed hex, code is hex. Code is stored as the source
+/// bytecode, rwasm code fields are left empty. Zero storage values are skipped because they
+/// are equal to absent slots.
+pub struct StateImporter<DB: TrieStorage> {
+    trie: JournaledTrie<DB>,
+    batch_size: usize,
+    pending: usize,
+    progress: ImportProgress,
+}
+
+impl<DB: TrieStorage> StateImporter<DB> {
+    pub fn new(trie: JournaledTrie<DB>) -> Self {
+        let root = trie.compute_root();
+        Self {
+            trie,
+            batch_size: DEFAULT_IMPORT_BATCH_SIZE,
+            pending: 0,
+            progress: ImportProgress {
+                root,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn progress(&self) -> &ImportProgress {
+        &self.progress
+    }
+
+    /// Writes the account into the trie and returns `true` if the batch has been committed
+    pub fn import_account(
+        &mut self,
+        address: &Address,
+        balance: U256,
+        nonce: u64,
+        code: &[u8],
+    ) -> Result<bool, ImportError> {
+        let address32 = address.into_word();
+        let mut fields = vec![[0u8; 32]; JZKT_ACCOUNT_FIELDS_COUNT as usize];
+        fields[JZKT_ACCOUNT_BALANCE_FIELD as usize].copy_from_slice(balance.as_le_slice());
+        LittleEndian::write_u64(&mut fields[JZKT_ACCOUNT_NONCE_FIELD as usize], nonce);
+        LittleEndian::write_u64(
+            &mut fields[JZKT_ACCOUNT_SOURCE_CODE_SIZE_FIELD as usize],
+            code.len() as u64,
+        );
+        fields[JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD as usize] = if code.is_empty() {
+            KECCAK_EMPTY.0
+        } else {
+            SyscallKeccak256::fn_impl(code)
+        };
+        fields[JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD as usize] = POSEIDON_EMPTY.0;
+        self.trie
+            .update(&address32, &fields, JZKT_ACCOUNT_COMPRESSION_FLAGS);
+        if !code.is_empty() {
+            self.trie
+                .update_preimage(&address32, JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD, code);
+        }
+        self.progress.accounts += 1;
+        self.entry_imported()
+    }
+
+    pub fn import_slot(
+        &mut self,
+        address: &Address,
+        slot: U256,
+        value: U256,
+    ) -> Result<bool, ImportError> {
+        if value.is_zero() {
+            return Ok(false);
+        }
+        let storage_key = Self::storage_key(address, &slot)?;
+        let mut value32 = [0u8; 32];
+        value32.copy_from_slice(value.as_le_slice());
+        self.trie
+            .update(&storage_key, &vec![value32], JZKT_STORAGE_COMPRESSION_FLAGS);
+        self.progress.slots += 1;
+        self.entry_imported()
+    }
+
+    /// Imports all entries from the CSV snapshot, progress callback is called after each
+    /// committed batch
+    pub fn import_csv<R: BufRead, F: FnMut(&ImportProgress)>(
+        &mut self,
+        reader: R,
+        mut on_progress: F,
+    ) -> Result<ImportProgress, ImportError> {
+        for line in reader.lines() {
+            let line = line?;
+            self.progress.lines += 1;
+            let line_number = self.progress.lines;
+            let parse_error = |message: String| ImportError::Parse {
+                line: line_number,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let columns = line.split(',').map(str::trim).collect::<Vec<_>>();
+            let committed = match columns.as_slice() {
+                ["account", address, balance, nonce, code @ ..] if code.len() <= 1 => {
+                    let code = match code.first() {
+                        Some(code) => parse_hex(code).map_err(parse_error)?,
+                        None => Vec::new(),
+                    };
+                    self.import_account(
+                        &parse_address(address).map_err(parse_error)?,
+                        parse_u256(balance).map_err(parse_error)?,
+                        parse_u256(nonce)
+                            .and_then(|v| {
+                                u64::try_from(v).map_err(|_| format!("nonce overflow ({})", nonce))
+                            })
+                            .map_err(parse_error)?,
+                        &code,
+                    )?
+                }
+                ["storage", address, slot, value] => self.import_slot(
+                    &parse_address(address).map_err(parse_error)?,
+                    parse_u256(slot).map_err(parse_error)?,
+                    parse_u256(value).map_err(parse_error)?,
+                )?,
+                _ => return Err(parse_error(format!("malformed entry ({})", line))),
+            };
+            if committed {
+                on_progress(&self.progress);
+            }
+        }
+        Ok(self.progress.clone())
+    }
+
+    /// Commits the last batch and returns the final state root
+    pub fn finish(mut self) -> Result<ImportProgress, ImportError> {
+        if self.pending > 0 {
+            self.commit()?;
+        }
+        Ok(self.progress)
+    }
+
+    fn entry_imported(&mut self) -> Result<bool, ImportError> {
+        self.pending += 1;
+        if self.pending < self.batch_size {
+            return Ok(false);
+        }
+        self.commit()?;
+        Ok(true)
+    }
+
+    fn commit(&mut self) -> Result<(), ImportError> {
+        let (root, _) = self.trie.commit()?;
+        self.pending = 0;
+        self.progress.batches += 1;
+        self.progress.root = root;
+        Ok(())
+    }
+
+    fn storage_key(address: &Address, slot: &U256) -> Result<[u8; 32], ImportError> {
+        contract_storage_key(address, slot).map_err(ImportError::Storage)
+    }
+}
+
+/// Storage key is computed as `p(address, p(slot_0, slot_1))`, the same way as contracts do
+pub fn contract_storage_key(address: &Address, slot: &U256) -> Result<[u8; 32], ExitCode> {
+    const DOMAIN: [u8; 32] = {
+        let mut domain = [0u8; 32];
+        domain[23] = 1;
+        domain
+    };
+    let slot32 = slot.as_le_slice();
+    let mut slot0 = [0u8; 32];
+    slot0[..16].copy_from_slice(&slot32[..16]);
+    let mut slot1 = [0u8; 32];
+    slot1[..16].copy_from_slice(&slot32[16..]);
+    let mut address32 = [0u8; 32];
+    address32[11..31].copy_from_slice(address.as_slice());
+    let slot_hash = SyscallPoseidonHash::fn_impl(&slot0, &slot1, &DOMAIN)
+        .map_err(|_| ExitCode::PoseidonError)?;
+    SyscallPoseidonHash::fn_impl(&address32, &slot_hash, &DOMAIN)
+        .map_err(|_| ExitCode::PoseidonError)
+}
+
+fn parse_address(value: &str) -> Result<Address, String> {
+    value
+        .parse::<Address>()
+        .map_err(|err| format!("invalid address ({}): {}", value, err))
+}
+
+fn parse_u256(value: &str) -> Result<U256, String> {
+    value
+        .parse::<U256>()
+        .map_err(|err| format!("invalid number ({}): {}", value, err))
+}
+
+fn parse_hex(value: &str) -> Result<Vec<u8>, String> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value))
+        .map_err(|err| format!("invalid hex ({}): {}", value, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        import::{ImportError, StateImporter},
+        types::InMemoryTrieDb,
+        zktrie::ZkTrieStateDb,
+        JournaledTrie,
+    };
+    use fluentbase_types::{address, IJournaledTrie, U256};
+
+    const SNAPSHOT: &str = "\
+# test snapshot
+account,0x1111111111111111111111111111111111111111,1000,1
+account,0x2222222222222222222222222222222222222222,0x10,0,0x6001600055
+
+storage,0x2222222222222222222222222222222222222222,0,0x2a
+storage,0x2222222222222222222222222222222222222222,1,0
+";
+
+    #[test]
+    fn test_import_csv() {
+        let trie = JournaledTrie::new(ZkTrieStateDb::new_empty(InMemoryTrieDb::default()));
+        let mut importer = StateImporter::new(trie.clone()).with_batch_size(2);
+        let mut batches = Vec::new();
+        importer
+            .import_csv(SNAPSHOT.as_bytes(), |progress| {
+                batches.push(progress.clone())
+            })
+            .unwrap();
+        // the last batch isn't full, so it's committed by finish
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].accounts, 2);
+        let progress = importer.finish().unwrap();
+        assert_eq!(progress.accounts, 2);
+        assert_eq!(progress.slots, 1);
+        assert_eq!(progress.batches, 2);
+        assert_eq!(progress.root, trie.compute_root());
+        // result doesn't depend on the batch size
+        let trie2 = JournaledTrie::new(ZkTrieStateDb::new_empty(InMemoryTrieDb::default()));
+        let mut importer2 = StateImporter::new(trie2);
+        importer2.import_csv(SNAPSHOT.as_bytes(), |_| {}).unwrap();
+        assert_eq!(importer2.finish().unwrap().root, progress.root);
+        let address = address!("2222222222222222222222222222222222222222");
+        let (fields, _, _) = trie.get(&address.into_word(), true).unwrap();
+        assert_eq!(U256::from_le_slice(&fields[0]), U256::from(16));
+        let storage_key =
+            StateImporter::<ZkTrieStateDb<InMemoryTrieDb>>::storage_key(&address, &U256::ZERO)
+                .unwrap();
+        let (value, _, _) = trie.get(&storage_key, true).unwrap();
+        assert_eq!(U256::from_le_slice(&value[0]), U256::from(42));
+    }
+
+    #[test]
+    fn test_import_malformed_line() {
+        let trie = JournaledTrie::new(ZkTrieStateDb::new_empty(InMemoryTrieDb::default()));
+        let mut importer = StateImporter::new(trie);
+        let result = importer.import_csv(
+            "account,0x1111111111111111111111111111111111111111,1\n".as_bytes(),
+            |_| {},
+        );
+        assert!(matches!(result, Err(ImportError::Parse { line: 1, .. })));
+    }
+}
@@ -0,0 +1,507 @@
+use crate::{import::contract_storage_key, instruction::keccak256::SyscallKeccak256};
+use fluentbase_types::{Address, Bytes, ExitCode, IJournaledTrie, U256};
+use hashbrown::HashMap;
+use serde_json::Value;
+
+/// Max number of dynamic array items decoded by the inspector, the rest is skipped (the full
+/// length is still reported)
+pub const MAX_INSPECTED_ARRAY_LEN: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InspectError {
+    Json(String),
+    Layout(String),
+    Storage(ExitCode),
+}
+
+impl From<serde_json::Error> for InspectError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value.to_string())
+    }
+}
+
+impl From<ExitCode> for InspectError {
+    fn from(value: ExitCode) -> Self {
+        Self::Storage(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageEncoding {
+    Inplace,
+    Mapping,
+    DynamicArray,
+    Bytes,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageLayoutEntry {
+    pub label: String,
+    pub slot: U256,
+    /// Offset in bytes from the least significant byte of the slot
+    pub offset: usize,
+    pub type_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageLayoutType {
+    pub label: String,
+    pub encoding: StorageEncoding,
+    pub number_of_bytes: usize,
+    /// Element type of arrays
+    pub base: Option<String>,
+    /// Value type of mappings
+    pub value: Option<String>,
+    /// Members of structs, slots are relative to the struct slot
+    pub members: Vec<StorageLayoutEntry>,
+}
+
+/// Storage layout in the solc `storageLayout` output format
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StorageLayout {
+    pub storage: Vec<StorageLayoutEntry>,
+    pub types: HashMap<String, StorageLayoutType>,
+}
+
+impl StorageLayout {
+    pub fn from_json(json: &str) -> Result<Self, InspectError> {
+        let value: Value = serde_json::from_str(json)?;
+        let storage = match value.get("storage") {
+            Some(storage) => parse_entries(storage)?,
+            None => return Err(InspectError::Layout("missing storage".to_string())),
+        };
+        let mut types = HashMap::new();
+        if let Some(types_value) = value.get("types").and_then(Value::as_object) {
+            for (type_id, type_value) in types_value.iter() {
+                types.insert(type_id.clone(), parse_type(type_id, type_value)?);
+            }
+        }
+        Ok(Self { storage, types })
+    }
+
+    fn type_of(&self, type_id: &str) -> Result<&StorageLayoutType, InspectError> {
+        self.types
+            .get(type_id)
+            .ok_or_else(|| InspectError::Layout(format!("unknown type ({})", type_id)))
+    }
+}
+
+fn parse_entries(value: &Value) -> Result<Vec<StorageLayoutEntry>, InspectError> {
+    let entries = value
+        .as_array()
+        .ok_or_else(|| InspectError::Layout("storage entries must be an array".to_string()))?;
+    entries
+        .iter()
+        .map(|entry| {
+            let field = |name: &str| {
+                entry
+                    .get(name)
+                    .ok_or_else(|| InspectError::Layout(format!("missing entry field ({})", name)))
+            };
+            Ok(StorageLayoutEntry {
+                label: value_to_string(field("label")?),
+                slot: value_to_u256(field("slot")?)?,
+                offset: value_to_u256(field("offset")?)?.saturating_to::<usize>(),
+                type_id: value_to_string(field("type")?),
+            })
+        })
+        .collect()
+}
+
+fn parse_type(type_id: &str, value: &Value) -> Result<StorageLayoutType, InspectError> {
+    let encoding = match value.get("encoding").and_then(Value::as_str) {
+        Some("inplace") => StorageEncoding::Inplace,
+        Some("mapping") => StorageEncoding::Mapping,
+        Some("dynamic_array") => StorageEncoding::DynamicArray,
+        Some("bytes") => StorageEncoding::Bytes,
+        encoding => {
+            return Err(InspectError::Layout(format!(
+                "unsupported encoding of {} ({:?})",
+                type_id, encoding
+            )))
+        }
+    };
+    let number_of_bytes = match value.get("numberOfBytes") {
+        Some(value) => value_to_u256(value)?.saturating_to::<usize>(),
+        None => 32,
+    };
+    let members = match value.get("members") {
+        Some(members) => parse_entries(members)?,
+        None => Vec::new(),
+    };
+    Ok(StorageLayoutType {
+        label: value.get("label").map(value_to_string).unwrap_or_default(),
+        encoding,
+        number_of_bytes,
+        base: value.get("base").map(value_to_string),
+        value: value.get("value").map(value_to_string),
+        members,
+    })
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// solc writes slots as decimal strings and offsets as numbers
+fn value_to_u256(value: &Value) -> Result<U256, InspectError> {
+    match value {
+        Value::Number(number) => number
+            .as_u64()
+            .map(U256::from)
+            .ok_or_else(|| InspectError::Layout(format!("invalid number ({})", number))),
+        Value::String(number) => number
+            .parse::<U256>()
+            .map_err(|err| InspectError::Layout(format!("invalid number ({}): {}", number, err))),
+        value => Err(InspectError::Layout(format!("invalid number ({})", value))),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageValue {
+    Bool(bool),
+    Uint(U256),
+    /// Two's complement value sign-extended to 256 bits
+    Int(U256),
+    Address(Address),
+    FixedBytes(Bytes),
+    Bytes(Bytes),
+    String(String),
+    Struct(Vec<StorageReportEntry>),
+    StaticArray(Vec<StorageValue>),
+    /// Only first [`MAX_INSPECTED_ARRAY_LEN`] items are decoded
+    DynamicArray {
+        length: U256,
+        items: Vec<StorageValue>,
+    },
+    /// Mapping keys can't be enumerated from the trie, so only the slot is reported
+    Mapping {
+        slot: U256,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageReportEntry {
+    pub label: String,
+    pub type_label: String,
+    pub slot: U256,
+    pub offset: usize,
+    pub value: StorageValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageReport {
+    pub address: Address,
+    pub entries: Vec<StorageReportEntry>,
+}
+
+/// Reads contract storage from the trie and decodes it according to the solidity storage
+/// layout, slots are mapped to the trie keys the same way as contracts do
+pub struct StorageInspector<'a, DB: IJournaledTrie> {
+    jzkt: &'a DB,
+    committed: bool,
+}
+
+impl<'a, DB: IJournaledTrie> StorageInspector<'a, DB> {
+    pub fn new(jzkt: &'a DB) -> Self {
+        Self {
+            jzkt,
+            committed: false,
+        }
+    }
+
+    /// Reads only committed values, uncommitted journal changes are ignored
+    pub fn with_committed(mut self, committed: bool) -> Self {
+        self.committed = committed;
+        self
+    }
+
+    pub fn inspect_json(
+        &self,
+        address: &Address,
+        layout_json: &str,
+    ) -> Result<StorageReport, InspectError> {
+        self.inspect(address, &StorageLayout::from_json(layout_json)?)
+    }
+
+    pub fn inspect(
+        &self,
+        address: &Address,
+        layout: &StorageLayout,
+    ) -> Result<StorageReport, InspectError> {
+        let entries = self.read_entries(address, layout, &layout.storage, U256::ZERO)?;
+        Ok(StorageReport {
+            address: *address,
+            entries,
+        })
+    }
+
+    /// Storage word of the slot, absent slots are zero
+    pub fn read_slot(&self, address: &Address, slot: &U256) -> Result<U256, InspectError> {
+        let storage_key = contract_storage_key(address, slot)?;
+        Ok(match self.jzkt.get(&storage_key, self.committed) {
+            Some((values, _, _)) => U256::from_le_slice(&values[0]),
+            None => U256::ZERO,
+        })
+    }
+
+    fn read_entries(
+        &self,
+        address: &Address,
+        layout: &StorageLayout,
+        entries: &[StorageLayoutEntry],
+        base_slot: U256,
+    ) -> Result<Vec<StorageReportEntry>, InspectError> {
+        entries
+            .iter()
+            .map(|entry| {
+                let slot = base_slot + entry.slot;
+                let value = self.read_value(address, layout, &entry.type_id, slot, entry.offset)?;
+                Ok(StorageReportEntry {
+                    label: entry.label.clone(),
+                    type_label: layout.type_of(&entry.type_id)?.label.clone(),
+                    slot,
+                    offset: entry.offset,
+                    value,
+                })
+            })
+            .collect()
+    }
+
+    fn read_value(
+        &self,
+        address: &Address,
+        layout: &StorageLayout,
+        type_id: &str,
+        slot: U256,
+        offset: usize,
+    ) -> Result<StorageValue, InspectError> {
+        let typ = layout.type_of(type_id)?;
+        match typ.encoding {
+            StorageEncoding::Mapping => Ok(StorageValue::Mapping { slot }),
+            StorageEncoding::Bytes => self.read_bytes(address, typ, slot),
+            StorageEncoding::DynamicArray => {
+                let length = self.read_slot(address, &slot)?;
+                let base = Self::array_base(typ)?;
+                let data_slot =
+                    U256::from_be_bytes(SyscallKeccak256::fn_impl(&slot.to_be_bytes::<32>()));
+                let len = length.saturating_to::<usize>().min(MAX_INSPECTED_ARRAY_LEN);
+                let items = self.read_items(address, layout, base, data_slot, len)?;
+                Ok(StorageValue::DynamicArray { length, items })
+            }
+            StorageEncoding::Inplace if !typ.members.is_empty() => Ok(StorageValue::Struct(
+                self.read_entries(address, layout, &typ.members, slot)?,
+            )),
+            StorageEncoding::Inplace if typ.base.is_some() => {
+                let len = Self::static_array_len(typ)?;
+                let base = Self::array_base(typ)?;
+                Ok(StorageValue::StaticArray(
+                    self.read_items(address, layout, base, slot, len)?,
+                ))
+            }
+            StorageEncoding::Inplace => {
+                let word = self.read_slot(address, &slot)?;
+                Ok(decode_primitive(typ, word, offset))
+            }
+        }
+    }
+
+    fn array_base(typ: &StorageLayoutType) -> Result<&str, InspectError> {
+        typ.base
+            .as_deref()
+            .ok_or_else(|| InspectError::Layout(format!("missing array base ({})", typ.label)))
+    }
+
+    /// Length of the static array is the last dimension in the label, like `uint8[2][3]`
+    fn static_array_len(typ: &StorageLayoutType) -> Result<usize, InspectError> {
+        typ.label
+            .strip_suffix(']')
+            .and_then(|label| label.rsplit_once('['))
+            .and_then(|(_, len)| len.parse::<usize>().ok())
+            .ok_or_else(|| InspectError::Layout(format!("invalid array type ({})", typ.label)))
+    }
+
+    fn read_items(
+        &self,
+        address: &Address,
+        layout: &StorageLayout,
+        base: &str,
+        slot: U256,
+        len: usize,
+    ) -> Result<Vec<StorageValue>, InspectError> {
+        let item_size = layout.type_of(base)?.number_of_bytes.max(1);
+        (0..len)
+            .map(|index| {
+                // small items are packed into the slot, others start from the new slot
+                let (slot_index, offset) = if item_size >= 32 {
+                    (index * item_size.div_ceil(32), 0)
+                } else {
+                    let per_slot = 32 / item_size;
+                    (index / per_slot, (index % per_slot) * item_size)
+                };
+                self.read_value(address, layout, base, slot + U256::from(slot_index), offset)
+            })
+            .collect()
+    }
+
+    /// Short values (up to 31 bytes) are stored in the slot with `len * 2` in the lowest byte,
+    /// long values store `len * 2 + 1` in the slot and data starting from `keccak256(slot)`
+    fn read_bytes(
+        &self,
+        address: &Address,
+        typ: &StorageLayoutType,
+        slot: U256,
+    ) -> Result<StorageValue, InspectError> {
+        let word = self.read_slot(address, &slot)?;
+        let data = if !word.bit(0) {
+            let len = (word.byte(0) / 2) as usize;
+            word.to_be_bytes::<32>()[..len].to_vec()
+        } else {
+            let len = (word >> 1).saturating_to::<usize>();
+            let data_slot =
+                U256::from_be_bytes(SyscallKeccak256::fn_impl(&slot.to_be_bytes::<32>()));
+            let mut data = Vec::with_capacity(len.div_ceil(32) * 32);
+            for index in 0..len.div_ceil(32) {
+                let word = self.read_slot(address, &(data_slot + U256::from(index)))?;
+                data.extend_from_slice(&word.to_be_bytes::<32>());
+            }
+            data.truncate(len);
+            data
+        };
+        Ok(if typ.label == "string" {
+            StorageValue::String(String::from_utf8_lossy(&data).into_owned())
+        } else {
+            StorageValue::Bytes(data.into())
+        })
+    }
+}
+
+fn decode_primitive(typ: &StorageLayoutType, word: U256, offset: usize) -> StorageValue {
+    let bits = typ.number_of_bytes.min(32) * 8;
+    let mut value = word >> (offset * 8);
+    if bits < 256 {
+        value &= (U256::from(1) << bits) - U256::from(1);
+    }
+    let label = typ.label.as_str();
+    if label == "bool" {
+        StorageValue::Bool(!value.is_zero())
+    } else if label.starts_with("address") || label.starts_with("contract ") {
+        StorageValue::Address(Address::from_word(value.to_be_bytes::<32>().into()))
+    } else if label.starts_with("int") {
+        // sign-extend to 256 bits
+        if bits < 256 && value.bit(bits - 1) {
+            value |= U256::MAX << bits;
+        }
+        StorageValue::Int(value)
+    } else if label.starts_with("bytes") {
+        // fixed bytes are left-aligned in the value
+        let bytes = value.to_be_bytes::<32>();
+        StorageValue::FixedBytes(Bytes::copy_from_slice(&bytes[32 - bits / 8..]))
+    } else {
+        // uints, enums and user-defined value types
+        StorageValue::Uint(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        import::StateImporter,
+        inspector::{StorageInspector, StorageValue},
+        instruction::keccak256::SyscallKeccak256,
+        types::InMemoryTrieDb,
+        zktrie::ZkTrieStateDb,
+        JournaledTrie,
+    };
+    use fluentbase_types::{address, Address, U256};
+
+    const LAYOUT: &str = r#"{
+        "storage": [
+            {"label": "total", "offset": 0, "slot": "0", "type": "t_uint256"},
+            {"label": "owner", "offset": 0, "slot": "1", "type": "t_address"},
+            {"label": "paused", "offset": 20, "slot": "1", "type": "t_bool"},
+            {"label": "name", "offset": 0, "slot": "2", "type": "t_string_storage"},
+            {"label": "values", "offset": 0, "slot": "3", "type": "t_array(t_int64)dyn_storage"},
+            {"label": "balances", "offset": 0, "slot": "4", "type": "t_mapping(t_address,t_uint256)"}
+        ],
+        "types": {
+            "t_address": {"encoding": "inplace", "label": "address", "numberOfBytes": "20"},
+            "t_bool": {"encoding": "inplace", "label": "bool", "numberOfBytes": "1"},
+            "t_int64": {"encoding": "inplace", "label": "int64", "numberOfBytes": "8"},
+            "t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"},
+            "t_string_storage": {"encoding": "bytes", "label": "string", "numberOfBytes": "32"},
+            "t_array(t_int64)dyn_storage": {
+                "base": "t_int64", "encoding": "dynamic_array", "label": "int64[]",
+                "numberOfBytes": "32"
+            },
+            "t_mapping(t_address,t_uint256)": {
+                "encoding": "mapping", "key": "t_address", "label": "mapping(address => uint256)",
+                "numberOfBytes": "32", "value": "t_uint256"
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_inspect_storage() {
+        let jzkt = JournaledTrie::new(ZkTrieStateDb::new_empty(InMemoryTrieDb::default()));
+        let contract = address!("1111111111111111111111111111111111111111");
+        let owner = address!("2222222222222222222222222222222222222222");
+        let mut importer = StateImporter::new(jzkt.clone());
+        let mut write = |slot: U256, value: U256| {
+            importer.import_slot(&contract, slot, value).unwrap();
+        };
+        write(U256::from(0), U256::from(42));
+        write(
+            U256::from(1),
+            (U256::from(1) << 160) | U256::from_be_slice(owner.as_slice()),
+        );
+        let mut name = [0u8; 32];
+        name[..5].copy_from_slice(b"hello");
+        name[31] = 10;
+        write(U256::from(2), U256::from_be_bytes(name));
+        write(U256::from(3), U256::from(2));
+        let data_slot = U256::from_be_bytes(SyscallKeccak256::fn_impl(
+            &U256::from(3).to_be_bytes::<32>(),
+        ));
+        // [7, -1] packed into one slot
+        write(data_slot, (U256::from(u64::MAX) << 64) | U256::from(7));
+        importer.finish().unwrap();
+
+        let report = StorageInspector::new(&jzkt)
+            .inspect_json(&contract, LAYOUT)
+            .unwrap();
+        let values = report
+            .entries
+            .iter()
+            .map(|entry| entry.value.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                StorageValue::Uint(U256::from(42)),
+                StorageValue::Address(owner),
+                StorageValue::Bool(true),
+                StorageValue::String("hello".to_string()),
+                StorageValue::DynamicArray {
+                    length: U256::from(2),
+                    items: vec![
+                        StorageValue::Int(U256::from(7)),
+                        StorageValue::Int(U256::MAX)
+                    ],
+                },
+                StorageValue::Mapping {
+                    slot: U256::from(4)
+                },
+            ]
+        );
+        assert_eq!(report.entries[5].type_label, "mapping(address => uint256)");
+        // storage of the other contract is empty
+        let report = StorageInspector::new(&jzkt)
+            .inspect_json(&Address::ZERO, LAYOUT)
+            .unwrap();
+        assert_eq!(report.entries[3].value, StorageValue::String(String::new()));
+    }
+}
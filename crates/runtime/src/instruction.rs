@@ -1,15 +1,31 @@
+pub mod assert_eq_mem;
+pub mod base_fee;
+pub mod blob_base_fee;
+pub mod blob_hash;
 pub mod charge_fuel;
 pub mod checkpoint;
 pub mod commit;
 pub mod compute_root;
 pub mod context_call;
+pub mod coverage_hit;
 pub mod debug_log;
 pub mod ecrecover;
 pub mod emit_log;
 pub mod exec;
+pub mod exec_address;
 pub mod exit;
+pub mod expect_fuel_le;
+pub mod expect_storage;
 pub mod forward_output;
+pub mod fuel_consumed;
+pub mod fuel_remaining;
+pub mod gas_price;
 pub mod get_leaf;
+pub mod hash_final;
+pub mod hash_init;
+pub mod hash_update;
+pub mod input_copy;
+pub mod input_reveal;
 pub mod input_size;
 pub mod keccak256;
 pub mod output_size;
@@ -17,30 +33,55 @@ pub mod poseidon;
 pub mod poseidon_hash;
 pub mod preimage_copy;
 pub mod preimage_size;
+pub mod profile_enter;
+pub mod profile_exit;
 pub mod read;
 pub mod read_context;
 pub mod read_output;
+pub mod remove_leaf;
+pub mod return_data_copy;
+pub mod return_data_size;
 pub mod rollback;
 pub mod state;
+pub mod static_exec;
+pub mod storage_read_external;
+pub mod suspend;
 pub mod update_leaf;
 pub mod update_preimage;
 pub mod write;
+pub mod write_output_chunk;
 
 use crate::{
     impl_runtime_handler,
     instruction::{
+        assert_eq_mem::SyscallAssertEqMem,
+        base_fee::SyscallBaseFee,
+        blob_base_fee::SyscallBlobBaseFee,
+        blob_hash::SyscallBlobHash,
         charge_fuel::SyscallChargeFuel,
         checkpoint::SyscallCheckpoint,
         commit::SyscallCommit,
         compute_root::SyscallComputeRoot,
         context_call::SyscallContextCall,
+        coverage_hit::SyscallCoverageHit,
         debug_log::SyscallDebugLog,
         ecrecover::SyscallEcrecover,
         emit_log::SyscallEmitLog,
         exec::SyscallExec,
+        exec_address::SyscallExecAddress,
         exit::SyscallExit,
+        expect_fuel_le::SyscallExpectFuelLe,
+        expect_storage::SyscallExpectStorage,
         forward_output::SyscallForwardOutput,
+        fuel_consumed::SyscallFuelConsumed,
+        fuel_remaining::SyscallFuelRemaining,
+        gas_price::SyscallGasPrice,
         get_leaf::SyscallGetLeaf,
+        hash_final::SyscallHashFinal,
+        hash_init::SyscallHashInit,
+        hash_update::SyscallHashUpdate,
+        input_copy::SyscallInputCopy,
+        input_reveal::SyscallInputReveal,
         input_size::SyscallInputSize,
         keccak256::SyscallKeccak256,
         output_size::SyscallOutputSize,
@@ -48,14 +89,23 @@ use crate::{
         poseidon_hash::SyscallPoseidonHash,
         preimage_copy::SyscallPreimageCopy,
         preimage_size::SyscallPreimageSize,
+        profile_enter::SyscallProfileEnter,
+        profile_exit::SyscallProfileExit,
         read::SyscallRead,
         read_context::SyscallReadContext,
         read_output::SyscallReadOutput,
+        remove_leaf::SyscallRemoveLeaf,
+        return_data_copy::SyscallReturnDataCopy,
+        return_data_size::SyscallReturnDataSize,
         rollback::SyscallRollback,
         state::SyscallState,
+        static_exec::SyscallStaticExec,
+        storage_read_external::SyscallStorageReadExternal,
+        suspend::SyscallSuspend,
         update_leaf::SyscallUpdateLeaf,
         update_preimage::SyscallUpdatePreimage,
         write::SyscallWrite,
+        write_output_chunk::SyscallWriteOutputChunk,
     },
     RuntimeContext,
 };
@@ -74,24 +124,43 @@ pub trait RuntimeHandler {
 }
 
 impl_runtime_handler!(SyscallKeccak256, KECCAK256, fn fluentbase_v1preview::_keccak256(data_ptr: u32, data_len: u32, output_ptr: u32) -> ());
+impl_runtime_handler!(SyscallHashInit, HASH_INIT, fn fluentbase_v1preview::_hash_init() -> u32);
+impl_runtime_handler!(SyscallHashUpdate, HASH_UPDATE, fn fluentbase_v1preview::_hash_update(handle: u32, data_ptr: u32, data_len: u32) -> ());
+impl_runtime_handler!(SyscallHashFinal, HASH_FINAL, fn fluentbase_v1preview::_hash_final(handle: u32, output_ptr: u32) -> ());
 impl_runtime_handler!(SyscallPoseidon, POSEIDON, fn fluentbase_v1preview::_poseidon(f32s_ptr: u32, f32s_len: u32, output_ptr: u32) -> ());
 impl_runtime_handler!(SyscallPoseidonHash, POSEIDON_HASH, fn fluentbase_v1preview::_poseidon_hash(fa32_ptr: u32, fb32_ptr: u32, fd32_ptr: u32, output_ptr: u32) -> ());
 impl_runtime_handler!(SyscallEcrecover, ECRECOVER, fn fluentbase_v1preview::_ecrecover(digest32_ptr: u32, sig64_ptr: u32, output65_ptr: u32, rec_id: u32) -> ());
 impl_runtime_handler!(SyscallExit, EXIT, fn fluentbase_v1preview::_exit(exit_code: i32) -> ());
 impl_runtime_handler!(SyscallWrite, WRITE, fn fluentbase_v1preview::_write(offset: u32, length: u32) -> ());
+impl_runtime_handler!(SyscallInputReveal, INPUT_REVEAL, fn fluentbase_v1preview::_input_reveal(section: u32, target: u32, target_len: u32) -> u32);
+impl_runtime_handler!(SyscallWriteOutputChunk, WRITE_OUTPUT_CHUNK, fn fluentbase_v1preview::_write_output_chunk(offset: u32, length: u32) -> u32);
 impl_runtime_handler!(SyscallInputSize, INPUT_SIZE, fn fluentbase_v1preview::_input_size() -> u32);
 impl_runtime_handler!(SyscallRead, READ, fn fluentbase_v1preview::_read(target: u32, offset: u32, length: u32) -> ());
+impl_runtime_handler!(SyscallInputCopy, INPUT_COPY, fn fluentbase_v1preview::_input_copy(target: u32, offset: u32, length: u32) -> u32);
 impl_runtime_handler!(SyscallOutputSize, OUTPUT_SIZE, fn fluentbase_v1preview::_output_size() -> u32);
 impl_runtime_handler!(SyscallReadOutput, READ_OUTPUT, fn fluentbase_v1preview::_read_output(target: u32, offset: u32, length: u32) -> ());
+impl_runtime_handler!(SyscallReturnDataSize, RETURN_DATA_SIZE, fn fluentbase_v1preview::_return_data_size() -> u32);
+impl_runtime_handler!(SyscallReturnDataCopy, RETURN_DATA_COPY, fn fluentbase_v1preview::_return_data_copy(target: u32, offset: u32, length: u32) -> ());
 impl_runtime_handler!(SyscallState, STATE, fn fluentbase_v1preview::_state() -> u32);
 impl_runtime_handler!(SyscallExec, EXEC, fn fluentbase_v1preview::_exec(code_hash32_ptr: u32, input_ptr: u32, input_len: u32, return_ptr: u32, return_len: u32, fuel_ptr: u32) -> i32);
+impl_runtime_handler!(SyscallStaticExec, STATIC_EXEC, fn fluentbase_v1preview::_static_exec(code_hash32_ptr: u32, input_ptr: u32, input_len: u32, return_ptr: u32, return_len: u32, fuel_ptr: u32) -> i32);
+impl_runtime_handler!(SyscallExecAddress, EXEC_ADDRESS, fn fluentbase_v1preview::_exec_address(address20_ptr: u32, input_ptr: u32, input_len: u32, return_ptr: u32, return_len: u32, fuel_ptr: u32) -> i32);
 impl_runtime_handler!(SyscallForwardOutput, FORWARD_OUTPUT, fn fluentbase_v1preview::_forward_output(offset: u32, len: u32) -> ());
 impl_runtime_handler!(SyscallChargeFuel, CHARGE_FUEL, fn fluentbase_v1preview::_charge_fuel(delta: u64) -> u64);
+impl_runtime_handler!(SyscallFuelRemaining, FUEL_REMAINING, fn fluentbase_v1preview::_fuel_remaining() -> u64);
+impl_runtime_handler!(SyscallFuelConsumed, FUEL_CONSUMED, fn fluentbase_v1preview::_fuel_consumed() -> u64);
+impl_runtime_handler!(SyscallBlobHash, BLOB_HASH, fn fluentbase_v1preview::_blob_hash(index: u32, output32_ptr: u32) -> ());
+impl_runtime_handler!(SyscallBlobBaseFee, BLOB_BASE_FEE, fn fluentbase_v1preview::_blob_base_fee(output32_ptr: u32) -> ());
+impl_runtime_handler!(SyscallGasPrice, GAS_PRICE, fn fluentbase_v1preview::_gas_price(output32_ptr: u32) -> ());
+impl_runtime_handler!(SyscallBaseFee, BASE_FEE, fn fluentbase_v1preview::_base_fee(output32_ptr: u32) -> ());
+impl_runtime_handler!(SyscallSuspend, SUSPEND, fn fluentbase_v1preview::_suspend(request_ptr: u32, request_len: u32, return_ptr: u32, return_len: u32) -> i32);
 impl_runtime_handler!(SyscallReadContext, READ_CONTEXT, fn fluentbase_v1preview::_read_context(target_ptr: u32, offset: u32, length: u32) -> ());
 impl_runtime_handler!(SyscallContextCall, CONTEXT_CALL, fn fluentbase_v1preview::_context_call(code_hash32_ptr: u32, input_ptr: u32, input_len: u32, context_ptr: u32, context_len: u32, return_ptr: u32, return_len: u32, fuel_ptr: u32, state: u32) -> i32);
 impl_runtime_handler!(SyscallCheckpoint, CHECKPOINT, fn fluentbase_v1preview::_checkpoint() -> u64);
 impl_runtime_handler!(SyscallGetLeaf, GET_LEAF, fn fluentbase_v1preview::_get_leaf(key32_ptr: u32, field: u32, output32_ptr: u32, committed: u32) -> u32);
+impl_runtime_handler!(SyscallStorageReadExternal, STORAGE_READ_EXTERNAL, fn fluentbase_v1preview::_storage_read_external(address20_ptr: u32, slot32_ptr: u32, output32_ptr: u32, with_proof: u32) -> u32);
 impl_runtime_handler!(SyscallUpdateLeaf, UPDATE_LEAF, fn fluentbase_v1preview::_update_leaf(key32_ptr: u32, flags: u32, vals32_ptr: u32, vals32_len: u32) -> ());
+impl_runtime_handler!(SyscallRemoveLeaf, REMOVE_LEAF, fn fluentbase_v1preview::_remove_leaf(key32_ptr: u32) -> ());
 impl_runtime_handler!(SyscallComputeRoot, COMPUTE_ROOT, fn fluentbase_v1preview::_compute_root(output32_ptr: u32) -> ());
 impl_runtime_handler!(SyscallEmitLog, EMIT_LOG, fn fluentbase_v1preview::_emit_log(key32_ptr: u32, topics32s_ptr: u32, topics32s_len: u32, data_ptr: u32, data_len: u32) -> ());
 impl_runtime_handler!(SyscallCommit, COMMIT, fn fluentbase_v1preview::_commit(root32_ptr: u32) -> ());
@@ -100,33 +169,58 @@ impl_runtime_handler!(SyscallPreimageSize, PREIMAGE_SIZE, fn fluentbase_v1previe
 impl_runtime_handler!(SyscallPreimageCopy, PREIMAGE_COPY, fn fluentbase_v1preview::_preimage_copy(hash32_ptr: u32, preimage_ptr: u32) -> ());
 impl_runtime_handler!(SyscallUpdatePreimage, UPDATE_PREIMAGE, fn fluentbase_v1preview::_update_preimage(key32_ptr: u32, field: u32, preimage_ptr: u32, preimage_len: u32) -> i32);
 impl_runtime_handler!(SyscallDebugLog, DEBUG_LOG, fn fluentbase_v1preview::_debug_log(msg_ptr: u32, msg_len: u32) -> ());
+impl_runtime_handler!(SyscallProfileEnter, PROFILE_ENTER, fn fluentbase_v1preview::_profile_enter(func_idx: u32) -> ());
+impl_runtime_handler!(SyscallProfileExit, PROFILE_EXIT, fn fluentbase_v1preview::_profile_exit() -> ());
+impl_runtime_handler!(SyscallCoverageHit, COVERAGE_HIT, fn fluentbase_v1preview::_coverage_hit(block_id: u32) -> ());
+impl_runtime_handler!(SyscallAssertEqMem, ASSERT_EQ_MEM, fn fluentbase_v1preview::_assert_eq_mem(actual_ptr: u32, expected_ptr: u32, len: u32) -> ());
+impl_runtime_handler!(SyscallExpectStorage, EXPECT_STORAGE, fn fluentbase_v1preview::_expect_storage(address20_ptr: u32, slot32_ptr: u32, value32_ptr: u32) -> ());
+impl_runtime_handler!(SyscallExpectFuelLe, EXPECT_FUEL_LE, fn fluentbase_v1preview::_expect_fuel_le(max_fuel: u64) -> ());
 
 fn runtime_register_handlers<DB: IJournaledTrie, const IS_SOVEREIGN: bool>(
     linker: &mut Linker<RuntimeContext<DB>>,
     store: &mut Store<RuntimeContext<DB>>,
 ) {
     SyscallKeccak256::register_handler(linker, store);
+    SyscallHashInit::register_handler(linker, store);
+    SyscallHashUpdate::register_handler(linker, store);
+    SyscallHashFinal::register_handler(linker, store);
     SyscallPoseidon::register_handler(linker, store);
     SyscallPoseidonHash::register_handler(linker, store);
     SyscallEcrecover::register_handler(linker, store);
     SyscallExit::register_handler(linker, store);
     SyscallWrite::register_handler(linker, store);
+    SyscallInputReveal::register_handler(linker, store);
+    SyscallWriteOutputChunk::register_handler(linker, store);
     SyscallForwardOutput::register_handler(linker, store);
     SyscallInputSize::register_handler(linker, store);
     SyscallRead::register_handler(linker, store);
+    SyscallInputCopy::register_handler(linker, store);
     SyscallOutputSize::register_handler(linker, store);
     SyscallReadOutput::register_handler(linker, store);
+    SyscallReturnDataSize::register_handler(linker, store);
+    SyscallReturnDataCopy::register_handler(linker, store);
     SyscallExec::register_handler(linker, store);
+    SyscallStaticExec::register_handler(linker, store);
+    SyscallExecAddress::register_handler(linker, store);
     SyscallState::register_handler(linker, store);
     SyscallChargeFuel::register_handler(linker, store);
+    SyscallFuelRemaining::register_handler(linker, store);
+    SyscallFuelConsumed::register_handler(linker, store);
     SyscallReadContext::register_handler(linker, store);
+    SyscallBlobHash::register_handler(linker, store);
+    SyscallBlobBaseFee::register_handler(linker, store);
+    SyscallGasPrice::register_handler(linker, store);
+    SyscallBaseFee::register_handler(linker, store);
+    SyscallSuspend::register_handler(linker, store);
     if IS_SOVEREIGN {
         SyscallContextCall::register_handler(linker, store);
         SyscallCheckpoint::register_handler(linker, store);
         SyscallUpdateLeaf::register_handler(linker, store);
+        SyscallRemoveLeaf::register_handler(linker, store);
         SyscallComputeRoot::register_handler(linker, store);
     }
     SyscallGetLeaf::register_handler(linker, store);
+    SyscallStorageReadExternal::register_handler(linker, store);
     SyscallEmitLog::register_handler(linker, store);
     if IS_SOVEREIGN {
         SyscallCommit::register_handler(linker, store);
@@ -138,6 +232,12 @@ fn runtime_register_handlers<DB: IJournaledTrie, const IS_SOVEREIGN: bool>(
     }
     SyscallPreimageCopy::register_handler(linker, store);
     SyscallDebugLog::register_handler(linker, store);
+    SyscallProfileEnter::register_handler(linker, store);
+    SyscallProfileExit::register_handler(linker, store);
+    SyscallCoverageHit::register_handler(linker, store);
+    SyscallAssertEqMem::register_handler(linker, store);
+    SyscallExpectStorage::register_handler(linker, store);
+    SyscallExpectFuelLe::register_handler(linker, store);
 }
 
 pub fn runtime_register_sovereign_handlers<DB: IJournaledTrie>(
@@ -0,0 +1,55 @@
+use crate::{
+    assertion::{check_debug_assertions, AssertionFailure, AssertionKind},
+    RuntimeContext,
+};
+use fluentbase_types::{ExitCode, IJournaledTrie};
+use rwasm::{core::Trap, errors::FuelError, Caller};
+
+/// Fuel charged for every compared byte, both regions are copied out of the memory
+pub const ASSERT_EQ_MEM_FUEL_PER_BYTE: u64 = 2;
+
+pub struct SyscallAssertEqMem;
+
+impl SyscallAssertEqMem {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        actual_ptr: u32,
+        expected_ptr: u32,
+        len: u32,
+    ) -> Result<(), Trap> {
+        check_debug_assertions(caller.data()).map_err(|err| err.into_trap())?;
+        // charge before reading the memory, so big regions fail fast
+        match caller.consume_fuel(len as u64 * ASSERT_EQ_MEM_FUEL_PER_BYTE) {
+            Ok(_) | Err(FuelError::FuelMeteringDisabled) => {}
+            Err(FuelError::OutOfFuel) => return Err(ExitCode::OutOfGas.into_trap()),
+        }
+        let actual = caller.read_memory(actual_ptr, len)?.to_vec();
+        let expected = caller.read_memory(expected_ptr, len)?.to_vec();
+        Self::fn_impl(caller.data_mut(), &actual, &expected).map_err(|err| err.into_trap())
+    }
+
+    /// Fails with `AssertionFailed` if the regions differ, the diagnostics contain both regions
+    /// and the offset of the first mismatched byte
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &mut RuntimeContext<DB>,
+        actual: &[u8],
+        expected: &[u8],
+    ) -> Result<(), ExitCode> {
+        let Some(offset) = actual.iter().zip(expected).position(|(a, b)| a != b) else {
+            return Ok(());
+        };
+        ctx.execution_result.assertion_failure = Some(AssertionFailure {
+            kind: AssertionKind::EqMem,
+            expected: expected.to_vec(),
+            actual: actual.to_vec(),
+            message: format!(
+                "memory differs at byte {} of {}: 0x{:02x} != 0x{:02x}",
+                offset,
+                actual.len(),
+                actual[offset],
+                expected[offset]
+            ),
+        });
+        Err(ExitCode::AssertionFailed)
+    }
+}
@@ -0,0 +1,21 @@
+use crate::RuntimeContext;
+use fluentbase_types::{IJournaledTrie, U256};
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallBaseFee;
+
+impl SyscallBaseFee {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        output32_ptr: u32,
+    ) -> Result<(), Trap> {
+        let base_fee = Self::fn_impl(caller.data());
+        caller.write_memory(output32_ptr, base_fee.as_le_slice())?;
+        Ok(())
+    }
+
+    /// Returns base fee of the current block (encoded as little-endian 32 bytes)
+    pub fn fn_impl<DB: IJournaledTrie>(ctx: &RuntimeContext<DB>) -> U256 {
+        ctx.gas_fees.base_fee()
+    }
+}
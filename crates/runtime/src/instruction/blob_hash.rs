@@ -0,0 +1,26 @@
+use crate::RuntimeContext;
+use fluentbase_types::{IJournaledTrie, B256};
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallBlobHash;
+
+impl SyscallBlobHash {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        index: u32,
+        output32_ptr: u32,
+    ) -> Result<(), Trap> {
+        let blob_hash = Self::fn_impl(caller.data(), index);
+        caller.write_memory(output32_ptr, blob_hash.as_slice())?;
+        Ok(())
+    }
+
+    /// Returns versioned hash of the blob, like `BLOBHASH` it returns zero hash if index is out of
+    /// bounds
+    pub fn fn_impl<DB: IJournaledTrie>(ctx: &RuntimeContext<DB>, index: u32) -> B256 {
+        ctx.blob_hashes
+            .get(index as usize)
+            .copied()
+            .unwrap_or(B256::ZERO)
+    }
+}
@@ -15,6 +15,10 @@ impl SyscallCommit {
     }
 
     pub fn fn_impl<DB: IJournaledTrie>(ctx: &mut RuntimeContext<DB>) -> Result<[u8; 32], ExitCode> {
+        // commit persists the whole journal, including pending writes of the callers
+        if ctx.is_static {
+            return Err(ExitCode::WriteProtection);
+        }
         let (root, _logs) = ctx.jzkt().commit()?;
         Ok(root)
     }
@@ -1,13 +1,19 @@
-use crate::{ExecutionResult, Runtime, RuntimeContext};
+use crate::{
+    call_stack::CallFrame,
+    instruction::exec::{charge_nested_fuel, forwarded_fuel_limit},
+    ExecutionResult,
+    Runtime,
+    RuntimeContext,
+};
 use byteorder::{ByteOrder, LittleEndian};
-use fluentbase_types::{ExitCode, IJournaledTrie};
+use fluentbase_types::{ExitCode, Fuel, IJournaledTrie};
 use rwasm::{
     core::{HostError, Trap},
     Caller,
 };
 use std::{
     fmt::{Display, Formatter},
-    mem::take,
+    mem::{replace, take},
     time::{SystemTime, UNIX_EPOCH},
 };
 
@@ -26,8 +32,6 @@ pub struct SysContextCallResumable {
     pub state: u32,
 }
 
-pub const CALL_STACK_LIMIT: u32 = 1024;
-
 impl Display for SysContextCallResumable {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "runtime resume error")
@@ -71,35 +75,61 @@ impl SyscallContextCall {
             .read_memory(state.code_hash32_ptr, 32)?
             .try_into()
             .unwrap();
-        let input = caller
-            .read_memory(state.input_ptr, state.input_len)?
-            .to_vec();
-        let context = caller
-            .read_memory(state.context_ptr, state.context_len)?
-            .to_vec();
+        let arena = caller.data().arena.clone();
+        let input = arena.alloc_from(caller.read_memory(state.input_ptr, state.input_len)?);
+        let context = arena.alloc_from(caller.read_memory(state.context_ptr, state.context_len)?);
         let fuel_data = caller.read_memory(state.fuel_ptr, 4)?;
-        let fuel_limit = LittleEndian::read_u32(fuel_data);
-        let exit_code = match Self::fn_impl(
+        let fuel_limit =
+            match forwarded_fuel_limit(&mut caller, Fuel(LittleEndian::read_u32(fuel_data) as u64))
+            {
+                Ok(fuel_limit) => fuel_limit,
+                Err(exit_code) => return Ok(exit_code),
+            };
+        let trace_writer = caller.data().trace_writer.clone();
+        let depth = caller.data().depth + 1;
+        if let Some(trace_writer) = trace_writer.as_ref() {
+            trace_writer.push_call_enter(
+                caller.fuel_consumed().unwrap_or_default(),
+                depth,
+                &bytecode_hash32,
+                fuel_limit.get(),
+            );
+        }
+        let fuel_consumed = caller.data().execution_result.fuel_consumed;
+        let result = Self::fn_impl(
             caller.data_mut(),
             &bytecode_hash32,
             input,
             context,
             state.return_len,
-            fuel_limit as u64,
+            fuel_limit,
             state.state,
-        ) {
+        );
+        let fuel_consumed = caller.data().execution_result.fuel_consumed - fuel_consumed;
+        charge_nested_fuel(&mut caller, fuel_consumed)?;
+        let exit_code = match result {
             Ok(remaining_fuel) => {
                 if state.return_len > 0 {
-                    let return_data = caller.data().execution_result.return_data.clone();
+                    let return_data = arena.alloc_from(&caller.data().execution_result.return_data);
                     caller.write_memory(state.return_ptr, &return_data)?;
+                    arena.release(return_data);
                 }
+                // the guest fuel slot is 32-bit, so fuel above `u32::MAX` is reported as `u32::MAX`
                 let mut fuel_buffer = [0u8; 4];
-                LittleEndian::write_u32(&mut fuel_buffer, remaining_fuel as u32);
+                LittleEndian::write_u32(&mut fuel_buffer, remaining_fuel.saturating_u32());
                 caller.write_memory(state.fuel_ptr, &fuel_buffer)?;
                 ExitCode::Ok.into_i32()
             }
             Err(err) => err,
         };
+        if let Some(trace_writer) = trace_writer.as_ref() {
+            trace_writer.push_call_exit(
+                caller.fuel_consumed().unwrap_or_default(),
+                depth,
+                fuel_consumed.get(),
+                exit_code,
+            );
+        }
         Ok(exit_code)
     }
 
@@ -109,49 +139,88 @@ impl SyscallContextCall {
         input: Vec<u8>,
         context: Vec<u8>,
         return_len: u32,
-        fuel_limit: u64,
+        fuel_limit: Fuel,
         state: u32,
-    ) -> Result<u64, i32> {
+    ) -> Result<Fuel, i32> {
         let time = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_millis();
 
         // check call depth overflow
-        if ctx.depth >= CALL_STACK_LIMIT {
-            return Err(ExitCode::CallDepthOverflow.into_i32());
-        }
+        ctx.call_stack
+            .push(CallFrame {
+                code_hash: bytecode_hash32.into(),
+                depth: ctx.depth + 1,
+                fuel_limit,
+                is_static: ctx.is_static,
+            })
+            .map_err(|exit_code| exit_code.into_i32())?;
 
         // take jzkt from the existing context (we will return it back soon)
         let jzkt = take(&mut ctx.jzkt).expect("jzkt is not initialized");
 
         // create new runtime instance with the context
-        let ctx2 = RuntimeContext::new_with_hash(bytecode_hash32.into())
+        let mut ctx2 = RuntimeContext::new_with_hash(bytecode_hash32.into())
             .with_input(input)
             .with_context(context)
             .with_state(state)
             .with_fuel_limit(fuel_limit)
             .with_jzkt(jzkt)
             .with_state(state)
-            .with_depth(ctx.depth + 1);
+            .with_depth(ctx.depth + 1)
+            .with_is_static(ctx.is_static);
+        ctx2.access_list_recorder = ctx.access_list_recorder.clone();
+        ctx2.bytecode_policy = ctx.bytecode_policy.clone();
+        ctx2.metadata_verifier = ctx.metadata_verifier.clone();
+        ctx2.capabilities = ctx.capabilities;
+        ctx2.escalation_policy = ctx.escalation_policy.clone();
+        ctx2.trace_writer = ctx.trace_writer.clone();
+        ctx2.signature_cache = ctx.signature_cache.clone();
+        ctx2.artifact_store = ctx.artifact_store.clone();
+        ctx2.stack_limits = ctx.stack_limits;
+        ctx2.arena = ctx.arena.clone();
+        ctx2.memory_init_mode = ctx.memory_init_mode;
+        ctx2.memory_poison = ctx.memory_poison;
+        ctx2.evm_interpreter = ctx.evm_interpreter;
+        ctx2.blob_hashes = ctx.blob_hashes.clone();
+        ctx2.blob_base_fee = ctx.blob_base_fee;
+        ctx2.gas_fees = ctx.gas_fees;
+        ctx2.fuel_schedule = ctx.fuel_schedule;
+        ctx2.fuel_policy = ctx.fuel_policy.clone();
+        ctx2.sealed_inputs = ctx.sealed_inputs.clone();
+        ctx2.debug_assertions = ctx.debug_assertions;
+        ctx2.call_stack = ctx.call_stack.clone();
+        ctx2.precompiles = ctx.precompiles.clone();
+        ctx2.system_contracts = ctx.system_contracts.clone();
         let mut runtime = Runtime::new(ctx2);
         let execution_result = runtime
             .call()
             .unwrap_or_else(|err| ExecutionResult::new_error(Runtime::catch_trap(&err)));
+        ctx.call_stack.pop();
 
         // return jzkt context back
         ctx.jzkt = take(&mut runtime.store.data_mut().jzkt);
 
+        // input and context buffers are not needed anymore
+        ctx.arena.release(take(&mut runtime.store.data_mut().input));
+        ctx.arena
+            .release(take(&mut runtime.store.data_mut().context));
+
+        // TODO(dmitry123): "do we need to put any fuel penalties for failed calls?"
+
+        // increase total fuel consumed (even if output overflows)
+        ctx.execution_result.fuel_consumed += execution_result.fuel_consumed;
+
         // make sure there is no return overflow
         if return_len > 0 && execution_result.output.len() > return_len as usize {
             return Err(ExitCode::OutputOverflow.into_i32());
         }
 
-        // TODO(dmitry123): "do we need to put any fuel penalties for failed calls?"
-
-        // increase total fuel consumed and remember return data
-        ctx.execution_result.fuel_consumed += execution_result.fuel_consumed;
-        ctx.execution_result.return_data = execution_result.output.clone();
+        // remember return data
+        let return_data = ctx.arena.alloc_from(&execution_result.output);
+        ctx.arena
+            .release(replace(&mut ctx.execution_result.return_data, return_data));
 
         println!(
             "sys_exec_hash ({}), exit_code={}, fuel_consumed={}, elapsed time: {}ms, output={}",
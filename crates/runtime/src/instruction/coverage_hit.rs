@@ -0,0 +1,21 @@
+use crate::RuntimeContext;
+use fluentbase_types::IJournaledTrie;
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallCoverageHit;
+
+impl SyscallCoverageHit {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        block_id: u32,
+    ) -> Result<(), Trap> {
+        Self::fn_impl(caller.data_mut(), block_id);
+        Ok(())
+    }
+
+    pub fn fn_impl<DB: IJournaledTrie>(ctx: &mut RuntimeContext<DB>, block_id: u32) {
+        if let Some(coverage_collector) = ctx.coverage_collector.as_ref() {
+            coverage_collector.hit(block_id);
+        }
+    }
+}
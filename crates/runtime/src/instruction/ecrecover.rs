@@ -1,4 +1,4 @@
-use crate::RuntimeContext;
+use crate::{signature_cache::SignatureRequest, RuntimeContext};
 use fluentbase_types::{ExitCode, IJournaledTrie};
 use k256::{
     ecdsa::{RecoveryId, Signature, VerifyingKey},
@@ -20,7 +20,18 @@ impl SyscallEcrecover {
     ) -> Result<(), Trap> {
         let digest = caller.read_memory(digest32_offset, 32)?;
         let sig = caller.read_memory(sig64_offset, 64)?;
-        let public_key = Self::fn_impl(digest, sig, rec_id).map_err(|err| err.into_trap())?;
+        let public_key = match caller.data().signature_cache.as_ref() {
+            Some(signature_cache) => {
+                let request = SignatureRequest::new(
+                    digest.try_into().unwrap(),
+                    sig.try_into().unwrap(),
+                    rec_id,
+                );
+                signature_cache.recover_public_key(&request)
+            }
+            None => Self::fn_impl(digest, sig, rec_id),
+        }
+        .map_err(|err| err.into_trap())?;
         caller.write_memory(output65_offset, &public_key)?;
         Ok(())
     }
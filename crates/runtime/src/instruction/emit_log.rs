@@ -1,9 +1,12 @@
 use crate::RuntimeContext;
-use fluentbase_types::{Address, Bytes, IJournaledTrie, B256};
+use fluentbase_types::{Address, Bytes, ExitCode, IJournaledTrie, B256};
 use rwasm::{core::Trap, Caller};
 
 pub struct SyscallEmitLog;
 
+/// Max number of topics allowed per log (LOG0-LOG4)
+pub const MAX_LOG_TOPICS: usize = 4;
+
 impl SyscallEmitLog {
     pub fn fn_fuel_cost(n: u8, len: u64) -> Option<u64> {
         // TODO(dmitry123): "how we can replace it with constants from EVM lib? do we need?"
@@ -29,6 +32,11 @@ impl SyscallEmitLog {
         //         _ => {}
         //     },
         // }
+        if topics32s_len % 32 != 0 {
+            return Err(ExitCode::MalformedLogTopics.into_trap());
+        } else if topics32s_len as usize > MAX_LOG_TOPICS * 32 {
+            return Err(ExitCode::TooManyLogTopics.into_trap());
+        }
         let address = Address::from_slice(caller.read_memory(address20_ptr, 20)?);
         let topics = caller
             .read_memory(topics32s_ptr, topics32s_len)?
@@ -40,8 +48,7 @@ impl SyscallEmitLog {
             })
             .collect::<Vec<_>>();
         let data = Bytes::copy_from_slice(caller.read_memory(data_ptr, data_len)?);
-        Self::fn_impl(caller.data_mut(), address, topics, data);
-        Ok(())
+        Self::fn_impl(caller.data_mut(), address, topics, data).map_err(|err| err.into_trap())
     }
 
     pub fn fn_impl<DB: IJournaledTrie>(
@@ -49,7 +56,15 @@ impl SyscallEmitLog {
         address: Address,
         topics: Vec<B256>,
         data: Bytes,
-    ) {
-        ctx.jzkt().emit_log(address, topics.clone(), data);
+    ) -> Result<(), ExitCode> {
+        if ctx.is_static {
+            return Err(ExitCode::WriteProtection);
+        }
+        if topics.len() > MAX_LOG_TOPICS {
+            return Err(ExitCode::TooManyLogTopics);
+        }
+        // logs are stored inside journal, so they're reverted with the rollback
+        ctx.jzkt().emit_log(address, topics, data);
+        Ok(())
     }
 }
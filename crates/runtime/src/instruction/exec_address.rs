@@ -0,0 +1,125 @@
+use crate::{
+    instruction::exec::{SysExecResumable, SyscallExec},
+    precompile::{execute_precompile, Precompile},
+    RuntimeContext,
+};
+use fluentbase_types::{
+    Address,
+    ExitCode,
+    Fuel,
+    IJournaledTrie,
+    JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+    JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD,
+    KECCAK_EMPTY,
+    POSEIDON_EMPTY,
+};
+use rwasm::{core::Trap, Caller};
+use std::mem::{replace, take};
+
+/// The same as `_exec`, but the callee is addressed by the account. Accounts with rWASM code are
+/// executed natively, accounts with only EVM code are executed by the EVM interpreter, both
+/// share the journal and fuel accounting of the caller. Call to the account without code
+/// succeeds with empty output (like EVM call to EOA).
+pub struct SyscallExecAddress;
+
+impl SyscallExecAddress {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        _caller: Caller<'_, RuntimeContext<DB>>,
+        address20_ptr: u32,
+        input_ptr: u32,
+        input_len: u32,
+        return_ptr: u32,
+        return_len: u32,
+        fuel_ptr: u32,
+    ) -> Result<i32, Trap> {
+        Err(SysExecResumable {
+            code_hash32_ptr: address20_ptr,
+            input_ptr,
+            input_len,
+            return_ptr,
+            return_len,
+            fuel_ptr,
+            is_static: false,
+            by_address: true,
+        }
+        .into())
+    }
+
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &mut RuntimeContext<DB>,
+        address: &Address,
+        input: Vec<u8>,
+        return_len: u32,
+        fuel_limit: Fuel,
+    ) -> Result<Fuel, i32> {
+        if let Some(precompile) = ctx.precompiles.as_ref().and_then(|v| v.get(address)) {
+            let result =
+                Self::fn_precompile_call(ctx, precompile.as_ref(), &input, return_len, fuel_limit);
+            ctx.arena.release(input);
+            return result;
+        }
+        match Self::resolve_code_hash(ctx, address) {
+            Some(bytecode_hash32) => {
+                SyscallExec::fn_exec(ctx, &bytecode_hash32, input, return_len, fuel_limit, false)
+            }
+            None => Ok(Self::fn_empty_call(ctx, fuel_limit)),
+        }
+    }
+
+    /// Returns hash of the bytecode to execute: rWASM code hash has priority, otherwise source
+    /// code hash is used, its preimage is EVM bytecode, so the runtime routes it through the EVM
+    /// interpreter
+    pub fn resolve_code_hash<DB: IJournaledTrie>(
+        ctx: &RuntimeContext<DB>,
+        address: &Address,
+    ) -> Option<[u8; 32]> {
+        let address32 = address.into_word();
+        if let Some(access_list_recorder) = ctx.access_list_recorder.as_ref() {
+            access_list_recorder.record_key(address32.as_slice());
+        }
+        let (fields, _, _) = ctx.jzkt.as_ref()?.get(&address32, false)?;
+        let rwasm_code_hash = fields[JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD as usize];
+        if rwasm_code_hash != [0u8; 32] && rwasm_code_hash != POSEIDON_EMPTY.0 {
+            return Some(rwasm_code_hash);
+        }
+        let source_code_hash = fields[JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD as usize];
+        if source_code_hash != [0u8; 32] && source_code_hash != KECCAK_EMPTY.0 {
+            return Some(source_code_hash);
+        }
+        None
+    }
+
+    /// Runs the native precompile instead of the account code, the output is returned as
+    /// return data of the call
+    pub fn fn_precompile_call<DB: IJournaledTrie>(
+        ctx: &mut RuntimeContext<DB>,
+        precompile: &dyn Precompile,
+        input: &[u8],
+        return_len: u32,
+        fuel_limit: Fuel,
+    ) -> Result<Fuel, i32> {
+        let execution_result = execute_precompile(precompile, input, fuel_limit);
+        ctx.execution_result.fuel_consumed += execution_result.fuel_consumed;
+        if return_len > 0 && execution_result.output.len() > return_len as usize {
+            return Err(ExitCode::OutputOverflow.into_i32());
+        }
+        let return_data = ctx.arena.alloc_from(&execution_result.output);
+        ctx.arena
+            .release(replace(&mut ctx.execution_result.return_data, return_data));
+        if execution_result.exit_code != ExitCode::Ok.into_i32() {
+            return Err(execution_result.exit_code);
+        }
+        // metering of the call can be disabled
+        Ok(fuel_limit.saturating_sub(execution_result.fuel_consumed))
+    }
+
+    /// Finishes the call to the account without code, no fuel is consumed
+    pub fn fn_empty_call<DB: IJournaledTrie>(
+        ctx: &mut RuntimeContext<DB>,
+        fuel_limit: Fuel,
+    ) -> Fuel {
+        ctx.arena
+            .release(take(&mut ctx.execution_result.return_data));
+        fuel_limit
+    }
+}
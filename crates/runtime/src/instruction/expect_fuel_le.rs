@@ -0,0 +1,47 @@
+use crate::{
+    assertion::{check_debug_assertions, AssertionFailure, AssertionKind},
+    RuntimeContext,
+};
+use fluentbase_types::{ExitCode, IJournaledTrie};
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallExpectFuelLe;
+
+impl SyscallExpectFuelLe {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        max_fuel: u64,
+    ) -> Result<(), Trap> {
+        check_debug_assertions(caller.data()).map_err(|err| err.into_trap())?;
+        // the consumed fuel isn't known without metering, so the check can't pass
+        let fuel_consumed = caller
+            .fuel_consumed()
+            .filter(|_| !caller.data().fuel_limit.is_zero())
+            .ok_or_else(|| ExitCode::NotSupportedCall.into_trap())?;
+        Self::fn_impl(caller.data_mut(), fuel_consumed, max_fuel).map_err(|err| err.into_trap())
+    }
+
+    /// Fails with `AssertionFailed` if the execution has consumed more fuel than the limit
+    /// (including the fuel of this call), unmetered executions fail with `NotSupportedCall`
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &mut RuntimeContext<DB>,
+        fuel_consumed: u64,
+        max_fuel: u64,
+    ) -> Result<(), ExitCode> {
+        if fuel_consumed <= max_fuel {
+            return Ok(());
+        }
+        ctx.execution_result.assertion_failure = Some(AssertionFailure {
+            kind: AssertionKind::FuelLe,
+            expected: max_fuel.to_le_bytes().to_vec(),
+            actual: fuel_consumed.to_le_bytes().to_vec(),
+            message: format!(
+                "consumed fuel {} exceeds the limit {} by {}",
+                fuel_consumed,
+                max_fuel,
+                fuel_consumed - max_fuel
+            ),
+        });
+        Err(ExitCode::AssertionFailed)
+    }
+}
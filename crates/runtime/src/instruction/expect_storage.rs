@@ -0,0 +1,49 @@
+use crate::{
+    assertion::{check_debug_assertions, AssertionFailure, AssertionKind},
+    instruction::storage_read_external::SyscallStorageReadExternal,
+    RuntimeContext,
+};
+use fluentbase_types::{Address, ExitCode, IJournaledTrie, U256};
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallExpectStorage;
+
+impl SyscallExpectStorage {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        address20_offset: u32,
+        slot32_offset: u32,
+        value32_offset: u32,
+    ) -> Result<(), Trap> {
+        check_debug_assertions(caller.data()).map_err(|err| err.into_trap())?;
+        let address = Address::from_slice(caller.read_memory(address20_offset, 20)?);
+        let slot = U256::from_le_slice(caller.read_memory(slot32_offset, 32)?);
+        let value = U256::from_le_slice(caller.read_memory(value32_offset, 32)?);
+        Self::fn_impl(caller.data_mut(), &address, &slot, &value).map_err(|err| err.into_trap())
+    }
+
+    /// Fails with `AssertionFailed` if the current (uncommitted) value of the storage slot isn't
+    /// equal to the expected one, absent slots are zero
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &mut RuntimeContext<DB>,
+        address: &Address,
+        slot: &U256,
+        expected: &U256,
+    ) -> Result<(), ExitCode> {
+        let (actual, _) = SyscallStorageReadExternal::fn_impl(ctx, address, slot, false)?;
+        let actual = U256::from_le_bytes(actual);
+        if actual == *expected {
+            return Ok(());
+        }
+        ctx.execution_result.assertion_failure = Some(AssertionFailure {
+            kind: AssertionKind::Storage,
+            expected: expected.as_le_slice().to_vec(),
+            actual: actual.as_le_slice().to_vec(),
+            message: format!(
+                "storage slot {} of {} is {}, expected {}",
+                slot, address, actual, expected
+            ),
+        });
+        Err(ExitCode::AssertionFailed)
+    }
+}
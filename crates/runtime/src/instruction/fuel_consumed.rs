@@ -0,0 +1,17 @@
+use crate::RuntimeContext;
+use fluentbase_types::IJournaledTrie;
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallFuelConsumed;
+
+impl SyscallFuelConsumed {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        caller: Caller<'_, RuntimeContext<DB>>,
+    ) -> Result<u64, Trap> {
+        Ok(caller.fuel_consumed().unwrap_or_default())
+    }
+
+    pub fn fn_impl<DB: IJournaledTrie>(ctx: &RuntimeContext<DB>) -> u64 {
+        ctx.execution_result.fuel_consumed.get()
+    }
+}
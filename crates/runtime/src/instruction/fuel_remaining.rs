@@ -0,0 +1,23 @@
+use crate::RuntimeContext;
+use fluentbase_types::IJournaledTrie;
+use rwasm::{core::Trap, errors::FuelError, Caller};
+
+pub struct SyscallFuelRemaining;
+
+impl SyscallFuelRemaining {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+    ) -> Result<u64, Trap> {
+        // consuming zero fuel is the only way to get remaining fuel from the store
+        match caller.consume_fuel(0) {
+            Ok(remaining) => Ok(remaining),
+            Err(FuelError::FuelMeteringDisabled) => Ok(u64::MAX),
+            Err(FuelError::OutOfFuel) => Ok(0),
+        }
+    }
+
+    pub fn fn_impl<DB: IJournaledTrie>(_ctx: &RuntimeContext<DB>) -> u64 {
+        // fuel is not metered in this mode
+        u64::MAX
+    }
+}
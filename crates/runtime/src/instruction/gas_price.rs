@@ -0,0 +1,21 @@
+use crate::RuntimeContext;
+use fluentbase_types::{IJournaledTrie, U256};
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallGasPrice;
+
+impl SyscallGasPrice {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        output32_ptr: u32,
+    ) -> Result<(), Trap> {
+        let gas_price = Self::fn_impl(caller.data());
+        caller.write_memory(output32_ptr, gas_price.as_le_slice())?;
+        Ok(())
+    }
+
+    /// Returns effective gas price of the transaction (encoded as little-endian 32 bytes)
+    pub fn fn_impl<DB: IJournaledTrie>(ctx: &RuntimeContext<DB>) -> U256 {
+        ctx.gas_fees.effective_gas_price()
+    }
+}
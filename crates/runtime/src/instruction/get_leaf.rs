@@ -15,6 +15,11 @@ impl SyscallGetLeaf {
         let key = caller.read_memory(key32_offset, 32)?.to_vec();
         let is_cold = match Self::fn_impl(caller.data_mut(), &key, field, committed != 0) {
             Some((value, is_cold)) => {
+                if let Some(trace_writer) = caller.data().trace_writer.as_ref() {
+                    let clk = caller.fuel_consumed().unwrap_or_default();
+                    trace_writer.push_storage(clk, false, &key, &value);
+                    trace_writer.push_memory_write(clk, output32_offset, &value);
+                }
                 caller.write_memory(output32_offset, &value)?;
                 is_cold
             }
@@ -29,6 +34,9 @@ impl SyscallGetLeaf {
         field: u32,
         committed: bool,
     ) -> Option<([u8; 32], bool)> {
+        if let Some(access_list_recorder) = ctx.access_list_recorder.as_ref() {
+            access_list_recorder.record_key(key);
+        }
         let (field_values, _flags, is_cold) = ctx.jzkt().get(key.try_into().unwrap(), committed)?;
         let field_value = field_values.get(field as usize)?;
         if field_value.len() < 32 {
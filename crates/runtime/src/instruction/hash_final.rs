@@ -0,0 +1,31 @@
+use crate::RuntimeContext;
+use fluentbase_types::{ExitCode, IJournaledTrie, B256};
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallHashFinal;
+
+impl SyscallHashFinal {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        handle: u32,
+        output32_offset: u32,
+    ) -> Result<(), Trap> {
+        let hash = Self::fn_impl(caller.data_mut(), handle).map_err(|err| err.into_trap())?;
+        caller.write_memory(output32_offset, hash.as_slice())?;
+        Ok(())
+    }
+
+    /// Returns the hash of all absorbed data and closes the context, the handle can't be used
+    /// after that
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &mut RuntimeContext<DB>,
+        handle: u32,
+    ) -> Result<B256, ExitCode> {
+        let hasher = ctx
+            .hashers
+            .get_mut(handle as usize)
+            .and_then(|hasher| hasher.take())
+            .ok_or(ExitCode::HashContextError)?;
+        Ok(hasher.finalize())
+    }
+}
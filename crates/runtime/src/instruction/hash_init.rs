@@ -0,0 +1,32 @@
+use crate::RuntimeContext;
+use fluentbase_types::{ExitCode, IJournaledTrie, Keccak256};
+use rwasm::{core::Trap, Caller};
+
+/// Max number of hashing contexts opened by one execution at the same time
+pub const MAX_HASH_CONTEXTS: usize = 16;
+
+pub struct SyscallHashInit;
+
+impl SyscallHashInit {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+    ) -> Result<u32, Trap> {
+        Self::fn_impl(caller.data_mut()).map_err(|err| err.into_trap())
+    }
+
+    /// Opens new keccak256 hashing context and returns its handle, handles of finalized
+    /// contexts are reused
+    pub fn fn_impl<DB: IJournaledTrie>(ctx: &mut RuntimeContext<DB>) -> Result<u32, ExitCode> {
+        let hashers = &mut ctx.hashers;
+        let handle = match hashers.iter().position(|hasher| hasher.is_none()) {
+            Some(handle) => handle,
+            None if hashers.len() < MAX_HASH_CONTEXTS => {
+                hashers.push(None);
+                hashers.len() - 1
+            }
+            None => return Err(ExitCode::HashContextError),
+        };
+        hashers[handle] = Some(Keccak256::new());
+        Ok(handle as u32)
+    }
+}
@@ -0,0 +1,39 @@
+use crate::RuntimeContext;
+use fluentbase_types::{ExitCode, IJournaledTrie};
+use rwasm::{core::Trap, errors::FuelError, Caller};
+
+/// Fuel charged for every byte absorbed by the hashing context
+pub const HASH_UPDATE_FUEL_PER_BYTE: u64 = 1;
+
+pub struct SyscallHashUpdate;
+
+impl SyscallHashUpdate {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        handle: u32,
+        data_offset: u32,
+        data_len: u32,
+    ) -> Result<(), Trap> {
+        // charge before reading the memory, so big updates fail fast
+        match caller.consume_fuel(data_len as u64 * HASH_UPDATE_FUEL_PER_BYTE) {
+            Ok(_) | Err(FuelError::FuelMeteringDisabled) => {}
+            Err(FuelError::OutOfFuel) => return Err(ExitCode::OutOfGas.into_trap()),
+        }
+        let data = caller.read_memory(data_offset, data_len)?.to_vec();
+        Self::fn_impl(caller.data_mut(), handle, &data).map_err(|err| err.into_trap())
+    }
+
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &mut RuntimeContext<DB>,
+        handle: u32,
+        data: &[u8],
+    ) -> Result<(), ExitCode> {
+        let hasher = ctx
+            .hashers
+            .get_mut(handle as usize)
+            .and_then(|hasher| hasher.as_mut())
+            .ok_or(ExitCode::HashContextError)?;
+        hasher.update(data);
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use crate::RuntimeContext;
+use fluentbase_types::{ExitCode, IJournaledTrie};
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallInputCopy;
+
+impl SyscallInputCopy {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        target: u32,
+        offset: u32,
+        length: u32,
+    ) -> Result<u32, Trap> {
+        let data = Self::fn_impl(caller.data(), offset, length)
+            .map_err(|err| err.into_trap())?
+            .to_vec();
+        if !data.is_empty() {
+            caller.write_memory(target, &data)?;
+        }
+        Ok(data.len() as u32)
+    }
+
+    /// Returns up to `length` bytes of the input starting from the offset, the window is
+    /// truncated at the end of the input. The offset must be inside the input (offset equal to
+    /// the input size gives an empty window), otherwise the execution halts with
+    /// `InputOutOfBounds`
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &RuntimeContext<DB>,
+        offset: u32,
+        length: u32,
+    ) -> Result<&[u8], ExitCode> {
+        let input = &ctx.input;
+        if offset as usize > input.len() {
+            return Err(ExitCode::InputOutOfBounds);
+        }
+        let end = (offset as u64 + length as u64).min(input.len() as u64);
+        Ok(&input[offset as usize..end as usize])
+    }
+}
@@ -0,0 +1,36 @@
+use crate::RuntimeContext;
+use fluentbase_types::{ExitCode, IJournaledTrie};
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallInputReveal;
+
+impl SyscallInputReveal {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        section: u32,
+        target: u32,
+        target_len: u32,
+    ) -> Result<u32, Trap> {
+        let plaintext = Self::fn_impl(caller.data(), section).map_err(|err| err.into_trap())?;
+        // the plaintext goes straight into the guest memory, it's never stored in the context
+        // (return data and output are visible to the caller of the contract)
+        let length = plaintext.len().min(target_len as usize);
+        caller.write_memory(target, &plaintext[..length])?;
+        Ok(plaintext.len() as u32)
+    }
+
+    /// Decrypts the sealed section of the input (see [`crate::envelope::InputEnvelope`]) with
+    /// the host decryptor. The handler copies up to `target_len` bytes of the plaintext into the
+    /// target and returns the plaintext size, so the contract can retry with a bigger buffer.
+    /// Calls without sealed sections fail with `InputOutOfBounds`.
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &RuntimeContext<DB>,
+        section: u32,
+    ) -> Result<Vec<u8>, ExitCode> {
+        let sealed_inputs = ctx
+            .sealed_inputs
+            .as_ref()
+            .ok_or(ExitCode::InputOutOfBounds)?;
+        sealed_inputs.reveal(section, &ctx.bytecode.resolve_hash())
+    }
+}
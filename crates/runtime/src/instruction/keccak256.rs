@@ -16,6 +16,15 @@ impl SyscallKeccak256 {
         Ok(())
     }
 
+    /// With the `asm-keccak` feature the permutation is computed by the assembly backend
+    /// (selected for the target architecture at build time)
+    #[cfg(feature = "asm-keccak")]
+    pub fn fn_impl(data: &[u8]) -> [u8; 32] {
+        use keccak_asm::{Digest, Keccak256};
+        Keccak256::digest(data).into()
+    }
+
+    #[cfg(not(feature = "asm-keccak"))]
     pub fn fn_impl(data: &[u8]) -> [u8; 32] {
         let mut result = [0u8; 32];
         keccak_hash::write_keccak(data, &mut result);
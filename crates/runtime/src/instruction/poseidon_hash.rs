@@ -14,11 +14,12 @@ impl SyscallPoseidonHash {
         fd_offset: u32,
         output_offset: u32,
     ) -> Result<(), Trap> {
-        let output = Self::fn_impl(
-            caller.read_memory(fa_offset, 32)?,
-            caller.read_memory(fb_offset, 32)?,
-            caller.read_memory(fd_offset, 32)?,
-        )?;
+        let fa = caller.read_memory(fa_offset, 32)?.to_vec();
+        let fb = caller.read_memory(fb_offset, 32)?.to_vec();
+        let output = Self::fn_impl(&fa, &fb, caller.read_memory(fd_offset, 32)?)?;
+        if let Some(access_list_recorder) = caller.data().access_list_recorder.as_ref() {
+            access_list_recorder.record_hash(&fa, &fb, &output);
+        }
         caller.write_memory(output_offset, &output)?;
         Ok(())
     }
@@ -0,0 +1,29 @@
+use crate::RuntimeContext;
+use fluentbase_types::IJournaledTrie;
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallProfileEnter;
+
+impl SyscallProfileEnter {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        func_idx: u32,
+    ) -> Result<(), Trap> {
+        let fuel_consumed = caller.fuel_consumed().unwrap_or_default();
+        Self::fn_impl(caller.data_mut(), func_idx, fuel_consumed);
+        Ok(())
+    }
+
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &mut RuntimeContext<DB>,
+        func_idx: u32,
+        fuel_consumed: u64,
+    ) {
+        if let Some(fuel_profiler) = ctx.fuel_profiler.as_ref() {
+            fuel_profiler.enter(func_idx, fuel_consumed);
+        }
+        if let Some(trace_writer) = ctx.trace_writer.as_ref() {
+            trace_writer.push_function_enter(fuel_consumed, func_idx);
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use crate::RuntimeContext;
+use fluentbase_types::IJournaledTrie;
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallProfileExit;
+
+impl SyscallProfileExit {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+    ) -> Result<(), Trap> {
+        let fuel_consumed = caller.fuel_consumed().unwrap_or_default();
+        Self::fn_impl(caller.data_mut(), fuel_consumed);
+        Ok(())
+    }
+
+    pub fn fn_impl<DB: IJournaledTrie>(ctx: &mut RuntimeContext<DB>, fuel_consumed: u64) {
+        if let Some(fuel_profiler) = ctx.fuel_profiler.as_ref() {
+            fuel_profiler.exit(fuel_consumed);
+        }
+        if let Some(trace_writer) = ctx.trace_writer.as_ref() {
+            trace_writer.push_function_exit(fuel_consumed);
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use crate::RuntimeContext;
+use fluentbase_types::{ExitCode, IJournaledTrie};
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallRemoveLeaf;
+
+impl SyscallRemoveLeaf {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        key32_offset: u32,
+    ) -> Result<(), Trap> {
+        let key = caller.read_memory(key32_offset, 32)?.to_vec();
+        Self::fn_impl(caller.data_mut(), &key).map_err(|err| err.into_trap())?;
+        if let Some(trace_writer) = caller.data().trace_writer.as_ref() {
+            let clk = caller.fuel_consumed().unwrap_or_default();
+            trace_writer.push_storage(clk, true, &key, &[0u8; 32]);
+        }
+        Ok(())
+    }
+
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &mut RuntimeContext<DB>,
+        key: &[u8],
+    ) -> Result<(), ExitCode> {
+        if ctx.is_static {
+            return Err(ExitCode::WriteProtection);
+        }
+        if let Some(access_list_recorder) = ctx.access_list_recorder.as_ref() {
+            access_list_recorder.record_key(key);
+        }
+        ctx.jzkt().remove(key.try_into().unwrap());
+        Ok(())
+    }
+}
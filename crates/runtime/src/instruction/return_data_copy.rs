@@ -0,0 +1,37 @@
+use crate::RuntimeContext;
+use fluentbase_types::{ExitCode, IJournaledTrie};
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallReturnDataCopy;
+
+impl SyscallReturnDataCopy {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        target: u32,
+        offset: u32,
+        length: u32,
+    ) -> Result<(), Trap> {
+        let data = Self::fn_impl(caller.data(), offset, length)
+            .map_err(|err| err.into_trap())?
+            .to_vec();
+        if !data.is_empty() {
+            caller.write_memory(target, &data)?;
+        }
+        Ok(())
+    }
+
+    /// Like EVM `RETURNDATACOPY` the range must be inside the return data (even if the length
+    /// is zero), otherwise the execution halts with `ReturnDataOutOfBounds`
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &RuntimeContext<DB>,
+        offset: u32,
+        length: u32,
+    ) -> Result<&[u8], ExitCode> {
+        let return_data = &ctx.execution_result.return_data;
+        let end = offset as u64 + length as u64;
+        if end > return_data.len() as u64 {
+            return Err(ExitCode::ReturnDataOutOfBounds);
+        }
+        Ok(&return_data[offset as usize..end as usize])
+    }
+}
@@ -1,5 +1,5 @@
 use crate::RuntimeContext;
-use fluentbase_types::{IJournaledTrie, JournalCheckpoint};
+use fluentbase_types::{ExitCode, IJournaledTrie, JournalCheckpoint};
 use rwasm::{core::Trap, Caller};
 
 pub struct SyscallRollback;
@@ -9,14 +9,23 @@ impl SyscallRollback {
         mut caller: Caller<'_, RuntimeContext<DB>>,
         checkpoint: u64,
     ) -> Result<(), Trap> {
-        Self::fn_impl(caller.data_mut(), JournalCheckpoint::from_u64(checkpoint));
-        Ok(())
+        Self::fn_impl(caller.data_mut(), JournalCheckpoint::from_u64(checkpoint))
+            .map_err(|err| err.into_trap())
     }
 
     pub fn fn_impl<DB: IJournaledTrie>(
         ctx: &mut RuntimeContext<DB>,
         checkpoint: JournalCheckpoint,
-    ) {
+    ) -> Result<(), ExitCode> {
+        // rollback discards writes of the callers, so it changes the state too
+        if ctx.is_static {
+            return Err(ExitCode::WriteProtection);
+        }
+        // checkpoint comes from the guest, so it must be validated to not panic on rollback
+        if !ctx.jzkt().is_valid_checkpoint(checkpoint) {
+            return Err(ExitCode::InvalidCheckpoint);
+        }
         ctx.jzkt().rollback(checkpoint);
+        Ok(())
     }
 }
@@ -1,4 +1,4 @@
-use crate::RuntimeContext;
+use crate::{memory_init::check_initial_memory, RuntimeContext};
 use fluentbase_types::IJournaledTrie;
 use rwasm::{core::Trap, Caller};
 
@@ -6,8 +6,10 @@ pub struct SyscallState;
 
 impl SyscallState {
     pub fn fn_handler<DB: IJournaledTrie>(
-        caller: Caller<'_, RuntimeContext<DB>>,
+        mut caller: Caller<'_, RuntimeContext<DB>>,
     ) -> Result<u32, Trap> {
+        // state router calls it right after memory initialization of the entrypoint
+        check_initial_memory(&mut caller)?;
         Ok(Self::fn_impl(caller.data()))
     }
 
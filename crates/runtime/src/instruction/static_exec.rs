@@ -0,0 +1,44 @@
+use crate::{
+    instruction::exec::{SysExecResumable, SyscallExec},
+    RuntimeContext,
+};
+use fluentbase_types::{Fuel, IJournaledTrie};
+use rwasm::{core::Trap, Caller};
+
+/// The same as `_exec`, but the nested call and all its subcalls are read-only, any state change
+/// fails with `WriteProtection` (an equivalent of EVM `STATICCALL`)
+pub struct SyscallStaticExec;
+
+impl SyscallStaticExec {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        _caller: Caller<'_, RuntimeContext<DB>>,
+        code_hash32_ptr: u32,
+        input_ptr: u32,
+        input_len: u32,
+        return_ptr: u32,
+        return_len: u32,
+        fuel_ptr: u32,
+    ) -> Result<i32, Trap> {
+        Err(SysExecResumable {
+            code_hash32_ptr,
+            input_ptr,
+            input_len,
+            return_ptr,
+            return_len,
+            fuel_ptr,
+            is_static: true,
+            by_address: false,
+        }
+        .into())
+    }
+
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &mut RuntimeContext<DB>,
+        bytecode_hash32: &[u8; 32],
+        input: Vec<u8>,
+        return_len: u32,
+        fuel_limit: Fuel,
+    ) -> Result<Fuel, i32> {
+        SyscallExec::fn_exec(ctx, bytecode_hash32, input, return_len, fuel_limit, true)
+    }
+}
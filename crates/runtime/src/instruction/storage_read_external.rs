@@ -0,0 +1,70 @@
+use crate::{
+    import::contract_storage_key,
+    replay::{write_bytes, write_u32},
+    RuntimeContext,
+};
+use fluentbase_types::{Address, ExitCode, IJournaledTrie, U256};
+use rwasm::{core::Trap, Caller};
+use std::mem::replace;
+
+pub struct SyscallStorageReadExternal;
+
+impl SyscallStorageReadExternal {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        address20_offset: u32,
+        slot32_offset: u32,
+        output32_offset: u32,
+        with_proof: u32,
+    ) -> Result<u32, Trap> {
+        let address = Address::from_slice(caller.read_memory(address20_offset, 20)?);
+        let slot = U256::from_le_slice(caller.read_memory(slot32_offset, 32)?);
+        let (value, proof_len) = Self::fn_impl(caller.data_mut(), &address, &slot, with_proof != 0)
+            .map_err(|err| err.into_trap())?;
+        if let Some(trace_writer) = caller.data().trace_writer.as_ref() {
+            let clk = caller.fuel_consumed().unwrap_or_default();
+            trace_writer.push_memory_write(clk, output32_offset, &value);
+        }
+        caller.write_memory(output32_offset, &value)?;
+        Ok(proof_len)
+    }
+
+    /// Reads the storage slot of any contract, the call has static semantics (nothing is
+    /// written), so it's allowed in static calls and shared executions. Absent slots are zero.
+    ///
+    /// If the proof is requested, the committed value is read (the proof can't cover
+    /// uncommitted changes) and the Merkle proof of the storage key against the last committed
+    /// root replaces the return data, the size of the proof is returned. The proof is encoded
+    /// as `nodes_count || (node_len || node)*` with little-endian u32 lengths, it has no nodes
+    /// if the storage can't prove the key.
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &mut RuntimeContext<DB>,
+        address: &Address,
+        slot: &U256,
+        with_proof: bool,
+    ) -> Result<([u8; 32], u32), ExitCode> {
+        let storage_key = contract_storage_key(address, slot)?;
+        if let Some(access_list_recorder) = ctx.access_list_recorder.as_ref() {
+            access_list_recorder.record_key(&storage_key);
+        }
+        let mut value = [0u8; 32];
+        if let Some((field_values, _flags, _is_cold)) = ctx.jzkt().get(&storage_key, with_proof) {
+            if let Some(field_value) = field_values.first() {
+                value.copy_from_slice(field_value);
+            }
+        }
+        if !with_proof {
+            return Ok((value, 0));
+        }
+        let nodes = ctx.jzkt().proof(&storage_key).unwrap_or_default();
+        let mut proof = ctx
+            .arena
+            .alloc(4 + nodes.iter().map(|node| 4 + node.len()).sum::<usize>());
+        write_u32(&mut proof, nodes.len() as u32);
+        nodes.iter().for_each(|node| write_bytes(&mut proof, node));
+        let proof_len = proof.len() as u32;
+        ctx.arena
+            .release(replace(&mut ctx.execution_result.return_data, proof));
+        Ok((value, proof_len))
+    }
+}
@@ -0,0 +1,73 @@
+use crate::RuntimeContext;
+use fluentbase_types::{ExitCode, IJournaledTrie};
+use rwasm::{
+    core::{HostError, Trap},
+    Caller,
+};
+use std::{
+    fmt::{Display, Formatter},
+    mem::replace,
+};
+
+pub struct SyscallSuspend;
+
+#[derive(Debug, Clone)]
+pub struct SysSuspendResumable {
+    pub request: Vec<u8>,
+    pub return_ptr: u32,
+    pub return_len: u32,
+}
+
+impl Display for SysSuspendResumable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "execution is suspended")
+    }
+}
+
+impl HostError for SysSuspendResumable {}
+
+impl SyscallSuspend {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        caller: Caller<'_, RuntimeContext<DB>>,
+        request_ptr: u32,
+        request_len: u32,
+        return_ptr: u32,
+        return_len: u32,
+    ) -> Result<i32, Trap> {
+        let request = caller.read_memory(request_ptr, request_len)?.to_vec();
+        Err(SysSuspendResumable {
+            request,
+            return_ptr,
+            return_len,
+        }
+        .into())
+    }
+
+    /// Writes response of the embedder into the guest memory, the whole response is available
+    /// as the return data
+    pub fn fn_continue<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        state: &SysSuspendResumable,
+        response: &[u8],
+    ) -> Result<i32, Trap> {
+        let exit_code = Self::fn_impl(caller.data_mut(), state.return_len, response);
+        if exit_code == ExitCode::Ok.into_i32() && state.return_len > 0 {
+            caller.write_memory(state.return_ptr, response)?;
+        }
+        Ok(exit_code)
+    }
+
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &mut RuntimeContext<DB>,
+        return_len: u32,
+        response: &[u8],
+    ) -> i32 {
+        let return_data = ctx.arena.alloc_from(response);
+        ctx.arena
+            .release(replace(&mut ctx.execution_result.return_data, return_data));
+        if return_len > 0 && response.len() > return_len as usize {
+            return ExitCode::OutputOverflow.into_i32();
+        }
+        ExitCode::Ok.into_i32()
+    }
+}
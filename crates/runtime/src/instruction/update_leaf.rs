@@ -1,4 +1,5 @@
 use crate::RuntimeContext;
+use fluentbase_poseidon::poseidon_hash;
 use fluentbase_types::{ExitCode, IJournaledTrie};
 use rwasm::{core::Trap, Caller};
 
@@ -22,7 +23,16 @@ impl SyscallUpdateLeaf {
                 res
             })
             .collect::<Vec<_>>();
+        let value = caller
+            .data()
+            .trace_writer
+            .is_some()
+            .then(|| poseidon_hash(&vals32.concat()));
         Self::fn_impl(caller.data_mut(), &key, flags, vals32).map_err(|err| err.into_trap())?;
+        if let (Some(trace_writer), Some(value)) = (caller.data().trace_writer.as_ref(), value) {
+            let clk = caller.fuel_consumed().unwrap_or_default();
+            trace_writer.push_storage(clk, true, &key, &value);
+        }
         Ok(())
     }
 
@@ -32,6 +42,12 @@ impl SyscallUpdateLeaf {
         value_flags: u32,
         vals: Vec<[u8; 32]>,
     ) -> Result<(), ExitCode> {
+        if ctx.is_static {
+            return Err(ExitCode::WriteProtection);
+        }
+        if let Some(access_list_recorder) = ctx.access_list_recorder.as_ref() {
+            access_list_recorder.record_key(key);
+        }
         ctx.jzkt()
             .update(key.try_into().unwrap(), &vals, value_flags);
         Ok(())
@@ -10,8 +10,11 @@ impl SyscallWrite {
         offset: u32,
         length: u32,
     ) -> Result<(), Trap> {
-        let data = caller.read_memory(offset, length)?.to_vec();
+        let arena = caller.data().arena.clone();
+        let mut data = arena.alloc(length as usize);
+        data.extend_from_slice(caller.read_memory(offset, length)?);
         Self::fn_impl(caller.data_mut(), &data);
+        arena.release(data);
         Ok(())
     }
 
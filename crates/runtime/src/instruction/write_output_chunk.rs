@@ -0,0 +1,35 @@
+use crate::RuntimeContext;
+use fluentbase_types::{ExitCode, IJournaledTrie};
+use rwasm::{core::Trap, Caller};
+
+pub struct SyscallWriteOutputChunk;
+
+impl SyscallWriteOutputChunk {
+    pub fn fn_handler<DB: IJournaledTrie>(
+        mut caller: Caller<'_, RuntimeContext<DB>>,
+        offset: u32,
+        length: u32,
+    ) -> Result<u32, Trap> {
+        let arena = caller.data().arena.clone();
+        let mut data = arena.alloc(length as usize);
+        data.extend_from_slice(caller.read_memory(offset, length)?);
+        let result = Self::fn_impl(caller.data_mut(), &data);
+        arena.release(data);
+        result.map_err(|err| err.into_trap())
+    }
+
+    /// Writes the chunk to the output stream and returns the number of accepted bytes, the rest
+    /// must be written again. Without the output stream the chunk is appended to the output
+    pub fn fn_impl<DB: IJournaledTrie>(
+        ctx: &mut RuntimeContext<DB>,
+        data: &[u8],
+    ) -> Result<u32, ExitCode> {
+        match ctx.output_stream.as_mut() {
+            Some(output_stream) => output_stream.write_chunk(data).map(|len| len as u32),
+            None => {
+                ctx.execution_result.output.extend_from_slice(data);
+                Ok(data.len() as u32)
+            }
+        }
+    }
+}
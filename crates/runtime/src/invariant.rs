@@ -0,0 +1,114 @@
+use fluentbase_types::JournalLog;
+use std::{
+    fmt::{Debug, Display, Formatter},
+    sync::Arc,
+};
+
+/// Leaf value with flags, `None` means that the key is absent
+pub type LeafValue = Option<(Vec<[u8; 32]>, u32)>;
+
+/// Change of the key that is going to be committed
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyChange {
+    pub key: [u8; 32],
+    pub prev_value: LeafValue,
+    pub value: LeafValue,
+}
+
+/// Everything the commit is going to write: changed keys (sorted by the key) and logs
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitView<'a> {
+    pub changes: &'a [KeyChange],
+    pub logs: &'a [JournalLog],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvariantViolation {
+    pub invariant: String,
+    pub message: String,
+}
+
+impl Display for InvariantViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invariant {} is violated: {}",
+            self.invariant, self.message
+        )
+    }
+}
+
+type Predicate = Arc<dyn Fn(&CommitView) -> Result<(), String> + Send + Sync>;
+
+/// Set of predicates evaluated before every commit of the journal, the commit is aborted with
+/// `InvariantViolation` on the first failed predicate and changes stay in the journal
+#[derive(Clone, Default)]
+pub struct InvariantChecker {
+    invariants: Vec<(String, Predicate)>,
+}
+
+impl Debug for InvariantChecker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.invariants.iter().map(|(name, _)| name))
+            .finish()
+    }
+}
+
+impl InvariantChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_invariant<F>(mut self, name: &str, predicate: F) -> Self
+    where
+        F: Fn(&CommitView) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.register(name, predicate);
+        self
+    }
+
+    pub fn register<F>(&mut self, name: &str, predicate: F)
+    where
+        F: Fn(&CommitView) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.invariants
+            .push((name.to_string(), Arc::new(predicate)));
+    }
+
+    /// Forbids writes (including removals) to the keys in the range `[start, end)`
+    pub fn with_reserved_range(self, name: &str, start: [u8; 32], end: [u8; 32]) -> Self {
+        self.with_invariant(name, move |commit| {
+            match commit
+                .changes
+                .iter()
+                .find(|change| change.key >= start && change.key < end)
+            {
+                Some(change) => Err(format!(
+                    "write to the reserved key 0x{}",
+                    hex::encode(change.key)
+                )),
+                None => Ok(()),
+            }
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.invariants.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.invariants.is_empty()
+    }
+
+    /// Evaluates invariants in the registration order
+    pub fn check(&self, commit: &CommitView) -> Result<(), InvariantViolation> {
+        for (name, predicate) in self.invariants.iter() {
+            predicate(commit).map_err(|message| InvariantViolation {
+                invariant: name.clone(),
+                message,
+            })?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,11 @@
-use crate::{types::InMemoryTrieDb, zktrie::ZkTrieStateDb, TrieStorage};
+use crate::{
+    invariant::{CommitView, InvariantChecker, InvariantViolation, KeyChange},
+    types::InMemoryTrieDb,
+    zktrie::ZkTrieStateDb,
+    TrieStorage,
+};
 use core::mem::take;
-use fluentbase_poseidon::{hash_with_domain, Poseidon};
+use fluentbase_poseidon::{hash_with_domain, poseidon_hasher};
 use fluentbase_types::{
     Address,
     Bytes,
@@ -30,19 +35,71 @@ macro_rules! bytes32 {
     }};
 }
 
+/// Checkpoint that can be rolled back to, equal checkpoints taken in a row share the entry, so
+/// it's dropped when the last holder releases it
+struct CheckpointEntry {
+    checkpoint: JournalCheckpoint,
+    // length of the preimage journal
+    preimages: usize,
+    holders: usize,
+}
+
 struct JournalTrieInner<DB: TrieStorage> {
     storage: DB,
     state: HashMap<[u8; 32], usize>,
     preimages: HashMap<[u8; 32], Vec<u8>>,
     logs: Vec<JournalLog>,
     journal: Vec<JournalEvent>,
+    // previous values of the changed preimages
+    preimage_journal: Vec<([u8; 32], Option<Vec<u8>>)>,
+    // checkpoints that can be rolled back to, released ones are dropped
+    checkpoints: Vec<CheckpointEntry>,
+    // every reader holds a copy, so we know whether some roots are still pinned
+    readers: Arc<()>,
     root: [u8; 32],
     committed: usize,
+    invariants: InvariantChecker,
+    // violation that aborted the last commit
+    violation: Option<InvariantViolation>,
+    // rewrite the last change of the key instead of appending one if no checkpoint separates them
+    compaction: bool,
 }
 
 impl<DB: TrieStorage> JournalTrieInner<DB> {
-    fn checkpoint(&self) -> JournalCheckpoint {
-        JournalCheckpoint(self.journal.len() as u32, self.logs.len() as u32)
+    fn checkpoint(&mut self) -> JournalCheckpoint {
+        let checkpoint = JournalCheckpoint(self.journal.len() as u32, self.logs.len() as u32);
+        let preimages = self.preimage_journal.len();
+        match self.checkpoints.last_mut() {
+            Some(entry) if entry.checkpoint == checkpoint && entry.preimages == preimages => {
+                entry.holders += 1;
+            }
+            _ => self.checkpoints.push(CheckpointEntry {
+                checkpoint,
+                preimages,
+                holders: 1,
+            }),
+        }
+        checkpoint
+    }
+
+    fn find_checkpoint(&self, checkpoint: JournalCheckpoint) -> Option<usize> {
+        self.checkpoints
+            .iter()
+            .rposition(|entry| entry.checkpoint == checkpoint)
+    }
+
+    fn release_checkpoint(&mut self, checkpoint: JournalCheckpoint) {
+        // the checkpoint might be dropped by the commit or the rollback to an earlier one already
+        let Some(pos) = self.find_checkpoint(checkpoint) else {
+            return;
+        };
+        // checkpoints taken after the released one belong to the finished call as well
+        self.checkpoints.truncate(pos + 1);
+        let entry = &mut self.checkpoints[pos];
+        entry.holders -= 1;
+        if entry.holders == 0 {
+            self.checkpoints.pop();
+        }
     }
 
     fn get(&self, key: &[u8; 32]) -> Option<(Vec<[u8; 32]>, u32, bool)> {
@@ -64,25 +121,45 @@ impl<DB: TrieStorage> JournalTrieInner<DB> {
     }
 
     fn update(&mut self, key: &[u8; 32], value: &Vec<[u8; 32]>, flags: u32) {
-        let pos = self.journal.len();
-        self.journal.push(JournalEvent::ItemChanged {
+        self.push_event(key, |prev_state| JournalEvent::ItemChanged {
             key: *key,
             preimage: value.clone(),
             flags,
-            prev_state: self.state.get(key).copied(),
+            prev_state,
         });
-        self.state.insert(*key, pos);
     }
 
     fn remove(&mut self, key: &[u8; 32]) {
-        let pos = self.journal.len();
-        self.journal.push(JournalEvent::ItemRemoved {
+        self.push_event(key, |prev_state| JournalEvent::ItemRemoved {
             key: *key,
-            prev_state: self.state.get(key).copied(),
+            prev_state,
         });
+    }
+
+    fn push_event<F: FnOnce(Option<usize>) -> JournalEvent>(&mut self, key: &[u8; 32], event: F) {
+        let prev_state = self.state.get(key).copied();
+        // no checkpoint can be rolled back to the position between the last change of the key
+        // and the new one, so the last change can be replaced keeping its previous state
+        if let Some(pos) = prev_state.filter(|pos| self.compaction && *pos >= self.segment_start())
+        {
+            let prev_state = self.journal[pos].prev_state();
+            self.journal[pos] = event(prev_state);
+            return;
+        }
+        let pos = self.journal.len();
+        self.journal.push(event(prev_state));
         self.state.insert(*key, pos);
     }
 
+    /// Position of the first journal event that isn't covered by any checkpoint
+    fn segment_start(&self) -> usize {
+        self.checkpoints
+            .last()
+            .map(|entry| entry.checkpoint.state())
+            .unwrap_or_default()
+            .max(self.committed)
+    }
+
     fn compute_root(&self) -> [u8; 32] {
         self.storage.compute_root()
     }
@@ -95,19 +172,83 @@ impl<DB: TrieStorage> JournalTrieInner<DB> {
         });
     }
 
+    fn logs(&self) -> &Vec<JournalLog> {
+        &self.logs
+    }
+
     fn journal(&self) -> &Vec<JournalEvent> {
         return &self.journal;
     }
 
-    fn commit(&mut self) -> Result<([u8; 32], Vec<JournalLog>), ExitCode> {
-        for (key, value) in self
-            .journal
+    /// Final values of the keys changed since the last commit. Keys that are rewritten with the
+    /// committed value (like accounts that are only touched by calls or slots that are restored
+    /// in the same block) are skipped, so commit doesn't rehash their trie paths.
+    fn dirty_changes(&self) -> HashMap<[u8; 32], Option<(Vec<[u8; 32]>, u32)>> {
+        self.journal
             .iter()
             .skip(self.committed)
             .map(|v| (*v.key(), v.preimage()))
             .collect::<HashMap<_, _>>()
             .into_iter()
-        {
+            .filter(|(key, value)| self.storage.get(&key[..]) != *value)
+            .collect()
+    }
+
+    fn commit(&mut self) -> Result<([u8; 32], Vec<JournalLog>), ExitCode> {
+        self.commit_inner(None)
+    }
+
+    fn commit_expect(
+        &mut self,
+        expected_root: &[u8; 32],
+    ) -> Result<([u8; 32], Vec<JournalLog>), ExitCode> {
+        self.commit_inner(Some(expected_root))
+    }
+
+    fn commit_inner(
+        &mut self,
+        expected_root: Option<&[u8; 32]>,
+    ) -> Result<([u8; 32], Vec<JournalLog>), ExitCode> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "journal_commit",
+            journal_len = self.journal.len() - self.committed,
+            preimages = self.preimages.len(),
+        )
+        .entered();
+        #[cfg(feature = "metrics")]
+        let time = std::time::Instant::now();
+        let changes = self.dirty_changes();
+        if !self.invariants.is_empty() {
+            let mut key_changes = changes
+                .iter()
+                .map(|(key, value)| KeyChange {
+                    key: *key,
+                    prev_value: self.storage.get(&key[..]),
+                    value: value.clone(),
+                })
+                .collect::<Vec<_>>();
+            key_changes.sort_by(|a, b| a.key.cmp(&b.key));
+            let commit = CommitView {
+                changes: &key_changes,
+                logs: &self.logs,
+            };
+            if let Err(violation) = self.invariants.check(&commit) {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(%violation, "journal commit rejected");
+                self.violation = Some(violation);
+                return Err(ExitCode::InvariantViolation);
+            }
+        }
+        self.violation = None;
+        // committed values of the changed keys to restore the storage if the root doesn't match
+        let prev_values = expected_root.map(|_| {
+            changes
+                .keys()
+                .map(|key| (*key, self.storage.get(&key[..])))
+                .collect::<Vec<_>>()
+        });
+        for (key, value) in changes.into_iter() {
             match value {
                 Some((value, flags)) => {
                     self.storage.update(&key[..], flags, &value)?;
@@ -117,16 +258,45 @@ impl<DB: TrieStorage> JournalTrieInner<DB> {
                 }
             }
         }
+        if let (Some(expected_root), Some(prev_values)) = (expected_root, prev_values) {
+            let root = self.storage.compute_root();
+            if root != *expected_root {
+                for (key, value) in prev_values.into_iter() {
+                    match value {
+                        Some((value, flags)) => self.storage.update(&key[..], flags, &value)?,
+                        None => self.storage.delete(&key[..])?,
+                    }
+                }
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    root = %hex::encode(root),
+                    expected_root = %hex::encode(expected_root),
+                    "journal commit rejected"
+                );
+                return Err(ExitCode::StateRootMismatch);
+            }
+        }
         for (hash, preimage) in self.preimages.iter() {
             self.storage
                 .update_preimage(hash, Bytes::from(preimage.clone()));
         }
+        self.storage.flush()?;
         self.journal.clear();
         self.preimages.clear();
+        self.preimage_journal.clear();
+        self.checkpoints.clear();
         self.state.clear();
         let logs = take(&mut self.logs);
         self.committed = 0;
         self.root = self.storage.compute_root();
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_trie_commit(time.elapsed());
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            root = %hex::encode(self.root),
+            logs = logs.len(),
+            "journal committed"
+        );
         Ok((self.root, logs))
     }
 
@@ -140,6 +310,23 @@ impl<DB: TrieStorage> JournalTrieInner<DB> {
                 self.journal.len()
             )
         }
+        let Some(pos) = self.find_checkpoint(checkpoint) else {
+            panic!(
+                "rollback to unknown or discarded checkpoint ({}, {})",
+                checkpoint.state(),
+                checkpoint.logs()
+            )
+        };
+        // discard descendants, but keep the checkpoint itself
+        let preimages = self.checkpoints[pos].preimages;
+        self.checkpoints.truncate(pos + 1);
+        while self.preimage_journal.len() > preimages {
+            let (hash, prev_preimage) = self.preimage_journal.pop().unwrap();
+            match prev_preimage {
+                Some(prev_preimage) => self.preimages.insert(hash, prev_preimage),
+                None => self.preimages.remove(&hash),
+            };
+        }
         self.journal
             .iter()
             .rev()
@@ -152,6 +339,12 @@ impl<DB: TrieStorage> JournalTrieInner<DB> {
                     self.state.remove(v.key());
                 }
             });
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            reverted = self.journal.len() - checkpoint.state(),
+            checkpoint = checkpoint.state(),
+            "journal rolled back"
+        );
         self.journal.truncate(checkpoint.state());
         self.logs.truncate(checkpoint.logs());
     }
@@ -168,7 +361,8 @@ impl<DB: TrieStorage> JournalTrieInner<DB> {
         // value hash stored inside trie must be equal to the provided value hash
         // TODO(dmitry123): "we can't do this check here because hash can also be keccak256"
         // write new preimage value into database
-        self.preimages.insert(value_hash, preimage.to_vec());
+        let prev_preimage = self.preimages.insert(value_hash, preimage.to_vec());
+        self.preimage_journal.push((value_hash, prev_preimage));
         true
     }
 
@@ -224,14 +418,84 @@ impl<DB: TrieStorage> JournaledTrie<DB> {
                 preimages: HashMap::new(),
                 logs: Vec::new(),
                 journal: Vec::new(),
+                preimage_journal: Vec::new(),
+                checkpoints: Vec::new(),
+                readers: Arc::new(()),
                 root,
                 committed: 0,
+                invariants: InvariantChecker::default(),
+                violation: None,
+                compaction: false,
             })),
         }
     }
 
+    /// Invariants are checked before every commit, see [`InvariantChecker`]
+    pub fn with_invariants(self, invariants: InvariantChecker) -> Self {
+        self.set_invariants(invariants);
+        self
+    }
+
+    pub fn set_invariants(&self, invariants: InvariantChecker) {
+        self.inner.write().unwrap().invariants = invariants;
+    }
+
+    /// Repeated writes of the same key between two checkpoints are collapsed into one journal
+    /// event, it bounds the journal of transactions rewriting the same slots (like airdrops and
+    /// migrations) by the number of distinct keys per checkpoint and speeds up the commit.
+    ///
+    /// Every checkpoint still rolls back to the exact state, but the journal no longer contains
+    /// intermediate values of the keys.
+    pub fn with_journal_compaction(self) -> Self {
+        self.set_journal_compaction(true);
+        self
+    }
+
+    pub fn set_journal_compaction(&self, compaction: bool) {
+        self.inner.write().unwrap().compaction = compaction;
+    }
+
+    /// Violation that aborted the last commit, it's reset by the successful commit
+    pub fn invariant_violation(&self) -> Option<InvariantViolation> {
+        self.inner.read().unwrap().violation.clone()
+    }
+
+    /// Drops trie nodes orphaned by the committed removals, see [`TrieStorage::compact`], the
+    /// compaction is skipped while there are readers pinned to the committed roots
+    pub fn compact(&self) -> usize {
+        let mut inner = self.inner.write().unwrap();
+        if Arc::strong_count(&inner.readers) > 1 {
+            return 0;
+        }
+        inner.storage.compact()
+    }
+
+    /// Creates read-only handle pinned to the last committed root, it can be sent to other
+    /// threads and isn't affected by the journal and next commits
+    pub fn reader(&self) -> JournaledTrieReader<DB> {
+        let inner = self.inner.read().unwrap();
+        JournaledTrieReader {
+            inner: self.inner.clone(),
+            root: inner.root,
+            _pin: inner.readers.clone(),
+        }
+    }
+
+    /// Keys that will be written into the trie by the next commit
+    pub fn dirty_keys(&self) -> Vec<[u8; 32]> {
+        let mut keys = self
+            .inner
+            .read()
+            .unwrap()
+            .dirty_changes()
+            .into_keys()
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys
+    }
+
     pub fn message_hash(val: &[u8]) -> Fr {
-        let mut hasher = Poseidon::<Fr, 3, 2>::new(8, 56);
+        let mut hasher = poseidon_hasher();
         const CHUNK_LEN: usize = 31;
         for chunk in val.chunks(CHUNK_LEN).into_iter() {
             let mut buffer32: [u8; 32] = [0u8; 32];
@@ -260,9 +524,48 @@ impl<DB: TrieStorage> JournaledTrie<DB> {
     }
 }
 
+/// Read-only view of the state at the pinned root, reads share the lock with the writer only
+/// for the lookup itself, so many readers can be served while new blocks are committed
+pub struct JournaledTrieReader<DB: TrieStorage> {
+    inner: Arc<RwLock<JournalTrieInner<DB>>>,
+    root: [u8; 32],
+    _pin: Arc<()>,
+}
+
+impl<DB: TrieStorage> Clone for JournaledTrieReader<DB> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            root: self.root,
+            _pin: self._pin.clone(),
+        }
+    }
+}
+
+impl<DB: TrieStorage> JournaledTrieReader<DB> {
+    pub fn root(&self) -> &[u8; 32] {
+        &self.root
+    }
+
+    pub fn get(&self, key: &[u8; 32]) -> Option<(Vec<[u8; 32]>, u32)> {
+        self.inner.read().unwrap().storage.get_at(&self.root, key)
+    }
+
+    pub fn preimage(&self, hash: &[u8; 32]) -> Vec<u8> {
+        // preimages are addressed by hash, so committed ones are valid for any root
+        self.inner
+            .write()
+            .unwrap()
+            .storage
+            .get_preimage(hash)
+            .map(|v| v.to_vec())
+            .unwrap_or_default()
+    }
+}
+
 impl<DB: TrieStorage> IJournaledTrie for JournaledTrie<DB> {
     fn checkpoint(&self) -> JournalCheckpoint {
-        self.inner.read().unwrap().checkpoint()
+        self.inner.write().unwrap().checkpoint()
     }
 
     fn get(&self, key: &[u8; 32], committed: bool) -> Option<(Vec<[u8; 32]>, u32, bool)> {
@@ -289,14 +592,37 @@ impl<DB: TrieStorage> IJournaledTrie for JournaledTrie<DB> {
         self.inner.write().unwrap().emit_log(address, topics, data)
     }
 
+    fn logs(&self) -> Vec<JournalLog> {
+        self.inner.read().unwrap().logs().clone()
+    }
+
     fn commit(&self) -> Result<([u8; 32], Vec<JournalLog>), ExitCode> {
         self.inner.write().unwrap().commit()
     }
 
+    fn commit_expect(
+        &self,
+        expected_root: &[u8; 32],
+    ) -> Result<([u8; 32], Vec<JournalLog>), ExitCode> {
+        self.inner.write().unwrap().commit_expect(expected_root)
+    }
+
     fn rollback(&self, checkpoint: JournalCheckpoint) {
         self.inner.write().unwrap().rollback(checkpoint)
     }
 
+    fn is_valid_checkpoint(&self, checkpoint: JournalCheckpoint) -> bool {
+        self.inner
+            .read()
+            .unwrap()
+            .find_checkpoint(checkpoint)
+            .is_some()
+    }
+
+    fn release_checkpoint(&self, checkpoint: JournalCheckpoint) {
+        self.inner.write().unwrap().release_checkpoint(checkpoint)
+    }
+
     fn update_preimage(&self, key: &[u8; 32], field: u32, preimage: &[u8]) -> bool {
         self.inner
             .write()
@@ -315,18 +641,23 @@ impl<DB: TrieStorage> IJournaledTrie for JournaledTrie<DB> {
     fn journal(&self) -> Vec<JournalEvent> {
         self.inner.write().unwrap().journal.clone()
     }
+
+    fn proof(&self, key: &[u8; 32]) -> Option<Vec<Vec<u8>>> {
+        self.inner.read().unwrap().storage.proof(key)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
+        invariant::InvariantChecker,
         journal::{IJournaledTrie, JournaledTrie},
         types::InMemoryTrieDb,
         zktrie::ZkTrieStateDb,
         TrieStorage,
     };
     use fluentbase_poseidon::poseidon_hash;
-    use fluentbase_types::JournalCheckpoint;
+    use fluentbase_types::{ExitCode, JournalCheckpoint};
 
     fn calc_trie_root(values: Vec<([u8; 32], Vec<[u8; 32]>, u32)>) -> [u8; 32] {
         let db = InMemoryTrieDb::default();
@@ -466,4 +797,280 @@ mod tests {
         assert_eq!(journal.compute_root(), calc_trie_root(vec![]));
         assert_eq!(journal.inner.read().unwrap().state.len(), 0);
     }
+
+    #[test]
+    fn test_rollback_to_nested_checkpoint() {
+        let db = InMemoryTrieDb::default();
+        let zktrie = ZkTrieStateDb::new_empty(db);
+        let journal = JournaledTrie::new(zktrie);
+        let checkpoint1 = journal.checkpoint();
+        journal.update(&bytes32!("key1"), &vec![bytes32!("val1")], 0);
+        let checkpoint2 = journal.checkpoint();
+        journal.update(&bytes32!("key2"), &vec![bytes32!("val2")], 0);
+        assert!(journal.update_preimage(&bytes32!("key2"), 0, b"preimage"));
+        let checkpoint3 = journal.checkpoint();
+        journal.update(&bytes32!("key3"), &vec![bytes32!("val3")], 0);
+        // rollback over several checkpoints discards descendants
+        journal.rollback(checkpoint2);
+        assert!(journal.is_valid_checkpoint(checkpoint1));
+        assert!(journal.is_valid_checkpoint(checkpoint2));
+        assert!(!journal.is_valid_checkpoint(checkpoint3));
+        assert!(journal.get(&bytes32!("key1"), false).is_some());
+        assert!(journal.get(&bytes32!("key2"), false).is_none());
+        assert!(journal.get(&bytes32!("key3"), false).is_none());
+        assert_eq!(journal.preimage_size(&bytes32!("val2")), 0);
+        // the same checkpoint can be reused
+        journal.update(&bytes32!("key4"), &vec![bytes32!("val4")], 0);
+        journal.rollback(checkpoint2);
+        journal.rollback(checkpoint1);
+        assert_eq!(journal.inner.read().unwrap().state.len(), 0);
+        assert!(!journal.is_valid_checkpoint(checkpoint2));
+    }
+
+    #[test]
+    #[should_panic(expected = "rollback to unknown or discarded checkpoint")]
+    fn test_rollback_to_discarded_checkpoint() {
+        let db = InMemoryTrieDb::default();
+        let zktrie = ZkTrieStateDb::new_empty(db);
+        let journal = JournaledTrie::new(zktrie);
+        let checkpoint1 = journal.checkpoint();
+        journal.update(&bytes32!("key1"), &vec![bytes32!("val1")], 0);
+        let checkpoint2 = journal.checkpoint();
+        journal.rollback(checkpoint1);
+        journal.update(&bytes32!("key2"), &vec![bytes32!("val2")], 0);
+        journal.update(&bytes32!("key3"), &vec![bytes32!("val3")], 0);
+        // checkpoint has the same index, but it refers to the discarded history
+        journal.rollback(checkpoint2);
+    }
+
+    #[test]
+    fn test_release_checkpoint() {
+        let db = InMemoryTrieDb::default();
+        let zktrie = ZkTrieStateDb::new_empty(db);
+        let journal = JournaledTrie::new(zktrie);
+        let checkpoint1 = journal.checkpoint();
+        // successful nested calls don't grow the list of checkpoints
+        for i in 0..100u32 {
+            let checkpoint = journal.checkpoint();
+            journal.update(&bytes32!(&i.to_le_bytes()), &vec![bytes32!("val")], 0);
+            let inner_checkpoint = journal.checkpoint();
+            journal.release_checkpoint(checkpoint);
+            assert!(!journal.is_valid_checkpoint(inner_checkpoint));
+        }
+        assert_eq!(journal.inner.read().unwrap().checkpoints.len(), 1);
+        // equal checkpoints are dropped by the last holder
+        let checkpoint2 = journal.checkpoint();
+        let checkpoint3 = journal.checkpoint();
+        assert_eq!(checkpoint2, checkpoint3);
+        journal.release_checkpoint(checkpoint3);
+        assert!(journal.is_valid_checkpoint(checkpoint2));
+        journal.update(&bytes32!("key"), &vec![bytes32!("val")], 0);
+        journal.rollback(checkpoint2);
+        journal.release_checkpoint(checkpoint2);
+        assert!(!journal.is_valid_checkpoint(checkpoint2));
+        // released changes are kept until the rollback to an earlier checkpoint
+        assert_eq!(journal.inner.read().unwrap().state.len(), 100);
+        journal.rollback(checkpoint1);
+        assert_eq!(journal.inner.read().unwrap().state.len(), 0);
+    }
+
+    #[test]
+    fn test_commit_expect() {
+        let db = InMemoryTrieDb::default();
+        let zktrie = ZkTrieStateDb::new_empty(db);
+        let journal = JournaledTrie::new(zktrie);
+        journal.update(&bytes32!("key1"), &vec![bytes32!("val1")], 0);
+        journal.commit().unwrap();
+        let root = journal.compute_root();
+        journal.update(&bytes32!("key1"), &vec![bytes32!("val2")], 0);
+        journal.update(&bytes32!("key2"), &vec![bytes32!("val2")], 1);
+        let expected_root = calc_trie_root(vec![
+            (bytes32!("key1"), vec![bytes32!("val2")], 0),
+            (bytes32!("key2"), vec![bytes32!("val2")], 1),
+        ]);
+        // mismatch restores the storage and keeps the journal
+        assert_eq!(
+            journal.commit_expect(&[1u8; 32]),
+            Err(ExitCode::StateRootMismatch)
+        );
+        assert_eq!(journal.compute_root(), root);
+        assert_eq!(journal.journal().len(), 2);
+        let (new_root, _) = journal.commit_expect(&expected_root).unwrap();
+        assert_eq!(new_root, expected_root);
+        assert_eq!(journal.compute_root(), expected_root);
+    }
+
+    #[test]
+    fn test_commit_invariants() {
+        let db = InMemoryTrieDb::default();
+        let zktrie = ZkTrieStateDb::new_empty(db);
+        let invariants = InvariantChecker::new()
+            .with_reserved_range("reserved", bytes32!("r"), bytes32!("s"))
+            .with_invariant("no_removals", |commit| {
+                match commit.changes.iter().any(|change| change.value.is_none()) {
+                    true => Err("key is removed".to_string()),
+                    false => Ok(()),
+                }
+            });
+        let journal = JournaledTrie::new(zktrie).with_invariants(invariants);
+        journal.update(&bytes32!("key1"), &vec![bytes32!("val1")], 0);
+        journal.commit().unwrap();
+        let root = journal.compute_root();
+        let checkpoint = journal.checkpoint();
+        // violation aborts the commit and keeps the journal
+        journal.update(&bytes32!("key2"), &vec![bytes32!("val2")], 0);
+        journal.update(&bytes32!("reserved"), &vec![bytes32!("val2")], 0);
+        assert_eq!(journal.commit(), Err(ExitCode::InvariantViolation));
+        let violation = journal.invariant_violation().unwrap();
+        assert_eq!(violation.invariant, "reserved");
+        assert_eq!(journal.compute_root(), root);
+        assert_eq!(journal.journal().len(), 2);
+        journal.rollback(checkpoint);
+        journal.remove(&bytes32!("key1"));
+        assert_eq!(journal.commit(), Err(ExitCode::InvariantViolation));
+        assert_eq!(
+            journal.invariant_violation().unwrap().to_string(),
+            "invariant no_removals is violated: key is removed"
+        );
+        journal.rollback(checkpoint);
+        journal.update(&bytes32!("key2"), &vec![bytes32!("val2")], 0);
+        journal.commit().unwrap();
+        assert!(journal.invariant_violation().is_none());
+    }
+
+    #[test]
+    fn test_remove_and_compact() {
+        let db = InMemoryTrieDb::default();
+        let zktrie = ZkTrieStateDb::new_empty(db).with_hard_delete();
+        let journal = JournaledTrie::new(zktrie);
+        journal.update(&bytes32!("key1"), &vec![bytes32!("val1")], 0);
+        journal.update(&bytes32!("key2"), &vec![bytes32!("val2")], 0);
+        journal.commit().unwrap();
+        // removal is journaled and can be rolled back
+        let checkpoint = journal.checkpoint();
+        journal.remove(&bytes32!("key2"));
+        assert!(journal.journal().last().unwrap().is_removed());
+        assert!(journal.get(&bytes32!("key2"), false).is_none());
+        assert!(journal.get(&bytes32!("key2"), true).is_some());
+        journal.rollback(checkpoint);
+        assert!(journal.get(&bytes32!("key2"), false).is_some());
+        // committed removal deletes the key from the trie
+        journal.remove(&bytes32!("key2"));
+        let (root, _) = journal.commit().unwrap();
+        assert_eq!(
+            root,
+            calc_trie_root(vec![(bytes32!("key1"), vec![bytes32!("val1")], 0)])
+        );
+        assert!(journal.get(&bytes32!("key2"), true).is_none());
+        assert!(journal.compact() > 0);
+        assert!(journal.get(&bytes32!("key1"), true).is_some());
+    }
+
+    #[test]
+    fn test_commit_skips_unchanged_keys() {
+        let db = InMemoryTrieDb::default();
+        let zktrie = ZkTrieStateDb::new_empty(db);
+        let journal = JournaledTrie::new(zktrie);
+        journal.update(&bytes32!("key1"), &vec![bytes32!("val1")], 0);
+        journal.update(&bytes32!("key2"), &vec![bytes32!("val2")], 0);
+        let (root, _) = journal.commit().unwrap();
+        // rewritten with the same value, changed and restored back, removed absent key
+        journal.update(&bytes32!("key1"), &vec![bytes32!("val1")], 0);
+        journal.update(&bytes32!("key2"), &vec![bytes32!("val3")], 0);
+        journal.update(&bytes32!("key2"), &vec![bytes32!("val2")], 0);
+        journal.remove(&bytes32!("key3"));
+        assert!(journal.dirty_keys().is_empty());
+        assert_eq!(journal.commit().unwrap().0, root);
+        // flags are part of the leaf
+        journal.update(&bytes32!("key1"), &vec![bytes32!("val1")], 1);
+        journal.update(&bytes32!("key2"), &vec![bytes32!("val2")], 0);
+        assert_eq!(journal.dirty_keys(), vec![bytes32!("key1")]);
+        assert_ne!(journal.commit().unwrap().0, root);
+    }
+
+    #[test]
+    fn test_journal_compaction() {
+        let db = InMemoryTrieDb::default();
+        let zktrie = ZkTrieStateDb::new_empty(db);
+        let journal = JournaledTrie::new(zktrie).with_journal_compaction();
+        for i in 0..1000u32 {
+            journal.update(&bytes32!("key1"), &vec![bytes32!(&i.to_le_bytes())], 0);
+        }
+        assert_eq!(journal.journal().len(), 1);
+        // changes after the checkpoint are journaled separately, so the rollback is exact
+        let checkpoint = journal.checkpoint();
+        for i in 1000..2000u32 {
+            journal.update(&bytes32!("key1"), &vec![bytes32!(&i.to_le_bytes())], 0);
+        }
+        journal.remove(&bytes32!("key2"));
+        journal.update(&bytes32!("key2"), &vec![bytes32!("val2")], 0);
+        assert_eq!(journal.journal().len(), 3);
+        assert_eq!(
+            journal.get(&bytes32!("key1"), false).unwrap().0,
+            vec![bytes32!(&1999u32.to_le_bytes())]
+        );
+        journal.rollback(checkpoint);
+        assert_eq!(journal.journal().len(), 1);
+        assert_eq!(
+            journal.get(&bytes32!("key1"), false).unwrap().0,
+            vec![bytes32!(&999u32.to_le_bytes())]
+        );
+        assert!(journal.get(&bytes32!("key2"), false).is_none());
+        journal.commit().unwrap();
+        assert_eq!(
+            journal.compute_root(),
+            calc_trie_root(vec![(
+                bytes32!("key1"),
+                vec![bytes32!(&999u32.to_le_bytes())],
+                0
+            )])
+        );
+    }
+
+    #[test]
+    fn test_concurrent_readers() {
+        let db = InMemoryTrieDb::default();
+        let zktrie = ZkTrieStateDb::new_empty(db).with_hard_delete();
+        let journal = JournaledTrie::new(zktrie);
+        journal.update(&bytes32!("key1"), &vec![bytes32!("val1")], 0);
+        let (root1, _) = journal.commit().unwrap();
+        let reader = journal.reader();
+        assert_eq!(reader.root(), &root1);
+        // uncommitted changes and next commits are invisible for the reader
+        journal.update(&bytes32!("key1"), &vec![bytes32!("val2")], 0);
+        journal.update(&bytes32!("key2"), &vec![bytes32!("val2")], 0);
+        assert_eq!(
+            reader.get(&bytes32!("key1")).unwrap().0,
+            vec![bytes32!("val1")]
+        );
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let reader = reader.clone();
+                scope.spawn(move || {
+                    for _ in 0..16 {
+                        let (values, _) = reader.get(&bytes32!("key1")).unwrap();
+                        assert_eq!(values, vec![bytes32!("val1")]);
+                        assert!(reader.get(&bytes32!("key2")).is_none());
+                    }
+                });
+            }
+            journal.commit().unwrap();
+            journal.remove(&bytes32!("key2"));
+            journal.commit().unwrap();
+        });
+        // pinned roots prevent compaction
+        assert_eq!(journal.compact(), 0);
+        assert_eq!(
+            reader.get(&bytes32!("key1")).unwrap().0,
+            vec![bytes32!("val1")]
+        );
+        let reader2 = journal.reader();
+        assert_eq!(
+            reader2.get(&bytes32!("key1")).unwrap().0,
+            vec![bytes32!("val2")]
+        );
+        drop(reader);
+        drop(reader2);
+        assert!(journal.compact() > 0);
+    }
 }
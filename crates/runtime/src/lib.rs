@@ -1,22 +1,81 @@
 #![allow(dead_code, unreachable_patterns, unused_macros)]
 #![warn(unused_crate_dependencies)]
 
+pub mod access_list;
+pub mod arena;
+pub mod artifact_store;
+pub mod assertion;
+pub mod breakpoint;
+#[cfg(feature = "calibration")]
+pub mod calibration;
+pub mod call_stack;
+pub mod capability;
+pub mod chain_spec;
+pub mod coverage;
+pub mod disassembler;
+pub mod envelope;
+pub mod events;
+pub mod fuel_policy;
+#[cfg(feature = "golden")]
+pub mod golden;
+pub mod import;
+pub mod inspector;
 pub mod instruction;
+pub mod invariant;
 mod macros;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod module_cache;
 mod runtime;
 
 pub use runtime::*;
 
+mod deploy;
+
+pub use deploy::*;
+
+mod estimate;
+
+pub use estimate::*;
+
 mod storage;
 
 pub use storage::*;
 
+mod suspend;
+
+pub use suspend::*;
+
+mod memory_init;
+
+pub use memory_init::*;
+
 mod journal;
 
 pub use journal::*;
 
+#[cfg(feature = "wasmtime")]
+pub mod differential;
 pub mod mptrie;
+pub mod output_stream;
+pub mod policy;
+pub mod precompile;
+pub mod profiler;
+pub mod quota;
+pub mod receipt;
+pub mod replay;
+pub mod result_codec;
+pub mod signature_cache;
+pub mod source_map;
+pub mod state_diff;
+pub mod stateless;
 #[cfg(test)]
 mod tests;
+pub mod trace;
+pub mod transaction;
+pub mod typed_call;
 pub mod types;
+pub mod validation;
+pub mod view_cache;
+pub mod wal;
 pub mod zktrie;
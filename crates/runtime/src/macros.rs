@@ -110,8 +110,39 @@ macro_rules! impl_runtime_handler {
                     use rwasm::AsContextMut;
                     let func = rwasm::Func::wrap(
                         store.as_context_mut(),
-                        |caller: Caller<'_, RuntimeContext<DB>>, $($t)*| -> Result<$out, rwasm::core::Trap> {
-                            return $crate::forward_call_args! { Self::fn_handler, caller, [$($t)*] };
+                        |mut caller: Caller<'_, RuntimeContext<DB>>, $($t)*| -> Result<$out, rwasm::core::Trap> {
+                            #[cfg(feature = "tracing")]
+                            let _span = tracing::trace_span!(
+                                "host_call",
+                                module = stringify!($module),
+                                name = stringify!($name),
+                            ).entered();
+                            if let Some(trace_writer) = caller.data().trace_writer.as_ref() {
+                                trace_writer.push(&$crate::trace::format::TraceRow::syscall(
+                                    caller.fuel_consumed().unwrap_or_default(),
+                                    Self::FUNC_INDEX as u32,
+                                ));
+                            }
+                            if !caller.data().effective_capabilities.allows(Self::FUNC_INDEX) {
+                                return Err(fluentbase_types::ExitCode::CapabilityDenied.into_trap());
+                            }
+                            // operator defined price of the host call is charged before the call
+                            let syscall_cost = caller.data().fuel_policy.syscall_cost(Self::FUNC_INDEX);
+                            if !syscall_cost.is_zero() {
+                                match caller.consume_fuel(syscall_cost.get()) {
+                                    Ok(_) | Err(rwasm::errors::FuelError::FuelMeteringDisabled) => {}
+                                    Err(rwasm::errors::FuelError::OutOfFuel) => {
+                                        return Err(fluentbase_types::ExitCode::OutOfGas.into_trap());
+                                    }
+                                }
+                            }
+                            // execution is paused after the host call, so the host can inspect its effects
+                            let is_breakpoint = caller.data().breakpoints.contains(&Self::FUNC_INDEX);
+                            let result = $crate::forward_call_args! { Self::fn_handler, caller, [$($t)*] };
+                            if is_breakpoint {
+                                return $crate::breakpoint::SysBreakpointResumable::pause(Self::FUNC_INDEX, result);
+                            }
+                            return result;
                         });
                     let wrapped_index = store.inner.wrap_stored(rwasm::engine::bytecode::FuncIdx::from(Self::FUNC_INDEX as u32));
                     linker.engine().register_trampoline(wrapped_index, func);
@@ -0,0 +1,122 @@
+use crate::{types::RuntimeError, ExecutionResult, Runtime, RuntimeContext};
+use fluentbase_types::{ExitCode, IJournaledTrie, F254};
+use hashbrown::HashMap;
+use rwasm::{core::Trap, Caller};
+use std::{
+    cell::RefCell,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// Byte used to fill never-written memory in the assertion mode
+pub const MEMORY_POISON: u8 = 0xa5;
+
+const MEMORY_PAGE_SIZE: u32 = 0x10000;
+
+/// Mode of the linear memory initialization checks.
+///
+/// rWASM applies data segments and initializes globals inside the entrypoint before the state
+/// router calls `_sys_state`, so the memory image at this call is the initial image of the
+/// module and the runtime can verify it without engine support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MemoryInitMode {
+    /// Trust the engine, no checks
+    #[default]
+    Unchecked,
+    /// Verify that fresh instance memory is zeroed and that the initial image of the module is
+    /// identical across instantiations, a mismatch traps with `FatalExternalError`
+    Deterministic,
+    /// The same as `Deterministic`, plus [`Runtime::call_with_memory_assertions`] detects reads
+    /// of never-written memory
+    AssertInitialized,
+}
+
+thread_local! {
+    // initial memory image hashes of the instantiated modules
+    static MEMORY_IMAGES: RefCell<HashMap<F254, u64>> = RefCell::new(HashMap::new());
+}
+
+fn read_whole_memory<DB: IJournaledTrie>(caller: &mut Caller<'_, RuntimeContext<DB>>) -> Vec<u8> {
+    let mut memory = Vec::new();
+    while let Ok(page) = caller.read_memory(memory.len() as u32, MEMORY_PAGE_SIZE) {
+        memory.extend_from_slice(page);
+    }
+    memory
+}
+
+/// Checks initial memory image of the instance, it must be called once per instance right after
+/// the entrypoint finished memory initialization
+pub(crate) fn check_initial_memory<DB: IJournaledTrie>(
+    caller: &mut Caller<'_, RuntimeContext<DB>>,
+) -> Result<(), Trap> {
+    if caller.data().memory_init_mode == MemoryInitMode::Unchecked
+        || caller.data().is_memory_initialized
+    {
+        return Ok(());
+    }
+    caller.data_mut().is_memory_initialized = true;
+    let mut memory = read_whole_memory(caller);
+    let mut hasher = DefaultHasher::new();
+    memory.hash(&mut hasher);
+    let image_hash = hasher.finish();
+    let rwasm_hash = caller.data().bytecode.resolve_hash();
+    let is_same_image = MEMORY_IMAGES
+        .with_borrow_mut(|images| *images.entry(rwasm_hash).or_insert(image_hash) == image_hash);
+    if !is_same_image {
+        return Err(ExitCode::FatalExternalError.into_trap());
+    }
+    // bytes that are still zero were never written by the initialization (explicit zeros of
+    // data segments are poisoned too, so the assertion mode can report false positives for them)
+    if let Some(poison) = caller.data().memory_poison {
+        memory
+            .iter_mut()
+            .filter(|v| **v == 0)
+            .for_each(|v| *v = poison);
+        caller.write_memory(0, &memory)?;
+    }
+    Ok(())
+}
+
+impl<DB: IJournaledTrie> Runtime<DB> {
+    /// Verifies that memory of the fresh instance is zeroed
+    pub(crate) fn check_fresh_memory(&mut self) -> Result<(), RuntimeError> {
+        if self.store.data().memory_init_mode == MemoryInitMode::Unchecked {
+            return Ok(());
+        }
+        let mut offset = 0;
+        while let Ok(page) = self.read_memory(offset, MEMORY_PAGE_SIZE) {
+            if page.iter().any(|v| *v != 0) {
+                return Err(RuntimeError::NonDeterministicMemory);
+            }
+            offset += MEMORY_PAGE_SIZE;
+        }
+        Ok(())
+    }
+}
+
+impl<DB: IJournaledTrie + Clone> Runtime<DB> {
+    /// Executes the context twice, the second time with never-written memory filled with
+    /// [`MEMORY_POISON`], if results are different then execution read uninitialized memory.
+    ///
+    /// The first attempt is rolled back, the second one keeps state changes.
+    pub fn call_with_memory_assertions(
+        mut runtime_context: RuntimeContext<DB>,
+    ) -> Result<ExecutionResult, RuntimeError> {
+        runtime_context.resolve_bytecode();
+        runtime_context.memory_init_mode = MemoryInitMode::AssertInitialized;
+        let checkpoint = runtime_context.jzkt.as_ref().map(|jzkt| jzkt.checkpoint());
+        let clean_result = Self::new(runtime_context.clone()).call()?;
+        if let (Some(jzkt), Some(checkpoint)) = (runtime_context.jzkt.as_ref(), checkpoint) {
+            jzkt.rollback(checkpoint);
+        }
+        runtime_context.memory_poison = Some(MEMORY_POISON);
+        let poisoned_result = Self::new(runtime_context).call()?;
+        if clean_result.exit_code != poisoned_result.exit_code
+            || clean_result.output != poisoned_result.output
+            || clean_result.fuel_consumed != poisoned_result.fuel_consumed
+        {
+            return Err(RuntimeError::UninitializedMemoryRead);
+        }
+        Ok(poisoned_result)
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{OnceLock, RwLock},
+    time::Duration,
+};
+
+/// Buckets of the fuel consumption histogram
+pub const FUEL_BUCKETS: [f64; 8] = [
+    1_000.0,
+    10_000.0,
+    100_000.0,
+    1_000_000.0,
+    3_000_000.0,
+    10_000_000.0,
+    30_000_000.0,
+    100_000_000.0,
+];
+
+/// Buckets (in seconds) of the latency histograms
+pub const LATENCY_BUCKETS: [f64; 10] =
+    [0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    buckets: Vec<f64>,
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    pub fn new(buckets: &[f64]) -> Self {
+        Self {
+            buckets: buckets.to_vec(),
+            counts: vec![0; buckets.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn observe(&mut self, value: f64) {
+        for (bucket, count) in self.buckets.iter().zip(self.counts.iter_mut()) {
+            if value <= *bucket {
+                *count += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    fn write_prometheus(&self, result: &mut String, name: &str, help: &str) {
+        writeln!(result, "# HELP {} {}", name, help).unwrap();
+        writeln!(result, "# TYPE {} histogram", name).unwrap();
+        for (bucket, count) in self.buckets.iter().zip(self.counts.iter()) {
+            writeln!(result, "{}_bucket{{le=\"{}\"}} {}", name, bucket, count).unwrap();
+        }
+        writeln!(result, "{}_bucket{{le=\"+Inf\"}} {}", name, self.count).unwrap();
+        writeln!(result, "{}_sum {}", name, self.sum).unwrap();
+        writeln!(result, "{}_count {}", name, self.count).unwrap();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeMetrics {
+    pub executions_total: u64,
+    pub exit_codes: BTreeMap<i32, u64>,
+    pub fuel_consumed: Histogram,
+    pub translation_seconds: Histogram,
+    pub trie_commit_seconds: Histogram,
+    /// Executions that reused the engine (and its cached stacks) of the translated module
+    pub engine_reuses_total: u64,
+    /// Executions that created a new engine
+    pub engine_creations_total: u64,
+    /// Translated modules evicted from the full module caches
+    pub module_evictions_total: u64,
+}
+
+impl Default for RuntimeMetrics {
+    fn default() -> Self {
+        Self {
+            executions_total: 0,
+            exit_codes: BTreeMap::new(),
+            fuel_consumed: Histogram::new(&FUEL_BUCKETS),
+            translation_seconds: Histogram::new(&LATENCY_BUCKETS),
+            trie_commit_seconds: Histogram::new(&LATENCY_BUCKETS),
+            engine_reuses_total: 0,
+            engine_creations_total: 0,
+            module_evictions_total: 0,
+        }
+    }
+}
+
+impl RuntimeMetrics {
+    /// Renders metrics in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let mut result = String::new();
+        writeln!(
+            result,
+            "# HELP fluentbase_executions_total Number of finished executions"
+        )
+        .unwrap();
+        writeln!(result, "# TYPE fluentbase_executions_total counter").unwrap();
+        writeln!(
+            result,
+            "fluentbase_executions_total {}",
+            self.executions_total
+        )
+        .unwrap();
+        writeln!(
+            result,
+            "# HELP fluentbase_exit_codes_total Number of executions by exit code"
+        )
+        .unwrap();
+        writeln!(result, "# TYPE fluentbase_exit_codes_total counter").unwrap();
+        for (exit_code, count) in self.exit_codes.iter() {
+            writeln!(
+                result,
+                "fluentbase_exit_codes_total{{exit_code=\"{}\"}} {}",
+                exit_code, count
+            )
+            .unwrap();
+        }
+        writeln!(
+            result,
+            "# HELP fluentbase_engine_resolutions_total Number of engines resolved for executions"
+        )
+        .unwrap();
+        writeln!(result, "# TYPE fluentbase_engine_resolutions_total counter").unwrap();
+        writeln!(
+            result,
+            "fluentbase_engine_resolutions_total{{reused=\"true\"}} {}",
+            self.engine_reuses_total
+        )
+        .unwrap();
+        writeln!(
+            result,
+            "fluentbase_engine_resolutions_total{{reused=\"false\"}} {}",
+            self.engine_creations_total
+        )
+        .unwrap();
+        writeln!(
+            result,
+            "# HELP fluentbase_module_evictions_total Number of modules evicted from module caches"
+        )
+        .unwrap();
+        writeln!(result, "# TYPE fluentbase_module_evictions_total counter").unwrap();
+        writeln!(
+            result,
+            "fluentbase_module_evictions_total {}",
+            self.module_evictions_total
+        )
+        .unwrap();
+        self.fuel_consumed.write_prometheus(
+            &mut result,
+            "fluentbase_fuel_consumed",
+            "Fuel consumed by executions",
+        );
+        self.translation_seconds.write_prometheus(
+            &mut result,
+            "fluentbase_translation_seconds",
+            "Time spent on building modules from rWASM bytecode",
+        );
+        self.trie_commit_seconds.write_prometheus(
+            &mut result,
+            "fluentbase_trie_commit_seconds",
+            "Time spent on committing journaled trie",
+        );
+        result
+    }
+}
+
+static RUNTIME_METRICS: OnceLock<RwLock<RuntimeMetrics>> = OnceLock::new();
+
+fn runtime_metrics() -> &'static RwLock<RuntimeMetrics> {
+    RUNTIME_METRICS.get_or_init(Default::default)
+}
+
+pub(crate) fn record_execution(exit_code: i32, fuel_consumed: u64) {
+    let mut metrics = runtime_metrics().write().unwrap();
+    metrics.executions_total += 1;
+    *metrics.exit_codes.entry(exit_code).or_default() += 1;
+    metrics.fuel_consumed.observe(fuel_consumed as f64);
+}
+
+pub(crate) fn record_engine_resolution(is_reused: bool) {
+    let mut metrics = runtime_metrics().write().unwrap();
+    if is_reused {
+        metrics.engine_reuses_total += 1;
+    } else {
+        metrics.engine_creations_total += 1;
+    }
+}
+
+pub(crate) fn record_module_eviction() {
+    let mut metrics = runtime_metrics().write().unwrap();
+    metrics.module_evictions_total += 1;
+}
+
+pub(crate) fn record_translation(elapsed: Duration) {
+    let mut metrics = runtime_metrics().write().unwrap();
+    metrics.translation_seconds.observe(elapsed.as_secs_f64());
+}
+
+pub(crate) fn record_trie_commit(elapsed: Duration) {
+    let mut metrics = runtime_metrics().write().unwrap();
+    metrics.trie_commit_seconds.observe(elapsed.as_secs_f64());
+}
+
+/// Returns snapshot of the process-wide runtime metrics
+pub fn snapshot() -> RuntimeMetrics {
+    runtime_metrics().read().unwrap().clone()
+}
+
+/// Returns process-wide runtime metrics in the Prometheus text exposition format, the result
+/// can be served as is by the scrape endpoint
+pub fn gather() -> String {
+    snapshot().to_prometheus()
+}
+
+/// Resets all process-wide metrics
+pub fn reset() {
+    *runtime_metrics().write().unwrap() = Default::default();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::{Histogram, RuntimeMetrics};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_histogram_buckets() {
+        let mut histogram = Histogram::new(&[1.0, 10.0]);
+        histogram.observe(0.5);
+        histogram.observe(5.0);
+        histogram.observe(50.0);
+        assert_eq!(histogram.count(), 3);
+        assert_eq!(histogram.sum(), 55.5);
+        let mut result = String::new();
+        histogram.write_prometheus(&mut result, "test", "test histogram");
+        assert!(result.contains("test_bucket{le=\"1\"} 1\n"));
+        assert!(result.contains("test_bucket{le=\"10\"} 2\n"));
+        assert!(result.contains("test_bucket{le=\"+Inf\"} 3\n"));
+    }
+
+    #[test]
+    fn test_prometheus_exposition() {
+        let metrics = RuntimeMetrics {
+            executions_total: 2,
+            exit_codes: BTreeMap::from([(0, 1), (-1004, 1)]),
+            engine_reuses_total: 3,
+            ..Default::default()
+        };
+        let result = metrics.to_prometheus();
+        assert!(result.contains("fluentbase_executions_total 2\n"));
+        assert!(result.contains("fluentbase_exit_codes_total{exit_code=\"-1004\"} 1\n"));
+        assert!(result.contains("fluentbase_engine_resolutions_total{reused=\"true\"} 3\n"));
+        assert!(result.contains("# TYPE fluentbase_fuel_consumed histogram\n"));
+    }
+}
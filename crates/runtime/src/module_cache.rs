@@ -0,0 +1,177 @@
+use crate::policy::{BytecodePolicy, PolicyViolation};
+use fluentbase_types::{ContractMetadata, F254};
+use hashbrown::{hash_map::Entry, HashMap};
+use rwasm::Module;
+use std::cell::{Cell, RefCell};
+
+/// Result of the bytecode validation against the policy
+pub type PolicyVerdict = Result<(), Vec<PolicyViolation>>;
+
+/// Max number of translated modules kept per caching runtime by default
+pub const DEFAULT_MODULE_CACHE_CAPACITY: usize = 1024;
+
+struct CachedModule {
+    module: Module,
+    /// Metadata section of the bytecode, it's verified on every call of the cached module
+    metadata: Option<ContractMetadata>,
+    /// Verdicts of the policies the module was validated against, contexts can have different
+    /// policies, so the module is checked once per policy
+    policy_verdicts: RefCell<Vec<(BytecodePolicy, PolicyVerdict)>>,
+    last_used: Cell<u64>,
+}
+
+/// Translated modules keyed by the rWASM code hash, the least recently used module is evicted
+/// when the cache is full. The cache always keeps the last inserted module, so the capacity is
+/// at least one.
+pub struct ModuleCache {
+    modules: HashMap<F254, CachedModule>,
+    capacity: usize,
+    // counter of the cache accesses, the least recently used module has the smallest stamp
+    clock: Cell<u64>,
+}
+
+impl Default for ModuleCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MODULE_CACHE_CAPACITY)
+    }
+}
+
+impl ModuleCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            modules: HashMap::new(),
+            capacity: capacity.max(1),
+            clock: Cell::new(0),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Changes the capacity, the least recently used modules above the capacity are evicted
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.modules.len() > self.capacity {
+            self.evict_least_recently_used();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    pub fn contains(&self, rwasm_hash: &F254) -> bool {
+        self.modules.contains_key(rwasm_hash)
+    }
+
+    /// Returns the module and marks it as the most recently used
+    pub fn get(&self, rwasm_hash: &F254) -> Option<&Module> {
+        let cached_module = self.modules.get(rwasm_hash)?;
+        cached_module.last_used.set(self.tick());
+        Some(&cached_module.module)
+    }
+
+    /// Returns metadata section of the cached bytecode (if any)
+    pub fn metadata(&self, rwasm_hash: &F254) -> Option<&ContractMetadata> {
+        self.modules.get(rwasm_hash)?.metadata.as_ref()
+    }
+
+    /// Returns the verdict of the policy for the cached module (if it was validated already)
+    pub fn policy_verdict(
+        &self,
+        rwasm_hash: &F254,
+        bytecode_policy: &BytecodePolicy,
+    ) -> Option<PolicyVerdict> {
+        self.modules
+            .get(rwasm_hash)?
+            .policy_verdicts
+            .borrow()
+            .iter()
+            .find(|(policy, _)| policy == bytecode_policy)
+            .map(|(_, verdict)| verdict.clone())
+    }
+
+    /// Remembers the verdict of the policy for the cached module, it's ignored if the module
+    /// isn't cached
+    pub fn record_policy_verdict(
+        &self,
+        rwasm_hash: &F254,
+        bytecode_policy: &BytecodePolicy,
+        verdict: PolicyVerdict,
+    ) {
+        let Some(cached_module) = self.modules.get(rwasm_hash) else {
+            return;
+        };
+        let mut policy_verdicts = cached_module.policy_verdicts.borrow_mut();
+        if !policy_verdicts
+            .iter()
+            .any(|(policy, _)| policy == bytecode_policy)
+        {
+            policy_verdicts.push((bytecode_policy.clone(), verdict));
+        }
+    }
+
+    /// Inserts the module, evicts the least recently used one if the cache is full
+    pub fn insert(&mut self, rwasm_hash: F254, module: Module) -> &Module {
+        self.insert_with_metadata(rwasm_hash, module, None)
+    }
+
+    /// Inserts the module along with the metadata section of its bytecode
+    pub fn insert_with_metadata(
+        &mut self,
+        rwasm_hash: F254,
+        module: Module,
+        metadata: Option<ContractMetadata>,
+    ) -> &Module {
+        if !self.modules.contains_key(&rwasm_hash) && self.modules.len() >= self.capacity {
+            self.evict_least_recently_used();
+        }
+        let cached_module = CachedModule {
+            module,
+            metadata,
+            policy_verdicts: RefCell::new(vec![]),
+            last_used: Cell::new(self.tick()),
+        };
+        let cached_module = match self.modules.entry(rwasm_hash) {
+            Entry::Occupied(mut entry) => {
+                entry.insert(cached_module);
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => entry.insert(cached_module),
+        };
+        &cached_module.module
+    }
+
+    /// Removes the module, returns `false` if it wasn't cached
+    pub fn remove(&mut self, rwasm_hash: &F254) -> bool {
+        self.modules.remove(rwasm_hash).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.modules.clear();
+    }
+
+    fn tick(&self) -> u64 {
+        let clock = self.clock.get() + 1;
+        self.clock.set(clock);
+        clock
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let least_recently_used = self
+            .modules
+            .iter()
+            .min_by_key(|(_, cached_module)| cached_module.last_used.get())
+            .map(|(rwasm_hash, _)| *rwasm_hash);
+        if let Some(rwasm_hash) = least_recently_used {
+            self.modules.remove(&rwasm_hash);
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_module_eviction();
+        }
+    }
+}
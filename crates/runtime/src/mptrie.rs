@@ -30,6 +30,28 @@ impl<DB: eth_trie::DB + TrieDb> MPTrieStateDb<DB> {
         storage.open(root32);
         storage
     }
+
+    fn get_from(trie: &EthTrie<DB>, key: &[u8]) -> Option<(Vec<[u8; 32]>, u32)> {
+        if let Ok(val) = trie.get(key) {
+            match val {
+                Some(data) => {
+                    let result = data
+                        .to_vec()
+                        .chunks(32)
+                        .map(|val| {
+                            let mut bytes = [0u8; 32];
+                            bytes.copy_from_slice(val);
+                            bytes
+                        })
+                        .collect::<Vec<_>>();
+                    Some((result, 0))
+                }
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
 }
 
 impl<DB: eth_trie::DB + TrieDb> TrieStorage for MPTrieStateDb<DB> {
@@ -52,25 +74,15 @@ impl<DB: eth_trie::DB + TrieDb> TrieStorage for MPTrieStateDb<DB> {
 
     fn get(&self, key: &[u8]) -> Option<(Vec<[u8; 32]>, u32)> {
         let trie = self.trie.as_ref().unwrap().borrow_mut();
-        if let Ok(val) = trie.get(key) {
-            match val {
-                Some(data) => {
-                    let result = data
-                        .to_vec()
-                        .chunks(32)
-                        .map(|val| {
-                            let mut bytes = [0u8; 32];
-                            bytes.copy_from_slice(val);
-                            bytes
-                        })
-                        .collect::<Vec<_>>();
-                    Some((result, 0))
-                }
-                _ => None,
-            }
-        } else {
-            None
+        Self::get_from(&trie, key)
+    }
+
+    fn get_at(&self, root32: &[u8; 32], key: &[u8]) -> Option<(Vec<[u8; 32]>, u32)> {
+        if root32 == &EMPTY_ROOT_HASH {
+            return None;
         }
+        let trie = EthTrie::new(self.storage.clone()).at_root(H256::from_slice(root32));
+        Self::get_from(&trie, key)
     }
 
     fn update(
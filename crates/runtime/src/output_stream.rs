@@ -0,0 +1,81 @@
+use fluentbase_types::ExitCode;
+use std::sync::{
+    mpsc::{sync_channel, Receiver, SyncSender},
+    Arc,
+    Mutex,
+};
+
+/// Host-side consumer of the output chunks written by the `_write_output_chunk` syscall
+pub trait OutputSink: Send {
+    /// Accepts a prefix of the chunk and returns its size, the sink applies back-pressure by
+    /// blocking or by accepting fewer bytes (the contract retries the rest). Errors halt the
+    /// execution with the returned exit code
+    fn write_chunk(&mut self, chunk: &[u8]) -> Result<usize, ExitCode>;
+}
+
+/// Streams chunks over a bounded channel, the execution is blocked while the channel is full
+pub struct ChannelOutputSink {
+    sender: SyncSender<Vec<u8>>,
+}
+
+impl ChannelOutputSink {
+    /// Creates the sink with the channel that buffers up to `bound` chunks
+    pub fn new(bound: usize) -> (Self, Receiver<Vec<u8>>) {
+        let (sender, receiver) = sync_channel(bound);
+        (Self { sender }, receiver)
+    }
+}
+
+impl OutputSink for ChannelOutputSink {
+    fn write_chunk(&mut self, chunk: &[u8]) -> Result<usize, ExitCode> {
+        self.sender
+            .send(chunk.to_vec())
+            .map_err(|_| ExitCode::FatalExternalError)?;
+        Ok(chunk.len())
+    }
+}
+
+/// Output stream of the top-level call, it counts the streamed bytes against the size limit
+#[derive(Clone)]
+pub struct OutputStream {
+    sink: Arc<Mutex<dyn OutputSink>>,
+    max_size: u64,
+    written: u64,
+}
+
+impl OutputStream {
+    pub fn new<S: OutputSink + 'static>(sink: S) -> Self {
+        Self {
+            sink: Arc::new(Mutex::new(sink)),
+            max_size: u64::MAX,
+            written: 0,
+        }
+    }
+
+    /// Sets the max total size of the streamed output, the execution halts with
+    /// `OutputOverflow` if a chunk crosses it
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    /// Number of bytes accepted by the sink
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    pub(crate) fn write_chunk(&mut self, chunk: &[u8]) -> Result<usize, ExitCode> {
+        if self.written + chunk.len() as u64 > self.max_size {
+            return Err(ExitCode::OutputOverflow);
+        }
+        let accepted = self.sink.lock().unwrap().write_chunk(chunk)?;
+        // sinks can't accept more than they are given
+        let accepted = accepted.min(chunk.len());
+        self.written += accepted as u64;
+        Ok(accepted)
+    }
+}
@@ -0,0 +1,513 @@
+use crate::MAX_CODE_SIZE;
+use fluentbase_types::create_sovereign_import_linker;
+use hashbrown::HashSet;
+use rwasm::{engine::bytecode::Instruction, rwasm::RwasmModule};
+use std::fmt::{Display, Formatter};
+use wasmparser::{Operator, Parser, Payload, Type, TypeRef, ValType};
+
+/// Matches instructions that operate on float values, WASM operators and rWASM instructions
+/// share the names of the numeric instructions
+macro_rules! is_float_instr {
+    ($instr:expr, $ty:ident) => {
+        matches!(
+            $instr,
+            $ty::F32Load { .. }
+                | $ty::F64Load { .. }
+                | $ty::F32Store { .. }
+                | $ty::F64Store { .. }
+                | $ty::F32Const { .. }
+                | $ty::F64Const { .. }
+                | $ty::F32Eq { .. }
+                | $ty::F32Ne { .. }
+                | $ty::F32Lt { .. }
+                | $ty::F32Gt { .. }
+                | $ty::F32Le { .. }
+                | $ty::F32Ge { .. }
+                | $ty::F64Eq { .. }
+                | $ty::F64Ne { .. }
+                | $ty::F64Lt { .. }
+                | $ty::F64Gt { .. }
+                | $ty::F64Le { .. }
+                | $ty::F64Ge { .. }
+                | $ty::F32Abs { .. }
+                | $ty::F32Neg { .. }
+                | $ty::F32Ceil { .. }
+                | $ty::F32Floor { .. }
+                | $ty::F32Trunc { .. }
+                | $ty::F32Nearest { .. }
+                | $ty::F32Sqrt { .. }
+                | $ty::F32Add { .. }
+                | $ty::F32Sub { .. }
+                | $ty::F32Mul { .. }
+                | $ty::F32Div { .. }
+                | $ty::F32Min { .. }
+                | $ty::F32Max { .. }
+                | $ty::F32Copysign { .. }
+                | $ty::F64Abs { .. }
+                | $ty::F64Neg { .. }
+                | $ty::F64Ceil { .. }
+                | $ty::F64Floor { .. }
+                | $ty::F64Trunc { .. }
+                | $ty::F64Nearest { .. }
+                | $ty::F64Sqrt { .. }
+                | $ty::F64Add { .. }
+                | $ty::F64Sub { .. }
+                | $ty::F64Mul { .. }
+                | $ty::F64Div { .. }
+                | $ty::F64Min { .. }
+                | $ty::F64Max { .. }
+                | $ty::F64Copysign { .. }
+                | $ty::I32TruncF32S { .. }
+                | $ty::I32TruncF32U { .. }
+                | $ty::I32TruncF64S { .. }
+                | $ty::I32TruncF64U { .. }
+                | $ty::I64TruncF32S { .. }
+                | $ty::I64TruncF32U { .. }
+                | $ty::I64TruncF64S { .. }
+                | $ty::I64TruncF64U { .. }
+                | $ty::I32TruncSatF32S { .. }
+                | $ty::I32TruncSatF32U { .. }
+                | $ty::I32TruncSatF64S { .. }
+                | $ty::I32TruncSatF64U { .. }
+                | $ty::I64TruncSatF32S { .. }
+                | $ty::I64TruncSatF32U { .. }
+                | $ty::I64TruncSatF64S { .. }
+                | $ty::I64TruncSatF64U { .. }
+                | $ty::F32ConvertI32S { .. }
+                | $ty::F32ConvertI32U { .. }
+                | $ty::F32ConvertI64S { .. }
+                | $ty::F32ConvertI64U { .. }
+                | $ty::F32DemoteF64 { .. }
+                | $ty::F64ConvertI32S { .. }
+                | $ty::F64ConvertI32U { .. }
+                | $ty::F64ConvertI64S { .. }
+                | $ty::F64ConvertI64U { .. }
+                | $ty::F64PromoteF32 { .. }
+                | $ty::I32ReinterpretF32 { .. }
+                | $ty::I64ReinterpretF64 { .. }
+                | $ty::F32ReinterpretI32 { .. }
+                | $ty::F64ReinterpretI64 { .. }
+        )
+    };
+}
+
+/// Max number of functions (including imported) allowed by the default policy
+pub const DEFAULT_MAX_FUNCTION_COUNT: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolicyViolation {
+    MalformedBytecode(String),
+    CodeSizeExceeded {
+        size: usize,
+        limit: usize,
+    },
+    TooManyFunctions {
+        count: usize,
+        limit: usize,
+    },
+    DisallowedImport {
+        module: String,
+        name: String,
+    },
+    UnknownImport {
+        func_idx: u32,
+    },
+    /// Float instruction at the byte offset (for WASM) or instruction position (for rWASM)
+    FloatInstruction {
+        func_idx: u32,
+        offset: usize,
+    },
+    FloatType {
+        type_idx: u32,
+    },
+    StartSection {
+        func_idx: u32,
+    },
+}
+
+impl Display for PolicyViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyViolation::MalformedBytecode(err) => write!(f, "malformed bytecode: {}", err),
+            PolicyViolation::CodeSizeExceeded { size, limit } => {
+                write!(f, "code size {} exceeds limit {}", size, limit)
+            }
+            PolicyViolation::TooManyFunctions { count, limit } => {
+                write!(f, "function count {} exceeds limit {}", count, limit)
+            }
+            PolicyViolation::DisallowedImport { module, name } => {
+                write!(f, "import {}::{} is not allowed", module, name)
+            }
+            PolicyViolation::UnknownImport { func_idx } => {
+                write!(f, "unknown import function {}", func_idx)
+            }
+            PolicyViolation::FloatInstruction { func_idx, offset } => write!(
+                f,
+                "float instruction in function {} at offset {}",
+                func_idx, offset
+            ),
+            PolicyViolation::FloatType { type_idx } => {
+                write!(f, "float value type in type {}", type_idx)
+            }
+            PolicyViolation::StartSection { func_idx } => {
+                write!(
+                    f,
+                    "start section is not allowed (start function {})",
+                    func_idx
+                )
+            }
+        }
+    }
+}
+
+/// Set of restrictions applied to the bytecode before instantiation, the policy reports all
+/// violations found instead of failing on the first one
+#[derive(Debug, Clone, PartialEq)]
+pub struct BytecodePolicy {
+    pub max_code_size: usize,
+    pub max_function_count: usize,
+    /// List of allowed imports (module and function names), if not specified then any import
+    /// known to the sovereign linker is allowed
+    pub allowed_imports: Option<HashSet<(String, String)>>,
+    pub allow_floats: bool,
+    pub allow_start_section: bool,
+}
+
+impl Default for BytecodePolicy {
+    fn default() -> Self {
+        Self {
+            max_code_size: MAX_CODE_SIZE,
+            max_function_count: DEFAULT_MAX_FUNCTION_COUNT,
+            allowed_imports: None,
+            allow_floats: false,
+            allow_start_section: false,
+        }
+    }
+}
+
+impl BytecodePolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_code_size(mut self, max_code_size: usize) -> Self {
+        self.max_code_size = max_code_size;
+        self
+    }
+
+    pub fn with_max_function_count(mut self, max_function_count: usize) -> Self {
+        self.max_function_count = max_function_count;
+        self
+    }
+
+    pub fn with_allowed_imports<I: IntoIterator<Item = (String, String)>>(
+        mut self,
+        allowed_imports: I,
+    ) -> Self {
+        self.allowed_imports = Some(allowed_imports.into_iter().collect());
+        self
+    }
+
+    /// Removes imports from the allowed list, if the list isn't specified then it's narrowed
+    /// down from the imports of the sovereign linker
+    pub fn with_disallowed_imports<I: IntoIterator<Item = (String, String)>>(
+        mut self,
+        disallowed_imports: I,
+    ) -> Self {
+        let allowed_imports = self.allowed_imports.get_or_insert_with(|| {
+            let import_linker: Vec<(&str, &str, u32, u32)> = create_sovereign_import_linker();
+            import_linker
+                .into_iter()
+                .map(|(module, name, _, _)| (module.to_string(), name.to_string()))
+                .collect()
+        });
+        for import in disallowed_imports {
+            allowed_imports.remove(&import);
+        }
+        self
+    }
+
+    pub fn with_allow_floats(mut self, allow_floats: bool) -> Self {
+        self.allow_floats = allow_floats;
+        self
+    }
+
+    pub fn with_allow_start_section(mut self, allow_start_section: bool) -> Self {
+        self.allow_start_section = allow_start_section;
+        self
+    }
+
+    fn is_import_allowed(&self, module: &str, name: &str) -> bool {
+        match &self.allowed_imports {
+            Some(allowed_imports) => {
+                allowed_imports.contains(&(module.to_string(), name.to_string()))
+            }
+            None => {
+                let import_linker: Vec<(&str, &str, u32, u32)> = create_sovereign_import_linker();
+                import_linker
+                    .iter()
+                    .any(|(m, n, _, _)| *m == module && *n == name)
+            }
+        }
+    }
+
+    /// Validates original WASM binary (before translation into rWASM)
+    pub fn validate_wasm(&self, wasm_binary: &[u8]) -> Result<(), Vec<PolicyViolation>> {
+        let mut violations = Vec::new();
+        if wasm_binary.len() > self.max_code_size {
+            violations.push(PolicyViolation::CodeSizeExceeded {
+                size: wasm_binary.len(),
+                limit: self.max_code_size,
+            });
+        }
+        let is_float = |val_type: &ValType| matches!(val_type, ValType::F32 | ValType::F64);
+        let malformed = |err: wasmparser::BinaryReaderError| {
+            vec![PolicyViolation::MalformedBytecode(err.to_string())]
+        };
+        let mut func_count = 0usize;
+        let mut func_idx = 0u32;
+        for payload in Parser::new(0).parse_all(wasm_binary) {
+            match payload.map_err(malformed)? {
+                Payload::TypeSection(reader) => {
+                    for (type_idx, ty) in reader.into_iter().enumerate() {
+                        let Type::Func(func_type) = ty.map_err(malformed)?;
+                        let has_floats = func_type
+                            .params()
+                            .iter()
+                            .chain(func_type.results().iter())
+                            .any(is_float);
+                        if has_floats && !self.allow_floats {
+                            violations.push(PolicyViolation::FloatType {
+                                type_idx: type_idx as u32,
+                            });
+                        }
+                    }
+                }
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        let import = import.map_err(malformed)?;
+                        if let TypeRef::Func(_) = import.ty {
+                            func_count += 1;
+                            func_idx += 1;
+                        }
+                        if !self.is_import_allowed(import.module, import.name) {
+                            violations.push(PolicyViolation::DisallowedImport {
+                                module: import.module.to_string(),
+                                name: import.name.to_string(),
+                            });
+                        }
+                    }
+                }
+                Payload::FunctionSection(reader) => {
+                    func_count += reader.count() as usize;
+                }
+                Payload::StartSection { func, .. } => {
+                    if !self.allow_start_section {
+                        violations.push(PolicyViolation::StartSection { func_idx: func });
+                    }
+                }
+                Payload::CodeSectionEntry(body) if !self.allow_floats => {
+                    let mut operators = body.get_operators_reader().map_err(malformed)?;
+                    while !operators.eof() {
+                        let (operator, offset) = operators.read_with_offset().map_err(malformed)?;
+                        if is_float_instr!(operator, Operator) {
+                            violations.push(PolicyViolation::FloatInstruction { func_idx, offset });
+                        }
+                    }
+                    func_idx += 1;
+                }
+                Payload::CodeSectionEntry(_) => {
+                    func_idx += 1;
+                }
+                _ => {}
+            }
+        }
+        if func_count > self.max_function_count {
+            violations.push(PolicyViolation::TooManyFunctions {
+                count: func_count,
+                limit: self.max_function_count,
+            });
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Validates translated rWASM bytecode, rWASM has no start section (entrypoint is generated
+    /// by the translator) and imports are represented as calls of the system functions
+    pub fn validate_rwasm(&self, rwasm_bytecode: &[u8]) -> Result<(), Vec<PolicyViolation>> {
+        let mut violations = Vec::new();
+        if rwasm_bytecode.len() > self.max_code_size {
+            violations.push(PolicyViolation::CodeSizeExceeded {
+                size: rwasm_bytecode.len(),
+                limit: self.max_code_size,
+            });
+        }
+        let rwasm_module = RwasmModule::new(rwasm_bytecode)
+            .map_err(|err| vec![PolicyViolation::MalformedBytecode(format!("{:?}", err))])?;
+        if rwasm_module.func_section.len() > self.max_function_count {
+            violations.push(PolicyViolation::TooManyFunctions {
+                count: rwasm_module.func_section.len(),
+                limit: self.max_function_count,
+            });
+        }
+        let import_linker = create_sovereign_import_linker();
+        let mut func_boundaries = rwasm_module
+            .func_section
+            .iter()
+            .scan(0usize, |end, len| {
+                *end += *len as usize;
+                Some(*end)
+            })
+            .peekable();
+        let mut func_idx = 0u32;
+        let mut reported_imports = HashSet::new();
+        for (pc, instr) in rwasm_module.code_section.instr.iter().enumerate() {
+            while func_boundaries.next_if(|end| *end <= pc).is_some() {
+                func_idx += 1;
+            }
+            if let Instruction::Call(idx) = instr {
+                let idx = idx.to_u32();
+                if !reported_imports.insert(idx) {
+                    continue;
+                }
+                match import_linker.iter().find(|(_, _, i, _)| *i == idx) {
+                    Some((module, name, _, _)) => {
+                        if !self.is_import_allowed(module, name) {
+                            violations.push(PolicyViolation::DisallowedImport {
+                                module: module.to_string(),
+                                name: name.to_string(),
+                            });
+                        }
+                    }
+                    None => violations.push(PolicyViolation::UnknownImport { func_idx: idx }),
+                }
+            } else if !self.allow_floats && is_float_instr!(instr, Instruction) {
+                violations.push(PolicyViolation::FloatInstruction {
+                    func_idx,
+                    offset: pc,
+                });
+            }
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        policy::{BytecodePolicy, PolicyViolation},
+        tests::wat2rwasm,
+        types::RuntimeError,
+        DefaultEmptyRuntimeDatabase,
+        Runtime,
+        RuntimeContext,
+    };
+
+    const WAT: &str = r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 12
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "Hello, World")
+  (export "main" (func $main)))
+    "#;
+
+    #[test]
+    fn test_wasm_policy_violations() {
+        let wasm_binary = wat::parse_str(
+            r#"
+(module
+  (import "env" "foo" (func $foo))
+  (func $main
+    f32.const 1.0
+    drop
+    )
+  (start $main)
+  (export "main" (func $main)))
+    "#,
+        )
+        .unwrap();
+        let violations = BytecodePolicy::new()
+            .with_max_function_count(1)
+            .validate_wasm(&wasm_binary)
+            .unwrap_err();
+        assert!(violations.contains(&PolicyViolation::DisallowedImport {
+            module: "env".to_string(),
+            name: "foo".to_string(),
+        }));
+        assert!(violations.contains(&PolicyViolation::StartSection { func_idx: 1 }));
+        assert!(violations.contains(&PolicyViolation::TooManyFunctions { count: 2, limit: 1 }));
+        assert!(violations
+            .iter()
+            .any(|v| matches!(v, PolicyViolation::FloatInstruction { func_idx: 1, .. })));
+        // the same module is fine for the permissive policy
+        BytecodePolicy::new()
+            .with_allowed_imports([("env".to_string(), "foo".to_string())])
+            .with_allow_floats(true)
+            .with_allow_start_section(true)
+            .validate_wasm(&wasm_binary)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_rwasm_policy_violations() {
+        let rwasm_binary = wat2rwasm(WAT);
+        BytecodePolicy::new().validate_rwasm(&rwasm_binary).unwrap();
+        let violations = BytecodePolicy::new()
+            .with_allowed_imports([])
+            .with_max_code_size(1)
+            .validate_rwasm(&rwasm_binary)
+            .unwrap_err();
+        assert!(violations.contains(&PolicyViolation::DisallowedImport {
+            module: "fluentbase_v1preview".to_string(),
+            name: "_write".to_string(),
+        }));
+        assert!(violations
+            .iter()
+            .any(|v| matches!(v, PolicyViolation::CodeSizeExceeded { .. })));
+    }
+
+    #[test]
+    fn test_runtime_enforces_policy() {
+        let rwasm_binary = wat2rwasm(WAT);
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+            .with_fuel_limit(1_000_000)
+            .with_bytecode_policy(BytecodePolicy::new().with_max_function_count(0));
+        let err = Runtime::run_with_context(ctx).unwrap_err();
+        assert!(matches!(err, RuntimeError::PolicyViolation(_)));
+    }
+
+    #[test]
+    fn test_cached_module_is_checked_by_every_policy() {
+        let rwasm_binary = wat2rwasm(WAT);
+        let run = |bytecode_policy: Option<BytecodePolicy>| {
+            let mut ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+                .with_fuel_limit(1_000_000);
+            if let Some(bytecode_policy) = bytecode_policy {
+                ctx = ctx.with_bytecode_policy(bytecode_policy);
+            }
+            Runtime::run_with_context(ctx)
+        };
+        // the module is translated and cached without a policy
+        run(None).unwrap();
+        // cached module is still validated against the policy of the next context
+        for _ in 0..2 {
+            let err = run(Some(BytecodePolicy::new().with_allowed_imports([]))).unwrap_err();
+            assert!(matches!(err, RuntimeError::PolicyViolation(_)));
+        }
+        run(Some(BytecodePolicy::new())).unwrap();
+    }
+}
@@ -0,0 +1,254 @@
+use crate::ExecutionResult;
+use fluentbase_types::{
+    contracts::{
+        PRECOMPILE_IDENTITY,
+        PRECOMPILE_MODEXP,
+        PRECOMPILE_SECP256K1_ECRECOVER,
+        PRECOMPILE_SHA256,
+    },
+    Address,
+    Bytes,
+    ExitCode,
+    Fuel,
+};
+use hashbrown::HashMap;
+use revm_precompile::{PrecompileError, PrecompileErrors};
+use std::sync::Arc;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PrecompileOutput {
+    pub output: Vec<u8>,
+    pub fuel_consumed: u64,
+}
+
+impl PrecompileOutput {
+    pub fn new(output: Vec<u8>, fuel_consumed: u64) -> Self {
+        Self {
+            output,
+            fuel_consumed,
+        }
+    }
+}
+
+/// Failed precompile consumes all the fuel of the call, the error is the exit code of the call
+pub type PrecompileResult = Result<PrecompileOutput, ExitCode>;
+
+/// Native implementation of the contract at a well-known address
+pub trait Precompile: Send + Sync {
+    /// Runs the precompile with the fuel limit, the precompile fails with `OutOfGas` if the
+    /// cost of the input exceeds the limit
+    fn run(&self, input: &[u8], fuel: u64) -> PrecompileResult;
+}
+
+/// Precompiles by their addresses, the runtime consults the registry before the code of the
+/// account is loaded, so calls to these addresses bypass rWASM (top-level calls of
+/// [`crate::RuntimeContext::new_with_address`] and nested `_exec_address` calls)
+#[derive(Clone, Default)]
+pub struct PrecompileRegistry {
+    precompiles: HashMap<Address, Arc<dyn Precompile>>,
+}
+
+impl PrecompileRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ethereum precompiles the runtime implements natively: ecrecover, sha256, identity and
+    /// modexp, they are backed by `revm_precompile` and charge Ethereum gas costs as fuel
+    pub fn standard() -> Self {
+        Self::new()
+            .with_precompile(
+                PRECOMPILE_SECP256K1_ECRECOVER,
+                RevmPrecompile(revm_precompile::secp256k1::ec_recover_run),
+            )
+            .with_precompile(
+                PRECOMPILE_SHA256,
+                RevmPrecompile(revm_precompile::hash::sha256_run),
+            )
+            .with_precompile(
+                PRECOMPILE_IDENTITY,
+                RevmPrecompile(revm_precompile::identity::identity_run),
+            )
+            .with_precompile(PRECOMPILE_MODEXP, ModexpPrecompile)
+    }
+
+    /// Registers the precompile, it replaces the precompile registered at the address before
+    pub fn with_precompile<P: Precompile + 'static>(
+        mut self,
+        address: Address,
+        precompile: P,
+    ) -> Self {
+        self.precompiles.insert(address, Arc::new(precompile));
+        self
+    }
+
+    pub fn get(&self, address: &Address) -> Option<Arc<dyn Precompile>> {
+        self.precompiles.get(address).cloned()
+    }
+
+    pub fn contains(&self, address: &Address) -> bool {
+        self.precompiles.contains_key(address)
+    }
+
+    pub fn addresses(&self) -> impl Iterator<Item = &Address> {
+        self.precompiles.keys()
+    }
+}
+
+/// Runs the precompile with the fuel limit of the call (zero limit disables metering), failed
+/// precompile consumes the whole limit
+pub(crate) fn execute_precompile(
+    precompile: &dyn Precompile,
+    input: &[u8],
+    fuel_limit: Fuel,
+) -> ExecutionResult {
+    let fuel = if fuel_limit.is_zero() {
+        u64::MAX
+    } else {
+        fuel_limit.get()
+    };
+    match precompile.run(input, fuel) {
+        Ok(precompile_output) => ExecutionResult {
+            output: precompile_output.output,
+            fuel_consumed: Fuel(precompile_output.fuel_consumed),
+            ..Default::default()
+        },
+        Err(exit_code) => ExecutionResult {
+            fuel_consumed: fuel_limit,
+            ..ExecutionResult::new_error(exit_code.into_i32())
+        },
+    }
+}
+
+/// Standard precompile implemented by `revm_precompile`, the same implementations serve EVM
+/// contracts executed by the interpreter, so both paths charge the same gas and return the same
+/// output
+pub struct RevmPrecompile(pub fn(&Bytes, u64) -> revm_precompile::PrecompileResult);
+
+impl Precompile for RevmPrecompile {
+    fn run(&self, input: &[u8], fuel: u64) -> PrecompileResult {
+        match (self.0)(&Bytes::copy_from_slice(input), fuel) {
+            Ok(output) => Ok(PrecompileOutput::new(
+                output.bytes.to_vec(),
+                output.gas_used,
+            )),
+            Err(PrecompileErrors::Error(PrecompileError::OutOfGas)) => Err(ExitCode::OutOfGas),
+            Err(PrecompileErrors::Error(_)) => Err(ExitCode::PrecompileError),
+            Err(PrecompileErrors::Fatal { .. }) => Err(ExitCode::FatalExternalError),
+        }
+    }
+}
+
+/// Max length of the base, the exponent and the modulus of unmetered modexp calls (the bound of
+/// EIP-7823)
+pub const MODEXP_MAX_INPUT_LENGTH: u64 = 1024;
+
+/// Modexp with the EIP-2565 pricing. Metered calls behave exactly like the interpreter path
+/// (the gas price bounds the operand size), lengths above [`MODEXP_MAX_INPUT_LENGTH`] are
+/// rejected only for unmetered calls (the whole `u64::MAX` budget), so they can't allocate
+/// operands of arbitrary size
+pub struct ModexpPrecompile;
+
+impl ModexpPrecompile {
+    /// Reads 32-byte length word, lengths that don't fit u64 saturate
+    fn read_length(input: &[u8], offset: usize) -> u64 {
+        let mut word = [0u8; 32];
+        if offset < input.len() {
+            let available = &input[offset..input.len().min(offset + 32)];
+            word[..available.len()].copy_from_slice(available);
+        }
+        if word[..24].iter().any(|v| *v != 0) {
+            return u64::MAX;
+        }
+        u64::from_be_bytes(word[24..].try_into().unwrap())
+    }
+}
+
+impl Precompile for ModexpPrecompile {
+    fn run(&self, input: &[u8], fuel: u64) -> PrecompileResult {
+        // the gas price bounds operand sizes of metered calls only
+        if fuel == u64::MAX {
+            let too_long = [0, 32, 64]
+                .into_iter()
+                .any(|offset| Self::read_length(input, offset) > MODEXP_MAX_INPUT_LENGTH);
+            if too_long {
+                return Err(ExitCode::PrecompileError);
+            }
+        }
+        RevmPrecompile(revm_precompile::modexp::berlin_run).run(input, fuel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_standard_precompiles() {
+        let precompiles = PrecompileRegistry::standard();
+        let run = |address: Address, input: &[u8], fuel: u64| {
+            precompiles.get(&address).unwrap().run(input, fuel)
+        };
+        assert_eq!(
+            run(PRECOMPILE_IDENTITY, b"hello", 1000),
+            Ok(PrecompileOutput::new(b"hello".to_vec(), 18))
+        );
+        assert_eq!(
+            run(PRECOMPILE_SHA256, b"", 1000),
+            Ok(PrecompileOutput::new(
+                hex!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855").to_vec(),
+                60
+            ))
+        );
+        assert_eq!(run(PRECOMPILE_SHA256, b"", 59), Err(ExitCode::OutOfGas));
+        // 3^5 mod 7 = 5
+        let mut input = vec![];
+        for length in [1u8, 1, 1] {
+            input.extend_from_slice(&[0u8; 31]);
+            input.push(length);
+        }
+        input.extend_from_slice(&[3, 5, 7]);
+        assert_eq!(
+            run(PRECOMPILE_MODEXP, &input, 1000),
+            Ok(PrecompileOutput::new(vec![5], 200))
+        );
+        // zero modulus gives zero result
+        *input.last_mut().unwrap() = 0;
+        assert_eq!(
+            run(PRECOMPILE_MODEXP, &input, 1000).unwrap().output,
+            vec![0]
+        );
+        // malformed signature recovers nothing
+        assert_eq!(
+            run(PRECOMPILE_SECP256K1_ECRECOVER, &[0u8; 128], 3000),
+            Ok(PrecompileOutput::new(vec![], 3000))
+        );
+        // metered calls match the interpreter, even with oversized operands
+        let mut input = vec![0u8; 96];
+        input[30] = 0x04;
+        input[31] = 0x01;
+        input[95] = 0x01;
+        let result = run(PRECOMPILE_MODEXP, &input, 100_000);
+        assert!(result.is_ok());
+        assert_eq!(
+            result,
+            RevmPrecompile(revm_precompile::modexp::berlin_run).run(&input, 100_000)
+        );
+        // oversized operands are rejected with the unlimited budget
+        let mut input = vec![0u8; 96];
+        input[0] = 1;
+        assert_eq!(
+            run(PRECOMPILE_MODEXP, &input, u64::MAX),
+            Err(ExitCode::PrecompileError)
+        );
+        input[0] = 0;
+        input[62] = 0x04;
+        input[63] = 0x01;
+        assert_eq!(
+            run(PRECOMPILE_MODEXP, &input, u64::MAX),
+            Err(ExitCode::PrecompileError)
+        );
+        assert!(!precompiles.contains(&Address::repeat_byte(0x11)));
+    }
+}
@@ -0,0 +1,249 @@
+use crate::{types::RuntimeError, ExecutionResult, Runtime, RuntimeContext};
+use fluentbase_types::IJournaledTrie;
+use hashbrown::HashMap;
+use std::sync::{Arc, RwLock};
+
+struct ProfilerFrame {
+    func_idx: u32,
+    entered_at: u64,
+    children_fuel: u64,
+}
+
+#[derive(Default)]
+struct FuelProfilerInner {
+    stack: Vec<ProfilerFrame>,
+    /// Self fuel of every unique call stack (a list of function indices)
+    folded_stacks: HashMap<Vec<u32>, u64>,
+}
+
+/// Attributes consumed fuel to guest functions using enter/exit probes injected into the
+/// bytecode (see `instrument_wasm`). Probes consume fuel too, so numbers are a bit higher than
+/// for the non-instrumented bytecode, but proportions between functions are preserved.
+#[derive(Clone, Default)]
+pub struct FuelProfiler {
+    inner: Arc<RwLock<FuelProfilerInner>>,
+}
+
+impl FuelProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn enter(&self, func_idx: u32, fuel_consumed: u64) {
+        self.inner.write().unwrap().stack.push(ProfilerFrame {
+            func_idx,
+            entered_at: fuel_consumed,
+            children_fuel: 0,
+        });
+    }
+
+    pub(crate) fn exit(&self, fuel_consumed: u64) {
+        let mut inner = self.inner.write().unwrap();
+        let Some(frame) = inner.stack.pop() else {
+            return;
+        };
+        let total_fuel = fuel_consumed.saturating_sub(frame.entered_at);
+        let self_fuel = total_fuel.saturating_sub(frame.children_fuel);
+        let mut path = inner
+            .stack
+            .iter()
+            .map(|frame| frame.func_idx)
+            .collect::<Vec<_>>();
+        path.push(frame.func_idx);
+        *inner.folded_stacks.entry(path).or_default() += self_fuel;
+        if let Some(parent) = inner.stack.last_mut() {
+            parent.children_fuel += total_fuel;
+        }
+    }
+
+    /// Closes all frames that are still open (execution was interrupted by exit or trap)
+    pub(crate) fn finish(&self, fuel_consumed: u64) {
+        while !self.inner.read().unwrap().stack.is_empty() {
+            self.exit(fuel_consumed);
+        }
+    }
+
+    /// Returns fuel consumed by every function itself (excluding nested calls)
+    pub fn self_fuel(&self) -> HashMap<u32, u64> {
+        let mut result = HashMap::new();
+        for (path, fuel) in self.inner.read().unwrap().folded_stacks.iter() {
+            *result.entry(*path.last().unwrap()).or_default() += *fuel;
+        }
+        result
+    }
+
+    /// Exports profile in the folded-stack format (`main;foo;bar 123`) that is consumable by
+    /// inferno or flamegraph.pl, functions without names are shown as `func[idx]`
+    pub fn to_folded_stacks(&self, names: &HashMap<u32, String>) -> String {
+        let inner = self.inner.read().unwrap();
+        let mut lines = inner
+            .folded_stacks
+            .iter()
+            .filter(|(_, fuel)| **fuel > 0)
+            .map(|(path, fuel)| {
+                let path = path
+                    .iter()
+                    .map(|func_idx| {
+                        names
+                            .get(func_idx)
+                            .cloned()
+                            .unwrap_or_else(|| format!("func[{}]", func_idx))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(";");
+                format!("{} {}", path, fuel)
+            })
+            .collect::<Vec<_>>();
+        lines.sort();
+        lines.join("\n")
+    }
+}
+
+impl<DB: IJournaledTrie> Runtime<DB> {
+    /// Executes instrumented bytecode and returns fuel profile of the execution
+    pub fn profile_fuel(
+        runtime_context: RuntimeContext<DB>,
+    ) -> Result<(ExecutionResult, FuelProfiler), RuntimeError> {
+        let fuel_profiler = FuelProfiler::new();
+        let execution_result =
+            Self::new(runtime_context.with_fuel_profiler(fuel_profiler.clone())).call()?;
+        fuel_profiler.finish(execution_result.fuel_consumed.get());
+        Ok((execution_result, fuel_profiler))
+    }
+}
+
+/// Injects `_profile_enter(func_idx)` at the beginning of every local function and
+/// `_profile_exit()` before every return, returns instrumented binary and names of the
+/// functions from the name section
+#[cfg(feature = "profiler")]
+pub fn instrument_wasm(wasm_binary: &[u8]) -> Result<(Vec<u8>, HashMap<u32, String>), String> {
+    use walrus::{
+        ir::{dfs_in_order, Call, Const, Instr, InstrSeq, InstrSeqId, Value, Visitor},
+        FunctionKind,
+        Module,
+        ValType,
+    };
+
+    #[derive(Default)]
+    struct InstrSeqCollector(Vec<InstrSeqId>);
+
+    impl<'instr> Visitor<'instr> for InstrSeqCollector {
+        fn start_instr_seq(&mut self, instr_seq: &'instr InstrSeq) {
+            self.0.push(instr_seq.id());
+        }
+    }
+
+    let mut module = Module::from_buffer(wasm_binary).map_err(|err| err.to_string())?;
+    let enter_type = module.types.add(&[ValType::I32], &[]);
+    let exit_type = module.types.add(&[], &[]);
+    let local_funcs = module
+        .funcs
+        .iter_local()
+        .map(|(id, _)| id)
+        .collect::<Vec<_>>();
+    let (enter_func, _) =
+        module.add_import_func("fluentbase_v1preview", "_profile_enter", enter_type);
+    let (exit_func, _) = module.add_import_func("fluentbase_v1preview", "_profile_exit", exit_type);
+
+    let mut names = HashMap::new();
+    for id in local_funcs {
+        let func_idx = id.index() as u32;
+        let func = module.funcs.get_mut(id);
+        if let Some(name) = func.name.as_ref() {
+            names.insert(func_idx, name.clone());
+        }
+        let FunctionKind::Local(local_func) = &mut func.kind else {
+            continue;
+        };
+        let entry = local_func.entry_block();
+        let mut collector = InstrSeqCollector::default();
+        dfs_in_order(&mut collector, local_func, entry);
+        for instr_seq_id in collector.0 {
+            let instrs = &mut local_func.block_mut(instr_seq_id).instrs;
+            let mut i = 0;
+            while i < instrs.len() {
+                if let Instr::Return(_) = instrs[i].0 {
+                    instrs.insert(i, (Call { func: exit_func }.into(), Default::default()));
+                    i += 1;
+                }
+                i += 1;
+            }
+        }
+        let instrs = &mut local_func.block_mut(entry).instrs;
+        instrs.insert(
+            0,
+            (
+                Const {
+                    value: Value::I32(func_idx as i32),
+                }
+                .into(),
+                Default::default(),
+            ),
+        );
+        instrs.insert(1, (Call { func: enter_func }.into(), Default::default()));
+        instrs.push((Call { func: exit_func }.into(), Default::default()));
+    }
+    Ok((module.emit_wasm(), names))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::profiler::FuelProfiler;
+    use hashbrown::HashMap;
+
+    #[test]
+    fn test_folded_stacks() {
+        let profiler = FuelProfiler::new();
+        profiler.enter(0, 0);
+        profiler.enter(1, 10);
+        profiler.exit(40);
+        profiler.enter(1, 50);
+        profiler.exit(60);
+        profiler.finish(100);
+        let names = HashMap::from([(0, "main".to_string())]);
+        assert_eq!(
+            profiler.to_folded_stacks(&names),
+            "main 60\nmain;func[1] 40"
+        );
+        assert_eq!(profiler.self_fuel().get(&1).copied(), Some(40));
+    }
+
+    #[cfg(feature = "profiler")]
+    #[test]
+    fn test_profile_instrumented_wasm() {
+        use crate::{
+            profiler::instrument_wasm,
+            tests::wasm2rwasm,
+            DefaultEmptyRuntimeDatabase,
+            Runtime,
+            RuntimeContext,
+        };
+
+        let wasm_binary = wat::parse_str(
+            r#"
+(module
+  (func $main
+    i32.const 100
+    i32.const 20
+    call $add
+    drop
+    )
+  (func $add (param $lhs i32) (param $rhs i32) (result i32)
+    local.get $lhs
+    local.get $rhs
+    i32.add
+    )
+  (export "main" (func $main)))
+    "#,
+        )
+        .unwrap();
+        let (wasm_binary, names) = instrument_wasm(&wasm_binary).unwrap();
+        let rwasm_binary = wasm2rwasm(&wasm_binary);
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+            .with_fuel_limit(1_000_000);
+        let (execution_result, profiler) = Runtime::profile_fuel(ctx).unwrap();
+        assert_eq!(execution_result.exit_code, 0);
+        let folded_stacks = profiler.to_folded_stacks(&names);
+        assert!(folded_stacks.contains("main;add "));
+    }
+}
@@ -0,0 +1,553 @@
+use crate::{types::RuntimeError, ExecutionResult, Runtime, RuntimeContext};
+use fluentbase_types::{Fuel, IJournaledTrie};
+use hashbrown::HashMap;
+use std::{
+    fmt::{Display, Formatter},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+pub type TenantId = u64;
+
+/// Limits of the tenant, `None` means that the resource isn't limited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TenantQuota {
+    /// Fuel the tenant can spend per second, bursts up to one second of fuel are allowed
+    pub fuel_per_second: Option<u64>,
+    pub max_concurrent_executions: Option<usize>,
+    /// Total memory (in bytes) reserved by the concurrent executions of the tenant
+    pub max_memory: Option<usize>,
+    /// Share of the tenant in the fair scheduling
+    pub weight: u32,
+}
+
+impl Default for TenantQuota {
+    fn default() -> Self {
+        Self {
+            fuel_per_second: None,
+            max_concurrent_executions: None,
+            max_memory: None,
+            weight: 1,
+        }
+    }
+}
+
+impl TenantQuota {
+    pub fn with_fuel_per_second(mut self, fuel_per_second: u64) -> Self {
+        self.fuel_per_second = Some(fuel_per_second);
+        self
+    }
+
+    pub fn with_max_concurrent_executions(mut self, max_concurrent_executions: usize) -> Self {
+        self.max_concurrent_executions = Some(max_concurrent_executions);
+        self
+    }
+
+    pub fn with_max_memory(mut self, max_memory: usize) -> Self {
+        self.max_memory = Some(max_memory);
+        self
+    }
+
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        assert!(weight > 0, "tenant weight must be positive");
+        self.weight = weight;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuotaError {
+    UnknownTenant(TenantId),
+    FuelRateExceeded {
+        tenant: TenantId,
+        requested: Fuel,
+        available: Fuel,
+    },
+    /// Zero fuel limit means unmetered execution, it can't be accounted by the fuel rate
+    UnmeteredExecution(TenantId),
+    TooManyExecutions {
+        tenant: TenantId,
+        limit: usize,
+    },
+    MemoryExceeded {
+        tenant: TenantId,
+        requested: usize,
+        available: usize,
+    },
+}
+
+impl Display for QuotaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuotaError::UnknownTenant(tenant) => write!(f, "unknown tenant {}", tenant),
+            QuotaError::FuelRateExceeded {
+                tenant,
+                requested,
+                available,
+            } => write!(
+                f,
+                "tenant {} requested {} fuel, but only {} is available",
+                tenant, requested, available
+            ),
+            QuotaError::UnmeteredExecution(tenant) => write!(
+                f,
+                "tenant {} has a fuel rate limit, but requested unmetered execution",
+                tenant
+            ),
+            QuotaError::TooManyExecutions { tenant, limit } => write!(
+                f,
+                "tenant {} already runs {} concurrent executions",
+                tenant, limit
+            ),
+            QuotaError::MemoryExceeded {
+                tenant,
+                requested,
+                available,
+            } => write!(
+                f,
+                "tenant {} requested {} bytes of memory, but only {} is available",
+                tenant, requested, available
+            ),
+        }
+    }
+}
+
+/// Current usage of the tenant resources
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TenantUsage {
+    pub active_executions: usize,
+    pub reserved_memory: usize,
+    /// Fuel that can be spent right now (`None` if the fuel rate isn't limited)
+    pub available_fuel: Option<Fuel>,
+    /// Fuel consumed by all finished executions
+    pub fuel_consumed: Fuel,
+    pub executions: u64,
+    pub rejections: u64,
+}
+
+/// Chooses the tenant to run next among tenants that have enough quota for their pending
+/// executions, custom policies can be plugged into the manager
+pub trait SchedulingPolicy: Send + Sync {
+    fn pick_next(&self, candidates: &[(TenantId, TenantQuota, TenantUsage)]) -> Option<TenantId>;
+}
+
+/// Weighted fair share, the tenant with the least fuel consumed per weight unit goes first
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FairShare;
+
+impl SchedulingPolicy for FairShare {
+    fn pick_next(&self, candidates: &[(TenantId, TenantQuota, TenantUsage)]) -> Option<TenantId> {
+        candidates
+            .iter()
+            .min_by_key(|(tenant, quota, usage)| {
+                (usage.fuel_consumed.get() / quota.weight as u64, *tenant)
+            })
+            .map(|(tenant, _, _)| *tenant)
+    }
+}
+
+struct TenantState {
+    quota: TenantQuota,
+    usage: TenantUsage,
+    available_fuel: u64,
+    refilled_at: Instant,
+}
+
+impl TenantState {
+    fn new(quota: TenantQuota, now: Instant) -> Self {
+        Self {
+            quota,
+            usage: Default::default(),
+            available_fuel: quota.fuel_per_second.unwrap_or_default(),
+            refilled_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let Some(fuel_per_second) = self.quota.fuel_per_second else {
+            return;
+        };
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_nanos();
+        let refill = elapsed * fuel_per_second as u128 / 1_000_000_000;
+        // the clock isn't moved until at least one unit of fuel is refilled, so slow tenants
+        // don't lose the fractional part
+        if refill > 0 {
+            self.available_fuel =
+                (self.available_fuel as u128 + refill).min(fuel_per_second as u128) as u64;
+            self.refilled_at = now;
+        }
+    }
+
+    fn check(&self, tenant: TenantId, fuel_limit: Fuel, memory: usize) -> Result<(), QuotaError> {
+        if let Some(limit) = self.quota.max_concurrent_executions {
+            if self.usage.active_executions >= limit {
+                return Err(QuotaError::TooManyExecutions { tenant, limit });
+            }
+        }
+        if let Some(max_memory) = self.quota.max_memory {
+            let available = max_memory.saturating_sub(self.usage.reserved_memory);
+            if memory > available {
+                return Err(QuotaError::MemoryExceeded {
+                    tenant,
+                    requested: memory,
+                    available,
+                });
+            }
+        }
+        if self.quota.fuel_per_second.is_some() && fuel_limit.is_zero() {
+            return Err(QuotaError::UnmeteredExecution(tenant));
+        }
+        if self.quota.fuel_per_second.is_some() && fuel_limit.get() > self.available_fuel {
+            return Err(QuotaError::FuelRateExceeded {
+                tenant,
+                requested: fuel_limit,
+                available: Fuel(self.available_fuel),
+            });
+        }
+        Ok(())
+    }
+
+    fn usage(&self) -> TenantUsage {
+        TenantUsage {
+            available_fuel: self
+                .quota
+                .fuel_per_second
+                .map(|_| Fuel(self.available_fuel)),
+            ..self.usage
+        }
+    }
+}
+
+/// Enforces per-tenant limits across many runtimes (the manager can be shared between threads).
+///
+/// Every execution holds a permit, the permit reserves the whole fuel limit and the declared
+/// memory of the execution, unused fuel is returned when the permit is finished. The runtime
+/// doesn't limit memory growth by itself, so the memory quota relies on the declared size.
+#[derive(Clone)]
+pub struct QuotaManager {
+    tenants: Arc<Mutex<HashMap<TenantId, TenantState>>>,
+    policy: Arc<dyn SchedulingPolicy>,
+}
+
+impl Default for QuotaManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QuotaManager {
+    pub fn new() -> Self {
+        Self {
+            tenants: Default::default(),
+            policy: Arc::new(FairShare),
+        }
+    }
+
+    pub fn with_scheduling_policy<P: SchedulingPolicy + 'static>(mut self, policy: P) -> Self {
+        self.policy = Arc::new(policy);
+        self
+    }
+
+    /// Registers the tenant or replaces its limits, the usage of the existing tenant is kept
+    pub fn set_quota(&self, tenant: TenantId, quota: TenantQuota) {
+        let now = Instant::now();
+        let mut tenants = self.tenants.lock().unwrap();
+        match tenants.get_mut(&tenant) {
+            Some(state) => {
+                state.refill(now);
+                state.quota = quota;
+                state.available_fuel = state
+                    .available_fuel
+                    .min(quota.fuel_per_second.unwrap_or_default());
+            }
+            None => {
+                tenants.insert(tenant, TenantState::new(quota, now));
+            }
+        }
+    }
+
+    pub fn remove_tenant(&self, tenant: TenantId) {
+        self.tenants.lock().unwrap().remove(&tenant);
+    }
+
+    pub fn usage(&self, tenant: TenantId) -> Option<TenantUsage> {
+        let now = Instant::now();
+        let mut tenants = self.tenants.lock().unwrap();
+        tenants.get_mut(&tenant).map(|state| {
+            state.refill(now);
+            state.usage()
+        })
+    }
+
+    /// Reserves resources for the execution, the permit must be finished with the consumed
+    /// fuel (a dropped permit releases resources, but doesn't return the reserved fuel)
+    pub fn try_acquire(
+        &self,
+        tenant: TenantId,
+        fuel_limit: impl Into<Fuel>,
+        memory: usize,
+    ) -> Result<QuotaPermit, QuotaError> {
+        self.try_acquire_at(tenant, fuel_limit.into(), memory, Instant::now())
+    }
+
+    fn try_acquire_at(
+        &self,
+        tenant: TenantId,
+        fuel_limit: Fuel,
+        memory: usize,
+        now: Instant,
+    ) -> Result<QuotaPermit, QuotaError> {
+        let mut tenants = self.tenants.lock().unwrap();
+        let state = tenants
+            .get_mut(&tenant)
+            .ok_or(QuotaError::UnknownTenant(tenant))?;
+        state.refill(now);
+        if let Err(err) = state.check(tenant, fuel_limit, memory) {
+            state.usage.rejections += 1;
+            return Err(err);
+        }
+        let reserved_fuel = if state.quota.fuel_per_second.is_some() {
+            state.available_fuel -= fuel_limit.get();
+            fuel_limit
+        } else {
+            Fuel::ZERO
+        };
+        state.usage.active_executions += 1;
+        state.usage.reserved_memory += memory;
+        Ok(QuotaPermit {
+            manager: self.clone(),
+            tenant,
+            reserved_fuel,
+            memory,
+            is_released: false,
+        })
+    }
+
+    /// Scheduling hook, returns the tenant that should run next among tenants waiting for
+    /// execution with their pending `(fuel limit, memory)`, tenants without enough quota are
+    /// skipped
+    pub fn next_tenant(&self, waiting: &[(TenantId, Fuel, usize)]) -> Option<TenantId> {
+        let now = Instant::now();
+        let candidates = {
+            let mut tenants = self.tenants.lock().unwrap();
+            waiting
+                .iter()
+                .filter_map(|(tenant, fuel_limit, memory)| {
+                    let state = tenants.get_mut(tenant)?;
+                    state.refill(now);
+                    state.check(*tenant, *fuel_limit, *memory).ok()?;
+                    Some((*tenant, state.quota, state.usage()))
+                })
+                .collect::<Vec<_>>()
+        };
+        self.policy.pick_next(&candidates)
+    }
+
+    /// Executes the context on behalf of the tenant, the fuel limit of the context is reserved
+    /// for the execution (tenants with the fuel rate limit can't run contexts without the fuel
+    /// limit)
+    pub fn execute<DB: IJournaledTrie>(
+        &self,
+        tenant: TenantId,
+        runtime_context: RuntimeContext<DB>,
+        memory: usize,
+    ) -> Result<ExecutionResult, RuntimeError> {
+        let permit = self
+            .try_acquire(tenant, runtime_context.fuel_limit, memory)
+            .map_err(RuntimeError::QuotaExceeded)?;
+        let result = Runtime::run_with_context(runtime_context);
+        let fuel_consumed = match result.as_ref() {
+            Ok(execution_result) => execution_result.fuel_consumed,
+            Err(_) => permit.reserved_fuel,
+        };
+        permit.finish(fuel_consumed);
+        result
+    }
+
+    fn release(&self, permit: &QuotaPermit, fuel_consumed: Option<Fuel>) {
+        let mut tenants = self.tenants.lock().unwrap();
+        // the tenant could be removed while the execution was running
+        let Some(state) = tenants.get_mut(&permit.tenant) else {
+            return;
+        };
+        state.usage.active_executions -= 1;
+        state.usage.reserved_memory -= permit.memory;
+        state.usage.executions += 1;
+        let fuel_consumed = fuel_consumed.unwrap_or(permit.reserved_fuel);
+        state.usage.fuel_consumed += fuel_consumed;
+        if let Some(fuel_per_second) = state.quota.fuel_per_second {
+            let refund = permit.reserved_fuel.saturating_sub(fuel_consumed).get();
+            state.available_fuel = state
+                .available_fuel
+                .saturating_add(refund)
+                .min(fuel_per_second);
+        }
+    }
+}
+
+/// Resources reserved for one execution of the tenant
+pub struct QuotaPermit {
+    manager: QuotaManager,
+    tenant: TenantId,
+    reserved_fuel: Fuel,
+    memory: usize,
+    is_released: bool,
+}
+
+impl QuotaPermit {
+    pub fn tenant(&self) -> TenantId {
+        self.tenant
+    }
+
+    /// Releases resources and returns fuel that wasn't consumed
+    pub fn finish(mut self, fuel_consumed: Fuel) {
+        self.manager.release(&self, Some(fuel_consumed));
+        self.is_released = true;
+    }
+}
+
+impl Drop for QuotaPermit {
+    fn drop(&mut self) {
+        if !self.is_released {
+            self.manager.release(self, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        quota::{QuotaError, QuotaManager, TenantQuota},
+        tests::wat2rwasm,
+        types::RuntimeError,
+        DefaultEmptyRuntimeDatabase,
+        RuntimeContext,
+    };
+    use fluentbase_types::Fuel;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_tenant_limits() {
+        let manager = QuotaManager::new();
+        manager.set_quota(
+            1,
+            TenantQuota::default()
+                .with_fuel_per_second(1_000)
+                .with_max_concurrent_executions(2)
+                .with_max_memory(100),
+        );
+        let now = Instant::now();
+        let permit1 = manager.try_acquire_at(1, Fuel(600), 50, now).unwrap();
+        // memory is reserved by the running execution
+        assert_eq!(
+            manager.try_acquire_at(1, Fuel(100), 60, now).err(),
+            Some(QuotaError::MemoryExceeded {
+                tenant: 1,
+                requested: 60,
+                available: 50
+            })
+        );
+        assert_eq!(
+            manager.try_acquire_at(1, Fuel(500), 10, now).err(),
+            Some(QuotaError::FuelRateExceeded {
+                tenant: 1,
+                requested: Fuel(500),
+                available: Fuel(400)
+            })
+        );
+        let permit2 = manager.try_acquire_at(1, Fuel(400), 10, now).unwrap();
+        assert!(matches!(
+            manager.try_acquire_at(1, Fuel(0), 0, now),
+            Err(QuotaError::TooManyExecutions { limit: 2, .. })
+        ));
+        // unused fuel is returned
+        permit1.finish(Fuel(100));
+        drop(permit2);
+        let usage = manager.usage(1).unwrap();
+        assert_eq!(usage.active_executions, 0);
+        assert_eq!(usage.reserved_memory, 0);
+        assert_eq!(usage.fuel_consumed, Fuel(500));
+        assert_eq!(usage.rejections, 3);
+        // fuel is refilled over time, but never above one second of fuel
+        let permit = manager
+            .try_acquire_at(1, Fuel(900), 0, now + Duration::from_millis(500))
+            .unwrap();
+        permit.finish(Fuel(900));
+        assert!(manager
+            .try_acquire_at(1, Fuel(500), 0, now + Duration::from_millis(500))
+            .is_err());
+        assert!(manager
+            .try_acquire_at(1, Fuel(1_000), 0, now + Duration::from_secs(10))
+            .is_ok());
+        assert_eq!(
+            manager.try_acquire(2, Fuel(1), 0).err(),
+            Some(QuotaError::UnknownTenant(2))
+        );
+    }
+
+    #[test]
+    fn test_fair_scheduling() {
+        let manager = QuotaManager::new();
+        manager.set_quota(1, TenantQuota::default());
+        manager.set_quota(2, TenantQuota::default().with_weight(4));
+        manager.set_quota(3, TenantQuota::default().with_max_concurrent_executions(0));
+        manager
+            .try_acquire(1, Fuel(100), 0)
+            .unwrap()
+            .finish(Fuel(100));
+        manager
+            .try_acquire(2, Fuel(300), 0)
+            .unwrap()
+            .finish(Fuel(300));
+        // tenant 2 consumed more, but it has bigger share, tenant 3 can't run at all
+        let waiting = [(1, Fuel(10), 0), (2, Fuel(10), 0), (3, Fuel(10), 0)];
+        assert_eq!(manager.next_tenant(&waiting), Some(2));
+        manager
+            .try_acquire(2, Fuel(200), 0)
+            .unwrap()
+            .finish(Fuel(200));
+        assert_eq!(manager.next_tenant(&waiting), Some(1));
+        assert_eq!(manager.next_tenant(&waiting[2..]), None);
+    }
+
+    #[test]
+    fn test_execute_with_quota() {
+        let rwasm_binary = wat2rwasm(
+            r#"
+(module
+  (func $main)
+  (export "main" (func $main)))
+    "#,
+        );
+        let manager = QuotaManager::new();
+        manager.set_quota(1, TenantQuota::default().with_fuel_per_second(1_000_000));
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+            .with_fuel_limit(1_000_000);
+        let execution_result = manager.execute(1, ctx, 0).unwrap();
+        assert_eq!(execution_result.exit_code, 0);
+        let usage = manager.usage(1).unwrap();
+        assert_eq!(usage.fuel_consumed, execution_result.fuel_consumed);
+        assert_eq!(usage.executions, 1);
+        // the whole fuel limit must be available
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+            .with_fuel_limit(2_000_000);
+        assert!(matches!(
+            manager.execute(1, ctx, 0),
+            Err(RuntimeError::QuotaExceeded(
+                QuotaError::FuelRateExceeded { .. }
+            ))
+        ));
+        // unmetered execution would bypass the fuel rate
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone());
+        assert!(matches!(
+            manager.execute(1, ctx, 0),
+            Err(RuntimeError::QuotaExceeded(QuotaError::UnmeteredExecution(
+                1
+            )))
+        ));
+        // tenants without the fuel rate limit can still run unmetered
+        manager.set_quota(2, TenantQuota::default());
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary);
+        assert_eq!(manager.execute(2, ctx, 0).unwrap().exit_code, 0);
+    }
+}
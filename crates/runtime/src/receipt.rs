@@ -0,0 +1,160 @@
+use crate::ExecutionResult;
+use fluentbase_types::{
+    Bloom,
+    BloomInput,
+    ExitCode,
+    FuelSchedule,
+    Gas,
+    IJournaledTrie,
+    JournalCheckpoint,
+    JournalLog,
+};
+
+/// Ethereum-compatible transaction receipt produced for every executed context
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Receipt {
+    pub status: bool,
+    pub exit_code: i32,
+    pub gas_used: Gas,
+    pub cumulative_gas_used: Gas,
+    pub logs_bloom: Bloom,
+    pub logs: Vec<JournalLog>,
+}
+
+/// Computes 2048-bit bloom filter over log addresses and topics
+pub fn logs_bloom<'a, I: IntoIterator<Item = &'a JournalLog>>(logs: I) -> Bloom {
+    let mut bloom = Bloom::ZERO;
+    for log in logs {
+        bloom.accrue(BloomInput::Raw(log.address.as_slice()));
+        for topic in log.topics.iter() {
+            bloom.accrue(BloomInput::Raw(topic.as_slice()));
+        }
+    }
+    bloom
+}
+
+/// Collects receipts for a batch of executed contexts, the cumulative gas used is
+/// accumulated across all pushed executions in the order they were added. Consumed fuel is
+/// converted into gas using the fuel schedule.
+#[derive(Debug, Default)]
+pub struct ReceiptBuilder {
+    fuel_schedule: FuelSchedule,
+    cumulative_gas_used: Gas,
+    receipts: Vec<Receipt>,
+}
+
+impl ReceiptBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_fuel_schedule(mut self, fuel_schedule: FuelSchedule) -> Self {
+        self.fuel_schedule = fuel_schedule;
+        self
+    }
+
+    pub fn push(&mut self, execution_result: &ExecutionResult, logs: Vec<JournalLog>) -> &Receipt {
+        let gas_used = self
+            .fuel_schedule
+            .fuel_to_gas(execution_result.fuel_consumed);
+        self.cumulative_gas_used = self.cumulative_gas_used.saturating_add(gas_used);
+        let status = ExitCode::from(execution_result.exit_code).is_ok();
+        // logs of failed executions are rolled back, but let's be sure we don't expose them
+        let logs = if status { logs } else { vec![] };
+        self.receipts.push(Receipt {
+            status,
+            exit_code: execution_result.exit_code,
+            gas_used,
+            cumulative_gas_used: self.cumulative_gas_used,
+            logs_bloom: logs_bloom(logs.iter()),
+            logs,
+        });
+        self.receipts.last().unwrap()
+    }
+
+    /// Pushes execution result with all logs emitted into the journal after the checkpoint
+    pub fn push_from_journal<DB: IJournaledTrie>(
+        &mut self,
+        execution_result: &ExecutionResult,
+        jzkt: &DB,
+        checkpoint: &JournalCheckpoint,
+    ) -> &Receipt {
+        let logs = jzkt
+            .logs()
+            .into_iter()
+            .skip(checkpoint.logs())
+            .collect::<Vec<_>>();
+        self.push(execution_result, logs)
+    }
+
+    pub fn cumulative_gas_used(&self) -> Gas {
+        self.cumulative_gas_used
+    }
+
+    pub fn receipts(&self) -> &Vec<Receipt> {
+        &self.receipts
+    }
+
+    /// Returns bloom filter of the whole batch (block level bloom)
+    pub fn block_bloom(&self) -> Bloom {
+        let mut bloom = Bloom::ZERO;
+        for receipt in self.receipts.iter() {
+            bloom.accrue_bloom(&receipt.logs_bloom);
+        }
+        bloom
+    }
+
+    pub fn finish(self) -> Vec<Receipt> {
+        self.receipts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        receipt::{logs_bloom, ReceiptBuilder},
+        ExecutionResult,
+    };
+    use fluentbase_types::{
+        address,
+        b256,
+        BloomInput,
+        Bytes,
+        ExitCode,
+        Fuel,
+        FuelSchedule,
+        Gas,
+        JournalLog,
+    };
+
+    #[test]
+    fn test_receipt_bloom_and_cumulative_gas() {
+        let log = JournalLog {
+            address: address!("0000000000000000000000000000000000005210"),
+            topics: vec![b256!(
+                "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+            )],
+            data: Bytes::new(),
+        };
+        let mut builder = ReceiptBuilder::new().with_fuel_schedule(FuelSchedule::new(10));
+        let mut result = ExecutionResult::default();
+        result.fuel_consumed = Fuel(1_000);
+        let receipt = builder.push(&result, vec![log.clone()]);
+        assert!(receipt.status);
+        assert!(receipt
+            .logs_bloom
+            .contains_input(BloomInput::Raw(log.address.as_slice())));
+        assert!(receipt
+            .logs_bloom
+            .contains_input(BloomInput::Raw(log.topics[0].as_slice())));
+        let mut result = ExecutionResult::new_error(ExitCode::Panic.into_i32());
+        // partially consumed gas unit is charged in full
+        result.fuel_consumed = Fuel(495);
+        let receipt = builder.push(&result, vec![log.clone()]);
+        assert!(!receipt.status);
+        assert!(receipt.logs.is_empty());
+        assert_eq!(receipt.gas_used, Gas(50));
+        assert_eq!(receipt.cumulative_gas_used, Gas(150));
+        assert_eq!(builder.block_bloom(), logs_bloom([log].iter()));
+    }
+}
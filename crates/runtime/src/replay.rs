@@ -0,0 +1,464 @@
+use crate::{
+    types::{InMemoryTrieDb, RuntimeError},
+    zktrie::ZkTrieStateDb,
+    ExecutionResult,
+    JournaledTrie,
+    Runtime,
+    RuntimeContext,
+    TrieStorage,
+};
+use byteorder::{ByteOrder, LittleEndian};
+use fluentbase_types::{Bytes, ExitCode, Fuel, IJournaledTrie, JournalEvent};
+use hashbrown::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+pub type WitnessTrie = JournaledTrie<ZkTrieStateDb<InMemoryTrieDb>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayError {
+    Runtime(String),
+    MalformedWitness,
+    ExitCodeMismatch {
+        expected: i32,
+        actual: i32,
+    },
+    OutputMismatch {
+        expected: Vec<u8>,
+        actual: Vec<u8>,
+    },
+    RootMismatch {
+        expected: [u8; 32],
+        actual: [u8; 32],
+    },
+}
+
+impl From<RuntimeError> for ReplayError {
+    fn from(value: RuntimeError) -> Self {
+        Self::Runtime(format!("{:?}", value))
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct StateReads {
+    // we store only first read of each key because it's the pre-state value
+    pub(crate) leafs: HashMap<[u8; 32], (Vec<[u8; 32]>, u32)>,
+    // keys that were read but don't exist in the storage
+    pub(crate) absent: HashSet<[u8; 32]>,
+    pub(crate) preimages: HashMap<[u8; 32], Bytes>,
+}
+
+/// Trie storage wrapper that remembers all values read from the underlying storage, these
+/// values form the pre-state required for the stateless replay
+pub struct RecordingTrieStorage<DB: TrieStorage> {
+    storage: DB,
+    pub(crate) reads: Arc<RwLock<StateReads>>,
+}
+
+impl<DB: TrieStorage> RecordingTrieStorage<DB> {
+    pub fn new(storage: DB) -> Self {
+        Self {
+            storage,
+            reads: Default::default(),
+        }
+    }
+}
+
+impl<DB: TrieStorage> TrieStorage for RecordingTrieStorage<DB> {
+    fn open(&mut self, root32: &[u8]) -> bool {
+        self.storage.open(root32)
+    }
+
+    fn compute_root(&self) -> [u8; 32] {
+        self.storage.compute_root()
+    }
+
+    fn get(&self, key: &[u8]) -> Option<(Vec<[u8; 32]>, u32)> {
+        let result = self.storage.get(key);
+        if let Ok(key) = key.try_into() {
+            let mut reads = self.reads.write().unwrap();
+            match result.as_ref() {
+                Some(result) => {
+                    reads.leafs.entry(key).or_insert_with(|| result.clone());
+                }
+                None => {
+                    reads.absent.insert(key);
+                }
+            }
+        }
+        result
+    }
+
+    fn get_at(&self, root32: &[u8; 32], key: &[u8]) -> Option<(Vec<[u8; 32]>, u32)> {
+        self.storage.get_at(root32, key)
+    }
+
+    fn update(
+        &mut self,
+        key: &[u8],
+        value_flags: u32,
+        value: &Vec<[u8; 32]>,
+    ) -> Result<(), ExitCode> {
+        self.storage.update(key, value_flags, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) -> Result<(), ExitCode> {
+        self.storage.remove(key)
+    }
+
+    fn delete(&mut self, key: &[u8]) -> Result<(), ExitCode> {
+        self.storage.delete(key)
+    }
+
+    fn compact(&mut self) -> usize {
+        self.storage.compact()
+    }
+
+    fn proof(&self, key: &[u8; 32]) -> Option<Vec<Vec<u8>>> {
+        self.storage.proof(key)
+    }
+
+    fn get_preimage(&mut self, key: &[u8]) -> Option<Bytes> {
+        let result = self.storage.get_preimage(key)?;
+        if let Ok(key) = key.try_into() {
+            self.reads
+                .write()
+                .unwrap()
+                .preimages
+                .entry(key)
+                .or_insert_with(|| result.clone());
+        }
+        Some(result)
+    }
+
+    fn update_preimage(&mut self, key: &[u8], value: Bytes) {
+        self.storage.update_preimage(key, value)
+    }
+
+    fn flush(&mut self) -> Result<(), ExitCode> {
+        self.storage.flush()
+    }
+}
+
+/// Call parameters required to re-execute the context
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayCall {
+    pub bytecode: Bytes,
+    pub input: Vec<u8>,
+    pub context: Vec<u8>,
+    pub state: u32,
+    pub fuel_limit: Fuel,
+}
+
+impl ReplayCall {
+    pub(crate) fn to_runtime_context<DB: IJournaledTrie>(&self, jzkt: DB) -> RuntimeContext<DB> {
+        RuntimeContext::new(self.bytecode.clone())
+            .with_input(self.input.clone())
+            .with_context(self.context.clone())
+            .with_state(self.state)
+            .with_fuel_limit(self.fuel_limit)
+            .with_jzkt(jzkt)
+    }
+
+    pub(crate) fn write(&self, buffer: &mut Vec<u8>) {
+        write_bytes(buffer, &self.bytecode);
+        write_bytes(buffer, &self.input);
+        write_bytes(buffer, &self.context);
+        write_u32(buffer, self.state);
+        write_u64(buffer, self.fuel_limit.get());
+    }
+
+    pub(crate) fn read(reader: &mut WitnessReader) -> Result<Self, ReplayError> {
+        Ok(Self {
+            bytecode: reader.read_bytes()?.into(),
+            input: reader.read_bytes()?,
+            context: reader.read_bytes()?,
+            state: reader.read_u32()?,
+            fuel_limit: Fuel(reader.read_u64()?),
+        })
+    }
+}
+
+/// Everything required to re-execute the call without access to the state: call parameters,
+/// state values read during the execution and expected results. Post root is computed over the
+/// partial trie that contains only witness values.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayWitness {
+    pub call: ReplayCall,
+    pub state_reads: Vec<([u8; 32], Vec<[u8; 32]>, u32)>,
+    pub preimages: Vec<([u8; 32], Bytes)>,
+    pub exit_code: i32,
+    pub output: Vec<u8>,
+    pub post_root: [u8; 32],
+}
+
+impl ReplayWitness {
+    /// Executes the call over the storage and records the witness, storage changes are not
+    /// committed, so caller can commit or rollback them using the returned journal
+    pub fn record<DB: TrieStorage>(
+        call: ReplayCall,
+        storage: DB,
+    ) -> Result<(Self, JournaledTrie<RecordingTrieStorage<DB>>), ReplayError> {
+        let storage = RecordingTrieStorage::new(storage);
+        let reads = storage.reads.clone();
+        let jzkt = JournaledTrie::new(storage);
+        let execution_result = Runtime::run_with_context(call.to_runtime_context(jzkt.clone()))?;
+
+        // sort reads to make witness deterministic
+        let reads = reads.read().unwrap();
+        let mut state_reads = reads
+            .leafs
+            .iter()
+            .map(|(key, (values, flags))| (*key, values.clone(), *flags))
+            .collect::<Vec<_>>();
+        state_reads.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut preimages = reads
+            .preimages
+            .iter()
+            .map(|(hash, preimage)| (*hash, preimage.clone()))
+            .collect::<Vec<_>>();
+        preimages.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut witness = Self {
+            call,
+            state_reads,
+            preimages,
+            exit_code: execution_result.exit_code,
+            output: execution_result.output,
+            post_root: [0u8; 32],
+        };
+
+        // apply recorded changes to the witness trie to compute the expected post root
+        let witness_trie = witness.witness_trie()?;
+        for event in jzkt.journal().iter() {
+            match event {
+                JournalEvent::ItemChanged {
+                    key,
+                    preimage,
+                    flags,
+                    ..
+                } => witness_trie.update(key, preimage, *flags),
+                JournalEvent::ItemRemoved { key, .. } => witness_trie.remove(key),
+            }
+        }
+        witness.post_root = witness_trie
+            .commit()
+            .map_err(|_| ReplayError::MalformedWitness)?
+            .0;
+        Ok((witness, jzkt))
+    }
+
+    /// Creates partial trie that contains only values from the witness
+    pub fn witness_trie(&self) -> Result<WitnessTrie, ReplayError> {
+        let mut storage = ZkTrieStateDb::new_empty(InMemoryTrieDb::default());
+        for (key, values, flags) in self.state_reads.iter() {
+            storage
+                .update(key, *flags, values)
+                .map_err(|_| ReplayError::MalformedWitness)?;
+        }
+        for (hash, preimage) in self.preimages.iter() {
+            storage.update_preimage(hash, preimage.clone());
+        }
+        Ok(JournaledTrie::new(storage))
+    }
+
+    /// Re-executes the call in the stateless mode and checks that exit code, output and post
+    /// root match the recorded ones
+    pub fn replay(&self) -> Result<ExecutionResult, ReplayError> {
+        let jzkt = self.witness_trie()?;
+        let execution_result =
+            Runtime::run_with_context(self.call.to_runtime_context(jzkt.clone()))?;
+        if execution_result.exit_code != self.exit_code {
+            return Err(ReplayError::ExitCodeMismatch {
+                expected: self.exit_code,
+                actual: execution_result.exit_code,
+            });
+        }
+        if execution_result.output != self.output {
+            return Err(ReplayError::OutputMismatch {
+                expected: self.output.clone(),
+                actual: execution_result.output,
+            });
+        }
+        let (post_root, _) = jzkt.commit().map_err(|_| ReplayError::MalformedWitness)?;
+        if post_root != self.post_root {
+            return Err(ReplayError::RootMismatch {
+                expected: self.post_root,
+                actual: post_root,
+            });
+        }
+        Ok(execution_result)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        self.call.write(&mut result);
+        write_u32(&mut result, self.state_reads.len() as u32);
+        for (key, values, flags) in self.state_reads.iter() {
+            result.extend_from_slice(key);
+            write_u32(&mut result, *flags);
+            write_u32(&mut result, values.len() as u32);
+            values.iter().for_each(|v| result.extend_from_slice(v));
+        }
+        write_u32(&mut result, self.preimages.len() as u32);
+        for (hash, preimage) in self.preimages.iter() {
+            result.extend_from_slice(hash);
+            write_bytes(&mut result, preimage);
+        }
+        write_u32(&mut result, self.exit_code as u32);
+        write_bytes(&mut result, &self.output);
+        result.extend_from_slice(&self.post_root);
+        result
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut reader = WitnessReader::new(bytes);
+        let call = ReplayCall::read(&mut reader)?;
+        let mut state_reads = Vec::new();
+        for _ in 0..reader.read_u32()? {
+            let key = reader.read_bytes32()?;
+            let flags = reader.read_u32()?;
+            let values = (0..reader.read_u32()?)
+                .map(|_| reader.read_bytes32())
+                .collect::<Result<Vec<_>, _>>()?;
+            state_reads.push((key, values, flags));
+        }
+        let mut preimages = Vec::new();
+        for _ in 0..reader.read_u32()? {
+            let hash = reader.read_bytes32()?;
+            preimages.push((hash, reader.read_bytes()?.into()));
+        }
+        let witness = Self {
+            call,
+            state_reads,
+            preimages,
+            exit_code: reader.read_u32()? as i32,
+            output: reader.read_bytes()?,
+            post_root: reader.read_bytes32()?,
+        };
+        reader.finish()?;
+        Ok(witness)
+    }
+}
+
+pub(crate) fn write_u32(buffer: &mut Vec<u8>, value: u32) {
+    let mut bytes = [0u8; 4];
+    LittleEndian::write_u32(&mut bytes, value);
+    buffer.extend_from_slice(&bytes);
+}
+
+pub(crate) fn write_u64(buffer: &mut Vec<u8>, value: u64) {
+    let mut bytes = [0u8; 8];
+    LittleEndian::write_u64(&mut bytes, value);
+    buffer.extend_from_slice(&bytes);
+}
+
+pub(crate) fn write_bytes(buffer: &mut Vec<u8>, value: &[u8]) {
+    write_u32(buffer, value.len() as u32);
+    buffer.extend_from_slice(value);
+}
+
+pub(crate) struct WitnessReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> WitnessReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    /// Checks that all bytes are consumed
+    pub(crate) fn finish(&self) -> Result<(), ReplayError> {
+        if self.offset != self.bytes.len() {
+            return Err(ReplayError::MalformedWitness);
+        }
+        Ok(())
+    }
+
+    fn read_slice(&mut self, length: usize) -> Result<&'a [u8], ReplayError> {
+        let result = self
+            .bytes
+            .get(self.offset..self.offset + length)
+            .ok_or(ReplayError::MalformedWitness)?;
+        self.offset += length;
+        Ok(result)
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32, ReplayError> {
+        Ok(LittleEndian::read_u32(self.read_slice(4)?))
+    }
+
+    pub(crate) fn read_u64(&mut self) -> Result<u64, ReplayError> {
+        Ok(LittleEndian::read_u64(self.read_slice(8)?))
+    }
+
+    pub(crate) fn read_bytes32(&mut self) -> Result<[u8; 32], ReplayError> {
+        Ok(self.read_slice(32)?.try_into().unwrap())
+    }
+
+    pub(crate) fn read_bytes(&mut self) -> Result<Vec<u8>, ReplayError> {
+        let length = self.read_u32()? as usize;
+        Ok(self.read_slice(length)?.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        replay::{ReplayCall, ReplayError, ReplayWitness},
+        tests::wat2rwasm,
+        types::InMemoryTrieDb,
+        zktrie::ZkTrieStateDb,
+    };
+    use fluentbase_types::Fuel;
+
+    #[test]
+    fn test_replay_witness() {
+        let rwasm_binary = wat2rwasm(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (param i32 i32 i32)))
+  (type (;2;) (func (result i32)))
+  (type (;3;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_read" (func $_read (type 1)))
+  (import "fluentbase_v1preview" "_input_size" (func $_input_size (type 2)))
+  (func $main (type 3)
+    i32.const 0
+    i32.const 0
+    call $_input_size
+    call $_read
+    i32.const 0
+    call $_input_size
+    call $_write
+    )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+        );
+        let call = ReplayCall {
+            bytecode: rwasm_binary.into(),
+            input: "Hello, World".as_bytes().to_vec(),
+            fuel_limit: Fuel(1_000_000),
+            ..Default::default()
+        };
+        let storage = ZkTrieStateDb::new_empty(InMemoryTrieDb::default());
+        let (witness, _) = ReplayWitness::record(call, storage).unwrap();
+        assert_eq!(witness.output, "Hello, World".as_bytes().to_vec());
+        let witness = ReplayWitness::from_bytes(&witness.to_bytes()).unwrap();
+        witness.replay().unwrap();
+        let mut malformed_witness = witness.clone();
+        malformed_witness.output = vec![];
+        assert!(matches!(
+            malformed_witness.replay(),
+            Err(ReplayError::OutputMismatch { .. })
+        ));
+        let mut malformed_witness = witness;
+        malformed_witness.post_root = [1u8; 32];
+        assert!(matches!(
+            malformed_witness.replay(),
+            Err(ReplayError::RootMismatch { .. })
+        ));
+    }
+}
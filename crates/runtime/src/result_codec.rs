@@ -0,0 +1,425 @@
+use crate::{
+    assertion::{AssertionFailure, AssertionKind},
+    state_diff::{AccountDiff, StateDiff, ValueChange},
+    ExecutionResult,
+};
+use byteorder::{ByteOrder, LittleEndian};
+use fluentbase_types::{Address, Fuel, JournalLog, B256, U256};
+
+/// Magic prefix of the serialized execution result
+pub const EXECUTION_RESULT_MAGIC: [u8; 4] = *b"FBER";
+/// Version 2 appends the assertion failure, results of version 1 are still decoded
+pub const EXECUTION_RESULT_FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResultCodecError {
+    BadMagic,
+    UnsupportedVersion(u32),
+    UnexpectedEof,
+    /// Flag of the optional value is neither 0 nor 1
+    MalformedFlag(u8),
+    /// Assertion kind is unknown or the message isn't UTF-8
+    MalformedAssertion,
+    TrailingBytes,
+}
+
+impl ExecutionResult {
+    /// Compact binary encoding of the result (little-endian, length-prefixed buffers), it's
+    /// used to pass results between sequencer, prover and RPC processes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = ResultWriter::default();
+        writer.buffer.extend_from_slice(&EXECUTION_RESULT_MAGIC);
+        writer.write_u32(EXECUTION_RESULT_FORMAT_VERSION);
+        writer.write_u32(self.exit_code as u32);
+        writer.write_bytes(&self.output);
+        writer.write_u64(self.fuel_consumed.get());
+        writer.write_bytes(&self.return_data);
+        writer.write_u32(self.logs.len() as u32);
+        for log in self.logs.iter() {
+            writer.buffer.extend_from_slice(log.address.as_slice());
+            writer.write_u32(log.topics.len() as u32);
+            log.topics
+                .iter()
+                .for_each(|topic| writer.buffer.extend_from_slice(topic.as_slice()));
+            writer.write_bytes(&log.data);
+        }
+        writer.write_option(self.state_diff.as_ref(), ResultWriter::write_state_diff);
+        writer.write_option(self.trace_hash.as_ref(), |writer, hash| {
+            writer.buffer.extend_from_slice(hash.as_slice())
+        });
+        writer.write_option(
+            self.assertion_failure.as_ref(),
+            ResultWriter::write_assertion_failure,
+        );
+        writer.buffer
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ResultCodecError> {
+        let mut reader = ResultReader { bytes, offset: 0 };
+        if reader.read_slice(4)? != EXECUTION_RESULT_MAGIC {
+            return Err(ResultCodecError::BadMagic);
+        }
+        let version = reader.read_u32()?;
+        if version != 1 && version != EXECUTION_RESULT_FORMAT_VERSION {
+            return Err(ResultCodecError::UnsupportedVersion(version));
+        }
+        let exit_code = reader.read_u32()? as i32;
+        let output = reader.read_bytes()?;
+        let fuel_consumed = Fuel(reader.read_u64()?);
+        let return_data = reader.read_bytes()?;
+        let mut logs = Vec::new();
+        for _ in 0..reader.read_u32()? {
+            let address = Address::from_slice(reader.read_slice(20)?);
+            let topics = (0..reader.read_u32()?)
+                .map(|_| reader.read_b256())
+                .collect::<Result<Vec<_>, _>>()?;
+            logs.push(JournalLog {
+                address,
+                topics,
+                data: reader.read_bytes()?.into(),
+            });
+        }
+        let state_diff = reader.read_option(ResultReader::read_state_diff)?;
+        let trace_hash = reader.read_option(ResultReader::read_b256)?;
+        let assertion_failure = if version > 1 {
+            reader.read_option(ResultReader::read_assertion_failure)?
+        } else {
+            None
+        };
+        if reader.offset != bytes.len() {
+            return Err(ResultCodecError::TrailingBytes);
+        }
+        Ok(Self {
+            exit_code,
+            output,
+            fuel_consumed,
+            return_data,
+            logs,
+            state_diff,
+            trace_hash,
+            assertion_failure,
+        })
+    }
+}
+
+#[derive(Default)]
+struct ResultWriter {
+    buffer: Vec<u8>,
+}
+
+impl ResultWriter {
+    fn write_u32(&mut self, value: u32) {
+        let mut bytes = [0u8; 4];
+        LittleEndian::write_u32(&mut bytes, value);
+        self.buffer.extend_from_slice(&bytes);
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        let mut bytes = [0u8; 8];
+        LittleEndian::write_u64(&mut bytes, value);
+        self.buffer.extend_from_slice(&bytes);
+    }
+
+    fn write_bytes(&mut self, value: &[u8]) {
+        self.write_u32(value.len() as u32);
+        self.buffer.extend_from_slice(value);
+    }
+
+    fn write_u256(&mut self, value: &U256) {
+        self.buffer.extend_from_slice(&value.to_be_bytes::<32>());
+    }
+
+    fn write_option<T, F: FnOnce(&mut Self, &T)>(&mut self, value: Option<&T>, write: F) {
+        match value {
+            Some(value) => {
+                self.buffer.push(1);
+                write(self, value);
+            }
+            None => self.buffer.push(0),
+        }
+    }
+
+    fn write_change<T, F: Fn(&mut Self, &T)>(&mut self, change: Option<&ValueChange<T>>, write: F) {
+        self.write_option(change, |writer, change| {
+            write(writer, &change.old);
+            write(writer, &change.new);
+        });
+    }
+
+    fn write_assertion_failure(&mut self, assertion_failure: &AssertionFailure) {
+        self.buffer.push(assertion_failure.kind as u8);
+        self.write_bytes(&assertion_failure.expected);
+        self.write_bytes(&assertion_failure.actual);
+        self.write_bytes(assertion_failure.message.as_bytes());
+    }
+
+    fn write_state_diff(&mut self, state_diff: &StateDiff) {
+        self.write_u32(state_diff.accounts.len() as u32);
+        for (address, account) in state_diff.accounts.iter() {
+            self.buffer.extend_from_slice(address.as_slice());
+            self.buffer.push(account.created as u8);
+            self.write_change(account.balance.as_ref(), Self::write_u256);
+            self.write_change(account.nonce.as_ref(), |writer, nonce| {
+                writer.write_u64(*nonce)
+            });
+            self.write_change(account.code_hash.as_ref(), |writer, hash| {
+                writer.buffer.extend_from_slice(hash.as_slice())
+            });
+            self.write_change(account.rwasm_code_hash.as_ref(), |writer, hash| {
+                writer.buffer.extend_from_slice(hash.as_slice())
+            });
+            self.write_u32(account.storage.len() as u32);
+            for (slot, change) in account.storage.iter() {
+                self.buffer.extend_from_slice(slot.as_slice());
+                self.write_u256(&change.old);
+                self.write_u256(&change.new);
+            }
+        }
+        self.write_u32(state_diff.unresolved.len() as u32);
+        for (key, change) in state_diff.unresolved.iter() {
+            self.buffer.extend_from_slice(key.as_slice());
+            for values in [&change.old, &change.new] {
+                self.write_option(values.as_ref(), |writer, values| {
+                    writer.write_u32(values.len() as u32);
+                    values
+                        .iter()
+                        .for_each(|value| writer.buffer.extend_from_slice(value));
+                });
+            }
+        }
+    }
+}
+
+struct ResultReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ResultReader<'a> {
+    fn read_slice(&mut self, length: usize) -> Result<&'a [u8], ResultCodecError> {
+        let result = self
+            .bytes
+            .get(self.offset..self.offset + length)
+            .ok_or(ResultCodecError::UnexpectedEof)?;
+        self.offset += length;
+        Ok(result)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, ResultCodecError> {
+        Ok(self.read_slice(1)?[0])
+    }
+
+    fn read_bool(&mut self) -> Result<bool, ResultCodecError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            flag => Err(ResultCodecError::MalformedFlag(flag)),
+        }
+    }
+
+    fn read_u32(&mut self) -> Result<u32, ResultCodecError> {
+        Ok(LittleEndian::read_u32(self.read_slice(4)?))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, ResultCodecError> {
+        Ok(LittleEndian::read_u64(self.read_slice(8)?))
+    }
+
+    fn read_bytes32(&mut self) -> Result<[u8; 32], ResultCodecError> {
+        Ok(self.read_slice(32)?.try_into().unwrap())
+    }
+
+    fn read_b256(&mut self) -> Result<B256, ResultCodecError> {
+        Ok(B256::from(self.read_bytes32()?))
+    }
+
+    fn read_u256(&mut self) -> Result<U256, ResultCodecError> {
+        Ok(U256::from_be_bytes(self.read_bytes32()?))
+    }
+
+    fn read_bytes(&mut self) -> Result<Vec<u8>, ResultCodecError> {
+        let length = self.read_u32()? as usize;
+        Ok(self.read_slice(length)?.to_vec())
+    }
+
+    fn read_option<T, F: FnOnce(&mut Self) -> Result<T, ResultCodecError>>(
+        &mut self,
+        read: F,
+    ) -> Result<Option<T>, ResultCodecError> {
+        if self.read_bool()? {
+            Ok(Some(read(self)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn read_change<T, F: Fn(&mut Self) -> Result<T, ResultCodecError>>(
+        &mut self,
+        read: F,
+    ) -> Result<Option<ValueChange<T>>, ResultCodecError> {
+        self.read_option(|reader| {
+            Ok(ValueChange {
+                old: read(reader)?,
+                new: read(reader)?,
+            })
+        })
+    }
+
+    fn read_values(&mut self) -> Result<Option<Vec<[u8; 32]>>, ResultCodecError> {
+        self.read_option(|reader| {
+            (0..reader.read_u32()?)
+                .map(|_| reader.read_bytes32())
+                .collect()
+        })
+    }
+
+    fn read_assertion_failure(&mut self) -> Result<AssertionFailure, ResultCodecError> {
+        let kind =
+            AssertionKind::from_u8(self.read_u8()?).ok_or(ResultCodecError::MalformedAssertion)?;
+        Ok(AssertionFailure {
+            kind,
+            expected: self.read_bytes()?,
+            actual: self.read_bytes()?,
+            message: String::from_utf8(self.read_bytes()?)
+                .map_err(|_| ResultCodecError::MalformedAssertion)?,
+        })
+    }
+
+    fn read_state_diff(&mut self) -> Result<StateDiff, ResultCodecError> {
+        let mut state_diff = StateDiff::default();
+        for _ in 0..self.read_u32()? {
+            let address = Address::from_slice(self.read_slice(20)?);
+            let mut account = AccountDiff {
+                created: self.read_bool()?,
+                balance: self.read_change(Self::read_u256)?,
+                nonce: self.read_change(Self::read_u64)?,
+                code_hash: self.read_change(Self::read_b256)?,
+                rwasm_code_hash: self.read_change(Self::read_b256)?,
+                ..Default::default()
+            };
+            for _ in 0..self.read_u32()? {
+                let slot = self.read_b256()?;
+                let change = ValueChange {
+                    old: self.read_u256()?,
+                    new: self.read_u256()?,
+                };
+                account.storage.insert(slot, change);
+            }
+            state_diff.accounts.insert(address, account);
+        }
+        for _ in 0..self.read_u32()? {
+            let key = self.read_b256()?;
+            let change = ValueChange {
+                old: self.read_values()?,
+                new: self.read_values()?,
+            };
+            state_diff.unresolved.insert(key, change);
+        }
+        Ok(state_diff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        assertion::{AssertionFailure, AssertionKind},
+        result_codec::ResultCodecError,
+        state_diff::{AccountDiff, StateDiff, ValueChange},
+        ExecutionResult,
+    };
+    use fluentbase_types::{address, ExitCode, Fuel, JournalLog, B256, U256};
+
+    #[test]
+    fn test_execution_result_encoding() {
+        let account = address!("1111111111111111111111111111111111111111");
+        let mut state_diff = StateDiff::default();
+        let mut account_diff = AccountDiff {
+            created: true,
+            nonce: Some(ValueChange { old: 0, new: 1 }),
+            balance: Some(ValueChange {
+                old: U256::ZERO,
+                new: U256::from(100),
+            }),
+            ..Default::default()
+        };
+        account_diff.storage.insert(
+            B256::repeat_byte(1),
+            ValueChange {
+                old: U256::from(1),
+                new: U256::ZERO,
+            },
+        );
+        state_diff.accounts.insert(account, account_diff);
+        state_diff.unresolved.insert(
+            B256::repeat_byte(2),
+            ValueChange {
+                old: None,
+                new: Some(vec![[3u8; 32]]),
+            },
+        );
+        let execution_result = ExecutionResult {
+            exit_code: ExitCode::Revert.into_i32(),
+            output: b"revert".to_vec(),
+            fuel_consumed: Fuel(12345),
+            return_data: vec![1, 2, 3],
+            logs: vec![JournalLog {
+                address: account,
+                topics: vec![B256::repeat_byte(4)],
+                data: b"data".to_vec().into(),
+            }],
+            state_diff: Some(state_diff),
+            trace_hash: None,
+            assertion_failure: Some(AssertionFailure {
+                kind: AssertionKind::Storage,
+                expected: vec![1],
+                actual: vec![2],
+                message: "slot mismatch".to_string(),
+            }),
+        }
+        .with_trace(b"trace");
+        let bytes = execution_result.to_bytes();
+        assert_eq!(ExecutionResult::from_bytes(&bytes), Ok(execution_result));
+
+        let empty = ExecutionResult::default();
+        assert_eq!(
+            ExecutionResult::from_bytes(&empty.to_bytes()),
+            Ok(empty.clone())
+        );
+        // results of the first version have no assertion failure
+        let mut legacy = empty.to_bytes();
+        legacy.pop();
+        legacy[4..8].copy_from_slice(&1u32.to_le_bytes());
+        assert_eq!(ExecutionResult::from_bytes(&legacy), Ok(empty.clone()));
+        let mut bytes = empty.to_bytes();
+        assert_eq!(
+            ExecutionResult::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ResultCodecError::UnexpectedEof)
+        );
+        bytes.push(0);
+        assert_eq!(
+            ExecutionResult::from_bytes(&bytes),
+            Err(ResultCodecError::TrailingBytes)
+        );
+        bytes[0] = 0;
+        assert_eq!(
+            ExecutionResult::from_bytes(&bytes),
+            Err(ResultCodecError::BadMagic)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_execution_result_serde() {
+        let execution_result = ExecutionResult {
+            exit_code: ExitCode::Ok.into_i32(),
+            output: b"Hello, World".to_vec(),
+            fuel_consumed: Fuel(100),
+            state_diff: Some(StateDiff::default()),
+            ..Default::default()
+        }
+        .with_trace(b"trace");
+        let json = serde_json::to_string(&execution_result).unwrap();
+        let decoded: ExecutionResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, execution_result);
+    }
+}
@@ -1,10 +1,36 @@
 use crate::{
+    access_list::AccessListRecorder,
+    arena::BufferArena,
+    artifact_store::{ModuleArtifact, ModuleArtifactStore},
+    assertion::AssertionFailure,
+    breakpoint::SysBreakpointResumable,
+    call_stack::CallStack,
+    capability::{Capabilities, EscalationPolicy},
+    coverage::CoverageCollector,
+    deploy::CodeSizeLimits,
+    envelope::{InputDecryptor, InputEnvelope, SealedInputs},
+    fuel_policy::FuelPolicy,
     instruction::{
         context_call::{SysContextCallResumable, SyscallContextCall},
         exec::{SysExecResumable, SyscallExec},
-        runtime_register_shared_handlers,
+        exec_address::SyscallExecAddress,
+        keccak256::SyscallKeccak256,
         runtime_register_sovereign_handlers,
+        suspend::SysSuspendResumable,
     },
+    memory_init::MemoryInitMode,
+    module_cache::{ModuleCache, PolicyVerdict, DEFAULT_MODULE_CACHE_CAPACITY},
+    output_stream::OutputStream,
+    policy::BytecodePolicy,
+    precompile::{execute_precompile, Precompile, PrecompileRegistry},
+    profiler::FuelProfiler,
+    signature_cache::SignatureCache,
+    state_diff::StateDiff,
+    trace::{
+        format::{TraceFormatError, TraceWriter},
+        json::trace_to_host_call_steps,
+    },
+    typed_call::{CallArgs, CallResults},
     types::{InMemoryTrieDb, RuntimeError},
     zktrie::ZkTrieStateDb,
     JournaledTrie,
@@ -13,20 +39,37 @@ use fluentbase_poseidon::poseidon_hash;
 use fluentbase_types::{
     create_shared_import_linker,
     create_sovereign_import_linker,
+    split_metadata,
+    Address,
+    BytecodeType,
     Bytes,
+    ContractMetadata,
     EmptyJournalTrie,
     ExitCode,
+    Fuel,
+    FuelSchedule,
     IJournaledTrie,
+    IntrinsicCost,
+    JournalCheckpoint,
+    JournalLog,
+    Keccak256,
+    MetadataError,
+    SysFuncIdx,
     SysFuncIdx::STATE,
+    SystemContractView,
+    B256,
     F254,
+    HOST_ABI_VERSION,
     POSEIDON_EMPTY,
     STATE_DEPLOY,
     STATE_MAIN,
+    U256,
 };
-use hashbrown::{hash_map::Entry, HashMap};
+use hashbrown::HashMap;
 use rwasm::{
     core::{ImportLinker, Trap},
     engine::{bytecode::Instruction, DropKeep, RwasmConfig, StateRouterConfig},
+    errors::FuelError,
     instruction_set,
     rwasm::RwasmModule,
     AsContextMut,
@@ -37,20 +80,33 @@ use rwasm::{
     Linker,
     Module,
     ResumableCall,
+    ResumableInvocation,
+    StackLimits,
     Store,
     Value,
 };
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     fmt::{Debug, Formatter},
-    mem::take,
+    mem::{replace, take},
+    sync::Arc,
 };
 
 pub type DefaultEmptyRuntimeDatabase = JournaledTrie<ZkTrieStateDb<InMemoryTrieDb>>;
 
+/// Hook evaluated for the metadata section of the bytecode before the instantiation
+pub type MetadataVerifier = Arc<dyn Fn(&ContractMetadata) -> Result<(), String> + Send + Sync>;
+
+/// Shared read-only view of the system contract registry
+pub type SystemContracts = Arc<dyn SystemContractView + Send + Sync>;
+
+#[derive(Clone)]
 pub enum BytecodeOrHash {
     Bytecode(Bytes, Option<F254>),
+    /// Bytecode is taken from the module cache or loaded from the jzkt preimages on demand
     Hash(F254),
+    /// Code hash is resolved from the account when the runtime is created
+    Address(Address),
 }
 
 impl Default for BytecodeOrHash {
@@ -67,7 +123,7 @@ impl BytecodeOrHash {
                 let hash = F254::from(poseidon_hash(&bytecode));
                 BytecodeOrHash::Bytecode(bytecode, Some(hash))
             }
-            BytecodeOrHash::Hash(_) => self,
+            BytecodeOrHash::Hash(_) | BytecodeOrHash::Address(_) => self,
         }
     }
 
@@ -75,20 +131,57 @@ impl BytecodeOrHash {
         match self {
             BytecodeOrHash::Bytecode(_, hash) => hash.expect("poseidon hash must be resolved"),
             BytecodeOrHash::Hash(hash) => *hash,
+            BytecodeOrHash::Address(_) => panic!("account code hash must be resolved"),
         }
     }
 }
 
+#[derive(Clone)]
 pub struct RuntimeContext<DB: IJournaledTrie> {
     // context inputs
     pub(crate) bytecode: BytecodeOrHash,
-    pub(crate) fuel_limit: u64,
+    pub(crate) fuel_limit: Fuel,
     pub(crate) state: u32,
-    #[deprecated(note = "this parameter can be removed, we filter on the AOT level")]
-    pub(crate) is_shared: bool,
+    pub(crate) capabilities: Capabilities,
+    pub(crate) effective_capabilities: Capabilities,
+    pub(crate) escalation_policy: Option<EscalationPolicy>,
+    pub(crate) is_static: bool,
     pub(crate) input: Vec<u8>,
     pub(crate) context: Vec<u8>,
     pub(crate) depth: u32,
+    pub(crate) call_stack: CallStack,
+    pub(crate) evm_interpreter: Option<F254>,
+    pub(crate) access_list_recorder: Option<AccessListRecorder>,
+    pub(crate) record_state_diff: bool,
+    pub(crate) fuel_profiler: Option<FuelProfiler>,
+    pub(crate) coverage_collector: Option<CoverageCollector>,
+    pub(crate) debug_assertions: bool,
+    pub(crate) bytecode_policy: Option<BytecodePolicy>,
+    pub(crate) metadata_verifier: Option<MetadataVerifier>,
+    pub(crate) trace_writer: Option<TraceWriter>,
+    pub(crate) output_stream: Option<OutputStream>,
+    pub(crate) sealed_inputs: Option<SealedInputs>,
+    pub(crate) precompiles: Option<PrecompileRegistry>,
+    pub(crate) system_contracts: Option<SystemContracts>,
+    pub(crate) signature_cache: Option<SignatureCache>,
+    pub(crate) artifact_store: Option<ModuleArtifactStore>,
+    pub(crate) stack_limits: RuntimeStackLimits,
+    pub(crate) arena: BufferArena,
+    // incremental hashing contexts indexed by the handle, finalized contexts are `None`
+    pub(crate) hashers: Vec<Option<Keccak256>>,
+    pub(crate) memory_init_mode: MemoryInitMode,
+    pub(crate) memory_poison: Option<u8>,
+    pub(crate) is_memory_initialized: bool,
+    pub(crate) blob_hashes: Vec<B256>,
+    pub(crate) blob_base_fee: U256,
+    pub(crate) gas_fees: RuntimeGasFees,
+    pub(crate) fuel_schedule: FuelSchedule,
+    pub(crate) fuel_policy: FuelPolicy,
+    pub(crate) intrinsic_cost: IntrinsicCost,
+    pub(crate) breakpoints: Vec<SysFuncIdx>,
+    // exported function invoked by the call, `main` if not set
+    pub(crate) entrypoint: Option<String>,
+    pub(crate) code_size_limits: CodeSizeLimits,
     // context outputs
     pub(crate) execution_result: ExecutionResult,
     // storage
@@ -105,12 +198,46 @@ impl<DB: IJournaledTrie> Default for RuntimeContext<DB> {
     fn default() -> Self {
         Self {
             bytecode: Default::default(),
-            fuel_limit: 0,
+            fuel_limit: Fuel::ZERO,
             state: 0,
-            is_shared: false,
+            capabilities: Capabilities::ALL,
+            effective_capabilities: Capabilities::ALL,
+            escalation_policy: None,
+            is_static: false,
             input: vec![],
             context: vec![],
             depth: 0,
+            call_stack: Default::default(),
+            evm_interpreter: None,
+            access_list_recorder: None,
+            record_state_diff: false,
+            fuel_profiler: None,
+            coverage_collector: None,
+            debug_assertions: false,
+            bytecode_policy: None,
+            metadata_verifier: None,
+            trace_writer: None,
+            output_stream: None,
+            sealed_inputs: None,
+            precompiles: None,
+            system_contracts: None,
+            signature_cache: None,
+            artifact_store: None,
+            stack_limits: Default::default(),
+            arena: Default::default(),
+            hashers: vec![],
+            memory_init_mode: Default::default(),
+            memory_poison: None,
+            is_memory_initialized: false,
+            blob_hashes: vec![],
+            blob_base_fee: U256::ZERO,
+            gas_fees: Default::default(),
+            fuel_schedule: Default::default(),
+            fuel_policy: Default::default(),
+            intrinsic_cost: IntrinsicCost::ZERO,
+            breakpoints: vec![],
+            entrypoint: None,
+            code_size_limits: CodeSizeLimits::DEFAULT,
             execution_result: Default::default(),
             jzkt: None,
         }
@@ -132,11 +259,82 @@ impl<DB: IJournaledTrie> RuntimeContext<DB> {
         }
     }
 
+    /// Executes code of the account, the code hash is read from the jzkt and the bytecode is
+    /// loaded only if the module isn't cached yet, so batch executors don't have to load code
+    /// of every contract up front. Accounts with only EVM code are executed by the EVM
+    /// interpreter and accounts without code succeed with empty output.
+    pub fn new_with_address(address: Address) -> Self {
+        Self {
+            bytecode: BytecodeOrHash::Address(address),
+            ..Default::default()
+        }
+    }
+
+    /// Resolves the code hash of the account (if the bytecode is addressed by the account) and
+    /// the poseidon hash of the bytecode
+    pub(crate) fn resolve_bytecode(&mut self) {
+        if let BytecodeOrHash::Address(address) = self.bytecode {
+            self.bytecode = match SyscallExecAddress::resolve_code_hash(self, &address) {
+                Some(code_hash) => BytecodeOrHash::Hash(F254::from(code_hash)),
+                None => BytecodeOrHash::default(),
+            };
+        }
+        self.bytecode = take(&mut self.bytecode).with_resolved_hash();
+    }
+
     pub fn with_input(mut self, input_data: Vec<u8>) -> Self {
         self.input = input_data;
         self
     }
 
+    /// Sets the plain input of the envelope, sealed sections are revealed to authorized contracts
+    /// by the `_input_reveal` syscall with the decryptor
+    pub fn with_input_envelope(
+        mut self,
+        envelope: InputEnvelope,
+        decryptor: Arc<dyn InputDecryptor>,
+    ) -> Self {
+        self.input = envelope.input;
+        self.sealed_inputs = Some(SealedInputs::new(envelope.sections, decryptor));
+        self
+    }
+
+    /// Calls to the addresses of the registry run the native precompiles instead of the account
+    /// code, nested calls inherit the registry
+    pub fn with_precompiles(mut self, precompiles: PrecompileRegistry) -> Self {
+        self.precompiles = Some(precompiles);
+        self
+    }
+
+    /// Sets the system contract registry the runtime resolves system contracts with, nested calls
+    /// inherit the registry
+    pub fn with_system_contracts(mut self, system_contracts: SystemContracts) -> Self {
+        self.system_contracts = Some(system_contracts);
+        self
+    }
+
+    /// Returns rWASM code hash of the system contract version active at the specified block,
+    /// `None` if the address isn't a system contract or the registry isn't set
+    pub fn system_contract_code_hash(&self, address: &Address, block_number: u64) -> Option<F254> {
+        self.system_contracts
+            .as_ref()?
+            .active_code_hash(address, block_number)
+    }
+
+    pub fn is_system_contract(&self, address: &Address) -> bool {
+        self.system_contracts
+            .as_ref()
+            .is_some_and(|system_contracts| system_contracts.is_system_contract(address))
+    }
+
+    /// Precompile of the account the bytecode is addressed by
+    pub(crate) fn resolve_precompile(&self) -> Option<Arc<dyn Precompile>> {
+        match &self.bytecode {
+            BytecodeOrHash::Address(address) => self.precompiles.as_ref()?.get(address),
+            _ => None,
+        }
+    }
+
     pub fn with_context(mut self, context: Vec<u8>) -> Self {
         self.context = context;
         self
@@ -150,18 +348,44 @@ impl<DB: IJournaledTrie> RuntimeContext<DB> {
         self.context = new_context;
     }
 
+    pub fn change_is_static(&mut self, is_static: bool) {
+        self.is_static = is_static;
+    }
+
     pub fn with_state(mut self, state: u32) -> Self {
         self.state = state;
         self
     }
 
-    pub fn with_is_shared(mut self, is_shared: bool) -> Self {
-        self.is_shared = is_shared;
+    /// Shared execution has no sovereign capabilities, sovereign execution has all of them
+    pub fn with_is_shared(self, is_shared: bool) -> Self {
+        self.with_capabilities(if is_shared {
+            Capabilities::NONE
+        } else {
+            Capabilities::ALL
+        })
+    }
+
+    /// Sovereign syscalls allowed for the execution and its nested calls
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Grants additional capabilities to the trusted bytecode, see [`EscalationPolicy`]
+    pub fn with_escalation_policy(mut self, escalation_policy: EscalationPolicy) -> Self {
+        self.escalation_policy = Some(escalation_policy);
+        self
+    }
+
+    /// Makes execution read-only, all state changes fail with `WriteProtection`
+    pub fn with_is_static(mut self, is_static: bool) -> Self {
+        self.is_static = is_static;
         self
     }
 
-    pub fn with_fuel_limit(mut self, fuel_limit: u64) -> Self {
-        self.fuel_limit = fuel_limit;
+    pub fn with_fuel_limit(mut self, fuel_limit: impl Into<Fuel>) -> Self {
+        self.fuel_limit = fuel_limit.into();
         self
     }
 
@@ -175,14 +399,232 @@ impl<DB: IJournaledTrie> RuntimeContext<DB> {
         self
     }
 
+    /// Sets max depth of the nested calls, calls exceeding it fail with `CallDepthOverflow`
+    pub fn with_call_stack_limit(mut self, limit: u32) -> Self {
+        self.call_stack = CallStack::new(limit);
+        self
+    }
+
+    /// Sets rWASM hash of the EVM interpreter, if bytecode is identified as EVM then it's executed
+    /// by the interpreter that shares the same journal, the interpreter reads EVM bytecode from the
+    /// account state, so the input must be encoded using the interpreter's ABI
+    pub fn with_evm_interpreter(mut self, evm_interpreter: F254) -> Self {
+        self.evm_interpreter = Some(evm_interpreter);
+        self
+    }
+
+    /// Enables recording of all accessed accounts and storage slots (including nested calls)
+    pub fn with_access_list_recorder(mut self, access_list_recorder: AccessListRecorder) -> Self {
+        self.access_list_recorder = Some(access_list_recorder);
+        self
+    }
+
+    /// Enables collection of the state diff of successful executions, storage keys are resolved
+    /// into slots with the access list recorder (a new one is attached if it's missing)
+    pub fn with_state_diff(mut self) -> Self {
+        self.record_state_diff = true;
+        self.access_list_recorder
+            .get_or_insert_with(AccessListRecorder::new);
+        self
+    }
+
+    /// Enables attribution of consumed fuel to guest functions, bytecode must be instrumented
+    /// with profiling probes
+    pub fn with_fuel_profiler(mut self, fuel_profiler: FuelProfiler) -> Self {
+        self.fuel_profiler = Some(fuel_profiler);
+        self
+    }
+
+    /// Enables collection of executed blocks, bytecode must be instrumented with coverage probes
+    pub fn with_coverage_collector(mut self, coverage_collector: CoverageCollector) -> Self {
+        self.coverage_collector = Some(coverage_collector);
+        self
+    }
+
+    /// Enables the assertion syscalls (`_assert_eq_mem`, `_expect_storage` and
+    /// `_expect_fuel_le`), they fail with `CapabilityDenied` otherwise. Nested calls inherit the
+    /// flag
+    pub fn with_debug_assertions(mut self, debug_assertions: bool) -> Self {
+        self.debug_assertions = debug_assertions;
+        self
+    }
+
+    /// Validates bytecode against the policy before instantiation (including nested calls and
+    /// bytecode returned by the deployment), cached modules are validated once per policy
+    pub fn with_bytecode_policy(mut self, bytecode_policy: BytecodePolicy) -> Self {
+        self.bytecode_policy = Some(bytecode_policy);
+        self
+    }
+
+    /// Registers a hook for the metadata section of the bytecode, it's called after the host ABI
+    /// version check on every call of bytecode with metadata (including cached modules)
+    pub fn with_metadata_verifier<F>(mut self, metadata_verifier: F) -> Self
+    where
+        F: Fn(&ContractMetadata) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.metadata_verifier = Some(Arc::new(metadata_verifier));
+        self
+    }
+
+    /// Checks the host ABI version required by the metadata section and runs the metadata
+    /// verifier of the context
+    pub(crate) fn verify_metadata(
+        &self,
+        metadata: Option<&ContractMetadata>,
+    ) -> Result<(), RuntimeError> {
+        let Some(metadata) = metadata else {
+            return Ok(());
+        };
+        metadata
+            .check_host_abi(HOST_ABI_VERSION)
+            .map_err(RuntimeError::Metadata)?;
+        if let Some(metadata_verifier) = &self.metadata_verifier {
+            metadata_verifier(metadata)
+                .map_err(|message| RuntimeError::Metadata(MetadataError::Rejected(message)))?;
+        }
+        Ok(())
+    }
+
+    /// Verifies the metadata section (if any) and validates the code against the bytecode policy
+    pub(crate) fn verify_bytecode(&self, rwasm_bytecode: &[u8]) -> Result<(), RuntimeError> {
+        let (rwasm_bytecode, metadata) =
+            split_metadata(rwasm_bytecode).map_err(RuntimeError::Metadata)?;
+        self.verify_metadata(metadata.as_ref())?;
+        match &self.bytecode_policy {
+            Some(bytecode_policy) => bytecode_policy
+                .validate_rwasm(rwasm_bytecode)
+                .map_err(RuntimeError::PolicyViolation),
+            None => Ok(()),
+        }
+    }
+
+    /// Sets limits of the value stack and recursion depth, nested calls inherit the limits
+    pub fn with_stack_limits(mut self, stack_limits: RuntimeStackLimits) -> Self {
+        self.stack_limits = stack_limits;
+        self
+    }
+
+    /// Sets EIP-4844 versioned hashes of the transaction blobs, nested calls inherit the hashes
+    pub fn with_blob_hashes(mut self, blob_hashes: Vec<B256>) -> Self {
+        self.blob_hashes = blob_hashes;
+        self
+    }
+
+    /// Sets EIP-4844 blob base fee of the current block
+    pub fn with_blob_base_fee(mut self, blob_base_fee: U256) -> Self {
+        self.blob_base_fee = blob_base_fee;
+        self
+    }
+
+    /// Sets base fee of the current block and fee caps of the transaction, nested calls inherit
+    /// the fees
+    pub fn with_gas_fees(mut self, gas_fees: RuntimeGasFees) -> Self {
+        self.gas_fees = gas_fees;
+        self
+    }
+
+    /// Sets exchange rate used to report consumed fuel as gas, nested calls inherit the schedule
+    pub fn with_fuel_schedule(mut self, fuel_schedule: FuelSchedule) -> Self {
+        self.fuel_schedule = fuel_schedule;
+        self
+    }
+
+    /// Sets extra fuel charged for the host calls, nested calls inherit the policy
+    pub fn with_fuel_policy(mut self, fuel_policy: FuelPolicy) -> Self {
+        self.fuel_policy = fuel_policy;
+        self
+    }
+
+    /// Sets fuel charged for the input before the execution and for the deployed code after the
+    /// deployment, it's applied to the top-level call only (nested calls aren't charged)
+    pub fn with_intrinsic_cost(mut self, intrinsic_cost: IntrinsicCost) -> Self {
+        self.intrinsic_cost = intrinsic_cost;
+        self
+    }
+
+    /// Sets size limits of the deployed code and the init code, it's applied to the deployment
+    pub fn with_code_size_limits(mut self, code_size_limits: CodeSizeLimits) -> Self {
+        self.code_size_limits = code_size_limits;
+        self
+    }
+
+    /// Pauses resumable calls after every call of the syscall, see [`Runtime::call_resumable`]
+    pub fn with_breakpoint(mut self, sys_func_idx: SysFuncIdx) -> Self {
+        self.breakpoints.push(sys_func_idx);
+        self
+    }
+
+    /// Invokes the exported function instead of `main`, it lets tests exercise contracts with
+    /// several entrypoints. Nested calls always invoke `main`.
+    pub fn with_entrypoint<S: Into<String>>(mut self, entrypoint: S) -> Self {
+        self.entrypoint = Some(entrypoint.into());
+        self
+    }
+
+    pub fn entrypoint(&self) -> &str {
+        self.entrypoint.as_deref().unwrap_or("main")
+    }
+
+    /// Sets arena for the call buffers, the arena is reset before the execution
+    pub fn with_arena(mut self, arena: BufferArena) -> Self {
+        self.arena = arena;
+        self
+    }
+
+    /// Enables checks of the linear memory initialization, nested calls inherit the mode
+    pub fn with_memory_init_mode(mut self, memory_init_mode: MemoryInitMode) -> Self {
+        self.memory_init_mode = memory_init_mode;
+        self
+    }
+
+    /// Enables writing of the circuit-friendly trace (host calls and storage side effects), the
+    /// sink of the writer (if any) is flushed after the top-level call
+    pub fn with_trace_writer(mut self, trace_writer: TraceWriter) -> Self {
+        self.trace_writer = Some(trace_writer);
+        self
+    }
+
+    /// Streams chunks written by `_write_output_chunk` to the host sink instead of the output,
+    /// nested calls don't inherit the stream (their output is the return data of the caller)
+    pub fn with_output_stream(mut self, output_stream: OutputStream) -> Self {
+        self.output_stream = Some(output_stream);
+        self
+    }
+
+    /// Takes results of the `_ecrecover` syscall from the cache filled ahead of the execution,
+    /// nested calls share the cache
+    pub fn with_signature_cache(mut self, signature_cache: SignatureCache) -> Self {
+        self.signature_cache = Some(signature_cache);
+        self
+    }
+
+    /// Code addressed by the hash is loaded from the artifact store before the trie, rWASM code
+    /// loaded from the trie is persisted in the store, nested calls share the store
+    pub fn with_artifact_store(mut self, artifact_store: ModuleArtifactStore) -> Self {
+        self.artifact_store = Some(artifact_store);
+        self
+    }
+
     pub fn jzkt(&mut self) -> &DB {
         self.jzkt.as_ref().expect("jzkt is not initialized")
     }
 
+    pub fn arena(&self) -> &BufferArena {
+        &self.arena
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.is_static
+    }
+
     pub fn depth(&self) -> u32 {
         self.depth
     }
 
+    pub fn call_stack(&self) -> &CallStack {
+        &self.call_stack
+    }
+
     pub fn exit_code(&self) -> i32 {
         self.execution_result.exit_code
     }
@@ -211,6 +653,10 @@ impl<DB: IJournaledTrie> RuntimeContext<DB> {
         &self.execution_result.return_data
     }
 
+    pub fn assertion_failure(&self) -> Option<&AssertionFailure> {
+        self.execution_result.assertion_failure.as_ref()
+    }
+
     pub fn state(&self) -> u32 {
         self.state
     }
@@ -220,12 +666,20 @@ impl<DB: IJournaledTrie> RuntimeContext<DB> {
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExecutionResult {
     pub exit_code: i32,
     pub output: Vec<u8>,
-    pub fuel_consumed: u64,
+    pub fuel_consumed: Fuel,
     pub return_data: Vec<u8>,
+    pub logs: Vec<JournalLog>,
+    pub state_diff: Option<StateDiff>,
+    /// Keccak256 hash of the execution trace, the trace itself is too big to be transferred
+    /// with the result, so it's stored separately and addressed by the hash
+    pub trace_hash: Option<B256>,
+    /// Diagnostics of the failed in-guest assertion (the exit code is `AssertionFailed`)
+    pub assertion_failure: Option<AssertionFailure>,
 }
 
 impl ExecutionResult {
@@ -235,21 +689,209 @@ impl ExecutionResult {
             ..Default::default()
         }
     }
+
+    /// Execution was reverted by the contract, output contains revert data
+    pub fn is_deliberate_revert(&self) -> bool {
+        ExitCode::from(self.exit_code).is_deliberate_revert()
+    }
+
+    /// Execution failed unexpectedly, output is empty unless it's a panic with a message
+    pub fn is_panic(&self) -> bool {
+        ExitCode::from(self.exit_code).is_panic()
+    }
+
+    /// Logs emitted during this execution (including nested calls), failed executions don't
+    /// have logs because they're reverted
+    pub fn logs(&self) -> &Vec<JournalLog> {
+        &self.logs
+    }
+
+    /// Accounts and storage slots changed by this execution (including nested calls), it's
+    /// collected only if enabled with [`RuntimeContext::with_state_diff`]
+    pub fn state_diff(&self) -> Option<&StateDiff> {
+        self.state_diff.as_ref()
+    }
+
+    /// Attaches reference to the trace produced by [`TraceWriter::to_bytes`]
+    pub fn with_trace(mut self, trace: &[u8]) -> Self {
+        self.trace_hash = Some(B256::from(SyscallKeccak256::fn_impl(trace)));
+        self
+    }
+
+    /// Host calls of the execution as JSON lines: the steps of the trace produced by
+    /// [`TraceWriter::to_bytes`] followed by the summary of the execution (see
+    /// [`trace_to_host_call_steps`]). The trace must match the hash attached by
+    /// [`Self::with_trace`] (if any), remaining fuel of the top-level steps is computed from the
+    /// fuel limit of the call
+    pub fn host_call_trace_json(
+        &self,
+        trace: &[u8],
+        fuel_limit: Fuel,
+    ) -> Result<Vec<serde_json::Value>, TraceFormatError> {
+        if let Some(trace_hash) = self.trace_hash {
+            if B256::from(SyscallKeccak256::fn_impl(trace)) != trace_hash {
+                return Err(TraceFormatError::HashMismatch);
+            }
+        }
+        let mut result = trace_to_host_call_steps(trace, fuel_limit.get())?
+            .iter()
+            .map(|step| step.to_json())
+            .collect::<Vec<_>>();
+        let mut summary = serde_json::json!({
+            "output": format!("0x{}", hex::encode(&self.output)),
+            "fuelUsed": format!("0x{:x}", self.fuel_consumed.get()),
+            "pass": self.exit_code == 0,
+        });
+        if self.exit_code != 0 {
+            summary["error"] = format!("{:?}", ExitCode::from(self.exit_code)).into();
+        }
+        result.push(summary);
+        Ok(result)
+    }
+}
+
+/// Default limits are the same as engine defaults
+pub const DEFAULT_INITIAL_VALUE_STACK_HEIGHT: usize = 1024;
+pub const DEFAULT_MAX_VALUE_STACK_HEIGHT: usize = 1024 * DEFAULT_INITIAL_VALUE_STACK_HEIGHT;
+pub const DEFAULT_MAX_RECURSION_DEPTH: usize = 1024;
+pub const DEFAULT_CACHED_STACKS: usize = 2;
+
+/// Limits of the engine value stack and call stack, exceeding of any limit finishes execution
+/// with `StackOverflow` exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RuntimeStackLimits {
+    initial_value_stack_height: usize,
+    max_value_stack_height: usize,
+    max_recursion_depth: usize,
+    cached_stacks: usize,
+}
+
+impl Default for RuntimeStackLimits {
+    fn default() -> Self {
+        Self {
+            initial_value_stack_height: DEFAULT_INITIAL_VALUE_STACK_HEIGHT,
+            max_value_stack_height: DEFAULT_MAX_VALUE_STACK_HEIGHT,
+            max_recursion_depth: DEFAULT_MAX_RECURSION_DEPTH,
+            cached_stacks: DEFAULT_CACHED_STACKS,
+        }
+    }
+}
+
+impl RuntimeStackLimits {
+    pub fn new(
+        initial_value_stack_height: usize,
+        max_value_stack_height: usize,
+        max_recursion_depth: usize,
+    ) -> Self {
+        assert!(
+            initial_value_stack_height <= max_value_stack_height,
+            "initial value stack height can't be greater than max height"
+        );
+        Self {
+            initial_value_stack_height,
+            max_value_stack_height,
+            max_recursion_depth,
+            cached_stacks: DEFAULT_CACHED_STACKS,
+        }
+    }
+
+    /// Number of value and call stacks the engine keeps after executions to reuse them instead
+    /// of allocating new ones. Engines are cached per limits (and per module), so high-throughput
+    /// call loops should set it to the max expected depth of nested calls of the same contract.
+    pub fn with_cached_stacks(mut self, cached_stacks: usize) -> Self {
+        self.cached_stacks = cached_stacks;
+        self
+    }
+
+    pub fn cached_stacks(&self) -> usize {
+        self.cached_stacks
+    }
+
+    pub fn initial_value_stack_height(&self) -> usize {
+        self.initial_value_stack_height
+    }
+
+    pub fn max_value_stack_height(&self) -> usize {
+        self.max_value_stack_height
+    }
+
+    pub fn max_recursion_depth(&self) -> usize {
+        self.max_recursion_depth
+    }
+}
+
+/// EIP-1559 fees of the transaction, for legacy transactions the max fee is a gas price and
+/// there is no priority fee
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RuntimeGasFees {
+    base_fee: U256,
+    max_fee_per_gas: U256,
+    max_priority_fee_per_gas: Option<U256>,
+}
+
+impl RuntimeGasFees {
+    pub fn new(
+        base_fee: U256,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: Option<U256>,
+    ) -> Self {
+        Self {
+            base_fee,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        }
+    }
+
+    pub fn base_fee(&self) -> U256 {
+        self.base_fee
+    }
+
+    pub fn max_fee_per_gas(&self) -> U256 {
+        self.max_fee_per_gas
+    }
+
+    pub fn max_priority_fee_per_gas(&self) -> Option<U256> {
+        self.max_priority_fee_per_gas
+    }
+
+    /// Price paid by the transaction for each unit of gas, it's `min(max_fee, base_fee +
+    /// priority_fee)` for EIP-1559 transactions
+    pub fn effective_gas_price(&self) -> U256 {
+        match self.max_priority_fee_per_gas {
+            Some(priority_fee) => self
+                .max_fee_per_gas
+                .min(self.base_fee.saturating_add(priority_fee)),
+            None => self.max_fee_per_gas,
+        }
+    }
 }
 
 pub struct CachingRuntime {
-    // TODO(dmitry123): "add expiration to this map to avoid memory leak"
-    modules: HashMap<F254, Module>,
+    modules: ModuleCache,
+    stack_limits: RuntimeStackLimits,
 }
 
 impl CachingRuntime {
     pub fn new() -> Self {
         Self {
-            modules: HashMap::new(),
+            modules: ModuleCache::default(),
+            stack_limits: RuntimeStackLimits::default(),
         }
     }
 
-    fn new_engine() -> Engine {
+    /// Max number of translated modules, the least recently used ones are evicted
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.modules.set_capacity(capacity);
+        self
+    }
+
+    /// Modules share engine of the caching runtime, so every set of limits needs its own cache
+    pub fn with_stack_limits(mut self, stack_limits: RuntimeStackLimits) -> Self {
+        self.stack_limits = stack_limits;
+        self
+    }
+
+    fn new_engine(&self) -> Engine {
         // we can safely use sovereign import linker because all protected are filtered out during
         // translation process
         let import_linker = Runtime::new_sovereign_linker();
@@ -266,10 +908,18 @@ impl CachingRuntime {
             import_linker: Some(import_linker),
             wrap_import_functions: true,
         });
+        let stack_limits = StackLimits::new(
+            self.stack_limits.initial_value_stack_height,
+            self.stack_limits.max_value_stack_height,
+            self.stack_limits.max_recursion_depth,
+        )
+        .expect("stack limits are validated on creation");
         config
             .floats(false)
             .fuel_consumption_mode(FuelConsumptionMode::Eager)
-            .consume_fuel(true);
+            .consume_fuel(true)
+            .set_stack_limits(stack_limits)
+            .set_cached_stacks(self.stack_limits.cached_stacks);
         Engine::new(&config)
     }
 
@@ -279,10 +929,12 @@ impl CachingRuntime {
         rwasm_hash: F254,
         rwasm_bytecode: &[u8],
     ) -> Result<&Module, RuntimeError> {
-        let entry = match self.modules.entry(rwasm_hash) {
-            Entry::Occupied(_) => return Err(RuntimeError::UnloadedModule(rwasm_hash)),
-            Entry::Vacant(entry) => entry,
-        };
+        if self.modules.contains(&rwasm_hash) {
+            return Err(RuntimeError::UnloadedModule(rwasm_hash));
+        }
+        // metadata section isn't a part of the executable code
+        let (rwasm_bytecode, metadata) =
+            split_metadata(rwasm_bytecode).map_err(RuntimeError::Metadata)?;
         // empty bytecode we can't execute so just return Ok exit code
         let reduced_module = if !rwasm_bytecode.is_empty() {
             RwasmModule::new(rwasm_bytecode).map_err(Into::<RuntimeError>::into)?
@@ -291,24 +943,184 @@ impl CachingRuntime {
                 Return(DropKeep::none())
             })
         };
+        #[cfg(feature = "metrics")]
+        let time = std::time::Instant::now();
         // let engine = Self::new_engine();
         let module_builder = reduced_module.to_module_builder(engine);
         let module = module_builder.finish();
-        Ok(entry.insert(module))
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_translation(time.elapsed());
+        Ok(self
+            .modules
+            .insert_with_metadata(rwasm_hash, module, metadata))
     }
 
     pub fn resolve_module(&self, rwasm_hash: &F254) -> Option<&Module> {
         self.modules.get(rwasm_hash)
     }
+
+    /// Returns metadata section of the cached module's bytecode (if any)
+    pub fn resolve_metadata(&self, rwasm_hash: &F254) -> Option<&ContractMetadata> {
+        self.modules.metadata(rwasm_hash)
+    }
+
+    /// Returns the memoized verdict of the policy for the cached module
+    pub fn policy_verdict(
+        &self,
+        rwasm_hash: &F254,
+        bytecode_policy: &BytecodePolicy,
+    ) -> Option<PolicyVerdict> {
+        self.modules.policy_verdict(rwasm_hash, bytecode_policy)
+    }
+
+    /// Memoizes the verdict of the policy for the cached module
+    pub fn record_policy_verdict(
+        &self,
+        rwasm_hash: &F254,
+        bytecode_policy: &BytecodePolicy,
+        verdict: PolicyVerdict,
+    ) {
+        self.modules
+            .record_policy_verdict(rwasm_hash, bytecode_policy, verdict)
+    }
+
+    /// Removes the translated module, returns `false` if it wasn't cached
+    pub fn invalidate_module(&mut self, rwasm_hash: &F254) -> bool {
+        self.modules.remove(rwasm_hash)
+    }
 }
 
 thread_local! {
-    static CACHING_RUNTIME: RefCell<CachingRuntime> = RefCell::new(CachingRuntime::new());
+    static CACHING_RUNTIMES: RefCell<HashMap<RuntimeStackLimits, CachingRuntime>> =
+        RefCell::new(HashMap::new());
+    static MODULE_CACHE_CAPACITY: Cell<usize> = const { Cell::new(DEFAULT_MODULE_CACHE_CAPACITY) };
+}
+
+fn with_caching_runtime<R, F: FnOnce(&mut CachingRuntime) -> R>(
+    stack_limits: RuntimeStackLimits,
+    func: F,
+) -> R {
+    CACHING_RUNTIMES.with_borrow_mut(|caching_runtimes| {
+        let caching_runtime = caching_runtimes.entry(stack_limits).or_insert_with(|| {
+            CachingRuntime::new()
+                .with_stack_limits(stack_limits)
+                .with_capacity(MODULE_CACHE_CAPACITY.get())
+        });
+        func(caching_runtime)
+    })
+}
+
+/// Result of the modules warm-up
+#[derive(Debug, Default)]
+pub struct WarmUpReport {
+    /// Modules translated during the warm-up
+    pub translated: Vec<F254>,
+    /// Modules that were already cached
+    pub cached: Vec<F254>,
+    pub failed: Vec<(F254, RuntimeError)>,
+}
+
+/// Translates and caches rWASM modules (system and genesis contracts) ahead of time, so the first
+/// execution of these modules doesn't pay translation latency.
+///
+/// Modules are cached per thread and per stack limits, so the warm-up must be done on every
+/// thread that executes transactions with the limits used by these executions.
+pub fn warm_up_modules<I, B>(stack_limits: RuntimeStackLimits, rwasm_bytecodes: I) -> WarmUpReport
+where
+    I: IntoIterator<Item = B>,
+    B: AsRef<[u8]>,
+{
+    with_caching_runtime(stack_limits, |caching_runtime| {
+        let mut report = WarmUpReport::default();
+        for rwasm_bytecode in rwasm_bytecodes {
+            let rwasm_bytecode = rwasm_bytecode.as_ref();
+            let rwasm_hash = F254::from(poseidon_hash(rwasm_bytecode));
+            if caching_runtime.resolve_module(&rwasm_hash).is_some() {
+                report.cached.push(rwasm_hash);
+                continue;
+            }
+            // every module has its own engine (the same as for the modules translated on demand)
+            let engine = caching_runtime.new_engine();
+            match caching_runtime.init_module(&engine, rwasm_hash, rwasm_bytecode) {
+                Ok(_) => report.translated.push(rwasm_hash),
+                Err(err) => report.failed.push((rwasm_hash, err)),
+            }
+        }
+        report
+    })
+}
+
+/// Removes the translated module from the caches of the current thread (for all stack limits),
+/// returns `false` if the module wasn't cached. The next execution of the module translates it
+/// again.
+pub fn invalidate_module(rwasm_hash: &F254) -> bool {
+    CACHING_RUNTIMES.with_borrow_mut(|caching_runtimes| {
+        caching_runtimes
+            .values_mut()
+            .fold(false, |is_invalidated, caching_runtime| {
+                caching_runtime.invalidate_module(rwasm_hash) || is_invalidated
+            })
+    })
+}
+
+/// Removes all translated modules from the caches of the current thread
+pub fn clear_module_caches() {
+    CACHING_RUNTIMES.with_borrow_mut(|caching_runtimes| caching_runtimes.clear());
+}
+
+/// Limits the number of translated modules cached by the current thread per stack limits, the
+/// least recently used modules are evicted
+pub fn set_module_cache_capacity(capacity: usize) {
+    MODULE_CACHE_CAPACITY.set(capacity);
+    CACHING_RUNTIMES.with_borrow_mut(|caching_runtimes| {
+        caching_runtimes
+            .values_mut()
+            .for_each(|caching_runtime| caching_runtime.modules.set_capacity(capacity))
+    });
 }
 
 pub struct Runtime<DB: IJournaledTrie> {
     pub(crate) store: Store<RuntimeContext<DB>>,
     pub(crate) linker: Linker<RuntimeContext<DB>>,
+    // instance of the last call, we keep it to let read memory after the execution
+    pub(crate) instance: Option<Instance>,
+    pub(crate) paused: Option<PausedInvocation>,
+    pub(crate) call_mode: CallMode,
+    // arguments and results of the entrypoint, `main` has none
+    pub(crate) call_params: Vec<Value>,
+    pub(crate) call_results: Vec<Value>,
+}
+
+/// How the top-level call can be paused, the mode is reset once the call is finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum CallMode {
+    #[default]
+    Regular,
+    /// Paused by the `_suspend` syscall (see [`Runtime::call_interruptible`])
+    Interruptible,
+    /// Paused after host calls with breakpoints (see [`Runtime::call_resumable`])
+    Resumable,
+}
+
+/// Reason of the paused execution
+pub(crate) enum PauseReason {
+    /// The `_suspend` syscall, the execution is resumed with the embedder's response
+    Suspend(SysSuspendResumable),
+    /// Host call with a breakpoint, the execution is resumed with the host results
+    Breakpoint(SysBreakpointResumable),
+}
+
+/// Top-level execution paused in the middle of the call
+pub(crate) struct PausedInvocation {
+    pub(crate) invocation: ResumableInvocation,
+    pub(crate) checkpoint: Option<JournalCheckpoint>,
+    pub(crate) reason: PauseReason,
+}
+
+/// Step of the pausable execution
+pub(crate) enum CallStep {
+    Finished(ExecutionResult),
+    Paused,
 }
 
 impl Runtime<EmptyJournalTrie> {
@@ -322,7 +1134,8 @@ impl Runtime<EmptyJournalTrie> {
     pub fn catch_trap(err: &RuntimeError) -> i32 {
         let err = match err {
             RuntimeError::Rwasm(err) => err,
-            _ => return ExitCode::UnknownError as i32,
+            RuntimeError::ExecutionFailed(exit_code) => return *exit_code,
+            err => return err.exit_code().into_i32(),
         };
         let err = match err {
             rwasm::Error::Trap(err) => err,
@@ -348,20 +1161,31 @@ impl<DB: IJournaledTrie> Runtime<DB> {
     pub fn run_with_context(
         runtime_context: RuntimeContext<DB>,
     ) -> Result<ExecutionResult, RuntimeError> {
+        // precompiles bypass the module instantiation
+        if let Some(precompile) = runtime_context.resolve_precompile() {
+            return Ok(execute_precompile(
+                precompile.as_ref(),
+                &runtime_context.input,
+                runtime_context.fuel_limit,
+            ));
+        }
         Self::new(runtime_context).call()
     }
 
     pub fn new(mut runtime_context: RuntimeContext<DB>) -> Self {
         // make sure bytecode hash is resolved
-        runtime_context.bytecode = runtime_context.bytecode.with_resolved_hash();
+        runtime_context.resolve_bytecode();
 
         // use existing engine or create a new one
-        let engine = CACHING_RUNTIME.with_borrow_mut(|caching_runtime| {
-            let rwasm_hash = runtime_context.bytecode.resolve_hash();
-            caching_runtime
+        let engine = with_caching_runtime(runtime_context.stack_limits, |caching_runtime| {
+            let rwasm_hash = Self::resolve_module_hash(&runtime_context, caching_runtime);
+            let engine = caching_runtime
                 .resolve_module(&rwasm_hash)
-                .map(|module| module.engine.clone())
-                .unwrap_or_else(|| CachingRuntime::new_engine())
+                .map(|module| module.engine.clone());
+            // engine keeps cached stacks, so a reused engine doesn't allocate them
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_engine_resolution(engine.is_some());
+            engine.unwrap_or_else(|| caching_runtime.new_engine())
         });
 
         // create new linker and store (it shares same engine resources)
@@ -369,22 +1193,157 @@ impl<DB: IJournaledTrie> Runtime<DB> {
         let mut linker = Linker::<RuntimeContext<DB>>::new(&engine);
 
         // add fuel if limit is specified
-        if store.data().fuel_limit > 0 {
-            store.add_fuel(store.data().fuel_limit).unwrap();
+        if !store.data().fuel_limit.is_zero() {
+            store.add_fuel(store.data().fuel_limit.get()).unwrap();
         }
 
-        // register linker trampolines for external calls
-        if store.data().is_shared {
-            runtime_register_shared_handlers(&mut linker, &mut store)
-        } else {
-            runtime_register_sovereign_handlers(&mut linker, &mut store)
+        // trusted bytecode can be granted extra capabilities by the escalation policy
+        let effective_capabilities = match store.data().escalation_policy.as_ref() {
+            Some(escalation_policy) => store
+                .data()
+                .capabilities
+                .union(escalation_policy.capabilities_for(&store.data().bytecode.resolve_hash())),
+            None => store.data().capabilities,
+        };
+        store.data_mut().effective_capabilities = effective_capabilities;
+
+        // register linker trampolines for external calls, sovereign syscalls are checked
+        // against the capabilities in the handlers
+        runtime_register_sovereign_handlers(&mut linker, &mut store);
+
+        Self {
+            store,
+            linker,
+            instance: None,
+            paused: None,
+            call_mode: CallMode::Regular,
+            call_params: vec![],
+            call_results: vec![],
+        }
+    }
+
+    /// The same as [`Runtime::call`], but invokes the exported function with the name, the
+    /// function must have no params and results (like `main`). The entrypoint of the context is
+    /// changed for this call only.
+    pub fn call_export(&mut self, name: &str) -> Result<ExecutionResult, RuntimeError> {
+        let entrypoint = replace(
+            &mut self.store.data_mut().entrypoint,
+            Some(name.to_string()),
+        );
+        let result = self.call();
+        self.store.data_mut().entrypoint = entrypoint;
+        result
+    }
+
+    /// Invokes the entrypoint (see [`RuntimeContext::with_entrypoint`]) with the arguments and
+    /// returns its results, it lets test harnesses call functions with i32/i64 params directly
+    /// instead of encoding them into the input. Failed execution returns
+    /// `RuntimeError::ExecutionFailed` with the exit code.
+    pub fn call_with_args(&mut self, args: &[Value]) -> Result<Vec<Value>, RuntimeError> {
+        self.call_params = args.to_vec();
+        let execution_result = self.call()?;
+        let results = take(&mut self.call_results);
+        if execution_result.exit_code != ExitCode::Ok.into_i32() {
+            return Err(RuntimeError::ExecutionFailed(execution_result.exit_code));
         }
+        Ok(results)
+    }
 
-        Self { store, linker }
+    /// Typed version of [`Runtime::call_with_args`], for example
+    /// `runtime.call_typed::<(i32, i64), i64>((1, 2))`
+    pub fn call_typed<P: CallArgs, R: CallResults>(&mut self, args: P) -> Result<R, RuntimeError> {
+        let results = self.call_with_args(&args.into_values())?;
+        R::from_values(&results).ok_or(RuntimeError::SignatureMismatch)
     }
 
     pub fn call(&mut self) -> Result<ExecutionResult, RuntimeError> {
-        let instance = CACHING_RUNTIME.with_borrow_mut(|caching_runtime| {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "runtime_call",
+            depth = self.store.data().depth,
+            state = self.store.data().state,
+            is_sovereign = self.store.data().effective_capabilities.is_sovereign(),
+            fuel_limit = self.store.data().fuel_limit.get(),
+        )
+        .entered();
+        // nested calls share the arena of the top-level call
+        if self.store.data().depth == 0 {
+            self.store.data().arena.reset();
+        }
+        // the regular call can't be paused, even if the previous call is left paused
+        self.call_mode = CallMode::Regular;
+        self.paused = None;
+        let result = self.call_inner().and_then(|step| match step {
+            CallStep::Finished(execution_result) => Ok(execution_result),
+            CallStep::Paused => Err(RuntimeError::UnexpectedPause),
+        });
+        self.finish_call(result)
+    }
+
+    pub(crate) fn finish_call(
+        &mut self,
+        result: Result<ExecutionResult, RuntimeError>,
+    ) -> Result<ExecutionResult, RuntimeError> {
+        // the next calls of the runtime can be paused only if they ask for it
+        self.call_mode = CallMode::Regular;
+        let result = result.map(|mut execution_result| {
+            // output of the trapped execution is a garbage, only reverts and panics return data
+            if ExitCode::from(execution_result.exit_code).is_trap() {
+                execution_result.output.clear();
+            }
+            execution_result
+        });
+        // trace sink errors are sticky, the embedder gets them from `TraceWriter::flush`
+        if self.store.data().depth == 0 {
+            if let Some(trace_writer) = self.store.data().trace_writer.as_ref() {
+                let _ = trace_writer.flush();
+            }
+        }
+        #[cfg(feature = "metrics")]
+        if let Ok(execution_result) = &result {
+            crate::metrics::record_execution(
+                execution_result.exit_code,
+                execution_result.fuel_consumed.get(),
+            );
+        }
+        #[cfg(feature = "tracing")]
+        match &result {
+            Ok(execution_result) => tracing::debug!(
+                exit_code = execution_result.exit_code,
+                fuel_consumed = execution_result.fuel_consumed.get(),
+                output_len = execution_result.output.len(),
+                "execution finished"
+            ),
+            Err(err) => tracing::warn!(error = ?err, "execution failed"),
+        }
+        result
+    }
+
+    pub(crate) fn call_inner(&mut self) -> Result<CallStep, RuntimeError> {
+        // input is charged before the instantiation, so the call fails fast if it can't pay
+        if self.store.data().depth == 0 {
+            let input_fuel = self
+                .store
+                .data()
+                .intrinsic_cost
+                .input_fuel(&self.store.data().input);
+            if !self.consume_intrinsic_fuel(input_fuel) {
+                let mut execution_result = self.store.data().execution_result.clone();
+                execution_result.exit_code = ExitCode::OutOfGas.into_i32();
+                execution_result.fuel_consumed = self.store.data().fuel_limit;
+                return Ok(CallStep::Finished(execution_result));
+            }
+        }
+        // remember logs offset to collect all logs emitted by this call
+        let checkpoint = self
+            .store
+            .data()
+            .jzkt
+            .as_ref()
+            .map(|jzkt| jzkt.checkpoint());
+
+        let stack_limits = self.store.data().stack_limits;
+        let instance = with_caching_runtime(stack_limits, |caching_runtime| {
             let bytecode_repr = take(&mut self.store.data_mut().bytecode);
 
             // resolve cached module or init it
@@ -392,28 +1351,44 @@ impl<DB: IJournaledTrie> Runtime<DB> {
                 BytecodeOrHash::Bytecode(bytecode, hash) => {
                     let hash = hash.unwrap_or_else(|| F254::from(poseidon_hash(&bytecode)));
                     // if we have cached module then use it, otherwise create new one and cache
-                    if let Some(module) = caching_runtime.resolve_module(&hash) {
-                        Ok(module)
+                    if caching_runtime.resolve_module(&hash).is_some() {
+                        // translation is cached, but the metadata and the policy are verified
+                        // by every context
+                        self.verify_cached_module(caching_runtime, &hash, Some(bytecode))?;
+                        Ok(caching_runtime.resolve_module(&hash).unwrap())
+                    } else if Self::is_evm_bytecode(bytecode) {
+                        self.resolve_evm_interpreter(caching_runtime)
                     } else {
-                        caching_runtime.init_module(self.store.engine(), hash, &bytecode)
+                        self.validate_bytecode(bytecode)?;
+                        caching_runtime.init_module(self.store.engine(), hash, &bytecode)?;
+                        self.record_policy_verdict(caching_runtime, &hash);
+                        Ok(caching_runtime.resolve_module(&hash).unwrap())
                     }
                 }
                 BytecodeOrHash::Hash(hash) => {
                     // if we have only hash then try to load module or fail fast
-                    match caching_runtime.resolve_module(hash) {
-                        Some(module) => Ok(module),
-                        None => {
-                            let rwasm_bytecode = self
-                                .store
-                                .data_mut()
-                                .jzkt
-                                .as_ref()
-                                .ok_or(RuntimeError::UnloadedModule(*hash))?
-                                .preimage(hash);
-                            caching_runtime.init_module(self.store.engine(), *hash, &rwasm_bytecode)
+                    if caching_runtime.resolve_module(hash).is_some() {
+                        self.verify_cached_module(caching_runtime, hash, None)?;
+                        Ok(caching_runtime.resolve_module(hash).unwrap())
+                    } else {
+                        let rwasm_bytecode = self.load_rwasm_bytecode(hash)?;
+                        if Self::is_evm_bytecode(&rwasm_bytecode) {
+                            self.resolve_evm_interpreter(caching_runtime)
+                        } else {
+                            self.validate_bytecode(&rwasm_bytecode)?;
+                            caching_runtime.init_module(
+                                self.store.engine(),
+                                *hash,
+                                &rwasm_bytecode,
+                            )?;
+                            self.record_policy_verdict(caching_runtime, hash);
+                            Ok(caching_runtime.resolve_module(hash).unwrap())
                         }
                     }
                 }
+                BytecodeOrHash::Address(_) => {
+                    unreachable!("account code hash is resolved on creation")
+                }
             }?;
 
             // return bytecode back
@@ -429,20 +1404,61 @@ impl<DB: IJournaledTrie> Runtime<DB> {
 
             Ok::<Instance, RuntimeError>(instance)
         })?;
+        self.instance = Some(instance);
+        self.check_fresh_memory()?;
 
-        let mut next_result = instance
-            .get_func(&mut self.store, "main")
-            .ok_or(RuntimeError::MissingEntrypoint)?
-            .call_resumable(&mut self.store, &[], &mut [])
+        let entrypoint = self.store.data().entrypoint().to_string();
+        let func = instance
+            .get_func(&mut self.store, &entrypoint)
+            .ok_or(RuntimeError::MissingEntrypoint(entrypoint))?;
+        let params = take(&mut self.call_params);
+        self.call_results = func
+            .ty(&self.store)
+            .results()
+            .iter()
+            .map(|value_type| Value::default(*value_type))
+            .collect();
+        let next_result = func
+            .call_resumable(&mut self.store, &params, &mut self.call_results)
             .map_err(Into::<RuntimeError>::into);
+        self.drive(checkpoint, next_result)
+    }
+
+    /// Handles host calls that interrupt the execution until the execution is finished (or
+    /// paused if the mode of the call lets it), the checkpoint of the finished call is released
+    pub(crate) fn drive(
+        &mut self,
+        checkpoint: Option<JournalCheckpoint>,
+        next_result: Result<ResumableCall, RuntimeError>,
+    ) -> Result<CallStep, RuntimeError> {
+        let step = self.drive_until_paused(checkpoint, next_result);
+        // paused call keeps the checkpoint until it's resumed and finished
+        if !matches!(step, Ok(CallStep::Paused)) {
+            if let (Some(jzkt), Some(checkpoint)) = (self.store.data().jzkt.as_ref(), checkpoint) {
+                jzkt.release_checkpoint(checkpoint);
+            }
+        }
+        step
+    }
+
+    fn drive_until_paused(
+        &mut self,
+        checkpoint: Option<JournalCheckpoint>,
+        mut next_result: Result<ResumableCall, RuntimeError>,
+    ) -> Result<CallStep, RuntimeError> {
+        let instance = self.instance.ok_or_else(|| self.missing_entrypoint())?;
         loop {
             match next_result {
                 Ok(resumable) => match resumable {
                     ResumableCall::Finished => {
                         let mut execution_result = self.store.data().execution_result.clone();
                         execution_result.fuel_consumed =
-                            self.store.fuel_consumed().unwrap_or_default();
-                        return Ok(execution_result);
+                            Fuel(self.store.fuel_consumed().unwrap_or_default());
+                        if execution_result.exit_code == ExitCode::Ok.into_i32() {
+                            execution_result.logs = self.collect_logs(&checkpoint);
+                            execution_result.state_diff = self.collect_state_diff(&checkpoint);
+                        }
+                        return Ok(CallStep::Finished(execution_result));
                     }
                     ResumableCall::Resumable(state) => {
                         // check i32 exit code
@@ -453,7 +1469,43 @@ impl<DB: IJournaledTrie> Runtime<DB> {
                             // maybe if was out of fuel
                             let mut execution_result = self.store.data().execution_result.clone();
                             execution_result.exit_code = exit_code;
-                            return Ok(execution_result);
+                            return Ok(CallStep::Finished(execution_result));
+                        } else if let Some(request) =
+                            state.host_error().downcast_ref::<SysSuspendResumable>()
+                        {
+                            if self.call_mode == CallMode::Interruptible {
+                                let reason = PauseReason::Suspend(request.clone());
+                                self.paused = Some(PausedInvocation {
+                                    invocation: state,
+                                    checkpoint,
+                                    reason,
+                                });
+                                return Ok(CallStep::Paused);
+                            }
+                            // nested calls can't be paused, there is nobody to answer the request
+                            ExitCode::NotSupportedCall.into_i32()
+                        } else if let Some(breakpoint) =
+                            state.host_error().downcast_ref::<SysBreakpointResumable>()
+                        {
+                            if self.call_mode == CallMode::Resumable {
+                                let reason = PauseReason::Breakpoint(breakpoint.clone());
+                                self.paused = Some(PausedInvocation {
+                                    invocation: state,
+                                    checkpoint,
+                                    reason,
+                                });
+                                return Ok(CallStep::Paused);
+                            }
+                            // breakpoints are ignored if the call can't be paused
+                            let results = breakpoint.results.clone();
+                            next_result = state
+                                .resume(
+                                    self.store.as_context_mut(),
+                                    &results,
+                                    &mut self.call_results,
+                                )
+                                .map_err(Into::<RuntimeError>::into);
+                            continue;
                         } else if let Some(delayed_state) =
                             state.host_error().downcast_ref::<SysExecResumable>()
                         {
@@ -488,20 +1540,214 @@ impl<DB: IJournaledTrie> Runtime<DB> {
                         // resume call with exit code
                         let exit_code = Value::I32(exit_code);
                         next_result = state
-                            .resume(self.store.as_context_mut(), &[exit_code], &mut [])
+                            .resume(
+                                self.store.as_context_mut(),
+                                &[exit_code],
+                                &mut self.call_results,
+                            )
                             .map_err(Into::<RuntimeError>::into);
                     }
                 },
                 Err(err) => {
                     let mut execution_result = self.store.data().execution_result.clone();
-                    execution_result.fuel_consumed = self.store.fuel_consumed().unwrap_or_default();
+                    execution_result.fuel_consumed =
+                        Fuel(self.store.fuel_consumed().unwrap_or_default());
                     execution_result.exit_code = Runtime::catch_trap(&err);
-                    return Ok(execution_result);
+                    return Ok(CallStep::Finished(execution_result));
                 }
             }
         }
     }
 
+    /// Charges fuel outside the executed code, returns `false` if there is not enough fuel
+    pub(crate) fn consume_intrinsic_fuel(&mut self, fuel: Fuel) -> bool {
+        if fuel.is_zero() {
+            return true;
+        }
+        match Caller::new(&mut self.store, self.instance.as_ref()).consume_fuel(fuel.get()) {
+            Ok(_) | Err(FuelError::FuelMeteringDisabled) => true,
+            Err(FuelError::OutOfFuel) => false,
+        }
+    }
+
+    fn validate_bytecode(&self, rwasm_bytecode: &[u8]) -> Result<(), RuntimeError> {
+        self.store.data().verify_bytecode(rwasm_bytecode)
+    }
+
+    /// Verifies metadata of the cached module and validates it against the policy of the
+    /// context, the verdict is memoized per (policy, code hash), so the bytecode is only loaded
+    /// for the policies the module wasn't checked against yet
+    fn verify_cached_module(
+        &self,
+        caching_runtime: &CachingRuntime,
+        rwasm_hash: &F254,
+        rwasm_bytecode: Option<&[u8]>,
+    ) -> Result<(), RuntimeError> {
+        let ctx = self.store.data();
+        ctx.verify_metadata(caching_runtime.resolve_metadata(rwasm_hash))?;
+        let Some(bytecode_policy) = &ctx.bytecode_policy else {
+            return Ok(());
+        };
+        let verdict = match caching_runtime.policy_verdict(rwasm_hash, bytecode_policy) {
+            Some(verdict) => verdict,
+            None => {
+                let loaded_bytecode;
+                let rwasm_bytecode = match rwasm_bytecode {
+                    Some(rwasm_bytecode) => rwasm_bytecode,
+                    None => {
+                        loaded_bytecode = self.load_rwasm_bytecode(rwasm_hash)?;
+                        &loaded_bytecode
+                    }
+                };
+                let (rwasm_bytecode, _) =
+                    split_metadata(rwasm_bytecode).map_err(RuntimeError::Metadata)?;
+                let verdict = bytecode_policy.validate_rwasm(rwasm_bytecode);
+                caching_runtime.record_policy_verdict(rwasm_hash, bytecode_policy, verdict.clone());
+                verdict
+            }
+        };
+        verdict.map_err(RuntimeError::PolicyViolation)
+    }
+
+    /// Remembers that the freshly translated module passed the policy of the context
+    fn record_policy_verdict(&self, caching_runtime: &CachingRuntime, rwasm_hash: &F254) {
+        if let Some(bytecode_policy) = &self.store.data().bytecode_policy {
+            caching_runtime.record_policy_verdict(rwasm_hash, bytecode_policy, Ok(()));
+        }
+    }
+
+    fn is_evm_bytecode(bytecode: &[u8]) -> bool {
+        !bytecode.is_empty() && BytecodeType::from_slice(bytecode) == BytecodeType::EVM
+    }
+
+    fn load_preimage(&self, hash: &F254) -> Result<Vec<u8>, RuntimeError> {
+        Ok(self
+            .store
+            .data()
+            .jzkt
+            .as_ref()
+            .ok_or(RuntimeError::UnloadedModule(*hash))?
+            .preimage(hash))
+    }
+
+    /// Loads the code from the artifact store if it's configured, otherwise (or if there is no
+    /// valid artifact) from the trie, rWASM code loaded from the trie is persisted in the store
+    fn load_rwasm_bytecode(&self, hash: &F254) -> Result<Vec<u8>, RuntimeError> {
+        let Some(artifact_store) = self.store.data().artifact_store.as_ref() else {
+            return self.load_preimage(hash);
+        };
+        if let Ok(artifact) = artifact_store.load(hash) {
+            return Ok(artifact.rwasm_bytecode);
+        }
+        let rwasm_bytecode = self.load_preimage(hash)?;
+        if !rwasm_bytecode.is_empty() && !Self::is_evm_bytecode(&rwasm_bytecode) {
+            let artifact = ModuleArtifact {
+                rwasm_bytecode: rwasm_bytecode.clone(),
+                engine_artifact: None,
+            };
+            // the store is only a cache, the code is loaded from the trie again next time
+            if let Err(_err) = artifact_store.store(hash, &artifact) {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(err = %_err, "failed to store module artifact");
+            }
+        }
+        Ok(rwasm_bytecode)
+    }
+
+    /// Returns hash of the module that executes the context, EVM bytecode is executed by the
+    /// interpreter, so the store must be created from the engine of the interpreter module
+    fn resolve_module_hash(
+        runtime_context: &RuntimeContext<DB>,
+        caching_runtime: &CachingRuntime,
+    ) -> F254 {
+        let rwasm_hash = runtime_context.bytecode.resolve_hash();
+        if caching_runtime.resolve_module(&rwasm_hash).is_some() {
+            return rwasm_hash;
+        }
+        // the code is only inspected if there is a cached interpreter to take the engine from
+        let Some(interpreter_hash) = runtime_context
+            .evm_interpreter
+            .filter(|hash| caching_runtime.resolve_module(hash).is_some())
+        else {
+            return rwasm_hash;
+        };
+        let is_evm_bytecode = match &runtime_context.bytecode {
+            BytecodeOrHash::Bytecode(bytecode, _) => Self::is_evm_bytecode(bytecode),
+            BytecodeOrHash::Hash(hash) => runtime_context
+                .jzkt
+                .as_ref()
+                .map(|jzkt| Self::is_evm_bytecode(&jzkt.preimage(hash)))
+                .unwrap_or(false),
+            BytecodeOrHash::Address(_) => {
+                unreachable!("account code hash is resolved on creation")
+            }
+        };
+        if is_evm_bytecode {
+            interpreter_hash
+        } else {
+            rwasm_hash
+        }
+    }
+
+    /// Returns module of the EVM interpreter, EVM bytecode can't be executed natively, so we
+    /// route it through the interpreter compiled to rWASM
+    fn resolve_evm_interpreter<'a>(
+        &self,
+        caching_runtime: &'a mut CachingRuntime,
+    ) -> Result<&'a Module, RuntimeError> {
+        let interpreter_hash = self
+            .store
+            .data()
+            .evm_interpreter
+            .ok_or(RuntimeError::MissingEvmInterpreter)?;
+        if caching_runtime.resolve_module(&interpreter_hash).is_some() {
+            return Ok(caching_runtime.resolve_module(&interpreter_hash).unwrap());
+        }
+        let interpreter_bytecode = self.load_preimage(&interpreter_hash)?;
+        if interpreter_bytecode.is_empty() || Self::is_evm_bytecode(&interpreter_bytecode) {
+            return Err(RuntimeError::MissingEvmInterpreter);
+        }
+        caching_runtime.init_module(self.store.engine(), interpreter_hash, &interpreter_bytecode)
+    }
+
+    fn collect_logs(&self, checkpoint: &Option<JournalCheckpoint>) -> Vec<JournalLog> {
+        match (self.store.data().jzkt.as_ref(), checkpoint) {
+            (Some(jzkt), Some(checkpoint)) => {
+                jzkt.logs().into_iter().skip(checkpoint.logs()).collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    fn collect_state_diff(&self, checkpoint: &Option<JournalCheckpoint>) -> Option<StateDiff> {
+        let ctx = self.store.data();
+        if !ctx.record_state_diff {
+            return None;
+        }
+        match (ctx.jzkt.as_ref(), checkpoint) {
+            (Some(jzkt), Some(checkpoint)) => Some(StateDiff::from_journal(
+                jzkt,
+                checkpoint,
+                ctx.access_list_recorder.as_ref(),
+            )),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn missing_entrypoint(&self) -> RuntimeError {
+        RuntimeError::MissingEntrypoint(self.store.data().entrypoint().to_string())
+    }
+
+    /// Reads memory of the last executed instance
+    pub fn read_memory(&mut self, offset: u32, length: u32) -> Result<Vec<u8>, RuntimeError> {
+        let instance = self.instance.ok_or_else(|| self.missing_entrypoint())?;
+        let memory = Caller::new(&mut self.store, Some(&instance))
+            .read_memory(offset, length)
+            .map_err(|err| RuntimeError::Rwasm(err.into()))?
+            .to_vec();
+        Ok(memory)
+    }
+
     pub fn store(&self) -> &Store<RuntimeContext<DB>> {
         &self.store
     }
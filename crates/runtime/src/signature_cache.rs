@@ -0,0 +1,208 @@
+use crate::{instruction::ecrecover::SyscallEcrecover, transaction::Transaction};
+use fluentbase_types::ExitCode;
+use hashbrown::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Inputs of the `_ecrecover` syscall
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SignatureRequest {
+    pub digest: [u8; 32],
+    pub signature: [u8; 64],
+    pub rec_id: u32,
+}
+
+impl SignatureRequest {
+    pub fn new(digest: [u8; 32], signature: [u8; 64], rec_id: u32) -> Self {
+        Self {
+            digest,
+            signature,
+            rec_id,
+        }
+    }
+}
+
+impl From<&Transaction> for SignatureRequest {
+    fn from(tx: &Transaction) -> Self {
+        Self::new(tx.signing_hash().0, tx.signature, tx.recovery_id as u32)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignatureCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Signatures recovered ahead of the execution
+    pub precomputed: u64,
+}
+
+#[derive(Default)]
+struct SignatureCacheInner {
+    results: HashMap<SignatureRequest, Result<[u8; 65], ExitCode>>,
+    stats: SignatureCacheStats,
+}
+
+/// Results of the public key recovery computed ahead of the execution (for example for all
+/// transactions of the block), the `_ecrecover` syscall takes results from the cache instead of
+/// recovering the key on the execution path.
+///
+/// Failed recoveries are cached too, so the syscall fails with the same exit code. The cache
+/// isn't bounded, it's expected to live as long as the block.
+#[derive(Clone, Default)]
+pub struct SignatureCache {
+    inner: Arc<RwLock<SignatureCacheInner>>,
+}
+
+impl SignatureCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.read().unwrap().results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> SignatureCacheStats {
+        self.inner.read().unwrap().stats
+    }
+
+    pub fn clear(&self) {
+        self.inner.write().unwrap().results.clear();
+    }
+
+    /// Recovers public keys of all requests using up to `threads` threads, requests that are
+    /// already cached are skipped
+    pub fn verify_batch(&self, requests: &[SignatureRequest], threads: usize) {
+        assert!(threads > 0, "number of threads must be positive");
+        let pending = {
+            let inner = self.inner.read().unwrap();
+            let mut pending = requests
+                .iter()
+                .filter(|request| !inner.results.contains_key(*request))
+                .copied()
+                .collect::<Vec<_>>();
+            pending.sort_unstable_by(|a, b| {
+                (a.digest, a.signature, a.rec_id).cmp(&(b.digest, b.signature, b.rec_id))
+            });
+            pending.dedup();
+            pending
+        };
+        if pending.is_empty() {
+            return;
+        }
+        let chunk_size = (pending.len() + threads - 1) / threads;
+        let results = std::thread::scope(|scope| {
+            let handles = pending
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|request| (*request, Self::recover(request)))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        let mut inner = self.inner.write().unwrap();
+        inner.stats.precomputed += results.len() as u64;
+        inner.results.extend(results);
+    }
+
+    /// Recovers signers of all transactions, see [`SignatureCache::verify_batch`]
+    pub fn verify_transactions(&self, transactions: &[Transaction], threads: usize) {
+        let requests = transactions
+            .iter()
+            .map(SignatureRequest::from)
+            .collect::<Vec<_>>();
+        self.verify_batch(&requests, threads);
+    }
+
+    /// Returns the cached public key or recovers it (the result isn't cached, the key is
+    /// recovered on the execution path only for signatures that weren't known in advance)
+    pub fn recover_public_key(&self, request: &SignatureRequest) -> Result<[u8; 65], ExitCode> {
+        if let Some(result) = self.get(request) {
+            return result;
+        }
+        Self::recover(request)
+    }
+
+    fn get(&self, request: &SignatureRequest) -> Option<Result<[u8; 65], ExitCode>> {
+        let mut inner = self.inner.write().unwrap();
+        let result = inner.results.get(request).copied();
+        if result.is_some() {
+            inner.stats.hits += 1;
+        } else {
+            inner.stats.misses += 1;
+        }
+        result
+    }
+
+    fn recover(request: &SignatureRequest) -> Result<[u8; 65], ExitCode> {
+        SyscallEcrecover::fn_impl(&request.digest, &request.signature, request.rec_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        instruction::ecrecover::SyscallEcrecover,
+        signature_cache::{SignatureCache, SignatureCacheStats, SignatureRequest},
+    };
+    use fluentbase_types::ExitCode;
+    use k256::ecdsa::SigningKey;
+
+    fn sign(key: u8, digest: [u8; 32]) -> SignatureRequest {
+        let signing_key = SigningKey::from_slice(&[key; 32]).unwrap();
+        let (signature, recovery_id) = signing_key.sign_prehash_recoverable(&digest).unwrap();
+        let mut signature64 = [0u8; 64];
+        signature64.copy_from_slice(&signature.to_bytes());
+        SignatureRequest::new(digest, signature64, recovery_id.to_byte() as u32)
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut requests = (1..=9u8).map(|i| sign(i, [i; 32])).collect::<Vec<_>>();
+        // duplicates are recovered once
+        requests.push(requests[0]);
+        let invalid = SignatureRequest::new([1; 32], [0; 64], 0);
+        requests.push(invalid);
+
+        let cache = SignatureCache::new();
+        cache.verify_batch(&requests, 4);
+        assert_eq!(cache.len(), 10);
+        // already known requests aren't recovered again
+        cache.verify_batch(&requests[..3], 2);
+        assert_eq!(cache.stats().precomputed, 10);
+
+        for request in requests[..9].iter() {
+            assert_eq!(
+                cache.recover_public_key(request),
+                SyscallEcrecover::fn_impl(&request.digest, &request.signature, request.rec_id)
+            );
+        }
+        assert_eq!(
+            cache.recover_public_key(&invalid),
+            Err(ExitCode::EcrecoverBadSignature)
+        );
+        // unknown signatures are recovered on the fly
+        let unknown = sign(10, [10; 32]);
+        assert!(cache.recover_public_key(&unknown).is_ok());
+        assert_eq!(
+            cache.stats(),
+            SignatureCacheStats {
+                hits: 10,
+                misses: 1,
+                precomputed: 10,
+            }
+        );
+        assert_eq!(cache.len(), 10);
+    }
+}
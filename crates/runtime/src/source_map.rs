@@ -0,0 +1,374 @@
+use crate::{
+    disassembler::resolve_import_name,
+    trace::format::{TraceFormatError, TraceReader, TraceSideEffect, SYSCALL_OPCODE_FLAG},
+};
+use hashbrown::HashMap;
+use rwasm::rwasm::RwasmModule;
+use std::fmt::{Display, Formatter};
+use wasmparser::{Name, NameSectionReader, Parser, Payload, TypeRef};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceMapError {
+    MalformedWasm(String),
+    MalformedRwasm(String),
+    MalformedDwarf(String),
+}
+
+impl Display for SourceMapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceMapError::MalformedWasm(err) => write!(f, "malformed wasm: {}", err),
+            SourceMapError::MalformedRwasm(err) => write!(f, "malformed rwasm: {}", err),
+            SourceMapError::MalformedDwarf(err) => write!(f, "malformed dwarf: {}", err),
+        }
+    }
+}
+
+/// Source line of the function, it's taken from DWARF `.debug_line` (the first row inside the
+/// function body)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLine {
+    pub file: String,
+    pub line: u64,
+}
+
+impl Display for SourceLine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// Index of the function in the original wasm (imports included)
+    pub func_idx: u32,
+    pub function: String,
+    pub source_line: Option<SourceLine>,
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.source_line {
+            Some(source_line) => write!(f, "{} ({})", self.function, source_line),
+            None => write!(f, "{}", self.function),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WasmFunction {
+    func_idx: u32,
+    name: Option<String>,
+    /// Range of the function body relative to the code section start (DWARF addresses are
+    /// relative to the code section too)
+    code_range: (u64, u64),
+    source_line: Option<SourceLine>,
+}
+
+/// Maps rWASM pcs back to the functions of the original wasm binary (names are taken from the
+/// name section, source lines from DWARF with the `dwarf` feature).
+///
+/// rWASM doesn't keep wasm offsets, so resolution works on the function level: the translator
+/// emits local functions in the wasm order (imports become system calls), so the rWASM function
+/// `i` is the wasm function `imports + i` and the code after the last function is the
+/// entrypoint generated by the translator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceMap {
+    imported_funcs: u32,
+    functions: Vec<WasmFunction>,
+    /// End pc (exclusive) of every rWASM function
+    func_boundaries: Vec<u32>,
+}
+
+impl SourceMap {
+    pub fn new(wasm_binary: &[u8], rwasm_bytecode: &[u8]) -> Result<Self, SourceMapError> {
+        let rwasm_module = RwasmModule::new(rwasm_bytecode)
+            .map_err(|err| SourceMapError::MalformedRwasm(format!("{:?}", err)))?;
+        Self::from_module(wasm_binary, &rwasm_module)
+    }
+
+    pub fn from_module(
+        wasm_binary: &[u8],
+        rwasm_module: &RwasmModule,
+    ) -> Result<Self, SourceMapError> {
+        let malformed =
+            |err: wasmparser::BinaryReaderError| SourceMapError::MalformedWasm(err.to_string());
+        let mut imported_funcs = 0u32;
+        let mut code_section_start = 0usize;
+        let mut functions = Vec::new();
+        let mut names = HashMap::new();
+        let mut debug_sections = HashMap::new();
+        for payload in Parser::new(0).parse_all(wasm_binary) {
+            match payload.map_err(malformed)? {
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        if let TypeRef::Func(_) = import.map_err(malformed)?.ty {
+                            imported_funcs += 1;
+                        }
+                    }
+                }
+                Payload::CodeSectionStart { range, .. } => {
+                    code_section_start = range.start;
+                }
+                Payload::CodeSectionEntry(body) => {
+                    let range = body.range();
+                    functions.push(WasmFunction {
+                        func_idx: imported_funcs + functions.len() as u32,
+                        name: None,
+                        code_range: (
+                            (range.start - code_section_start) as u64,
+                            (range.end - code_section_start) as u64,
+                        ),
+                        source_line: None,
+                    });
+                }
+                Payload::CustomSection(reader) if reader.name() == "name" => {
+                    let name_section = NameSectionReader::new(reader.data(), reader.data_offset());
+                    for subsection in name_section {
+                        let Name::Function(name_map) = subsection.map_err(malformed)? else {
+                            continue;
+                        };
+                        for naming in name_map {
+                            let naming = naming.map_err(malformed)?;
+                            names.insert(naming.index, naming.name.to_string());
+                        }
+                    }
+                }
+                Payload::CustomSection(reader) if reader.name().starts_with(".debug_") => {
+                    debug_sections.insert(reader.name(), reader.data());
+                }
+                _ => {}
+            }
+        }
+        for function in functions.iter_mut() {
+            function.name = names.remove(&function.func_idx);
+        }
+        if !debug_sections.is_empty() {
+            Self::resolve_source_lines(&mut functions, &debug_sections)?;
+        }
+        let func_boundaries = rwasm_module
+            .func_section
+            .iter()
+            .scan(0u32, |end, len| {
+                *end += *len;
+                Some(*end)
+            })
+            .collect();
+        Ok(Self {
+            imported_funcs,
+            functions,
+            func_boundaries,
+        })
+    }
+
+    #[cfg(feature = "dwarf")]
+    fn resolve_source_lines(
+        functions: &mut [WasmFunction],
+        debug_sections: &HashMap<&str, &[u8]>,
+    ) -> Result<(), SourceMapError> {
+        use gimli::{Dwarf, EndianSlice, LittleEndian, SectionId};
+
+        let malformed = |err: gimli::Error| SourceMapError::MalformedDwarf(err.to_string());
+        let dwarf = Dwarf::load(|id: SectionId| -> Result<_, gimli::Error> {
+            let data = debug_sections.get(id.name()).copied().unwrap_or_default();
+            Ok(EndianSlice::new(data, LittleEndian))
+        })
+        .map_err(malformed)?;
+        // (address, source line) of every row, sorted by address
+        let mut rows = Vec::new();
+        let mut units = dwarf.units();
+        while let Some(header) = units.next().map_err(malformed)? {
+            let unit = dwarf.unit(header).map_err(malformed)?;
+            let Some(line_program) = unit.line_program.clone() else {
+                continue;
+            };
+            let mut line_rows = line_program.rows();
+            while let Some((header, row)) = line_rows.next_row().map_err(malformed)? {
+                if row.end_sequence() {
+                    continue;
+                }
+                let (Some(file), Some(line)) = (row.file(header), row.line()) else {
+                    continue;
+                };
+                let mut path = String::new();
+                if let Some(dir) = file.directory(header) {
+                    path.push_str(
+                        &dwarf
+                            .attr_string(&unit, dir)
+                            .map_err(malformed)?
+                            .to_string_lossy(),
+                    );
+                    path.push('/');
+                }
+                path.push_str(
+                    &dwarf
+                        .attr_string(&unit, file.path_name())
+                        .map_err(malformed)?
+                        .to_string_lossy(),
+                );
+                rows.push((
+                    row.address(),
+                    SourceLine {
+                        file: path,
+                        line: line.get(),
+                    },
+                ));
+            }
+        }
+        rows.sort_by_key(|(address, _)| *address);
+        for function in functions.iter_mut() {
+            let (start, end) = function.code_range;
+            let first_row = rows.partition_point(|(address, _)| *address < start);
+            function.source_line = rows
+                .get(first_row)
+                .filter(|(address, _)| *address < end)
+                .map(|(_, source_line)| source_line.clone());
+        }
+        Ok(())
+    }
+
+    /// Without the `dwarf` feature debug sections are ignored
+    #[cfg(not(feature = "dwarf"))]
+    fn resolve_source_lines(
+        _functions: &mut [WasmFunction],
+        _debug_sections: &HashMap<&str, &[u8]>,
+    ) -> Result<(), SourceMapError> {
+        Ok(())
+    }
+
+    /// Index of the rWASM function that contains the pc, the entrypoint has index equal to
+    /// the number of functions
+    pub fn rwasm_func_idx(&self, pc: u32) -> usize {
+        self.func_boundaries.partition_point(|end| *end <= pc)
+    }
+
+    pub fn resolve_pc(&self, pc: u32) -> SourceLocation {
+        let rwasm_func_idx = self.rwasm_func_idx(pc);
+        match self.functions.get(rwasm_func_idx) {
+            Some(function) => SourceLocation {
+                func_idx: function.func_idx,
+                function: function
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("func[{}]", function.func_idx)),
+                source_line: function.source_line.clone(),
+            },
+            None => SourceLocation {
+                func_idx: self.imported_funcs + rwasm_func_idx as u32,
+                function: "<entrypoint>".to_string(),
+                source_line: None,
+            },
+        }
+    }
+
+    /// Names of the functions by wasm index, it's the format expected by
+    /// [`FuelProfiler::to_folded_stacks`](crate::profiler::FuelProfiler::to_folded_stacks),
+    /// functions with known source lines are shown as `name (file:line)`
+    pub fn function_names(&self) -> HashMap<u32, String> {
+        self.functions
+            .iter()
+            .filter(|function| function.name.is_some() || function.source_line.is_some())
+            .map(|function| {
+                let location = SourceLocation {
+                    func_idx: function.func_idx,
+                    function: function
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("func[{}]", function.func_idx)),
+                    source_line: function.source_line.clone(),
+                };
+                (function.func_idx, location.to_string())
+            })
+            .collect()
+    }
+
+    /// Backtrace of the pcs (innermost first), one frame per line
+    pub fn backtrace(&self, pcs: &[u32]) -> String {
+        pcs.iter()
+            .enumerate()
+            .map(|(i, pc)| format!("{:>4}: {:04} {}", i, pc, self.resolve_pc(*pc)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Exports the serialized trace in the text form, host calls are annotated with the system
+    /// function name, function entries (of instrumented binaries) with the function name and its
+    /// source line, other rows with their side effect
+    pub fn annotate_trace(&self, trace: &[u8]) -> Result<String, TraceFormatError> {
+        let reader = TraceReader::new(trace)?;
+        let function_names = self.function_names();
+        let mut lines = Vec::with_capacity(reader.len());
+        for row in reader.rows() {
+            let row = row?;
+            let annotation = if row.is_syscall() {
+                let sys_func_idx = row.opcode & !SYSCALL_OPCODE_FLAG;
+                resolve_import_name(sys_func_idx)
+                    .unwrap_or_else(|| format!("syscall[{}]", sys_func_idx))
+            } else if row.side_effect == TraceSideEffect::FunctionEnter {
+                let func_idx = row.operands[0] as u32;
+                function_names
+                    .get(&func_idx)
+                    .cloned()
+                    .unwrap_or_else(|| format!("func[{}]", func_idx))
+            } else {
+                format!("{:?}", row.side_effect)
+            };
+            lines.push(format!(
+                "{:>8} 0x{:08x} ;; {}",
+                row.clk, row.opcode, annotation
+            ));
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{source_map::SourceMap, tests::wasm2rwasm, trace::format::TraceWriter};
+
+    #[test]
+    fn test_resolve_function_names() {
+        let wasm_binary = wat::parse_str(
+            r#"
+(module
+  (import "fluentbase_v1preview" "_exit" (func $exit (param i32)))
+  (func $main
+    i32.const 100
+    i32.const 20
+    call $add
+    drop
+    )
+  (func $add (param $lhs i32) (param $rhs i32) (result i32)
+    local.get $lhs
+    local.get $rhs
+    i32.add
+    )
+  (export "main" (func $main)))
+    "#,
+        )
+        .unwrap();
+        let rwasm_bytecode = wasm2rwasm(&wasm_binary);
+        let source_map = SourceMap::new(&wasm_binary, &rwasm_bytecode).unwrap();
+        // imports are counted in wasm indices
+        let names = source_map.function_names();
+        assert_eq!(names.get(&1).map(String::as_str), Some("main"));
+        assert_eq!(names.get(&2).map(String::as_str), Some("add"));
+        assert_eq!(source_map.resolve_pc(0).function, "main");
+        let add_start = source_map.func_boundaries[0];
+        assert_eq!(source_map.resolve_pc(add_start).function, "add");
+        let entrypoint = *source_map.func_boundaries.last().unwrap();
+        assert_eq!(source_map.resolve_pc(entrypoint).function, "<entrypoint>");
+
+        let trace_writer = TraceWriter::new();
+        trace_writer.push_function_enter(1, 2);
+        trace_writer.push_function_exit(5);
+        let trace = source_map.annotate_trace(&trace_writer.to_bytes()).unwrap();
+        assert_eq!(
+            trace.lines().collect::<Vec<_>>(),
+            vec![
+                "       1 0x00000000 ;; add",
+                "       5 0x00000000 ;; FunctionExit",
+            ]
+        );
+    }
+}
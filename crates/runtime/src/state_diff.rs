@@ -0,0 +1,246 @@
+use crate::access_list::AccessListRecorder;
+use byteorder::{ByteOrder, LittleEndian};
+use fluentbase_types::{
+    Address,
+    IJournaledTrie,
+    JournalCheckpoint,
+    B256,
+    JZKT_ACCOUNT_BALANCE_FIELD,
+    JZKT_ACCOUNT_NONCE_FIELD,
+    JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+    JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD,
+    U256,
+};
+use hashbrown::HashSet;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValueChange<T> {
+    pub old: T,
+    pub new: T,
+}
+
+impl<T: PartialEq> ValueChange<T> {
+    fn new(old: T, new: T) -> Option<Self> {
+        if old != new {
+            Some(Self { old, new })
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountDiff {
+    /// Account didn't exist before the execution
+    pub created: bool,
+    pub balance: Option<ValueChange<U256>>,
+    pub nonce: Option<ValueChange<u64>>,
+    pub code_hash: Option<ValueChange<B256>>,
+    pub rwasm_code_hash: Option<ValueChange<B256>>,
+    /// Changed storage slots, removed slots are reported as zero
+    pub storage: BTreeMap<B256, ValueChange<U256>>,
+}
+
+impl AccountDiff {
+    fn is_empty(&self) -> bool {
+        !self.created
+            && self.balance.is_none()
+            && self.nonce.is_none()
+            && self.code_hash.is_none()
+            && self.rwasm_code_hash.is_none()
+            && self.storage.is_empty()
+    }
+}
+
+/// State changes made by the execution, it's derived from the journal events written after
+/// the execution checkpoint, so changes that are reverted by nested calls aren't included
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateDiff {
+    pub accounts: BTreeMap<Address, AccountDiff>,
+    /// Changed trie keys that can't be resolved into the account or the storage slot (like
+    /// custom keys of sovereign apps), `None` means that the key is absent
+    pub unresolved: BTreeMap<B256, ValueChange<Option<Vec<[u8; 32]>>>>,
+}
+
+impl StateDiff {
+    /// Builds the diff from the journal events after the checkpoint, storage keys are resolved
+    /// with the recorder (without the recorder all storage changes are unresolved)
+    pub fn from_journal<DB: IJournaledTrie>(
+        jzkt: &DB,
+        checkpoint: &JournalCheckpoint,
+        recorder: Option<&AccessListRecorder>,
+    ) -> Self {
+        let journal = jzkt.journal();
+        let mut result = Self::default();
+        let mut visited = HashSet::new();
+        for event in journal.iter().skip(checkpoint.state()) {
+            let key = *event.key();
+            if !visited.insert(key) {
+                continue;
+            }
+            // the first event after the checkpoint refers to the value before the execution
+            let old = match event.prev_state() {
+                Some(prev_state) => journal[prev_state].preimage().map(|(values, _)| values),
+                None => jzkt.get(&key, true).map(|(values, _, _)| values),
+            };
+            let new = jzkt.get(&key, false).map(|(values, _, _)| values);
+            result.push_change(&key, old, new, recorder);
+        }
+        result.accounts.retain(|_, account| !account.is_empty());
+        result
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.unresolved.is_empty()
+    }
+
+    fn push_change(
+        &mut self,
+        key: &[u8; 32],
+        old: Option<Vec<[u8; 32]>>,
+        new: Option<Vec<[u8; 32]>>,
+        recorder: Option<&AccessListRecorder>,
+    ) {
+        if let Some((address, slot)) = recorder.and_then(|recorder| recorder.resolve_key(key)) {
+            let storage_value = |values: &Option<Vec<[u8; 32]>>| {
+                values
+                    .as_ref()
+                    .and_then(|values| values.first())
+                    .map(|value| U256::from_le_bytes(*value))
+                    .unwrap_or_default()
+            };
+            if let Some(change) = ValueChange::new(storage_value(&old), storage_value(&new)) {
+                let account = self.accounts.entry(address).or_default();
+                account.storage.insert(slot, change);
+            }
+        } else if key[..12].iter().all(|v| *v == 0) {
+            // account keys are addresses padded to 32 bytes
+            let account = self
+                .accounts
+                .entry(Address::from_slice(&key[12..]))
+                .or_default();
+            let field = |values: &Option<Vec<[u8; 32]>>, field: u32| {
+                values
+                    .as_ref()
+                    .and_then(|values| values.get(field as usize).copied())
+                    .unwrap_or_default()
+            };
+            let balance = |values| U256::from_le_bytes(field(values, JZKT_ACCOUNT_BALANCE_FIELD));
+            let nonce = |values| LittleEndian::read_u64(&field(values, JZKT_ACCOUNT_NONCE_FIELD));
+            let code_hash = |values, field_index| B256::new(field(values, field_index));
+            account.created |= old.is_none() && new.is_some();
+            account.balance = ValueChange::new(balance(&old), balance(&new));
+            account.nonce = ValueChange::new(nonce(&old), nonce(&new));
+            account.code_hash = ValueChange::new(
+                code_hash(&old, JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD),
+                code_hash(&new, JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD),
+            );
+            account.rwasm_code_hash = ValueChange::new(
+                code_hash(&old, JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD),
+                code_hash(&new, JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD),
+            );
+        } else if let Some(change) = ValueChange::new(old, new) {
+            self.unresolved.insert(B256::new(*key), change);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        access_list::AccessListRecorder,
+        import::StateImporter,
+        instruction::poseidon_hash::SyscallPoseidonHash,
+        state_diff::{StateDiff, ValueChange},
+        types::InMemoryTrieDb,
+        zktrie::ZkTrieStateDb,
+        JournaledTrie,
+    };
+    use byteorder::{ByteOrder, LittleEndian};
+    use fluentbase_types::{
+        address,
+        b256,
+        IJournaledTrie,
+        B256,
+        JZKT_ACCOUNT_BALANCE_FIELD,
+        JZKT_ACCOUNT_NONCE_FIELD,
+        U256,
+    };
+
+    const DOMAIN: [u8; 32] =
+        b256!("0000000000000000000000000000000000000000000000010000000000000000").0;
+
+    fn hash(recorder: &AccessListRecorder, fa: &[u8; 32], fb: &[u8; 32]) -> [u8; 32] {
+        let output = SyscallPoseidonHash::fn_impl(fa, fb, &DOMAIN).unwrap();
+        recorder.record_hash(fa, fb, &output);
+        output
+    }
+
+    #[test]
+    fn test_state_diff_from_journal() {
+        let jzkt = JournaledTrie::new(ZkTrieStateDb::new_empty(InMemoryTrieDb::default()));
+        let contract = address!("1111111111111111111111111111111111111111");
+        let mut importer = StateImporter::new(jzkt.clone());
+        importer
+            .import_account(&contract, U256::from(100), 1, &[])
+            .unwrap();
+        importer
+            .import_slot(&contract, U256::from(7), U256::from(1))
+            .unwrap();
+        importer.finish().unwrap();
+
+        let recorder = AccessListRecorder::new();
+        let checkpoint = jzkt.checkpoint();
+        // bump the nonce twice and change the balance, only the final value is reported
+        let (mut fields, flags, _) = jzkt.get(&contract.into_word(), false).unwrap();
+        LittleEndian::write_u64(&mut fields[JZKT_ACCOUNT_NONCE_FIELD as usize], 2);
+        jzkt.update(&contract.into_word(), &fields, flags);
+        LittleEndian::write_u64(&mut fields[JZKT_ACCOUNT_NONCE_FIELD as usize], 3);
+        fields[JZKT_ACCOUNT_BALANCE_FIELD as usize] = U256::from(50).to_le_bytes::<32>();
+        jzkt.update(&contract.into_word(), &fields, flags);
+        // storage key of the slot 7
+        let mut slot0 = [0u8; 32];
+        slot0[0] = 7;
+        let slot_hash = hash(&recorder, &slot0, &[0u8; 32]);
+        let mut address32 = [0u8; 32];
+        address32[11..31].copy_from_slice(contract.as_slice());
+        let storage_key = hash(&recorder, &address32, &slot_hash);
+        jzkt.update(&storage_key, &vec![U256::from(2).to_le_bytes::<32>()], 0);
+        // the key can't be resolved
+        jzkt.update(&[0xff; 32], &vec![[1u8; 32]], 0);
+
+        let diff = StateDiff::from_journal(&jzkt, &checkpoint, Some(&recorder));
+        let account = diff.accounts.get(&contract).unwrap();
+        assert!(!account.created);
+        assert_eq!(account.nonce, Some(ValueChange { old: 1, new: 3 }));
+        assert_eq!(
+            account.balance,
+            Some(ValueChange {
+                old: U256::from(100),
+                new: U256::from(50)
+            })
+        );
+        assert_eq!(account.code_hash, None);
+        assert_eq!(
+            account.storage.get(&B256::from(U256::from(7))),
+            Some(&ValueChange {
+                old: U256::from(1),
+                new: U256::from(2)
+            })
+        );
+        assert_eq!(
+            diff.unresolved.get(&B256::new([0xff; 32])),
+            Some(&ValueChange {
+                old: None,
+                new: Some(vec![[1u8; 32]])
+            })
+        );
+        // changes before the checkpoint aren't included
+        let checkpoint = jzkt.checkpoint();
+        assert!(StateDiff::from_journal(&jzkt, &checkpoint, Some(&recorder)).is_empty());
+    }
+}
@@ -0,0 +1,570 @@
+use crate::{
+    instruction::{keccak256::SyscallKeccak256, poseidon::SyscallPoseidon},
+    replay::{
+        write_bytes,
+        write_u32,
+        RecordingTrieStorage,
+        ReplayCall,
+        ReplayError,
+        WitnessReader,
+    },
+    types::{InMemoryTrieDb, RuntimeError, TrieDb},
+    zktrie::ZkTrieStateDb,
+    ExecutionResult,
+    JournaledTrie,
+    Runtime,
+    TrieStorage,
+};
+use fluentbase_poseidon::poseidon_hash;
+use fluentbase_types::{
+    Address,
+    Bytes,
+    ExitCode,
+    IJournaledTrie,
+    B256,
+    JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+    JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD,
+    KECCAK_EMPTY,
+    POSEIDON_EMPTY,
+};
+use fluentbase_zktrie::{
+    decode_smt_proofs,
+    test_bit,
+    to_secure_key,
+    Hash,
+    NodeValue,
+    PoseidonHash,
+};
+use hashbrown::HashSet;
+use std::sync::{Arc, RwLock};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatelessError {
+    Runtime(String),
+    MalformedWitness,
+    /// Proof of the key doesn't lead from the pre-root to the terminal node
+    InvalidProof {
+        key: [u8; 32],
+    },
+    /// Preimage hash (poseidon or keccak256) doesn't match the witness key
+    InvalidPreimage {
+        hash: [u8; 32],
+    },
+    /// Execution accessed the key or the preimage that isn't covered by the witness
+    MissingWitness {
+        key: [u8; 32],
+    },
+    /// Bytecode of the call isn't the code of the account proven against the pre-root
+    CodeHashMismatch {
+        address: Address,
+    },
+}
+
+impl From<RuntimeError> for StatelessError {
+    fn from(value: RuntimeError) -> Self {
+        Self::Runtime(format!("{:?}", value))
+    }
+}
+
+/// Everything required to execute the call without the database: call parameters, merkle
+/// proofs of all touched keys against the pre-root and preimages read by the execution.
+///
+/// Unlike [`crate::replay::ReplayWitness`] the witness isn't trusted, every value is verified
+/// against the pre-root before the execution, including the bytecode of the call that must be
+/// the code of the called account.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExecutionWitness {
+    pub call: ReplayCall,
+    /// Account whose code is executed by the call
+    pub address: Address,
+    pub pre_root: [u8; 32],
+    pub proofs: Vec<([u8; 32], Vec<Vec<u8>>)>,
+    pub preimages: Vec<([u8; 32], Bytes)>,
+}
+
+impl ExecutionWitness {
+    /// Executes the call over the storage and records proofs of all read and written keys,
+    /// storage changes are not committed, so caller can commit or rollback them using the
+    /// returned journal
+    pub fn record<DB: TrieStorage>(
+        call: ReplayCall,
+        address: Address,
+        storage: DB,
+    ) -> Result<
+        (
+            Self,
+            ExecutionResult,
+            JournaledTrie<RecordingTrieStorage<DB>>,
+        ),
+        StatelessError,
+    > {
+        let storage = RecordingTrieStorage::new(storage);
+        let reads = storage.reads.clone();
+        let jzkt = JournaledTrie::new(storage);
+        let pre_root = jzkt.compute_root();
+        // account leaf is read before the execution, so its proof is a part of the witness
+        let account = jzkt
+            .get(&address.into_word(), false)
+            .map(|(fields, _, _)| fields);
+        if !is_account_code(account.as_deref(), &call.bytecode) {
+            return Err(StatelessError::CodeHashMismatch { address });
+        }
+        let execution_result = Runtime::run_with_context(call.to_runtime_context(jzkt.clone()))?;
+
+        let reads = reads.read().unwrap();
+        let mut keys = reads
+            .leafs
+            .keys()
+            .chain(reads.absent.iter())
+            .copied()
+            .chain(jzkt.dirty_keys())
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+        let proofs = keys
+            .into_iter()
+            .map(|key| {
+                jzkt.proof(&key)
+                    .map(|proof| (key, proof))
+                    .ok_or(StatelessError::InvalidProof { key })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut preimages = reads
+            .preimages
+            .iter()
+            .map(|(hash, preimage)| (*hash, preimage.clone()))
+            .collect::<Vec<_>>();
+        preimages.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let witness = Self {
+            call,
+            address,
+            pre_root,
+            proofs,
+            preimages,
+        };
+        Ok((witness, execution_result, jzkt))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        self.call.write(&mut result);
+        result.extend_from_slice(self.address.into_word().as_slice());
+        result.extend_from_slice(&self.pre_root);
+        write_u32(&mut result, self.proofs.len() as u32);
+        for (key, proof) in self.proofs.iter() {
+            result.extend_from_slice(key);
+            write_u32(&mut result, proof.len() as u32);
+            proof.iter().for_each(|node| write_bytes(&mut result, node));
+        }
+        write_u32(&mut result, self.preimages.len() as u32);
+        for (hash, preimage) in self.preimages.iter() {
+            result.extend_from_slice(hash);
+            write_bytes(&mut result, preimage);
+        }
+        result
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StatelessError> {
+        let mut reader = WitnessReader::new(bytes);
+        let witness = Self::read(&mut reader).map_err(|_| StatelessError::MalformedWitness)?;
+        reader
+            .finish()
+            .map_err(|_| StatelessError::MalformedWitness)?;
+        Ok(witness)
+    }
+
+    fn read(reader: &mut WitnessReader) -> Result<Self, ReplayError> {
+        let call = ReplayCall::read(reader)?;
+        let address = Address::from_word(B256::from(reader.read_bytes32()?));
+        let pre_root = reader.read_bytes32()?;
+        let mut proofs = Vec::new();
+        for _ in 0..reader.read_u32()? {
+            let key = reader.read_bytes32()?;
+            let proof = (0..reader.read_u32()?)
+                .map(|_| reader.read_bytes())
+                .collect::<Result<Vec<_>, _>>()?;
+            proofs.push((key, proof));
+        }
+        let mut preimages = Vec::new();
+        for _ in 0..reader.read_u32()? {
+            let hash = reader.read_bytes32()?;
+            preimages.push((hash, reader.read_bytes()?.into()));
+        }
+        Ok(Self {
+            call,
+            address,
+            pre_root,
+            proofs,
+            preimages,
+        })
+    }
+}
+
+/// Trie storage over the partial trie restored from the witness, it remembers the first key
+/// or preimage that isn't covered by the witness, because the partial trie can't tell whether
+/// such key is absent or just not proven
+struct WitnessTrieStorage {
+    storage: ZkTrieStateDb<InMemoryTrieDb>,
+    keys: HashSet<[u8; 32]>,
+    missing: Arc<RwLock<Option<[u8; 32]>>>,
+}
+
+impl WitnessTrieStorage {
+    fn check_key(&self, key: &[u8]) -> Result<(), ExitCode> {
+        match <[u8; 32]>::try_from(key) {
+            Ok(key) if self.keys.contains(&key) => Ok(()),
+            _ => {
+                self.report_missing(key);
+                Err(ExitCode::PersistentStorageError)
+            }
+        }
+    }
+
+    fn report_missing(&self, key: &[u8]) {
+        let mut missing = self.missing.write().unwrap();
+        if missing.is_none() {
+            let mut word = [0u8; 32];
+            let length = key.len().min(32);
+            word[..length].copy_from_slice(&key[..length]);
+            *missing = Some(word);
+        }
+    }
+}
+
+impl TrieStorage for WitnessTrieStorage {
+    fn open(&mut self, root32: &[u8]) -> bool {
+        self.storage.open(root32)
+    }
+
+    fn compute_root(&self) -> [u8; 32] {
+        self.storage.compute_root()
+    }
+
+    fn get(&self, key: &[u8]) -> Option<(Vec<[u8; 32]>, u32)> {
+        self.check_key(key).ok()?;
+        self.storage.get(key)
+    }
+
+    fn get_at(&self, _root32: &[u8; 32], key: &[u8]) -> Option<(Vec<[u8; 32]>, u32)> {
+        // only nodes of the pre-root are proven
+        self.report_missing(key);
+        None
+    }
+
+    fn update(
+        &mut self,
+        key: &[u8],
+        value_flags: u32,
+        value: &Vec<[u8; 32]>,
+    ) -> Result<(), ExitCode> {
+        self.check_key(key)?;
+        self.storage.update(key, value_flags, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) -> Result<(), ExitCode> {
+        self.check_key(key)?;
+        self.storage.remove(key)
+    }
+
+    fn delete(&mut self, key: &[u8]) -> Result<(), ExitCode> {
+        self.check_key(key)?;
+        self.storage.delete(key)
+    }
+
+    fn proof(&self, key: &[u8; 32]) -> Option<Vec<Vec<u8>>> {
+        self.storage.proof(key)
+    }
+
+    fn get_preimage(&mut self, key: &[u8]) -> Option<Bytes> {
+        let result = self.storage.get_preimage(key);
+        if result.is_none() {
+            self.report_missing(key);
+        }
+        result
+    }
+
+    fn update_preimage(&mut self, key: &[u8], value: Bytes) {
+        self.storage.update_preimage(key, value)
+    }
+}
+
+/// Result of the stateless execution, post root is computed over the partial trie after the
+/// execution changes are committed
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatelessOutcome {
+    pub execution_result: ExecutionResult,
+    pub pre_root: [u8; 32],
+    pub post_root: [u8; 32],
+}
+
+/// Executes the call using only the witness instead of the database, so the execution can be
+/// verified by a party that doesn't have the state
+pub struct StatelessRuntime {
+    witness: ExecutionWitness,
+}
+
+impl StatelessRuntime {
+    pub fn new(witness: ExecutionWitness) -> Self {
+        Self { witness }
+    }
+
+    /// Verifies the witness against the pre-root, executes the call and computes the post-root.
+    ///
+    /// Keys that are removed by the execution might require sibling nodes that aren't part of
+    /// the inclusion proof, such executions fail with the missing witness error.
+    pub fn execute(&self) -> Result<StatelessOutcome, StatelessError> {
+        let mut db = InMemoryTrieDb::default();
+        let mut keys = HashSet::new();
+        for (key, proof) in self.witness.proofs.iter() {
+            verify_proof(&self.witness.pre_root, key, proof, &mut db)?;
+            keys.insert(*key);
+        }
+        for (hash, preimage) in self.witness.preimages.iter() {
+            if &poseidon_hash(preimage) != hash && &SyscallKeccak256::fn_impl(preimage) != hash {
+                return Err(StatelessError::InvalidPreimage { hash: *hash });
+            }
+            db.update_preimage(hash, preimage.clone());
+        }
+
+        let missing = Arc::new(RwLock::new(None));
+        let storage = WitnessTrieStorage {
+            storage: ZkTrieStateDb::new_opened(db, &self.witness.pre_root),
+            keys,
+            missing: missing.clone(),
+        };
+        // the bytecode must be the code of the account proven against the pre-root
+        let address32 = self.witness.address.into_word();
+        let account = storage.get(address32.as_slice()).map(|(fields, _)| fields);
+        if let Some(key) = *missing.read().unwrap() {
+            return Err(StatelessError::MissingWitness { key });
+        }
+        if !is_account_code(account.as_deref(), &self.witness.call.bytecode) {
+            return Err(StatelessError::CodeHashMismatch {
+                address: self.witness.address,
+            });
+        }
+        let jzkt = JournaledTrie::new(storage);
+        let execution_result =
+            Runtime::run_with_context(self.witness.call.to_runtime_context(jzkt.clone()))?;
+        // commit can touch keys that aren't covered by the witness too
+        let commit_result = jzkt.commit();
+        if let Some(key) = *missing.read().unwrap() {
+            return Err(StatelessError::MissingWitness { key });
+        }
+        let (post_root, _) = commit_result.map_err(|_| StatelessError::MalformedWitness)?;
+        Ok(StatelessOutcome {
+            execution_result,
+            pre_root: self.witness.pre_root,
+            post_root,
+        })
+    }
+}
+
+/// Checks the bytecode against the code hash of the account, rWASM code hash has priority
+/// over the source code hash (the same way the code is resolved for nested calls), accounts
+/// without code can only run empty bytecode
+fn is_account_code(account: Option<&[[u8; 32]]>, bytecode: &[u8]) -> bool {
+    let Some(fields) = account else {
+        return bytecode.is_empty();
+    };
+    let rwasm_code_hash = fields[JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD as usize];
+    if rwasm_code_hash != [0u8; 32] && rwasm_code_hash != POSEIDON_EMPTY.0 {
+        return rwasm_code_hash == SyscallPoseidon::fn_impl(bytecode);
+    }
+    let source_code_hash = fields[JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD as usize];
+    if source_code_hash != [0u8; 32] && source_code_hash != KECCAK_EMPTY.0 {
+        return source_code_hash == SyscallKeccak256::fn_impl(bytecode);
+    }
+    bytecode.is_empty()
+}
+
+/// Checks that the proof is a path from the root along the key bits that ends with the
+/// terminal node (empty node or leaf), nodes of the valid path are inserted into the database
+fn verify_proof(
+    root: &[u8; 32],
+    key: &[u8; 32],
+    proof: &[Vec<u8>],
+    db: &mut InMemoryTrieDb,
+) -> Result<(), StatelessError> {
+    let invalid_proof = || StatelessError::InvalidProof { key: *key };
+    let node_key: Hash = to_secure_key::<PoseidonHash>(key)
+        .map_err(|_| invalid_proof())?
+        .into();
+    let mut next_hash = Hash::from_bytes(root);
+    let mut level = 0;
+    for buf in proof.iter() {
+        // zero hash is an empty subtree, it doesn't have a node
+        if next_hash.is_zero() {
+            return Ok(());
+        }
+        let node = match decode_smt_proofs::<PoseidonHash>(buf).map_err(|_| invalid_proof())? {
+            Some(node) => node,
+            None => break,
+        };
+        if node.hash() != &next_hash {
+            return Err(invalid_proof());
+        }
+        db.update_node(node.hash().raw_bytes(), node.canonical_value().into());
+        match node.value() {
+            NodeValue::Empty | NodeValue::Leaf(_) => return Ok(()),
+            NodeValue::Branch(branch) => {
+                next_hash = if test_bit(node_key.raw_bytes(), level) {
+                    *branch.right.hash()
+                } else {
+                    *branch.left.hash()
+                };
+                level += 1;
+            }
+        }
+    }
+    if next_hash.is_zero() {
+        return Ok(());
+    }
+    Err(invalid_proof())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        instruction::poseidon::SyscallPoseidon,
+        replay::ReplayCall,
+        stateless::{ExecutionWitness, StatelessError, StatelessRuntime},
+        tests::wat2rwasm,
+        types::InMemoryTrieDb,
+        zktrie::ZkTrieStateDb,
+        TrieStorage,
+    };
+    use fluentbase_types::{
+        Address,
+        Fuel,
+        JZKT_ACCOUNT_COMPRESSION_FLAGS,
+        JZKT_ACCOUNT_FIELDS_COUNT,
+        JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+    };
+
+    #[test]
+    fn test_stateless_execution() {
+        // reads the leaf `key` into the output and overwrites it with the input
+        let rwasm_binary = wat2rwasm(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (param i32 i32 i32)))
+  (type (;2;) (func (result i32)))
+  (type (;3;) (func))
+  (type (;4;) (func (param i32 i32 i32 i32)))
+  (type (;5;) (func (param i32 i32 i32 i32) (result i32)))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_read" (func $_read (type 1)))
+  (import "fluentbase_v1preview" "_update_leaf" (func $_update_leaf (type 4)))
+  (import "fluentbase_v1preview" "_get_leaf" (func $_get_leaf (type 5)))
+  (func $main (type 3)
+    i32.const 32
+    i32.const 0
+    i32.const 32
+    call $_read
+    i32.const 64
+    i32.const 0
+    i32.const 96
+    i32.const 0
+    call $_get_leaf
+    drop
+    i32.const 64
+    i32.const 0
+    i32.const 32
+    i32.const 1
+    call $_update_leaf
+    i32.const 96
+    i32.const 32
+    call $_write
+    )
+  (data (;0;) (i32.const 64) "key")
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+        );
+        let address = Address::with_last_byte(0xcc);
+        let mut fields = vec![[0u8; 32]; JZKT_ACCOUNT_FIELDS_COUNT as usize];
+        fields[JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD as usize] =
+            SyscallPoseidon::fn_impl(&rwasm_binary);
+        let call = ReplayCall {
+            bytecode: rwasm_binary.into(),
+            input: [7u8; 32].to_vec(),
+            fuel_limit: Fuel(1_000_000),
+            ..Default::default()
+        };
+        let mut storage = ZkTrieStateDb::new_empty(InMemoryTrieDb::default());
+        storage.update(&[1u8; 32], 0, &vec![[2u8; 32]]).unwrap();
+        storage.update(&[3u8; 32], 0, &vec![[4u8; 32]]).unwrap();
+        storage
+            .update(
+                address.into_word().as_slice(),
+                JZKT_ACCOUNT_COMPRESSION_FLAGS,
+                &fields,
+            )
+            .unwrap();
+
+        // the call must run the code of the account
+        let mut foreign_call = call.clone();
+        foreign_call.bytecode =
+            wat2rwasm("(module (func $main) (export \"main\" (func $main)))").into();
+        assert_eq!(
+            ExecutionWitness::record(foreign_call.clone(), address, storage.clone()).err(),
+            Some(StatelessError::CodeHashMismatch { address })
+        );
+
+        let (witness, execution_result, jzkt) =
+            ExecutionWitness::record(call, address, storage).unwrap();
+        assert_eq!(execution_result.exit_code, 0);
+        let (post_root, _) = jzkt.commit().unwrap();
+        assert_ne!(witness.pre_root, post_root);
+        assert_eq!(
+            ExecutionWitness::from_bytes(&witness.to_bytes()).unwrap(),
+            witness
+        );
+
+        let outcome = StatelessRuntime::new(witness.clone()).execute().unwrap();
+        assert_eq!(outcome.execution_result.exit_code, 0);
+        assert_eq!(outcome.pre_root, witness.pre_root);
+        assert_eq!(outcome.post_root, post_root);
+
+        // tampered proof must be rejected
+        let mut tampered = witness.clone();
+        let (key, proof) = tampered.proofs.first_mut().unwrap();
+        proof.first_mut().unwrap()[1] ^= 1;
+        let key = *key;
+        assert_eq!(
+            StatelessRuntime::new(tampered).execute(),
+            Err(StatelessError::InvalidProof { key })
+        );
+
+        // the witness can't substitute the bytecode of the proven account
+        let mut substituted = witness.clone();
+        substituted.call = foreign_call;
+        assert_eq!(
+            StatelessRuntime::new(substituted).execute(),
+            Err(StatelessError::CodeHashMismatch { address })
+        );
+
+        // the account must be proven
+        let mut unproven = witness.clone();
+        unproven
+            .proofs
+            .retain(|(key, _)| key != &address.into_word().0);
+        assert_eq!(
+            StatelessRuntime::new(unproven).execute(),
+            Err(StatelessError::MissingWitness {
+                key: address.into_word().0
+            })
+        );
+
+        // keys without proofs can't be accessed
+        let mut incomplete = witness.clone();
+        let (key, _) = incomplete.proofs.pop().unwrap();
+        assert_eq!(
+            StatelessRuntime::new(incomplete).execute(),
+            Err(StatelessError::MissingWitness { key })
+        );
+    }
+}
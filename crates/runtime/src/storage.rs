@@ -7,6 +7,10 @@ pub trait TrieStorage {
 
     fn get(&self, key: &[u8]) -> Option<(Vec<[u8; 32]>, u32)>;
 
+    /// Reads the value at the committed root, it doesn't depend on the opened root, so it can
+    /// serve readers pinned to an older state
+    fn get_at(&self, root32: &[u8; 32], key: &[u8]) -> Option<(Vec<[u8; 32]>, u32)>;
+
     fn update(
         &mut self,
         key: &[u8],
@@ -14,8 +18,23 @@ pub trait TrieStorage {
         value: &Vec<[u8; 32]>,
     ) -> Result<(), ExitCode>;
 
+    /// Removes the key from the state, how the removal is represented in the trie (an empty
+    /// leaf or an absent key) is defined by the storage
     fn remove(&mut self, key: &[u8]) -> Result<(), ExitCode>;
 
+    /// Deletes the key from the trie, unlike writing zero values the key becomes absent and
+    /// its absence can be proven, it's used to restore keys that were absent before a rejected
+    /// commit
+    fn delete(&mut self, key: &[u8]) -> Result<(), ExitCode> {
+        self.remove(key)
+    }
+
+    /// Drops nodes orphaned by deleted keys from the database and returns number of dropped
+    /// nodes, roots computed before the compaction might become unreadable
+    fn compact(&mut self) -> usize {
+        0
+    }
+
     fn proof(&self, key: &[u8; 32]) -> Option<Vec<Vec<u8>>>;
 
     fn get_preimage(&mut self, key: &[u8]) -> Option<Bytes>;
@@ -27,4 +46,9 @@ pub trait TrieStorage {
     }
 
     fn update_preimage(&mut self, key: &[u8], value: Bytes);
+
+    /// Persists committed changes, it's called once per journal commit
+    fn flush(&mut self) -> Result<(), ExitCode> {
+        Ok(())
+    }
 }
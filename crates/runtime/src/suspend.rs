@@ -0,0 +1,206 @@
+use crate::{
+    instruction::suspend::SyscallSuspend,
+    runtime::{CallMode, CallStep, PauseReason},
+    types::RuntimeError,
+    ExecutionResult,
+    Runtime,
+    RuntimeContext,
+};
+use fluentbase_types::{ExitCode, Fuel, IJournaledTrie, JournalCheckpoint};
+use rwasm::{AsContextMut, Caller, Value};
+
+/// Result of the suspendable call, execution that runs out of fuel is suspended instead of
+/// being finished with `OutOfGas` exit code
+pub enum CallOutcome<DB: IJournaledTrie> {
+    Finished(ExecutionResult),
+    OutOfFuel(SuspendedCall<DB>),
+}
+
+impl<DB: IJournaledTrie> CallOutcome<DB> {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, CallOutcome::Finished(_))
+    }
+
+    pub fn into_result(self) -> Option<ExecutionResult> {
+        match self {
+            CallOutcome::Finished(execution_result) => Some(execution_result),
+            CallOutcome::OutOfFuel(_) => None,
+        }
+    }
+}
+
+/// Handle of the execution that ran out of fuel, the host can top up fuel (for example when a
+/// next part of the cross-chain message gas arrives) and resume the execution.
+///
+/// Engine can't resume from the out of fuel trap, so the execution is deterministically
+/// re-executed from the beginning with the increased fuel limit, all changes made by the
+/// suspended attempt are rolled back before that.
+pub struct SuspendedCall<DB: IJournaledTrie> {
+    runtime_context: RuntimeContext<DB>,
+    checkpoint: Option<JournalCheckpoint>,
+    fuel_limit: Fuel,
+    fuel_consumed: Fuel,
+}
+
+impl<DB: IJournaledTrie + Clone> SuspendedCall<DB> {
+    /// Fuel limit of the suspended attempt (including all top-ups)
+    pub fn fuel_limit(&self) -> Fuel {
+        self.fuel_limit
+    }
+
+    /// Fuel consumed before the suspension
+    pub fn fuel_consumed(&self) -> Fuel {
+        self.fuel_consumed
+    }
+
+    pub fn top_up(&mut self, fuel: Fuel) {
+        self.fuel_limit = self.fuel_limit.saturating_add(fuel);
+    }
+
+    pub fn resume(self) -> Result<CallOutcome<DB>, RuntimeError> {
+        if let (Some(jzkt), Some(checkpoint)) =
+            (self.runtime_context.jzkt.as_ref(), self.checkpoint)
+        {
+            jzkt.rollback(checkpoint);
+            jzkt.release_checkpoint(checkpoint);
+        }
+        Runtime::call_suspendable(self.runtime_context.with_fuel_limit(self.fuel_limit))
+    }
+
+    /// Finishes suspended execution with `OutOfGas` exit code without resuming
+    pub fn abort(self) -> ExecutionResult {
+        if let (Some(jzkt), Some(checkpoint)) =
+            (self.runtime_context.jzkt.as_ref(), self.checkpoint)
+        {
+            jzkt.rollback(checkpoint);
+            jzkt.release_checkpoint(checkpoint);
+        }
+        let mut execution_result = ExecutionResult::new_error(ExitCode::OutOfGas.into_i32());
+        execution_result.fuel_consumed = self.fuel_consumed;
+        execution_result
+    }
+}
+
+impl<DB: IJournaledTrie + Clone> Runtime<DB> {
+    /// Executes the context and suspends it if it runs out of fuel, state changes of the
+    /// suspended execution stay in the journal until it's resumed or aborted
+    pub fn call_suspendable(
+        mut runtime_context: RuntimeContext<DB>,
+    ) -> Result<CallOutcome<DB>, RuntimeError> {
+        // resolve hash once to let all attempts reuse the same cached module
+        runtime_context.resolve_bytecode();
+        let checkpoint = runtime_context.jzkt.as_ref().map(|jzkt| jzkt.checkpoint());
+        let execution_result = Self::new(runtime_context.clone()).call()?;
+        if execution_result.exit_code != ExitCode::OutOfGas.into_i32() {
+            if let (Some(jzkt), Some(checkpoint)) = (runtime_context.jzkt.as_ref(), checkpoint) {
+                jzkt.release_checkpoint(checkpoint);
+            }
+            return Ok(CallOutcome::Finished(execution_result));
+        }
+        Ok(CallOutcome::OutOfFuel(SuspendedCall {
+            fuel_limit: runtime_context.fuel_limit,
+            fuel_consumed: execution_result.fuel_consumed,
+            runtime_context,
+            checkpoint,
+        }))
+    }
+}
+
+/// Result of the interruptible call
+pub enum ExecutionOutcome {
+    Finished(ExecutionResult),
+    /// Execution is paused by the `_suspend` syscall, the payload is the guest's request
+    Suspended(Vec<u8>),
+}
+
+impl ExecutionOutcome {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, ExecutionOutcome::Finished(_))
+    }
+
+    pub fn into_result(self) -> Option<ExecutionResult> {
+        match self {
+            ExecutionOutcome::Finished(execution_result) => Some(execution_result),
+            ExecutionOutcome::Suspended(_) => None,
+        }
+    }
+}
+
+impl<DB: IJournaledTrie> Runtime<DB> {
+    /// Executes the call that can be paused by the `_suspend` syscall, the request is surfaced
+    /// to the embedder that resumes the execution with [`Runtime::resume_with`] once the
+    /// response is ready (for example an oracle answer or a result of an async bridge).
+    ///
+    /// Only the top-level call can be suspended, `_suspend` fails with `NotSupportedCall` in
+    /// nested calls and in calls executed with [`Runtime::call`].
+    pub fn call_interruptible(&mut self) -> Result<ExecutionOutcome, RuntimeError> {
+        self.call_mode = CallMode::Interruptible;
+        self.paused = None;
+        if self.store.data().depth == 0 {
+            self.store.data().arena.reset();
+        }
+        let step = self.call_inner();
+        self.complete_step(step)
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.pending_request().is_some()
+    }
+
+    /// Request of the suspended execution
+    pub fn pending_request(&self) -> Option<&[u8]> {
+        match self.paused.as_ref().map(|paused| &paused.reason) {
+            Some(PauseReason::Suspend(request)) => Some(request.request.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Resumes the suspended execution, the response is written into the buffer provided by the
+    /// guest and is also available as the return data. Fails with `NotPaused` if the execution
+    /// isn't suspended.
+    pub fn resume_with(&mut self, response: &[u8]) -> Result<ExecutionOutcome, RuntimeError> {
+        if !self.is_suspended() {
+            return Err(RuntimeError::NotPaused);
+        }
+        let paused = self.paused.take().ok_or(RuntimeError::NotPaused)?;
+        let PauseReason::Suspend(request) = &paused.reason else {
+            return Err(RuntimeError::NotPaused);
+        };
+        let instance = self.instance.ok_or_else(|| self.missing_entrypoint())?;
+        let exit_code = SyscallSuspend::fn_continue(
+            Caller::new(&mut self.store, Some(&instance)),
+            request,
+            response,
+        )
+        .unwrap_or_else(|exit_code| {
+            exit_code
+                .i32_exit_status()
+                .unwrap_or(ExitCode::UnknownError.into_i32())
+        });
+        let next_result = paused
+            .invocation
+            .resume(
+                self.store.as_context_mut(),
+                &[Value::I32(exit_code)],
+                &mut self.call_results,
+            )
+            .map_err(Into::<RuntimeError>::into);
+        let step = self.drive(paused.checkpoint, next_result);
+        self.complete_step(step)
+    }
+
+    fn complete_step(
+        &mut self,
+        step: Result<CallStep, RuntimeError>,
+    ) -> Result<ExecutionOutcome, RuntimeError> {
+        let result = match step {
+            Ok(CallStep::Paused) => {
+                let request = self.pending_request().unwrap_or_default().to_vec();
+                return Ok(ExecutionOutcome::Suspended(request));
+            }
+            Ok(CallStep::Finished(execution_result)) => Ok(execution_result),
+            Err(err) => Err(err),
+        };
+        self.finish_call(result).map(ExecutionOutcome::Finished)
+    }
+}
@@ -1,19 +1,82 @@
-use crate::{runtime::Runtime, DefaultEmptyRuntimeDatabase, RuntimeContext};
+use crate::{
+    artifact_store::ModuleArtifactStore,
+    assertion::AssertionKind,
+    breakpoint::ResumableExecution,
+    capability::{Capabilities, EscalationPolicy},
+    clear_module_caches,
+    deploy::{CodeSizeLimits, MAX_CODE_SIZE},
+    envelope::{InputDecryptor, InputEnvelope, RevealAccess},
+    fuel_policy::FuelPolicy,
+    import::contract_storage_key,
+    instruction::{
+        emit_log::SyscallEmitLog,
+        keccak256::SyscallKeccak256,
+        poseidon::SyscallPoseidon,
+    },
+    invalidate_module,
+    nested_call_fuel_limit,
+    output_stream::{ChannelOutputSink, OutputSink, OutputStream},
+    policy::BytecodePolicy,
+    precompile::PrecompileRegistry,
+    runtime::Runtime,
+    set_module_cache_capacity,
+    types::RuntimeError,
+    warm_up_modules,
+    CallOutcome,
+    DefaultEmptyRuntimeDatabase,
+    ExecutionOutcome,
+    MemoryInitMode,
+    RuntimeContext,
+    RuntimeGasFees,
+    RuntimeStackLimits,
+};
+use fluentbase_poseidon::poseidon_hash;
 use fluentbase_types::{
+    address,
+    contracts::{PRECOMPILE_EVM, PRECOMPILE_IDENTITY},
     create_sovereign_import_linker,
+    split_metadata,
+    Address,
+    Bytes,
+    ContractMetadata,
+    ExitCode,
+    Fuel,
+    FuelSchedule,
+    Gas,
+    IJournaledTrie,
+    IntrinsicCost,
+    IntrinsicCostSchedule,
+    JournalCheckpoint,
+    MetadataError,
+    SysFuncIdx,
     SysFuncIdx::STATE,
+    SystemContractView,
+    B256,
+    F254,
+    HOST_ABI_VERSION,
+    JZKT_ACCOUNT_COMPRESSION_FLAGS,
+    JZKT_ACCOUNT_FIELDS_COUNT,
+    JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+    JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD,
+    METADATA_MAGIC,
     STATE_DEPLOY,
     STATE_MAIN,
+    U256,
 };
 use hex_literal::hex;
 use rwasm::{
     engine::{bytecode::Instruction, RwasmConfig, StateRouterConfig},
     rwasm::{BinaryFormat, RwasmModule},
+    Value,
 };
+use std::sync::{Arc, Mutex};
 
 pub(crate) fn wat2rwasm(wat: &str) -> Vec<u8> {
+    wasm2rwasm(&wat::parse_str(wat).unwrap())
+}
+
+pub(crate) fn wasm2rwasm(wasm_binary: &[u8]) -> Vec<u8> {
     let import_linker = Runtime::new_sovereign_linker();
-    let wasm_binary = wat::parse_str(wat).unwrap();
     let mut rwasm_config = RwasmModule::default_config(Some(import_linker));
     rwasm_config.rwasm_config(RwasmConfig {
         state_router: Some(StateRouterConfig {
@@ -27,7 +90,7 @@ pub(crate) fn wat2rwasm(wat: &str) -> Vec<u8> {
         import_linker: Some(create_sovereign_import_linker()),
         wrap_import_functions: true,
     });
-    let rwasm_module = RwasmModule::compile_with_config(&wasm_binary, &rwasm_config).unwrap();
+    let rwasm_module = RwasmModule::compile_with_config(wasm_binary, &rwasm_config).unwrap();
     let mut result = Vec::new();
     rwasm_module.write_binary_to_vec(&mut result).unwrap();
     result
@@ -127,3 +190,2125 @@ fn test_keccak256() {
         execution_result.output.as_slice()
     );
 }
+
+/// Contract that returns "Hello, World" as the deployed code and does nothing on main
+fn hello_world_deployer() -> Vec<u8> {
+    wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $deploy (type 1)
+    i32.const 0
+    i32.const 12
+    call $_write
+    )
+  (func $main (type 1))
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "Hello, World")
+  (export "deploy" (func $deploy))
+  (export "main" (func $main)))
+    "#,
+    )
+}
+
+#[test]
+fn test_deploy_stores_bytecode() {
+    let rwasm_binary = hello_world_deployer();
+    let jzkt = DefaultEmptyRuntimeDatabase::default();
+    let deployer = address!("1231238908230948230948209348203984029834");
+    let ctx = RuntimeContext::new(rwasm_binary)
+        .with_fuel_limit(1_000_000)
+        .with_jzkt(jzkt.clone());
+    let deploy_result = Runtime::deploy(ctx, &deployer, 0).unwrap();
+    assert!(deploy_result.is_ok());
+    assert_eq!(deploy_result.address, deployer.create(0));
+    let (fields, _, _) = jzkt.get(&deploy_result.address.into_word(), false).unwrap();
+    let rwasm_code_hash = fields[JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD as usize];
+    assert_eq!(deploy_result.rwasm_code_hash, rwasm_code_hash);
+    assert_eq!(jzkt.preimage(&rwasm_code_hash), b"Hello, World".to_vec());
+}
+
+#[test]
+fn test_intrinsic_cost() {
+    let rwasm_binary = hello_world_deployer();
+    let schedule = IntrinsicCostSchedule::new()
+        .with_activation(10, IntrinsicCost::new(4, 16, 10))
+        .with_activation(20, IntrinsicCost::ETHEREUM);
+    assert_eq!(schedule.at(9), IntrinsicCost::ZERO);
+    assert_eq!(schedule.at(15), IntrinsicCost::new(4, 16, 10));
+    assert_eq!(schedule.at(20), IntrinsicCost::ETHEREUM);
+    let intrinsic_cost = schedule.at(10);
+
+    // input is charged for the top-level call only
+    let run = |intrinsic_cost: IntrinsicCost, fuel_limit: u64| {
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+            .with_input(vec![0, 1, 2])
+            .with_fuel_limit(fuel_limit)
+            .with_intrinsic_cost(intrinsic_cost);
+        Runtime::run_with_context(ctx).unwrap()
+    };
+    let execution_result = run(IntrinsicCost::ZERO, 1_000_000);
+    let charged_execution_result = run(intrinsic_cost, 1_000_000);
+    assert_eq!(charged_execution_result.exit_code, ExitCode::Ok.into_i32());
+    assert_eq!(
+        charged_execution_result.fuel_consumed,
+        execution_result.fuel_consumed + Fuel(4 + 16 * 2)
+    );
+    let execution_result = run(intrinsic_cost, 35);
+    assert_eq!(execution_result.exit_code, ExitCode::OutOfGas.into_i32());
+    assert_eq!(execution_result.fuel_consumed, Fuel(35));
+
+    // deployed code is charged after the init code
+    let deploy = |intrinsic_cost: IntrinsicCost, fuel_limit: u64| {
+        let jzkt = DefaultEmptyRuntimeDatabase::default();
+        let ctx = RuntimeContext::new(rwasm_binary.clone())
+            .with_fuel_limit(fuel_limit)
+            .with_jzkt(jzkt.clone())
+            .with_intrinsic_cost(intrinsic_cost);
+        let deployer = address!("1231238908230948230948209348203984029834");
+        (Runtime::deploy(ctx, &deployer, 0).unwrap(), jzkt)
+    };
+    let (deploy_result, _) = deploy(IntrinsicCost::ZERO, 1_000_000);
+    let (charged_deploy_result, _) = deploy(IntrinsicCost::new(0, 0, 10), 1_000_000);
+    assert!(charged_deploy_result.is_ok());
+    assert_eq!(
+        charged_deploy_result.execution_result.fuel_consumed,
+        deploy_result.execution_result.fuel_consumed + Fuel(12 * 10)
+    );
+    let fuel_limit = deploy_result.execution_result.fuel_consumed.get() + 12 * 10 - 1;
+    let (deploy_result, jzkt) = deploy(IntrinsicCost::new(0, 0, 10), fuel_limit);
+    assert_eq!(
+        deploy_result.execution_result.exit_code,
+        ExitCode::OutOfGas.into_i32()
+    );
+    assert!(jzkt
+        .get(&deploy_result.address.into_word(), false)
+        .is_none());
+}
+
+#[test]
+fn test_code_size_limits() {
+    let rwasm_binary = hello_world_deployer();
+    let init_code_size = rwasm_binary.len();
+    let deploy = |code_size_limits: CodeSizeLimits| {
+        let jzkt = DefaultEmptyRuntimeDatabase::default();
+        let ctx = RuntimeContext::new(rwasm_binary.clone())
+            .with_fuel_limit(1_000_000)
+            .with_jzkt(jzkt.clone())
+            .with_code_size_limits(code_size_limits);
+        let deployer = address!("1231238908230948230948209348203984029834");
+        let deploy_result = Runtime::deploy(ctx, &deployer, 0).unwrap();
+        let is_deployed = jzkt
+            .get(&deploy_result.address.into_word(), false)
+            .is_some();
+        (deploy_result, is_deployed)
+    };
+    let (deploy_result, is_deployed) = deploy(CodeSizeLimits::DEFAULT);
+    assert!(deploy_result.is_ok() && is_deployed);
+
+    // init code is charged per word before the execution
+    let (charged_deploy_result, _) = deploy(CodeSizeLimits::ETHEREUM);
+    assert!(charged_deploy_result.is_ok());
+    assert_eq!(
+        charged_deploy_result.execution_result.fuel_consumed,
+        deploy_result.execution_result.fuel_consumed
+            + CodeSizeLimits::ETHEREUM.init_code_fuel(init_code_size)
+    );
+    assert_eq!(CodeSizeLimits::ETHEREUM.init_code_fuel(33), Fuel(4));
+
+    let (deploy_result, is_deployed) =
+        deploy(CodeSizeLimits::new(MAX_CODE_SIZE, init_code_size - 1, 0));
+    assert_eq!(
+        deploy_result.execution_result.exit_code,
+        ExitCode::InitCodeSizeLimit.into_i32()
+    );
+    assert_eq!(
+        deploy_result.execution_result.fuel_consumed,
+        Fuel(1_000_000)
+    );
+    assert!(!is_deployed);
+
+    let (deploy_result, is_deployed) = deploy(CodeSizeLimits::new(11, usize::MAX, 0));
+    assert_eq!(
+        deploy_result.execution_result.exit_code,
+        ExitCode::ContractSizeLimit.into_i32()
+    );
+    assert!(!is_deployed);
+}
+
+#[test]
+fn test_failed_deploy_releases_checkpoint() {
+    let rwasm_binary = hello_world_deployer();
+    let deployer = address!("1231238908230948230948209348203984029834");
+    let deploy = |bytecode_policy: BytecodePolicy| {
+        let jzkt = DefaultEmptyRuntimeDatabase::default();
+        let ctx = RuntimeContext::new(rwasm_binary.clone())
+            .with_fuel_limit(1_000_000)
+            .with_jzkt(jzkt.clone())
+            .with_bytecode_policy(bytecode_policy);
+        (Runtime::deploy(ctx, &deployer, 0), jzkt)
+    };
+
+    // returned "Hello, World" isn't a valid rWASM binary, the init code is still charged
+    let (deploy_result, jzkt) = deploy(BytecodePolicy::new());
+    let deploy_result = deploy_result.unwrap();
+    assert_eq!(
+        deploy_result.execution_result.exit_code,
+        ExitCode::CompilationError.into_i32()
+    );
+    assert!(deploy_result.execution_result.output.is_empty());
+    assert!(deploy_result.execution_result.fuel_consumed > Fuel(0));
+    assert_eq!(
+        deploy_result.gas_used,
+        FuelSchedule::default().fuel_to_gas(deploy_result.execution_result.fuel_consumed)
+    );
+    assert!(jzkt
+        .get(&deploy_result.address.into_word(), false)
+        .is_none());
+    assert!(!jzkt.is_valid_checkpoint(JournalCheckpoint(0, 0)));
+
+    // init code rejected by the policy fails before the execution
+    let (deploy_result, jzkt) = deploy(BytecodePolicy::new().with_max_function_count(0));
+    assert!(matches!(
+        deploy_result,
+        Err(RuntimeError::PolicyViolation(_))
+    ));
+    assert!(!jzkt.is_valid_checkpoint(JournalCheckpoint(0, 0)));
+}
+
+#[test]
+fn test_estimate_fuel() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (func $main
+    (local $i i32)
+    (loop $continue
+      local.get $i
+      i32.const 1
+      i32.add
+      local.tee $i
+      i32.const 100
+      i32.lt_u
+      br_if $continue)
+    )
+  (export "main" (func $main)))
+    "#,
+    );
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+        .with_fuel_limit(1_000_000);
+    let fuel_estimate = Runtime::estimate_fuel(ctx).unwrap();
+    let ctx = RuntimeContext::new(rwasm_binary.clone()).with_fuel_limit(fuel_estimate);
+    let execution_result = Runtime::<DefaultEmptyRuntimeDatabase>::run_with_context(ctx).unwrap();
+    assert_eq!(execution_result.exit_code, 0);
+    let ctx = RuntimeContext::new(rwasm_binary).with_fuel_limit(fuel_estimate - Fuel(1));
+    let execution_result = Runtime::<DefaultEmptyRuntimeDatabase>::run_with_context(ctx).unwrap();
+    assert_ne!(execution_result.exit_code, 0);
+}
+
+#[test]
+fn test_nested_call_fuel_limit() {
+    // zero limit requests everything except the withheld 64th
+    assert_eq!(nested_call_fuel_limit(Fuel(0), Fuel(6400)), Fuel(6300));
+    assert_eq!(nested_call_fuel_limit(Fuel(1000), Fuel(6400)), Fuel(1000));
+    assert_eq!(nested_call_fuel_limit(Fuel(10_000), Fuel(6400)), Fuel(6300));
+    assert_eq!(nested_call_fuel_limit(Fuel(0), Fuel(63)), Fuel(63));
+    assert_eq!(nested_call_fuel_limit(Fuel(0), Fuel(0)), Fuel(0));
+}
+
+#[test]
+fn test_fuel_schedule_conversions() {
+    let schedule = FuelSchedule::new(1000);
+    assert_eq!(schedule.gas_to_fuel(Gas(21)), Fuel(21_000));
+    assert_eq!(schedule.fuel_to_gas(Fuel(21_000)), Gas(21));
+    // partially consumed gas unit is charged in full
+    assert_eq!(schedule.fuel_to_gas(Fuel(21_001)), Gas(22));
+    assert_eq!(schedule.gas_to_fuel(Gas::MAX), Fuel::MAX);
+    let schedule = FuelSchedule::default();
+    assert_eq!(schedule.fuel_to_gas(schedule.gas_to_fuel(Gas(7))), Gas(7));
+}
+
+#[test]
+fn test_resume_after_fuel_top_up() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (func $main
+    (local $i i32)
+    (loop $continue
+      local.get $i
+      i32.const 1
+      i32.add
+      local.tee $i
+      i32.const 100
+      i32.lt_u
+      br_if $continue)
+    )
+  (export "main" (func $main)))
+    "#,
+    );
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary).with_fuel_limit(100);
+    let mut suspended_call = match Runtime::call_suspendable(ctx).unwrap() {
+        CallOutcome::OutOfFuel(suspended_call) => suspended_call,
+        CallOutcome::Finished(_) => panic!("execution must run out of fuel"),
+    };
+    assert!(suspended_call.fuel_consumed() > Fuel::ZERO);
+    suspended_call.top_up(Fuel(1_000_000));
+    let execution_result = suspended_call.resume().unwrap().into_result().unwrap();
+    assert_eq!(execution_result.exit_code, 0);
+}
+
+#[test]
+fn test_fuel_remaining_and_consumed() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (result i64)))
+  (type (;2;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_fuel_remaining" (func $_fuel_remaining (type 1)))
+  (import "fluentbase_v1preview" "_fuel_consumed" (func $_fuel_consumed (type 1)))
+  (func $main (type 2)
+    i32.const 0
+    call $_fuel_remaining
+    i64.store
+    i32.const 8
+    call $_fuel_consumed
+    i64.store
+    i32.const 0
+    i32.const 16
+    call $_write
+    )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+    );
+    let ctx =
+        RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary).with_fuel_limit(1_000_000);
+    let execution_result = Runtime::run_with_context(ctx).unwrap();
+    assert_eq!(execution_result.exit_code, 0);
+    let fuel_remaining = u64::from_le_bytes(execution_result.output[0..8].try_into().unwrap());
+    let fuel_consumed = u64::from_le_bytes(execution_result.output[8..16].try_into().unwrap());
+    assert!(fuel_remaining > 0 && fuel_remaining < 1_000_000);
+    assert!(fuel_consumed > 0 && fuel_consumed < 1_000_000);
+    // fuel consumed is read after fuel remaining
+    assert!(fuel_remaining + fuel_consumed >= 1_000_000);
+}
+
+#[test]
+fn test_fuel_limit_above_u32() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (result i64)))
+  (type (;2;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_fuel_remaining" (func $_fuel_remaining (type 1)))
+  (func $main (type 2)
+    i32.const 0
+    call $_fuel_remaining
+    i64.store
+    i32.const 0
+    i32.const 8
+    call $_write
+    )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+    );
+    let fuel_limit = Fuel(u32::MAX as u64 * 4);
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+        .with_fuel_limit(fuel_limit);
+    let execution_result = Runtime::run_with_context(ctx).unwrap();
+    assert_eq!(execution_result.exit_code, 0);
+    let fuel_remaining = u64::from_le_bytes(execution_result.output[0..8].try_into().unwrap());
+    assert!(fuel_remaining > u32::MAX as u64 && fuel_remaining < fuel_limit.get());
+    assert!(execution_result.fuel_consumed < fuel_limit);
+    // 32-bit fuel slots of nested calls saturate
+    assert_eq!(fuel_limit.saturating_u32(), u32::MAX);
+    assert_eq!(Fuel(7).saturating_u32(), 7);
+}
+
+#[test]
+fn test_nested_remaining_fuel_saturates() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (param i32 i32 i32 i32 i32 i32) (result i32)))
+  (type (;2;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_exec_address" (func $_exec_address (type 1)))
+  (func $main (type 2)
+    i32.const 0
+    i32.const 0
+    i32.const 0
+    i32.const 0
+    i32.const 0
+    i32.const 64
+    call $_exec_address
+    drop
+    i32.const 64
+    i32.const 4
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11")
+  (export "main" (func $main)))
+    "#,
+    );
+    let jzkt = DefaultEmptyRuntimeDatabase::default();
+    let callee = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func))
+  (func $main (type 0))
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+    );
+    write_code(
+        &jzkt,
+        &Address::repeat_byte(0x11),
+        JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+        &callee,
+    );
+    // zero fuel slot forwards all available fuel, that is more than `u32::MAX`
+    let ctx = RuntimeContext::new(rwasm_binary)
+        .with_fuel_limit(Fuel(u32::MAX as u64 * 4))
+        .with_jzkt(jzkt);
+    let execution_result = Runtime::run_with_context(ctx).unwrap();
+    assert_eq!(execution_result.exit_code, 0);
+    assert_eq!(execution_result.output, u32::MAX.to_le_bytes().to_vec());
+}
+
+#[test]
+fn test_fuel_policy_syscall_cost() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 8
+    call $_write
+    i32.const 0
+    i32.const 8
+    call $_write
+    )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+    );
+    let run = |fuel_policy: FuelPolicy, fuel_limit: Fuel| {
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+            .with_fuel_limit(fuel_limit)
+            .with_fuel_policy(fuel_policy);
+        Runtime::run_with_context(ctx).unwrap()
+    };
+    let default_result = run(FuelPolicy::new(), Fuel(1_000_000));
+    assert_eq!(default_result.exit_code, 0);
+    // both host calls are charged
+    let fuel_policy = FuelPolicy::new().with_syscall_cost(SysFuncIdx::WRITE, Fuel(1_000));
+    let priced_result = run(fuel_policy.clone(), Fuel(1_000_000));
+    assert_eq!(priced_result.exit_code, 0);
+    assert_eq!(
+        priced_result.fuel_consumed.get(),
+        default_result.fuel_consumed.get() + 2_000
+    );
+    // not enough fuel for the second call
+    let limited_result = run(
+        fuel_policy,
+        Fuel(default_result.fuel_consumed.get() + 1_500),
+    );
+    assert_eq!(limited_result.exit_code, ExitCode::OutOfGas as i32);
+}
+
+#[test]
+fn test_recursion_limit() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (func $main
+    i32.const 100
+    call $recursive
+    )
+  (func $recursive (param $depth i32)
+    local.get $depth
+    if
+      local.get $depth
+      i32.const 1
+      i32.sub
+      call $recursive
+    end
+    )
+  (export "main" (func $main)))
+    "#,
+    );
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+        .with_fuel_limit(10_000_000);
+    let execution_result = Runtime::run_with_context(ctx).unwrap();
+    assert_eq!(execution_result.exit_code, 0);
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+        .with_fuel_limit(10_000_000)
+        .with_stack_limits(RuntimeStackLimits::new(1024, 1024 * 1024, 50));
+    let execution_result = Runtime::run_with_context(ctx).unwrap();
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::StackOverflow.into_i32()
+    );
+    // cached stacks are a part of the engine config, so the results don't depend on them
+    let stack_limits = RuntimeStackLimits::default().with_cached_stacks(16);
+    for _ in 0..3 {
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+            .with_fuel_limit(10_000_000)
+            .with_stack_limits(stack_limits);
+        let execution_result = Runtime::run_with_context(ctx).unwrap();
+        assert_eq!(execution_result.exit_code, 0);
+    }
+}
+
+#[test]
+fn test_deterministic_memory_init() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 32
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "Hello, World")
+  (export "main" (func $main)))
+    "#,
+    );
+    let execution_results = (0..2)
+        .map(|_| {
+            let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+                .with_fuel_limit(1_000_000)
+                .with_memory_init_mode(MemoryInitMode::Deterministic);
+            Runtime::run_with_context(ctx).unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(execution_results[0].exit_code, 0);
+    assert_eq!(&execution_results[0].output[..12], b"Hello, World");
+    assert_eq!(&execution_results[0].output[12..], &[0u8; 20]);
+    assert_eq!(execution_results[0].output, execution_results[1].output);
+}
+
+#[test]
+fn test_uninitialized_memory_read() {
+    let wat = |offset: u32| {
+        format!(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 100
+    i32.const 7
+    i32.store
+    i32.const {}
+    i32.const 4
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "Hello, World")
+  (export "main" (func $main)))
+    "#,
+            offset
+        )
+    };
+    // reads of data segments and written memory are fine
+    for offset in [0, 100] {
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(wat2rwasm(&wat(offset)))
+            .with_fuel_limit(1_000_000);
+        let execution_result = Runtime::call_with_memory_assertions(ctx).unwrap();
+        assert_eq!(execution_result.exit_code, 0);
+    }
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(wat2rwasm(&wat(200)))
+        .with_fuel_limit(1_000_000);
+    assert!(matches!(
+        Runtime::call_with_memory_assertions(ctx),
+        Err(RuntimeError::UninitializedMemoryRead)
+    ));
+}
+
+#[test]
+fn test_revert_and_trap_output() {
+    let wat = |exit: &str| {
+        format!(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (param i32)))
+  (type (;2;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_exit" (func $_exit (type 1)))
+  (func $main (type 2)
+    i32.const 0
+    i32.const 12
+    call $_write
+    {}
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "Hello, World")
+  (export "main" (func $main)))
+    "#,
+            exit
+        )
+    };
+    let run = |exit: String| {
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(wat2rwasm(&wat(&exit)))
+            .with_fuel_limit(1_000_000);
+        Runtime::run_with_context(ctx).unwrap()
+    };
+    let execution_result = run(format!(
+        "i32.const {}\n    call $_exit",
+        ExitCode::Revert.into_i32()
+    ));
+    assert!(execution_result.is_deliberate_revert() && !execution_result.is_panic());
+    assert_eq!(execution_result.output, b"Hello, World".to_vec());
+    let execution_result = run("unreachable".to_string());
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::UnreachableCodeReached.into_i32()
+    );
+    assert!(execution_result.is_panic() && !execution_result.is_deliberate_revert());
+    // any failure still reverts the state changes
+    assert!(ExitCode::from(execution_result.exit_code).is_revert());
+    assert!(ExitCode::Revert.is_revert() && !ExitCode::Ok.is_revert());
+    assert!(execution_result.output.is_empty());
+}
+
+#[test]
+fn test_static_context_write_protection() {
+    // commit and rollback change the state of the callers, so they are protected too
+    let run = |import: &str, call: &str| {
+        let rwasm_binary = wat2rwasm(&format!(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32 i32 i32 i32)))
+  (type (;1;) (func (param i32)))
+  (type (;2;) (func (param i64)))
+  (type (;3;) (func))
+  {}
+  (func $main (type 3)
+    {}
+    )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+            import, call
+        ));
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+            .with_fuel_limit(1_000_000)
+            .with_is_static(true);
+        Runtime::run_with_context(ctx).unwrap()
+    };
+    for (import, call) in [
+        (
+            r#"(import "fluentbase_v1preview" "_emit_log" (func $_emit_log (type 0)))"#,
+            "i32.const 0 i32.const 0 i32.const 0 i32.const 0 i32.const 0 call $_emit_log",
+        ),
+        (
+            r#"(import "fluentbase_v1preview" "_commit" (func $_commit (type 1)))"#,
+            "i32.const 0 call $_commit",
+        ),
+        (
+            r#"(import "fluentbase_v1preview" "_rollback" (func $_rollback (type 2)))"#,
+            "i64.const 0 call $_rollback",
+        ),
+    ] {
+        let execution_result = run(import, call);
+        assert_eq!(
+            execution_result.exit_code,
+            ExitCode::WriteProtection.into_i32()
+        );
+    }
+}
+
+#[test]
+fn test_emit_log_too_many_topics() {
+    let run = |topics32s_len: u32| {
+        let rwasm_binary = wat2rwasm(&format!(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32 i32 i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_emit_log" (func $_emit_log (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 0
+    i32.const {topics32s_len}
+    i32.const 0
+    i32.const 0
+    call $_emit_log
+    )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#
+        ));
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+            .with_fuel_limit(1_000_000)
+            .with_jzkt(DefaultEmptyRuntimeDatabase::default());
+        Runtime::run_with_context(ctx).unwrap()
+    };
+    // LOG opcodes can't have more than 4 topics
+    let execution_result = run(160);
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::TooManyLogTopics.into_i32()
+    );
+    assert!(execution_result.logs.is_empty());
+    // length that isn't a multiple of 32 bytes is malformed, even if it's small enough
+    let execution_result = run(33);
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::MalformedLogTopics.into_i32()
+    );
+    assert!(execution_result.logs.is_empty());
+    // the native SDK passes decoded topics, so they're checked again
+    let mut ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(vec![])
+        .with_jzkt(DefaultEmptyRuntimeDatabase::default());
+    assert_eq!(
+        SyscallEmitLog::fn_impl(&mut ctx, Address::ZERO, vec![B256::ZERO; 5], Bytes::new()),
+        Err(ExitCode::TooManyLogTopics)
+    );
+}
+
+#[test]
+fn test_blob_hash_and_blob_base_fee() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (param i32)))
+  (type (;2;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_blob_hash" (func $_blob_hash (type 0)))
+  (import "fluentbase_v1preview" "_blob_base_fee" (func $_blob_base_fee (type 1)))
+  (func $main (type 2)
+    i32.const 1
+    i32.const 0
+    call $_blob_hash
+    i32.const 2
+    i32.const 32
+    call $_blob_hash
+    i32.const 64
+    call $_blob_base_fee
+    i32.const 0
+    i32.const 96
+    call $_write
+    )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+    );
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+        .with_fuel_limit(1_000_000)
+        .with_blob_hashes(vec![B256::repeat_byte(0x01), B256::repeat_byte(0x02)])
+        .with_blob_base_fee(U256::from(7));
+    let execution_result = Runtime::run_with_context(ctx).unwrap();
+    assert_eq!(execution_result.exit_code, 0);
+    assert_eq!(&execution_result.output[0..32], &[0x02u8; 32]);
+    // out of bounds index returns zero hash
+    assert_eq!(&execution_result.output[32..64], &[0u8; 32]);
+    assert_eq!(
+        U256::from_le_slice(&execution_result.output[64..96]),
+        U256::from(7)
+    );
+}
+
+#[test]
+fn test_call_export() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 4
+    call $_write
+    )
+  (func $custom (type 1)
+    i32.const 4
+    i32.const 4
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "mainmine")
+  (export "main" (func $main))
+  (export "custom" (func $custom)))
+    "#,
+    );
+    let ctx =
+        RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary).with_fuel_limit(1_000_000);
+    let execution_result = Runtime::run_with_context(ctx.clone()).unwrap();
+    assert_eq!(execution_result.output, b"main".to_vec());
+    let execution_result =
+        Runtime::run_with_context(ctx.clone().with_entrypoint("custom")).unwrap();
+    assert_eq!(execution_result.output, b"mine".to_vec());
+    let mut runtime = Runtime::new(ctx);
+    assert_eq!(
+        runtime.call_export("custom").unwrap().output,
+        b"mine".to_vec()
+    );
+    // the next call invokes the entrypoint of the context again
+    assert_eq!(runtime.store().data().entrypoint(), "main");
+    let execution_result = runtime.call().unwrap();
+    assert_eq!(execution_result.exit_code, 0);
+    assert!(execution_result.output.ends_with(b"main"));
+    let err = runtime.call_export("missing").unwrap_err();
+    assert!(matches!(err, RuntimeError::MissingEntrypoint(name) if name == "missing"));
+    assert_eq!(runtime.store().data().entrypoint(), "main");
+}
+
+#[test]
+fn test_call_with_args() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i64) (result i64)))
+  (type (;1;) (func))
+  (func $main (type 1))
+  (func $add (type 0)
+    local.get 0
+    i64.extend_i32_s
+    local.get 1
+    i64.add
+    )
+  (export "main" (func $main))
+  (export "add" (func $add)))
+    "#,
+    );
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+        .with_fuel_limit(1_000_000)
+        .with_entrypoint("add");
+    let mut runtime = Runtime::new(ctx);
+    let results = runtime
+        .call_with_args(&[Value::I32(-2), Value::I64(5)])
+        .unwrap();
+    assert!(matches!(results[..], [Value::I64(3)]));
+    assert_eq!(runtime.call_typed::<(i32, i64), i64>((40, 2)).unwrap(), 42);
+    // results are checked against the requested types
+    assert!(matches!(
+        runtime.call_typed::<(i32, i64), i32>((40, 2)),
+        Err(RuntimeError::SignatureMismatch)
+    ));
+}
+
+#[test]
+fn test_input_envelope_reveal() {
+    struct XorDecryptor;
+    impl InputDecryptor for XorDecryptor {
+        fn decrypt(&self, _section: u32, ciphertext: &[u8]) -> Option<Vec<u8>> {
+            Some(ciphertext.iter().map(|byte| byte ^ 0x42).collect())
+        }
+    }
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32 i32) (result i32)))
+  (type (;1;) (func (param i32 i32)))
+  (type (;2;) (func))
+  (import "fluentbase_v1preview" "_input_reveal" (func $_input_reveal (type 0)))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 1)))
+  (func $main (type 2)
+    (local i32)
+    i32.const 0
+    i32.const 0
+    i32.const 64
+    call $_input_reveal
+    local.set 0
+    i32.const 0
+    local.get 0
+    call $_write
+    )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+    );
+    let secret = b"secret".iter().map(|byte| byte ^ 0x42).collect::<Vec<_>>();
+    let run = |access: RevealAccess| {
+        let envelope = InputEnvelope::new(b"plain".to_vec()).with_section(secret.clone(), access);
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+            .with_fuel_limit(1_000_000)
+            .with_input_envelope(envelope, Arc::new(XorDecryptor));
+        Runtime::run_with_context(ctx).unwrap()
+    };
+    let execution_result = run(RevealAccess::Anyone);
+    assert_eq!(execution_result.exit_code, ExitCode::Ok.into_i32());
+    assert_eq!(execution_result.output, b"secret".to_vec());
+    // the plaintext is copied into the memory of the contract only
+    assert!(execution_result.return_data.is_empty());
+    let code_hash = F254::from(poseidon_hash(&rwasm_binary));
+    let execution_result = run(RevealAccess::CodeHashes(vec![code_hash]));
+    assert_eq!(execution_result.output, b"secret".to_vec());
+    // the section is authorized for another contract only
+    let execution_result = run(RevealAccess::CodeHashes(vec![F254::repeat_byte(1)]));
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::CapabilityDenied.into_i32()
+    );
+    // empty list doesn't authorize anyone
+    let execution_result = run(RevealAccess::CodeHashes(vec![]));
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::CapabilityDenied.into_i32()
+    );
+}
+
+#[test]
+fn test_resumable_call_breakpoint() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (result i32)))
+  (type (;2;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_input_size" (func $_input_size (type 1)))
+  (func $main (type 2)
+    i32.const 0
+    call $_input_size
+    i32.store
+    i32.const 0
+    i32.const 4
+    call $_write
+    )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+    );
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+        .with_input(vec![1, 2, 3])
+        .with_fuel_limit(1_000_000)
+        .with_breakpoint(SysFuncIdx::INPUT_SIZE);
+    let mut runtime = Runtime::new(ctx.clone());
+    let breakpoint = match runtime.call_resumable().unwrap() {
+        ResumableExecution::Paused(breakpoint) => breakpoint,
+        ResumableExecution::Finished(_) => panic!("execution must be paused"),
+    };
+    assert_eq!(breakpoint.sys_func_idx(), SysFuncIdx::INPUT_SIZE);
+    assert!(matches!(breakpoint.results(), [Value::I32(3)]));
+    assert_eq!(breakpoint.context().input, vec![1, 2, 3]);
+    // the guest gets the host provided result
+    let execution_result = breakpoint
+        .resume_with(vec![Value::I32(7)])
+        .unwrap()
+        .into_result()
+        .unwrap();
+    assert_eq!(execution_result.exit_code, 0);
+    assert_eq!(execution_result.output, 7u32.to_le_bytes().to_vec());
+    assert!(!runtime.is_paused());
+    // the next regular call of the same runtime isn't paused
+    let execution_result = runtime.call().unwrap();
+    assert_eq!(execution_result.exit_code, 0);
+    assert!(!runtime.is_paused());
+    // breakpoints are ignored by the regular call
+    let execution_result = Runtime::run_with_context(ctx).unwrap();
+    assert_eq!(execution_result.output, 3u32.to_le_bytes().to_vec());
+}
+
+#[test]
+fn test_assertion_syscalls() {
+    let run = |expected: &str, max_fuel: u64, debug_assertions: bool| {
+        let rwasm_binary = wat2rwasm(&format!(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32 i32)))
+  (type (;1;) (func (param i64)))
+  (type (;2;) (func))
+  (import "fluentbase_v1preview" "_assert_eq_mem" (func $_assert_eq_mem (type 0)))
+  (import "fluentbase_v1preview" "_expect_fuel_le" (func $_expect_fuel_le (type 1)))
+  (func $main (type 2)
+    i32.const 0
+    i32.const 16
+    i32.const 4
+    call $_assert_eq_mem
+    i64.const {}
+    call $_expect_fuel_le
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "abcd")
+  (data (;1;) (i32.const 16) "{}")
+  (export "main" (func $main)))
+        "#,
+            max_fuel, expected
+        ));
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+            .with_fuel_limit(1_000_000)
+            .with_debug_assertions(debug_assertions);
+        Runtime::<DefaultEmptyRuntimeDatabase>::run_with_context(ctx).unwrap()
+    };
+    // assertions must be enabled explicitly
+    let execution_result = run("abcd", 1_000_000, false);
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::CapabilityDenied.into_i32()
+    );
+    let execution_result = run("abcd", 1_000_000, true);
+    assert_eq!(execution_result.exit_code, ExitCode::Ok.into_i32());
+    assert_eq!(execution_result.assertion_failure, None);
+    // the first mismatched byte is reported
+    let execution_result = run("abXd", 1_000_000, true);
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::AssertionFailed.into_i32()
+    );
+    let assertion_failure = execution_result.assertion_failure.unwrap();
+    assert_eq!(assertion_failure.kind, AssertionKind::EqMem);
+    assert_eq!(assertion_failure.actual, b"abcd".to_vec());
+    assert_eq!(assertion_failure.expected, b"abXd".to_vec());
+    assert!(assertion_failure.message.contains("byte 2 of 4"));
+    // the execution has consumed some fuel before the check
+    let execution_result = run("abcd", 0, true);
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::AssertionFailed.into_i32()
+    );
+    let assertion_failure = execution_result.assertion_failure.unwrap();
+    assert_eq!(assertion_failure.kind, AssertionKind::FuelLe);
+    assert_eq!(assertion_failure.expected, 0u64.to_le_bytes().to_vec());
+    assert!(u64::from_le_bytes(assertion_failure.actual.try_into().unwrap()) > 0);
+}
+
+#[test]
+fn test_suspend_and_resume_with_response() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (param i32 i32 i32 i32) (result i32)))
+  (type (;2;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_suspend" (func $_suspend (type 1)))
+  (func $main (type 2)
+    i32.const 40
+    i32.const 0
+    i32.const 4
+    i32.const 32
+    i32.const 8
+    call $_suspend
+    i32.store
+    i32.const 44
+    i32.const 4
+    i32.const 4
+    i32.const 48
+    i32.const 2
+    call $_suspend
+    i32.store
+    i32.const 32
+    i32.const 16
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "pingpong")
+  (export "main" (func $main)))
+    "#,
+    );
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+        .with_fuel_limit(1_000_000);
+    let mut runtime = Runtime::new(ctx);
+    let outcome = runtime.call_interruptible().unwrap();
+    assert!(matches!(&outcome, ExecutionOutcome::Suspended(request) if request == b"ping"));
+    assert!(runtime.is_suspended());
+    let outcome = runtime.resume_with(b"pong!").unwrap();
+    assert!(matches!(&outcome, ExecutionOutcome::Suspended(request) if request == b"pong"));
+    // response doesn't fit into the buffer
+    let execution_result = runtime
+        .resume_with(b"too long")
+        .unwrap()
+        .into_result()
+        .unwrap();
+    assert_eq!(execution_result.exit_code, 0);
+    assert!(!runtime.is_suspended());
+    assert_eq!(&execution_result.output[0..8], b"pong!\0\0\0");
+    assert_eq!(&execution_result.output[8..12], &0i32.to_le_bytes());
+    assert_eq!(
+        &execution_result.output[12..16],
+        &ExitCode::OutputOverflow.into_i32().to_le_bytes()
+    );
+    assert!(execution_result.fuel_consumed > Fuel::ZERO);
+    assert!(matches!(
+        runtime.resume_with(b"pong"),
+        Err(RuntimeError::NotPaused)
+    ));
+    // the next regular call of the same runtime can't be suspended
+    let execution_result = runtime.call().unwrap();
+    assert_eq!(execution_result.exit_code, 0);
+    assert!(!runtime.is_suspended());
+
+    // regular calls can't be suspended
+    let ctx =
+        RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary).with_fuel_limit(1_000_000);
+    let execution_result = Runtime::run_with_context(ctx.clone()).unwrap();
+    assert_eq!(
+        &execution_result.output[8..12],
+        &ExitCode::NotSupportedCall.into_i32().to_le_bytes()
+    );
+    // resumable calls can't be suspended too
+    let mut runtime = Runtime::new(ctx);
+    let execution_result = runtime.call_resumable().unwrap().into_result().unwrap();
+    assert_eq!(
+        &execution_result.output[8..12],
+        &ExitCode::NotSupportedCall.into_i32().to_le_bytes()
+    );
+}
+
+#[test]
+fn test_return_data_size_and_copy() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (param i32 i32 i32 i32) (result i32)))
+  (type (;2;) (func (result i32)))
+  (type (;3;) (func (param i32 i32 i32)))
+  (type (;4;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_suspend" (func $_suspend (type 1)))
+  (import "fluentbase_v1preview" "_input_size" (func $_input_size (type 2)))
+  (import "fluentbase_v1preview" "_return_data_size" (func $_return_data_size (type 2)))
+  (import "fluentbase_v1preview" "_return_data_copy" (func $_return_data_copy (type 3)))
+  (func $main (type 4)
+    i32.const 0
+    i32.const 4
+    i32.const 0
+    i32.const 0
+    call $_suspend
+    drop
+    i32.const 100
+    call $_return_data_size
+    i32.store
+    i32.const 104
+    i32.const 2
+    i32.const 3
+    call $_return_data_copy
+    ;; empty range at the end of the return data is fine
+    i32.const 0
+    i32.const 8
+    i32.const 0
+    call $_return_data_copy
+    i32.const 100
+    i32.const 7
+    call $_write
+    call $_input_size
+    if
+      i32.const 0
+      i32.const 6
+      i32.const 3
+      call $_return_data_copy
+    end
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "ping")
+  (export "main" (func $main)))
+    "#,
+    );
+    let run = |input: &[u8]| {
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+            .with_input(input.to_vec())
+            .with_fuel_limit(1_000_000);
+        let mut runtime = Runtime::new(ctx);
+        let outcome = runtime.call_interruptible().unwrap();
+        assert!(matches!(&outcome, ExecutionOutcome::Suspended(request) if request == b"ping"));
+        runtime
+            .resume_with(b"response")
+            .unwrap()
+            .into_result()
+            .unwrap()
+    };
+    let execution_result = run(&[]);
+    assert_eq!(execution_result.exit_code, ExitCode::Ok.into_i32());
+    assert_eq!(&execution_result.output[0..4], &8u32.to_le_bytes());
+    assert_eq!(&execution_result.output[4..7], b"spo");
+    // range is out of the return data
+    let execution_result = run(&[1]);
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::ReturnDataOutOfBounds.into_i32()
+    );
+}
+
+#[test]
+fn test_input_copy_windows() {
+    // copies the input in windows of 4 bytes and writes each window with its size
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (param i32 i32 i32) (result i32)))
+  (type (;2;) (func (result i32)))
+  (type (;3;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_input_copy" (func $_input_copy (type 1)))
+  (import "fluentbase_v1preview" "_input_size" (func $_input_size (type 2)))
+  (func $main (type 3)
+    (local $offset i32)
+    (local $length i32)
+    loop $windows
+      i32.const 4
+      local.get $offset
+      i32.const 4
+      call $_input_copy
+      local.set $length
+      i32.const 0
+      local.get $length
+      i32.store8
+      i32.const 0
+      local.get $length
+      i32.const 1
+      i32.add
+      call $_write
+      local.get $offset
+      local.get $length
+      i32.add
+      local.set $offset
+      local.get $offset
+      call $_input_size
+      i32.lt_u
+      br_if $windows
+    end
+    ;; the offset equal to the input size gives an empty window, so the target isn't touched
+    i32.const -16
+    call $_input_size
+    i32.const 8
+    call $_input_copy
+    drop
+    call $_input_size
+    i32.eqz
+    if
+      i32.const 0
+      i32.const 1
+      i32.const 0
+      call $_input_copy
+      drop
+    end
+    )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+    );
+    let run = |input: &[u8]| {
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+            .with_input(input.to_vec())
+            .with_fuel_limit(1_000_000);
+        Runtime::run_with_context(ctx).unwrap()
+    };
+    let execution_result = run(b"abcdefghij");
+    assert_eq!(execution_result.exit_code, ExitCode::Ok.into_i32());
+    assert_eq!(execution_result.output, b"\x04abcd\x04efgh\x02ij".to_vec());
+    // the offset is past the end of the empty input
+    let execution_result = run(b"");
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::InputOutOfBounds.into_i32()
+    );
+}
+
+#[test]
+fn test_incremental_keccak256() {
+    // hashes the input in two chunks split at the middle, the first input byte equal to 1
+    // finalizes the context twice
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (param i32 i32 i32)))
+  (type (;2;) (func (result i32)))
+  (type (;3;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_read" (func $_read (type 1)))
+  (import "fluentbase_v1preview" "_input_size" (func $_input_size (type 2)))
+  (import "fluentbase_v1preview" "_hash_init" (func $_hash_init (type 2)))
+  (import "fluentbase_v1preview" "_hash_update" (func $_hash_update (type 1)))
+  (import "fluentbase_v1preview" "_hash_final" (func $_hash_final (type 0)))
+  (func $main (type 3)
+    (local $handle i32)
+    (local $half i32)
+    i32.const 64
+    i32.const 0
+    call $_input_size
+    call $_read
+    call $_input_size
+    i32.const 2
+    i32.div_u
+    local.set $half
+    call $_hash_init
+    local.tee $handle
+    i32.const 64
+    local.get $half
+    call $_hash_update
+    local.get $handle
+    i32.const 64
+    local.get $half
+    i32.add
+    call $_input_size
+    local.get $half
+    i32.sub
+    call $_hash_update
+    local.get $handle
+    i32.const 0
+    call $_hash_final
+    i32.const 0
+    i32.const 32
+    call $_write
+    i32.const 64
+    i32.load8_u
+    i32.const 1
+    i32.eq
+    if
+      local.get $handle
+      i32.const 0
+      call $_hash_final
+    end
+    )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+    );
+    let run = |input: &[u8]| {
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+            .with_input(input.to_vec())
+            .with_fuel_limit(1_000_000);
+        Runtime::run_with_context(ctx).unwrap()
+    };
+    let input = "Hello, World".as_bytes();
+    let execution_result = run(input);
+    assert_eq!(execution_result.exit_code, ExitCode::Ok.into_i32());
+    assert_eq!(
+        execution_result.output,
+        SyscallKeccak256::fn_impl(input).to_vec()
+    );
+    // fuel is charged per absorbed byte
+    let big_input = [7u8; 1000];
+    let big_execution_result = run(&big_input);
+    assert_eq!(
+        big_execution_result.output,
+        SyscallKeccak256::fn_impl(&big_input).to_vec()
+    );
+    assert!(
+        big_execution_result.fuel_consumed - execution_result.fuel_consumed
+            >= Fuel((big_input.len() - input.len()) as u64)
+    );
+    // finalized context can't be used anymore
+    let execution_result = run(&[1, 2, 3]);
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::HashContextError.into_i32()
+    );
+}
+
+#[test]
+fn test_contract_metadata_section() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 5
+    call $_write)
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "Hello")
+  (export "main" (func $main)))
+    "#,
+    );
+    let metadata = ContractMetadata::new("fluentbase-build/0.1.0")
+        .with_abi_hash(B256::with_last_byte(1))
+        .with_source_hash(B256::with_last_byte(2));
+    let bytecode = metadata.attach_to(&rwasm_binary);
+    let (code, decoded) = split_metadata(&bytecode).unwrap();
+    assert_eq!(code, rwasm_binary.as_slice());
+    assert_eq!(decoded, Some(metadata.clone()));
+    assert_eq!(
+        split_metadata(&rwasm_binary).unwrap(),
+        (rwasm_binary.as_slice(), None)
+    );
+    // payload length must fit into the bytecode
+    let mut corrupted = bytecode.clone();
+    corrupted[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(split_metadata(&corrupted), Err(MetadataError::Malformed));
+    assert_eq!(
+        split_metadata(&bytecode[..6]),
+        Err(MetadataError::Malformed)
+    );
+    // the section must be followed by rWASM code
+    let truncated = &bytecode[..bytecode.len() - rwasm_binary.len()];
+    assert_eq!(split_metadata(truncated), Err(MetadataError::Malformed));
+    // the magic at the end of the code isn't a metadata section
+    let mut trailing_magic = rwasm_binary.clone();
+    trailing_magic.extend_from_slice(&METADATA_MAGIC);
+    assert_eq!(
+        split_metadata(&trailing_magic).unwrap(),
+        (trailing_magic.as_slice(), None)
+    );
+
+    // metadata is stripped before the translation
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(bytecode.clone())
+        .with_fuel_limit(1_000_000)
+        .with_metadata_verifier(|metadata| {
+            if metadata.compiler_version.starts_with("fluentbase-build/") {
+                Ok(())
+            } else {
+                Err("unknown compiler".to_string())
+            }
+        });
+    let execution_result = Runtime::run_with_context(ctx).unwrap();
+    assert_eq!(execution_result.exit_code, ExitCode::Ok.into_i32());
+    assert_eq!(execution_result.output, "Hello".as_bytes().to_vec());
+    // the module is cached now, but the verifier of the next context is still called
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(bytecode.clone())
+        .with_fuel_limit(1_000_000)
+        .with_metadata_verifier(|_| Err("unknown compiler".to_string()));
+    let err = Runtime::run_with_context(ctx).unwrap_err();
+    assert!(matches!(
+        err,
+        RuntimeError::Metadata(MetadataError::Rejected(_))
+    ));
+
+    // contracts that require newer host ABI are rejected
+    let bytecode = metadata
+        .clone()
+        .with_host_abi_version(HOST_ABI_VERSION + 1)
+        .attach_to(&rwasm_binary);
+    let ctx =
+        RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(bytecode).with_fuel_limit(1_000_000);
+    let err = Runtime::run_with_context(ctx).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::IncompatibleMetadata);
+
+    // verification hook can reject the metadata
+    let bytecode = ContractMetadata::new("unknown/1.0").attach_to(&rwasm_binary);
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(bytecode)
+        .with_fuel_limit(1_000_000)
+        .with_metadata_verifier(|_| Err("unknown compiler".to_string()));
+    let err = Runtime::run_with_context(ctx).unwrap_err();
+    assert!(matches!(
+        err,
+        RuntimeError::Metadata(MetadataError::Rejected(_))
+    ));
+}
+
+#[test]
+fn test_write_output_chunk() {
+    // streams "Hello, World" retrying the rest of the data until it's accepted
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32) (result i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write_output_chunk" (func $_write_output_chunk (type 0)))
+  (func $main (type 1)
+    (local $offset i32)
+    (loop $continue
+      local.get $offset
+      local.get $offset
+      i32.const 12
+      local.get $offset
+      i32.sub
+      call $_write_output_chunk
+      i32.add
+      local.tee $offset
+      i32.const 12
+      i32.lt_u
+      br_if $continue))
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "Hello, World")
+  (export "main" (func $main)))
+    "#,
+    );
+    // accepts up to 5 bytes per chunk
+    struct SlowSink(Arc<Mutex<Vec<Vec<u8>>>>);
+    impl OutputSink for SlowSink {
+        fn write_chunk(&mut self, chunk: &[u8]) -> Result<usize, ExitCode> {
+            let len = chunk.len().min(5);
+            self.0.lock().unwrap().push(chunk[..len].to_vec());
+            Ok(len)
+        }
+    }
+    let run = |output_stream: Option<OutputStream>| {
+        let mut ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+            .with_fuel_limit(1_000_000);
+        if let Some(output_stream) = output_stream {
+            ctx = ctx.with_output_stream(output_stream);
+        }
+        Runtime::run_with_context(ctx).unwrap()
+    };
+    let chunks = Arc::new(Mutex::new(vec![]));
+    let execution_result = run(Some(OutputStream::new(SlowSink(chunks.clone()))));
+    assert_eq!(execution_result.exit_code, ExitCode::Ok.into_i32());
+    assert!(execution_result.output.is_empty());
+    assert_eq!(
+        chunks.lock().unwrap().clone(),
+        vec![b"Hello".to_vec(), b", Wor".to_vec(), b"ld".to_vec()]
+    );
+    // without the stream chunks are appended to the output
+    let execution_result = run(None);
+    assert_eq!(execution_result.output, b"Hello, World".to_vec());
+    // total size is limited
+    let (sink, receiver) = ChannelOutputSink::new(1);
+    let execution_result = run(Some(OutputStream::new(sink).with_max_size(11)));
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::OutputOverflow.into_i32()
+    );
+    assert!(receiver.try_recv().is_err());
+    let (sink, receiver) = ChannelOutputSink::new(1);
+    let execution_result = run(Some(OutputStream::new(sink).with_max_size(12)));
+    assert_eq!(execution_result.exit_code, ExitCode::Ok.into_i32());
+    assert_eq!(receiver.recv().unwrap(), b"Hello, World".to_vec());
+}
+
+#[test]
+fn test_storage_read_external() {
+    let target = address!("1111111111111111111111111111111111111111");
+    let slot = U256::from(7);
+    let storage_key = contract_storage_key(&target, &slot).unwrap();
+    let jzkt = DefaultEmptyRuntimeDatabase::default();
+    jzkt.update(&storage_key, &vec![U256::from(100).to_le_bytes::<32>()], 0);
+    jzkt.commit().unwrap();
+    // uncommitted change isn't covered by the proof
+    jzkt.update(&storage_key, &vec![U256::from(200).to_le_bytes::<32>()], 0);
+    // writes the value and the proof read from the return data
+    let run = |with_proof: bool| {
+        let rwasm_binary = wat2rwasm(&format!(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32 i32 i32) (result i32)))
+  (type (;1;) (func (param i32 i32)))
+  (type (;2;) (func (param i32 i32 i32)))
+  (type (;3;) (func))
+  (import "fluentbase_v1preview" "_storage_read_external" (func $_storage_read_external (type 0)))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 1)))
+  (import "fluentbase_v1preview" "_return_data_copy" (func $_return_data_copy (type 2)))
+  (func $main (type 3)
+    (local $proof_len i32)
+    i32.const 0
+    i32.const 20
+    i32.const 64
+    i32.const {}
+    call $_storage_read_external
+    local.set $proof_len
+    i32.const 64
+    i32.const 32
+    call $_write
+    i32.const 96
+    i32.const 0
+    local.get $proof_len
+    call $_return_data_copy
+    i32.const 96
+    local.get $proof_len
+    call $_write)
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "{}")
+  (data (;1;) (i32.const 20) "\07")
+  (export "main" (func $main)))
+    "#,
+            with_proof as u32,
+            target
+                .iter()
+                .map(|b| format!("\\{:02x}", b))
+                .collect::<String>(),
+        ));
+        // the function is available in the shared mode
+        let ctx = RuntimeContext::new(rwasm_binary)
+            .with_fuel_limit(1_000_000)
+            .with_jzkt(jzkt.clone())
+            .with_is_shared(true);
+        let execution_result = Runtime::run_with_context(ctx).unwrap();
+        assert_eq!(execution_result.exit_code, ExitCode::Ok.into_i32());
+        let (value, proof) = execution_result.output.split_at(32);
+        (U256::from_le_slice(value), proof.to_vec())
+    };
+    let (value, proof) = run(false);
+    assert_eq!(value, U256::from(200));
+    assert!(proof.is_empty());
+    let (value, proof) = run(true);
+    assert_eq!(value, U256::from(100));
+    let nodes = jzkt.proof(&storage_key).unwrap();
+    assert!(!nodes.is_empty());
+    let mut expected = (nodes.len() as u32).to_le_bytes().to_vec();
+    for node in nodes.iter() {
+        expected.extend_from_slice(&(node.len() as u32).to_le_bytes());
+        expected.extend_from_slice(node);
+    }
+    assert_eq!(proof, expected);
+    // nothing is written by the read
+    assert_eq!(
+        jzkt.get(&storage_key, false).unwrap().0[0],
+        U256::from(200).to_le_bytes::<32>()
+    );
+}
+
+#[test]
+fn test_gas_price_and_base_fee() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (param i32)))
+  (type (;2;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_gas_price" (func $_gas_price (type 1)))
+  (import "fluentbase_v1preview" "_base_fee" (func $_base_fee (type 1)))
+  (func $main (type 2)
+    i32.const 0
+    call $_gas_price
+    i32.const 32
+    call $_base_fee
+    i32.const 0
+    i32.const 64
+    call $_write
+    )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+    );
+    let run = |gas_fees: RuntimeGasFees| {
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone())
+            .with_fuel_limit(1_000_000)
+            .with_gas_fees(gas_fees);
+        let execution_result = Runtime::run_with_context(ctx).unwrap();
+        assert_eq!(execution_result.exit_code, 0);
+        (
+            U256::from_le_slice(&execution_result.output[0..32]),
+            U256::from_le_slice(&execution_result.output[32..64]),
+        )
+    };
+    // priority fee is paid on top of the base fee
+    let gas_fees = RuntimeGasFees::new(U256::from(100), U256::from(200), Some(U256::from(30)));
+    assert_eq!(run(gas_fees), (U256::from(130), U256::from(100)));
+    // but the total price can't exceed the max fee
+    let gas_fees = RuntimeGasFees::new(U256::from(100), U256::from(120), Some(U256::from(30)));
+    assert_eq!(run(gas_fees), (U256::from(120), U256::from(100)));
+    // legacy transaction pays its gas price
+    let gas_fees = RuntimeGasFees::new(U256::from(100), U256::from(150), None);
+    assert_eq!(run(gas_fees), (U256::from(150), U256::from(100)));
+}
+
+fn write_code<DB: IJournaledTrie>(jzkt: &DB, address: &Address, field: u32, code: &[u8]) {
+    let mut fields = vec![[0u8; 32]; JZKT_ACCOUNT_FIELDS_COUNT as usize];
+    fields[field as usize] = if field == JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD {
+        SyscallPoseidon::fn_impl(code)
+    } else {
+        SyscallKeccak256::fn_impl(code)
+    };
+    jzkt.update(
+        &address.into_word(),
+        &fields,
+        JZKT_ACCOUNT_COMPRESSION_FLAGS,
+    );
+    jzkt.update_preimage(&address.into_word(), field, code);
+}
+
+#[test]
+fn test_exec_address_dispatch() {
+    let wat_with_output = |output: &str| {
+        wat2rwasm(&format!(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 4
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "{}")
+  (export "main" (func $main)))
+    "#,
+            output
+        ))
+    };
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (param i32 i32 i32 i32 i32 i32) (result i32)))
+  (type (;2;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_exec_address" (func $_exec_address (type 1)))
+  (func $main (type 2)
+    i32.const 108
+    i32.const 0
+    i32.const 0
+    i32.const 0
+    i32.const 100
+    i32.const 4
+    i32.const 64
+    call $_exec_address
+    i32.store
+    i32.const 112
+    i32.const 32
+    i32.const 0
+    i32.const 0
+    i32.const 104
+    i32.const 4
+    i32.const 68
+    call $_exec_address
+    i32.store
+    i32.const 116
+    i32.const 160
+    i32.const 0
+    i32.const 0
+    i32.const 140
+    i32.const 4
+    i32.const 72
+    call $_exec_address
+    i32.store
+    i32.const 100
+    i32.const 20
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11")
+  (data (;1;) (i32.const 32) "\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22")
+  (data (;2;) (i32.const 64) "\a0\86\01\00\a0\86\01\00\a0\86\01\00")
+  (data (;3;) (i32.const 160) "\33\33\33\33\33\33\33\33\33\33\33\33\33\33\33\33\33\33\33\33")
+  (export "main" (func $main)))
+    "#,
+    );
+    let jzkt = DefaultEmptyRuntimeDatabase::default();
+    // rWASM contract is executed natively
+    let wasm_contract = wat_with_output("wasm");
+    write_code(
+        &jzkt,
+        &Address::repeat_byte(0x11),
+        JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+        &wasm_contract,
+    );
+    // EVM contract has only source code, so it's routed through the EVM interpreter
+    write_code(
+        &jzkt,
+        &Address::repeat_byte(0x22),
+        JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD,
+        &hex!("6001600055"),
+    );
+    let evm_interpreter = wat_with_output("evm!");
+    write_code(
+        &jzkt,
+        &Address::repeat_byte(0x44),
+        JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+        &evm_interpreter,
+    );
+    let ctx = RuntimeContext::new(rwasm_binary)
+        .with_fuel_limit(1_000_000)
+        .with_jzkt(jzkt)
+        .with_evm_interpreter(SyscallPoseidon::fn_impl(&evm_interpreter).into());
+    // the second run takes the modules (including the interpreter) from the cache
+    for _ in 0..2 {
+        let execution_result = Runtime::run_with_context(ctx.clone()).unwrap();
+        assert_eq!(execution_result.exit_code, 0);
+        assert_eq!(&execution_result.output[0..8], b"wasmevm!");
+        // all calls succeed, including the call to the account without code
+        assert_eq!(&execution_result.output[8..20], &[0u8; 12]);
+    }
+}
+
+#[test]
+fn test_evm_bytecode_runs_twice() {
+    let evm_interpreter = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 3
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "evm")
+  (export "main" (func $main)))
+    "#,
+    );
+    let jzkt = DefaultEmptyRuntimeDatabase::default();
+    write_code(
+        &jzkt,
+        &Address::repeat_byte(0x44),
+        JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+        &evm_interpreter,
+    );
+    // the interpreter is translated by the first run and taken from the cache by the second one,
+    // so the store of the second run must be created from the engine of the cached module
+    for _ in 0..2 {
+        let ctx = RuntimeContext::new(hex!("6001600055").to_vec())
+            .with_fuel_limit(1_000_000)
+            .with_jzkt(jzkt.clone())
+            .with_evm_interpreter(SyscallPoseidon::fn_impl(&evm_interpreter).into());
+        let execution_result = Runtime::run_with_context(ctx).unwrap();
+        assert_eq!(execution_result.exit_code, 0);
+        assert_eq!(execution_result.output, b"evm".to_vec());
+    }
+}
+
+#[test]
+fn test_call_stack_limit() {
+    // the contract calls itself until the call stack overflows and exits with the exit code of
+    // the nested call
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32)))
+  (type (;1;) (func (param i32 i32 i32 i32 i32 i32) (result i32)))
+  (type (;2;) (func))
+  (import "fluentbase_v1preview" "_exit" (func $_exit (type 0)))
+  (import "fluentbase_v1preview" "_exec_address" (func $_exec_address (type 1)))
+  (func $main (type 2)
+    i32.const 0
+    i32.const 0
+    i32.const 0
+    i32.const 0
+    i32.const 0
+    i32.const 64
+    call $_exec_address
+    call $_exit
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11")
+  (data (;1;) (i32.const 64) "\a0\86\01\00")
+  (export "main" (func $main)))
+    "#,
+    );
+    let jzkt = DefaultEmptyRuntimeDatabase::default();
+    write_code(
+        &jzkt,
+        &Address::repeat_byte(0x11),
+        JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+        &rwasm_binary,
+    );
+    let ctx = RuntimeContext::new(rwasm_binary)
+        .with_fuel_limit(1_000_000)
+        .with_jzkt(jzkt)
+        .with_call_stack_limit(3);
+    let mut runtime = Runtime::new(ctx);
+    let execution_result = runtime.call().unwrap();
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::CallDepthOverflow.into_i32()
+    );
+    // frames are popped once the nested calls return
+    assert!(runtime.store.data().call_stack().is_empty());
+}
+
+#[test]
+fn test_precompile_registry() {
+    // top-level call of the precompile doesn't need the account code
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new_with_address(PRECOMPILE_IDENTITY)
+        .with_input(b"hello".to_vec())
+        .with_fuel_limit(1_000_000)
+        .with_precompiles(PrecompileRegistry::standard());
+    let execution_result = Runtime::run_with_context(ctx).unwrap();
+    assert_eq!(execution_result.exit_code, ExitCode::Ok.into_i32());
+    assert_eq!(execution_result.output, b"hello".to_vec());
+    assert_eq!(execution_result.fuel_consumed, Fuel(18));
+    // nested call returns the output of the precompile as return data
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func (param i32 i32 i32 i32 i32 i32) (result i32)))
+  (type (;2;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (import "fluentbase_v1preview" "_exec_address" (func $_exec_address (type 1)))
+  (func $main (type 2)
+    i32.const 0
+    i32.const 32
+    i32.const 5
+    i32.const 64
+    i32.const 5
+    i32.const 96
+    call $_exec_address
+    drop
+    i32.const 64
+    i32.const 5
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 19) "\04")
+  (data (;1;) (i32.const 32) "hello")
+  (data (;2;) (i32.const 96) "\a0\86\01\00")
+  (export "main" (func $main)))
+    "#,
+    );
+    let ctx = RuntimeContext::new(rwasm_binary)
+        .with_fuel_limit(1_000_000)
+        .with_jzkt(DefaultEmptyRuntimeDatabase::default())
+        .with_precompiles(PrecompileRegistry::standard());
+    let execution_result = Runtime::run_with_context(ctx).unwrap();
+    assert_eq!(execution_result.exit_code, ExitCode::Ok.into_i32());
+    assert_eq!(execution_result.output, b"hello".to_vec());
+}
+
+#[test]
+fn test_system_contract_view() {
+    // the EVM interpreter is upgraded at block 10
+    struct EvmUpgrade;
+    impl SystemContractView for EvmUpgrade {
+        fn active_code_hash(&self, address: &Address, block_number: u64) -> Option<F254> {
+            match (*address == PRECOMPILE_EVM, block_number < 10) {
+                (false, _) => None,
+                (true, true) => Some(F254::repeat_byte(1)),
+                (true, false) => Some(F254::repeat_byte(2)),
+            }
+        }
+    }
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::default();
+    assert!(!ctx.is_system_contract(&PRECOMPILE_EVM));
+    assert_eq!(ctx.system_contract_code_hash(&PRECOMPILE_EVM, 0), None);
+    let ctx = ctx.with_system_contracts(Arc::new(EvmUpgrade));
+    assert!(ctx.is_system_contract(&PRECOMPILE_EVM));
+    assert!(!ctx.is_system_contract(&PRECOMPILE_IDENTITY));
+    assert_eq!(
+        ctx.system_contract_code_hash(&PRECOMPILE_EVM, 9),
+        Some(F254::repeat_byte(1))
+    );
+    assert_eq!(
+        ctx.system_contract_code_hash(&PRECOMPILE_EVM, 10),
+        Some(F254::repeat_byte(2))
+    );
+}
+
+#[test]
+fn test_lazy_code_loading_by_address() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 4
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "lazy")
+  (export "main" (func $main)))
+    "#,
+    );
+    let jzkt = DefaultEmptyRuntimeDatabase::default();
+    let address = Address::repeat_byte(0x55);
+    write_code(
+        &jzkt,
+        &address,
+        JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+        &rwasm_binary,
+    );
+    let run = |address: Address| {
+        let ctx = RuntimeContext::new_with_address(address)
+            .with_fuel_limit(1_000_000)
+            .with_jzkt(jzkt.clone());
+        Runtime::run_with_context(ctx).unwrap()
+    };
+    // the first call loads the bytecode from the preimage, the second one uses the module cache
+    for _ in 0..2 {
+        let execution_result = run(address);
+        assert_eq!(execution_result.exit_code, 0);
+        assert_eq!(execution_result.output, b"lazy");
+    }
+    // account without code behaves like EOA
+    let execution_result = run(Address::repeat_byte(0x66));
+    assert_eq!(execution_result.exit_code, 0);
+    assert!(execution_result.output.is_empty());
+}
+
+#[test]
+fn test_artifact_store_code_loading() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 4
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "disk")
+  (export "main" (func $main)))
+    "#,
+    );
+    let code_hash = F254::from(poseidon_hash(&rwasm_binary));
+    let dir = std::env::temp_dir().join(format!(
+        "fluentbase-runtime-artifacts-{}",
+        std::process::id()
+    ));
+    let artifact_store = ModuleArtifactStore::open(&dir, RuntimeStackLimits::default()).unwrap();
+    let jzkt = DefaultEmptyRuntimeDatabase::default();
+    write_code(
+        &jzkt,
+        &Address::repeat_byte(0x77),
+        JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+        &rwasm_binary,
+    );
+    let run = |jzkt: DefaultEmptyRuntimeDatabase| {
+        let ctx = RuntimeContext::new_with_hash(code_hash)
+            .with_fuel_limit(1_000_000)
+            .with_jzkt(jzkt)
+            .with_artifact_store(artifact_store.clone());
+        Runtime::run_with_context(ctx).unwrap()
+    };
+    // the code is loaded from the trie and persisted
+    let execution_result = run(jzkt);
+    assert_eq!(execution_result.output, b"disk");
+    assert_eq!(
+        artifact_store.load(&code_hash).unwrap().rwasm_bytecode,
+        rwasm_binary
+    );
+    // after the restart the code is loaded from the store even without the preimage
+    clear_module_caches();
+    let execution_result = run(DefaultEmptyRuntimeDatabase::default());
+    assert_eq!(execution_result.exit_code, ExitCode::Ok.into_i32());
+    assert_eq!(execution_result.output, b"disk");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_warm_up_modules() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (func $main
+    i32.const 7
+    drop
+    )
+  (export "main" (func $main)))
+    "#,
+    );
+    let report = warm_up_modules(
+        RuntimeStackLimits::default(),
+        [rwasm_binary.clone(), vec![0xff, 0xff]],
+    );
+    assert_eq!(report.translated.len(), 1);
+    assert_eq!(report.failed.len(), 1);
+    let report = warm_up_modules(RuntimeStackLimits::default(), [rwasm_binary.clone()]);
+    assert_eq!(report.cached.len(), 1);
+    // execution reuses the warmed module
+    let ctx =
+        RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary).with_fuel_limit(1_000_000);
+    let execution_result = Runtime::run_with_context(ctx).unwrap();
+    assert_eq!(execution_result.exit_code, 0);
+}
+
+#[test]
+fn test_module_cache_eviction() {
+    let rwasm_binaries = (0..3)
+        .map(|value| {
+            wat2rwasm(&format!(
+                "(module (func $main i32.const {} drop) (export \"main\" (func $main)))",
+                value
+            ))
+        })
+        .collect::<Vec<_>>();
+    let rwasm_hash = |rwasm_binary: &Vec<u8>| F254::from(poseidon_hash(rwasm_binary));
+    set_module_cache_capacity(2);
+    let report = warm_up_modules(RuntimeStackLimits::default(), &rwasm_binaries[..2]);
+    assert_eq!(report.translated.len(), 2);
+    // the first module is used, so the second one is the least recently used
+    let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binaries[0].clone())
+        .with_fuel_limit(1_000_000);
+    assert_eq!(Runtime::run_with_context(ctx).unwrap().exit_code, 0);
+    let report = warm_up_modules(RuntimeStackLimits::default(), &rwasm_binaries[2..]);
+    assert_eq!(report.translated.len(), 1);
+    let report = warm_up_modules(RuntimeStackLimits::default(), &rwasm_binaries[..1]);
+    assert_eq!(report.cached, vec![rwasm_hash(&rwasm_binaries[0])]);
+
+    assert!(invalidate_module(&rwasm_hash(&rwasm_binaries[2])));
+    assert!(!invalidate_module(&rwasm_hash(&rwasm_binaries[2])));
+    // evicted module is translated again
+    let report = warm_up_modules(RuntimeStackLimits::default(), &rwasm_binaries[1..2]);
+    assert_eq!(report.translated, vec![rwasm_hash(&rwasm_binaries[1])]);
+    clear_module_caches();
+    let report = warm_up_modules(RuntimeStackLimits::default(), &rwasm_binaries[..1]);
+    assert_eq!(report.translated.len(), 1);
+}
+
+#[test]
+fn test_runtime_error_source() {
+    use std::error::Error;
+    let err = RuntimeError::from(fluentbase_zktrie::Error::KeyNotFound);
+    assert_eq!(err.exit_code(), ExitCode::PersistentStorageError);
+    assert_eq!(
+        err.source()
+            .and_then(|source| source.downcast_ref::<fluentbase_zktrie::Error>()),
+        Some(&fluentbase_zktrie::Error::KeyNotFound)
+    );
+    let err = RuntimeError::from(fluentbase_codec::CodecError::NonCanonical);
+    assert_eq!(err.source().unwrap().to_string(), "non-canonical encoding");
+    // exit code of the failed execution is kept
+    let err = RuntimeError::ExecutionFailed(ExitCode::OutOfGas.into_i32());
+    assert_eq!(
+        Runtime::<DefaultEmptyRuntimeDatabase>::catch_trap(&err),
+        ExitCode::OutOfGas.into_i32()
+    );
+    assert!(err.to_string().contains("OutOfGas"));
+    assert!(err.source().is_none());
+}
+
+#[test]
+fn test_shared_execution_escalation_policy() {
+    let rwasm_binary = wat2rwasm(
+        r#"
+(module
+  (type (;0;) (func (result i64)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_checkpoint" (func $_checkpoint (type 0)))
+  (func $main (type 1)
+    call $_checkpoint
+    drop
+    )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+    );
+    let new_ctx = |rwasm_binary: &Vec<u8>| {
+        RuntimeContext::new(rwasm_binary.clone())
+            .with_fuel_limit(1_000_000)
+            .with_jzkt(DefaultEmptyRuntimeDatabase::default())
+            .with_is_shared(true)
+    };
+    // shared execution can't use sovereign syscalls
+    let execution_result = Runtime::run_with_context(new_ctx(&rwasm_binary)).unwrap();
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::CapabilityDenied.into_i32()
+    );
+    // unless the bytecode is trusted by the escalation policy
+    let code_hash = F254::from(poseidon_hash(&rwasm_binary));
+    let escalation_policy = EscalationPolicy::new()
+        .with_grant(code_hash, Capabilities::NONE.with(SysFuncIdx::CHECKPOINT));
+    let ctx = new_ctx(&rwasm_binary).with_escalation_policy(escalation_policy.clone());
+    let execution_result = Runtime::run_with_context(ctx).unwrap();
+    assert_eq!(execution_result.exit_code, ExitCode::Ok.into_i32());
+    // grants are bound to the bytecode hash
+    let ctx = new_ctx(&rwasm_binary)
+        .with_escalation_policy(EscalationPolicy::new().with_grant(F254::ZERO, Capabilities::ALL));
+    let execution_result = Runtime::run_with_context(ctx).unwrap();
+    assert_eq!(
+        execution_result.exit_code,
+        ExitCode::CapabilityDenied.into_i32()
+    );
+}
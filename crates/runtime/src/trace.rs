@@ -0,0 +1,9 @@
+//! Execution trace artifacts shared between the runtime and the prover
+pub mod call_graph;
+pub mod commitment;
+pub mod debugger;
+pub mod format;
+pub mod json;
+pub mod public_io;
+pub mod segment;
+pub mod sink;
@@ -0,0 +1,270 @@
+use crate::trace::format::{TraceFormatError, TraceReader, TraceSideEffect};
+use fluentbase_types::B256;
+use hashbrown::HashMap;
+use serde_json::json;
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+/// Node of the call graph, contracts are identified by the code hash (`None` is the root
+/// bytecode of the trace), guest functions by the contract and the function index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CallGraphNode {
+    Contract(Option<B256>),
+    Function(Option<B256>, u32),
+}
+
+impl CallGraphNode {
+    fn contract(&self) -> Option<B256> {
+        match self {
+            CallGraphNode::Contract(contract) | CallGraphNode::Function(contract, _) => *contract,
+        }
+    }
+
+    fn label(&self, names: &HashMap<u32, String>) -> String {
+        let contract = match self.contract() {
+            // first 4 bytes of the hash are enough to tell contracts apart in the graph
+            Some(code_hash) => format!("0x{}", hex::encode(&code_hash[..4])),
+            None => "root".to_string(),
+        };
+        match self {
+            CallGraphNode::Contract(_) => contract,
+            CallGraphNode::Function(None, func_idx) => names
+                .get(func_idx)
+                .cloned()
+                .unwrap_or_else(|| format!("func[{}]", func_idx)),
+            CallGraphNode::Function(_, func_idx) => format!("{}::func[{}]", contract, func_idx),
+        }
+    }
+}
+
+impl Display for CallGraphNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label(&HashMap::new()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CallGraphEdge {
+    pub calls: u64,
+    /// Fuel consumed by the callee in all calls of the edge (nested calls included)
+    pub fuel: u64,
+    /// Number of nested contract calls that failed
+    pub failed_calls: u64,
+}
+
+/// Dynamic call graph reconstructed from the trace. Guest function edges need the binary
+/// instrumented with profile probes (see `instrument_wasm`), nested contract calls are always
+/// traced. Fuel of guest functions is measured in the fuel of the contract they belong to, so
+/// it includes fuel of nested contract calls made by the function.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallGraph {
+    pub edges: BTreeMap<(CallGraphNode, CallGraphNode), CallGraphEdge>,
+}
+
+struct CallFrame {
+    node: CallGraphNode,
+    entered_at: u64,
+    /// The last clock seen inside the contract (used to close frames of the failed calls)
+    last_clk: u64,
+}
+
+impl CallGraph {
+    pub fn from_trace(trace: &[u8]) -> Result<Self, TraceFormatError> {
+        let mut result = Self::default();
+        let mut stack = vec![CallFrame {
+            node: CallGraphNode::Contract(None),
+            entered_at: 0,
+            last_clk: 0,
+        }];
+        for row in TraceReader::new(trace)?.rows() {
+            let row = row?;
+            // clock of the call rows belongs to the caller
+            if row.side_effect != TraceSideEffect::CallExit {
+                Self::touch(&mut stack, row.clk);
+            }
+            match row.side_effect {
+                TraceSideEffect::FunctionEnter => {
+                    let contract = stack.last().unwrap().node.contract();
+                    stack.push(CallFrame {
+                        node: CallGraphNode::Function(contract, row.operands[0] as u32),
+                        entered_at: row.clk,
+                        last_clk: row.clk,
+                    });
+                }
+                TraceSideEffect::FunctionExit => {
+                    if let CallGraphNode::Function(..) = stack.last().unwrap().node {
+                        let frame = stack.pop().unwrap();
+                        let fuel = row.clk.saturating_sub(frame.entered_at);
+                        result.push_edge(&stack, frame.node, fuel, false);
+                        Self::touch(&mut stack, row.clk);
+                    }
+                }
+                TraceSideEffect::CallEnter => {
+                    stack.push(CallFrame {
+                        node: CallGraphNode::Contract(Some(B256::new(row.address))),
+                        entered_at: 0,
+                        last_clk: 0,
+                    });
+                }
+                TraceSideEffect::CallExit => {
+                    // functions of the callee aren't closed if the call is interrupted
+                    result.close_functions(&mut stack);
+                    if stack.len() > 1 {
+                        let frame = stack.pop().unwrap();
+                        let is_failed = row.operands[2] != 0;
+                        result.push_edge(&stack, frame.node, row.operands[1], is_failed);
+                    }
+                    Self::touch(&mut stack, row.clk);
+                }
+                _ => {}
+            }
+        }
+        result.close_functions(&mut stack);
+        Ok(result)
+    }
+
+    fn touch(stack: &mut [CallFrame], clk: u64) {
+        let frame = stack.last_mut().unwrap();
+        frame.last_clk = frame.last_clk.max(clk);
+    }
+
+    fn close_functions(&mut self, stack: &mut Vec<CallFrame>) {
+        while let CallGraphNode::Function(..) = stack.last().unwrap().node {
+            let frame = stack.pop().unwrap();
+            let fuel = frame.last_clk.saturating_sub(frame.entered_at);
+            self.push_edge(stack, frame.node, fuel, false);
+            Self::touch(stack, frame.last_clk);
+        }
+    }
+
+    fn push_edge(&mut self, stack: &[CallFrame], callee: CallGraphNode, fuel: u64, failed: bool) {
+        let caller = stack.last().unwrap().node;
+        let edge = self.edges.entry((caller, callee)).or_default();
+        edge.calls += 1;
+        edge.fuel += fuel;
+        edge.failed_calls += failed as u64;
+    }
+
+    /// Fuel consumed by every node in all its calls
+    pub fn node_fuel(&self) -> BTreeMap<CallGraphNode, u64> {
+        let mut result = BTreeMap::new();
+        for ((_, callee), edge) in self.edges.iter() {
+            *result.entry(*callee).or_default() += edge.fuel;
+        }
+        result
+    }
+
+    /// Exports the graph in the Graphviz format, names of the root contract functions can be
+    /// taken from the source map
+    pub fn to_dot(&self, names: &HashMap<u32, String>) -> String {
+        let mut lines = vec!["digraph calls {".to_string()];
+        for ((caller, callee), edge) in self.edges.iter() {
+            let mut label = format!("calls={} fuel={}", edge.calls, edge.fuel);
+            if edge.failed_calls > 0 {
+                label.push_str(&format!(" failed={}", edge.failed_calls));
+            }
+            lines.push(format!(
+                "  \"{}\" -> \"{}\" [label=\"{}\"];",
+                caller.label(names),
+                callee.label(names),
+                label
+            ));
+        }
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+
+    pub fn to_json(&self, names: &HashMap<u32, String>) -> serde_json::Value {
+        let edges = self
+            .edges
+            .iter()
+            .map(|((caller, callee), edge)| {
+                json!({
+                    "caller": caller.label(names),
+                    "callee": callee.label(names),
+                    "calls": edge.calls,
+                    "fuel": edge.fuel,
+                    "failed_calls": edge.failed_calls,
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({ "edges": edges })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trace::{
+        call_graph::{CallGraph, CallGraphEdge, CallGraphNode},
+        format::TraceWriter,
+    };
+    use fluentbase_types::{ExitCode, B256};
+    use hashbrown::HashMap;
+
+    #[test]
+    fn test_call_graph_from_trace() {
+        let writer = TraceWriter::new();
+        let callee = [7u8; 32];
+        writer.push_function_enter(0, 0);
+        writer.push_function_enter(10, 1);
+        writer.push_function_exit(30);
+        writer.push_function_enter(40, 1);
+        writer.push_call_enter(45, 1, &callee, 1000);
+        writer.push_function_enter(0, 0);
+        writer.push_function_exit(200);
+        writer.push_call_exit(300, 1, 250, 0);
+        writer.push_function_exit(310);
+        // failed call, the callee function isn't closed
+        writer.push_call_enter(320, 1, &callee, 1000);
+        writer.push_function_enter(0, 0);
+        writer.push_function_enter(20, 2);
+        writer.push_call_exit(400, 1, 50, ExitCode::OutOfGas.into_i32());
+        writer.push_function_exit(410);
+
+        let graph = CallGraph::from_trace(&writer.to_bytes()).unwrap();
+        let root = CallGraphNode::Contract(None);
+        let main = CallGraphNode::Function(None, 0);
+        let helper = CallGraphNode::Function(None, 1);
+        let contract = CallGraphNode::Contract(Some(B256::new(callee)));
+        let contract_main = CallGraphNode::Function(Some(B256::new(callee)), 0);
+        assert_eq!(
+            graph.edges.get(&(main, helper)),
+            Some(&CallGraphEdge {
+                calls: 2,
+                fuel: 290,
+                failed_calls: 0
+            })
+        );
+        assert_eq!(graph.edges.get(&(root, main)).unwrap().fuel, 410);
+        assert_eq!(
+            graph.edges.get(&(helper, contract)),
+            Some(&CallGraphEdge {
+                calls: 1,
+                fuel: 250,
+                failed_calls: 0
+            })
+        );
+        assert_eq!(
+            graph.edges.get(&(main, contract)),
+            Some(&CallGraphEdge {
+                calls: 1,
+                fuel: 50,
+                failed_calls: 1
+            })
+        );
+        assert_eq!(
+            graph.edges.get(&(contract, contract_main)).unwrap().calls,
+            2
+        );
+        assert_eq!(graph.node_fuel().get(&contract).copied(), Some(300));
+
+        let names = HashMap::from([(0, "main".to_string())]);
+        let dot = graph.to_dot(&names);
+        assert!(dot.contains("\"root\" -> \"main\" [label=\"calls=1 fuel=410\"];"));
+        assert!(dot.contains("\"main\" -> \"0x07070707\" [label=\"calls=1 fuel=50 failed=1\"];"));
+        let json = graph.to_json(&names);
+        assert_eq!(json["edges"].as_array().unwrap().len(), 6);
+    }
+}
@@ -0,0 +1,127 @@
+use fluentbase_poseidon::{hash_with_domain, poseidon_hash};
+use halo2curves::bn256::Fr;
+
+const DOMAIN: Fr = Fr::zero();
+
+/// Commitment scheme over the trace chunks, both schemes use the same leaves (Poseidon hash of
+/// the chunk bytes), so the prover can open any chunk against the commitment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceCommitmentScheme {
+    /// `acc[i + 1] = p(acc[i], leaf[i])` with `acc[0] = 0`, the commitment is
+    /// `p(acc[n], n)`, every continuation segment proves the transition `acc[i] -> acc[i + 1]`
+    HashChain,
+    /// Binary Merkle tree with `p(left, right)` nodes, leaves are padded with zeros to the next
+    /// power of two, the commitment of an empty trace is zero
+    MerkleRoot,
+}
+
+/// Returns leaf of the trace chunk, it's a Poseidon hash of the chunk bytes split into 31-byte
+/// field elements
+pub fn trace_chunk_leaf(chunk: &[u8]) -> [u8; 32] {
+    poseidon_hash(chunk)
+}
+
+fn leaf_to_fr(chunk: &[u8]) -> Fr {
+    // poseidon output is always a canonical field element
+    Fr::from_bytes(&trace_chunk_leaf(chunk)).unwrap()
+}
+
+/// Returns hash chain accumulators (`acc[0]..=acc[n]`) for the list of trace chunks, these values
+/// are public inputs of the continuation segments
+pub fn trace_hash_chain<C: AsRef<[u8]>>(chunks: &[C]) -> Vec<[u8; 32]> {
+    let mut acc = Fr::zero();
+    let mut result = Vec::with_capacity(chunks.len() + 1);
+    result.push(acc.to_bytes());
+    for chunk in chunks.iter() {
+        acc = hash_with_domain(&[acc, leaf_to_fr(chunk.as_ref())], &DOMAIN);
+        result.push(acc.to_bytes());
+    }
+    result
+}
+
+/// Returns Merkle root over the trace chunks
+pub fn trace_merkle_root<C: AsRef<[u8]>>(chunks: &[C]) -> [u8; 32] {
+    if chunks.is_empty() {
+        return Fr::zero().to_bytes();
+    }
+    let mut layer = chunks
+        .iter()
+        .map(|chunk| leaf_to_fr(chunk.as_ref()))
+        .collect::<Vec<_>>();
+    layer.resize(layer.len().next_power_of_two(), Fr::zero());
+    while layer.len() > 1 {
+        layer = layer
+            .chunks(2)
+            .map(|pair| hash_with_domain(&[pair[0], pair[1]], &DOMAIN))
+            .collect();
+    }
+    layer[0].to_bytes()
+}
+
+/// Computes commitment over the execution trace chunks in the layout expected by the circuit
+pub fn commit_trace<C: AsRef<[u8]>>(chunks: &[C], scheme: TraceCommitmentScheme) -> [u8; 32] {
+    match scheme {
+        TraceCommitmentScheme::HashChain => {
+            let acc = *trace_hash_chain(chunks).last().unwrap();
+            let acc = Fr::from_bytes(&acc).unwrap();
+            hash_with_domain(&[acc, Fr::from(chunks.len() as u64)], &DOMAIN).to_bytes()
+        }
+        TraceCommitmentScheme::MerkleRoot => trace_merkle_root(chunks),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trace::commitment::{
+        commit_trace,
+        trace_chunk_leaf,
+        trace_hash_chain,
+        TraceCommitmentScheme,
+    };
+    use fluentbase_poseidon::hash_with_domain;
+    use halo2curves::bn256::Fr;
+
+    #[test]
+    fn test_hash_chain_commitment() {
+        let chunks = [b"chunk0".to_vec(), b"chunk1".to_vec()];
+        let accumulators = trace_hash_chain(&chunks);
+        assert_eq!(accumulators.len(), 3);
+        assert_eq!(accumulators[0], [0u8; 32]);
+        let leaf = Fr::from_bytes(&trace_chunk_leaf(b"chunk1")).unwrap();
+        let acc1 = Fr::from_bytes(&accumulators[1]).unwrap();
+        assert_eq!(
+            hash_with_domain(&[acc1, leaf], &Fr::zero()).to_bytes(),
+            accumulators[2]
+        );
+        // order of the chunks matters for both schemes
+        let reversed = [chunks[1].clone(), chunks[0].clone()];
+        for scheme in [
+            TraceCommitmentScheme::HashChain,
+            TraceCommitmentScheme::MerkleRoot,
+        ] {
+            assert_ne!(
+                commit_trace(&chunks, scheme),
+                commit_trace(&reversed, scheme)
+            );
+        }
+    }
+
+    #[test]
+    fn test_merkle_root_padding() {
+        let chunks = [b"a".to_vec(), b"b".to_vec(), b"c".to_vec()];
+        let leaves = chunks
+            .iter()
+            .map(|chunk| Fr::from_bytes(&trace_chunk_leaf(chunk)).unwrap())
+            .collect::<Vec<_>>();
+        let left = hash_with_domain(&[leaves[0], leaves[1]], &Fr::zero());
+        let right = hash_with_domain(&[leaves[2], Fr::zero()], &Fr::zero());
+        assert_eq!(
+            commit_trace(&chunks, TraceCommitmentScheme::MerkleRoot),
+            hash_with_domain(&[left, right], &Fr::zero()).to_bytes()
+        );
+        assert_eq!(
+            commit_trace::<Vec<u8>>(&[], TraceCommitmentScheme::MerkleRoot),
+            [0u8; 32]
+        );
+    }
+}
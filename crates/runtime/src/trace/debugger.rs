@@ -0,0 +1,251 @@
+use crate::{
+    trace::segment::{memory_delta, MemoryDelta},
+    types::RuntimeError,
+    ExecutionResult,
+    Runtime,
+    RuntimeContext,
+};
+use fluentbase_types::{Fuel, IJournaledTrie};
+
+/// Default number of steps between two full memory snapshots
+pub const DEFAULT_DEBUG_CHECKPOINT_INTERVAL: usize = 64;
+
+/// Recorded step of the execution, memory is stored as the delta to the previous step
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugStep {
+    pub fuel_consumed: Fuel,
+    pub memory_size: u32,
+    pub memory_delta: Vec<MemoryDelta>,
+}
+
+/// Recorded execution that can be navigated in both directions.
+///
+/// Every step is a fuel boundary of the execution, full memory is kept only for every
+/// `checkpoint_interval`-th step, any other step is restored from the nearest checkpoint
+/// before it by replaying recorded memory deltas. Step 0 is the state before the execution.
+pub struct DebugSession {
+    steps: Vec<DebugStep>,
+    checkpoints: Vec<Vec<u8>>,
+    checkpoint_interval: usize,
+    execution_result: ExecutionResult,
+    position: usize,
+    memory: Vec<u8>,
+}
+
+impl DebugSession {
+    fn new(
+        snapshots: Vec<(Fuel, Vec<u8>)>,
+        checkpoint_interval: usize,
+        execution_result: ExecutionResult,
+    ) -> Self {
+        let mut steps = Vec::with_capacity(snapshots.len());
+        let mut checkpoints = Vec::new();
+        let mut prev: &[u8] = &[];
+        for (index, (fuel_consumed, memory)) in snapshots.iter().enumerate() {
+            if index % checkpoint_interval == 0 {
+                checkpoints.push(memory.clone());
+            }
+            steps.push(DebugStep {
+                fuel_consumed: *fuel_consumed,
+                memory_size: memory.len() as u32,
+                memory_delta: memory_delta(prev, memory),
+            });
+            prev = memory;
+        }
+        Self {
+            steps,
+            checkpoints,
+            checkpoint_interval,
+            execution_result,
+            position: 0,
+            memory: Vec::new(),
+        }
+    }
+
+    /// All recorded steps (including the initial one)
+    pub fn steps(&self) -> &[DebugStep] {
+        &self.steps
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The last step is the finished execution
+    pub fn is_finished(&self) -> bool {
+        self.position + 1 == self.steps.len()
+    }
+
+    pub fn current_step(&self) -> &DebugStep {
+        &self.steps[self.position]
+    }
+
+    pub fn fuel_consumed(&self) -> Fuel {
+        self.current_step().fuel_consumed
+    }
+
+    /// Linear memory of the root call at the current step
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    /// Result of the whole execution
+    pub fn execution_result(&self) -> &ExecutionResult {
+        &self.execution_result
+    }
+
+    /// Moves to the next step, returns `false` if the execution is already finished
+    pub fn step(&mut self) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        self.position += 1;
+        let step = &self.steps[self.position];
+        Self::apply_step(step, &mut self.memory);
+        true
+    }
+
+    /// Moves to the previous step, returns `false` at the initial step
+    pub fn step_back(&mut self) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+        self.seek(self.position - 1)
+    }
+
+    /// Moves to the step by index, returns `false` if the index is out of bounds
+    pub fn seek(&mut self, position: usize) -> bool {
+        if position >= self.steps.len() {
+            return false;
+        }
+        let checkpoint = position / self.checkpoint_interval;
+        let mut memory = self.checkpoints[checkpoint].clone();
+        for step in self.steps[checkpoint * self.checkpoint_interval + 1..=position].iter() {
+            Self::apply_step(step, &mut memory);
+        }
+        self.memory = memory;
+        self.position = position;
+        true
+    }
+
+    fn apply_step(step: &DebugStep, memory: &mut Vec<u8>) {
+        memory.resize(step.memory_size as usize, 0);
+        for delta in step.memory_delta.iter() {
+            let offset = delta.offset as usize;
+            memory[offset..offset + delta.data.len()].copy_from_slice(&delta.data);
+        }
+    }
+}
+
+impl<DB: IJournaledTrie + Clone> Runtime<DB> {
+    /// Records the execution for the time-travel debugging, every `step_size` fuel is a step.
+    ///
+    /// Like trace segments, the execution is replayed up to every step boundary to snapshot the
+    /// memory (so the cost is quadratic in number of steps), all attempts except the last one
+    /// are rolled back, the last one keeps state changes.
+    pub fn record_debug_session(
+        mut runtime_context: RuntimeContext<DB>,
+        step_size: Fuel,
+        checkpoint_interval: usize,
+    ) -> Result<DebugSession, RuntimeError> {
+        assert!(!step_size.is_zero(), "step size must be positive");
+        assert!(
+            checkpoint_interval > 0,
+            "checkpoint interval must be positive"
+        );
+        runtime_context.resolve_bytecode();
+
+        // execute once to know total fuel
+        let checkpoint = runtime_context.jzkt.as_ref().map(|jzkt| jzkt.checkpoint());
+        let execution_result = Self::new(runtime_context.clone()).call()?;
+        if let (Some(jzkt), Some(checkpoint)) = (runtime_context.jzkt.as_ref(), checkpoint) {
+            jzkt.rollback(checkpoint);
+        }
+
+        let mut snapshots = vec![(Fuel::ZERO, Vec::new())];
+        let mut boundary = step_size;
+        while boundary < execution_result.fuel_consumed {
+            let checkpoint = runtime_context.jzkt.as_ref().map(|jzkt| jzkt.checkpoint());
+            let mut runtime = Self::new(runtime_context.clone().with_fuel_limit(boundary));
+            let partial_result = runtime.call()?;
+            let memory = runtime.memory_snapshot();
+            drop(runtime);
+            if let (Some(jzkt), Some(checkpoint)) = (runtime_context.jzkt.as_ref(), checkpoint) {
+                jzkt.rollback(checkpoint);
+            }
+            // fuel is charged per block, so several boundaries can end up at the same step
+            if partial_result.fuel_consumed > snapshots.last().unwrap().0 {
+                snapshots.push((partial_result.fuel_consumed, memory));
+            }
+            boundary += step_size;
+        }
+
+        let mut runtime = Self::new(runtime_context);
+        let execution_result = runtime.call()?;
+        snapshots.push((execution_result.fuel_consumed, runtime.memory_snapshot()));
+        Ok(DebugSession::new(
+            snapshots,
+            checkpoint_interval,
+            execution_result,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tests::wat2rwasm, DefaultEmptyRuntimeDatabase, Runtime, RuntimeContext};
+    use fluentbase_types::Fuel;
+
+    #[test]
+    fn test_debug_session_navigation() {
+        let rwasm_binary = wat2rwasm(
+            r#"
+(module
+  (func $main
+    (local $i i32)
+    (loop $loop
+      local.get $i
+      local.get $i
+      i32.store
+      local.get $i
+      i32.const 4
+      i32.add
+      local.tee $i
+      i32.const 4000
+      i32.lt_u
+      br_if $loop
+    )
+  )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+        );
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+            .with_fuel_limit(10_000_000);
+        let mut session = Runtime::record_debug_session(ctx, Fuel(1_000), 3).unwrap();
+        assert_eq!(session.execution_result().exit_code, 0);
+        assert!(session.steps().len() > 4);
+        // walk forward and remember memory of every step
+        let mut memories = vec![session.memory().to_vec()];
+        while session.step() {
+            memories.push(session.memory().to_vec());
+        }
+        assert!(session.is_finished());
+        assert_eq!(
+            session.fuel_consumed(),
+            session.execution_result().fuel_consumed
+        );
+        assert_eq!(&session.memory()[4..8], &4u32.to_le_bytes());
+        // step back through the whole execution
+        while session.step_back() {
+            assert_eq!(session.memory(), memories[session.position()].as_slice());
+        }
+        assert_eq!(session.position(), 0);
+        // jump between steps restored from different checkpoints
+        for position in [memories.len() - 1, 1, 4, 2] {
+            assert!(session.seek(position));
+            assert_eq!(session.memory(), memories[position].as_slice());
+        }
+        assert!(!session.seek(memories.len()));
+    }
+}
@@ -0,0 +1,409 @@
+use crate::trace::sink::TraceSink;
+use byteorder::{ByteOrder, LittleEndian};
+use std::{
+    io,
+    sync::{Arc, Mutex, RwLock},
+};
+
+/// Magic prefix of the serialized trace
+pub const TRACE_MAGIC: [u8; 4] = *b"FBTR";
+pub const TRACE_FORMAT_VERSION: u32 = 2;
+pub const TRACE_HEADER_SIZE: usize = 12;
+
+/// Row layout (little-endian): clk (u64), opcode (u32), operands (3 x u64), side effect (u32),
+/// address (32 bytes) and value (32 bytes).
+///
+/// The runtime records host calls and their side effects only (guest instructions aren't
+/// traced), so rows have no pc.
+pub const TRACE_ROW_SIZE: usize = 8 + 4 + 8 * 3 + 4 + 32 + 32;
+
+/// Opcodes of the host calls have this bit set, the rest bits store system function index
+pub const SYSCALL_OPCODE_FLAG: u32 = 0x8000_0000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u32)]
+pub enum TraceSideEffect {
+    #[default]
+    None = 0,
+    MemoryWrite = 1,
+    StorageRead = 2,
+    StorageWrite = 3,
+    /// Guest function is entered (instrumented binaries only), operands: function index
+    FunctionEnter = 4,
+    /// Guest function is left (instrumented binaries only)
+    FunctionExit = 5,
+    /// Nested contract call is started, address is the callee code hash, operands: depth of
+    /// the callee and fuel limit
+    CallEnter = 6,
+    /// Nested contract call is finished, operands: depth of the callee, consumed fuel and exit
+    /// code
+    CallExit = 7,
+}
+
+impl TryFrom<u32> for TraceSideEffect {
+    type Error = TraceFormatError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::None),
+            1 => Ok(Self::MemoryWrite),
+            2 => Ok(Self::StorageRead),
+            3 => Ok(Self::StorageWrite),
+            4 => Ok(Self::FunctionEnter),
+            5 => Ok(Self::FunctionExit),
+            6 => Ok(Self::CallEnter),
+            7 => Ok(Self::CallExit),
+            _ => Err(TraceFormatError::UnknownSideEffect(value)),
+        }
+    }
+}
+
+/// Fixed-width row of the trace, for memory writes address contains offset (first 4 bytes) and
+/// value contains written bytes (up to 32), for storage effects address is a trie key
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TraceRow {
+    pub clk: u64,
+    /// System function index with [`SYSCALL_OPCODE_FLAG`] for host calls, zero for side effects
+    pub opcode: u32,
+    pub operands: [u64; 3],
+    pub side_effect: TraceSideEffect,
+    pub address: [u8; 32],
+    pub value: [u8; 32],
+}
+
+impl TraceRow {
+    pub fn syscall(clk: u64, sys_func_idx: u32) -> Self {
+        Self {
+            clk,
+            opcode: SYSCALL_OPCODE_FLAG | sys_func_idx,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_syscall(&self) -> bool {
+        self.opcode & SYSCALL_OPCODE_FLAG != 0
+    }
+
+    pub fn write_to(&self, buffer: &mut [u8]) {
+        LittleEndian::write_u64(&mut buffer[0..8], self.clk);
+        LittleEndian::write_u32(&mut buffer[8..12], self.opcode);
+        LittleEndian::write_u64_into(&self.operands, &mut buffer[12..36]);
+        LittleEndian::write_u32(&mut buffer[36..40], self.side_effect as u32);
+        buffer[40..72].copy_from_slice(&self.address);
+        buffer[72..104].copy_from_slice(&self.value);
+    }
+
+    pub fn read_from(buffer: &[u8]) -> Result<Self, TraceFormatError> {
+        if buffer.len() < TRACE_ROW_SIZE {
+            return Err(TraceFormatError::UnexpectedEof);
+        }
+        let mut operands = [0u64; 3];
+        LittleEndian::read_u64_into(&buffer[12..36], &mut operands);
+        Ok(Self {
+            clk: LittleEndian::read_u64(&buffer[0..8]),
+            opcode: LittleEndian::read_u32(&buffer[8..12]),
+            operands,
+            side_effect: TraceSideEffect::try_from(LittleEndian::read_u32(&buffer[36..40]))?,
+            address: buffer[40..72].try_into().unwrap(),
+            value: buffer[72..104].try_into().unwrap(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceFormatError {
+    BadMagic,
+    UnsupportedVersion(u32),
+    UnexpectedEof,
+    UnknownSideEffect(u32),
+    /// Trace doesn't match the hash of the execution result
+    HashMismatch,
+}
+
+struct SinkState {
+    sink: Box<dyn TraceSink>,
+    // the first failed write, rows aren't written to the sink after it
+    error: Option<io::Error>,
+}
+
+impl SinkState {
+    fn write(&mut self, data: &[u8]) {
+        if self.error.is_none() {
+            self.error = self.sink.write(data).err();
+        }
+    }
+}
+
+/// Collects trace rows during the execution, writer can be shared between nested calls to get
+/// one trace for the whole transaction
+#[derive(Clone, Default)]
+pub struct TraceWriter {
+    rows: Arc<RwLock<Vec<u8>>>,
+    sink: Option<Arc<Mutex<SinkState>>>,
+}
+
+impl TraceWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Streams the serialized trace (header and rows) to the sink, rows pushed before are written
+    /// immediately. Rows are still collected in memory for the commitment and chunking.
+    pub fn with_sink<S: TraceSink + 'static>(mut self, sink: S) -> Self {
+        let mut sink_state = SinkState {
+            sink: Box::new(sink),
+            error: None,
+        };
+        sink_state.write(&Self::header());
+        sink_state.write(&self.rows.read().unwrap());
+        self.sink = Some(Arc::new(Mutex::new(sink_state)));
+        self
+    }
+
+    pub fn push(&self, row: &TraceRow) {
+        let mut rows = self.rows.write().unwrap();
+        let offset = rows.len();
+        rows.resize(offset + TRACE_ROW_SIZE, 0);
+        row.write_to(&mut rows[offset..]);
+        if let Some(sink) = self.sink.as_ref() {
+            sink.lock().unwrap().write(&rows[offset..]);
+        }
+    }
+
+    /// Flushes the sink, returns the error of the first failed write (if any)
+    pub fn flush(&self) -> io::Result<()> {
+        let Some(sink) = self.sink.as_ref() else {
+            return Ok(());
+        };
+        let mut sink_state = sink.lock().unwrap();
+        if let Some(err) = sink_state.error.as_ref() {
+            return Err(io::Error::new(err.kind(), err.to_string()));
+        }
+        sink_state.sink.flush()
+    }
+
+    pub fn push_memory_write(&self, clk: u64, offset: u32, data: &[u8]) {
+        for (i, chunk) in data.chunks(32).enumerate() {
+            let mut row = TraceRow {
+                clk,
+                side_effect: TraceSideEffect::MemoryWrite,
+                ..Default::default()
+            };
+            LittleEndian::write_u32(&mut row.address[0..4], offset + i as u32 * 32);
+            row.operands[0] = chunk.len() as u64;
+            row.value[..chunk.len()].copy_from_slice(chunk);
+            self.push(&row);
+        }
+    }
+
+    pub fn push_storage(&self, clk: u64, is_write: bool, key: &[u8], value: &[u8; 32]) {
+        let mut row = TraceRow {
+            clk,
+            side_effect: if is_write {
+                TraceSideEffect::StorageWrite
+            } else {
+                TraceSideEffect::StorageRead
+            },
+            value: *value,
+            ..Default::default()
+        };
+        row.address.copy_from_slice(&key[..32]);
+        self.push(&row);
+    }
+
+    pub fn push_function_enter(&self, clk: u64, func_idx: u32) {
+        self.push(&TraceRow {
+            clk,
+            operands: [func_idx as u64, 0, 0],
+            side_effect: TraceSideEffect::FunctionEnter,
+            ..Default::default()
+        });
+    }
+
+    pub fn push_function_exit(&self, clk: u64) {
+        self.push(&TraceRow {
+            clk,
+            side_effect: TraceSideEffect::FunctionExit,
+            ..Default::default()
+        });
+    }
+
+    pub fn push_call_enter(&self, clk: u64, depth: u32, code_hash: &[u8; 32], fuel_limit: u64) {
+        self.push(&TraceRow {
+            clk,
+            operands: [depth as u64, fuel_limit, 0],
+            side_effect: TraceSideEffect::CallEnter,
+            address: *code_hash,
+            ..Default::default()
+        });
+    }
+
+    pub fn push_call_exit(&self, clk: u64, depth: u32, fuel_consumed: u64, exit_code: i32) {
+        self.push(&TraceRow {
+            clk,
+            operands: [depth as u64, fuel_consumed, exit_code as u32 as u64],
+            side_effect: TraceSideEffect::CallExit,
+            ..Default::default()
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.read().unwrap().len() / TRACE_ROW_SIZE
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn header() -> [u8; TRACE_HEADER_SIZE] {
+        let mut header = [0u8; TRACE_HEADER_SIZE];
+        header[0..4].copy_from_slice(&TRACE_MAGIC);
+        LittleEndian::write_u32(&mut header[4..8], TRACE_FORMAT_VERSION);
+        LittleEndian::write_u32(&mut header[8..12], TRACE_ROW_SIZE as u32);
+        header
+    }
+
+    /// Returns serialized trace (header and rows)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let rows = self.rows.read().unwrap();
+        let mut result = Vec::with_capacity(TRACE_HEADER_SIZE + rows.len());
+        result.extend_from_slice(&Self::header());
+        result.extend_from_slice(&rows);
+        result
+    }
+
+    /// Splits rows into chunks of the fixed number of rows, chunks are leaves of the trace
+    /// commitment
+    pub fn chunks(&self, rows_per_chunk: usize) -> Vec<Vec<u8>> {
+        self.rows
+            .read()
+            .unwrap()
+            .chunks(rows_per_chunk * TRACE_ROW_SIZE)
+            .map(|chunk| chunk.to_vec())
+            .collect()
+    }
+}
+
+/// Reads rows of the serialized trace
+pub struct TraceReader<'a> {
+    rows: &'a [u8],
+}
+
+impl<'a> TraceReader<'a> {
+    pub fn new(trace: &'a [u8]) -> Result<Self, TraceFormatError> {
+        if trace.len() < TRACE_HEADER_SIZE {
+            return Err(TraceFormatError::UnexpectedEof);
+        } else if trace[0..4] != TRACE_MAGIC {
+            return Err(TraceFormatError::BadMagic);
+        }
+        let version = LittleEndian::read_u32(&trace[4..8]);
+        let row_size = LittleEndian::read_u32(&trace[8..12]);
+        if version != TRACE_FORMAT_VERSION || row_size != TRACE_ROW_SIZE as u32 {
+            return Err(TraceFormatError::UnsupportedVersion(version));
+        }
+        let rows = &trace[TRACE_HEADER_SIZE..];
+        if rows.len() % TRACE_ROW_SIZE != 0 {
+            return Err(TraceFormatError::UnexpectedEof);
+        }
+        Ok(Self { rows })
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len() / TRACE_ROW_SIZE
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn row(&self, index: usize) -> Option<Result<TraceRow, TraceFormatError>> {
+        let offset = index * TRACE_ROW_SIZE;
+        self.rows
+            .get(offset..offset + TRACE_ROW_SIZE)
+            .map(TraceRow::read_from)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = Result<TraceRow, TraceFormatError>> + 'a {
+        self.rows.chunks(TRACE_ROW_SIZE).map(TraceRow::read_from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::wat2rwasm,
+        trace::format::{
+            TraceReader,
+            TraceRow,
+            TraceSideEffect,
+            TraceWriter,
+            SYSCALL_OPCODE_FLAG,
+            TRACE_ROW_SIZE,
+        },
+        DefaultEmptyRuntimeDatabase,
+        Runtime,
+        RuntimeContext,
+    };
+    use fluentbase_types::SysFuncIdx;
+
+    #[test]
+    fn test_trace_roundtrip() {
+        let writer = TraceWriter::new();
+        writer.push(&TraceRow {
+            clk: 7,
+            opcode: SYSCALL_OPCODE_FLAG | 3,
+            operands: [1, 2, 3],
+            ..Default::default()
+        });
+        writer.push_memory_write(8, 100, &[0xff; 40]);
+        writer.push_storage(9, true, &[1u8; 32], &[2u8; 32]);
+        let trace = writer.to_bytes();
+        let reader = TraceReader::new(&trace).unwrap();
+        assert_eq!(reader.len(), 4);
+        let rows = reader.rows().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(rows[0].opcode, SYSCALL_OPCODE_FLAG | 3);
+        assert_eq!(rows[0].operands, [1, 2, 3]);
+        assert_eq!(rows[2].side_effect, TraceSideEffect::MemoryWrite);
+        assert_eq!(&rows[2].address[0..4], &132u32.to_le_bytes());
+        assert_eq!(rows[2].operands[0], 8);
+        assert_eq!(rows[3].side_effect, TraceSideEffect::StorageWrite);
+        assert_eq!(writer.chunks(3).len(), 2);
+        assert_eq!(writer.chunks(3)[1].len(), TRACE_ROW_SIZE);
+        assert!(TraceReader::new(&trace[..trace.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_runtime_writes_syscalls() {
+        let rwasm_binary = wat2rwasm(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 12
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "Hello, World")
+  (export "main" (func $main)))
+    "#,
+        );
+        let writer = TraceWriter::new();
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+            .with_fuel_limit(1_000_000)
+            .with_trace_writer(writer.clone());
+        Runtime::run_with_context(ctx).unwrap();
+        let trace = writer.to_bytes();
+        let rows = TraceReader::new(&trace)
+            .unwrap()
+            .rows()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(rows
+            .iter()
+            .any(|row| row.is_syscall()
+                && row.opcode & !SYSCALL_OPCODE_FLAG == SysFuncIdx::WRITE as u32));
+    }
+}
@@ -0,0 +1,174 @@
+use crate::trace::format::{TraceFormatError, TraceReader, TraceSideEffect, SYSCALL_OPCODE_FLAG};
+use byteorder::{ByteOrder, LittleEndian};
+use fluentbase_types::SysFuncIdx;
+use serde_json::{json, Value};
+
+/// Host call of the execution, memory is reported as the delta of the call (bytes written by
+/// the call) instead of the full snapshot
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostCallStep {
+    pub sys_func_idx: u32,
+    pub name: String,
+    /// Fuel remaining before the call
+    pub fuel: u64,
+    pub fuel_cost: u64,
+    /// Size of the memory touched by the writes of the frame so far
+    pub mem_size: u64,
+    pub depth: u32,
+    pub memory_delta: Vec<(u32, Vec<u8>)>,
+}
+
+impl HostCallStep {
+    pub fn to_json(&self) -> Value {
+        let memory_delta = self
+            .memory_delta
+            .iter()
+            .map(|(offset, data)| {
+                json!({
+                    "offset": offset,
+                    "data": format!("0x{}", hex::encode(data)),
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "syscall": self.sys_func_idx,
+            "name": self.name,
+            "fuel": format!("0x{:x}", self.fuel),
+            "fuelCost": format!("0x{:x}", self.fuel_cost),
+            "memSize": self.mem_size,
+            "depth": self.depth,
+            "memoryDelta": memory_delta,
+        })
+    }
+}
+
+struct Frame {
+    fuel_limit: u64,
+    mem_size: u64,
+    /// Index and clock of the previous step of the frame, its cost is known once the next step
+    /// is seen
+    pending_step: Option<(usize, u64)>,
+}
+
+/// Converts the trace of [`crate::trace::format::TraceWriter`] into host call steps.
+///
+/// The trace has rows for host calls only (rWASM instructions aren't traced), so it's not an
+/// EIP-3155 trace: there are no pcs or stacks. Memory writes are attached to the host call
+/// before them, nested calls increase the depth (the top-level call has depth 1). Clocks of
+/// the rows are consumed fuel, so the remaining fuel of the step is the limit of its frame
+/// minus the clock, the cost of the step is the fuel consumed until the next step of the same
+/// frame (including guest instructions executed in between and nested calls made by the step).
+pub fn trace_to_host_call_steps(
+    trace: &[u8],
+    fuel_limit: u64,
+) -> Result<Vec<HostCallStep>, TraceFormatError> {
+    let mut steps: Vec<HostCallStep> = vec![];
+    let mut frames = vec![Frame {
+        fuel_limit,
+        mem_size: 0,
+        pending_step: None,
+    }];
+    for row in TraceReader::new(trace)?.rows() {
+        let row = row?;
+        match row.side_effect {
+            TraceSideEffect::None if row.is_syscall() => {
+                let depth = frames.len() as u32;
+                let frame = frames.last_mut().unwrap();
+                if let Some((index, clk)) = frame.pending_step {
+                    steps[index].fuel_cost = row.clk.saturating_sub(clk);
+                }
+                let sys_func_idx = row.opcode & !SYSCALL_OPCODE_FLAG;
+                let name = SysFuncIdx::from_repr(sys_func_idx)
+                    .map(|v| v.to_string())
+                    .unwrap_or_else(|| format!("syscall[{}]", sys_func_idx));
+                frame.pending_step = Some((steps.len(), row.clk));
+                steps.push(HostCallStep {
+                    sys_func_idx,
+                    name,
+                    fuel: frame.fuel_limit.saturating_sub(row.clk),
+                    fuel_cost: 0,
+                    mem_size: frame.mem_size,
+                    depth,
+                    memory_delta: vec![],
+                });
+            }
+            TraceSideEffect::MemoryWrite => {
+                let offset = LittleEndian::read_u32(&row.address[0..4]);
+                let length = (row.operands[0] as usize).min(32);
+                let frame = frames.last_mut().unwrap();
+                frame.mem_size = frame.mem_size.max(offset as u64 + length as u64);
+                if let Some((index, _)) = frame.pending_step {
+                    steps[index]
+                        .memory_delta
+                        .push((offset, row.value[..length].to_vec()));
+                }
+            }
+            TraceSideEffect::CallEnter => frames.push(Frame {
+                fuel_limit: row.operands[1],
+                mem_size: 0,
+                pending_step: None,
+            }),
+            // exit of the top-level call isn't recorded, so the root frame is never popped
+            TraceSideEffect::CallExit if frames.len() > 1 => {
+                frames.pop();
+            }
+            _ => {}
+        }
+    }
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        trace::{
+            format::{TraceFormatError, TraceRow, TraceWriter},
+            json::trace_to_host_call_steps,
+        },
+        ExecutionResult,
+    };
+    use fluentbase_types::{ExitCode, Fuel, SysFuncIdx};
+
+    #[test]
+    fn test_trace_to_host_call_steps() {
+        let writer = TraceWriter::new();
+        writer.push(&TraceRow::syscall(10, SysFuncIdx::READ as u32));
+        writer.push_memory_write(10, 64, &[0xaa; 4]);
+        writer.push(&TraceRow::syscall(20, SysFuncIdx::EXEC as u32));
+        writer.push_call_enter(20, 1, &[7u8; 32], 500);
+        writer.push(&TraceRow::syscall(5, SysFuncIdx::WRITE as u32));
+        writer.push_call_exit(120, 1, 100, 0);
+        writer.push(&TraceRow::syscall(130, SysFuncIdx::EXIT as u32));
+        let trace = writer.to_bytes();
+
+        let steps = trace_to_host_call_steps(&trace, 1000).unwrap();
+        assert_eq!(steps.len(), 4);
+        assert_eq!(steps[0].name, SysFuncIdx::READ.to_string());
+        assert_eq!(steps[0].fuel, 990);
+        assert_eq!(steps[0].fuel_cost, 10);
+        assert_eq!(steps[0].memory_delta, vec![(64, vec![0xaa; 4])]);
+        // cost of the call includes the fuel of the callee
+        assert_eq!((steps[1].fuel_cost, steps[1].mem_size), (110, 68));
+        assert_eq!((steps[2].depth, steps[2].fuel), (2, 495));
+        assert_eq!((steps[3].depth, steps[3].fuel), (1, 870));
+
+        let result = ExecutionResult {
+            fuel_consumed: Fuel(130),
+            ..ExecutionResult::new_error(ExitCode::Panic.into_i32())
+        }
+        .with_trace(&trace);
+        let lines = result.host_call_trace_json(&trace, Fuel(1000)).unwrap();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0]["syscall"], SysFuncIdx::READ as u32);
+        assert_eq!(lines[0]["fuel"], "0x3de");
+        assert_eq!(lines[0]["memoryDelta"][0]["data"], "0xaaaaaaaa");
+        // only the recorded columns are exported
+        assert!(lines[0].get("pc").is_none() && lines[0].get("stack").is_none());
+        assert_eq!(lines[4]["fuelUsed"], "0x82");
+        assert_eq!(lines[4]["pass"], false);
+        assert_eq!(
+            result.host_call_trace_json(&trace[..trace.len() - 1], Fuel(1000)),
+            Err(TraceFormatError::HashMismatch)
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{DefaultEmptyRuntimeDatabase, ExecutionResult};
+use fluentbase_poseidon::{hash_with_domain, poseidon_hash};
+use halo2curves::bn256::Fr;
+
+const DOMAIN: Fr = Fr::zero();
+
+/// Public values of the execution that are committed as the public input of the validity proof
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PublicIo {
+    pub input: Vec<u8>,
+    pub output: Vec<u8>,
+    pub exit_code: i32,
+    pub pre_state_root: [u8; 32],
+    pub post_state_root: [u8; 32],
+}
+
+impl PublicIo {
+    pub fn new(
+        input: Vec<u8>,
+        execution_result: &ExecutionResult,
+        pre_state_root: [u8; 32],
+        post_state_root: [u8; 32],
+    ) -> Self {
+        Self {
+            input,
+            output: execution_result.output.clone(),
+            exit_code: execution_result.exit_code,
+            pre_state_root,
+            post_state_root,
+        }
+    }
+
+    /// Returns canonical commitment:
+    /// `p(p(p(h(input), h(output)), exit_code), p(c(pre_state_root), c(post_state_root)))`, where
+    /// `h` is a Poseidon hash of bytes, `c` compresses 32-byte value the same way as journaled
+    /// trie does and negative exit codes are encoded as negated field elements
+    pub fn commitment(&self) -> [u8; 32] {
+        let input_hash = Fr::from_bytes(&poseidon_hash(&self.input)).unwrap();
+        let output_hash = Fr::from_bytes(&poseidon_hash(&self.output)).unwrap();
+        let exit_code = Fr::from(self.exit_code.unsigned_abs() as u64);
+        let exit_code = if self.exit_code < 0 {
+            -exit_code
+        } else {
+            exit_code
+        };
+        let io_hash = hash_with_domain(&[input_hash, output_hash], &DOMAIN);
+        let io_hash = hash_with_domain(&[io_hash, exit_code], &DOMAIN);
+        let state_hash = hash_with_domain(
+            &[
+                DefaultEmptyRuntimeDatabase::compress_value(&self.pre_state_root),
+                DefaultEmptyRuntimeDatabase::compress_value(&self.post_state_root),
+            ],
+            &DOMAIN,
+        );
+        hash_with_domain(&[io_hash, state_hash], &DOMAIN).to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trace::public_io::PublicIo;
+
+    #[test]
+    fn test_public_io_commitment() {
+        let public_io = PublicIo {
+            input: b"input".to_vec(),
+            output: b"output".to_vec(),
+            exit_code: 0,
+            pre_state_root: [1u8; 32],
+            post_state_root: [2u8; 32],
+        };
+        let commitment = public_io.commitment();
+        assert_eq!(commitment, public_io.clone().commitment());
+        for changed in [
+            PublicIo {
+                exit_code: -1,
+                ..public_io.clone()
+            },
+            PublicIo {
+                exit_code: 1,
+                ..public_io.clone()
+            },
+            PublicIo {
+                input: b"output".to_vec(),
+                output: b"input".to_vec(),
+                ..public_io.clone()
+            },
+            PublicIo {
+                pre_state_root: [2u8; 32],
+                post_state_root: [1u8; 32],
+                ..public_io.clone()
+            },
+        ] {
+            assert_ne!(changed.commitment(), commitment);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use crate::{
+    trace::commitment::{commit_trace, TraceCommitmentScheme},
+    types::RuntimeError,
+    ExecutionResult,
+    Runtime,
+    RuntimeContext,
+};
+use byteorder::{ByteOrder, LittleEndian};
+use fluentbase_poseidon::poseidon_hash;
+use fluentbase_types::{Fuel, IJournaledTrie};
+
+/// Default amount of fuel executed by one segment
+pub const DEFAULT_SEGMENT_SIZE: Fuel = Fuel(1 << 20);
+
+/// Granularity of the memory deltas
+pub const MEMORY_DELTA_BLOCK_SIZE: usize = 256;
+
+const MEMORY_PAGE_SIZE: u32 = 0x10000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryDelta {
+    pub offset: u32,
+    pub data: Vec<u8>,
+}
+
+/// Part of the execution between two fuel boundaries, a segment carries hashes of the memory
+/// at both boundaries and the memory delta, so it can be proven independently of other segments
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceSegment {
+    pub index: u32,
+    pub fuel_start: Fuel,
+    pub fuel_end: Fuel,
+    pub memory_size: u32,
+    pub start_memory_hash: [u8; 32],
+    pub end_memory_hash: [u8; 32],
+    pub memory_delta: Vec<MemoryDelta>,
+    /// Exit code of the execution (only for the final segment)
+    pub exit_code: Option<i32>,
+}
+
+impl TraceSegment {
+    /// Serializes segment into the chunk committed by the trace commitment
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        let mut buffer = [0u8; 8];
+        LittleEndian::write_u32(&mut buffer, self.index);
+        result.extend_from_slice(&buffer[..4]);
+        LittleEndian::write_u64(&mut buffer, self.fuel_start.get());
+        result.extend_from_slice(&buffer);
+        LittleEndian::write_u64(&mut buffer, self.fuel_end.get());
+        result.extend_from_slice(&buffer);
+        LittleEndian::write_u32(&mut buffer, self.memory_size);
+        result.extend_from_slice(&buffer[..4]);
+        result.extend_from_slice(&self.start_memory_hash);
+        result.extend_from_slice(&self.end_memory_hash);
+        LittleEndian::write_u32(&mut buffer, self.memory_delta.len() as u32);
+        result.extend_from_slice(&buffer[..4]);
+        for delta in self.memory_delta.iter() {
+            LittleEndian::write_u32(&mut buffer, delta.offset);
+            result.extend_from_slice(&buffer[..4]);
+            LittleEndian::write_u32(&mut buffer, delta.data.len() as u32);
+            result.extend_from_slice(&buffer[..4]);
+            result.extend_from_slice(&delta.data);
+        }
+        match self.exit_code {
+            Some(exit_code) => {
+                result.push(1);
+                LittleEndian::write_i32(&mut buffer, exit_code);
+                result.extend_from_slice(&buffer[..4]);
+            }
+            None => result.push(0),
+        }
+        result
+    }
+
+    /// Applies memory delta of the segment to the memory snapshot at the segment start
+    pub fn apply_delta(&self, memory: &mut Vec<u8>) {
+        if memory.len() < self.memory_size as usize {
+            memory.resize(self.memory_size as usize, 0);
+        }
+        for delta in self.memory_delta.iter() {
+            let offset = delta.offset as usize;
+            memory[offset..offset + delta.data.len()].copy_from_slice(&delta.data);
+        }
+    }
+}
+
+/// Returns changed blocks of the memory, memory can only grow during the execution
+pub fn memory_delta(prev: &[u8], next: &[u8]) -> Vec<MemoryDelta> {
+    next.chunks(MEMORY_DELTA_BLOCK_SIZE)
+        .enumerate()
+        .filter_map(|(i, block)| {
+            let offset = i * MEMORY_DELTA_BLOCK_SIZE;
+            let prev_block = prev
+                .get(offset..)
+                .map(|prev| &prev[..prev.len().min(block.len())]);
+            let is_changed = match prev_block {
+                Some(prev_block) if prev_block.len() == block.len() => prev_block != block,
+                // new memory is zero-initialized
+                _ => block.iter().any(|v| *v != 0),
+            };
+            is_changed.then(|| MemoryDelta {
+                offset: offset as u32,
+                data: block.to_vec(),
+            })
+        })
+        .collect()
+}
+
+/// Returns commitment over the serialized segments
+pub fn commit_segments(segments: &[TraceSegment], scheme: TraceCommitmentScheme) -> [u8; 32] {
+    let chunks = segments
+        .iter()
+        .map(|segment| segment.to_bytes())
+        .collect::<Vec<_>>();
+    commit_trace(&chunks, scheme)
+}
+
+impl<DB: IJournaledTrie + Clone> Runtime<DB> {
+    /// Splits execution into segments of the fixed fuel size for the parallel proving.
+    ///
+    /// Execution is replayed up to every boundary (the out of fuel trap stops it there) to
+    /// snapshot memory of the root call, so the cost is quadratic in number of segments. All
+    /// attempts except the last one are rolled back, the last one keeps state changes.
+    pub fn split_into_segments(
+        mut runtime_context: RuntimeContext<DB>,
+        segment_size: Fuel,
+    ) -> Result<(ExecutionResult, Vec<TraceSegment>), RuntimeError> {
+        assert!(!segment_size.is_zero(), "segment size must be positive");
+        runtime_context.resolve_bytecode();
+
+        // execute once to know total fuel
+        let checkpoint = runtime_context.jzkt.as_ref().map(|jzkt| jzkt.checkpoint());
+        let execution_result = Self::new(runtime_context.clone()).call()?;
+        if let (Some(jzkt), Some(checkpoint)) = (runtime_context.jzkt.as_ref(), checkpoint) {
+            jzkt.rollback(checkpoint);
+        }
+
+        let mut boundaries = vec![(Fuel::ZERO, Vec::new())];
+        let mut boundary = segment_size;
+        while boundary < execution_result.fuel_consumed {
+            let checkpoint = runtime_context.jzkt.as_ref().map(|jzkt| jzkt.checkpoint());
+            let mut runtime = Self::new(runtime_context.clone().with_fuel_limit(boundary));
+            let partial_result = runtime.call()?;
+            let memory = runtime.memory_snapshot();
+            drop(runtime);
+            if let (Some(jzkt), Some(checkpoint)) = (runtime_context.jzkt.as_ref(), checkpoint) {
+                jzkt.rollback(checkpoint);
+            }
+            // fuel is charged per block, so the execution might stop before the boundary
+            if partial_result.fuel_consumed > boundaries.last().unwrap().0 {
+                boundaries.push((partial_result.fuel_consumed, memory));
+            }
+            boundary += segment_size;
+        }
+
+        let mut runtime = Self::new(runtime_context);
+        let execution_result = runtime.call()?;
+        boundaries.push((execution_result.fuel_consumed, runtime.memory_snapshot()));
+
+        let segments = boundaries
+            .windows(2)
+            .enumerate()
+            .map(|(index, window)| {
+                let ((fuel_start, prev), (fuel_end, next)) = (&window[0], &window[1]);
+                TraceSegment {
+                    index: index as u32,
+                    fuel_start: *fuel_start,
+                    fuel_end: *fuel_end,
+                    memory_size: next.len() as u32,
+                    start_memory_hash: poseidon_hash(prev),
+                    end_memory_hash: poseidon_hash(next),
+                    memory_delta: memory_delta(prev, next),
+                    exit_code: (index + 2 == boundaries.len())
+                        .then_some(execution_result.exit_code),
+                }
+            })
+            .collect();
+        Ok((execution_result, segments))
+    }
+
+    /// Reads whole linear memory of the last executed instance page by page
+    pub(crate) fn memory_snapshot(&mut self) -> Vec<u8> {
+        let mut memory = Vec::new();
+        while let Ok(page) = self.read_memory(memory.len() as u32, MEMORY_PAGE_SIZE) {
+            memory.extend(page);
+        }
+        memory
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::wat2rwasm,
+        trace::segment::{memory_delta, MemoryDelta, MEMORY_DELTA_BLOCK_SIZE},
+        DefaultEmptyRuntimeDatabase,
+        Runtime,
+        RuntimeContext,
+    };
+    use fluentbase_types::Fuel;
+
+    #[test]
+    fn test_memory_delta() {
+        let prev = vec![0u8; MEMORY_DELTA_BLOCK_SIZE * 2];
+        let mut next = vec![0u8; MEMORY_DELTA_BLOCK_SIZE * 3];
+        next[MEMORY_DELTA_BLOCK_SIZE + 1] = 7;
+        let delta = memory_delta(&prev, &next);
+        assert_eq!(delta.len(), 1);
+        assert_eq!(delta[0].offset, MEMORY_DELTA_BLOCK_SIZE as u32);
+        next[MEMORY_DELTA_BLOCK_SIZE * 2] = 1;
+        let delta = memory_delta(&prev, &next);
+        assert_eq!(delta.len(), 2);
+        assert_eq!(
+            delta[1],
+            MemoryDelta {
+                offset: MEMORY_DELTA_BLOCK_SIZE as u32 * 2,
+                data: next[MEMORY_DELTA_BLOCK_SIZE * 2..].to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_split_into_segments() {
+        let rwasm_binary = wat2rwasm(
+            r#"
+(module
+  (func $main
+    (local $i i32)
+    (loop $loop
+      local.get $i
+      local.get $i
+      i32.store
+      local.get $i
+      i32.const 4
+      i32.add
+      local.tee $i
+      i32.const 4000
+      i32.lt_u
+      br_if $loop
+    )
+  )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+        );
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+            .with_fuel_limit(10_000_000);
+        let (execution_result, segments) = Runtime::split_into_segments(ctx, Fuel(1_000)).unwrap();
+        assert_eq!(execution_result.exit_code, 0);
+        assert!(segments.len() > 1);
+        let mut memory = Vec::new();
+        for (i, segment) in segments.iter().enumerate() {
+            assert_eq!(segment.index, i as u32);
+            if i > 0 {
+                assert_eq!(segment.fuel_start, segments[i - 1].fuel_end);
+                assert_eq!(segment.start_memory_hash, segments[i - 1].end_memory_hash);
+            }
+            segment.apply_delta(&mut memory);
+        }
+        assert_eq!(segments.last().unwrap().exit_code, Some(0));
+        assert_eq!(
+            segments.last().unwrap().fuel_end,
+            execution_result.fuel_consumed
+        );
+        assert_eq!(&memory[4..8], &4u32.to_le_bytes());
+    }
+}
@@ -0,0 +1,242 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::Path,
+};
+
+/// Destination of the serialized trace, the writer passes the header once and then every row as
+/// soon as it's pushed, so consumers can process the trace while the execution is in progress.
+///
+/// Rows may be buffered by the sink, they must be visible to consumers after `flush`.
+pub trait TraceSink: Send + Sync {
+    fn write(&mut self, data: &[u8]) -> io::Result<()>;
+
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+/// Appends the trace to a file, readers can follow the file with the row size granularity
+pub struct FileTraceSink {
+    writer: BufWriter<File>,
+}
+
+impl FileTraceSink {
+    /// Creates (or truncates) the file
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+        })
+    }
+}
+
+impl TraceSink for FileTraceSink {
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.writer.write_all(data)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Streams the trace to a TCP socket, the stream is half-closed on drop
+pub struct SocketTraceSink {
+    writer: BufWriter<TcpStream>,
+}
+
+impl SocketTraceSink {
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Ok(Self::from_stream(TcpStream::connect(addr)?))
+    }
+
+    pub fn from_stream(stream: TcpStream) -> Self {
+        Self {
+            writer: BufWriter::new(stream),
+        }
+    }
+}
+
+impl TraceSink for SocketTraceSink {
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.writer.write_all(data)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl Drop for SocketTraceSink {
+    fn drop(&mut self) {
+        if self.writer.flush().is_ok() {
+            let _ = self.writer.get_ref().shutdown(std::net::Shutdown::Write);
+        }
+    }
+}
+
+/// Size of the mapped file prefix that stores the length of the written trace
+#[cfg(feature = "trace-mmap")]
+pub const MMAP_LENGTH_PREFIX_SIZE: usize = 8;
+
+/// Writes the trace into a memory-mapped file of the fixed capacity. The file starts with the
+/// length of the written trace (little-endian u64), the length is updated after the data, so
+/// readers that map the same file never observe partially written rows.
+#[cfg(feature = "trace-mmap")]
+pub struct MmapTraceSink {
+    mmap: memmap2::MmapMut,
+    len: usize,
+}
+
+#[cfg(feature = "trace-mmap")]
+impl MmapTraceSink {
+    /// Creates (or truncates) the file and maps it, `capacity` is the max size of the trace
+    pub fn create<P: AsRef<Path>>(path: P, capacity: usize) -> io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len((MMAP_LENGTH_PREFIX_SIZE + capacity) as u64)?;
+        // SAFETY: the file is created by us, other processes are expected to map it read-only
+        let mmap = unsafe { memmap2::MmapMut::map_mut(&file)? };
+        Ok(Self { mmap, len: 0 })
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.mmap.len() - MMAP_LENGTH_PREFIX_SIZE
+    }
+
+    /// Returns the written part of the trace from the mapped file
+    pub fn committed(mapped: &[u8]) -> &[u8] {
+        let Some(prefix) = mapped.get(..MMAP_LENGTH_PREFIX_SIZE) else {
+            return &[];
+        };
+        let len = u64::from_le_bytes(prefix.try_into().unwrap()) as usize;
+        let end = (MMAP_LENGTH_PREFIX_SIZE + len).min(mapped.len());
+        &mapped[MMAP_LENGTH_PREFIX_SIZE..end]
+    }
+}
+
+#[cfg(feature = "trace-mmap")]
+impl TraceSink for MmapTraceSink {
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        if self.len + data.len() > self.capacity() {
+            return Err(io::Error::new(
+                io::ErrorKind::WriteZero,
+                "trace exceeds capacity of the mapped file",
+            ));
+        }
+        let offset = MMAP_LENGTH_PREFIX_SIZE + self.len;
+        self.mmap[offset..offset + data.len()].copy_from_slice(data);
+        self.len += data.len();
+        // data must be visible before the length
+        std::sync::atomic::fence(std::sync::atomic::Ordering::Release);
+        self.mmap[..MMAP_LENGTH_PREFIX_SIZE].copy_from_slice(&(self.len as u64).to_le_bytes());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.mmap.flush_async()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::wat2rwasm,
+        trace::{
+            format::{TraceReader, TraceRow, TraceWriter},
+            sink::{FileTraceSink, SocketTraceSink, TraceSink},
+        },
+        DefaultEmptyRuntimeDatabase,
+        Runtime,
+        RuntimeContext,
+    };
+    use std::{fs, io, io::Read, net::TcpListener};
+
+    fn run_traced(trace_writer: TraceWriter) {
+        let rwasm_binary = wat2rwasm(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 12
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "Hello, World")
+  (export "main" (func $main)))
+    "#,
+        );
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary)
+            .with_fuel_limit(1_000_000)
+            .with_trace_writer(trace_writer);
+        Runtime::run_with_context(ctx).unwrap();
+    }
+
+    #[test]
+    fn test_file_and_socket_sinks() {
+        let path = std::env::temp_dir().join(format!("fluentbase-trace-{}", std::process::id()));
+        let trace_writer = TraceWriter::new().with_sink(FileTraceSink::create(&path).unwrap());
+        run_traced(trace_writer.clone());
+        let trace = fs::read(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(trace, trace_writer.to_bytes());
+        assert!(!TraceReader::new(&trace).unwrap().is_empty());
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let sink = SocketTraceSink::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+        // rows pushed before the sink is attached are written too
+        let trace_writer = TraceWriter::new();
+        trace_writer.push(&TraceRow::default());
+        let trace_writer = trace_writer.with_sink(sink);
+        run_traced(trace_writer.clone());
+        let expected = trace_writer.to_bytes();
+        drop(trace_writer);
+        let mut trace = vec![];
+        stream.read_to_end(&mut trace).unwrap();
+        assert_eq!(trace, expected);
+    }
+
+    struct FailingSink;
+
+    impl TraceSink for FailingSink {
+        fn write(&mut self, _data: &[u8]) -> io::Result<()> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_sink_error_is_sticky() {
+        let trace_writer = TraceWriter::new().with_sink(FailingSink);
+        run_traced(trace_writer.clone());
+        assert_eq!(
+            trace_writer.flush().unwrap_err().kind(),
+            io::ErrorKind::BrokenPipe
+        );
+        // rows are still collected in memory
+        assert!(!trace_writer.is_empty());
+    }
+
+    #[cfg(feature = "trace-mmap")]
+    #[test]
+    fn test_mmap_sink() {
+        use crate::trace::sink::MmapTraceSink;
+        let path = std::env::temp_dir().join(format!("fluentbase-mmap-{}", std::process::id()));
+        let trace_writer =
+            TraceWriter::new().with_sink(MmapTraceSink::create(&path, 1 << 16).unwrap());
+        run_traced(trace_writer.clone());
+        let mapped = fs::read(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(MmapTraceSink::committed(&mapped), trace_writer.to_bytes());
+    }
+}
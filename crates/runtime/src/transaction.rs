@@ -0,0 +1,452 @@
+use crate::{
+    instruction::{ecrecover::SyscallEcrecover, keccak256::SyscallKeccak256},
+    RuntimeGasFees,
+};
+use byteorder::{ByteOrder, LittleEndian};
+use fluentbase_codec::rlp::{rlp_encode_bytes, rlp_encode_header, RlpEncode};
+use fluentbase_types::{
+    Address,
+    Bytes,
+    Fuel,
+    FuelSchedule,
+    Gas,
+    IJournaledTrie,
+    B256,
+    JZKT_ACCOUNT_BALANCE_FIELD,
+    JZKT_ACCOUNT_COMPRESSION_FLAGS,
+    JZKT_ACCOUNT_FIELDS_COUNT,
+    JZKT_ACCOUNT_NONCE_FIELD,
+    JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+    JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD,
+    KECCAK_EMPTY,
+    POSEIDON_EMPTY,
+    U256,
+};
+
+pub const TX_BASE_GAS: u64 = 21_000;
+pub const TX_CREATE_GAS: u64 = 32_000;
+pub const TX_DATA_ZERO_GAS: u64 = 4;
+pub const TX_DATA_NON_ZERO_GAS: u64 = 16;
+pub const TX_INITCODE_WORD_GAS: u64 = 2;
+pub const TX_ACCESS_LIST_ADDRESS_GAS: u64 = 2_400;
+pub const TX_ACCESS_LIST_STORAGE_KEY_GAS: u64 = 1_900;
+
+/// Half of the secp256k1 curve order, signatures with a bigger `s` are malleable (EIP-2)
+const SECP256K1N_HALF: U256 = U256::from_limbs([
+    0xdfe92f46681b20a0,
+    0x5d576e7357a4501d,
+    0xffffffffffffffff,
+    0x7fffffffffffffff,
+]);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransactionError {
+    NonceTooLow {
+        expected: u64,
+        actual: u64,
+    },
+    NonceTooHigh {
+        expected: u64,
+        actual: u64,
+    },
+    NonceOverflow,
+    InsufficientFunds {
+        required: U256,
+        available: U256,
+    },
+    IntrinsicGasTooLow {
+        required: Gas,
+        gas_limit: Gas,
+    },
+    FeeCapTooLow {
+        max_fee_per_gas: U256,
+        base_fee: U256,
+    },
+    PriorityFeeGreaterThanMaxFee,
+    InvalidSignature,
+    CallerMismatch {
+        expected: Address,
+        recovered: Address,
+    },
+}
+
+/// Envelope of the transaction, defines the payload of the signing hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionType {
+    /// EIP-155 legacy transaction, `max_fee_per_gas` is the gas price
+    Legacy,
+    /// EIP-2930 transaction with the access list, `max_fee_per_gas` is the gas price
+    AccessList,
+    /// EIP-1559 transaction with the priority fee
+    DynamicFee,
+}
+
+/// Signed transaction, the signing hash is computed from the transaction fields (see
+/// [`Transaction::signing_hash`])
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction {
+    pub tx_type: TransactionType,
+    pub chain_id: u64,
+    pub caller: Address,
+    /// Target of the call or `None` for the deployment
+    pub to: Option<Address>,
+    pub nonce: u64,
+    pub gas_limit: Gas,
+    pub gas_fees: RuntimeGasFees,
+    pub value: U256,
+    pub data: Bytes,
+    pub access_list: Vec<(Address, Vec<U256>)>,
+    pub signature: [u8; 64],
+    pub recovery_id: u8,
+}
+
+impl Transaction {
+    /// Gas charged before the execution: base cost, calldata, deployment and access list costs
+    pub fn intrinsic_gas(&self) -> Gas {
+        let zero_bytes = self.data.iter().filter(|byte| **byte == 0).count() as u64;
+        let non_zero_bytes = self.data.len() as u64 - zero_bytes;
+        let mut gas =
+            TX_BASE_GAS + zero_bytes * TX_DATA_ZERO_GAS + non_zero_bytes * TX_DATA_NON_ZERO_GAS;
+        if self.to.is_none() {
+            gas += TX_CREATE_GAS + (self.data.len() as u64).div_ceil(32) * TX_INITCODE_WORD_GAS;
+        }
+        for (_, storage_keys) in self.access_list.iter() {
+            gas += TX_ACCESS_LIST_ADDRESS_GAS
+                + storage_keys.len() as u64 * TX_ACCESS_LIST_STORAGE_KEY_GAS;
+        }
+        Gas(gas)
+    }
+
+    /// Fuel limit of the execution, gas left after the intrinsic charge converted by the schedule
+    pub fn execution_fuel_limit(&self, fuel_schedule: &FuelSchedule) -> Fuel {
+        fuel_schedule.gas_to_fuel(self.gas_limit.saturating_sub(self.intrinsic_gas()))
+    }
+
+    /// Max amount of funds the transaction can spend, `gas_limit * max_fee + value`
+    pub fn max_cost(&self) -> U256 {
+        U256::from(self.gas_limit.get())
+            .saturating_mul(self.gas_fees.max_fee_per_gas())
+            .saturating_add(self.value)
+    }
+
+    /// Hash signed by the caller: `keccak256(rlp([nonce, gas_price, gas_limit, to, value, data,
+    /// chain_id, 0, 0]))` for legacy transactions and `keccak256(type || rlp(fields))` for typed
+    /// ones (EIP-2718), so the signature commits to every field and the chain id
+    pub fn signing_hash(&self) -> B256 {
+        let mut payload = Vec::new();
+        match self.tx_type {
+            TransactionType::Legacy => {
+                self.encode_common_fields(&mut payload, &[self.gas_fees.max_fee_per_gas()]);
+                self.chain_id.rlp_encode(&mut payload);
+                0u8.rlp_encode(&mut payload);
+                0u8.rlp_encode(&mut payload);
+            }
+            TransactionType::AccessList => {
+                self.chain_id.rlp_encode(&mut payload);
+                self.encode_common_fields(&mut payload, &[self.gas_fees.max_fee_per_gas()]);
+                self.encode_access_list(&mut payload);
+            }
+            TransactionType::DynamicFee => {
+                self.chain_id.rlp_encode(&mut payload);
+                let priority_fee = self.gas_fees.max_priority_fee_per_gas().unwrap_or_default();
+                self.encode_common_fields(
+                    &mut payload,
+                    &[priority_fee, self.gas_fees.max_fee_per_gas()],
+                );
+                self.encode_access_list(&mut payload);
+            }
+        }
+        let mut encoded = Vec::with_capacity(payload.len() + 10);
+        match self.tx_type {
+            TransactionType::Legacy => {}
+            TransactionType::AccessList => encoded.push(0x01),
+            TransactionType::DynamicFee => encoded.push(0x02),
+        }
+        rlp_encode_header(&mut encoded, true, payload.len());
+        encoded.extend_from_slice(&payload);
+        B256::from(SyscallKeccak256::fn_impl(&encoded))
+    }
+
+    /// Encodes `nonce, fees.., gas_limit, to, value, data`
+    fn encode_common_fields(&self, out: &mut Vec<u8>, fees: &[U256]) {
+        self.nonce.rlp_encode(out);
+        fees.iter().for_each(|fee| fee.rlp_encode(out));
+        self.gas_limit.get().rlp_encode(out);
+        match self.to {
+            Some(to) => to.rlp_encode(out),
+            None => rlp_encode_bytes(out, &[]),
+        }
+        self.value.rlp_encode(out);
+        self.data.rlp_encode(out);
+    }
+
+    /// Encodes the access list as `[[address, [storage_key, ..]], ..]`
+    fn encode_access_list(&self, out: &mut Vec<u8>) {
+        let mut payload = Vec::new();
+        for (address, storage_keys) in self.access_list.iter() {
+            let mut item = Vec::new();
+            address.rlp_encode(&mut item);
+            storage_keys
+                .iter()
+                .map(|key| B256::from(key.to_be_bytes::<32>()))
+                .collect::<Vec<_>>()
+                .rlp_encode(&mut item);
+            rlp_encode_header(&mut payload, true, item.len());
+            payload.extend_from_slice(&item);
+        }
+        rlp_encode_header(out, true, payload.len());
+        out.extend_from_slice(&payload);
+    }
+
+    /// Recovers the signer using the same routine as the `_ecrecover` syscall, signatures with
+    /// high `s` are rejected (EIP-2)
+    pub fn recover_caller(&self) -> Result<Address, TransactionError> {
+        if U256::from_be_slice(&self.signature[32..]) > SECP256K1N_HALF {
+            return Err(TransactionError::InvalidSignature);
+        }
+        let public_key = SyscallEcrecover::fn_impl(
+            self.signing_hash().as_slice(),
+            &self.signature,
+            self.recovery_id as u32,
+        )
+        .map_err(|_| TransactionError::InvalidSignature)?;
+        // address is the last 20 bytes of the public key hash (without the sec1 prefix)
+        let hash = SyscallKeccak256::fn_impl(&public_key[1..]);
+        Ok(Address::from_slice(&hash[12..]))
+    }
+}
+
+/// Checks that the transaction can be included on top of the journal state, returns the
+/// intrinsic gas of the transaction. The state is not modified.
+pub fn validate_transaction<DB: IJournaledTrie>(
+    jzkt: &DB,
+    tx: &Transaction,
+) -> Result<Gas, TransactionError> {
+    let recovered = tx.recover_caller()?;
+    if recovered != tx.caller {
+        return Err(TransactionError::CallerMismatch {
+            expected: tx.caller,
+            recovered,
+        });
+    }
+    let gas_fees = &tx.gas_fees;
+    if gas_fees.max_fee_per_gas() < gas_fees.base_fee() {
+        return Err(TransactionError::FeeCapTooLow {
+            max_fee_per_gas: gas_fees.max_fee_per_gas(),
+            base_fee: gas_fees.base_fee(),
+        });
+    }
+    if gas_fees
+        .max_priority_fee_per_gas()
+        .is_some_and(|priority_fee| priority_fee > gas_fees.max_fee_per_gas())
+    {
+        return Err(TransactionError::PriorityFeeGreaterThanMaxFee);
+    }
+    let intrinsic_gas = tx.intrinsic_gas();
+    if tx.gas_limit < intrinsic_gas {
+        return Err(TransactionError::IntrinsicGasTooLow {
+            required: intrinsic_gas,
+            gas_limit: tx.gas_limit,
+        });
+    }
+    let (balance, nonce) = account_balance_and_nonce(jzkt, &tx.caller);
+    if tx.nonce < nonce {
+        return Err(TransactionError::NonceTooLow {
+            expected: nonce,
+            actual: tx.nonce,
+        });
+    } else if tx.nonce > nonce {
+        return Err(TransactionError::NonceTooHigh {
+            expected: nonce,
+            actual: tx.nonce,
+        });
+    } else if nonce == u64::MAX {
+        return Err(TransactionError::NonceOverflow);
+    }
+    let required = tx.max_cost();
+    if balance < required {
+        return Err(TransactionError::InsufficientFunds {
+            required,
+            available: balance,
+        });
+    }
+    Ok(intrinsic_gas)
+}
+
+/// Validates the transaction and bumps the caller's nonce in the journal, the nonce stays
+/// bumped even if the execution fails, so the bump must be done before the execution checkpoint
+pub fn include_transaction<DB: IJournaledTrie>(
+    jzkt: &DB,
+    tx: &Transaction,
+) -> Result<Gas, TransactionError> {
+    let intrinsic_gas = validate_transaction(jzkt, tx)?;
+    let address32 = tx.caller.into_word();
+    let mut fields = match jzkt.get(&address32, false) {
+        Some((fields, _, _)) => fields,
+        None => {
+            let mut fields = vec![[0u8; 32]; JZKT_ACCOUNT_FIELDS_COUNT as usize];
+            fields[JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD as usize] = KECCAK_EMPTY.0;
+            fields[JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD as usize] = POSEIDON_EMPTY.0;
+            fields
+        }
+    };
+    LittleEndian::write_u64(&mut fields[JZKT_ACCOUNT_NONCE_FIELD as usize], tx.nonce + 1);
+    jzkt.update(&address32, &fields, JZKT_ACCOUNT_COMPRESSION_FLAGS);
+    Ok(intrinsic_gas)
+}
+
+fn account_balance_and_nonce<DB: IJournaledTrie>(jzkt: &DB, address: &Address) -> (U256, u64) {
+    match jzkt.get(&address.into_word(), false) {
+        Some((fields, _, _)) => (
+            U256::from_le_slice(&fields[JZKT_ACCOUNT_BALANCE_FIELD as usize]),
+            LittleEndian::read_u64(&fields[JZKT_ACCOUNT_NONCE_FIELD as usize]),
+        ),
+        None => (U256::ZERO, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        import::StateImporter,
+        transaction::{
+            include_transaction,
+            validate_transaction,
+            Transaction,
+            TransactionError,
+            TransactionType,
+            SECP256K1N_HALF,
+        },
+        types::InMemoryTrieDb,
+        zktrie::ZkTrieStateDb,
+        JournaledTrie,
+        RuntimeGasFees,
+    };
+    use fluentbase_types::{Address, Bytes, Fuel, FuelSchedule, Gas, U256};
+    use k256::ecdsa::SigningKey;
+
+    fn sign(signing_key: &SigningKey, tx: &mut Transaction) {
+        let (signature, recovery_id) = signing_key
+            .sign_prehash_recoverable(tx.signing_hash().as_slice())
+            .unwrap();
+        tx.signature.copy_from_slice(&signature.to_bytes());
+        tx.recovery_id = recovery_id.to_byte();
+    }
+
+    fn signed_transaction(signing_key: &SigningKey, nonce: u64, value: U256) -> Transaction {
+        let mut tx = Transaction {
+            tx_type: TransactionType::DynamicFee,
+            chain_id: 1337,
+            caller: Address::ZERO,
+            to: Some(Address::repeat_byte(0x11)),
+            nonce,
+            gas_limit: Gas(30_000),
+            gas_fees: RuntimeGasFees::new(U256::from(10), U256::from(20), Some(U256::from(2))),
+            value,
+            data: Bytes::from_static(&[0, 1, 2]),
+            access_list: vec![],
+            signature: [0u8; 64],
+            recovery_id: 0,
+        };
+        sign(signing_key, &mut tx);
+        tx.caller = tx.recover_caller().unwrap();
+        tx
+    }
+
+    #[test]
+    fn test_validate_and_include_transaction() {
+        let signing_key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+        let jzkt = JournaledTrie::new(ZkTrieStateDb::new_empty(InMemoryTrieDb::default()));
+        let tx = signed_transaction(&signing_key, 0, U256::from(1_000));
+        // 21000 + 4 + 16 * 2
+        assert_eq!(tx.intrinsic_gas(), Gas(21_036));
+        assert_eq!(
+            tx.execution_fuel_limit(&FuelSchedule::new(1000)),
+            Fuel(8_964_000)
+        );
+        assert!(matches!(
+            validate_transaction(&jzkt, &tx),
+            Err(TransactionError::InsufficientFunds { .. })
+        ));
+        let mut importer = StateImporter::new(jzkt.clone());
+        importer
+            .import_account(&tx.caller, U256::from(1_000_000), 0, &[])
+            .unwrap();
+        importer.finish().unwrap();
+        assert_eq!(include_transaction(&jzkt, &tx), Ok(Gas(21_036)));
+        // the same transaction can't be included twice
+        assert_eq!(
+            validate_transaction(&jzkt, &tx),
+            Err(TransactionError::NonceTooLow {
+                expected: 1,
+                actual: 0
+            })
+        );
+        let tx = signed_transaction(&signing_key, 2, U256::ZERO);
+        assert!(matches!(
+            validate_transaction(&jzkt, &tx),
+            Err(TransactionError::NonceTooHigh { .. })
+        ));
+        // signature doesn't match the caller
+        let mut tx = signed_transaction(&signing_key, 1, U256::ZERO);
+        tx.caller = Address::repeat_byte(0x22);
+        assert!(matches!(
+            validate_transaction(&jzkt, &tx),
+            Err(TransactionError::CallerMismatch { .. })
+        ));
+        let mut tx = signed_transaction(&signing_key, 1, U256::ZERO);
+        tx.gas_limit = Gas(21_000);
+        assert!(matches!(
+            validate_transaction(&jzkt, &tx),
+            Err(TransactionError::IntrinsicGasTooLow { .. })
+        ));
+    }
+
+    #[test]
+    fn test_signature_commits_to_fields() {
+        let signing_key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+        let tx = signed_transaction(&signing_key, 0, U256::from(1_000));
+        let jzkt = JournaledTrie::new(ZkTrieStateDb::new_empty(InMemoryTrieDb::default()));
+        let mut importer = StateImporter::new(jzkt.clone());
+        importer
+            .import_account(&tx.caller, U256::from(1_000_000), 0, &[])
+            .unwrap();
+        importer.finish().unwrap();
+        assert_eq!(validate_transaction(&jzkt, &tx), Ok(Gas(21_036)));
+        // changed fields don't match the signature, so another signer is recovered
+        let mut changed = tx.clone();
+        changed.value = U256::from(999_000);
+        assert!(matches!(
+            validate_transaction(&jzkt, &changed),
+            Err(TransactionError::CallerMismatch { .. })
+        ));
+        let mut changed = tx.clone();
+        changed.nonce = 1;
+        assert!(matches!(
+            validate_transaction(&jzkt, &changed),
+            Err(TransactionError::CallerMismatch { .. })
+        ));
+        let mut changed = tx.clone();
+        changed.chain_id = 1;
+        assert!(matches!(
+            validate_transaction(&jzkt, &changed),
+            Err(TransactionError::CallerMismatch { .. })
+        ));
+        // every envelope has its own signing hash
+        let mut legacy = tx.clone();
+        legacy.tx_type = TransactionType::Legacy;
+        assert_ne!(legacy.signing_hash(), tx.signing_hash());
+        sign(&signing_key, &mut legacy);
+        assert_eq!(validate_transaction(&jzkt, &legacy), Ok(Gas(21_036)));
+        // the same signature with `s' = n - s` and flipped parity recovers the same signer
+        let curve_order = SECP256K1N_HALF * U256::from(2) + U256::from(1);
+        let s = U256::from_be_slice(&tx.signature[32..]);
+        let mut malleable = tx.clone();
+        malleable.signature[32..].copy_from_slice(&(curve_order - s).to_be_bytes::<32>());
+        malleable.recovery_id ^= 1;
+        assert_eq!(
+            validate_transaction(&jzkt, &malleable),
+            Err(TransactionError::InvalidSignature)
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use rwasm::Value;
+
+/// Arguments of the typed entrypoint call, see [`crate::Runtime::call_typed`]
+pub trait CallArgs {
+    fn into_values(self) -> Vec<Value>;
+}
+
+/// Results of the typed entrypoint call, `None` if the values don't match the types
+pub trait CallResults: Sized {
+    fn from_values(values: &[Value]) -> Option<Self>;
+}
+
+/// Single value of the arguments or results, unsigned integers are passed as the signed
+/// values of the same width
+pub trait CallValue: Sized {
+    fn into_value(self) -> Value;
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+macro_rules! impl_call_value {
+    ($ty:ty, $variant:ident, $repr:ty) => {
+        impl CallValue for $ty {
+            fn into_value(self) -> Value {
+                Value::$variant(self as $repr)
+            }
+
+            fn from_value(value: &Value) -> Option<Self> {
+                match value {
+                    Value::$variant(value) => Some(*value as $ty),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_call_value!(i32, I32, i32);
+impl_call_value!(u32, I32, i32);
+impl_call_value!(i64, I64, i64);
+impl_call_value!(u64, I64, i64);
+
+impl<T: CallValue> CallArgs for T {
+    fn into_values(self) -> Vec<Value> {
+        vec![self.into_value()]
+    }
+}
+
+impl<T: CallValue> CallResults for T {
+    fn from_values(values: &[Value]) -> Option<Self> {
+        match values {
+            [value] => T::from_value(value),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_call_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: CallValue),*> CallArgs for ($($name,)*) {
+            #[allow(non_snake_case)]
+            fn into_values(self) -> Vec<Value> {
+                let ($($name,)*) = self;
+                vec![$($name.into_value()),*]
+            }
+        }
+
+        impl<$($name: CallValue),*> CallResults for ($($name,)*) {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn from_values(values: &[Value]) -> Option<Self> {
+                let mut values = values.iter();
+                let result = ($($name::from_value(values.next()?)?,)*);
+                values.next().is_none().then_some(result)
+            }
+        }
+    };
+}
+
+impl_call_tuple!();
+impl_call_tuple!(A, B);
+impl_call_tuple!(A, B, C);
+impl_call_tuple!(A, B, C, D);
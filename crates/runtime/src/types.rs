@@ -1,16 +1,28 @@
+use crate::{policy::PolicyViolation, quota::QuotaError};
 use eth_trie::DB;
-use fluentbase_types::{Bytes, F254};
+use fluentbase_codec::CodecError;
+use fluentbase_types::{Bytes, ExitCode, MetadataError, F254};
+use fluentbase_zktrie::Error as TrieError;
 use hashbrown::HashMap;
 use rwasm::{rwasm::BinaryFormatError, Error as RwasmError};
+use std::fmt::{Display, Formatter};
 
 pub trait TrieDb {
     fn get_node(&mut self, key: &[u8]) -> Option<Bytes>;
 
     fn update_node(&mut self, key: &[u8], value: Bytes);
 
+    fn remove_node(&mut self, _key: &[u8]) {}
+
     fn get_preimage(&mut self, key: &[u8]) -> Option<Bytes>;
 
     fn update_preimage(&mut self, key: &[u8], value: Bytes);
+
+    /// Makes all writes since the previous flush durable, root is the state root these writes
+    /// belong to
+    fn flush(&mut self, _root: &[u8; 32]) -> Result<(), ExitCode> {
+        Ok(())
+    }
 }
 
 #[derive(Default, Clone)]
@@ -28,6 +40,10 @@ impl TrieDb for InMemoryTrieDb {
         self.nodes.insert(Bytes::copy_from_slice(key), value);
     }
 
+    fn remove_node(&mut self, key: &[u8]) {
+        self.nodes.remove(&Bytes::copy_from_slice(key));
+    }
+
     fn get_preimage(&mut self, key: &[u8]) -> Option<Bytes> {
         self.preimages.get(&Bytes::copy_from_slice(key)).cloned()
     }
@@ -46,6 +62,10 @@ impl TrieDb for eth_trie::MemoryDB {
         self.insert(key, value.into()).unwrap()
     }
 
+    fn remove_node(&mut self, key: &[u8]) {
+        self.remove(key).unwrap()
+    }
+
     fn get_preimage(&mut self, key: &[u8]) -> Option<Bytes> {
         self.get(key).map_or(None, |v| v.map(|v| Bytes::from(v)))
     }
@@ -61,8 +81,111 @@ pub enum RuntimeError {
     BinaryFormatError(BinaryFormatError),
     Rwasm(RwasmError),
     StorageError(String),
-    MissingEntrypoint,
+    Trie(TrieError),
+    Codec(CodecError),
+    MissingEntrypoint(String),
     UnloadedModule(F254),
+    MissingEvmInterpreter,
+    PolicyViolation(Vec<PolicyViolation>),
+    NonDeterministicMemory,
+    UninitializedMemoryRead,
+    QuotaExceeded(QuotaError),
+    Metadata(MetadataError),
+    /// Results of the typed call don't match the signature of the entrypoint
+    SignatureMismatch,
+    /// Execution is resumed, but it isn't paused
+    NotPaused,
+    /// Execution is paused, but the call can't be paused
+    UnexpectedPause,
+}
+
+impl RuntimeError {
+    /// Exit code reported to the caller for this error, raw exit codes of failed executions are
+    /// kept as is
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            RuntimeError::ExecutionFailed(exit_code) => ExitCode::from(*exit_code),
+            RuntimeError::Rwasm(RwasmError::Trap(trap)) => {
+                if let Some(exit_status) = trap.i32_exit_status() {
+                    ExitCode::from(exit_status)
+                } else if let Some(trap_code) = trap.trap_code() {
+                    trap_code.into()
+                } else {
+                    ExitCode::UnknownError
+                }
+            }
+            RuntimeError::BinaryFormatError(_) | RuntimeError::PolicyViolation(_) => {
+                ExitCode::CompilationError
+            }
+            RuntimeError::StorageError(_) | RuntimeError::Trie(_) => {
+                ExitCode::PersistentStorageError
+            }
+            RuntimeError::Metadata(MetadataError::Malformed)
+            | RuntimeError::Metadata(MetadataError::UnsupportedVersion(_)) => {
+                ExitCode::CompilationError
+            }
+            RuntimeError::Metadata(_) => ExitCode::IncompatibleMetadata,
+            _ => ExitCode::UnknownError,
+        }
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::ExecutionFailed(exit_code) => write!(
+                f,
+                "execution failed with exit code {} ({})",
+                exit_code,
+                ExitCode::from(*exit_code)
+            ),
+            RuntimeError::BinaryFormatError(err) => write!(f, "malformed rwasm binary: {:?}", err),
+            RuntimeError::Rwasm(_) => write!(f, "rwasm error"),
+            RuntimeError::StorageError(message) => write!(f, "storage error: {}", message),
+            RuntimeError::Trie(_) => write!(f, "trie error"),
+            RuntimeError::Codec(_) => write!(f, "codec error"),
+            RuntimeError::MissingEntrypoint(name) => write!(f, "missing `{}` entrypoint", name),
+            RuntimeError::UnloadedModule(hash) => write!(f, "module {} is not loaded", hash),
+            RuntimeError::MissingEvmInterpreter => write!(f, "EVM interpreter is not set"),
+            RuntimeError::PolicyViolation(violations) => {
+                write!(f, "bytecode policy violation")?;
+                for violation in violations.iter() {
+                    write!(f, "; {}", violation)?;
+                }
+                Ok(())
+            }
+            RuntimeError::NonDeterministicMemory => write!(f, "non-deterministic memory"),
+            RuntimeError::UninitializedMemoryRead => write!(f, "uninitialized memory read"),
+            RuntimeError::QuotaExceeded(err) => write!(f, "quota exceeded: {}", err),
+            RuntimeError::Metadata(err) => write!(f, "{}", err),
+            RuntimeError::SignatureMismatch => write!(f, "entrypoint signature mismatch"),
+            RuntimeError::NotPaused => write!(f, "execution is not paused"),
+            RuntimeError::UnexpectedPause => write!(f, "execution can't be paused"),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RuntimeError::Rwasm(err) => Some(err),
+            RuntimeError::Trie(err) => Some(err),
+            RuntimeError::Codec(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<TrieError> for RuntimeError {
+    fn from(value: TrieError) -> Self {
+        Self::Trie(value)
+    }
+}
+
+impl From<CodecError> for RuntimeError {
+    fn from(value: CodecError) -> Self {
+        Self::Codec(value)
+    }
 }
 
 impl From<BinaryFormatError> for RuntimeError {
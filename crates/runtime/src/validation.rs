@@ -0,0 +1,267 @@
+use crate::{
+    access_list::{AccessList, AccessListRecorder},
+    trace::format::{TraceReader, TraceWriter, SYSCALL_OPCODE_FLAG},
+    types::RuntimeError,
+    ExecutionResult,
+    Runtime,
+    RuntimeContext,
+};
+use fluentbase_types::{Address, ExitCode, Gas, IJournaledTrie, SysFuncIdx, B256};
+use hashbrown::HashSet;
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Formatter},
+};
+
+/// Default gas limit of the account validation
+pub const DEFAULT_VALIDATION_GAS_LIMIT: u64 = 1_000_000;
+
+/// Syscalls banned during the validation by default, their results depend on the block or the
+/// transaction (like `GASPRICE`, `BASEFEE` or `GAS` opcodes banned by ERC-4337) or on the
+/// embedder, so the validation result could differ between simulation and inclusion
+pub const DEFAULT_BANNED_SYSCALLS: [SysFuncIdx; 10] = [
+    SysFuncIdx::GAS_PRICE,
+    SysFuncIdx::BASE_FEE,
+    SysFuncIdx::BLOB_HASH,
+    SysFuncIdx::BLOB_BASE_FEE,
+    SysFuncIdx::FUEL_REMAINING,
+    SysFuncIdx::FUEL_CONSUMED,
+    SysFuncIdx::CHARGE_FUEL,
+    SysFuncIdx::COMPUTE_ROOT,
+    SysFuncIdx::COMMIT,
+    SysFuncIdx::SUSPEND,
+];
+
+/// Rules of the ERC-4337-style account validation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationRules {
+    pub gas_limit: Gas,
+    pub banned_syscalls: HashSet<SysFuncIdx>,
+    /// Accounts whose storage can be accessed besides the storage of the validated account
+    /// (for example staked factories or paymasters)
+    pub allowed_storage: HashSet<Address>,
+}
+
+impl Default for ValidationRules {
+    fn default() -> Self {
+        Self {
+            gas_limit: Gas(DEFAULT_VALIDATION_GAS_LIMIT),
+            banned_syscalls: DEFAULT_BANNED_SYSCALLS.into_iter().collect(),
+            allowed_storage: Default::default(),
+        }
+    }
+}
+
+impl ValidationRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_gas_limit(mut self, gas_limit: impl Into<Gas>) -> Self {
+        self.gas_limit = gas_limit.into();
+        self
+    }
+
+    pub fn with_banned_syscalls<I: IntoIterator<Item = SysFuncIdx>>(
+        mut self,
+        banned_syscalls: I,
+    ) -> Self {
+        self.banned_syscalls = banned_syscalls.into_iter().collect();
+        self
+    }
+
+    pub fn with_allowed_storage(mut self, address: Address) -> Self {
+        self.allowed_storage.insert(address);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ValidationViolation {
+    BannedSyscall(SysFuncIdx),
+    StorageAccess {
+        address: Address,
+        slot: B256,
+    },
+    GasLimitExceeded {
+        gas_limit: Gas,
+    },
+    /// Validation entrypoint finished with non-zero exit code
+    ValidationFailed {
+        exit_code: i32,
+    },
+}
+
+impl Display for ValidationViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationViolation::BannedSyscall(sys_func_idx) => {
+                write!(f, "banned syscall {} is used", sys_func_idx)
+            }
+            ValidationViolation::StorageAccess { address, slot } => {
+                write!(f, "storage slot {} of {} is accessed", slot, address)
+            }
+            ValidationViolation::GasLimitExceeded { gas_limit } => {
+                write!(f, "validation gas limit {} is exceeded", gas_limit)
+            }
+            ValidationViolation::ValidationFailed { exit_code } => write!(
+                f,
+                "validation failed with exit code {} ({})",
+                exit_code,
+                ExitCode::from(*exit_code)
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ValidationReport {
+    pub execution_result: ExecutionResult,
+    pub gas_used: Gas,
+    /// Addresses and storage slots touched by the validation (nested calls included)
+    pub access_list: AccessList,
+    /// Violated rules sorted and without duplicates
+    pub violations: Vec<ValidationViolation>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl<DB: IJournaledTrie> Runtime<DB> {
+    /// Simulates validation of the account (the context must call its validation entrypoint)
+    /// and checks the result against the rules, all state changes are rolled back.
+    ///
+    /// Syscalls and storage accesses are checked for the whole call tree. Account fields of
+    /// other accounts can be read, because nested calls need code hashes of callees, but only
+    /// the storage of the account itself (and of the allowed accounts) can be touched.
+    pub fn simulate_validation(
+        runtime_context: RuntimeContext<DB>,
+        account: &Address,
+        rules: &ValidationRules,
+    ) -> Result<ValidationReport, RuntimeError> {
+        let fuel_schedule = runtime_context.fuel_schedule;
+        let recorder = AccessListRecorder::new();
+        let trace_writer = TraceWriter::new();
+        let checkpoint = runtime_context.jzkt.as_ref().map(|jzkt| jzkt.checkpoint());
+        let mut runtime = Self::new(
+            runtime_context
+                .with_fuel_limit(fuel_schedule.gas_to_fuel(rules.gas_limit))
+                .with_access_list_recorder(recorder.clone())
+                .with_trace_writer(trace_writer.clone()),
+        );
+        let execution_result = runtime.call();
+        if let (Some(jzkt), Some(checkpoint)) = (runtime.data().jzkt.as_ref(), checkpoint) {
+            jzkt.rollback(checkpoint);
+        }
+        let execution_result = execution_result?;
+
+        let mut violations = BTreeSet::new();
+        let trace = trace_writer.to_bytes();
+        let reader = TraceReader::new(&trace).expect("trace is written by the runtime");
+        for row in reader.rows() {
+            let row = row.expect("trace is written by the runtime");
+            if !row.is_syscall() {
+                continue;
+            }
+            let sys_func_idx =
+                SysFuncIdx::from_repr(row.opcode & !SYSCALL_OPCODE_FLAG).unwrap_or_default();
+            if rules.banned_syscalls.contains(&sys_func_idx) {
+                violations.insert(ValidationViolation::BannedSyscall(sys_func_idx));
+            }
+        }
+        let access_list = recorder.access_list();
+        for item in access_list.iter() {
+            if item.address == *account || rules.allowed_storage.contains(&item.address) {
+                continue;
+            }
+            for slot in item.storage_keys.iter() {
+                violations.insert(ValidationViolation::StorageAccess {
+                    address: item.address,
+                    slot: *slot,
+                });
+            }
+        }
+        if execution_result.exit_code == ExitCode::OutOfGas.into_i32() {
+            violations.insert(ValidationViolation::GasLimitExceeded {
+                gas_limit: rules.gas_limit,
+            });
+        } else if execution_result.exit_code != ExitCode::Ok.into_i32() {
+            violations.insert(ValidationViolation::ValidationFailed {
+                exit_code: execution_result.exit_code,
+            });
+        }
+        Ok(ValidationReport {
+            gas_used: fuel_schedule.fuel_to_gas(execution_result.fuel_consumed),
+            execution_result,
+            access_list,
+            violations: violations.into_iter().collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::wat2rwasm,
+        validation::{ValidationRules, ValidationViolation},
+        DefaultEmptyRuntimeDatabase,
+        Runtime,
+        RuntimeContext,
+    };
+    use fluentbase_types::{address, ExitCode, Gas, SysFuncIdx};
+
+    #[test]
+    fn test_simulate_validation() {
+        let rwasm_binary = wat2rwasm(
+            r#"
+(module
+  (type (;0;) (func (param i32)))
+  (type (;1;) (func (result i64)))
+  (type (;2;) (func))
+  (import "fluentbase_v1preview" "_gas_price" (func $_gas_price (type 0)))
+  (import "fluentbase_v1preview" "_fuel_remaining" (func $_fuel_remaining (type 1)))
+  (import "fluentbase_v1preview" "_exit" (func $_exit (type 0)))
+  (func $main (type 2)
+    i32.const 0
+    call $_gas_price
+    call $_fuel_remaining
+    drop
+    call $_fuel_remaining
+    drop
+    i32.const -72
+    call $_exit
+    )
+  (memory (;0;) 1)
+  (export "main" (func $main)))
+    "#,
+        );
+        let account = address!("1111111111111111111111111111111111111111");
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary.clone());
+        let report = Runtime::simulate_validation(ctx, &account, &ValidationRules::new()).unwrap();
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.violations,
+            vec![
+                ValidationViolation::BannedSyscall(SysFuncIdx::FUEL_REMAINING),
+                ValidationViolation::BannedSyscall(SysFuncIdx::GAS_PRICE),
+                ValidationViolation::ValidationFailed {
+                    exit_code: ExitCode::Revert.into_i32()
+                },
+            ]
+        );
+
+        // the same code is fine if the syscalls aren't banned, but the gas limit is too low
+        let ctx = RuntimeContext::<DefaultEmptyRuntimeDatabase>::new(rwasm_binary);
+        let rules = ValidationRules::new()
+            .with_banned_syscalls([])
+            .with_gas_limit(Gas(1));
+        let report = Runtime::simulate_validation(ctx, &account, &rules).unwrap();
+        assert_eq!(
+            report.violations,
+            vec![ValidationViolation::GasLimitExceeded { gas_limit: Gas(1) }]
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use crate::{types::RuntimeError, ExecutionResult, Runtime, RuntimeContext};
+use fluentbase_types::{Fuel, IJournaledTrie, F254};
+use hashbrown::HashMap;
+use keccak_hash::keccak;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, RwLock},
+};
+
+/// Default max number of the cached view-call results
+pub const DEFAULT_VIEW_CACHE_CAPACITY: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ViewCallKey {
+    code_hash: F254,
+    // keccak256 of the input and the context
+    input_hash: [u8; 32],
+    state_root: [u8; 32],
+    fuel_limit: Fuel,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ViewCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Calls executed over the uncommitted state, such calls can't be cached
+    pub bypassed: u64,
+}
+
+#[derive(Default)]
+struct ViewCallCacheInner {
+    results: HashMap<ViewCallKey, ExecutionResult>,
+    // insertion order of the keys, the oldest result is evicted first
+    order: VecDeque<ViewCallKey>,
+    stats: ViewCacheStats,
+}
+
+/// Cache of the read-only call results keyed by (code hash, input, state root), results stay
+/// valid until the state root changes, so there is no need in explicit invalidation.
+///
+/// The cache can be shared between threads (for example between RPC workers).
+#[derive(Clone)]
+pub struct ViewCallCache {
+    inner: Arc<RwLock<ViewCallCacheInner>>,
+    capacity: usize,
+}
+
+impl Default for ViewCallCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_VIEW_CACHE_CAPACITY)
+    }
+}
+
+impl ViewCallCache {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "view cache capacity must be positive");
+        Self {
+            inner: Default::default(),
+            capacity,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.read().unwrap().results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> ViewCacheStats {
+        self.inner.read().unwrap().stats
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.inner.write().unwrap();
+        inner.results.clear();
+        inner.order.clear();
+    }
+
+    fn get(&self, key: &ViewCallKey) -> Option<ExecutionResult> {
+        let mut inner = self.inner.write().unwrap();
+        let result = inner.results.get(key).cloned();
+        if result.is_some() {
+            inner.stats.hits += 1;
+        } else {
+            inner.stats.misses += 1;
+        }
+        result
+    }
+
+    fn insert(&self, key: ViewCallKey, execution_result: ExecutionResult) {
+        let mut inner = self.inner.write().unwrap();
+        if inner.results.insert(key, execution_result).is_some() {
+            return;
+        }
+        inner.order.push_back(key);
+        while inner.order.len() > self.capacity {
+            let key = inner.order.pop_front().unwrap();
+            inner.results.remove(&key);
+        }
+    }
+
+    fn record_bypass(&self) {
+        self.inner.write().unwrap().stats.bypassed += 1;
+    }
+}
+
+impl<DB: IJournaledTrie> Runtime<DB> {
+    /// Executes the context as a read-only (static) call and memoizes its result.
+    ///
+    /// The state root is taken from the committed trie, so calls over the uncommitted journal
+    /// (or without the journal at all) are executed without the cache.
+    pub fn call_view(
+        mut runtime_context: RuntimeContext<DB>,
+        cache: &ViewCallCache,
+    ) -> Result<ExecutionResult, RuntimeError> {
+        runtime_context.resolve_bytecode();
+        runtime_context.is_static = true;
+        let state_root = match runtime_context.jzkt.as_ref() {
+            Some(jzkt) if jzkt.checkpoint().state() == 0 => jzkt.compute_root(),
+            _ => {
+                cache.record_bypass();
+                return Self::new(runtime_context).call();
+            }
+        };
+        let mut input =
+            Vec::with_capacity(4 + runtime_context.input.len() + runtime_context.context.len());
+        input.extend_from_slice(&(runtime_context.input.len() as u32).to_le_bytes());
+        input.extend_from_slice(&runtime_context.input);
+        input.extend_from_slice(&runtime_context.context);
+        let key = ViewCallKey {
+            code_hash: runtime_context.bytecode.resolve_hash(),
+            input_hash: keccak(&input).0,
+            state_root,
+            fuel_limit: runtime_context.fuel_limit,
+        };
+        if let Some(execution_result) = cache.get(&key) {
+            return Ok(execution_result);
+        }
+        let execution_result = Self::new(runtime_context).call()?;
+        cache.insert(key, execution_result.clone());
+        Ok(execution_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::wat2rwasm,
+        view_cache::ViewCallCache,
+        DefaultEmptyRuntimeDatabase,
+        Runtime,
+        RuntimeContext,
+    };
+    use fluentbase_types::IJournaledTrie;
+
+    #[test]
+    fn test_view_call_cache() {
+        let rwasm_binary = wat2rwasm(
+            r#"
+(module
+  (type (;0;) (func (param i32 i32)))
+  (type (;1;) (func))
+  (import "fluentbase_v1preview" "_write" (func $_write (type 0)))
+  (func $main (type 1)
+    i32.const 0
+    i32.const 12
+    call $_write
+    )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 0) "Hello, World")
+  (export "main" (func $main)))
+    "#,
+        );
+        let jzkt = DefaultEmptyRuntimeDatabase::default();
+        let cache = ViewCallCache::new(16);
+        let call = |input: &[u8]| {
+            let ctx = RuntimeContext::new(rwasm_binary.clone())
+                .with_input(input.to_vec())
+                .with_fuel_limit(1_000_000)
+                .with_jzkt(jzkt.clone());
+            Runtime::call_view(ctx, &cache).unwrap()
+        };
+        assert_eq!(call(b"a").output, b"Hello, World".to_vec());
+        assert_eq!(call(b"a").output, b"Hello, World".to_vec());
+        call(b"b");
+        assert_eq!(cache.stats().hits, 1);
+        assert_eq!(cache.stats().misses, 2);
+        // uncommitted changes bypass the cache
+        jzkt.update(&[1u8; 32], &vec![[2u8; 32]], 0);
+        call(b"a");
+        assert_eq!(cache.stats().bypassed, 1);
+        // new state root invalidates cached results
+        jzkt.commit().unwrap();
+        call(b"a");
+        assert_eq!(cache.stats().misses, 3);
+        assert_eq!(cache.len(), 3);
+    }
+}
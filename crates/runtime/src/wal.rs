@@ -0,0 +1,240 @@
+use crate::types::TrieDb;
+use byteorder::{ByteOrder, LittleEndian};
+use fluentbase_types::{Bytes, ExitCode};
+use hashbrown::HashMap;
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+const ENTRY_UPDATE_NODE: u8 = 0x01;
+const ENTRY_REMOVE_NODE: u8 = 0x02;
+const ENTRY_UPDATE_PREIMAGE: u8 = 0x03;
+
+/// Write-ahead log on top of the node database, writes are buffered until the flush, then the
+/// whole batch with its state root is appended to the log file and synced before the database
+/// is touched. A crash can leave only a torn tail of the log, such batch is dropped on open, so
+/// the recovered root always matches stored nodes.
+///
+/// Batch format: `len(u32) || root(32) || entries || keccak256(root || entries)`, where every
+/// entry is `kind(u8) || key_len(u32) || key || value_len(u32) || value`.
+pub struct WalTrieDb<DB: TrieDb> {
+    inner: DB,
+    file: File,
+    nodes: HashMap<Vec<u8>, Option<Bytes>>,
+    preimages: HashMap<Vec<u8>, Bytes>,
+    entries: Vec<u8>,
+    root: Option<[u8; 32]>,
+}
+
+impl<DB: TrieDb> WalTrieDb<DB> {
+    /// Opens the log and replays all complete batches into the database
+    pub fn open<P: AsRef<Path>>(path: P, mut inner: DB) -> std::io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(path)?;
+        let mut log = Vec::new();
+        file.read_to_end(&mut log)?;
+        let (root, valid_len) = Self::recover(&log, &mut inner);
+        if valid_len < log.len() {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                dropped = log.len() - valid_len,
+                "dropping torn write-ahead log tail"
+            );
+            file.set_len(valid_len as u64)?;
+            file.sync_all()?;
+        }
+        file.seek(SeekFrom::End(0))?;
+        Ok(Self {
+            inner,
+            file,
+            nodes: HashMap::new(),
+            preimages: HashMap::new(),
+            entries: Vec::new(),
+            root,
+        })
+    }
+
+    /// State root of the last durable batch
+    pub fn root(&self) -> Option<[u8; 32]> {
+        self.root
+    }
+
+    /// Clears the log, it must be called only after the database persisted all batches
+    pub fn truncate(&mut self) -> std::io::Result<()> {
+        self.file.set_len(0)?;
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.sync_all()
+    }
+
+    pub fn into_inner(self) -> DB {
+        self.inner
+    }
+
+    fn recover(log: &[u8], inner: &mut DB) -> (Option<[u8; 32]>, usize) {
+        let mut root = None;
+        let mut offset = 0;
+        while let Some((batch_root, batch_len)) = Self::replay_batch(&log[offset..], inner) {
+            root = Some(batch_root);
+            offset += batch_len;
+        }
+        (root, offset)
+    }
+
+    fn replay_batch(log: &[u8], inner: &mut DB) -> Option<([u8; 32], usize)> {
+        if log.len() < 4 {
+            return None;
+        }
+        let len = LittleEndian::read_u32(log) as usize;
+        let body = log.get(4..4 + len)?;
+        let checksum = log.get(4 + len..4 + len + 32)?;
+        if body.len() < 32 || keccak_hash::keccak(body).as_bytes() != checksum {
+            return None;
+        }
+        let mut entries = Vec::new();
+        let mut cursor = &body[32..];
+        while !cursor.is_empty() {
+            let kind = cursor[0];
+            if !matches!(
+                kind,
+                ENTRY_UPDATE_NODE | ENTRY_REMOVE_NODE | ENTRY_UPDATE_PREIMAGE
+            ) {
+                return None;
+            }
+            let (key, rest) = Self::read_chunk(&cursor[1..])?;
+            let (value, rest) = Self::read_chunk(rest)?;
+            entries.push((kind, key, value));
+            cursor = rest;
+        }
+        // apply only fully parsed batches
+        for (kind, key, value) in entries.into_iter() {
+            match kind {
+                ENTRY_UPDATE_NODE => inner.update_node(key, Bytes::copy_from_slice(value)),
+                ENTRY_REMOVE_NODE => inner.remove_node(key),
+                _ => inner.update_preimage(key, Bytes::copy_from_slice(value)),
+            }
+        }
+        let mut root = [0u8; 32];
+        root.copy_from_slice(&body[..32]);
+        Some((root, 4 + len + 32))
+    }
+
+    fn read_chunk(cursor: &[u8]) -> Option<(&[u8], &[u8])> {
+        let len = LittleEndian::read_u32(cursor.get(..4)?) as usize;
+        let chunk = cursor.get(4..4 + len)?;
+        Some((chunk, &cursor[4 + len..]))
+    }
+
+    fn push_entry(&mut self, kind: u8, key: &[u8], value: &[u8]) {
+        self.entries.push(kind);
+        let mut len = [0u8; 4];
+        LittleEndian::write_u32(&mut len, key.len() as u32);
+        self.entries.extend_from_slice(&len);
+        self.entries.extend_from_slice(key);
+        LittleEndian::write_u32(&mut len, value.len() as u32);
+        self.entries.extend_from_slice(&len);
+        self.entries.extend_from_slice(value);
+    }
+
+    fn write_batch(&mut self, root: &[u8; 32]) -> std::io::Result<()> {
+        let mut body = Vec::with_capacity(32 + self.entries.len());
+        body.extend_from_slice(root);
+        body.extend_from_slice(&self.entries);
+        let mut batch = Vec::with_capacity(4 + body.len() + 32);
+        let mut len = [0u8; 4];
+        LittleEndian::write_u32(&mut len, body.len() as u32);
+        batch.extend_from_slice(&len);
+        batch.extend_from_slice(&body);
+        batch.extend_from_slice(keccak_hash::keccak(&body).as_bytes());
+        self.file.write_all(&batch)?;
+        self.file.sync_data()
+    }
+}
+
+impl<DB: TrieDb> TrieDb for WalTrieDb<DB> {
+    fn get_node(&mut self, key: &[u8]) -> Option<Bytes> {
+        match self.nodes.get(key) {
+            Some(value) => value.clone(),
+            None => self.inner.get_node(key),
+        }
+    }
+
+    fn update_node(&mut self, key: &[u8], value: Bytes) {
+        self.push_entry(ENTRY_UPDATE_NODE, key, &value);
+        self.nodes.insert(key.to_vec(), Some(value));
+    }
+
+    fn remove_node(&mut self, key: &[u8]) {
+        self.push_entry(ENTRY_REMOVE_NODE, key, &[]);
+        self.nodes.insert(key.to_vec(), None);
+    }
+
+    fn get_preimage(&mut self, key: &[u8]) -> Option<Bytes> {
+        match self.preimages.get(key) {
+            Some(value) => Some(value.clone()),
+            None => self.inner.get_preimage(key),
+        }
+    }
+
+    fn update_preimage(&mut self, key: &[u8], value: Bytes) {
+        self.push_entry(ENTRY_UPDATE_PREIMAGE, key, &value);
+        self.preimages.insert(key.to_vec(), value);
+    }
+
+    fn flush(&mut self, root: &[u8; 32]) -> Result<(), ExitCode> {
+        self.write_batch(root)
+            .map_err(|_| ExitCode::PersistentStorageError)?;
+        // the batch is durable, so now it's safe to apply it to the database
+        for (key, value) in self.nodes.drain() {
+            match value {
+                Some(value) => self.inner.update_node(&key, value),
+                None => self.inner.remove_node(&key),
+            }
+        }
+        for (key, value) in self.preimages.drain() {
+            self.inner.update_preimage(&key, value);
+        }
+        self.entries.clear();
+        self.root = Some(*root);
+        self.inner.flush(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{types::InMemoryTrieDb, wal::WalTrieDb, zktrie::ZkTrieStateDb, JournaledTrie};
+    use fluentbase_types::IJournaledTrie;
+    use std::{fs::OpenOptions, io::Write};
+
+    #[test]
+    fn test_wal_recovery() {
+        let path = std::env::temp_dir().join(format!("fluentbase-wal-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let root = {
+            let db = WalTrieDb::open(&path, InMemoryTrieDb::default()).unwrap();
+            let journal = JournaledTrie::new(ZkTrieStateDb::new_empty(db));
+            journal.update(&[1u8; 32], &vec![[2u8; 32]], 0);
+            let (root, _) = journal.commit().unwrap();
+            // changes that haven't been committed are lost
+            journal.update(&[3u8; 32], &vec![[4u8; 32]], 0);
+            root
+        };
+        // simulate the crash in the middle of the next batch
+        let log_len = std::fs::metadata(&path).unwrap().len();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[0xff, 0x00, 0x00, 0x00, 0x01, 0x02])
+            .unwrap();
+        drop(file);
+        let db = WalTrieDb::open(&path, InMemoryTrieDb::default()).unwrap();
+        assert_eq!(db.root(), Some(root));
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), log_len);
+        let journal = JournaledTrie::new(ZkTrieStateDb::new_opened(db, &root));
+        assert_eq!(journal.get(&[1u8; 32], true).unwrap().0, vec![[2u8; 32]]);
+        assert!(journal.get(&[3u8; 32], true).is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,10 +1,12 @@
 use crate::{storage::TrieStorage, types::TrieDb};
 use fluentbase_types::{Bytes, ExitCode, POSEIDON_EMPTY};
 use fluentbase_zktrie::{
+    to_secure_key,
     Byte32,
     Database,
     Error,
     Hash,
+    HashScheme,
     Node,
     PoseidonHash,
     PreimageDatabase,
@@ -12,10 +14,21 @@ use fluentbase_zktrie::{
     ZkTrie,
 };
 use halo2curves::bn256::Fr;
-use std::{cell::RefCell, rc::Rc, sync::Arc};
+use std::{
+    collections::BTreeSet,
+    marker::PhantomData,
+    mem::take,
+    sync::{Arc, Mutex},
+};
 
-#[derive(Clone)]
-struct NodeDb<DB>(Rc<RefCell<DB>>);
+// nodes are shared between the trie and concurrent readers of the pinned roots
+struct NodeDb<DB, H>(Arc<Mutex<DB>>, PhantomData<H>);
+
+impl<DB, H> Clone for NodeDb<DB, H> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
 
 const STORAGE_PREFIX_NODE: u8 = 0x01;
 const STORAGE_PREFIX_PREIMAGE: u8 = 0x02;
@@ -31,18 +44,18 @@ macro_rules! storage_key {
     }};
 }
 
-impl<DB: TrieDb> Database for NodeDb<DB> {
-    type Node = Node<PoseidonHash>;
+impl<DB: TrieDb, H: HashScheme> Database for NodeDb<DB, H> {
+    type Node = Node<H>;
 
     fn get_node(&self, key: &Hash) -> Result<Option<Arc<Self::Node>>, Error> {
-        match self.0.borrow_mut().get_node(key.raw_bytes()) {
+        match self.0.lock().unwrap().get_node(key.raw_bytes()) {
             Some(value) => Ok(Some(Arc::new(Node::from_bytes(&value)?))),
             None => Ok(None),
         }
     }
 
     fn update_node(&mut self, node: Self::Node) -> Result<Arc<Self::Node>, Error> {
-        self.0.borrow_mut().update_node(
+        self.0.lock().unwrap().update_node(
             node.hash().raw_bytes(),
             Bytes::copy_from_slice(&node.canonical_value()),
         );
@@ -50,68 +63,83 @@ impl<DB: TrieDb> Database for NodeDb<DB> {
     }
 }
 
-impl<'a, DB: TrieDb> PreimageDatabase for NodeDb<DB> {
+impl<'a, DB: TrieDb, H: HashScheme> PreimageDatabase for NodeDb<DB, H> {
     fn update_preimage(&mut self, preimage: &[u8], hash_field: &Fr) {
         self.0
-            .borrow_mut()
+            .lock()
+            .unwrap()
             .update_preimage(&hash_field.to_bytes(), Bytes::copy_from_slice(preimage));
     }
 
     fn preimage(&self, key: &Fr) -> Vec<u8> {
         self.0
-            .borrow_mut()
+            .lock()
+            .unwrap()
             .get_preimage(&key.to_bytes())
             .unwrap_or_default()
             .to_vec()
     }
 }
 
+/// Binary sparse Merkle trie, node hash is defined by the hash scheme (Poseidon by default,
+/// Keccak and Blake3 schemes are available in `fluentbase_zktrie`). The branching factor isn't
+/// configurable, nodes are always binary.
 #[derive(Clone)]
-pub struct ZkTrieStateDb<DB> {
-    storage: NodeDb<DB>,
-    trie: Option<ZkTrie<PoseidonHash>>,
+pub struct ZkTrieStateDb<DB, H: HashScheme = PoseidonHash> {
+    storage: NodeDb<DB, H>,
+    trie: Option<ZkTrie<H>>,
+    // removals delete the leaves instead of writing `POSEIDON_EMPTY`
+    hard_delete: bool,
+    // hashes of the nodes that were on the paths of the deleted keys
+    orphans: Vec<Hash>,
 }
 
 const MAX_LEVEL: usize = 31 * 8;
 
 impl<DB: TrieDb> ZkTrieStateDb<DB> {
     pub fn new(storage: DB) -> Self {
-        Self {
-            storage: NodeDb(Rc::new(RefCell::new(storage))),
-            trie: None,
-        }
+        Self::new_with_hash(storage)
     }
 
     pub fn new_empty(storage: DB) -> Self {
-        Self::new_opened(storage, &[0u8; 32])
+        Self::new_empty_with_hash(storage)
     }
 
     pub fn new_opened(storage: DB, root32: &[u8]) -> Self {
-        let mut storage = Self::new(storage);
-        storage.open(root32);
-        storage
+        Self::new_opened_with_hash(storage, root32)
     }
 }
 
-impl<DB: TrieDb> TrieStorage for ZkTrieStateDb<DB> {
-    fn open(&mut self, root32: &[u8]) -> bool {
-        if self.trie.is_some() {
-            return false;
+impl<DB: TrieDb, H: HashScheme> ZkTrieStateDb<DB, H> {
+    pub fn new_with_hash(storage: DB) -> Self {
+        Self {
+            storage: NodeDb(Arc::new(Mutex::new(storage)), PhantomData),
+            trie: None,
+            hard_delete: false,
+            orphans: Vec::new(),
         }
-        self.trie = Some(ZkTrie::new(MAX_LEVEL, Hash::from_bytes(&root32)));
-        true
     }
 
-    fn compute_root(&self) -> [u8; 32] {
-        self.trie
-            .as_ref()
-            .map(|trie| trie.hash().clone())
-            .unwrap_or(Hash::default())
-            .bytes()
+    /// Removed keys are deleted from the trie instead of being overwritten with
+    /// `POSEIDON_EMPTY`, it changes state roots of the removals, so it must be enabled only by
+    /// the hardfork that introduces it
+    pub fn with_hard_delete(mut self) -> Self {
+        self.hard_delete = true;
+        self
     }
 
-    fn get(&self, key: &[u8]) -> Option<(Vec<[u8; 32]>, u32)> {
-        if let Ok(val) = self.trie.as_ref()?.get_data(&self.storage, key) {
+    pub fn new_empty_with_hash(storage: DB) -> Self {
+        Self::new_opened_with_hash(storage, &[0u8; 32])
+    }
+
+    pub fn new_opened_with_hash(storage: DB, root32: &[u8]) -> Self {
+        let mut storage = Self::new_with_hash(storage);
+        storage.open(root32);
+        storage
+    }
+
+    fn get_from(&self, trie: &ZkTrie<H>, key: &[u8]) -> Option<(Vec<[u8; 32]>, u32)> {
+        if let Ok(val) = trie.get_data(&self.storage, key) {
             match val {
                 TrieData::Node(node) => {
                     let (data, flags) = node.data_with_flags();
@@ -132,6 +160,32 @@ impl<DB: TrieDb> TrieStorage for ZkTrieStateDb<DB> {
             None
         }
     }
+}
+
+impl<DB: TrieDb, H: HashScheme> TrieStorage for ZkTrieStateDb<DB, H> {
+    fn open(&mut self, root32: &[u8]) -> bool {
+        if self.trie.is_some() {
+            return false;
+        }
+        self.trie = Some(ZkTrie::new(MAX_LEVEL, Hash::from_bytes(&root32)));
+        true
+    }
+
+    fn compute_root(&self) -> [u8; 32] {
+        self.trie
+            .as_ref()
+            .map(|trie| trie.hash().clone())
+            .unwrap_or(Hash::default())
+            .bytes()
+    }
+
+    fn get(&self, key: &[u8]) -> Option<(Vec<[u8; 32]>, u32)> {
+        self.get_from(self.trie.as_ref()?, key)
+    }
+
+    fn get_at(&self, root32: &[u8; 32], key: &[u8]) -> Option<(Vec<[u8; 32]>, u32)> {
+        self.get_from(&ZkTrie::new(MAX_LEVEL, Hash::from_bytes(root32)), key)
+    }
 
     fn update(
         &mut self,
@@ -150,7 +204,63 @@ impl<DB: TrieDb> TrieStorage for ZkTrieStateDb<DB> {
     }
 
     fn remove(&mut self, key: &[u8]) -> Result<(), ExitCode> {
-        self.update(key, 0, &vec![POSEIDON_EMPTY.0])
+        if self.hard_delete {
+            self.delete(key)
+        } else {
+            self.update(key, 0, &vec![POSEIDON_EMPTY.0])
+        }
+    }
+
+    fn delete(&mut self, key: &[u8]) -> Result<(), ExitCode> {
+        let trie = self.trie.as_mut().unwrap();
+        let node_key: Hash = to_secure_key::<H>(key)
+            .map_err(|_| ExitCode::PersistentStorageError)?
+            .into();
+        let mut path = Vec::new();
+        trie.walk(&self.storage, &node_key, 0, |_, node| {
+            path.push(*node.hash());
+            Ok(())
+        })
+        .map_err(|_| ExitCode::PersistentStorageError)?;
+        trie.delete(&mut self.storage, key)
+            .map_err(|_| ExitCode::PersistentStorageError)?;
+        // nodes are orphaned only if the key existed and the root has been changed
+        if path.first() != Some(trie.hash()) {
+            self.orphans
+                .extend(path.into_iter().filter(|hash| !hash.is_zero()));
+        }
+        Ok(())
+    }
+
+    fn compact(&mut self) -> usize {
+        let Some(trie) = self.trie.as_ref() else {
+            return 0;
+        };
+        if self.orphans.is_empty() {
+            return 0;
+        }
+        // removed nodes can be re-created by the next updates, so we keep everything that is
+        // reachable from the current root
+        let mut alive = BTreeSet::new();
+        let mut stack = vec![*trie.hash()];
+        while let Some(hash) = stack.pop() {
+            if hash.is_zero() || !alive.insert(hash) {
+                continue;
+            }
+            if let Ok(Some(node)) = self.storage.get_node(&hash) {
+                if let Some(branch) = node.branch() {
+                    stack.push(*branch.left.hash());
+                    stack.push(*branch.right.hash());
+                }
+            }
+        }
+        let mut removed = BTreeSet::new();
+        for hash in take(&mut self.orphans).into_iter() {
+            if !alive.contains(&hash) && removed.insert(hash) {
+                self.storage.0.lock().unwrap().remove_node(hash.raw_bytes());
+            }
+        }
+        removed.len()
     }
 
     fn proof(&self, key: &[u8; 32]) -> Option<Vec<Vec<u8>>> {
@@ -162,17 +272,24 @@ impl<DB: TrieDb> TrieStorage for ZkTrieStateDb<DB> {
     }
 
     fn get_preimage(&mut self, key: &[u8]) -> Option<Bytes> {
-        self.storage.0.borrow_mut().get_preimage(key)
+        self.storage.0.lock().unwrap().get_preimage(key)
     }
 
     fn update_preimage(&mut self, key: &[u8], value: Bytes) {
-        self.storage.0.borrow_mut().update_preimage(key, value);
+        self.storage.0.lock().unwrap().update_preimage(key, value);
+    }
+
+    fn flush(&mut self) -> Result<(), ExitCode> {
+        let root = self.compute_root();
+        self.storage.0.lock().unwrap().flush(&root)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{storage::TrieStorage, types::InMemoryTrieDb, zktrie::ZkTrieStateDb};
+    use fluentbase_types::POSEIDON_EMPTY;
+    use fluentbase_zktrie::{verify_absence_proof, Blake3Hash, Hash, KeccakHash, PoseidonHash};
 
     macro_rules! bytes32 {
         ($val:expr) => {{
@@ -200,9 +317,100 @@ mod tests {
         let root = zkt.compute_root();
         println!("root: {:?}", hex::encode(root));
         // open and read value
-        let zkt2 = ZkTrieStateDb::new_opened(zkt.storage.0.borrow_mut().clone(), &root);
+        let zkt2 = ZkTrieStateDb::new_opened(zkt.storage.0.lock().unwrap().clone(), &root);
         let (data, _flags) = zkt2.get(bytes32!("key1")).unwrap();
         assert_eq!(data[0], *bytes32!("value1"));
         assert_eq!(data[1], *bytes32!("value2"));
     }
+
+    #[test]
+    fn test_keccak_hash_scheme() {
+        let mut poseidon_zkt = ZkTrieStateDb::new_empty(InMemoryTrieDb::default());
+        let mut keccak_zkt =
+            ZkTrieStateDb::<_, KeccakHash>::new_empty_with_hash(InMemoryTrieDb::default());
+        let value = vec![*bytes32!("value1")];
+        poseidon_zkt.update(bytes32!("key1"), 0, &value).unwrap();
+        keccak_zkt.update(bytes32!("key1"), 0, &value).unwrap();
+        assert_ne!(poseidon_zkt.compute_root(), keccak_zkt.compute_root());
+        let root = keccak_zkt.compute_root();
+        let keccak_zkt2 = ZkTrieStateDb::<_, KeccakHash>::new_opened_with_hash(
+            keccak_zkt.storage.0.lock().unwrap().clone(),
+            &root,
+        );
+        assert_eq!(keccak_zkt2.get(bytes32!("key1")).unwrap().0, value);
+    }
+
+    #[test]
+    fn test_blake3_hash_scheme() {
+        let mut keccak_zkt =
+            ZkTrieStateDb::<_, KeccakHash>::new_empty_with_hash(InMemoryTrieDb::default());
+        let mut blake3_zkt =
+            ZkTrieStateDb::<_, Blake3Hash>::new_empty_with_hash(InMemoryTrieDb::default());
+        let value = vec![*bytes32!("value1")];
+        keccak_zkt.update(bytes32!("key1"), 0, &value).unwrap();
+        blake3_zkt.update(bytes32!("key1"), 0, &value).unwrap();
+        assert_ne!(keccak_zkt.compute_root(), blake3_zkt.compute_root());
+        let root = blake3_zkt.compute_root();
+        let blake3_zkt2 = ZkTrieStateDb::<_, Blake3Hash>::new_opened_with_hash(
+            blake3_zkt.storage.0.lock().unwrap().clone(),
+            &root,
+        );
+        assert_eq!(blake3_zkt2.get(bytes32!("key1")).unwrap().0, value);
+    }
+
+    #[test]
+    fn test_remove_writes_empty_leaf() {
+        let mut zkt = ZkTrieStateDb::new_empty(InMemoryTrieDb::default());
+        let value = vec![*bytes32!("value1")];
+        zkt.update(bytes32!("key1"), 0, &value).unwrap();
+        let root = zkt.compute_root();
+        zkt.update(bytes32!("key2"), 0, &value).unwrap();
+        // removal without the hardfork keeps the leaf with the empty value
+        zkt.remove(bytes32!("key2")).unwrap();
+        assert_ne!(zkt.compute_root(), root);
+        assert_eq!(zkt.get(bytes32!("key2")).unwrap().0, vec![POSEIDON_EMPTY.0]);
+        let mut zkt2 = ZkTrieStateDb::new_empty(InMemoryTrieDb::default());
+        zkt2.update(bytes32!("key1"), 0, &value).unwrap();
+        zkt2.update(bytes32!("key2"), 0, &vec![POSEIDON_EMPTY.0])
+            .unwrap();
+        assert_eq!(zkt.compute_root(), zkt2.compute_root());
+        assert_eq!(zkt.compact(), 0);
+    }
+
+    #[test]
+    fn test_remove_and_compact() {
+        let mut zkt = ZkTrieStateDb::new_empty(InMemoryTrieDb::default()).with_hard_delete();
+        let value = vec![*bytes32!("value1")];
+        zkt.update(bytes32!("key1"), 0, &value).unwrap();
+        zkt.update(bytes32!("key2"), 0, &value).unwrap();
+        let root = zkt.compute_root();
+        zkt.update(bytes32!("key3"), 0, &value).unwrap();
+        // writing zero doesn't delete the key
+        zkt.update(bytes32!("key3"), 0, &vec![[0u8; 32]]).unwrap();
+        assert_ne!(zkt.compute_root(), root);
+        assert!(zkt.get(bytes32!("key3")).is_some());
+        // removal restores the root of the trie without the key
+        zkt.remove(bytes32!("key3")).unwrap();
+        assert_eq!(zkt.compute_root(), root);
+        assert!(zkt.get(bytes32!("key3")).is_none());
+        // removal of the absent key changes nothing
+        zkt.remove(bytes32!("key4")).unwrap();
+        assert_eq!(zkt.compute_root(), root);
+        // absence can be proven, but presence can't
+        let root_hash = Hash::from_bytes(&root);
+        let proof = zkt.proof(bytes32!("key3")).unwrap();
+        assert!(
+            verify_absence_proof::<PoseidonHash>(&root_hash, bytes32!("key3"), &proof).unwrap()
+        );
+        let proof = zkt.proof(bytes32!("key1")).unwrap();
+        assert!(
+            !verify_absence_proof::<PoseidonHash>(&root_hash, bytes32!("key1"), &proof).unwrap()
+        );
+        // compaction drops orphaned nodes, but keeps the current state readable
+        assert!(zkt.compact() > 0);
+        assert_eq!(zkt.compact(), 0);
+        let zkt2 = ZkTrieStateDb::new_opened(zkt.storage.0.lock().unwrap().clone(), &root);
+        assert_eq!(zkt2.get(bytes32!("key1")).unwrap().0, value);
+        assert_eq!(zkt2.get(bytes32!("key2")).unwrap().0, value);
+    }
 }
@@ -181,14 +181,11 @@ pub fn derive_codec_client(_attr: TokenStream, ast: ItemTrait) -> TokenStream {
                     method_id: #sol_sig,
                     method_data: input,
                 }.encode_to_vec(0);
-                let (output, exit_code) =
-                    #sdk_crate_name::contracts::call_system_contract(&self.address, &core_input, self.fuel);
+                let (result, exit_code) =
+                    #sdk_crate_name::contracts::call_system_contract_decoded::<#output_type>(&self.address, &core_input, self.fuel);
                 if exit_code != 0 {
                     panic!("system contract call failed with exit code: {}", exit_code);
                 }
-                let mut decoder = #codec_crate_name::BufferDecoder::new(&output);
-                let mut result = #output_type::default();
-                #output_type::decode_body(&mut decoder, 0, &mut result);
                 result
             }
         };
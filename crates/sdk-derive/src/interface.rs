@@ -0,0 +1,229 @@
+use crate::utils::{get_signatures, sol_call_fn_name};
+use convert_case::{Case, Casing};
+use proc_macro::TokenStream;
+use proc_macro2::Ident;
+use quote::quote;
+use syn::{self, parse_macro_input, FnArg, ItemTrait, ReturnType, TraitItem, TraitItemFn};
+
+/// Name of the trait without the `API` suffix (`GreetingAPI` -> `Greeting`)
+fn interface_name(ast: &ItemTrait) -> String {
+    ast.ident.to_string().trim_end_matches("API").to_string()
+}
+
+pub(crate) fn interface_module_name(ast: &ItemTrait) -> Ident {
+    let name = interface_name(ast).to_case(Case::Snake) + "_interface";
+    Ident::new(&name, ast.ident.span())
+}
+
+fn interface_methods(ast: &ItemTrait) -> Vec<&TraitItemFn> {
+    ast.items
+        .iter()
+        .filter_map(|item| {
+            if let TraitItem::Fn(func) = item {
+                Some(func)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn method_args(func: &TraitItemFn) -> syn::Result<Vec<&Ident>> {
+    func.sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Receiver(_) => None,
+            FnArg::Typed(pat_type) => Some(match &*pat_type.pat {
+                syn::Pat::Ident(pat_ident) => Ok(&pat_ident.ident),
+                pat => Err(syn::Error::new_spanned(
+                    pat,
+                    "interface method arguments must be identifiers",
+                )),
+            }),
+        })
+        .collect()
+}
+
+/// Turbofish of the method generics, interface methods can only be generic over the SDK
+fn method_generics(func: &TraitItemFn) -> proc_macro2::TokenStream {
+    if func.sig.generics.params.is_empty() {
+        quote!()
+    } else {
+        quote!(::<SDK>)
+    }
+}
+
+pub fn derive_interface(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(item as ItemTrait);
+    TokenStream::from(expand_interface(&ast).unwrap_or_else(|err| err.to_compile_error()))
+}
+
+/// Expands the trait into the ABI of its methods, the router of the guest and the client of the
+/// host, all of them are derived from the same `sol!` definitions, so selectors and the layout of
+/// inputs and outputs can't diverge between the contract and its callers
+pub(crate) fn expand_interface(ast: &ItemTrait) -> syn::Result<proc_macro2::TokenStream> {
+    let methods = interface_methods(ast);
+    for func in methods.iter() {
+        if func.sig.generics.params.len() > 1 {
+            return Err(syn::Error::new_spanned(
+                &func.sig.generics,
+                "interface methods can only be generic over the SDK",
+            ));
+        }
+    }
+    let sdk_crate_name = if std::env::var("CARGO_PKG_NAME").unwrap_or_default() == "fluentbase-sdk"
+    {
+        quote! { crate }
+    } else {
+        quote! { fluentbase_sdk }
+    };
+    let trait_name = &ast.ident;
+    let module_name = interface_module_name(ast);
+    let client_name = Ident::new(&(interface_name(ast) + "Client"), ast.ident.span());
+    let signatures = get_signatures(&methods);
+    let route_arms = methods
+        .iter()
+        .map(|func| derive_route_arm(func))
+        .collect::<syn::Result<Vec<_>>>()?;
+    let client_methods = methods
+        .iter()
+        .map(|func| derive_client_method(func, &module_name, &sdk_crate_name))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        #ast
+
+        pub mod #module_name {
+            use alloy_sol_types::{sol, SolCall};
+            use super::*;
+
+            #signatures
+
+            /// Decodes the input of the contract with the selectors of the interface, calls the
+            /// method of the contract and writes the encoded result as the output
+            pub fn route<SDK: #sdk_crate_name::SharedAPI, C: #trait_name>(contract: &C) {
+                let input_size = SDK::input_size();
+                if input_size < 4 {
+                    panic!("input too short, cannot extract selector");
+                }
+                let mut selector: [u8; 4] = [0; 4];
+                SDK::read(selector.as_mut_ptr(), selector.len() as u32, 0);
+                let input = #sdk_crate_name::alloc_slice(input_size as usize);
+                SDK::read(input.as_mut_ptr(), input_size, 0);
+                match selector {
+                    #(#route_arms,)*
+                    _ => panic!("unknown method"),
+                }
+            }
+        }
+
+        pub struct #client_name {
+            pub address: #sdk_crate_name::Address,
+            pub fuel: u32,
+        }
+
+        impl #client_name {
+            pub fn new(address: #sdk_crate_name::Address) -> impl #trait_name {
+                Self { address, fuel: u32::MAX }
+            }
+        }
+
+        impl #trait_name for #client_name {
+            #(#client_methods)*
+        }
+    })
+}
+
+fn derive_route_arm(func: &TraitItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let method_name = &func.sig.ident;
+    let method_call = sol_call_fn_name(method_name);
+    let generics = method_generics(func);
+    let args = method_args(func)?;
+    let output = match &func.sig.output {
+        ReturnType::Default => quote! { #method_call::abi_encode_returns(&()) },
+        ReturnType::Type(_, _) => quote! { #method_call::abi_encode_returns(&(output,)) },
+    };
+    Ok(quote! {
+        #method_call::SELECTOR => {
+            let decoded = match #method_call::abi_decode(&input, true) {
+                Ok(decoded) => decoded,
+                Err(_) => panic!("failed to decode input"),
+            };
+            let output = contract.#method_name #generics(#(decoded.#args),*);
+            let output = #output;
+            SDK::write(output.as_ptr(), output.len() as u32);
+        }
+    })
+}
+
+fn derive_client_method(
+    func: &TraitItemFn,
+    module_name: &Ident,
+    sdk_crate_name: &proc_macro2::TokenStream,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let sig = &func.sig;
+    let method_call = sol_call_fn_name(&sig.ident);
+    let args = method_args(func)?;
+    let outputs = match &sig.output {
+        ReturnType::Default => quote! {},
+        ReturnType::Type(_, _) => quote! {
+            #module_name::#method_call::abi_decode_returns(&result, false)
+                .expect("failed to decode result")
+                ._0
+        },
+    };
+    Ok(quote! {
+        #sig {
+            use alloy_sol_types::SolCall;
+            let input = #module_name::#method_call { #(#args),* }.abi_encode();
+            let (result, exit_code) = #sdk_crate_name::contracts::call_system_contract(&self.address, &input, self.fuel);
+            if exit_code != 0 {
+                panic!("call failed with exit code: {}", exit_code)
+            }
+            #outputs
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_interface_route_arm() {
+        let ast: ItemTrait = parse_quote! {
+            pub trait GreetingAPI {
+                fn greeting<SDK: SharedAPI>(&self, message: String) -> String;
+            }
+        };
+        assert_eq!(
+            interface_module_name(&ast).to_string(),
+            "greeting_interface"
+        );
+        let expected = quote! {
+            greetingCall::SELECTOR => {
+                let decoded = match greetingCall::abi_decode(&input, true) {
+                    Ok(decoded) => decoded,
+                    Err(_) => panic!("failed to decode input"),
+                };
+                let output = contract.greeting::<SDK>(decoded.message);
+                let output = greetingCall::abi_encode_returns(&(output,));
+                SDK::write(output.as_ptr(), output.len() as u32);
+            }
+        };
+        let actual = derive_route_arm(interface_methods(&ast)[0]).unwrap();
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_interface_rejects_non_sdk_generics() {
+        let ast: ItemTrait = parse_quote! {
+            pub trait GreetingAPI {
+                fn greeting<SDK: SharedAPI, T>(&self, message: T) -> String;
+            }
+        };
+        assert!(expand_interface(&ast).is_err());
+    }
+}
@@ -18,6 +18,8 @@ use syn::{
 
 mod codec_router;
 mod contract;
+mod interface;
+mod pausable;
 mod solidity_router;
 mod solidity_storage;
 
@@ -119,6 +121,28 @@ pub fn client(attr: TokenStream, item: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+/// Generates the Solidity ABI of the trait methods (`<name>_interface` module), the guest router
+/// `<name>_interface::route::<SDK, _>(&contract)` dispatching to any implementation of the trait
+/// and `<Name>Client` implementing the trait with calls to the contract. Both sides use the same
+/// selectors and codec layout, so they can't drift apart like hand-written routers and clients.
+///
+/// Selectors are derived from the method names and argument types unless overridden with
+/// `#[signature]`, methods can only be generic over the SDK.
+#[proc_macro_attribute]
+pub fn interface(attr: TokenStream, item: TokenStream) -> TokenStream {
+    interface::derive_interface(attr, item)
+}
+
+/// Generates a paused flag in the namespaced storage slot, `pause()`/`unpause()` functions that
+/// only the `admin` address can call and guards of the public state-mutating functions (view and
+/// pure functions, `deploy` and functions marked with `#[pausable(skip)]` aren't guarded).
+///
+/// The attribute must be placed above `#[router]`, so the generated functions are routed.
+#[proc_macro_attribute]
+pub fn pausable(attr: TokenStream, item: TokenStream) -> TokenStream {
+    pausable::derive_pausable(attr, item)
+}
+
 // Fake implementation of the attribute to avoid compiler and linter complaints
 #[proc_macro_attribute]
 pub fn signature(_attr: TokenStream, item: TokenStream) -> TokenStream {
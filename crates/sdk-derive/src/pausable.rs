@@ -0,0 +1,246 @@
+use crate::utils::{calculate_keccak256, get_public_methods};
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    self,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Expr,
+    ImplItem,
+    ImplItemFn,
+    ItemImpl,
+    LitStr,
+    Meta,
+    Token,
+};
+
+/// Namespace of the paused flag, the slot is `keccak256(namespace)`, so it can't collide with
+/// sequential slots of `solidity_storage!`
+pub(crate) const PAUSED_SLOT_NAMESPACE: &str = "fluentbase.pausable.paused";
+
+#[derive(Debug)]
+pub(crate) struct PausableArgs {
+    admin: Expr,
+}
+
+impl Parse for PausableArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut admin = None;
+        let metas = Punctuated::<Meta, Token![,]>::parse_terminated(input)?;
+        for meta in metas {
+            match meta {
+                Meta::NameValue(m) if m.path.is_ident("admin") => admin = Some(m.value),
+                meta => return Err(syn::Error::new_spanned(meta, "unknown pausable argument")),
+            }
+        }
+        let admin =
+            admin.ok_or_else(|| syn::Error::new(input.span(), "admin address is required"))?;
+        Ok(Self { admin })
+    }
+}
+
+pub fn derive_pausable(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as PausableArgs);
+    let ast = parse_macro_input!(item as ItemImpl);
+    TokenStream::from(expand_pausable(&args, ast).unwrap_or_else(|err| err.to_compile_error()))
+}
+
+pub(crate) fn expand_pausable(
+    args: &PausableArgs,
+    mut ast: ItemImpl,
+) -> syn::Result<proc_macro2::TokenStream> {
+    if let Some((_, trait_path, _)) = &ast.trait_ {
+        return Err(syn::Error::new_spanned(
+            trait_path,
+            "#[pausable] must be applied to an inherent impl, so pause() and unpause() are routed",
+        ));
+    }
+    let guarded = get_public_methods(&ast)
+        .into_iter()
+        .filter(|func| is_guarded(func))
+        .map(|func| func.sig.ident.clone())
+        .collect::<Vec<_>>();
+    for item in ast.items.iter_mut() {
+        let ImplItem::Fn(func) = item else {
+            continue;
+        };
+        func.attrs.retain(|attr| !is_skip_attr(attr));
+        if guarded.contains(&func.sig.ident) {
+            func.block.stmts.insert(
+                0,
+                syn::parse_quote! {
+                    if self.paused() {
+                        panic!("contract is paused");
+                    }
+                },
+            );
+        }
+    }
+    let paused_slot = calculate_keccak256(PAUSED_SLOT_NAMESPACE);
+    let admin = &args.admin;
+    let pausable_methods: Vec<ImplItem> = vec![
+        syn::parse_quote! {
+            pub fn paused(&self) -> bool {
+                !Self::pausable_sload().is_zero()
+            }
+        },
+        syn::parse_quote! {
+            pub fn pause(&self) {
+                Self::pausable_only_admin();
+                Self::pausable_sstore(fluentbase_sdk::U256::from(1));
+            }
+        },
+        syn::parse_quote! {
+            pub fn unpause(&self) {
+                Self::pausable_only_admin();
+                Self::pausable_sstore(fluentbase_sdk::U256::ZERO);
+            }
+        },
+    ];
+    ast.items.extend(pausable_methods);
+    let self_ty = &ast.self_ty;
+    let (impl_generics, _, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        #ast
+
+        impl #impl_generics #self_ty #where_clause {
+            const PAUSED_SLOT: fluentbase_sdk::U256 =
+                fluentbase_sdk::U256::from_be_bytes([#( #paused_slot, )*]);
+
+            fn pausable_only_admin() {
+                let admin: fluentbase_sdk::Address = #admin;
+                let caller = <fluentbase_sdk::GuestContextReader as fluentbase_sdk::ContextReader>::contract_caller(
+                    &fluentbase_sdk::GuestContextReader::DEFAULT,
+                );
+                if caller != admin {
+                    panic!("caller is not the pause admin");
+                }
+            }
+
+            fn pausable_sload() -> fluentbase_sdk::U256 {
+                use fluentbase_sdk::contracts::EvmAPI;
+                let input = fluentbase_sdk::contracts::EvmSloadInput {
+                    index: Self::PAUSED_SLOT,
+                };
+                fluentbase_sdk::contracts::EVM_STORAGE_CLIENT.sload(input).value
+            }
+
+            fn pausable_sstore(value: fluentbase_sdk::U256) {
+                use fluentbase_sdk::contracts::EvmAPI;
+                let input = fluentbase_sdk::contracts::EvmSstoreInput {
+                    index: Self::PAUSED_SLOT,
+                    value,
+                };
+                fluentbase_sdk::contracts::EVM_STORAGE_CLIENT.sstore(input);
+            }
+        }
+    })
+}
+
+fn is_skip_attr(attr: &syn::Attribute) -> bool {
+    attr.path().is_ident("pausable")
+        && attr
+            .parse_args::<syn::Ident>()
+            .is_ok_and(|ident| ident == "skip")
+}
+
+/// Public methods mutate the state unless their signature is `view` or `pure`, the deploy
+/// function and methods marked with `#[pausable(skip)]` aren't guarded either
+fn is_guarded(func: &ImplItemFn) -> bool {
+    if func.sig.ident == "deploy" || func.attrs.iter().any(is_skip_attr) {
+        return false;
+    }
+    let signature = func.attrs.iter().find_map(|attr| {
+        if attr.path().is_ident("signature") {
+            attr.parse_args::<LitStr>().ok()
+        } else {
+            None
+        }
+    });
+    match signature {
+        Some(signature) => !signature
+            .value()
+            .split_whitespace()
+            .any(|word| word == "view" || word == "pure"),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn method_names(ast: &ItemImpl) -> Vec<String> {
+        ast.items
+            .iter()
+            .filter_map(|item| match item {
+                ImplItem::Fn(func) => Some(func.sig.ident.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pausable_guards_mutating_methods() {
+        let args: PausableArgs = parse_quote!(admin = ADMIN);
+        let ast: ItemImpl = parse_quote! {
+            impl Token {
+                pub fn transfer(&self, to: Address, value: U256) -> U256 {
+                    value
+                }
+                #[signature("function balanceOf(address owner) external view returns (uint256)")]
+                pub fn balance_of(&self, owner: Address) -> U256 {
+                    U256::ZERO
+                }
+                #[pausable(skip)]
+                pub fn rescue(&self) {}
+                fn helper(&self) {}
+            }
+        };
+        let expanded = expand_pausable(&args, ast).unwrap();
+        let file: syn::File = syn::parse2(expanded).unwrap();
+        let syn::Item::Impl(ast) = &file.items[0] else {
+            panic!("impl expected");
+        };
+        assert_eq!(
+            method_names(ast),
+            vec![
+                "transfer",
+                "balance_of",
+                "rescue",
+                "helper",
+                "paused",
+                "pause",
+                "unpause"
+            ]
+        );
+        let is_guarded = |name: &str| {
+            ast.items.iter().any(|item| match item {
+                ImplItem::Fn(func) if func.sig.ident == name => {
+                    func.block.stmts.first().is_some_and(|first| {
+                        quote!(#first).to_string().contains("contract is paused")
+                    })
+                }
+                _ => false,
+            })
+        };
+        assert!(is_guarded("transfer"));
+        assert!(!is_guarded("balance_of"));
+        assert!(!is_guarded("rescue"));
+        assert!(!is_guarded("helper"));
+        // skip markers are removed
+        assert!(!quote!(#ast).to_string().contains("skip"));
+    }
+
+    #[test]
+    fn test_pausable_rejects_trait_impls() {
+        let args: PausableArgs = parse_quote!(admin = ADMIN);
+        let ast: ItemImpl = parse_quote! {
+            impl TokenAPI for Token {}
+        };
+        assert!(expand_pausable(&args, ast).is_err());
+        assert!(syn::parse2::<PausableArgs>(quote!()).is_err());
+    }
+}
@@ -9,6 +9,7 @@ use syn::{
     token::Semi,
     Path,
     Result as SynResult,
+    Token,
 };
 use syn_solidity::{Type, TypeArray, TypeMapping};
 
@@ -91,6 +92,7 @@ struct WrappedTypeMapping {
     pub type_mapping: TypeMapping,
     pub ident: Ident,
     pub client: Path,
+    pub global: Option<Ident>,
 }
 
 impl WrappedTypeMapping {
@@ -234,10 +236,11 @@ impl Expandable for WrappedTypeMapping {
         let client_trait = &self.client;
 
         let new_fn = quote! {
-            pub fn new(client: &'a T) -> Self {
+            pub const fn new(client: &'a T) -> Self {
                 Self { client }
             }
         };
+        let global = global_accessor(ident, self.global.as_ref(), quote! { pub });
 
         let set_client_fn = quote! {
             pub fn set_client(&mut self, client: &'a T) {
@@ -256,6 +259,7 @@ impl Expandable for WrappedTypeMapping {
                 #new_fn
                 #funcs
             }
+            #global
         };
         Ok(expanded)
     }
@@ -272,11 +276,13 @@ impl Parse for WrappedTypeMapping {
         input.parse::<syn::token::Lt>()?;
         let client: Path = input.parse()?;
         input.parse::<syn::token::Gt>()?;
+        let global = parse_global_accessor(input)?;
 
         Ok(Self {
             type_mapping,
             ident,
             client,
+            global,
         })
     }
 }
@@ -286,6 +292,7 @@ struct WrappedTypeArray {
     pub type_array: TypeArray,
     pub ident: Ident,
     pub client: Path,
+    pub global: Option<Ident>,
 }
 
 impl Expandable for WrappedTypeArray {
@@ -295,10 +302,11 @@ impl Expandable for WrappedTypeArray {
         let client_trait = &self.client;
 
         let new_fn = quote! {
-            pub fn new(client: &'a T) -> Self {
+            pub const fn new(client: &'a T) -> Self {
                 Self { client }
             }
         };
+        let global = global_accessor(ident, self.global.as_ref(), quote! {});
 
         let key_hash_fn = quote! {
             fn key_hash(&self, slot: fluentbase_sdk::U256, index: fluentbase_sdk::U256) -> fluentbase_sdk::U256 {
@@ -345,6 +353,7 @@ impl Expandable for WrappedTypeArray {
                 #set_fn
 
             }
+            #global
         };
         Ok(expanded)
     }
@@ -362,15 +371,43 @@ impl Parse for WrappedTypeArray {
         input.parse::<syn::token::Lt>()?;
         let client: Path = input.parse()?;
         input.parse::<syn::token::Gt>()?;
+        let global = parse_global_accessor(input)?;
 
         Ok(Self {
             type_array,
             ident,
             client,
+            global,
         })
     }
 }
 
+/// Parses optional `as NAME` suffix of the storage item
+fn parse_global_accessor(input: ParseStream) -> SynResult<Option<Ident>> {
+    if !input.peek(Token![as]) {
+        return Ok(None);
+    }
+    input.parse::<Token![as]>()?;
+    Ok(Some(input.parse()?))
+}
+
+/// Global accessor is a constant handle bound to the EVM storage client, so contract code can
+/// call `BALANCES.get(owner)` directly. The handle is just a static reference, slot math runs
+/// on every access and nothing is allocated.
+fn global_accessor(
+    ident: &Ident,
+    global: Option<&Ident>,
+    visibility: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let Some(global) = global else {
+        return quote! {};
+    };
+    quote! {
+        #visibility const #global: #ident<'static, fluentbase_sdk::contracts::EvmClient> =
+            #ident::new(&fluentbase_sdk::contracts::EVM_STORAGE_CLIENT);
+    }
+}
+
 fn slot_from_index(index: usize) -> proc_macro2::TokenStream {
     quote! {
         const SLOT: fluentbase_sdk::U256 = Self::u256_from_usize(#index);
@@ -440,6 +477,33 @@ mod tests {
         assert_eq!(args[2].name.to_string(), "balances");
         assert_eq!(args[2].ty.to_string(), "Address");
     }
+    #[test]
+    fn test_parse_global_accessor() {
+        let items: StorageItems = parse_quote! {
+            U256[] Arr<EvmAPI>;
+            mapping(Address => U256) Balance<EvmAPI> as BALANCES;
+        };
+        let items = items.items.into_iter().collect::<Vec<_>>();
+        match &items[0] {
+            StorageItem::Array(array) => assert!(array.global.is_none()),
+            _ => panic!("array expected"),
+        }
+        match &items[1] {
+            StorageItem::Mapping(mapping) => {
+                assert_eq!(mapping.global.as_ref().unwrap().to_string(), "BALANCES")
+            }
+            _ => panic!("mapping expected"),
+        }
+        let expanded = global_accessor(
+            &parse_quote!(Balance),
+            Some(&parse_quote!(BALANCES)),
+            quote! { pub },
+        )
+        .to_string();
+        assert!(expanded.contains("pub const BALANCES"));
+        assert!(expanded.contains("EVM_STORAGE_CLIENT"));
+    }
+
     #[test]
     fn test_u256() {
         assert_eq!(
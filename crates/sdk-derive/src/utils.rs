@@ -41,6 +41,13 @@ pub fn get_public_methods(ast: &ItemImpl) -> Vec<&ImplItemFn> {
         .collect()
 }
 
+pub fn calculate_keccak256(data: &str) -> [u8; 32] {
+    use crypto_hashes::{digest::Digest, sha3::Keccak256};
+    let mut hash = Keccak256::new();
+    hash.update(data);
+    hash.finalize().into()
+}
+
 pub fn calculate_keccak256_bytes(signature: &str) -> [u8; 4] {
     use crypto_hashes::{digest::Digest, sha3::Keccak256};
     let mut hash = Keccak256::new();
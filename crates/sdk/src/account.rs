@@ -15,6 +15,13 @@ use fluentbase_types::{
     SovereignAPI,
     B256,
     F254,
+    JZKT_ACCOUNT_BALANCE_FIELD,
+    JZKT_ACCOUNT_FIELDS_COUNT,
+    JZKT_ACCOUNT_NONCE_FIELD,
+    JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
+    JZKT_ACCOUNT_RWASM_CODE_SIZE_FIELD,
+    JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD,
+    JZKT_ACCOUNT_SOURCE_CODE_SIZE_FIELD,
     KECCAK_EMPTY,
     NATIVE_TRANSFER_ADDRESS,
     NATIVE_TRANSFER_KECCAK,
@@ -23,28 +30,6 @@ use fluentbase_types::{
 };
 use revm_primitives::AccountInfo;
 
-/// Number of fields
-pub const JZKT_ACCOUNT_FIELDS_COUNT: u32 = 6;
-pub const JZKT_STORAGE_FIELDS_COUNT: u32 = 1;
-
-pub const JZKT_ACCOUNT_BALANCE_FIELD: u32 = 0;
-pub const JZKT_ACCOUNT_NONCE_FIELD: u32 = 1;
-pub const JZKT_ACCOUNT_SOURCE_CODE_SIZE_FIELD: u32 = 2;
-pub const JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD: u32 = 3;
-pub const JZKT_ACCOUNT_RWASM_CODE_SIZE_FIELD: u32 = 4;
-pub const JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD: u32 = 5;
-
-/// Compression flags for upper fields.
-///
-/// We compress the following fields:
-/// - balance (0) because of balance overflow
-/// - source code hash (3) because its keccak256
-///
-/// Mask is: 0b00001001
-pub const JZKT_ACCOUNT_COMPRESSION_FLAGS: u32 =
-    (1 << JZKT_ACCOUNT_BALANCE_FIELD) + (1 << JZKT_ACCOUNT_SOURCE_CODE_HASH_FIELD);
-pub const JZKT_STORAGE_COMPRESSION_FLAGS: u32 = 0;
-
 pub type AccountCheckpoint = u64;
 pub type AccountFields = [Bytes32; JZKT_ACCOUNT_FIELDS_COUNT as usize];
 
@@ -54,11 +39,15 @@ pub trait AccountManager {
     fn rollback(&self, checkpoint: AccountCheckpoint);
     fn account(&self, address: Address) -> (Account, bool);
     fn write_account(&self, account: &Account);
+    /// Deletes the account leaf, unlike writing an empty account the key becomes absent
+    fn remove_account(&self, address: Address);
     fn preimage_size(&self, hash: &[u8; 32]) -> u32;
     fn preimage(&self, hash: &[u8; 32]) -> Bytes;
     fn update_preimage(&self, key: &[u8; 32], field: u32, preimage: &[u8]);
     fn storage(&self, address: Address, slot: U256, committed: bool) -> (U256, bool);
     fn write_storage(&self, address: Address, slot: U256, value: U256) -> bool;
+    /// Deletes the storage slot, unlike writing zero the slot becomes absent
+    fn remove_storage(&self, address: Address, slot: U256);
     fn log(&self, address: Address, data: Bytes, topics: &[B256]);
     fn exec_hash(
         &self,
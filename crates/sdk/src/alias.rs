@@ -0,0 +1,110 @@
+use crate::{contracts::ADDRESS_ALIAS_REGISTRY, AccountManager, LowLevelSDK, SharedAPI};
+use fluentbase_types::{Address, B256, U256};
+
+/// Prefix of the alias preimage, it can't collide with CREATE (RLP list) and CREATE2 (`0xff`)
+/// preimages, so an alias never points to a regular contract address
+const ALIAS_PREFIX: u8 = 0xfe;
+
+const ALIAS_SLOT_PREFIX: u8 = 0x01;
+const ORIGIN_SLOT_PREFIX: u8 = 0x02;
+
+/// Derives an EVM-visible alias of the WASM contract, where formula is
+/// `keccak256(0xfe || address)[12..]`
+#[inline(always)]
+pub fn calc_alias_address(address: &Address) -> Address {
+    let mut hash = B256::ZERO;
+    let preimage = prefixed_address(ALIAS_PREFIX, address);
+    LowLevelSDK::keccak256(preimage.as_ptr(), preimage.len() as u32, hash.as_mut_ptr());
+    Address::from_word(hash)
+}
+
+/// Links the WASM contract with its alias in the registry, returns the alias. Registration is
+/// idempotent, so it's safe to call it for already registered contracts.
+pub fn register_alias<AM: AccountManager>(am: &AM, address: &Address) -> Address {
+    let alias = calc_alias_address(address);
+    am.write_storage(
+        ADDRESS_ALIAS_REGISTRY,
+        registry_slot(ALIAS_SLOT_PREFIX, address),
+        address_to_u256(&alias),
+    );
+    am.write_storage(
+        ADDRESS_ALIAS_REGISTRY,
+        registry_slot(ORIGIN_SLOT_PREFIX, &alias),
+        address_to_u256(address),
+    );
+    alias
+}
+
+/// Returns the registered alias of the WASM contract
+pub fn alias_of<AM: AccountManager>(am: &AM, address: &Address) -> Option<Address> {
+    let (value, _) = am.storage(
+        ADDRESS_ALIAS_REGISTRY,
+        registry_slot(ALIAS_SLOT_PREFIX, address),
+        false,
+    );
+    u256_to_address(value)
+}
+
+/// Returns the WASM contract that is registered under the alias
+pub fn resolve_alias<AM: AccountManager>(am: &AM, alias: &Address) -> Option<Address> {
+    let (value, _) = am.storage(
+        ADDRESS_ALIAS_REGISTRY,
+        registry_slot(ORIGIN_SLOT_PREFIX, alias),
+        false,
+    );
+    u256_to_address(value)
+}
+
+/// Resolves the alias if it's registered, otherwise returns the address as is
+pub fn resolve_address<AM: AccountManager>(am: &AM, address: &Address) -> Address {
+    resolve_alias(am, address).unwrap_or(*address)
+}
+
+fn prefixed_address(prefix: u8, address: &Address) -> [u8; 21] {
+    let mut result = [0u8; 21];
+    result[0] = prefix;
+    result[1..].copy_from_slice(address.as_slice());
+    result
+}
+
+fn registry_slot(prefix: u8, address: &Address) -> U256 {
+    let mut hash = B256::ZERO;
+    let preimage = prefixed_address(prefix, address);
+    LowLevelSDK::keccak256(preimage.as_ptr(), preimage.len() as u32, hash.as_mut_ptr());
+    U256::from_be_bytes(hash.0)
+}
+
+fn address_to_u256(address: &Address) -> U256 {
+    U256::from_be_slice(address.as_slice())
+}
+
+fn u256_to_address(value: U256) -> Option<Address> {
+    if value.is_zero() {
+        return None;
+    }
+    Some(Address::from_word(B256::from(value.to_be_bytes::<32>())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GuestAccountManager;
+    use fluentbase_types::address;
+
+    #[test]
+    fn test_register_and_resolve_alias() {
+        let am = GuestAccountManager::DEFAULT;
+        let address = address!("1111111111111111111111111111111111111111");
+        let alias = calc_alias_address(&address);
+        assert_ne!(alias, address);
+        assert_eq!(alias_of(&am, &address), None);
+        assert_eq!(resolve_address(&am, &alias), alias);
+        assert_eq!(register_alias(&am, &address), alias);
+        assert_eq!(alias_of(&am, &address), Some(alias));
+        assert_eq!(resolve_alias(&am, &alias), Some(address));
+        assert_eq!(resolve_address(&am, &alias), address);
+        // non-alias addresses are resolved as is
+        assert_eq!(resolve_alias(&am, &address), None);
+        assert_eq!(resolve_address(&am, &address), address);
+    }
+}
@@ -1,23 +1,257 @@
-use core::alloc::{GlobalAlloc, Layout};
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    cell::UnsafeCell,
+    mem::{align_of, size_of},
+    ptr,
+};
 
-#[cfg(target_arch = "wasm32")]
-fn _sys_alloc_aligned(bytes: usize, align: usize) -> *mut u8 {
-    extern "C" {
-        static __heap_base: u8;
+/// Default limit of the guest heap, allocations beyond the limit fail (the contract panics with
+/// the allocation error)
+pub const DEFAULT_HEAP_SIZE: usize = 16 * 1024 * 1024;
+
+/// Global allocator of the SDK, it can be switched to the free-list variant with the
+/// `free-list-allocator` feature or disabled with the `custom-allocator` feature (then the
+/// contract must declare its own `#[global_allocator]`)
+#[cfg(not(feature = "free-list-allocator"))]
+pub type GuestAllocator = BumpAllocator<DEFAULT_HEAP_SIZE>;
+#[cfg(feature = "free-list-allocator")]
+pub type GuestAllocator = FreeListAllocator<DEFAULT_HEAP_SIZE>;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HeapUsage {
+    /// Size of live allocations
+    pub used: usize,
+    /// Size of the heap region taken by the allocator (a high watermark, it never shrinks)
+    pub reserved: usize,
+    /// Number of allocations made so far
+    pub allocations: usize,
+}
+
+struct Heap {
+    start: usize,
+    end: usize,
+    pos: usize,
+    usage: HeapUsage,
+}
+
+impl Heap {
+    const fn empty() -> Self {
+        Self {
+            start: 0,
+            end: 0,
+            pos: 0,
+            usage: HeapUsage {
+                used: 0,
+                reserved: 0,
+                allocations: 0,
+            },
+        }
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    fn new(start: usize, size: usize) -> Self {
+        Self {
+            start,
+            end: start.saturating_add(size),
+            pos: start,
+            usage: HeapUsage::default(),
+        }
+    }
+
+    /// Heap starts at `__heap_base` provided by the linker
+    #[inline(always)]
+    fn init(&mut self, _size: usize) {
+        #[cfg(target_arch = "wasm32")]
+        if self.start == 0 {
+            extern "C" {
+                static __heap_base: u8;
+            }
+            *self = Self::new(unsafe { (&__heap_base) as *const u8 as usize }, _size);
+        }
+    }
+
+    #[inline(always)]
+    fn bump(&mut self, size: usize, align: usize) -> *mut u8 {
+        let offset = self.pos & (align - 1);
+        let ptr = if offset != 0 {
+            self.pos + (align - offset)
+        } else {
+            self.pos
+        };
+        match ptr.checked_add(size) {
+            Some(pos) if pos <= self.end => {
+                self.pos = pos;
+                self.usage.reserved = pos - self.start;
+                ptr as *mut u8
+            }
+            _ => ptr::null_mut(),
+        }
+    }
+}
+
+/// Allocator that only moves the heap pointer forward, memory is never reused. It's the
+/// cheapest option in size and fuel for short-living contracts.
+pub struct BumpAllocator<const HEAP_SIZE: usize> {
+    heap: UnsafeCell<Heap>,
+}
+
+// guest is single-threaded
+unsafe impl<const HEAP_SIZE: usize> Sync for BumpAllocator<HEAP_SIZE> {}
+
+impl<const HEAP_SIZE: usize> BumpAllocator<HEAP_SIZE> {
+    pub const fn new() -> Self {
+        Self {
+            heap: UnsafeCell::new(Heap::empty()),
+        }
+    }
+
+    pub fn heap_usage(&self) -> HeapUsage {
+        unsafe { (*self.heap.get()).usage }
+    }
+}
+
+unsafe impl<const HEAP_SIZE: usize> GlobalAlloc for BumpAllocator<HEAP_SIZE> {
+    #[inline(always)]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let heap = &mut *self.heap.get();
+        heap.init(HEAP_SIZE);
+        let ptr = heap.bump(layout.size(), layout.align());
+        if !ptr.is_null() {
+            heap.usage.used += layout.size();
+            heap.usage.allocations += 1;
+        }
+        ptr
+    }
+
+    #[inline(always)]
+    unsafe fn dealloc(&self, _ptr: *mut u8, layout: Layout) {
+        // memory isn't reused, only the statistics is updated
+        let heap = &mut *self.heap.get();
+        heap.usage.used -= layout.size();
+    }
+}
+
+struct FreeBlock {
+    next: *mut FreeBlock,
+    size: usize,
+}
+
+/// Allocator that keeps freed blocks in the list and reuses them (first fit, blocks aren't
+/// split or merged), it's useful for contracts with many short-living allocations.
+pub struct FreeListAllocator<const HEAP_SIZE: usize> {
+    heap: UnsafeCell<Heap>,
+    free_list: UnsafeCell<*mut FreeBlock>,
+}
+
+// guest is single-threaded
+unsafe impl<const HEAP_SIZE: usize> Sync for FreeListAllocator<HEAP_SIZE> {}
+
+impl<const HEAP_SIZE: usize> FreeListAllocator<HEAP_SIZE> {
+    pub const fn new() -> Self {
+        Self {
+            heap: UnsafeCell::new(Heap::empty()),
+            free_list: UnsafeCell::new(ptr::null_mut()),
+        }
+    }
+
+    pub fn heap_usage(&self) -> HeapUsage {
+        unsafe { (*self.heap.get()).usage }
+    }
+
+    #[cfg(test)]
+    fn with_heap(heap: Heap) -> Self {
+        Self {
+            heap: UnsafeCell::new(heap),
+            free_list: UnsafeCell::new(ptr::null_mut()),
+        }
+    }
+
+    /// Every block must be able to hold the free list entry once it's released
+    #[inline(always)]
+    fn block_layout(layout: Layout) -> (usize, usize) {
+        let align = layout.align().max(align_of::<FreeBlock>());
+        let size = layout.size().max(size_of::<FreeBlock>());
+        ((size + (align - 1)) & !(align - 1), align)
+    }
+
+    unsafe fn take_free_block(&self, size: usize, align: usize) -> *mut u8 {
+        let mut link = self.free_list.get();
+        while !(*link).is_null() {
+            let block = *link;
+            if (*block).size >= size && (block as usize) & (align - 1) == 0 {
+                *link = (*block).next;
+                return block as *mut u8;
+            }
+            link = &mut (*block).next;
+        }
+        ptr::null_mut()
+    }
+}
+
+unsafe impl<const HEAP_SIZE: usize> GlobalAlloc for FreeListAllocator<HEAP_SIZE> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let heap = &mut *self.heap.get();
+        heap.init(HEAP_SIZE);
+        let (size, align) = Self::block_layout(layout);
+        let mut ptr = self.take_free_block(size, align);
+        if ptr.is_null() {
+            ptr = heap.bump(size, align);
+        }
+        if !ptr.is_null() {
+            heap.usage.used += size;
+            heap.usage.allocations += 1;
+        }
+        ptr
     }
-    static mut HEAP_POS: usize = 0;
-    let mut heap_pos = unsafe { HEAP_POS };
-    if heap_pos == 0 {
-        heap_pos = unsafe { (&__heap_base) as *const u8 as usize };
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let heap = &mut *self.heap.get();
+        let (size, _) = Self::block_layout(layout);
+        heap.usage.used -= size;
+        let block = ptr as *mut FreeBlock;
+        block.write(FreeBlock {
+            next: *self.free_list.get(),
+            size,
+        });
+        *self.free_list.get() = block;
     }
-    let offset = heap_pos & (align - 1);
-    if offset != 0 {
-        heap_pos += align - offset;
+}
+
+/// Usage of the SDK global allocator
+#[cfg(all(
+    not(feature = "std"),
+    not(feature = "custom-allocator"),
+    target_arch = "wasm32"
+))]
+pub fn heap_usage() -> HeapUsage {
+    crate::ALLOCATOR.heap_usage()
+}
+
+/// Usage of the SDK global allocator, it's empty because the allocator isn't used
+#[cfg(not(all(
+    not(feature = "std"),
+    not(feature = "custom-allocator"),
+    target_arch = "wasm32"
+)))]
+pub fn heap_usage() -> HeapUsage {
+    HeapUsage::default()
+}
+
+/// Writes the heap usage into the debug log, it's compiled only with the `debug-heap` feature
+#[inline(always)]
+pub fn report_heap_usage() {
+    #[cfg(feature = "debug-heap")]
+    {
+        use crate::{LowLevelSDK, SovereignAPI};
+        let usage = heap_usage();
+        let message = alloc::format!(
+            "heap usage: used={} reserved={} allocations={}",
+            usage.used,
+            usage.reserved,
+            usage.allocations
+        );
+        LowLevelSDK::debug_log(message.as_ptr(), message.len() as u32);
     }
-    let ptr = heap_pos as *mut u8;
-    heap_pos += bytes;
-    unsafe { HEAP_POS = heap_pos };
-    ptr
 }
 
 #[inline(always)]
@@ -28,22 +262,35 @@ pub fn alloc_ptr(len: usize) -> *mut u8 {
 
 #[inline(always)]
 pub fn alloc_slice<'a>(len: usize) -> &'a mut [u8] {
-    use core::ptr;
     unsafe { &mut *ptr::slice_from_raw_parts_mut(alloc_ptr(len), len) }
 }
 
-#[cfg(target_arch = "wasm32")]
-pub struct HeapBaseAllocator {}
-
-#[cfg(target_arch = "wasm32")]
-unsafe impl GlobalAlloc for HeapBaseAllocator {
-    #[inline(always)]
-    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        _sys_alloc_aligned(layout.size(), layout.align())
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    #[inline(always)]
-    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {
-        // ops, no dealoc
+    #[test]
+    fn test_free_list_allocator() {
+        let mut region = [0u64; 64];
+        let heap = Heap::new(region.as_mut_ptr() as usize, region.len() * 8);
+        let allocator = FreeListAllocator::<0>::with_heap(heap);
+        unsafe {
+            let layout = Layout::from_size_align(24, 8).unwrap();
+            let a = allocator.alloc(layout);
+            let b = allocator.alloc(layout);
+            assert!(!a.is_null() && !b.is_null());
+            assert_ne!(a, b);
+            allocator.dealloc(a, layout);
+            // the freed block is reused
+            assert_eq!(allocator.alloc(Layout::from_size_align(16, 8).unwrap()), a);
+            let usage = allocator.heap_usage();
+            assert_eq!(usage.used, 40);
+            assert_eq!(usage.reserved, 48);
+            assert_eq!(usage.allocations, 3);
+            // heap limit is exceeded
+            assert!(allocator
+                .alloc(Layout::from_size_align(512, 8).unwrap())
+                .is_null());
+        }
     }
 }
@@ -5,6 +5,12 @@ extern "C" {
     /// - Poseidon (two modes, message hash and two elements hash)
     /// - Ecrecover
     pub fn _keccak256(data_offset: *const u8, data_len: u32, output32_offset: *mut u8);
+    /// Opens incremental keccak256 hashing context and returns its handle
+    pub fn _hash_init() -> u32;
+    /// Absorbs the data into the hashing context, fuel is charged per byte
+    pub fn _hash_update(handle: u32, data_offset: *const u8, data_len: u32);
+    /// Writes the hash into the output and closes the hashing context
+    pub fn _hash_final(handle: u32, output32_offset: *mut u8);
     pub fn _poseidon(data_offset: *const u8, data_len: u32, output32_offset: *mut u8);
     pub fn _poseidon_hash(
         fa32_offset: *const u8,
@@ -22,10 +28,25 @@ extern "C" {
     /// Basic system methods that are available for every app (shared and sovereign)
     pub fn _exit(code: i32) -> !;
     pub fn _write(offset: *const u8, length: u32);
+    /// Streams the chunk to the host output sink and returns the number of accepted bytes (the
+    /// rest must be written again), without the sink the chunk is appended to the output
+    pub fn _write_output_chunk(offset: *const u8, length: u32) -> u32;
     pub fn _input_size() -> u32;
+    /// Decrypts the sealed section of the input, copies up to `target_len` bytes of the
+    /// plaintext into the target and returns the plaintext size (only authorized contracts can
+    /// reveal the section)
+    pub fn _input_reveal(section: u32, target: *mut u8, target_len: u32) -> u32;
     pub fn _read(target: *mut u8, offset: u32, length: u32);
+    /// Copies up to `length` bytes of the input starting from the offset into the target and
+    /// returns the number of copied bytes, the execution halts if the offset is out of the input
+    pub fn _input_copy(target: *mut u8, offset: u32, length: u32) -> u32;
     pub fn _output_size() -> u32;
     pub fn _read_output(target: *mut u8, offset: u32, length: u32);
+    /// Size of the return data of the last nested call
+    pub fn _return_data_size() -> u32;
+    /// Copies the range of the return data into the target, the execution halts if the range
+    /// is out of bounds (like EVM `RETURNDATACOPY`)
+    pub fn _return_data_copy(target: *mut u8, offset: u32, length: u32);
     pub fn _forward_output(offset: u32, len: u32);
     pub fn _state() -> u32;
 
@@ -37,7 +58,8 @@ extern "C" {
     /// - `context_len` - length of the context
     /// - `return_ptr` - pointer to the return data (might be `ptr::null()`)
     /// - `return_len` - length of return data buffer (might be zero)
-    /// - `fuel_ptr` - pointer to the fuel memory field (modifiable)
+    /// - `fuel_ptr` - pointer to the 32-bit fuel memory field (modifiable), zero forwards all
+    ///   available fuel; on return it holds the remaining fuel, saturated to `u32::MAX`
     pub fn _exec(
         code_hash32_ptr: *const u8,
         input_ptr: *const u8,
@@ -46,6 +68,26 @@ extern "C" {
         return_len: u32,
         fuel_ptr: *mut u32,
     ) -> i32;
+    /// The same as `_exec`, but the nested call can't change the state (an equivalent of EVM
+    /// `STATICCALL`), all state changes in the call subtree fail with `WriteProtection`
+    pub fn _static_exec(
+        code_hash32_ptr: *const u8,
+        input_ptr: *const u8,
+        input_len: u32,
+        return_ptr: *mut u8,
+        return_len: u32,
+        fuel_ptr: *mut u32,
+    ) -> i32;
+    /// The same as `_exec`, but the callee is addressed by the account, accounts with rWASM code
+    /// are executed natively and accounts with EVM code are executed by the EVM interpreter
+    pub fn _exec_address(
+        address20_ptr: *const u8,
+        input_ptr: *const u8,
+        input_len: u32,
+        return_ptr: *mut u8,
+        return_len: u32,
+        fuel_ptr: *mut u32,
+    ) -> i32;
     pub fn _context_call(
         code_hash32_ptr: *const u8,
         input_ptr: *const u8,
@@ -59,10 +101,31 @@ extern "C" {
     ) -> i32;
 
     pub fn _charge_fuel(delta: u64) -> u64;
+    /// Returns fuel remaining for the current call
+    pub fn _fuel_remaining() -> u64;
+    /// Returns fuel consumed by the current call (including nested calls)
+    pub fn _fuel_consumed() -> u64;
 
     /// Read context and write into specified target with offset and length.
     pub fn _read_context(target_ptr: *mut u8, offset: u32, length: u32);
 
+    /// Writes EIP-4844 versioned hash of the transaction blob (zero hash if index is out of bounds)
+    pub fn _blob_hash(index: u32, output32_ptr: *mut u8);
+    /// Writes blob base fee of the current block as little-endian 32 bytes
+    pub fn _blob_base_fee(output32_ptr: *mut u8);
+    /// Writes effective gas price of the transaction as little-endian 32 bytes
+    pub fn _gas_price(output32_ptr: *mut u8);
+    /// Writes base fee of the current block as little-endian 32 bytes
+    pub fn _base_fee(output32_ptr: *mut u8);
+    /// Pauses the execution and passes the request to the embedder, the response is written into
+    /// the return buffer (and is available as the return data) when the execution is resumed
+    pub fn _suspend(
+        request_ptr: *const u8,
+        request_len: u32,
+        return_ptr: *mut u8,
+        return_len: u32,
+    ) -> i32;
+
     /// Journaled ZK Trie methods to work with blockchain state
     pub fn _checkpoint() -> u64;
     pub fn _get_leaf(
@@ -71,12 +134,25 @@ extern "C" {
         output32_ptr: *mut u8,
         committed: bool,
     ) -> bool;
+    /// Reads the storage slot (little-endian) of another contract into `output32_ptr`, the
+    /// function is read-only and allowed in static and shared executions. If `with_proof` is
+    /// set, the committed value is read and the encoded Merkle proof of the slot is placed into
+    /// the return data buffer, returns the proof size (zero if the proof isn't requested)
+    pub fn _storage_read_external(
+        address20_ptr: *const u8,
+        slot32_ptr: *const u8,
+        output32_ptr: *mut u8,
+        with_proof: bool,
+    ) -> u32;
     pub fn _update_leaf(
         key32_ptr: *const u8,
         flags: u32,
         vals32_offset: *const [u8; 32],
         vals32_len: u32,
     );
+    /// Removes the leaf from the trie, the key becomes absent once the hard delete hardfork is
+    /// enabled, before it the leaf is overwritten with the empty value
+    pub fn _remove_leaf(key32_ptr: *const u8);
     pub fn _update_preimage(
         key32_ptr: *const u8,
         field: u32,
@@ -97,4 +173,12 @@ extern "C" {
     pub fn _preimage_copy(hash32_ptr: *const u8, preimage_ptr: *mut u8);
 
     pub fn _debug_log(msg_ptr: *const u8, msg_len: u32);
+    // assertion syscalls fail with `CapabilityDenied` unless the host enables debug assertions
+    /// Fails with `AssertionFailed` if the memory regions aren't equal
+    pub fn _assert_eq_mem(actual_ptr: *const u8, expected_ptr: *const u8, len: u32);
+    /// Fails with `AssertionFailed` if the storage slot of the contract isn't equal to the value
+    pub fn _expect_storage(address20_ptr: *const u8, slot32_ptr: *const u8, value32_ptr: *const u8);
+    /// Fails with `AssertionFailed` if the execution has consumed more fuel than the limit
+    /// (unmetered executions fail with `NotSupportedCall`)
+    pub fn _expect_fuel_le(max_fuel: u64);
 }
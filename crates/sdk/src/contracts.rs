@@ -1,11 +1,13 @@
 use crate::{
     alloc_slice,
+    decode_output,
     types::{EvmCallMethodInput, EvmCallMethodOutput, EvmCreateMethodInput, EvmCreateMethodOutput},
     LowLevelSDK,
     SharedAPI,
     JZKT_ACCOUNT_RWASM_CODE_HASH_FIELD,
     U256,
 };
+use fluentbase_codec::Encoder;
 use fluentbase_codec_derive::Codec;
 use fluentbase_sdk_derive::{client, signature};
 pub use fluentbase_types::contracts::*;
@@ -43,6 +45,13 @@ pub trait EvmAPI {
     fn sstore(&self, input: EvmSstoreInput) -> EvmSstoreOutput;
 }
 
+/// Client of the EVM precompile used by global storage accessors generated by
+/// `solidity_storage!`
+pub const EVM_STORAGE_CLIENT: EvmClient = EvmClient {
+    address: PRECOMPILE_EVM,
+    fuel: u32::MAX,
+};
+
 pub trait WasmAPI {}
 
 pub trait SvmAPI {}
@@ -52,7 +61,25 @@ pub trait BlendedAPI {
     fn exec_svm_tx(&self, raw_svm_tx: Bytes);
 }
 
-pub fn call_system_contract(address: &Address, input: &[u8], mut fuel: u32) -> (Bytes, i32) {
+pub fn call_system_contract(address: &Address, input: &[u8], fuel: u32) -> (Bytes, i32) {
+    let exit_code = exec_system_contract(address, input, fuel);
+    let output_size = LowLevelSDK::output_size();
+    let output = alloc_slice(output_size as usize);
+    LowLevelSDK::read_output(output.as_mut_ptr(), 0, output_size);
+    (Bytes::copy_from_slice(output), exit_code)
+}
+
+/// Calls the system contract and decodes its output in place, without copying it into `Bytes`
+pub fn call_system_contract_decoded<T: Encoder<T> + Default>(
+    address: &Address,
+    input: &[u8],
+    fuel: u32,
+) -> (T, i32) {
+    let exit_code = exec_system_contract(address, input, fuel);
+    (decode_output::<LowLevelSDK, T>(), exit_code)
+}
+
+fn exec_system_contract(address: &Address, input: &[u8], mut fuel: u32) -> i32 {
     let mut address32: [u8; 32] = [0u8; 32];
     address32[12..].copy_from_slice(address.as_slice());
     let mut hash32: [u8; 32] = [0u8; 32];
@@ -62,16 +89,12 @@ pub fn call_system_contract(address: &Address, input: &[u8], mut fuel: u32) -> (
         hash32.as_mut_ptr(),
         false,
     );
-    let exit_code = LowLevelSDK::exec(
+    LowLevelSDK::exec(
         hash32.as_ptr(),
         input.as_ptr(),
         input.len() as u32,
         core::ptr::null_mut(),
         0,
         &mut fuel as *mut u32,
-    );
-    let output_size = LowLevelSDK::output_size();
-    let output = alloc_slice(output_size as usize);
-    LowLevelSDK::read_output(output.as_mut_ptr(), 0, output_size);
-    (Bytes::copy_from_slice(output), exit_code)
+    )
 }
@@ -26,6 +26,7 @@ pub trait ContextReader {
     fn block_prevrandao(&self) -> B256;
     fn block_gas_limit(&self) -> u64;
     fn block_base_fee(&self) -> U256;
+    fn block_blob_base_fee(&self) -> U256;
     fn tx_gas_limit(&self) -> u64;
     fn tx_nonce(&self) -> u64;
     fn tx_gas_price(&self) -> U256;
@@ -40,6 +41,16 @@ pub trait ContextReader {
     fn contract_caller(&self) -> Address;
     fn contract_value(&self) -> U256;
     fn contract_is_static(&self) -> bool;
+
+    /// Price paid for each unit of gas, for EIP-1559 transactions gas price is a max fee, so the
+    /// effective price is `min(max_fee, base_fee + priority_fee)`
+    fn tx_effective_gas_price(&self) -> U256 {
+        let gas_price = self.tx_gas_price();
+        match self.tx_gas_priority_fee() {
+            Some(priority_fee) => gas_price.min(self.block_base_fee().saturating_add(priority_fee)),
+            None => gas_price,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, Codec)]
@@ -53,6 +64,7 @@ pub struct ContractInput {
     pub block_prevrandao: B256,
     pub block_gas_limit: u64,
     pub block_base_fee: U256,
+    pub block_blob_base_fee: U256,
     // tx info
     pub tx_gas_limit: u64,
     pub tx_nonce: u64,
@@ -81,6 +93,7 @@ impl ContractInput {
             block_prevrandao: cr.block_prevrandao(),
             block_gas_limit: cr.block_gas_limit(),
             block_base_fee: cr.block_base_fee(),
+            block_blob_base_fee: cr.block_blob_base_fee(),
             tx_gas_limit: cr.tx_gas_limit(),
             tx_nonce: cr.tx_nonce(),
             tx_gas_price: cr.tx_gas_price(),
@@ -131,6 +144,10 @@ impl ContextReader for ContractInput {
         self.block_base_fee
     }
 
+    fn block_blob_base_fee(&self) -> U256 {
+        self.block_blob_base_fee
+    }
+
     fn tx_gas_limit(&self) -> u64 {
         self.tx_gas_limit
     }
@@ -108,6 +108,12 @@ impl AccountManager for GuestAccountManager {
         );
     }
 
+    #[inline(always)]
+    fn remove_account(&self, address: Address) {
+        let account_address = address.into_word();
+        LowLevelSDK::remove_leaf(account_address.as_ptr());
+    }
+
     #[inline(always)]
     fn preimage_size(&self, hash: &[u8; 32]) -> u32 {
         LowLevelSDK::preimage_size(hash.as_ptr())
@@ -157,6 +163,12 @@ impl AccountManager for GuestAccountManager {
         true
     }
 
+    #[inline(always)]
+    fn remove_storage(&self, address: Address, slot: U256) {
+        let storage_key = calc_storage_key(&address, slot.as_le_slice().as_ptr());
+        LowLevelSDK::remove_leaf(storage_key.as_ptr());
+    }
+
     fn log(&self, address: Address, data: Bytes, topics: &[B256]) {
         LowLevelSDK::emit_log(
             address.as_ptr(),
@@ -333,6 +345,7 @@ impl ContextReader for GuestContextReader {
     impl_reader_func!(fn block_prevrandao() -> B256, BlockPrevrandao);
     impl_reader_func!(fn block_gas_limit() -> u64, BlockGasLimit);
     impl_reader_func!(fn block_base_fee() -> U256, BlockBaseFee);
+    impl_reader_func!(fn block_blob_base_fee() -> U256, BlockBlobBaseFee);
     // tx info
     impl_reader_func!(fn tx_gas_limit() -> u64, TxGasLimit);
     impl_reader_func!(fn tx_nonce() -> u64, TxNonce);
@@ -12,6 +12,7 @@ pub use evm::*;
 
 mod account;
 pub use account::*;
+pub mod alias;
 #[cfg(not(feature = "std"))]
 mod bindings;
 mod guest;
@@ -19,16 +20,30 @@ pub use guest::*;
 #[macro_use]
 pub mod macros;
 mod allocator;
-pub use allocator::{alloc_ptr, alloc_slice};
+pub use allocator::{
+    alloc_ptr,
+    alloc_slice,
+    heap_usage,
+    report_heap_usage,
+    BumpAllocator,
+    FreeListAllocator,
+    GuestAllocator,
+    HeapUsage,
+    DEFAULT_HEAP_SIZE,
+};
+mod memory;
+pub use memory::*;
 pub mod contracts;
 #[cfg(feature = "std")]
 mod runtime;
 #[cfg(not(feature = "std"))]
 mod rwasm;
+pub mod syscalls;
 pub mod types;
 pub mod utils;
 
 #[cfg(not(feature = "std"))]
+#[cfg(not(feature = "panic-revert"))]
 #[panic_handler]
 #[cfg(target_arch = "wasm32")]
 #[inline(always)]
@@ -45,10 +60,24 @@ fn panic(info: &core::panic::PanicInfo) -> ! {
     LowLevelSDK::exit(ExitCode::Panic.into_i32());
 }
 
+/// Reverts with the panic message and location encoded as `Error(string)`, so the reason is
+/// readable by EVM tooling
 #[cfg(not(feature = "std"))]
+#[cfg(feature = "panic-revert")]
+#[panic_handler]
+#[cfg(target_arch = "wasm32")]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    let panic_message = alloc::format!("{}", info).replace("\n", " ");
+    let output = utils::encode_revert_reason(&panic_message);
+    LowLevelSDK::write(output.as_ptr(), output.len() as u32);
+    LowLevelSDK::exit(ExitCode::Revert.into_i32());
+}
+
+#[cfg(not(feature = "std"))]
+#[cfg(not(feature = "custom-allocator"))]
 #[global_allocator]
 #[cfg(target_arch = "wasm32")]
-static ALLOCATOR: allocator::HeapBaseAllocator = allocator::HeapBaseAllocator {};
+static ALLOCATOR: GuestAllocator = GuestAllocator::new();
 
 pub use byteorder;
 pub use fluentbase_codec as codec;
@@ -6,12 +6,14 @@ macro_rules! basic_entrypoint {
         extern "C" fn deploy() {
             let typ = <$struct_typ as Default>::default();
             typ.deploy::<fluentbase_sdk::LowLevelSDK>();
+            fluentbase_sdk::report_heap_usage();
         }
         #[cfg(target_arch = "wasm32")]
         #[no_mangle]
         extern "C" fn main() {
             let typ = <$struct_typ as Default>::default();
             typ.main::<fluentbase_sdk::LowLevelSDK>();
+            fluentbase_sdk::report_heap_usage();
         }
     };
 }
@@ -0,0 +1,94 @@
+use crate::{alloc_slice, SharedAPI};
+use fluentbase_codec::{BufferDecoder, Encoder};
+
+/// Encodes the value straight into the (ptr, len) region of the guest memory and returns number
+/// of the written bytes, panics if the region is too small
+///
+/// # Safety
+///
+/// The region must be valid for writes and must not overlap with the value
+pub unsafe fn encode_to_ptr<T: Encoder<T>>(value: &T, ptr: *mut u8, len: usize) -> usize {
+    let buffer = core::slice::from_raw_parts_mut(ptr, len);
+    value.encode_to_slice(buffer, 0)
+}
+
+/// Decodes the value from the (ptr, len) region of the guest memory without copying it
+///
+/// # Safety
+///
+/// The region must be valid for reads
+pub unsafe fn decode_from_ptr<T: Encoder<T> + Default>(ptr: *const u8, len: usize) -> T {
+    let buffer = core::slice::from_raw_parts(ptr, len);
+    decode_from_slice(buffer)
+}
+
+fn decode_from_slice<T: Encoder<T> + Default>(buffer: &[u8]) -> T {
+    let mut decoder = BufferDecoder::new(buffer);
+    let mut result = T::default();
+    T::decode_body(&mut decoder, 0, &mut result);
+    result
+}
+
+/// Reads the call input into the guest memory and decodes it in place
+pub fn decode_input<SDK: SharedAPI, T: Encoder<T> + Default>() -> T {
+    let input_size = SDK::input_size();
+    let input = alloc_slice(input_size as usize);
+    SDK::read(input.as_mut_ptr(), input_size, 0);
+    decode_from_slice(input)
+}
+
+/// Reads the output of the last nested call into the guest memory and decodes it in place
+pub fn decode_output<SDK: SharedAPI, T: Encoder<T> + Default>() -> T {
+    let output_size = SDK::output_size();
+    let output = alloc_slice(output_size as usize);
+    SDK::read_output(output.as_mut_ptr(), 0, output_size);
+    decode_from_slice(output)
+}
+
+/// Encodes the value into the scratch buffer and writes it to the output, panics if the buffer
+/// is too small
+pub fn write_encoded<SDK: SharedAPI, T: Encoder<T>>(value: &T, buffer: &mut [u8]) {
+    let length = value.encode_to_slice(buffer, 0);
+    SDK::write(buffer.as_ptr(), length as u32);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{decode_from_ptr, decode_input, encode_to_ptr, write_encoded, LowLevelSDK};
+    use fluentbase_codec::Encoder;
+    use fluentbase_codec_derive::Codec;
+    use fluentbase_types::Bytes;
+
+    #[derive(Default, Debug, Clone, Codec, PartialEq)]
+    struct Message {
+        value: u64,
+        data: Bytes,
+    }
+
+    #[test]
+    fn test_encode_decode_ptr() {
+        let message = Message {
+            value: 100,
+            data: Bytes::from_static(b"Hello, World"),
+        };
+        let mut memory = [0u8; 64];
+        let length = unsafe { encode_to_ptr(&message, memory.as_mut_ptr(), memory.len()) };
+        assert_eq!(&memory[..length], message.encode_to_vec(0).as_slice());
+        let decoded_message: Message = unsafe { decode_from_ptr(memory.as_ptr(), length) };
+        assert_eq!(decoded_message, message);
+    }
+
+    #[test]
+    fn test_decode_input_write_encoded() {
+        let message = Message {
+            value: 7,
+            data: Bytes::from_static(b"input"),
+        };
+        LowLevelSDK::with_test_input(message.encode_to_vec(0));
+        let decoded_message: Message = decode_input::<LowLevelSDK, _>();
+        assert_eq!(decoded_message, message);
+        let mut buffer = [0u8; 64];
+        write_encoded::<LowLevelSDK, _>(&decoded_message, &mut buffer);
+        assert_eq!(LowLevelSDK::get_test_output(), message.encode_to_vec(0));
+    }
+}
@@ -15,6 +15,10 @@ use fluentbase_genesis::devnet::{
 };
 use fluentbase_runtime::{
     instruction::{
+        assert_eq_mem::SyscallAssertEqMem,
+        base_fee::SyscallBaseFee,
+        blob_base_fee::SyscallBlobBaseFee,
+        blob_hash::SyscallBlobHash,
         charge_fuel::SyscallChargeFuel,
         checkpoint::SyscallCheckpoint,
         commit::SyscallCommit,
@@ -24,9 +28,20 @@ use fluentbase_runtime::{
         ecrecover::SyscallEcrecover,
         emit_log::SyscallEmitLog,
         exec::SyscallExec,
+        exec_address::SyscallExecAddress,
         exit::SyscallExit,
+        expect_fuel_le::SyscallExpectFuelLe,
+        expect_storage::SyscallExpectStorage,
         forward_output::SyscallForwardOutput,
+        fuel_consumed::SyscallFuelConsumed,
+        fuel_remaining::SyscallFuelRemaining,
+        gas_price::SyscallGasPrice,
         get_leaf::SyscallGetLeaf,
+        hash_final::SyscallHashFinal,
+        hash_init::SyscallHashInit,
+        hash_update::SyscallHashUpdate,
+        input_copy::SyscallInputCopy,
+        input_reveal::SyscallInputReveal,
         input_size::SyscallInputSize,
         keccak256::SyscallKeccak256,
         output_size::SyscallOutputSize,
@@ -37,11 +52,16 @@ use fluentbase_runtime::{
         read::SyscallRead,
         read_context::SyscallReadContext,
         read_output::SyscallReadOutput,
+        remove_leaf::SyscallRemoveLeaf,
+        return_data_copy::SyscallReturnDataCopy,
+        return_data_size::SyscallReturnDataSize,
         rollback::SyscallRollback,
         state::SyscallState,
+        storage_read_external::SyscallStorageReadExternal,
         update_leaf::SyscallUpdateLeaf,
         update_preimage::SyscallUpdatePreimage,
         write::SyscallWrite,
+        write_output_chunk::SyscallWriteOutputChunk,
     },
     types::InMemoryTrieDb,
     zktrie::ZkTrieStateDb,
@@ -53,12 +73,14 @@ use fluentbase_types::{
     Address,
     Bytes,
     ExitCode,
+    Fuel,
     JournalCheckpoint,
     SharedAPI,
     SovereignAPI,
     B256,
     KECCAK_EMPTY,
     POSEIDON_EMPTY,
+    U256,
 };
 use std::ptr;
 
@@ -93,6 +115,42 @@ where
     })
 }
 
+fn exec_impl(
+    bytecode_hash32_ptr: *const u8,
+    input_ptr: *const u8,
+    input_len: u32,
+    return_ptr: *mut u8,
+    return_len: u32,
+    fuel_ptr: *mut u32,
+    is_static: bool,
+) -> i32 {
+    with_context_mut(|ctx| {
+        let bytecode_hash32 = unsafe { &*ptr::slice_from_raw_parts(bytecode_hash32_ptr, 32) };
+        let input = unsafe { &*ptr::slice_from_raw_parts(input_ptr, input_len as usize) }.to_vec();
+        let fuel = unsafe { *fuel_ptr };
+        match SyscallExec::fn_exec(
+            ctx,
+            bytecode_hash32.try_into().unwrap(),
+            input,
+            return_len,
+            Fuel(fuel as u64),
+            is_static,
+        ) {
+            Ok(remaining_fuel) => {
+                if return_len > 0 {
+                    let return_data = ctx.return_data();
+                    unsafe { ptr::copy(return_data.as_ptr(), return_ptr, return_len as usize) }
+                }
+                unsafe {
+                    *fuel_ptr = remaining_fuel.saturating_u32();
+                }
+                0
+            }
+            Err(err) => err,
+        }
+    })
+}
+
 impl SharedAPI for LowLevelSDK {
     fn keccak256(data_ptr: *const u8, data_len: u32, output32_ptr: *mut u8) {
         let result = SyscallKeccak256::fn_impl(unsafe {
@@ -103,6 +161,22 @@ impl SharedAPI for LowLevelSDK {
         }
     }
 
+    fn hash_init() -> u32 {
+        with_context_mut(|ctx| SyscallHashInit::fn_impl(ctx).unwrap())
+    }
+
+    fn hash_update(handle: u32, data_ptr: *const u8, data_len: u32) {
+        let data = unsafe { &*ptr::slice_from_raw_parts(data_ptr, data_len as usize) };
+        with_context_mut(|ctx| SyscallHashUpdate::fn_impl(ctx, handle, data).unwrap())
+    }
+
+    fn hash_final(handle: u32, output32_ptr: *mut u8) {
+        let result = with_context_mut(|ctx| SyscallHashFinal::fn_impl(ctx, handle).unwrap());
+        unsafe {
+            ptr::copy(result.as_ptr(), output32_ptr, 32);
+        }
+    }
+
     fn poseidon(data_ptr: *const u8, data_len: u32, output32_ptr: *mut u8) {
         let result = SyscallPoseidon::fn_impl(unsafe {
             &*ptr::slice_from_raw_parts(data_ptr, data_len as usize)
@@ -145,6 +219,18 @@ impl SharedAPI for LowLevelSDK {
         target.copy_from_slice(&result);
     }
 
+    fn input_copy(target: *mut u8, offset: u32, length: u32) -> u32 {
+        let result = with_context(|ctx| {
+            SyscallInputCopy::fn_impl(ctx, offset, length)
+                .unwrap()
+                .to_vec()
+        });
+        if !result.is_empty() {
+            unsafe { ptr::copy(result.as_ptr(), target, result.len()) }
+        }
+        result.len() as u32
+    }
+
     fn input_size() -> u32 {
         with_context(|ctx| SyscallInputSize::fn_impl(ctx))
     }
@@ -154,6 +240,19 @@ impl SharedAPI for LowLevelSDK {
         with_context_mut(|ctx| SyscallWrite::fn_impl(ctx, value))
     }
 
+    fn input_reveal(section: u32, target: *mut u8, target_len: u32) -> u32 {
+        let target = unsafe { &mut *ptr::slice_from_raw_parts_mut(target, target_len as usize) };
+        let plaintext = with_context(|ctx| SyscallInputReveal::fn_impl(ctx, section)).unwrap();
+        let length = plaintext.len().min(target.len());
+        target[..length].copy_from_slice(&plaintext[..length]);
+        plaintext.len() as u32
+    }
+
+    fn write_output_chunk(offset: *const u8, length: u32) -> u32 {
+        let chunk = unsafe { &*ptr::slice_from_raw_parts(offset, length as usize) };
+        with_context_mut(|ctx| SyscallWriteOutputChunk::fn_impl(ctx, chunk)).unwrap()
+    }
+
     fn forward_output(offset: u32, len: u32) {
         with_context_mut(|ctx| SyscallForwardOutput::fn_impl(ctx, offset, len)).unwrap()
     }
@@ -172,6 +271,21 @@ impl SharedAPI for LowLevelSDK {
         unsafe { ptr::copy(result.as_ptr(), target, length as usize) }
     }
 
+    fn return_data_size() -> u32 {
+        with_context(|ctx| SyscallReturnDataSize::fn_impl(ctx))
+    }
+
+    fn return_data_copy(target: *mut u8, offset: u32, length: u32) {
+        let result = with_context(|ctx| {
+            SyscallReturnDataCopy::fn_impl(ctx, offset, length)
+                .unwrap()
+                .to_vec()
+        });
+        if length > 0 {
+            unsafe { ptr::copy(result.as_ptr(), target, length as usize) }
+        }
+    }
+
     fn state() -> u32 {
         with_context(|ctx| SyscallState::fn_impl(ctx))
     }
@@ -184,37 +298,93 @@ impl SharedAPI for LowLevelSDK {
         return_len: u32,
         fuel_ptr: *mut u32,
     ) -> i32 {
-        with_context_mut(|ctx| {
-            let bytecode_hash32 = unsafe { &*ptr::slice_from_raw_parts(bytecode_hash32_ptr, 32) };
-            let input =
-                unsafe { &*ptr::slice_from_raw_parts(input_ptr, input_len as usize) }.to_vec();
-            let fuel = unsafe { *fuel_ptr };
-            match SyscallExec::fn_impl(
-                ctx,
-                bytecode_hash32.try_into().unwrap(),
-                input,
+        exec_impl(
+            bytecode_hash32_ptr,
+            input_ptr,
+            input_len,
+            return_ptr,
+            return_len,
+            fuel_ptr,
+            false,
+        )
+    }
+
+    fn static_exec(
+        bytecode_hash32_ptr: *const u8,
+        input_ptr: *const u8,
+        input_len: u32,
+        return_ptr: *mut u8,
+        return_len: u32,
+        fuel_ptr: *mut u32,
+    ) -> i32 {
+        exec_impl(
+            bytecode_hash32_ptr,
+            input_ptr,
+            input_len,
+            return_ptr,
+            return_len,
+            fuel_ptr,
+            true,
+        )
+    }
+
+    fn exec_address(
+        address20_ptr: *const u8,
+        input_ptr: *const u8,
+        input_len: u32,
+        return_ptr: *mut u8,
+        return_len: u32,
+        fuel_ptr: *mut u32,
+    ) -> i32 {
+        let address =
+            Address::from_slice(unsafe { &*ptr::slice_from_raw_parts(address20_ptr, 20) });
+        match with_context(|ctx| SyscallExecAddress::resolve_code_hash(ctx, &address)) {
+            Some(bytecode_hash32) => exec_impl(
+                bytecode_hash32.as_ptr(),
+                input_ptr,
+                input_len,
+                return_ptr,
                 return_len,
-                fuel as u64,
-            ) {
-                Ok(remaining_fuel) => {
-                    if return_len > 0 {
-                        let return_data = ctx.return_data();
-                        unsafe { ptr::copy(return_data.as_ptr(), return_ptr, return_len as usize) }
-                    }
-                    unsafe {
-                        *fuel_ptr = remaining_fuel as u32;
-                    }
-                    0
-                }
-                Err(err) => err,
+                fuel_ptr,
+                false,
+            ),
+            None => {
+                // account has no code, so the call succeeds without consuming fuel
+                with_context_mut(|ctx| SyscallExecAddress::fn_empty_call(ctx, Fuel::ZERO));
+                ExitCode::Ok.into_i32()
             }
+        }
+    }
+
+    fn storage_read_external(
+        address20_ptr: *const u8,
+        slot32_ptr: *const u8,
+        output32_ptr: *mut u8,
+        with_proof: bool,
+    ) -> u32 {
+        let address =
+            Address::from_slice(unsafe { &*ptr::slice_from_raw_parts(address20_ptr, 20) });
+        let slot = U256::from_le_slice(unsafe { &*ptr::slice_from_raw_parts(slot32_ptr, 32) });
+        let (value, proof_len) = with_context_mut(|ctx| {
+            SyscallStorageReadExternal::fn_impl(ctx, &address, &slot, with_proof)
         })
+        .unwrap();
+        unsafe { ptr::copy(value.as_ptr(), output32_ptr, 32) }
+        proof_len
     }
 
     fn charge_fuel(delta: u64) -> u64 {
         with_context_mut(|ctx| SyscallChargeFuel::fn_impl(ctx, delta))
     }
 
+    fn fuel_remaining() -> u64 {
+        with_context(|ctx| SyscallFuelRemaining::fn_impl(ctx))
+    }
+
+    fn fuel_consumed() -> u64 {
+        with_context(|ctx| SyscallFuelConsumed::fn_impl(ctx))
+    }
+
     fn read_context(target_ptr: *mut u8, offset: u32, length: u32) {
         let context =
             with_context_mut(|ctx| SyscallReadContext::fn_impl(ctx, offset, length).unwrap());
@@ -222,6 +392,44 @@ impl SharedAPI for LowLevelSDK {
             ptr::copy(context.as_ptr(), target_ptr, length as usize);
         }
     }
+
+    fn blob_hash(index: u32, output32_ptr: *mut u8) {
+        let blob_hash = with_context(|ctx| SyscallBlobHash::fn_impl(ctx, index));
+        unsafe {
+            ptr::copy(blob_hash.as_ptr(), output32_ptr, 32);
+        }
+    }
+
+    fn blob_base_fee(output32_ptr: *mut u8) {
+        let blob_base_fee = with_context(|ctx| SyscallBlobBaseFee::fn_impl(ctx));
+        unsafe {
+            ptr::copy(blob_base_fee.as_le_slice().as_ptr(), output32_ptr, 32);
+        }
+    }
+
+    fn gas_price(output32_ptr: *mut u8) {
+        let gas_price = with_context(|ctx| SyscallGasPrice::fn_impl(ctx));
+        unsafe {
+            ptr::copy(gas_price.as_le_slice().as_ptr(), output32_ptr, 32);
+        }
+    }
+
+    fn base_fee(output32_ptr: *mut u8) {
+        let base_fee = with_context(|ctx| SyscallBaseFee::fn_impl(ctx));
+        unsafe {
+            ptr::copy(base_fee.as_le_slice().as_ptr(), output32_ptr, 32);
+        }
+    }
+
+    fn suspend(
+        _request_ptr: *const u8,
+        _request_len: u32,
+        _return_ptr: *mut u8,
+        _return_len: u32,
+    ) -> i32 {
+        // native execution has no embedder to answer the request
+        ExitCode::NotSupportedCall.into_i32()
+    }
 }
 
 impl SovereignAPI for LowLevelSDK {
@@ -249,7 +457,7 @@ impl SovereignAPI for LowLevelSDK {
                 input,
                 context,
                 return_len,
-                fuel as u64,
+                Fuel(fuel as u64),
                 state,
             ) {
                 Ok(remaining_fuel) => {
@@ -258,7 +466,7 @@ impl SovereignAPI for LowLevelSDK {
                         unsafe { ptr::copy(return_data.as_ptr(), return_ptr, return_len as usize) }
                     }
                     unsafe {
-                        *fuel_ptr = remaining_fuel as u32;
+                        *fuel_ptr = remaining_fuel.saturating_u32();
                     }
                     0
                 }
@@ -288,7 +496,18 @@ impl SovereignAPI for LowLevelSDK {
         let values =
             unsafe { &*ptr::slice_from_raw_parts(vals32_ptr, vals32_len as usize / 32) }.to_vec();
         with_context_mut(|ctx| {
-            SyscallUpdateLeaf::fn_impl(ctx, key, flags, values.clone()).unwrap()
+            if let Err(exit_code) = SyscallUpdateLeaf::fn_impl(ctx, key, flags, values.clone()) {
+                SyscallExit::fn_impl(ctx, exit_code.into_i32());
+            }
+        });
+    }
+
+    fn remove_leaf(key32_ptr: *const u8) {
+        let key = unsafe { &*ptr::slice_from_raw_parts(key32_ptr, 32) };
+        with_context_mut(|ctx| {
+            if let Err(exit_code) = SyscallRemoveLeaf::fn_impl(ctx, key) {
+                SyscallExit::fn_impl(ctx, exit_code.into_i32());
+            }
         });
     }
 
@@ -300,7 +519,15 @@ impl SovereignAPI for LowLevelSDK {
     ) -> bool {
         let key = unsafe { &*ptr::slice_from_raw_parts(key32_ptr, 32) };
         let preimage = unsafe { &*ptr::slice_from_raw_parts(preimage_ptr, preimage_len as usize) };
-        with_context_mut(|ctx| SyscallUpdatePreimage::fn_impl(ctx, key, field, preimage).unwrap())
+        with_context_mut(
+            |ctx| match SyscallUpdatePreimage::fn_impl(ctx, key, field, preimage) {
+                Ok(result) => result,
+                Err(exit_code) => {
+                    SyscallExit::fn_impl(ctx, exit_code.into_i32());
+                    false
+                }
+            },
+        )
     }
 
     fn compute_root(output32_ptr: *mut u8) {
@@ -318,28 +545,41 @@ impl SovereignAPI for LowLevelSDK {
         with_context_mut(|ctx| {
             let key = unsafe { &*ptr::slice_from_raw_parts(address20_ptr, 20) };
             let topics =
-                unsafe { &*ptr::slice_from_raw_parts(topics32s_ptr, topics32s_len as usize) }
+                unsafe { &*ptr::slice_from_raw_parts(topics32s_ptr, topics32s_len as usize / 32) }
                     .iter()
                     .map(|v| B256::new(*v))
                     .collect::<Vec<_>>();
             let data = unsafe { &*ptr::slice_from_raw_parts(data_ptr, data_len as usize) };
-            SyscallEmitLog::fn_impl(
+            let result = SyscallEmitLog::fn_impl(
                 ctx,
                 Address::from_slice(key),
                 topics,
                 Bytes::copy_from_slice(data),
-            )
+            );
+            // guest execution halts with the exit code, so record it instead of panicking
+            if let Err(exit_code) = result {
+                SyscallExit::fn_impl(ctx, exit_code.into_i32());
+            }
         });
     }
 
     fn commit(root32_ptr: *mut u8) {
-        let root = with_context_mut(|ctx| SyscallCommit::fn_impl(ctx).unwrap());
+        let root = with_context_mut(|ctx| match SyscallCommit::fn_impl(ctx) {
+            Ok(root) => root,
+            Err(exit_code) => {
+                SyscallExit::fn_impl(ctx, exit_code.into_i32());
+                [0u8; 32]
+            }
+        });
         unsafe { ptr::copy(root.as_ptr(), root32_ptr, 32) }
     }
 
     fn rollback(checkpoint: u64) {
         with_context_mut(|ctx| {
-            SyscallRollback::fn_impl(ctx, JournalCheckpoint::from_u64(checkpoint))
+            let checkpoint = JournalCheckpoint::from_u64(checkpoint);
+            if let Err(exit_code) = SyscallRollback::fn_impl(ctx, checkpoint) {
+                SyscallExit::fn_impl(ctx, exit_code.into_i32());
+            }
         });
     }
 
@@ -360,6 +600,34 @@ impl SovereignAPI for LowLevelSDK {
         let msg = unsafe { &*ptr::slice_from_raw_parts(msg_ptr, msg_len as usize) };
         SyscallDebugLog::fn_impl(msg)
     }
+
+    fn assert_eq_mem(actual_ptr: *const u8, expected_ptr: *const u8, len: u32) {
+        let actual = unsafe { &*ptr::slice_from_raw_parts(actual_ptr, len as usize) };
+        let expected = unsafe { &*ptr::slice_from_raw_parts(expected_ptr, len as usize) };
+        with_context_mut(|ctx| {
+            SyscallAssertEqMem::fn_impl(ctx, actual, expected)
+                .unwrap_or_else(|_| panic!("{}", ctx.assertion_failure().unwrap()))
+        })
+    }
+
+    fn expect_storage(address20_ptr: *const u8, slot32_ptr: *const u8, value32_ptr: *const u8) {
+        let address =
+            Address::from_slice(unsafe { &*ptr::slice_from_raw_parts(address20_ptr, 20) });
+        let slot = U256::from_le_slice(unsafe { &*ptr::slice_from_raw_parts(slot32_ptr, 32) });
+        let value = U256::from_le_slice(unsafe { &*ptr::slice_from_raw_parts(value32_ptr, 32) });
+        with_context_mut(|ctx| {
+            SyscallExpectStorage::fn_impl(ctx, &address, &slot, &value)
+                .unwrap_or_else(|_| panic!("{}", ctx.assertion_failure().unwrap()))
+        })
+    }
+
+    fn expect_fuel_le(max_fuel: u64) {
+        with_context_mut(|ctx| {
+            let fuel_consumed = SyscallFuelConsumed::fn_impl(ctx);
+            SyscallExpectFuelLe::fn_impl(ctx, fuel_consumed, max_fuel)
+                .unwrap_or_else(|_| panic!("{}", ctx.assertion_failure().unwrap()))
+        })
+    }
 }
 
 impl LowLevelSDK {
@@ -375,6 +643,22 @@ impl LowLevelSDK {
         });
     }
 
+    /// Makes the test context read-only, like a nested static call
+    pub fn with_test_static(is_static: bool) {
+        with_context_mut(|ctx| {
+            ctx.change_is_static(is_static);
+        });
+    }
+
+    /// Returns the exit code recorded by the failed syscall and resets it
+    pub fn get_test_exit_code() -> i32 {
+        with_context_mut(|ctx| {
+            let exit_code = ctx.exit_code();
+            SyscallExit::fn_impl(ctx, ExitCode::Ok.into_i32());
+            exit_code
+        })
+    }
+
     pub fn get_test_output() -> Vec<u8> {
         with_context_mut(|ctx| {
             let output = ctx.output().clone();
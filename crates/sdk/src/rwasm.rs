@@ -1,5 +1,9 @@
 use crate::{
     bindings::{
+        _assert_eq_mem,
+        _base_fee,
+        _blob_base_fee,
+        _blob_hash,
         _charge_fuel,
         _checkpoint,
         _commit,
@@ -9,9 +13,20 @@ use crate::{
         _ecrecover,
         _emit_log,
         _exec,
+        _exec_address,
         _exit,
+        _expect_fuel_le,
+        _expect_storage,
         _forward_output,
+        _fuel_consumed,
+        _fuel_remaining,
+        _gas_price,
         _get_leaf,
+        _hash_final,
+        _hash_init,
+        _hash_update,
+        _input_copy,
+        _input_reveal,
         _input_size,
         _keccak256,
         _output_size,
@@ -22,11 +37,18 @@ use crate::{
         _read,
         _read_context,
         _read_output,
+        _remove_leaf,
+        _return_data_copy,
+        _return_data_size,
         _rollback,
         _state,
+        _static_exec,
+        _storage_read_external,
+        _suspend,
         _update_leaf,
         _update_preimage,
         _write,
+        _write_output_chunk,
     },
     LowLevelSDK,
 };
@@ -38,6 +60,11 @@ impl SharedAPI for LowLevelSDK {
         unsafe { _read(target_ptr, offset, target_len) }
     }
 
+    #[inline(always)]
+    fn input_copy(target: *mut u8, offset: u32, length: u32) -> u32 {
+        unsafe { _input_copy(target, offset, length) }
+    }
+
     #[inline(always)]
     fn input_size() -> u32 {
         unsafe { _input_size() }
@@ -48,6 +75,16 @@ impl SharedAPI for LowLevelSDK {
         unsafe { _write(value_ptr, value_len) }
     }
 
+    #[inline(always)]
+    fn input_reveal(section: u32, target: *mut u8, target_len: u32) -> u32 {
+        unsafe { _input_reveal(section, target, target_len) }
+    }
+
+    #[inline(always)]
+    fn write_output_chunk(offset: *const u8, length: u32) -> u32 {
+        unsafe { _write_output_chunk(offset, length) }
+    }
+
     #[inline(always)]
     fn forward_output(offset: u32, len: u32) {
         unsafe { _forward_output(offset, len) }
@@ -68,6 +105,16 @@ impl SharedAPI for LowLevelSDK {
         unsafe { _read_output(target, offset, length) }
     }
 
+    #[inline(always)]
+    fn return_data_size() -> u32 {
+        unsafe { _return_data_size() }
+    }
+
+    #[inline(always)]
+    fn return_data_copy(target: *mut u8, offset: u32, length: u32) {
+        unsafe { _return_data_copy(target, offset, length) }
+    }
+
     #[inline(always)]
     fn state() -> u32 {
         unsafe { _state() }
@@ -94,21 +141,128 @@ impl SharedAPI for LowLevelSDK {
         }
     }
 
+    #[inline(always)]
+    fn static_exec(
+        code_hash32_ptr: *const u8,
+        input_ptr: *const u8,
+        input_len: u32,
+        return_ptr: *mut u8,
+        return_len: u32,
+        fuel_ptr: *mut u32,
+    ) -> i32 {
+        unsafe {
+            _static_exec(
+                code_hash32_ptr,
+                input_ptr,
+                input_len,
+                return_ptr,
+                return_len,
+                fuel_ptr,
+            )
+        }
+    }
+
+    #[inline(always)]
+    fn exec_address(
+        address20_ptr: *const u8,
+        input_ptr: *const u8,
+        input_len: u32,
+        return_ptr: *mut u8,
+        return_len: u32,
+        fuel_ptr: *mut u32,
+    ) -> i32 {
+        unsafe {
+            _exec_address(
+                address20_ptr,
+                input_ptr,
+                input_len,
+                return_ptr,
+                return_len,
+                fuel_ptr,
+            )
+        }
+    }
+
+    #[inline(always)]
+    fn storage_read_external(
+        address20_ptr: *const u8,
+        slot32_ptr: *const u8,
+        output32_ptr: *mut u8,
+        with_proof: bool,
+    ) -> u32 {
+        unsafe { _storage_read_external(address20_ptr, slot32_ptr, output32_ptr, with_proof) }
+    }
+
     #[inline(always)]
     fn charge_fuel(delta: u64) -> u64 {
         unsafe { _charge_fuel(delta) }
     }
 
+    #[inline(always)]
+    fn fuel_remaining() -> u64 {
+        unsafe { _fuel_remaining() }
+    }
+
+    #[inline(always)]
+    fn fuel_consumed() -> u64 {
+        unsafe { _fuel_consumed() }
+    }
+
     #[inline(always)]
     fn read_context(target_ptr: *mut u8, offset: u32, length: u32) {
         unsafe { _read_context(target_ptr, offset, length) }
     }
 
+    #[inline(always)]
+    fn blob_hash(index: u32, output32_ptr: *mut u8) {
+        unsafe { _blob_hash(index, output32_ptr) }
+    }
+
+    #[inline(always)]
+    fn blob_base_fee(output32_ptr: *mut u8) {
+        unsafe { _blob_base_fee(output32_ptr) }
+    }
+
+    #[inline(always)]
+    fn gas_price(output32_ptr: *mut u8) {
+        unsafe { _gas_price(output32_ptr) }
+    }
+
+    #[inline(always)]
+    fn base_fee(output32_ptr: *mut u8) {
+        unsafe { _base_fee(output32_ptr) }
+    }
+
+    #[inline(always)]
+    fn suspend(
+        request_ptr: *const u8,
+        request_len: u32,
+        return_ptr: *mut u8,
+        return_len: u32,
+    ) -> i32 {
+        unsafe { _suspend(request_ptr, request_len, return_ptr, return_len) }
+    }
+
     #[inline(always)]
     fn keccak256(data_ptr: *const u8, data_len: u32, output32_ptr: *mut u8) {
         unsafe { _keccak256(data_ptr, data_len, output32_ptr) }
     }
 
+    #[inline(always)]
+    fn hash_init() -> u32 {
+        unsafe { _hash_init() }
+    }
+
+    #[inline(always)]
+    fn hash_update(handle: u32, data_ptr: *const u8, data_len: u32) {
+        unsafe { _hash_update(handle, data_ptr, data_len) }
+    }
+
+    #[inline(always)]
+    fn hash_final(handle: u32, output32_ptr: *mut u8) {
+        unsafe { _hash_final(handle, output32_ptr) }
+    }
+
     #[inline(always)]
     fn poseidon(data_ptr: *const u8, data_len: u32, output32_ptr: *mut u8) {
         unsafe { _poseidon(data_ptr, data_len, output32_ptr) }
@@ -175,6 +329,11 @@ impl SovereignAPI for LowLevelSDK {
         }
     }
 
+    #[inline(always)]
+    fn remove_leaf(key32_ptr: *const u8) {
+        unsafe { _remove_leaf(key32_ptr) }
+    }
+
     #[inline(always)]
     fn update_preimage(
         key32_ptr: *const u8,
@@ -232,4 +391,19 @@ impl SovereignAPI for LowLevelSDK {
     fn debug_log(msg_ptr: *const u8, msg_len: u32) {
         unsafe { _debug_log(msg_ptr, msg_len) }
     }
+
+    #[inline(always)]
+    fn assert_eq_mem(actual_ptr: *const u8, expected_ptr: *const u8, len: u32) {
+        unsafe { _assert_eq_mem(actual_ptr, expected_ptr, len) }
+    }
+
+    #[inline(always)]
+    fn expect_storage(address20_ptr: *const u8, slot32_ptr: *const u8, value32_ptr: *const u8) {
+        unsafe { _expect_storage(address20_ptr, slot32_ptr, value32_ptr) }
+    }
+
+    #[inline(always)]
+    fn expect_fuel_le(max_fuel: u64) {
+        unsafe { _expect_fuel_le(max_fuel) }
+    }
 }
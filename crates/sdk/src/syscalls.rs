@@ -0,0 +1,600 @@
+//! Safe wrappers over the host functions. Every wrapper takes care of pointers and lengths
+//! (empty call buffers are passed as null pointers, lengths are in bytes) and converts exit codes
+//! into `Result`, so contracts don't have to touch raw FFI.
+use crate::{LowLevelSDK, SharedAPI, SovereignAPI};
+use alloc::{vec, vec::Vec};
+use core::ptr;
+use fluentbase_types::{Address, ExitCode, JournalCheckpoint, B256, F254, U256};
+
+#[inline(always)]
+fn input_ptr(input: &[u8]) -> *const u8 {
+    if input.is_empty() {
+        ptr::null()
+    } else {
+        input.as_ptr()
+    }
+}
+
+#[inline(always)]
+fn output_ptr(output: &mut [u8]) -> *mut u8 {
+    if output.is_empty() {
+        ptr::null_mut()
+    } else {
+        output.as_mut_ptr()
+    }
+}
+
+#[inline(always)]
+fn exit_code_result(exit_code: i32) -> Result<(), ExitCode> {
+    match ExitCode::from(exit_code) {
+        ExitCode::Ok => Ok(()),
+        err => Err(err),
+    }
+}
+
+#[inline(always)]
+pub fn keccak256(data: &[u8]) -> B256 {
+    let mut output = B256::ZERO;
+    LowLevelSDK::keccak256(data.as_ptr(), data.len() as u32, output.as_mut_ptr());
+    output
+}
+
+/// Keccak256 hasher that keeps the state on the host, so big data can be hashed chunk by chunk
+/// without buffering it in the memory. Fuel is charged per absorbed byte
+pub struct KeccakHasher {
+    handle: u32,
+}
+
+impl Default for KeccakHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeccakHasher {
+    /// Opens the hashing context, the execution halts with `HashContextError` if there are too
+    /// many contexts opened at the same time
+    pub fn new() -> Self {
+        Self {
+            handle: LowLevelSDK::hash_init(),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        LowLevelSDK::hash_update(self.handle, input_ptr(data), data.len() as u32);
+    }
+
+    pub fn finalize(self) -> B256 {
+        let mut output = B256::ZERO;
+        LowLevelSDK::hash_final(self.handle, output.as_mut_ptr());
+        // the context is already closed
+        core::mem::forget(self);
+        output
+    }
+}
+
+impl Drop for KeccakHasher {
+    fn drop(&mut self) {
+        // close the context, so its handle can be reused
+        let mut output = B256::ZERO;
+        LowLevelSDK::hash_final(self.handle, output.as_mut_ptr());
+    }
+}
+
+#[inline(always)]
+pub fn poseidon(data: &[u8]) -> F254 {
+    let mut output = F254::ZERO;
+    LowLevelSDK::poseidon(data.as_ptr(), data.len() as u32, output.as_mut_ptr());
+    output
+}
+
+/// Poseidon hash of two field elements with the domain
+#[inline(always)]
+pub fn poseidon_hash(fa: &[u8; 32], fb: &[u8; 32], fd: &[u8; 32]) -> F254 {
+    let mut output = F254::ZERO;
+    LowLevelSDK::poseidon_hash(fa.as_ptr(), fb.as_ptr(), fd.as_ptr(), output.as_mut_ptr());
+    output
+}
+
+/// Recovers uncompressed sec1 public key (65 bytes with the `0x04` prefix)
+#[inline(always)]
+pub fn ecrecover(digest: &B256, signature: &[u8; 64], rec_id: u8) -> [u8; 65] {
+    let mut output = [0u8; 65];
+    LowLevelSDK::ecrecover(
+        digest.as_ptr(),
+        signature.as_ptr(),
+        output.as_mut_ptr(),
+        rec_id,
+    );
+    output
+}
+
+#[inline(always)]
+pub fn input_size() -> u32 {
+    LowLevelSDK::input_size()
+}
+
+/// Reads the input starting from the offset into the buffer
+#[inline(always)]
+pub fn read_input(offset: u32, target: &mut [u8]) {
+    if target.is_empty() {
+        return;
+    }
+    LowLevelSDK::read(target.as_mut_ptr(), target.len() as u32, offset);
+}
+
+/// Copies the input starting from the offset into the buffer and returns the number of copied
+/// bytes, it's less than the buffer size if the input ends earlier. The execution halts with
+/// `InputOutOfBounds` if the offset is greater than the input size
+#[inline(always)]
+pub fn input_copy(offset: u32, target: &mut [u8]) -> usize {
+    LowLevelSDK::input_copy(output_ptr(target), offset, target.len() as u32) as usize
+}
+
+/// Reads the input in windows, so big inputs can be processed without copying the entire
+/// input into the memory
+#[derive(Default)]
+pub struct InputReader {
+    offset: u32,
+}
+
+impl InputReader {
+    pub fn new() -> Self {
+        Self::from_offset(0)
+    }
+
+    pub fn from_offset(offset: u32) -> Self {
+        Self { offset }
+    }
+
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Number of bytes that are not read yet
+    pub fn remaining(&self) -> u32 {
+        input_size().saturating_sub(self.offset)
+    }
+
+    /// Reads the next window into the buffer and returns its size, zero means that the input
+    /// is over
+    pub fn read(&mut self, target: &mut [u8]) -> usize {
+        if self.remaining() == 0 {
+            return 0;
+        }
+        let length = input_copy(self.offset, target);
+        self.offset += length as u32;
+        length
+    }
+}
+
+#[inline(always)]
+pub fn input() -> Vec<u8> {
+    let mut input = vec![0u8; input_size() as usize];
+    read_input(0, &mut input);
+    input
+}
+
+#[inline(always)]
+pub fn write(output: &[u8]) {
+    if output.is_empty() {
+        return;
+    }
+    LowLevelSDK::write(output.as_ptr(), output.len() as u32);
+}
+
+/// Plaintext of the sealed input section, the execution halts with `CapabilityDenied` if the
+/// contract isn't authorized to reveal it
+pub fn input_reveal(section: u32) -> Vec<u8> {
+    let mut plaintext = vec![0u8; 256];
+    let plaintext_len =
+        LowLevelSDK::input_reveal(section, plaintext.as_mut_ptr(), plaintext.len() as u32);
+    if plaintext_len as usize > plaintext.len() {
+        plaintext.resize(plaintext_len as usize, 0);
+        LowLevelSDK::input_reveal(section, plaintext.as_mut_ptr(), plaintext_len);
+    }
+    plaintext.truncate(plaintext_len as usize);
+    plaintext
+}
+
+/// Streams the chunk to the host output sink and returns the number of accepted bytes, it's less
+/// than the chunk size if the sink applies back-pressure. The execution halts with
+/// `OutputOverflow` if the streamed output exceeds the limit of the host
+#[inline(always)]
+pub fn write_output_chunk(chunk: &[u8]) -> usize {
+    LowLevelSDK::write_output_chunk(input_ptr(chunk), chunk.len() as u32) as usize
+}
+
+/// Streams all the data to the host output sink, chunks rejected by the back-pressure are
+/// retried until the sink accepts them
+pub fn write_output_all(mut data: &[u8]) {
+    while !data.is_empty() {
+        let accepted = write_output_chunk(data);
+        data = &data[accepted..];
+    }
+}
+
+#[inline(always)]
+pub fn exit(exit_code: ExitCode) -> ! {
+    LowLevelSDK::exit(exit_code.into_i32())
+}
+
+/// Size of the output of the last nested call
+#[inline(always)]
+pub fn output_size() -> u32 {
+    LowLevelSDK::output_size()
+}
+
+/// Reads the output of the last nested call starting from the offset into the buffer
+#[inline(always)]
+pub fn read_output(offset: u32, target: &mut [u8]) {
+    if target.is_empty() {
+        return;
+    }
+    LowLevelSDK::read_output(target.as_mut_ptr(), offset, target.len() as u32);
+}
+
+/// Size of the return data of the last nested call
+#[inline(always)]
+pub fn return_data_size() -> u32 {
+    LowLevelSDK::return_data_size()
+}
+
+/// Copies the return data of the last nested call starting from the offset into the buffer,
+/// the execution halts with `ReturnDataOutOfBounds` if the buffer doesn't fit into the return
+/// data (like EVM `RETURNDATACOPY`)
+#[inline(always)]
+pub fn return_data_copy(offset: u32, target: &mut [u8]) {
+    LowLevelSDK::return_data_copy(output_ptr(target), offset, target.len() as u32);
+}
+
+/// Output of the last nested call
+#[inline(always)]
+pub fn return_data() -> Vec<u8> {
+    let mut output = vec![0u8; return_data_size() as usize];
+    return_data_copy(0, &mut output);
+    output
+}
+
+/// Appends the part of the last nested call output to the output of the current call
+#[inline(always)]
+pub fn forward_output(offset: u32, len: u32) {
+    LowLevelSDK::forward_output(offset, len)
+}
+
+#[inline(always)]
+pub fn state() -> u32 {
+    LowLevelSDK::state()
+}
+
+#[inline(always)]
+pub fn charge_fuel(delta: u64) -> u64 {
+    LowLevelSDK::charge_fuel(delta)
+}
+
+#[inline(always)]
+pub fn fuel_remaining() -> u64 {
+    LowLevelSDK::fuel_remaining()
+}
+
+#[inline(always)]
+pub fn fuel_consumed() -> u64 {
+    LowLevelSDK::fuel_consumed()
+}
+
+/// Reads the execution context starting from the offset into the buffer
+#[inline(always)]
+pub fn read_context(offset: u32, target: &mut [u8]) {
+    if target.is_empty() {
+        return;
+    }
+    LowLevelSDK::read_context(target.as_mut_ptr(), offset, target.len() as u32);
+}
+
+/// EIP-4844 versioned hash of the transaction blob, zero hash if index is out of bounds
+#[inline(always)]
+pub fn blob_hash(index: u32) -> B256 {
+    let mut output = B256::ZERO;
+    LowLevelSDK::blob_hash(index, output.as_mut_ptr());
+    output
+}
+
+#[inline(always)]
+pub fn blob_base_fee() -> U256 {
+    let mut output = [0u8; 32];
+    LowLevelSDK::blob_base_fee(output.as_mut_ptr());
+    U256::from_le_bytes(output)
+}
+
+#[inline(always)]
+pub fn gas_price() -> U256 {
+    let mut output = [0u8; 32];
+    LowLevelSDK::gas_price(output.as_mut_ptr());
+    U256::from_le_bytes(output)
+}
+
+#[inline(always)]
+pub fn base_fee() -> U256 {
+    let mut output = [0u8; 32];
+    LowLevelSDK::base_fee(output.as_mut_ptr());
+    U256::from_le_bytes(output)
+}
+
+/// Executes the nested call of the bytecode with the poseidon hash, the output is copied into
+/// the buffer (the rest is available through [`read_output`]), fuel is updated with the
+/// remaining fuel
+#[inline(always)]
+pub fn exec(
+    code_hash: &F254,
+    input: &[u8],
+    output: &mut [u8],
+    fuel: &mut u32,
+) -> Result<(), ExitCode> {
+    exit_code_result(LowLevelSDK::exec(
+        code_hash.as_ptr(),
+        input_ptr(input),
+        input.len() as u32,
+        output_ptr(output),
+        output.len() as u32,
+        fuel as *mut u32,
+    ))
+}
+
+/// The same as [`exec`], but the nested call can't change the state
+#[inline(always)]
+pub fn static_exec(
+    code_hash: &F254,
+    input: &[u8],
+    output: &mut [u8],
+    fuel: &mut u32,
+) -> Result<(), ExitCode> {
+    exit_code_result(LowLevelSDK::static_exec(
+        code_hash.as_ptr(),
+        input_ptr(input),
+        input.len() as u32,
+        output_ptr(output),
+        output.len() as u32,
+        fuel as *mut u32,
+    ))
+}
+
+/// The same as [`exec`], but the callee is addressed by the account
+#[inline(always)]
+pub fn exec_address(
+    address: &Address,
+    input: &[u8],
+    output: &mut [u8],
+    fuel: &mut u32,
+) -> Result<(), ExitCode> {
+    exit_code_result(LowLevelSDK::exec_address(
+        address.as_ptr(),
+        input_ptr(input),
+        input.len() as u32,
+        output_ptr(output),
+        output.len() as u32,
+        fuel as *mut u32,
+    ))
+}
+
+/// Reads the storage slot of another contract, allowed in static and shared executions
+#[inline(always)]
+pub fn storage_read_external(address: &Address, slot: &U256) -> U256 {
+    let mut value = U256::ZERO;
+    LowLevelSDK::storage_read_external(
+        address.as_ptr(),
+        slot.as_le_slice().as_ptr(),
+        unsafe { value.as_le_slice_mut().as_mut_ptr() },
+        false,
+    );
+    value
+}
+
+/// The same as [`storage_read_external`], but the committed value is read and returned with
+/// the encoded Merkle proof of the slot against the last committed state root
+#[inline(always)]
+pub fn storage_read_external_with_proof(address: &Address, slot: &U256) -> (U256, Vec<u8>) {
+    let mut value = U256::ZERO;
+    let proof_len = LowLevelSDK::storage_read_external(
+        address.as_ptr(),
+        slot.as_le_slice().as_ptr(),
+        unsafe { value.as_le_slice_mut().as_mut_ptr() },
+        true,
+    );
+    let mut proof = vec![0u8; proof_len as usize];
+    return_data_copy(0, &mut proof);
+    (value, proof)
+}
+
+/// Pauses the execution until the embedder answers the request, the response is copied into the
+/// buffer (the whole response is available through [`read_output`])
+#[inline(always)]
+pub fn suspend(request: &[u8], output: &mut [u8]) -> Result<(), ExitCode> {
+    exit_code_result(LowLevelSDK::suspend(
+        input_ptr(request),
+        request.len() as u32,
+        output_ptr(output),
+        output.len() as u32,
+    ))
+}
+
+/// Executes the nested call with the context (sovereign apps only)
+#[inline(always)]
+pub fn context_call(
+    code_hash: &F254,
+    input: &[u8],
+    context: &[u8],
+    output: &mut [u8],
+    fuel: &mut u32,
+    state: u32,
+) -> Result<(), ExitCode> {
+    exit_code_result(LowLevelSDK::context_call(
+        code_hash.as_ptr(),
+        input_ptr(input),
+        input.len() as u32,
+        input_ptr(context),
+        context.len() as u32,
+        output_ptr(output),
+        output.len() as u32,
+        fuel as *mut u32,
+        state,
+    ))
+}
+
+#[inline(always)]
+pub fn checkpoint() -> JournalCheckpoint {
+    JournalCheckpoint::from_u64(LowLevelSDK::checkpoint())
+}
+
+#[inline(always)]
+pub fn commit() -> [u8; 32] {
+    let mut root = [0u8; 32];
+    LowLevelSDK::commit(root.as_mut_ptr());
+    root
+}
+
+#[inline(always)]
+pub fn rollback(checkpoint: JournalCheckpoint) {
+    LowLevelSDK::rollback(checkpoint.to_u64())
+}
+
+/// Reads the field of the leaf, returns the value and the cold access flag. Value of the
+/// absent leaf (or field) is zero.
+#[inline(always)]
+pub fn get_leaf(key: &[u8; 32], field: u32, committed: bool) -> ([u8; 32], bool) {
+    let mut output = [0u8; 32];
+    let is_cold = LowLevelSDK::get_leaf(key.as_ptr(), field, output.as_mut_ptr(), committed);
+    (output, is_cold)
+}
+
+#[inline(always)]
+pub fn update_leaf(key: &[u8; 32], flags: u32, values: &[[u8; 32]]) {
+    LowLevelSDK::update_leaf(
+        key.as_ptr(),
+        flags,
+        values.as_ptr(),
+        (values.len() * 32) as u32,
+    );
+}
+
+#[inline(always)]
+pub fn remove_leaf(key: &[u8; 32]) {
+    LowLevelSDK::remove_leaf(key.as_ptr());
+}
+
+#[inline(always)]
+pub fn update_preimage(key: &[u8; 32], field: u32, preimage: &[u8]) -> Result<(), ExitCode> {
+    if LowLevelSDK::update_preimage(
+        key.as_ptr(),
+        field,
+        preimage.as_ptr(),
+        preimage.len() as u32,
+    ) {
+        Ok(())
+    } else {
+        Err(ExitCode::PersistentStorageError)
+    }
+}
+
+#[inline(always)]
+pub fn compute_root() -> [u8; 32] {
+    let mut root = [0u8; 32];
+    LowLevelSDK::compute_root(root.as_mut_ptr());
+    root
+}
+
+#[inline(always)]
+pub fn emit_log(address: &Address, topics: &[B256], data: &[u8]) {
+    LowLevelSDK::emit_log(
+        address.as_ptr(),
+        // B256 has transparent repr
+        topics.as_ptr() as *const [u8; 32],
+        (topics.len() * 32) as u32,
+        data.as_ptr(),
+        data.len() as u32,
+    );
+}
+
+/// Preimage of the hash, empty if the preimage is unknown
+#[inline(always)]
+pub fn preimage(hash: &[u8; 32]) -> Vec<u8> {
+    let size = LowLevelSDK::preimage_size(hash.as_ptr());
+    let mut preimage = vec![0u8; size as usize];
+    if size > 0 {
+        LowLevelSDK::preimage_copy(hash.as_ptr(), preimage.as_mut_ptr());
+    }
+    preimage
+}
+
+#[inline(always)]
+pub fn debug_log(message: &str) {
+    LowLevelSDK::debug_log(message.as_ptr(), message.len() as u32);
+}
+
+/// Fails the execution with `AssertionFailed` if the buffers aren't equal, the execution result
+/// contains both buffers and the first mismatched byte (native builds panic with the message).
+/// Buffers of different lengths panic in the guest.
+#[inline(always)]
+pub fn assert_eq_mem(actual: &[u8], expected: &[u8]) {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "buffers have different lengths"
+    );
+    LowLevelSDK::assert_eq_mem(actual.as_ptr(), expected.as_ptr(), actual.len() as u32);
+}
+
+/// Fails the execution with `AssertionFailed` if the storage slot of the contract isn't equal
+/// to the value, uncommitted changes are visible
+#[inline(always)]
+pub fn expect_storage(address: &Address, slot: &U256, value: &U256) {
+    LowLevelSDK::expect_storage(
+        address.as_ptr(),
+        slot.as_le_slice().as_ptr(),
+        value.as_le_slice().as_ptr(),
+    );
+}
+
+/// Fails the execution with `AssertionFailed` if the execution has consumed more fuel than
+/// the limit
+#[inline(always)]
+pub fn expect_fuel_le(max_fuel: u64) {
+    LowLevelSDK::expect_fuel_le(max_fuel);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fluentbase_types::KECCAK_EMPTY;
+
+    #[test]
+    fn test_typed_syscalls() {
+        assert_eq!(keccak256(&[]), KECCAK_EMPTY);
+        let key = [1u8; 32];
+        assert_eq!(get_leaf(&key, 0, false), ([0u8; 32], true));
+        update_leaf(&key, 0, &[[2u8; 32], [3u8; 32]]);
+        assert_eq!(get_leaf(&key, 1, false).0, [3u8; 32]);
+        emit_log(&Address::ZERO, &[B256::ZERO, B256::ZERO], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_static_context_write_protection() {
+        let key = [1u8; 32];
+        let checkpoint = checkpoint();
+        LowLevelSDK::with_test_static(true);
+        let write_protection = ExitCode::WriteProtection.into_i32();
+        // failed syscalls record the exit code instead of panicking
+        update_leaf(&key, 0, &[[2u8; 32]]);
+        assert_eq!(LowLevelSDK::get_test_exit_code(), write_protection);
+        remove_leaf(&key);
+        assert_eq!(LowLevelSDK::get_test_exit_code(), write_protection);
+        assert!(update_preimage(&key, 0, &[1, 2, 3]).is_err());
+        assert_eq!(LowLevelSDK::get_test_exit_code(), write_protection);
+        assert_eq!(commit(), [0u8; 32]);
+        assert_eq!(LowLevelSDK::get_test_exit_code(), write_protection);
+        rollback(checkpoint);
+        assert_eq!(LowLevelSDK::get_test_exit_code(), write_protection);
+        // nothing was written
+        assert_eq!(get_leaf(&key, 0, false), ([0u8; 32], true));
+        LowLevelSDK::with_test_static(false);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{LowLevelSDK, SharedAPI};
+use alloc::{vec, vec::Vec};
 use fluentbase_types::{b256, Address, Bytes32, B256, U256};
 use revm_primitives::alloy_primitives::private::alloy_rlp::{
     Encodable,
@@ -81,6 +82,21 @@ pub fn calc_create2_address(deployer: &Address, salt: &U256, init_code_hash: &B2
     Address::from_word(B256::from(bytes32))
 }
 
+/// Selector of the solidity `Error(string)`
+pub const REVERT_REASON_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Encodes the message as a solidity `Error(string)` revert payload, the same as `revert(message)`
+/// produces: `selector || offset(32) || len(32) || message padded to 32 bytes`
+pub fn encode_revert_reason(message: &str) -> Vec<u8> {
+    let padded_len = (message.len() + 31) / 32 * 32;
+    let mut output = vec![0u8; 4 + 32 + 32 + padded_len];
+    output[0..4].copy_from_slice(&REVERT_REASON_SELECTOR);
+    output[35] = 0x20;
+    output[36..68].copy_from_slice(&U256::from(message.len()).to_be_bytes::<32>());
+    output[68..68 + message.len()].copy_from_slice(message.as_bytes());
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -130,4 +146,18 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_encode_revert_reason() {
+        assert_eq!(
+            encode_revert_reason("Not enough Ether provided."),
+            hex!(
+                "08c379a0"
+                "0000000000000000000000000000000000000000000000000000000000000020"
+                "000000000000000000000000000000000000000000000000000000000000001a"
+                "4e6f7420656e6f7567682045746865722070726f76696465642e000000000000"
+            )
+        );
+        assert_eq!(encode_revert_reason("").len(), 68);
+    }
 }
@@ -9,6 +9,22 @@ pub const PRECOMPILE_WASM_LOADER: Address = address!("00000000000000000000000000
 pub const PRECOMPILE_SVM: Address = address!("0000000000000000000000000000000000005230");
 pub const PRECOMPILE_BLENDED: Address = address!("0000000000000000000000000000000000005240");
 
+/// Storage-only account with links between WASM contracts and their EVM-visible aliases
+pub const ADDRESS_ALIAS_REGISTRY: Address = address!("0000000000000000000000000000000000005250");
+
+/// Addresses reserved for system contracts (interpreters, deployers and blended runtime shims),
+/// only these accounts are installed or upgraded as system contracts
+pub const SYSTEM_CONTRACT_ADDRESSES: [Address; 8] = [
+    PRECOMPILE_EVM,
+    PRECOMPILE_EVM_DEPLOYER,
+    PRECOMPILE_EVM_LOADER,
+    PRECOMPILE_WASM,
+    PRECOMPILE_WASM_DEPLOYER,
+    PRECOMPILE_WASM_LOADER,
+    PRECOMPILE_SVM,
+    PRECOMPILE_BLENDED,
+];
+
 pub const PRECOMPILE_SECP256K1_ECRECOVER: Address =
     address!("0000000000000000000000000000000000000001");
 pub const PRECOMPILE_SHA256: Address = address!("0000000000000000000000000000000000000002");
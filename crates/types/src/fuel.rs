@@ -0,0 +1,236 @@
+use alloc::vec::Vec;
+use core::{
+    fmt,
+    ops::{Add, AddAssign, Sub, SubAssign},
+};
+
+macro_rules! impl_resource_unit {
+    ($name:ident) => {
+        impl $name {
+            pub const ZERO: Self = Self(0);
+            pub const MAX: Self = Self(u64::MAX);
+
+            pub const fn new(value: u64) -> Self {
+                Self(value)
+            }
+
+            pub const fn get(self) -> u64 {
+                self.0
+            }
+
+            pub const fn is_zero(self) -> bool {
+                self.0 == 0
+            }
+
+            pub const fn saturating_add(self, rhs: Self) -> Self {
+                Self(self.0.saturating_add(rhs.0))
+            }
+
+            pub const fn saturating_sub(self, rhs: Self) -> Self {
+                Self(self.0.saturating_sub(rhs.0))
+            }
+
+            pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+                match self.0.checked_sub(rhs.0) {
+                    Some(value) => Some(Self(value)),
+                    None => None,
+                }
+            }
+
+            /// Amount for 32-bit ABI slots (like the fuel pointer of nested calls), larger
+            /// amounts saturate at `u32::MAX`
+            pub const fn saturating_u32(self) -> u32 {
+                if self.0 > u32::MAX as u64 {
+                    u32::MAX
+                } else {
+                    self.0 as u32
+                }
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(value: u64) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: Self) {
+                self.0 += rhs.0;
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) {
+                self.0 -= rhs.0;
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+}
+
+/// Amount of rWASM fuel, the unit consumed by the runtime while executing instructions and
+/// syscalls
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fuel(pub u64);
+
+impl_resource_unit!(Fuel);
+
+/// Amount of EVM gas, the unit of transaction limits, receipts and fees
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Gas(pub u64);
+
+impl_resource_unit!(Gas);
+
+/// Exchange rate between gas and fuel. There is no implicit conversion between the units,
+/// every place where gas becomes fuel (or back) must go through the schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FuelSchedule {
+    fuel_per_gas: u64,
+}
+
+impl Default for FuelSchedule {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl FuelSchedule {
+    /// Gas limit of the call is forwarded as the fuel limit
+    pub const DEFAULT: Self = Self { fuel_per_gas: 1 };
+
+    pub const fn new(fuel_per_gas: u64) -> Self {
+        assert!(fuel_per_gas > 0, "fuel per gas must be positive");
+        Self { fuel_per_gas }
+    }
+
+    pub const fn fuel_per_gas(&self) -> u64 {
+        self.fuel_per_gas
+    }
+
+    /// Fuel available for the given gas, saturates at `Fuel::MAX`
+    pub const fn gas_to_fuel(&self, gas: Gas) -> Fuel {
+        Fuel(gas.0.saturating_mul(self.fuel_per_gas))
+    }
+
+    /// Gas charged for the consumed fuel, partially used gas unit is charged in full
+    pub const fn fuel_to_gas(&self, fuel: Fuel) -> Gas {
+        Gas(fuel.0.div_ceil(self.fuel_per_gas))
+    }
+}
+
+/// Fuel charged by the runtime on top of the executed instructions: for every byte of the
+/// top-level call input and for every byte of the deployed code
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntrinsicCost {
+    pub input_zero_byte: Fuel,
+    pub input_non_zero_byte: Fuel,
+    pub code_byte: Fuel,
+}
+
+impl IntrinsicCost {
+    /// Only executed instructions are charged
+    pub const ZERO: Self = Self::new(0, 0, 0);
+
+    /// Calldata (EIP-2028) and code deposit costs of Ethereum, assuming one fuel per gas
+    pub const ETHEREUM: Self = Self::new(4, 16, 200);
+
+    pub const fn new(input_zero_byte: u64, input_non_zero_byte: u64, code_byte: u64) -> Self {
+        Self {
+            input_zero_byte: Fuel(input_zero_byte),
+            input_non_zero_byte: Fuel(input_non_zero_byte),
+            code_byte: Fuel(code_byte),
+        }
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        self.input_zero_byte.is_zero()
+            && self.input_non_zero_byte.is_zero()
+            && self.code_byte.is_zero()
+    }
+
+    /// Fuel charged for the input of the top-level call, saturates at `Fuel::MAX`
+    pub fn input_fuel(&self, input: &[u8]) -> Fuel {
+        let zero_bytes = input.iter().filter(|byte| **byte == 0).count() as u64;
+        let non_zero_bytes = input.len() as u64 - zero_bytes;
+        Fuel(
+            zero_bytes
+                .saturating_mul(self.input_zero_byte.0)
+                .saturating_add(non_zero_bytes.saturating_mul(self.input_non_zero_byte.0)),
+        )
+    }
+
+    /// Fuel charged for the deployed code, saturates at `Fuel::MAX`
+    pub const fn code_fuel(&self, code_size: usize) -> Fuel {
+        Fuel((code_size as u64).saturating_mul(self.code_byte.0))
+    }
+}
+
+/// Intrinsic costs activated at block numbers (the same way as hardforks), blocks before the
+/// first activation aren't charged
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntrinsicCostSchedule {
+    activations: Vec<(u64, IntrinsicCost)>,
+}
+
+impl IntrinsicCostSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Activates the cost since the block, activation at the same block replaces the previous one
+    pub fn with_activation(mut self, block_number: u64, intrinsic_cost: IntrinsicCost) -> Self {
+        match self
+            .activations
+            .binary_search_by_key(&block_number, |(block_number, _)| *block_number)
+        {
+            Ok(index) => self.activations[index].1 = intrinsic_cost,
+            Err(index) => self
+                .activations
+                .insert(index, (block_number, intrinsic_cost)),
+        }
+        self
+    }
+
+    /// Cost active at the block
+    pub fn at(&self, block_number: u64) -> IntrinsicCost {
+        self.activations
+            .iter()
+            .rev()
+            .find(|(activation, _)| *activation <= block_number)
+            .map(|(_, intrinsic_cost)| *intrinsic_cost)
+            .unwrap_or(IntrinsicCost::ZERO)
+    }
+}
@@ -50,7 +50,7 @@ impl JournalEvent {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct JournalCheckpoint(pub u32, pub u32);
 
 impl From<(u32, u32)> for JournalCheckpoint {
@@ -82,6 +82,8 @@ impl JournalCheckpoint {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct JournalLog {
     pub address: Address,
     pub topics: Vec<B256>,
@@ -89,18 +91,39 @@ pub struct JournalLog {
 }
 
 pub trait IJournaledTrie {
+    /// Takes a checkpoint that can be rolled back to until it's released, committed or
+    /// discarded. Checkpoints are tracked by the journal, so taking one needs exclusive access
+    /// and blocks concurrent readers
     fn checkpoint(&self) -> JournalCheckpoint;
     fn get(&self, key: &[u8; 32], committed: bool) -> Option<(Vec<[u8; 32]>, u32, bool)>;
     fn update(&self, key: &[u8; 32], value: &Vec<[u8; 32]>, flags: u32);
     fn remove(&self, key: &[u8; 32]);
     fn compute_root(&self) -> [u8; 32];
     fn emit_log(&self, address: Address, topics: Vec<B256>, data: Bytes);
+    fn logs(&self) -> Vec<JournalLog>;
     fn commit(&self) -> Result<([u8; 32], Vec<JournalLog>), ExitCode>;
+    /// Commits changes only if the new state root is equal to the expected one, otherwise the
+    /// storage is left untouched, changes stay in the journal and `StateRootMismatch` is
+    /// returned
+    fn commit_expect(
+        &self,
+        expected_root: &[u8; 32],
+    ) -> Result<([u8; 32], Vec<JournalLog>), ExitCode>;
+    /// Rolls back all changes made after the checkpoint, the checkpoint can be any earlier one,
+    /// checkpoints taken after it are discarded
     fn rollback(&self, checkpoint: JournalCheckpoint);
+    /// Returns whether the checkpoint can be rolled back to (it's not committed or discarded)
+    fn is_valid_checkpoint(&self, checkpoint: JournalCheckpoint) -> bool;
+    /// Releases the checkpoint when the call that took it returns, changes are kept, but the
+    /// checkpoint and checkpoints taken after it can't be rolled back to anymore
+    fn release_checkpoint(&self, checkpoint: JournalCheckpoint);
     fn update_preimage(&self, key: &[u8; 32], field: u32, preimage: &[u8]) -> bool;
     fn preimage(&self, hash: &[u8; 32]) -> Vec<u8>;
     fn preimage_size(&self, hash: &[u8; 32]) -> u32;
     fn journal(&self) -> Vec<JournalEvent>;
+    /// Merkle proof of the key against the last committed root, uncommitted changes aren't
+    /// included
+    fn proof(&self, key: &[u8; 32]) -> Option<Vec<Vec<u8>>>;
 }
 
 #[derive(Default, Clone)]
@@ -132,14 +155,33 @@ impl IJournaledTrie for EmptyJournalTrie {
         todo!()
     }
 
+    fn logs(&self) -> Vec<JournalLog> {
+        todo!()
+    }
+
     fn commit(&self) -> Result<([u8; 32], Vec<JournalLog>), ExitCode> {
         todo!()
     }
 
+    fn commit_expect(
+        &self,
+        expected_root: &[u8; 32],
+    ) -> Result<([u8; 32], Vec<JournalLog>), ExitCode> {
+        todo!()
+    }
+
     fn rollback(&self, checkpoint: JournalCheckpoint) {
         todo!()
     }
 
+    fn is_valid_checkpoint(&self, checkpoint: JournalCheckpoint) -> bool {
+        todo!()
+    }
+
+    fn release_checkpoint(&self, checkpoint: JournalCheckpoint) {
+        todo!()
+    }
+
     fn update_preimage(&self, key: &[u8; 32], field: u32, preimage: &[u8]) -> bool {
         todo!()
     }
@@ -155,4 +197,8 @@ impl IJournaledTrie for EmptyJournalTrie {
     fn journal(&self) -> Vec<JournalEvent> {
         todo!()
     }
+
+    fn proof(&self, key: &[u8; 32]) -> Option<Vec<Vec<u8>>> {
+        todo!()
+    }
 }
@@ -5,17 +5,38 @@
 extern crate alloc;
 extern crate core;
 
+mod account;
 pub mod consts;
 pub mod contracts;
+mod fuel;
 mod journal;
 mod linker;
+mod metadata;
 mod sdk;
+mod system;
 mod types;
 
-pub use alloy_primitives::{address, b256, bloom, bytes, fixed_bytes, Address, Bytes, B256, U256};
+pub use account::*;
+pub use alloy_primitives::{
+    address,
+    b256,
+    bloom,
+    bytes,
+    fixed_bytes,
+    Address,
+    Bloom,
+    BloomInput,
+    Bytes,
+    Keccak256,
+    B256,
+    U256,
+};
+pub use fuel::*;
 pub use journal::*;
 pub use linker::*;
+pub use metadata::*;
 pub use sdk::*;
+pub use system::*;
 pub use types::*;
 
 pub const KECCAK_EMPTY: B256 =
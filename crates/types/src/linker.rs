@@ -9,25 +9,43 @@ macro_rules! import_func {
     };
 }
 
-const SHARED_IMPORT_LINKER: [(&'static str, &'static str, u32, u32); 20] = [
+const SHARED_IMPORT_LINKER: [(&'static str, &'static str, u32, u32); 41] = [
     import_func!("_keccak256", KECCAK256),
+    import_func!("_hash_init", HASH_INIT),
+    import_func!("_hash_update", HASH_UPDATE),
+    import_func!("_hash_final", HASH_FINAL),
     import_func!("_poseidon", KECCAK256),
     import_func!("_poseidon_hash", POSEIDON_HASH),
     import_func!("_ecrecover", ECRECOVER),
     import_func!("_exit", EXIT),
     import_func!("_write", WRITE),
+    import_func!("_write_output_chunk", WRITE_OUTPUT_CHUNK),
     import_func!("_input_size", INPUT_SIZE),
     import_func!("_read", READ),
+    import_func!("_input_copy", INPUT_COPY),
+    import_func!("_input_reveal", INPUT_REVEAL),
     import_func!("_output_size", OUTPUT_SIZE),
     import_func!("_read_output", READ_OUTPUT),
+    import_func!("_return_data_size", RETURN_DATA_SIZE),
+    import_func!("_return_data_copy", RETURN_DATA_COPY),
     import_func!("_forward_output", FORWARD_OUTPUT),
     import_func!("_state", STATE),
     import_func!("_exec", EXEC),
+    import_func!("_static_exec", STATIC_EXEC),
+    import_func!("_exec_address", EXEC_ADDRESS),
     // import_func!("_context_call", SYS_CONTEXT_CALL),
     import_func!("_charge_fuel", CHARGE_FUEL),
+    import_func!("_fuel_remaining", FUEL_REMAINING),
+    import_func!("_fuel_consumed", FUEL_CONSUMED),
+    import_func!("_blob_hash", BLOB_HASH),
+    import_func!("_blob_base_fee", BLOB_BASE_FEE),
+    import_func!("_gas_price", GAS_PRICE),
+    import_func!("_base_fee", BASE_FEE),
+    import_func!("_suspend", SUSPEND),
     // import_func!("_sys_read_context", SYS_CONTEXT),
     // import_func!("_checkpoint", JZKT_CHECKPOINT),
     import_func!("_get_leaf", GET_LEAF),
+    import_func!("_storage_read_external", STORAGE_READ_EXTERNAL),
     // import_func!("_update_leaf", JZKT_UPDATE),
     // import_func!("_update_preimage", JZKT_UPDATE_PREIMAGE),
     // import_func!("_remove", JZKT_REMOVE),
@@ -38,6 +56,9 @@ const SHARED_IMPORT_LINKER: [(&'static str, &'static str, u32, u32); 20] = [
     import_func!("_preimage_size", PREIMAGE_SIZE),
     import_func!("_preimage_copy", PREIMAGE_COPY),
     import_func!("_debug_log", DEBUG_LOG),
+    import_func!("_assert_eq_mem", ASSERT_EQ_MEM),
+    import_func!("_expect_storage", EXPECT_STORAGE),
+    import_func!("_expect_fuel_le", EXPECT_FUEL_LE),
 ];
 
 pub fn create_shared_import_linker<
@@ -46,27 +67,46 @@ pub fn create_shared_import_linker<
     F::from(SHARED_IMPORT_LINKER)
 }
 
-const SOVEREIGN_IMPORT_LINKER: [(&'static str, &'static str, u32, u32); 27] = [
+const SOVEREIGN_IMPORT_LINKER: [(&'static str, &'static str, u32, u32); 52] = [
     import_func!("_keccak256", KECCAK256),
+    import_func!("_hash_init", HASH_INIT),
+    import_func!("_hash_update", HASH_UPDATE),
+    import_func!("_hash_final", HASH_FINAL),
     import_func!("_poseidon", KECCAK256),
     import_func!("_poseidon_hash", POSEIDON_HASH),
     import_func!("_ecrecover", ECRECOVER),
     import_func!("_exit", EXIT),
     import_func!("_write", WRITE),
+    import_func!("_write_output_chunk", WRITE_OUTPUT_CHUNK),
     import_func!("_input_size", INPUT_SIZE),
     import_func!("_read", READ),
+    import_func!("_input_copy", INPUT_COPY),
+    import_func!("_input_reveal", INPUT_REVEAL),
     import_func!("_output_size", OUTPUT_SIZE),
     import_func!("_read_output", READ_OUTPUT),
+    import_func!("_return_data_size", RETURN_DATA_SIZE),
+    import_func!("_return_data_copy", RETURN_DATA_COPY),
     import_func!("_forward_output", FORWARD_OUTPUT),
     import_func!("_state", STATE),
     import_func!("_exec", EXEC),
+    import_func!("_static_exec", STATIC_EXEC),
+    import_func!("_exec_address", EXEC_ADDRESS),
     import_func!("_context_call", CONTEXT_CALL),
     import_func!("_charge_fuel", CHARGE_FUEL),
+    import_func!("_fuel_remaining", FUEL_REMAINING),
+    import_func!("_fuel_consumed", FUEL_CONSUMED),
+    import_func!("_blob_hash", BLOB_HASH),
+    import_func!("_blob_base_fee", BLOB_BASE_FEE),
+    import_func!("_gas_price", GAS_PRICE),
+    import_func!("_base_fee", BASE_FEE),
+    import_func!("_suspend", SUSPEND),
     import_func!("_read_context", READ_CONTEXT),
     import_func!("_checkpoint", CHECKPOINT),
     import_func!("_get_leaf", GET_LEAF),
+    import_func!("_storage_read_external", STORAGE_READ_EXTERNAL),
     import_func!("_update_leaf", UPDATE_LEAF),
     import_func!("_update_preimage", UPDATE_PREIMAGE),
+    import_func!("_remove_leaf", REMOVE_LEAF),
     import_func!("_compute_root", COMPUTE_ROOT),
     import_func!("_emit_log", EMIT_LOG),
     import_func!("_commit", COMMIT),
@@ -74,6 +114,12 @@ const SOVEREIGN_IMPORT_LINKER: [(&'static str, &'static str, u32, u32); 27] = [
     import_func!("_preimage_size", PREIMAGE_SIZE),
     import_func!("_preimage_copy", PREIMAGE_COPY),
     import_func!("_debug_log", DEBUG_LOG),
+    import_func!("_profile_enter", PROFILE_ENTER),
+    import_func!("_profile_exit", PROFILE_EXIT),
+    import_func!("_coverage_hit", COVERAGE_HIT),
+    import_func!("_assert_eq_mem", ASSERT_EQ_MEM),
+    import_func!("_expect_storage", EXPECT_STORAGE),
+    import_func!("_expect_fuel_le", EXPECT_FUEL_LE),
 ];
 
 pub fn create_sovereign_import_linker<
@@ -0,0 +1,169 @@
+use crate::{types::RWASM_SIG, B256};
+use alloc::{string::String, vec::Vec};
+use core::fmt::{Display, Formatter};
+
+/// Version of the host ABI (system functions and their semantics) implemented by the runtime,
+/// contracts that require a newer version are rejected before the instantiation
+pub const HOST_ABI_VERSION: u32 = 1;
+
+/// Version of the metadata section layout
+pub const METADATA_VERSION: u8 = 1;
+
+/// Magic that starts the metadata section:
+/// - 0xef 0x00 - EIP-3540 compatible prefix (the same as for rWASM binaries, so it can't start
+///   valid EVM code)
+/// - 0x4d 0x44 - 'MD'
+pub const METADATA_MAGIC: [u8; 4] = [0xef, 0x00, 0x4d, 0x44];
+
+/// Size of the fixed part of the payload: version, ABI hash, source hash and host ABI version
+const METADATA_FIXED_SIZE: usize = 1 + 32 + 32 + 4;
+
+/// Size of the header: magic and payload length
+const METADATA_HEADER_SIZE: usize = METADATA_MAGIC.len() + 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataError {
+    Malformed,
+    UnsupportedVersion(u8),
+    IncompatibleHostAbi { required: u32, supported: u32 },
+    Rejected(String),
+}
+
+impl Display for MetadataError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MetadataError::Malformed => write!(f, "malformed metadata section"),
+            MetadataError::UnsupportedVersion(version) => {
+                write!(f, "unsupported metadata version {}", version)
+            }
+            MetadataError::IncompatibleHostAbi {
+                required,
+                supported,
+            } => write!(
+                f,
+                "contract requires host ABI version {}, but {} is supported",
+                required, supported
+            ),
+            MetadataError::Rejected(message) => write!(f, "metadata is rejected: {}", message),
+        }
+    }
+}
+
+/// Optional metadata attached to the deployed rWASM bytecode, it's not a part of the executed
+/// code and is only used by the verification tooling and the runtime compatibility checks.
+///
+/// The section is stored in front of the code as `magic || payload_len || payload || code`,
+/// where `payload_len` is a little-endian u32 and the payload is:
+/// - version (u8)
+/// - ABI hash (32 bytes)
+/// - source hash (32 bytes)
+/// - required host ABI version (little-endian u32)
+/// - compiler version (the rest of the payload, UTF-8)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContractMetadata {
+    pub abi_hash: B256,
+    pub source_hash: B256,
+    pub compiler_version: String,
+    pub host_abi_version: u32,
+}
+
+impl ContractMetadata {
+    pub fn new(compiler_version: &str) -> Self {
+        Self {
+            compiler_version: compiler_version.into(),
+            host_abi_version: HOST_ABI_VERSION,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_abi_hash(mut self, abi_hash: B256) -> Self {
+        self.abi_hash = abi_hash;
+        self
+    }
+
+    pub fn with_source_hash(mut self, source_hash: B256) -> Self {
+        self.source_hash = source_hash;
+        self
+    }
+
+    pub fn with_host_abi_version(mut self, host_abi_version: u32) -> Self {
+        self.host_abi_version = host_abi_version;
+        self
+    }
+
+    /// Encodes the section including the header
+    pub fn encode(&self) -> Vec<u8> {
+        let payload_len = METADATA_FIXED_SIZE + self.compiler_version.len();
+        let mut result = Vec::with_capacity(METADATA_HEADER_SIZE + payload_len);
+        result.extend_from_slice(&METADATA_MAGIC);
+        result.extend_from_slice(&(payload_len as u32).to_le_bytes());
+        result.push(METADATA_VERSION);
+        result.extend_from_slice(self.abi_hash.as_slice());
+        result.extend_from_slice(self.source_hash.as_slice());
+        result.extend_from_slice(&self.host_abi_version.to_le_bytes());
+        result.extend_from_slice(self.compiler_version.as_bytes());
+        result
+    }
+
+    /// Returns bytecode with the metadata section in front of it
+    pub fn attach_to(&self, bytecode: &[u8]) -> Vec<u8> {
+        let mut result = self.encode();
+        result.extend_from_slice(bytecode);
+        result
+    }
+
+    fn decode_payload(payload: &[u8]) -> Result<Self, MetadataError> {
+        if payload.len() < METADATA_FIXED_SIZE {
+            return Err(MetadataError::Malformed);
+        }
+        if payload[0] != METADATA_VERSION {
+            return Err(MetadataError::UnsupportedVersion(payload[0]));
+        }
+        let compiler_version = core::str::from_utf8(&payload[METADATA_FIXED_SIZE..])
+            .map_err(|_| MetadataError::Malformed)?;
+        Ok(Self {
+            abi_hash: B256::from_slice(&payload[1..33]),
+            source_hash: B256::from_slice(&payload[33..65]),
+            host_abi_version: u32::from_le_bytes(payload[65..69].try_into().unwrap()),
+            compiler_version: compiler_version.into(),
+        })
+    }
+
+    /// Checks that the host implements the ABI version required by the contract
+    pub fn check_host_abi(&self, supported: u32) -> Result<(), MetadataError> {
+        if self.host_abi_version > supported {
+            return Err(MetadataError::IncompatibleHostAbi {
+                required: self.host_abi_version,
+                supported,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Splits bytecode into the executable code and the metadata section, bytecode that doesn't
+/// start with the metadata magic is returned as is. The payload length must fit into the
+/// bytecode, and the code after the section must be rWASM.
+pub fn split_metadata(bytecode: &[u8]) -> Result<(&[u8], Option<ContractMetadata>), MetadataError> {
+    if !bytecode.starts_with(&METADATA_MAGIC) {
+        return Ok((bytecode, None));
+    }
+    if bytecode.len() < METADATA_HEADER_SIZE {
+        return Err(MetadataError::Malformed);
+    }
+    let payload_len = u32::from_le_bytes(
+        bytecode[METADATA_MAGIC.len()..METADATA_HEADER_SIZE]
+            .try_into()
+            .unwrap(),
+    ) as usize;
+    let code_offset = METADATA_HEADER_SIZE
+        .checked_add(payload_len)
+        .filter(|code_offset| *code_offset <= bytecode.len())
+        .ok_or(MetadataError::Malformed)?;
+    let metadata = ContractMetadata::decode_payload(&bytecode[METADATA_HEADER_SIZE..code_offset])?;
+    let code = &bytecode[code_offset..];
+    if !code.starts_with(&RWASM_SIG) {
+        return Err(MetadataError::Malformed);
+    }
+    Ok((code, Some(metadata)))
+}
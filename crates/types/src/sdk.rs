@@ -1,5 +1,8 @@
 pub trait SharedAPI {
     fn keccak256(data_ptr: *const u8, data_len: u32, output32_ptr: *mut u8);
+    fn hash_init() -> u32;
+    fn hash_update(handle: u32, data_ptr: *const u8, data_len: u32);
+    fn hash_final(handle: u32, output32_ptr: *mut u8);
     fn poseidon(data_ptr: *const u8, data_len: u32, output32_ptr: *mut u8);
     fn poseidon_hash(
         fa32_ptr: *const u8,
@@ -10,15 +13,32 @@ pub trait SharedAPI {
     fn ecrecover(digest32_ptr: *const u8, sig65_ptr: *const u8, output65_ptr: *mut u8, rec_id: u8);
 
     fn read(target_ptr: *mut u8, target_len: u32, offset: u32);
+    fn input_copy(target: *mut u8, offset: u32, length: u32) -> u32;
     fn input_size() -> u32;
+    fn input_reveal(section: u32, target: *mut u8, target_len: u32) -> u32;
     fn write(value_ptr: *const u8, value_len: u32);
+    fn write_output_chunk(offset: *const u8, length: u32) -> u32;
     fn forward_output(offset: u32, len: u32);
     fn exit(exit_code: i32) -> !;
     fn output_size() -> u32;
     fn read_output(target: *mut u8, offset: u32, length: u32);
+    fn return_data_size() -> u32;
+    fn return_data_copy(target: *mut u8, offset: u32, length: u32);
     fn state() -> u32;
     fn charge_fuel(delta: u64) -> u64;
+    fn fuel_remaining() -> u64;
+    fn fuel_consumed() -> u64;
     fn read_context(target_ptr: *mut u8, offset: u32, length: u32);
+    fn blob_hash(index: u32, output32_ptr: *mut u8);
+    fn blob_base_fee(output32_ptr: *mut u8);
+    fn gas_price(output32_ptr: *mut u8);
+    fn base_fee(output32_ptr: *mut u8);
+    fn suspend(
+        request_ptr: *const u8,
+        request_len: u32,
+        return_ptr: *mut u8,
+        return_len: u32,
+    ) -> i32;
 
     fn exec(
         code_hash32_ptr: *const u8,
@@ -28,6 +48,28 @@ pub trait SharedAPI {
         return_len: u32,
         fuel_ptr: *mut u32,
     ) -> i32;
+    fn static_exec(
+        code_hash32_ptr: *const u8,
+        input_ptr: *const u8,
+        input_len: u32,
+        return_ptr: *mut u8,
+        return_len: u32,
+        fuel_ptr: *mut u32,
+    ) -> i32;
+    fn exec_address(
+        address20_ptr: *const u8,
+        input_ptr: *const u8,
+        input_len: u32,
+        return_ptr: *mut u8,
+        return_len: u32,
+        fuel_ptr: *mut u32,
+    ) -> i32;
+    fn storage_read_external(
+        address20_ptr: *const u8,
+        slot32_ptr: *const u8,
+        output32_ptr: *mut u8,
+        with_proof: bool,
+    ) -> u32;
 }
 
 pub trait SovereignAPI: SharedAPI {
@@ -46,6 +88,7 @@ pub trait SovereignAPI: SharedAPI {
     fn checkpoint() -> u64;
     fn get_leaf(key32_ptr: *const u8, field: u32, output32_ptr: *mut u8, committed: bool) -> bool;
     fn update_leaf(key32_ptr: *const u8, flags: u32, vals32_ptr: *const [u8; 32], vals32_len: u32);
+    fn remove_leaf(key32_ptr: *const u8);
     fn update_preimage(
         key32_ptr: *const u8,
         field: u32,
@@ -66,4 +109,9 @@ pub trait SovereignAPI: SharedAPI {
     fn preimage_copy(hash32_ptr: *const u8, preimage_ptr: *mut u8);
 
     fn debug_log(msg_ptr: *const u8, msg_len: u32);
+    /// Test assertions, a failure aborts the execution with `AssertionFailed` and the
+    /// diagnostics are returned in the execution result
+    fn assert_eq_mem(actual_ptr: *const u8, expected_ptr: *const u8, len: u32);
+    fn expect_storage(address20_ptr: *const u8, slot32_ptr: *const u8, value32_ptr: *const u8);
+    fn expect_fuel_le(max_fuel: u64);
 }
@@ -0,0 +1,12 @@
+use crate::{Address, F254};
+
+/// Read-only view of the system contract registry, the runtime resolves versions of system
+/// contracts through it while the registry itself is built by the node (genesis and upgrades)
+pub trait SystemContractView {
+    /// Returns rWASM code hash of the contract version that is active at the specified block
+    fn active_code_hash(&self, address: &Address, block_number: u64) -> Option<F254>;
+
+    fn is_system_contract(&self, address: &Address) -> bool {
+        self.active_code_hash(address, u64::MAX).is_some()
+    }
+}
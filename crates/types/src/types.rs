@@ -17,7 +17,10 @@ pub enum ExitCode {
     // warning: when adding new codes doesn't forget to add them to impls below
     #[default]
     Ok = 0,
+    // panic of the contract, output contains panic message
     Panic = -71,
+    // deliberate failure of the contract, output is preserved as revert data
+    Revert = -72,
     // fluentbase error codes
     ExecutionHalted = -1001,
     NotSupportedCall = -1003,
@@ -52,6 +55,19 @@ pub enum ExitCode {
     InvalidJump = -1032,
     NotActivatedEIP = -1033,
     ImmutableContext = -1034,
+    TooManyLogTopics = -1035,
+    InvalidCheckpoint = -1036,
+    StateRootMismatch = -1037,
+    CapabilityDenied = -1038,
+    ReturnDataOutOfBounds = -1039,
+    InputOutOfBounds = -1040,
+    InvariantViolation = -1041,
+    HashContextError = -1042,
+    IncompatibleMetadata = -1043,
+    InitCodeSizeLimit = -1044,
+    AssertionFailed = -1045,
+    DecryptionFailed = -1046,
+    MalformedLogTopics = -1047,
     // NotActivated = -1033,
     // ReturnContract = -1034,
     // ReturnContractInNotInitEOF = -1035,
@@ -109,12 +125,30 @@ impl ExitCode {
         self.into_i32() != Self::Ok.into_i32()
     }
 
-    /// Returns whether the result is a revert.
+    /// Returns whether the result is a revert (any failure, the state changes are rolled back).
     #[inline]
     pub const fn is_revert(self) -> bool {
         self.into_i32() != Self::Ok.into_i32()
     }
 
+    /// Returns whether the result is a deliberate revert of the contract (`Revert` exit code).
+    #[inline]
+    pub const fn is_deliberate_revert(self) -> bool {
+        self.into_i32() == Self::Revert.into_i32()
+    }
+
+    /// Returns whether the result is an unexpected failure (panic, trap or host error).
+    #[inline]
+    pub const fn is_panic(self) -> bool {
+        self.is_error() && !self.is_deliberate_revert()
+    }
+
+    /// Returns whether the result is a trap or host error, such results have no output.
+    #[inline]
+    pub const fn is_trap(self) -> bool {
+        self.is_panic() && self.into_i32() != Self::Panic.into_i32()
+    }
+
     pub const fn into_i32(self) -> i32 {
         self as i32
     }
@@ -178,6 +212,9 @@ pub enum SysFuncIdx {
     POSEIDON = 0x0102,
     POSEIDON_HASH = 0x0103,
     ECRECOVER = 0x0104,
+    HASH_INIT = 0x0105,
+    HASH_UPDATE = 0x0106,
+    HASH_FINAL = 0x0107,
 
     // SYS host
     EXIT = 0x0001,
@@ -192,20 +229,42 @@ pub enum SysFuncIdx {
     CHARGE_FUEL = 0x000b,
     READ_CONTEXT = 0x000d,
     CONTEXT_CALL = 0x000e,
+    FUEL_REMAINING = 0x000f,
+    FUEL_CONSUMED = 0x0010,
+    STATIC_EXEC = 0x0011,
+    BLOB_HASH = 0x0012,
+    BLOB_BASE_FEE = 0x0013,
+    GAS_PRICE = 0x0014,
+    BASE_FEE = 0x0015,
+    EXEC_ADDRESS = 0x0016,
+    SUSPEND = 0x0017,
+    RETURN_DATA_SIZE = 0x0018,
+    RETURN_DATA_COPY = 0x0019,
+    INPUT_COPY = 0x001a,
+    WRITE_OUTPUT_CHUNK = 0x001b,
+    INPUT_REVEAL = 0x001c,
 
     // jzkt
     CHECKPOINT = 0x0702,
     GET_LEAF = 0x0703,
     UPDATE_LEAF = 0x0704,
     UPDATE_PREIMAGE = 0x0705,
+    REMOVE_LEAF = 0x0706,
     COMPUTE_ROOT = 0x0707,
     EMIT_LOG = 0x0708,
     COMMIT = 0x0709,
     ROLLBACK = 0x070A,
+    STORAGE_READ_EXTERNAL = 0x070B,
     PREIMAGE_SIZE = 0x070D,
     PREIMAGE_COPY = 0x070E,
 
     DEBUG_LOG = 0x0901,
+    PROFILE_ENTER = 0x0902,
+    PROFILE_EXIT = 0x0903,
+    COVERAGE_HIT = 0x0904,
+    ASSERT_EQ_MEM = 0x0905,
+    EXPECT_STORAGE = 0x0906,
+    EXPECT_FUEL_LE = 0x0907,
 }
 
 impl SysFuncIdx {
@@ -222,6 +281,7 @@ impl SysFuncIdx {
             SysFuncIdx::ECRECOVER => 1,
             SysFuncIdx::UPDATE_LEAF => 1,
             SysFuncIdx::GET_LEAF => 1,
+            SysFuncIdx::REMOVE_LEAF => 1,
             SysFuncIdx::COMPUTE_ROOT => 1,
             SysFuncIdx::ROLLBACK => 1,
             SysFuncIdx::COMMIT => 1,
@@ -243,6 +303,7 @@ impl Into<FuncIdx> for SysFuncIdx {
     }
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[allow(non_camel_case_types)]
 pub enum BytecodeType {
     EVM,
@@ -255,7 +316,7 @@ const WASM_SIG: [u8; 4] = [0x00, 0x61, 0x73, 0x6d];
 /// rWASM binary format signature:
 /// - 0xef 0x00 - EIP-3540 compatible prefix
 /// - 0x52 - rWASM version number (equal to 'R')
-const RWASM_SIG: [u8; 3] = [0xef, 0x00, 0x52];
+pub(crate) const RWASM_SIG: [u8; 3] = [0xef, 0x00, 0x52];
 
 impl BytecodeType {
     pub fn from_slice(input: &[u8]) -> Self {
@@ -267,6 +328,10 @@ impl BytecodeType {
         if input.len() >= RWASM_SIG.len() && input[0..3] == RWASM_SIG {
             return Self::WASM;
         }
+        // rWASM contracts with the metadata section in front of the code
+        if input.starts_with(&crate::METADATA_MAGIC) {
+            return Self::WASM;
+        }
         // all the rest are EVM bytecode
         Self::EVM
     }
@@ -1,5 +1,7 @@
 use crate::{fr_from_little_endian, fr_to_little_endian, reverse_byte_order, Byte32, Fr};
 use fluentbase_poseidon::hash_with_domain;
+#[cfg(not(feature = "asm-keccak"))]
+use keccak_hash::keccak;
 use std::{prelude::v1::*, sync::Arc};
 
 pub const HASH_DOMAIN_ELEMS_BASE: usize = 256;
@@ -31,6 +33,50 @@ impl HashScheme for PoseidonHash {
         hash_with_domain(arr, domain)
     }
 }
+/// Keccak256 of the domain and elements (little-endian), the result is truncated to 253 bits to
+/// fit into the field, it's cheaper to compute natively but expensive to prove in circuits
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeccakHash;
+impl HashScheme for KeccakHash {
+    fn hash_scheme(arr: &[Fr], domain: &Fr) -> Fr {
+        let mut input = Vec::with_capacity((arr.len() + 1) * HASH_BYTE_LEN);
+        input.extend_from_slice(&domain.to_bytes());
+        arr.iter()
+            .for_each(|v| input.extend_from_slice(&v.to_bytes()));
+        let mut output = keccak256(&input);
+        output[HASH_BYTE_LEN - 1] &= 0x1f;
+        Fr::from_bytes(&output).unwrap()
+    }
+}
+
+/// Blake3 of the domain and elements (little-endian), truncated to 253 bits the same way as
+/// [`KeccakHash`], it's the fastest scheme for native execution
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Blake3Hash;
+impl HashScheme for Blake3Hash {
+    fn hash_scheme(arr: &[Fr], domain: &Fr) -> Fr {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&domain.to_bytes());
+        arr.iter().for_each(|v| {
+            hasher.update(&v.to_bytes());
+        });
+        let mut output: [u8; 32] = hasher.finalize().into();
+        output[HASH_BYTE_LEN - 1] &= 0x1f;
+        Fr::from_bytes(&output).unwrap()
+    }
+}
+
+#[cfg(feature = "asm-keccak")]
+fn keccak256(input: &[u8]) -> [u8; 32] {
+    use keccak_asm::{Digest, Keccak256};
+    Keccak256::digest(input).into()
+}
+
+#[cfg(not(feature = "asm-keccak"))]
+fn keccak256(input: &[u8]) -> [u8; 32] {
+    keccak(input).0
+}
+
 pub trait HashScheme: PartialEq + Clone + std::fmt::Debug {
     fn hash_scheme(arr: &[Fr], domain: &Fr) -> Fr;
 }
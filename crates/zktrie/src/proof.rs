@@ -1,4 +1,4 @@
-use crate::{Error, HashScheme, Node};
+use crate::{test_bit, to_secure_key, Error, Hash, HashScheme, Node, NodeValue};
 use std::prelude::v1::*;
 
 lazy_static::lazy_static! {
@@ -12,3 +12,37 @@ pub fn decode_smt_proofs<H: HashScheme>(buf: &[u8]) -> Result<Option<Node<H>>, E
     }
     Ok(Some(<Node<H>>::from_bytes(buf)?))
 }
+
+// VerifyAbsenceProof checks that the proof is a path from the root along the key bits that
+// ends with the empty node or with the leaf of another key, so the key is absent in the trie
+pub fn verify_absence_proof<H: HashScheme>(
+    root: &Hash,
+    key: &[u8],
+    proof: &[Vec<u8>],
+) -> Result<bool, Error> {
+    let node_key: Hash = to_secure_key::<H>(key)?.into();
+    let mut next_hash = *root;
+    let mut level = 0;
+    for buf in proof.iter() {
+        let node = match decode_smt_proofs::<H>(buf)? {
+            Some(node) => node,
+            None => break,
+        };
+        if node.hash() != &next_hash {
+            return Ok(false);
+        }
+        match node.value() {
+            NodeValue::Empty => return Ok(true),
+            NodeValue::Leaf(_) => return Ok(!node.match_leaf_key(&node_key)),
+            NodeValue::Branch(branch) => {
+                next_hash = if test_bit(node_key.raw_bytes(), level) {
+                    *branch.right.hash()
+                } else {
+                    *branch.left.hash()
+                };
+                level += 1;
+            }
+        }
+    }
+    Ok(false)
+}
@@ -138,3 +138,31 @@ pub enum Error {
     NotInField(String),
     ExpectedLeafNode,
 }
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ReachedMaxLevel => write!(f, "reached max trie level"),
+            Error::EntryIndexAlreadyExists => write!(f, "entry index already exists"),
+            Error::NodeKeyAlreadyExists => write!(f, "node key already exists"),
+            Error::NodeNotFound((level, hash)) => {
+                write!(
+                    f,
+                    "node not found (level={}, hash={})",
+                    level,
+                    hex::encode(hash.raw_bytes())
+                )
+            }
+            Error::KeyNotFound => write!(f, "key not found"),
+            Error::InvalidField => write!(f, "invalid field"),
+            Error::NodeBytesBadSize => write!(f, "node bytes have bad size"),
+            Error::InvalidNodeFound(node_type) => {
+                write!(f, "invalid node found (type={})", node_type)
+            }
+            Error::NotInField(value) => write!(f, "value is not in the field ({})", value),
+            Error::ExpectedLeafNode => write!(f, "expected leaf node"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -2,7 +2,16 @@ use fluentbase_codec::Encoder;
 use fluentbase_core::{Account, JZKT_ACCOUNT_COMPRESSION_FLAGS};
 use fluentbase_runtime::{DefaultEmptyRuntimeDatabase, ExecutionResult, Runtime, RuntimeContext};
 use fluentbase_sdk::{ContractInput, LowLevelSDK};
-use fluentbase_types::{Address, Bytes, IJournaledTrie, STATE_DEPLOY, STATE_MAIN, U256};
+use fluentbase_types::{
+    Address,
+    Bytes,
+    FuelSchedule,
+    Gas,
+    IJournaledTrie,
+    STATE_DEPLOY,
+    STATE_MAIN,
+    U256,
+};
 use hashbrown::HashMap;
 use paste::paste;
 
@@ -43,7 +52,7 @@ impl<const IS_RUNTIME: bool> TestingContext<IS_RUNTIME> {
     ) -> ExecutionResult {
         let runtime_ctx = runtime_ctx
             .with_state(if is_deploy { STATE_DEPLOY } else { STATE_MAIN })
-            .with_fuel_limit(gas_limit);
+            .with_fuel_limit(FuelSchedule::DEFAULT.gas_to_fuel(Gas(gas_limit)));
         let mut runtime = Runtime::<DefaultEmptyRuntimeDatabase>::new(runtime_ctx);
         runtime.data_mut().clean_output();
         runtime.call().unwrap()
@@ -136,6 +145,7 @@ impl ContractInputWrapper {
     impl_once_setter!(block_difficulty, u64);
     impl_once_setter!(block_gas_limit, u64);
     impl_once_setter!(block_base_fee, U256);
+    impl_once_setter!(block_blob_base_fee, U256);
     impl_once_setter!(tx_gas_price, U256);
     impl_once_setter!(tx_gas_priority_fee, Option<U256>);
     impl_once_setter!(tx_caller, Address);
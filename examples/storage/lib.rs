@@ -18,7 +18,7 @@ use fluentbase_sdk::{
 
 solidity_storage! {
     U256[] Arr<EvmAPI>;
-    mapping(Address => U256) Balance<EvmAPI>;
+    mapping(Address => U256) Balance<EvmAPI> as BALANCES;
 }
 
 #[cfg(test)]
@@ -95,6 +95,23 @@ mod test {
         assert_eq!(output, owner_balance);
     }
 
+    #[serial]
+    #[test]
+    pub fn test_global_accessor() {
+        let owner_address = Address::from(hex!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266"));
+        LowLevelSDK::init_with_devnet_genesis();
+        with_test_input(vec![], Some(owner_address));
+        let owner_balance = U256::from(1000);
+
+        BALANCES.set(owner_address, owner_balance);
+
+        assert_eq!(BALANCES.get(owner_address), owner_balance);
+        assert_eq!(
+            BALANCES.key(owner_address),
+            Balance::new(&EvmClient::new(PRECOMPILE_EVM)).key(owner_address)
+        );
+    }
+
     #[serial]
     #[test]
     pub fn test_storage() {